@@ -0,0 +1,156 @@
+//! Animated image decoding (APNG)
+//! Frames come out fully composited (dispose and blend ops applied) as RGBA,
+//! ready to be shown one after another.
+
+use crate::AppError;
+use image::codecs::png::PngDecoder;
+use image::{AnimationDecoder, RgbaImage};
+use std::io::Cursor;
+use std::time::Duration;
+
+/// Delay used for frames that ask for 10ms or less (same as browsers)
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// Decoded frames stop once they take this much memory
+const MAX_ANIMATION_BYTES: usize = 512 * 1024 * 1024;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// One composited animation frame
+#[derive(Debug, Clone)]
+pub struct AnimationFrame {
+    pub image: RgbaImage,
+    /// How long the frame stays on screen
+    pub delay: Duration,
+}
+
+/// Whether PNG data has an `acTL` chunk (before its image data)
+/// Only chunk headers are read, so static PNGs are rejected cheaply.
+pub fn is_apng(data: &[u8]) -> bool {
+    if !data.starts_with(PNG_SIGNATURE) {
+        return false;
+    }
+    let mut pos = PNG_SIGNATURE.len();
+    while let Some(header) = data.get(pos..pos + 8) {
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        match &header[4..8] {
+            b"acTL" => return true,
+            b"IDAT" | b"IEND" => return false,
+            _ => {}
+        }
+        // length + type + data + CRC
+        pos = match pos.checked_add(12 + len) {
+            Some(next) => next,
+            None => return false,
+        };
+    }
+    false
+}
+
+/// Decode every frame of an APNG
+/// Returns fewer frames if the animation would exceed the memory budget.
+pub fn decode_apng(data: &[u8]) -> Result<Vec<AnimationFrame>, AppError> {
+    let decoder = PngDecoder::new(Cursor::new(data))
+        .and_then(|decoder| decoder.apng())
+        .map_err(|e| AppError::ImageDecode(e.to_string()))?;
+
+    let mut frames = Vec::new();
+    let mut bytes = 0;
+    for frame in decoder.into_frames() {
+        let frame = frame.map_err(|e| AppError::ImageDecode(e.to_string()))?;
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        let delay = Duration::from_micros(numer as u64 * 1000 / denom.max(1) as u64);
+        let image = frame.into_buffer();
+
+        bytes += image.as_raw().len();
+        if bytes > MAX_ANIMATION_BYTES && !frames.is_empty() {
+            tracing::warn!("Animation truncated at {} frames (memory limit)", frames.len());
+            break;
+        }
+        frames.push(AnimationFrame {
+            image,
+            delay: if delay <= Duration::from_millis(10) { DEFAULT_FRAME_DELAY } else { delay },
+        });
+    }
+
+    if frames.is_empty() {
+        return Err(AppError::ImageDecode("APNG has no frames".to_string()));
+    }
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const GREEN: [u8; 4] = [0, 255, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+    const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+    /// 2x2 APNG: a red frame, then 1x1 patches exercising each dispose op
+    fn sample_apng() -> Vec<u8> {
+        use png::{BlendOp, DisposeOp};
+
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, 2, 2);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(4, 0).unwrap();
+        let mut writer = encoder.write_header().unwrap();
+
+        writer.set_frame_delay(0, 100).unwrap();
+        writer.set_dispose_op(DisposeOp::None).unwrap();
+        writer.write_image_data(&RED.repeat(4)).unwrap();
+
+        let patches = [
+            ((1, 1), GREEN, DisposeOp::Previous, BlendOp::Source),
+            ((0, 0), BLUE, DisposeOp::Background, BlendOp::Over),
+            ((1, 0), WHITE, DisposeOp::None, BlendOp::Over),
+        ];
+        for ((x, y), color, dispose, blend) in patches {
+            writer.set_frame_dimension(1, 1).unwrap();
+            writer.set_frame_position(x, y).unwrap();
+            writer.set_frame_delay(50, 1000).unwrap();
+            writer.set_dispose_op(dispose).unwrap();
+            writer.set_blend_op(blend).unwrap();
+            writer.write_image_data(&color).unwrap();
+        }
+        writer.finish().unwrap();
+        data
+    }
+
+    fn pixels(frame: &AnimationFrame) -> Vec<[u8; 4]> {
+        frame.image.pixels().map(|p| p.0).collect()
+    }
+
+    #[test]
+    fn test_decode_apng_composites_frames() {
+        let data = sample_apng();
+        assert!(is_apng(&data));
+
+        let frames = decode_apng(&data).unwrap();
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0].delay, DEFAULT_FRAME_DELAY);
+        assert_eq!(frames[1].delay, Duration::from_millis(50));
+
+        // Pixels in row order: (0,0) (1,0) (0,1) (1,1)
+        assert_eq!(pixels(&frames[0]), [RED, RED, RED, RED]);
+        assert_eq!(pixels(&frames[1]), [RED, RED, RED, GREEN]);
+        // dispose=previous restored (1,1) before blue was drawn
+        assert_eq!(pixels(&frames[2]), [BLUE, RED, RED, RED]);
+        // dispose=background cleared (0,0) before white was drawn
+        assert_eq!(pixels(&frames[3]), [CLEAR, WHITE, RED, RED]);
+    }
+
+    #[test]
+    fn test_static_png_is_not_apng() {
+        let mut data = Vec::new();
+        image::RgbaImage::from_pixel(2, 2, image::Rgba(RED))
+            .write_to(&mut Cursor::new(&mut data), image::ImageFormat::Png)
+            .unwrap();
+        assert!(!is_apng(&data));
+        assert!(!is_apng(b"GIF89a"));
+    }
+}
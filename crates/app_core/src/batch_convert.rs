@@ -0,0 +1,131 @@
+//! Batch image conversion (file.batch_convert)
+//! Each file is decoded upright, downscaled to fit the maximum dimension,
+//! re-encoded and written to the destination folder under a name built from
+//! a batch rename pattern (`{name}`, `{n}`, `{date}`).
+
+use crate::batch_rename::{expand_pattern, uses_date, DateSource, PatternError, RenameSource};
+use crate::config::{ConflictPolicy, Interpolation};
+use crate::exif::apply_orientation;
+use crate::image_loader::{encode_image, read_exif_block, read_exif_info, resize_image, SaveFormat};
+use crate::AppError;
+use std::path::{Path, PathBuf};
+
+/// Settings shared by every file of a batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvertOptions {
+    pub dest: PathBuf,
+    pub format: SaveFormat,
+    pub quality: u8,
+    /// Longest edge of the output; None keeps the original size
+    pub max_dimension: Option<u32>,
+    /// Output name pattern; the extension follows `format`
+    pub pattern: String,
+    pub keep_exif: bool,
+    pub conflict: ConflictPolicy,
+}
+
+/// What happened to one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConvertOutcome {
+    Written(PathBuf),
+    /// Target existed and the conflict policy is Skip
+    Skipped(PathBuf),
+}
+
+/// Output file name for the `index`-th source (1-based)
+pub fn output_name(pattern: &str, source: &RenameSource, index: usize, format: SaveFormat) -> Result<String, PatternError> {
+    let name = expand_pattern(pattern, source, index)?;
+    // expand_pattern keeps the source extension; swap it for the target's
+    let stem = match source.path.extension() {
+        Some(ext) => name.strip_suffix(&format!(".{}", ext.to_string_lossy())).unwrap_or(&name),
+        None => name.as_str(),
+    };
+    Ok(format!("{}.{}", stem, format.extension()))
+}
+
+/// Convert one file into `options.dest`
+pub fn convert_file(path: &Path, index: usize, options: &ConvertOptions) -> Result<ConvertOutcome, AppError> {
+    let source = if uses_date(&options.pattern) {
+        RenameSource::load(path)
+    } else {
+        RenameSource { path: path.to_path_buf(), date: None, date_source: DateSource::Missing }
+    };
+    let name = output_name(&options.pattern, &source, index, options.format)
+        .map_err(|e| AppError::Config(format!("Name pattern: {}", e)))?;
+    let target = options.dest.join(name);
+    let target = match options.conflict {
+        _ if !target.exists() => target,
+        ConflictPolicy::Overwrite => target,
+        ConflictPolicy::Rename => app_fs::unique_path(&target),
+        ConflictPolicy::Skip => return Ok(ConvertOutcome::Skipped(target)),
+    };
+
+    let img = image::open(path).map_err(|e| AppError::ImageDecode(e.to_string()))?;
+    let orientation = read_exif_info(path).map_or(1, |info| info.orientation);
+    let mut rgba = apply_orientation(img, orientation).to_rgba8();
+
+    if let Some(max) = options.max_dimension {
+        let (width, height) = rgba.dimensions();
+        let longest = width.max(height);
+        if longest > max {
+            let scale = max as f64 / longest as f64;
+            let size = |v: u32| ((v as f64 * scale).round() as u32).max(1);
+            rgba = resize_image(&rgba, size(width), size(height), Interpolation::Lanczos3);
+        }
+    }
+
+    let exif = (options.keep_exif && options.format.supports_exif())
+        .then(|| read_exif_block(path))
+        .flatten();
+    let data = encode_image(&rgba, options.format, options.quality, exif)?;
+    std::fs::write(&target, data)?;
+    Ok(ConvertOutcome::Written(target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(dest: &Path) -> ConvertOptions {
+        ConvertOptions {
+            dest: dest.to_path_buf(),
+            format: SaveFormat::Jpeg,
+            quality: 80,
+            max_dimension: Some(50),
+            pattern: "{name}_web".to_string(),
+            keep_exif: true,
+            conflict: ConflictPolicy::Rename,
+        }
+    }
+
+    #[test]
+    fn test_convert_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("large.png");
+        image::RgbaImage::from_pixel(200, 100, image::Rgba([10, 200, 30, 255])).save(&source).unwrap();
+        let out = dir.path().join("out");
+        std::fs::create_dir(&out).unwrap();
+
+        let written = convert_file(&source, 1, &options(&out)).unwrap();
+        assert_eq!(written, ConvertOutcome::Written(out.join("large_web.jpg")));
+        let converted = image::open(out.join("large_web.jpg")).unwrap();
+        assert_eq!((converted.width(), converted.height()), (50, 25));
+
+        // Existing target: numbered name, or nothing with Skip
+        let again = convert_file(&source, 1, &options(&out)).unwrap();
+        assert_eq!(again, ConvertOutcome::Written(out.join("large_web (2).jpg")));
+        let skip = ConvertOptions { conflict: ConflictPolicy::Skip, ..options(&out) };
+        assert_eq!(convert_file(&source, 1, &skip).unwrap(), ConvertOutcome::Skipped(out.join("large_web.jpg")));
+
+        assert!(convert_file(&dir.path().join("missing.png"), 1, &options(&out)).is_err());
+    }
+
+    #[test]
+    fn test_output_name() {
+        let source = RenameSource { path: PathBuf::from("/p/IMG.1.JPG"), date: None, date_source: DateSource::Missing };
+        assert_eq!(output_name("{name}", &source, 1, SaveFormat::WebP).unwrap(), "IMG.1.webp");
+        assert_eq!(output_name("web_{n:3}", &source, 4, SaveFormat::Png).unwrap(), "web_004.png");
+        let bare = RenameSource { path: PathBuf::from("/p/scan"), ..source };
+        assert_eq!(output_name("{name}", &bare, 1, SaveFormat::Jpeg).unwrap(), "scan.jpg");
+    }
+}
@@ -0,0 +1,163 @@
+//! Batch rename patterns
+//!
+//! Tokens:
+//! - `{name}`: original file stem
+//! - `{n}` / `{n:3}`: sequence number, optionally zero-padded
+//! - `{date}` / `{date:%Y%m%d_%H%M%S}`: EXIF capture time, falling back to the modified time
+//!
+//! The original extension is always kept.
+
+use crate::image_loader::read_exif_info;
+use chrono::{DateTime, Local, NaiveDateTime};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Format used by a bare `{date}` token
+pub const DEFAULT_DATE_FORMAT: &str = "%Y%m%d_%H%M%S";
+
+/// Where the date of a file came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateSource {
+    /// EXIF DateTimeOriginal
+    Exif,
+    /// File system modified time (no usable EXIF date)
+    Modified,
+    /// Neither was available
+    Missing,
+}
+
+/// A file to rename, with its date resolved up front
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenameSource {
+    pub path: PathBuf,
+    pub date: Option<NaiveDateTime>,
+    pub date_source: DateSource,
+}
+
+impl RenameSource {
+    /// Resolve the date of `path` from EXIF, else from its modified time
+    pub fn load(path: &Path) -> Self {
+        if let Some(date) = read_exif_info(path).and_then(|info| info.date_taken) {
+            return Self { path: path.to_path_buf(), date: Some(date), date_source: DateSource::Exif };
+        }
+        let modified = std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .map(|time| DateTime::<Local>::from(time).naive_local());
+        Self {
+            path: path.to_path_buf(),
+            date: modified,
+            date_source: if modified.is_some() { DateSource::Modified } else { DateSource::Missing },
+        }
+    }
+
+    pub fn file_name(&self) -> String {
+        self.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+    }
+}
+
+/// Why a pattern could not be expanded
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PatternError {
+    #[error("Unclosed '{{' in pattern")]
+    Unclosed,
+    #[error("Unknown token: {{{0}}}")]
+    UnknownToken(String),
+    #[error("Invalid date format: {0}")]
+    InvalidDateFormat(String),
+    #[error("No date available")]
+    NoDate,
+    #[error("Pattern produces an empty name")]
+    Empty,
+}
+
+/// Whether the pattern contains a `{date}` token
+pub fn uses_date(pattern: &str) -> bool {
+    pattern.contains("{date}") || pattern.contains("{date:")
+}
+
+/// New file name for `source`, the `index`-th file of the batch
+pub fn expand_pattern(pattern: &str, source: &RenameSource, index: usize) -> Result<String, PatternError> {
+    let stem = source.path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let mut name = String::new();
+    let mut rest = pattern;
+
+    while let Some(open) = rest.find('{') {
+        name.push_str(&rest[..open]);
+        let close = rest[open..].find('}').ok_or(PatternError::Unclosed)? + open;
+        let token = &rest[open + 1..close];
+        let (key, arg) = match token.split_once(':') {
+            Some((key, arg)) => (key, Some(arg)),
+            None => (token, None),
+        };
+
+        match key {
+            "name" => name.push_str(&stem),
+            "n" => {
+                let width = match arg {
+                    Some(arg) => arg.parse::<usize>().map_err(|_| PatternError::UnknownToken(token.to_string()))?,
+                    None => 0,
+                };
+                let _ = write!(name, "{:0width$}", index, width = width);
+            }
+            "date" => {
+                let date = source.date.ok_or(PatternError::NoDate)?;
+                let format = arg.unwrap_or(DEFAULT_DATE_FORMAT);
+                // Unknown specifiers surface as a fmt error instead of panicking
+                write!(name, "{}", date.format(format))
+                    .map_err(|_| PatternError::InvalidDateFormat(format.to_string()))?;
+            }
+            _ => return Err(PatternError::UnknownToken(token.to_string())),
+        }
+        rest = &rest[close + 1..];
+    }
+    name.push_str(rest);
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(PatternError::Empty);
+    }
+    Ok(match source.path.extension() {
+        Some(ext) => format!("{}.{}", name, ext.to_string_lossy()),
+        None => name,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(path: &str, date: Option<&str>, date_source: DateSource) -> RenameSource {
+        RenameSource {
+            path: PathBuf::from(path),
+            date: date.map(|d| NaiveDateTime::parse_from_str(d, "%Y-%m-%d %H:%M:%S").unwrap()),
+            date_source,
+        }
+    }
+
+    #[test]
+    fn test_expand_pattern() {
+        let photo = source("/photos/IMG_0001.JPG", Some("2023-07-14 18:05:09"), DateSource::Exif);
+        assert_eq!(expand_pattern("{date}", &photo, 1).unwrap(), "20230714_180509.JPG");
+        assert_eq!(expand_pattern("{date:%Y-%m}_{n:3}", &photo, 7).unwrap(), "2023-07_007.JPG");
+        assert_eq!(expand_pattern("trip {name} {n}", &photo, 12).unwrap(), "trip IMG_0001 12.JPG");
+        assert!(uses_date("{date:%Y}") && !uses_date("{name}"));
+
+        assert_eq!(expand_pattern("{size}", &photo, 1), Err(PatternError::UnknownToken("size".to_string())));
+        assert_eq!(expand_pattern("{date", &photo, 1), Err(PatternError::Unclosed));
+        assert!(matches!(expand_pattern("{date:%Q}", &photo, 1), Err(PatternError::InvalidDateFormat(_))));
+        assert_eq!(expand_pattern("{name}", &source("/photos/ .jpg", None, DateSource::Missing), 1), Err(PatternError::Empty));
+        assert_eq!(expand_pattern("{date}", &source("/a.jpg", None, DateSource::Missing), 1), Err(PatternError::NoDate));
+    }
+
+    #[test]
+    fn test_load_falls_back_to_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plain.jpg");
+        std::fs::write(&path, b"not a jpeg").unwrap();
+
+        let loaded = RenameSource::load(&path);
+        assert_eq!(loaded.date_source, DateSource::Modified);
+        assert!(loaded.date.is_some());
+    }
+}
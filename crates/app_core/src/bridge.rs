@@ -0,0 +1,994 @@
+//! Susie bridge supervision (main process side)
+//!
+//! The 32-bit bridge can die while a plugin decodes a malformed file.
+//! `BridgeSupervisor` relaunches it, replays the plugins that were loaded
+//! and retries the failed command once. Every command has a deadline so a
+//! hung plugin can't freeze the caller, and a heartbeat thread restarts a
+//! bridge that stops answering Pings.
+
+use crate::AppError;
+use ipc_proto::{is_compatible, ArchiveEntry, BridgeCommand, BridgeResponse, ErrorCode, PROTO_VERSION};
+use std::collections::HashMap;
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Consecutive failed restarts before the bridge is given up on
+pub const MAX_BRIDGE_RESTARTS: u32 = 3;
+
+/// A live connection to a bridge process.
+/// Dropping it must terminate the bridge process.
+pub trait BridgeConnection {
+    /// Send a command.
+    /// An `Err` means the connection is broken (pipe closed, child exited).
+    fn send(&mut self, command: &BridgeCommand) -> Result<(), AppError>;
+
+    /// Wait up to `timeout` for the next response (`None` = nothing arrived in time)
+    fn recv(&mut self, timeout: Duration) -> Result<Option<BridgeResponse>, AppError>;
+}
+
+/// Bridge child process talking over its stdin/stdout
+/// (framed with `ipc_proto::write_message`); killed when dropped
+pub struct ProcessConnection {
+    child: Child,
+    stdin: ChildStdin,
+    /// Filled by a reader thread; disconnects when the bridge exits
+    responses: crossbeam_channel::Receiver<BridgeResponse>,
+}
+
+impl ProcessConnection {
+    /// Start the bridge executable at `exe`
+    pub fn spawn(exe: &Path) -> Result<Self, AppError> {
+        let mut child = Command::new(exe)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| AppError::Bridge(format!("Cannot start {}: {}", exe.display(), e)))?;
+        let (Some(stdin), Some(mut stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            return Err(AppError::Bridge("Bridge pipes are missing".into()));
+        };
+
+        let (tx, responses) = crossbeam_channel::unbounded();
+        std::thread::Builder::new()
+            .name("bridge-reader".into())
+            .spawn(move || {
+                while let Ok(response) = ipc_proto::read_message(&mut stdout) {
+                    if tx.send(response).is_err() {
+                        break;
+                    }
+                }
+            })?;
+        Ok(Self { child, stdin, responses })
+    }
+
+    /// Launcher for `BridgeSupervisor::new` that starts `exe` each time
+    pub fn launcher(exe: PathBuf) -> BridgeLauncher {
+        Box::new(move || Self::spawn(&exe))
+    }
+}
+
+/// Boxed launch function, so supervisors over processes have a nameable type
+pub type BridgeLauncher = Box<dyn FnMut() -> Result<ProcessConnection, AppError> + Send>;
+
+impl BridgeConnection for ProcessConnection {
+    fn send(&mut self, command: &BridgeCommand) -> Result<(), AppError> {
+        ipc_proto::write_message(&mut self.stdin, command)
+            .map_err(|e| AppError::Bridge(format!("Bridge pipe closed: {}", e)))
+    }
+
+    fn recv(&mut self, timeout: Duration) -> Result<Option<BridgeResponse>, AppError> {
+        match self.responses.recv_timeout(timeout) {
+            Ok(response) => Ok(Some(response)),
+            Err(crossbeam_channel::RecvTimeoutError::Timeout) => Ok(None),
+            Err(crossbeam_channel::RecvTimeoutError::Disconnected) => {
+                Err(AppError::Bridge("Bridge process exited".into()))
+            }
+        }
+    }
+}
+
+impl Drop for ProcessConnection {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Consecutive unanswered heartbeats before the bridge is restarted
+pub const MAX_MISSED_HEARTBEATS: u32 = 3;
+
+/// Bridge state as seen by the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BridgeHealth {
+    Healthy,
+    /// Missed heartbeats; a restart follows if it doesn't recover
+    Unresponsive,
+    /// Crashed repeatedly or could not be launched ("plugin bridge unavailable")
+    Unavailable,
+}
+
+/// Cheap, thread-safe view of a supervisor's health
+#[derive(Debug, Clone)]
+pub struct BridgeHealthHandle(Arc<AtomicU8>);
+
+impl BridgeHealthHandle {
+    fn new() -> Self {
+        Self(Arc::new(AtomicU8::new(BridgeHealth::Healthy as u8)))
+    }
+
+    pub fn get(&self) -> BridgeHealth {
+        match self.0.load(Ordering::Relaxed) {
+            0 => BridgeHealth::Healthy,
+            1 => BridgeHealth::Unresponsive,
+            _ => BridgeHealth::Unavailable,
+        }
+    }
+
+    fn set(&self, health: BridgeHealth) {
+        self.0.store(health as u8, Ordering::Relaxed);
+    }
+}
+
+/// Opens shared memory blocks created by the bridge
+pub trait SharedMemoryReader {
+    /// Copy the first `size` bytes out of the block named `handle`
+    /// (mapped with `ipc_proto::aligned_size(size)` bytes)
+    fn read(&self, handle: &str, size: usize) -> Result<Vec<u8>, AppError>;
+}
+
+/// Named file mappings, as the bridge creates them
+pub struct NamedSharedMemory;
+
+impl SharedMemoryReader for NamedSharedMemory {
+    #[cfg(windows)]
+    fn read(&self, handle: &str, size: usize) -> Result<Vec<u8>, AppError> {
+        use windows::core::HSTRING;
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::System::Memory::{MapViewOfFile, OpenFileMappingW, UnmapViewOfFile, FILE_MAP_READ};
+
+        unsafe {
+            let mapping = OpenFileMappingW(FILE_MAP_READ.0, false, &HSTRING::from(handle))
+                .map_err(|e| AppError::Bridge(format!("Cannot open shared memory {}: {}", handle, e)))?;
+            let view = MapViewOfFile(mapping, FILE_MAP_READ, 0, 0, ipc_proto::aligned_size(size));
+            let data = (!view.Value.is_null())
+                .then(|| std::slice::from_raw_parts(view.Value as *const u8, size).to_vec());
+            if data.is_some() {
+                let _ = UnmapViewOfFile(view);
+            }
+            let _ = CloseHandle(mapping);
+            data.ok_or_else(|| AppError::Bridge(format!("Cannot map shared memory {}", handle)))
+        }
+    }
+
+    #[cfg(not(windows))]
+    fn read(&self, handle: &str, _size: usize) -> Result<Vec<u8>, AppError> {
+        Err(AppError::Bridge(format!("Shared memory {} requires Windows", handle)))
+    }
+}
+
+/// Per-command deadlines
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BridgeTimeouts {
+    /// Plugin loading and other quick commands
+    pub command: Duration,
+    /// GetPicture, GetArchiveList and ExtractFile
+    pub decode: Duration,
+    /// Ping liveness check
+    pub ping: Duration,
+    /// Cap on a whole command, however often Progress restarts its deadline
+    pub total: Duration,
+}
+
+impl Default for BridgeTimeouts {
+    fn default() -> Self {
+        Self {
+            command: Duration::from_secs(5),
+            decode: Duration::from_secs(30),
+            ping: Duration::from_secs(2),
+            total: Duration::from_secs(120),
+        }
+    }
+}
+
+impl BridgeTimeouts {
+    pub fn for_command(&self, command: &BridgeCommand) -> Duration {
+        match command {
+            BridgeCommand::GetPicture { .. }
+            | BridgeCommand::GetArchiveList { .. }
+            | BridgeCommand::ExtractFile { .. } => self.decode,
+            BridgeCommand::Ping => self.ping,
+            _ => self.command,
+        }
+    }
+}
+
+/// Susie plugin file extensions (.spi, and .sph for 64-bit builds)
+const PLUGIN_EXTENSIONS: &[&str] = &["spi", "sph"];
+
+/// Plugin files in `dir`, sorted by name so the load order is stable
+pub fn plugin_files(dir: &Path) -> std::io::Result<Vec<std::path::PathBuf>> {
+    let mut files: Vec<_> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| PLUGIN_EXTENSIONS.iter().any(|p| p.eq_ignore_ascii_case(e)))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Split Susie extension filters ("*.pi;*.pic") into bare lowercase extensions
+pub fn normalize_extensions(filters: &[String]) -> Vec<String> {
+    filters
+        .iter()
+        .flat_map(|f| f.split(';'))
+        .map(|f| f.trim().trim_start_matches('*').trim_start_matches('.').to_ascii_lowercase())
+        .filter(|e| !e.is_empty() && e != "*")
+        .collect()
+}
+
+/// Send `command` and wait for its response
+fn exchange<C: BridgeConnection>(
+    connection: &mut C,
+    command: &BridgeCommand,
+    timeout: Duration,
+) -> Result<Option<BridgeResponse>, AppError> {
+    connection.send(command)?;
+    connection.recv(timeout)
+}
+
+/// Plugin loaded through the supervisor
+#[derive(Debug, Clone)]
+struct LoadedPlugin {
+    path: String,
+    /// Id in the currently running bridge
+    current_id: u32,
+}
+
+/// Restarts a crashed bridge and replays its plugins
+///
+/// Callers keep using the plugin ids from the first `PluginLoaded` response;
+/// they are translated to the ids of the current bridge process.
+pub struct BridgeSupervisor<C, F> {
+    launch: F,
+    connection: Option<C>,
+    /// Keyed by the id handed out to callers
+    plugins: HashMap<u32, LoadedPlugin>,
+    /// Lowercase extension -> plugin that decodes it (first loaded wins)
+    extensions: HashMap<String, u32>,
+    /// IsSupported answers by (plugin id, header hash)
+    supported: HashMap<(u32, u64), bool>,
+    /// Restarts since the last successful command
+    restarts: u32,
+    launched: bool,
+    timeouts: BridgeTimeouts,
+    missed_heartbeats: u32,
+    /// Set when the bridge binary speaks another protocol version (never retried)
+    incompatible: Option<String>,
+    /// Pings sent whose Pong hasn't been read yet (the stream is out of sync until it is)
+    outstanding_pongs: u32,
+    health: BridgeHealthHandle,
+}
+
+impl<C, F> BridgeSupervisor<C, F>
+where
+    C: BridgeConnection,
+    F: FnMut() -> Result<C, AppError>,
+{
+    /// Create a supervisor; `launch` starts a bridge process and connects to it
+    pub fn new(launch: F) -> Self {
+        Self {
+            launch,
+            connection: None,
+            plugins: HashMap::new(),
+            extensions: HashMap::new(),
+            supported: HashMap::new(),
+            restarts: 0,
+            launched: false,
+            timeouts: BridgeTimeouts::default(),
+            missed_heartbeats: 0,
+            incompatible: None,
+            outstanding_pongs: 0,
+            health: BridgeHealthHandle::new(),
+        }
+    }
+
+    /// Health indicator shared with the UI
+    pub fn health(&self) -> BridgeHealthHandle {
+        self.health.clone()
+    }
+
+    /// Ping the running bridge; after MAX_MISSED_HEARTBEATS misses in a row
+    /// it is killed and relaunched. Does nothing before the first launch.
+    pub fn heartbeat(&mut self) -> BridgeHealth {
+        if self.connection.is_none() {
+            return self.health.get();
+        }
+
+        match self.ping() {
+            Ok(true) => {
+                self.missed_heartbeats = 0;
+                self.health.set(BridgeHealth::Healthy);
+                return BridgeHealth::Healthy;
+            }
+            Ok(false) => self.missed_heartbeats += 1,
+            Err(e) => {
+                tracing::warn!("Susie bridge heartbeat failed: {}", e);
+                self.missed_heartbeats = MAX_MISSED_HEARTBEATS;
+            }
+        }
+        if self.missed_heartbeats < MAX_MISSED_HEARTBEATS {
+            self.health.set(BridgeHealth::Unresponsive);
+            return BridgeHealth::Unresponsive;
+        }
+
+        tracing::warn!("Susie bridge missed {} heartbeats, restarting", self.missed_heartbeats);
+        self.missed_heartbeats = 0;
+        self.drop_connection();
+        let health = match self.ensure_connected() {
+            Ok(()) => BridgeHealth::Healthy,
+            Err(e) => {
+                tracing::error!("Susie bridge restart failed: {}", e);
+                BridgeHealth::Unavailable
+            }
+        };
+        self.health.set(health);
+        health
+    }
+
+    /// Send a Ping and read every outstanding Pong, skipping stale responses
+    /// (e.g. the late answer to a timed-out command). Returns false if the
+    /// bridge didn't answer in time; the Pongs then stay outstanding.
+    fn ping(&mut self) -> Result<bool, AppError> {
+        let Some(connection) = self.connection.as_mut() else {
+            return Ok(false);
+        };
+        connection.send(&BridgeCommand::Ping)?;
+        self.outstanding_pongs += 1;
+
+        let deadline = Instant::now() + self.timeouts.ping;
+        while self.outstanding_pongs > 0 {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match connection.recv(remaining)? {
+                Some(BridgeResponse::Pong) => self.outstanding_pongs -= 1,
+                Some(stale) => tracing::debug!("Discarding late bridge response: {:?}", stale),
+                None => return Ok(false),
+            }
+        }
+        Ok(true)
+    }
+
+    /// Forget (and so kill) the current bridge; the next command relaunches it
+    fn drop_connection(&mut self) {
+        self.connection = None;
+        self.outstanding_pongs = 0;
+    }
+
+    pub fn with_timeouts(mut self, timeouts: BridgeTimeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Paths of the plugins that will be reloaded after a restart
+    pub fn plugin_paths(&self) -> Vec<&str> {
+        self.plugins.values().map(|p| p.path.as_str()).collect()
+    }
+
+    /// Send a command, restarting the bridge and retrying once if it crashed.
+    /// A command that misses its deadline yields `BridgeResponse::Error` with
+    /// `ErrorCode::Timeout` (callers may fall back to the built-in decoder).
+    pub fn request(&mut self, command: BridgeCommand) -> Result<BridgeResponse, AppError> {
+        self.request_with_progress(command, |_| {})
+    }
+
+    /// `request`, reporting `BridgeResponse::Progress` messages that arrive
+    /// before the final response (each one also restarts the deadline, up to
+    /// `BridgeTimeouts::total` for the whole command)
+    pub fn request_with_progress(
+        &mut self,
+        command: BridgeCommand,
+        mut on_progress: impl FnMut(f32),
+    ) -> Result<BridgeResponse, AppError> {
+        // IsSupported depends only on the plugin and the header bytes
+        let supported_key = match &command {
+            BridgeCommand::IsSupported { plugin_id, header } => Some((*plugin_id, xxh3_64(header))),
+            _ => None,
+        };
+        if let Some(&supported) = supported_key.and_then(|key| self.supported.get(&key)) {
+            return Ok(BridgeResponse::Supported { supported });
+        }
+
+        let response = match self.try_request(&command, &mut on_progress) {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!("Susie bridge failed ({}), restarting", e);
+                self.drop_connection();
+                match self.try_request(&command, &mut on_progress) {
+                    Ok(response) => response,
+                    Err(e) => {
+                        self.drop_connection();
+                        self.health.set(BridgeHealth::Unavailable);
+                        return Err(e);
+                    }
+                }
+            }
+        };
+        self.restarts = 0;
+        self.health.set(BridgeHealth::Healthy);
+
+        if let (Some(key), BridgeResponse::Supported { supported }) = (supported_key, &response) {
+            self.supported.insert(key, *supported);
+        }
+        if let (
+            BridgeCommand::LoadPlugin { path },
+            BridgeResponse::PluginLoaded { plugin_id, supported_extensions, .. },
+        ) = (&command, &response)
+        {
+            self.plugins.insert(*plugin_id, LoadedPlugin { path: path.clone(), current_id: *plugin_id });
+            self.register_extensions(*plugin_id, path, supported_extensions);
+        }
+        if let BridgeResponse::PluginUnloaded { plugin_id } = response {
+            let unloaded: Vec<u32> = self.plugins.iter()
+                .filter(|(_, p)| p.current_id == plugin_id)
+                .map(|(&id, _)| id)
+                .collect();
+            self.plugins.retain(|id, _| !unloaded.contains(id));
+            self.extensions.retain(|_, id| !unloaded.contains(id));
+            self.supported.retain(|(id, _), _| !unloaded.contains(id));
+        }
+        Ok(response)
+    }
+
+    fn register_extensions(&mut self, plugin_id: u32, path: &str, filters: &[String]) {
+        for ext in normalize_extensions(filters) {
+            match self.extensions.get(&ext) {
+                Some(&existing) if existing != plugin_id => {
+                    let owner = self.plugins.get(&existing).map(|p| p.path.as_str()).unwrap_or("?");
+                    tracing::info!("Extension .{} of {} is already handled by {}", ext, path, owner);
+                }
+                Some(_) => {}
+                None => {
+                    self.extensions.insert(ext, plugin_id);
+                }
+            }
+        }
+    }
+
+    /// Load every plugin in `dir`; returns the extensions now supported.
+    /// Plugins that fail to load are logged and skipped.
+    pub fn load_plugin_dir(&mut self, dir: &Path) -> Result<Vec<String>, AppError> {
+        for file in plugin_files(dir)? {
+            let path = file.to_string_lossy().into_owned();
+            match self.request(BridgeCommand::LoadPlugin { path: path.clone() })? {
+                BridgeResponse::PluginLoaded { name, .. } => tracing::info!("Loaded Susie plugin {} ({})", name, path),
+                other => tracing::warn!("Failed to load Susie plugin {}: {:?}", path, other),
+            }
+        }
+        let mut extensions: Vec<_> = self.extensions.keys().cloned().collect();
+        extensions.sort();
+        Ok(extensions)
+    }
+
+    /// Members of a plugin-backed archive (e.g. .lzh)
+    pub fn list_archive(&mut self, archive_path: &Path) -> Result<Vec<ArchiveEntry>, AppError> {
+        let plugin_id = self.archive_plugin(archive_path)?;
+        let response = self.request(BridgeCommand::GetArchiveList {
+            plugin_id,
+            archive_path: archive_path.to_string_lossy().into_owned(),
+        })?;
+
+        match response {
+            BridgeResponse::ArchiveList { entries } => Ok(entries),
+            BridgeResponse::Error { message, .. } => Err(AppError::Archive(message)),
+            other => Err(AppError::Bridge(format!("Unexpected response to GetArchiveList: {:?}", other))),
+        }
+    }
+
+    /// Extract `inner_path` from a plugin-backed archive (e.g. .lzh) into memory,
+    /// reporting the plugin's progress. The bridge's shared memory block is
+    /// released as soon as it is copied out.
+    pub fn extract_to_memory<R: SharedMemoryReader>(
+        &mut self,
+        archive_path: &Path,
+        inner_path: &str,
+        reader: &R,
+        on_progress: impl FnMut(f32),
+    ) -> Result<Vec<u8>, AppError> {
+        let plugin_id = self.archive_plugin(archive_path)?;
+        let command = BridgeCommand::ExtractFile {
+            plugin_id,
+            archive_path: archive_path.to_string_lossy().into_owned(),
+            inner_path: inner_path.to_string(),
+            dest_path: None,
+        };
+        let response = self.request_with_progress(command, on_progress)?;
+
+        match response {
+            BridgeResponse::FileExtracted { shmem_handle: Some(handle), size, .. } => {
+                let data = reader.read(&handle, size);
+                if let Err(e) = self.request(BridgeCommand::FreeSharedMemory { shmem_handle: handle }) {
+                    tracing::warn!("Failed to free bridge shared memory: {}", e);
+                }
+                data
+            }
+            BridgeResponse::FileExtracted { path: Some(path), .. } => Ok(std::fs::read(path)?),
+            BridgeResponse::Error { message, .. } => Err(AppError::Archive(message)),
+            other => Err(AppError::Bridge(format!("Unexpected response to ExtractFile: {:?}", other))),
+        }
+    }
+
+    /// Plugin that decodes `path`, by extension
+    pub fn plugin_for(&self, path: &Path) -> Option<u32> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        self.extensions.get(&ext).copied()
+    }
+
+    fn archive_plugin(&self, archive_path: &Path) -> Result<u32, AppError> {
+        self.plugin_for(archive_path).ok_or_else(|| {
+            AppError::Archive(format!("No Susie plugin for {}", archive_path.display()))
+        })
+    }
+
+    fn try_request(
+        &mut self,
+        command: &BridgeCommand,
+        on_progress: &mut dyn FnMut(f32),
+    ) -> Result<BridgeResponse, AppError> {
+        self.ensure_connected()?;
+
+        let mut command = command.clone();
+        if let Some(id) = command.plugin_id_mut() {
+            if let Some(plugin) = self.plugins.get(id) {
+                *id = plugin.current_id;
+            }
+        }
+
+        // Missed heartbeats leave Pongs in the stream; they must be read first
+        if self.outstanding_pongs > 0 && !self.ping()? {
+            return Err(AppError::Bridge("Susie bridge is not responding".into()));
+        }
+
+        let timeout = self.timeouts.for_command(&command);
+        let deadline = Instant::now() + self.timeouts.total.max(timeout);
+        let Some(connection) = self.connection.as_mut() else {
+            return Err(AppError::Bridge("Not connected".into()));
+        };
+        connection.send(&command)?;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            match connection.recv(timeout.min(remaining))? {
+                Some(BridgeResponse::Progress { fraction }) => on_progress(fraction.clamp(0.0, 1.0)),
+                Some(response) => return Ok(response),
+                None => break,
+            }
+        }
+
+        tracing::warn!("Susie bridge command timed out after {:?}: {:?}", timeout, command);
+        if !self.ping()? {
+            // Hung plugin: kill the bridge; the next command relaunches it
+            self.drop_connection();
+        }
+        Ok(BridgeResponse::Error {
+            code: ErrorCode::Timeout,
+            message: format!("Bridge did not respond within {:?}", timeout),
+        })
+    }
+
+    /// Launch the bridge if needed and reload the known plugins into it
+    fn ensure_connected(&mut self) -> Result<(), AppError> {
+        if self.connection.is_some() {
+            return Ok(());
+        }
+        if let Some(ref message) = self.incompatible {
+            return Err(AppError::Bridge(message.clone()));
+        }
+        // The first launch isn't a restart
+        if self.launched {
+            if self.restarts >= MAX_BRIDGE_RESTARTS {
+                return Err(AppError::Bridge(format!(
+                    "Susie bridge crashed {} times in a row; giving up",
+                    self.restarts
+                )));
+            }
+            self.restarts += 1;
+        }
+
+        self.launched = true;
+        let mut connection = (self.launch)()?;
+        let hello = BridgeCommand::Hello { proto_version: PROTO_VERSION };
+        match exchange(&mut connection, &hello, self.timeouts.command)? {
+            Some(BridgeResponse::HelloAck { proto_version, bridge_version }) if is_compatible(proto_version) => {
+                tracing::info!("Connected to Susie bridge {} (protocol v{})", bridge_version, proto_version);
+            }
+            Some(BridgeResponse::HelloAck { proto_version, bridge_version }) => {
+                let message = format!(
+                    "Susie bridge {} speaks protocol v{}, but v{} is required; reinstall matching binaries",
+                    bridge_version, proto_version, PROTO_VERSION
+                );
+                tracing::error!("{}", message);
+                self.incompatible = Some(message.clone());
+                return Err(AppError::Bridge(message));
+            }
+            Some(other) => {
+                let message = format!("Susie bridge handshake failed (unexpected {:?}); incompatible bridge binary?", other);
+                self.incompatible = Some(message.clone());
+                return Err(AppError::Bridge(message));
+            }
+            None => return Err(AppError::Bridge("Susie bridge did not answer the handshake".into())),
+        }
+        for plugin in self.plugins.values_mut() {
+            let command = BridgeCommand::LoadPlugin { path: plugin.path.clone() };
+            match exchange(&mut connection, &command, self.timeouts.command)? {
+                Some(BridgeResponse::PluginLoaded { plugin_id, .. }) => plugin.current_id = plugin_id,
+                other => tracing::warn!("Failed to reload plugin {}: {:?}", plugin.path, other),
+            }
+        }
+        self.connection = Some(connection);
+        Ok(())
+    }
+}
+
+/// Run `heartbeat` every `interval` on a background thread until the supervisor is dropped.
+/// The supervisor's lock keeps Pings from interleaving with in-flight requests;
+/// a beat is skipped while a request holds it.
+pub fn spawn_heartbeat<C, F>(supervisor: Weak<Mutex<BridgeSupervisor<C, F>>>, interval: Duration)
+where
+    C: BridgeConnection + Send + 'static,
+    F: FnMut() -> Result<C, AppError> + Send + 'static,
+{
+    std::thread::spawn(move || loop {
+        std::thread::sleep(interval);
+        let Some(shared) = supervisor.upgrade() else {
+            break;
+        };
+        let guard = shared.try_lock();
+        if let Some(mut guard) = guard {
+            guard.heartbeat();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    /// Fake bridge: hands out plugin ids from `id_base`; GetPicture crashes,
+    /// hangs (answering late) or succeeds
+    #[derive(Default)]
+    struct FakeBridge {
+        id_base: u32,
+        loaded: u32,
+        log: Rc<RefCell<Vec<String>>>,
+        crash: bool,
+        hang: bool,
+        /// Ping answers are held back as late responses
+        mute: bool,
+        /// Protocol version reported in HelloAck (0 = current)
+        proto_version: u32,
+        /// Progress messages sent ahead of each GetPicture and ExtractFile answer
+        progress: Vec<f32>,
+        /// Responses that will arrive only after the next timeout
+        late: Vec<BridgeResponse>,
+        queue: VecDeque<BridgeResponse>,
+    }
+
+    impl BridgeConnection for FakeBridge {
+        fn send(&mut self, command: &BridgeCommand) -> Result<(), AppError> {
+            let response = match command {
+                BridgeCommand::LoadPlugin { path } => {
+                    self.loaded += 1;
+                    self.log.borrow_mut().push(format!("load {}", path));
+                    let supported_extensions = match Path::new(path).file_stem().and_then(|s| s.to_str()) {
+                        Some("ifpi") => vec!["*.pi;*.PIC".to_string()],
+                        Some("ifpic") => vec!["*.pic".to_string()],
+                        _ => Vec::new(),
+                    };
+                    BridgeResponse::PluginLoaded {
+                        plugin_id: self.id_base + self.loaded,
+                        name: path.clone(),
+                        version: String::new(),
+                        supported_extensions,
+                    }
+                }
+                BridgeCommand::GetPicture { plugin_id, .. } => {
+                    if self.crash {
+                        return Err(AppError::Bridge("broken pipe".into()));
+                    }
+                    self.log.borrow_mut().push(format!("picture {}", plugin_id));
+                    if self.hang {
+                        self.late.push(BridgeResponse::Supported { supported: true });
+                        return Ok(());
+                    }
+                    let progress = self.progress.iter().map(|&fraction| BridgeResponse::Progress { fraction });
+                    self.queue.extend(progress);
+                    BridgeResponse::Supported { supported: true }
+                }
+                BridgeCommand::IsSupported { plugin_id, header } => {
+                    self.log.borrow_mut().push(format!("supported {}", plugin_id));
+                    BridgeResponse::Supported { supported: header.starts_with(b"PI") }
+                }
+                BridgeCommand::Hello { .. } => BridgeResponse::HelloAck {
+                    proto_version: if self.proto_version == 0 { PROTO_VERSION } else { self.proto_version },
+                    bridge_version: "test".into(),
+                },
+                BridgeCommand::Ping if self.mute => {
+                    self.late.push(BridgeResponse::Pong);
+                    return Ok(());
+                }
+                BridgeCommand::UnloadPlugin { plugin_id } => BridgeResponse::PluginUnloaded { plugin_id: *plugin_id },
+                BridgeCommand::GetArchiveList { .. } => BridgeResponse::ArchiveList {
+                    entries: vec![ArchiveEntry {
+                        path: "page1.bmp".into(),
+                        size: 4,
+                        compressed_size: 2,
+                        is_directory: false,
+                        timestamp: None,
+                    }],
+                },
+                BridgeCommand::ExtractFile { inner_path, .. } => {
+                    let progress = self.progress.iter().map(|&fraction| BridgeResponse::Progress { fraction });
+                    self.queue.extend(progress);
+                    self.log.borrow_mut().push(format!("extract {}", inner_path));
+                    BridgeResponse::FileExtracted { path: None, shmem_handle: Some("LF_1".into()), size: 4 }
+                }
+                BridgeCommand::FreeSharedMemory { shmem_handle } => {
+                    self.log.borrow_mut().push(format!("free {}", shmem_handle));
+                    BridgeResponse::SharedMemoryFreed
+                }
+                _ => BridgeResponse::Pong,
+            };
+            self.queue.push_back(response);
+            Ok(())
+        }
+
+        fn recv(&mut self, _timeout: Duration) -> Result<Option<BridgeResponse>, AppError> {
+            Ok(self.queue.pop_front())
+        }
+    }
+
+    fn picture(plugin_id: u32) -> BridgeCommand {
+        BridgeCommand::GetPicture { plugin_id, file_path: "a.pi".into(), offset: 0, total_size: 0 }
+    }
+
+    #[test]
+    fn test_restart_replays_plugins() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let launches = Rc::new(RefCell::new(0u32));
+        let (l, n) = (log.clone(), launches.clone());
+        let mut supervisor = BridgeSupervisor::new(move || {
+            *n.borrow_mut() += 1;
+            let launch = *n.borrow();
+            // The first bridge crashes on its first picture
+            Ok(FakeBridge { id_base: launch * 100, log: l.clone(), crash: launch == 1, ..Default::default() })
+        });
+
+        let id = match supervisor.request(BridgeCommand::LoadPlugin { path: "ifpi.spi".into() }).unwrap() {
+            BridgeResponse::PluginLoaded { plugin_id, .. } => plugin_id,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(id, 101);
+
+        // The restarted bridge reloads the plugin (under a new id) and serves the retry
+        assert!(supervisor.request(picture(id)).is_ok());
+        assert_eq!(*launches.borrow(), 2);
+        assert_eq!(*log.borrow(), vec!["load ifpi.spi", "load ifpi.spi", "picture 201"]);
+        assert_eq!(supervisor.plugin_paths(), vec!["ifpi.spi"]);
+    }
+
+    #[test]
+    fn test_repeated_crashes_give_up() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let launches = Rc::new(RefCell::new(0u32));
+        let (l, n) = (log.clone(), launches.clone());
+        let mut supervisor = BridgeSupervisor::new(move || {
+            *n.borrow_mut() += 1;
+            Ok(FakeBridge { log: l.clone(), crash: true, ..Default::default() })
+        });
+
+        // Each request restarts once; after MAX_BRIDGE_RESTARTS it stops launching
+        for _ in 0..5 {
+            assert!(supervisor.request(picture(1)).is_err());
+        }
+        assert_eq!(*launches.borrow(), 1 + MAX_BRIDGE_RESTARTS);
+        assert!(matches!(supervisor.request(BridgeCommand::Ping), Err(AppError::Bridge(_))));
+    }
+
+    #[test]
+    fn test_load_plugin_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["ifpi.spi", "ifpic.SPI", "readme.txt"] {
+            std::fs::write(dir.path().join(name), b"").unwrap();
+        }
+
+        let mut supervisor = BridgeSupervisor::new(|| Ok(FakeBridge { id_base: 10, ..Default::default() }));
+        let extensions = supervisor.load_plugin_dir(dir.path()).unwrap();
+        assert_eq!(extensions, vec!["pi", "pic"]);
+
+        // .pic is claimed by both; the first loaded plugin (ifpi) keeps it
+        assert_eq!(supervisor.plugin_for(Path::new("a.PI")), Some(11));
+        assert_eq!(supervisor.plugin_for(Path::new("b.pic")), Some(11));
+        assert_eq!(supervisor.plugin_for(Path::new("c.jpg")), None);
+    }
+
+    #[test]
+    fn test_is_supported_cache() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let l = log.clone();
+        let mut supervisor = BridgeSupervisor::new(move || Ok(FakeBridge { log: l.clone(), ..Default::default() }));
+        let check = |header: &[u8]| BridgeCommand::IsSupported { plugin_id: 1, header: header.to_vec() };
+        supervisor.request(BridgeCommand::LoadPlugin { path: "ifpi.spi".into() }).unwrap();
+
+        for _ in 0..3 {
+            assert!(matches!(supervisor.request(check(b"PI01")).unwrap(), BridgeResponse::Supported { supported: true }));
+        }
+        assert!(matches!(supervisor.request(check(b"GIF8")).unwrap(), BridgeResponse::Supported { supported: false }));
+        assert_eq!(log.borrow().iter().filter(|l| l.starts_with("supported")).count(), 2);
+
+        // Unloading the plugin forgets its answers
+        supervisor.request(BridgeCommand::UnloadPlugin { plugin_id: 1 }).unwrap();
+        supervisor.request(check(b"PI01")).unwrap();
+        assert_eq!(log.borrow().iter().filter(|l| l.starts_with("supported")).count(), 3);
+    }
+
+    #[test]
+    fn test_plugin_archive() {
+        struct FakeShmem;
+        impl SharedMemoryReader for FakeShmem {
+            fn read(&self, handle: &str, size: usize) -> Result<Vec<u8>, AppError> {
+                assert_eq!(handle, "LF_1");
+                Ok(vec![7; size])
+            }
+        }
+
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let l = log.clone();
+        let mut supervisor =
+            BridgeSupervisor::new(move || Ok(FakeBridge { log: l.clone(), progress: vec![0.5], ..Default::default() }));
+        supervisor.request(BridgeCommand::LoadPlugin { path: "ifpi.spi".into() }).unwrap();
+
+        let entries = supervisor.list_archive(Path::new("a.pi")).unwrap();
+        assert_eq!(entries.iter().map(|e| e.path.as_str()).collect::<Vec<_>>(), ["page1.bmp"]);
+
+        let mut reported = Vec::new();
+        let data = supervisor
+            .extract_to_memory(Path::new("a.pi"), "page1.bmp", &FakeShmem, |f| reported.push(f))
+            .unwrap();
+        assert_eq!(data, vec![7; 4]);
+        assert_eq!(reported, vec![0.5]);
+        assert_eq!(log.borrow()[1..], ["extract page1.bmp", "free LF_1"]);
+
+        // No plugin claims the extension
+        assert!(supervisor.extract_to_memory(Path::new("a.lzh"), "x", &FakeShmem, |_| {}).is_err());
+        assert!(supervisor.list_archive(Path::new("a.lzh")).is_err());
+    }
+
+    #[test]
+    fn test_heartbeat_restarts_wedged_bridge() {
+        let launches = Rc::new(RefCell::new(0u32));
+        let n = launches.clone();
+        let mut supervisor = BridgeSupervisor::new(move || {
+            *n.borrow_mut() += 1;
+            // The first bridge wedges: it never answers Pings
+            Ok(FakeBridge { mute: *n.borrow() == 1, ..Default::default() })
+        });
+        let health = supervisor.health();
+        assert_eq!(supervisor.heartbeat(), BridgeHealth::Healthy); // not launched yet
+        supervisor.request(BridgeCommand::LoadPlugin { path: "ifpi.spi".into() }).unwrap();
+
+        for _ in 1..MAX_MISSED_HEARTBEATS {
+            assert_eq!(supervisor.heartbeat(), BridgeHealth::Unresponsive);
+        }
+        assert_eq!(health.get(), BridgeHealth::Unresponsive);
+
+        assert_eq!(supervisor.heartbeat(), BridgeHealth::Healthy);
+        assert_eq!(*launches.borrow(), 2);
+        assert_eq!(health.get(), BridgeHealth::Healthy);
+        assert_eq!(supervisor.heartbeat(), BridgeHealth::Healthy);
+    }
+
+    #[test]
+    fn test_handshake_rejects_other_version() {
+        let launches = Rc::new(RefCell::new(0u32));
+        let n = launches.clone();
+        let mut supervisor = BridgeSupervisor::new(move || {
+            *n.borrow_mut() += 1;
+            Ok(FakeBridge { proto_version: PROTO_VERSION + 1, ..Default::default() })
+        });
+
+        let err = supervisor.request(BridgeCommand::Ping).unwrap_err();
+        assert!(err.to_string().contains("protocol"));
+        // Refused for good: no relaunch loop
+        assert!(supervisor.request(BridgeCommand::Ping).is_err());
+        assert_eq!(*launches.borrow(), 1);
+    }
+
+    #[test]
+    fn test_progress_before_final_response() {
+        let mut supervisor = BridgeSupervisor::new(|| Ok(FakeBridge { progress: vec![0.25, 0.5, 1.5], ..Default::default() }));
+        let mut reported = Vec::new();
+        let response = supervisor.request_with_progress(picture(1), |f| reported.push(f)).unwrap();
+        assert!(matches!(response, BridgeResponse::Supported { supported: true }));
+        assert_eq!(reported, vec![0.25, 0.5, 1.0]);
+
+        // Plugins without progress still work through plain request
+        assert!(matches!(supervisor.request(picture(1)).unwrap(), BridgeResponse::Supported { .. }));
+    }
+
+    #[test]
+    fn test_process_connection_reports_exit() {
+        assert!(matches!(ProcessConnection::spawn(Path::new("no-such-bridge")), Err(AppError::Bridge(_))));
+
+        // A "bridge" that exits right away reads as a broken connection, not a timeout
+        #[cfg(unix)]
+        {
+            let mut connection = ProcessConnection::spawn(Path::new("true")).unwrap();
+            assert!(connection.recv(Duration::from_secs(5)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_endless_progress_times_out() {
+        /// Reports progress forever and never answers
+        struct ChattyBridge(FakeBridge);
+        impl BridgeConnection for ChattyBridge {
+            fn send(&mut self, command: &BridgeCommand) -> Result<(), AppError> {
+                self.0.send(command)
+            }
+            fn recv(&mut self, timeout: Duration) -> Result<Option<BridgeResponse>, AppError> {
+                if self.0.queue.is_empty() && !self.0.late.is_empty() {
+                    std::thread::sleep(timeout.min(Duration::from_millis(5)));
+                    return Ok(Some(BridgeResponse::Progress { fraction: 0.5 }));
+                }
+                self.0.recv(timeout)
+            }
+        }
+
+        let timeouts = BridgeTimeouts { decode: Duration::from_millis(20), total: Duration::from_millis(50), ..Default::default() };
+        let mut supervisor = BridgeSupervisor::new(|| Ok(ChattyBridge(FakeBridge { hang: true, ..Default::default() })))
+            .with_timeouts(timeouts);
+        let mut reported = 0;
+        let response = supervisor.request_with_progress(picture(1), |_| reported += 1).unwrap();
+        assert!(matches!(response, BridgeResponse::Error { code: ErrorCode::Timeout, .. }));
+        assert!(reported > 0);
+    }
+
+    #[test]
+    fn test_timeout_resyncs_stream() {
+        /// Delivers the hung command's response late, right before the Pong
+        struct LateBridge(FakeBridge);
+        impl BridgeConnection for LateBridge {
+            fn send(&mut self, command: &BridgeCommand) -> Result<(), AppError> {
+                if matches!(command, BridgeCommand::Ping) {
+                    let late: Vec<_> = self.0.late.drain(..).collect();
+                    self.0.queue.extend(late);
+                }
+                self.0.send(command)
+            }
+            fn recv(&mut self, timeout: Duration) -> Result<Option<BridgeResponse>, AppError> {
+                self.0.recv(timeout)
+            }
+        }
+
+        let mut supervisor = BridgeSupervisor::new(|| Ok(LateBridge(FakeBridge { hang: true, ..Default::default() })));
+        let response = supervisor.request(picture(1)).unwrap();
+        assert!(matches!(response, BridgeResponse::Error { code: ErrorCode::Timeout, .. }));
+
+        // The late answer was discarded, so the next reply matches its command
+        assert!(supervisor.connection.is_some());
+        assert!(matches!(supervisor.request(BridgeCommand::Ping).unwrap(), BridgeResponse::Pong));
+    }
+}
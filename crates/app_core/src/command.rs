@@ -4,6 +4,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use crate::config::SpreadMode;
+use app_fs::WallpaperStyle;
 
 /// Command identifier
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -44,6 +45,7 @@ impl CommandId {
     pub const NAV_NEXT_SIBLING: &'static str = "nav.next_sibling";
     pub const NAV_PREV_SIBLING: &'static str = "nav.prev_sibling";
     pub const NAV_ROOT: &'static str = "nav.root";
+    pub const NAV_RECENT_FOLDERS: &'static str = "nav.recent_folders";
 
     // D. Scroll
     pub const NAV_SCROLL_Y: &'static str = "nav.scroll_y";
@@ -67,6 +69,7 @@ impl CommandId {
     pub const VIEW_ZOOM_SET: &'static str = "view.zoom_set";
     pub const VIEW_ZOOM_MODE_CYCLE: &'static str = "view.zoom_mode_cycle";
     pub const VIEW_LOCK_ZOOM: &'static str = "view.lock_zoom";
+    pub const VIEW_ACTUAL_PIXELS: &'static str = "view.actual_pixels";
     pub const VIEW_ZOOM_RESET: &'static str = "view.zoom_set"; // alias
     pub const VIEW_FIT_TO_WINDOW: &'static str = "view.zoom_set";
     pub const VIEW_ORIGINAL_SIZE: &'static str = "view.zoom_set";
@@ -110,6 +113,14 @@ impl CommandId {
     pub const VIEW_TOGGLE_CHROMELESS: &'static str = "view.toggle_chromeless";
     pub const VIEW_SET_BACKGROUND: &'static str = "view.set_background";
     pub const VIEW_QUICK_LOOK: &'static str = "view.quick_look";
+    pub const VIEW_TOGGLE_HISTOGRAM: &'static str = "view.toggle_histogram";
+    pub const VIEW_HISTOGRAM_MODE: &'static str = "view.histogram_mode";
+    pub const VIEW_TOGGLE_LOUPE: &'static str = "view.toggle_loupe";
+    pub const VIEW_TOGGLE_EYEDROPPER: &'static str = "view.toggle_eyedropper";
+    pub const VIEW_TIMELINE: &'static str = "view.timeline";
+    pub const VIEW_ADJUST: &'static str = "view.adjust";
+    pub const VIEW_GRAYSCALE: &'static str = "view.grayscale";
+    pub const VIEW_INVERT: &'static str = "view.invert";
 
     // Legacy aliases
     pub const VIEW_ROTATE_LEFT: &'static str = "view.rotate";
@@ -129,14 +140,26 @@ impl CommandId {
     // B. File system
     pub const FILE_DELETE: &'static str = "file.delete";
     pub const FILE_RENAME: &'static str = "file.rename";
+    pub const FILE_BATCH_RENAME: &'static str = "file.batch_rename";
+    pub const FILE_ROTATE_SAVE: &'static str = "file.rotate_save";
+    pub const FILE_SAVE_AS: &'static str = "file.save_as";
+    pub const FILE_EXPORT: &'static str = "file.export";
+    pub const FILE_BATCH_CONVERT: &'static str = "file.batch_convert";
     pub const FILE_CREATE_DIR: &'static str = "file.create_dir";
     pub const FILE_COPY_TO: &'static str = "file.copy_to";
     pub const FILE_MOVE_TO: &'static str = "file.move_to";
+    pub const FILE_BOOKMARK_FOLDER: &'static str = "file.bookmark_folder";
+    pub const FILE_FIND_DUPLICATES: &'static str = "file.find_duplicates";
+    pub const FILE_TOGGLE_HIDDEN: &'static str = "file.toggle_hidden";
+    pub const FILE_FLATTEN_SUBFOLDERS: &'static str = "file.flatten_subfolders";
+    pub const FILE_TOGGLE_SORT_ORDER: &'static str = "file.toggle_sort_order";
 
     // C. External/Shell
     pub const FILE_OPEN_EXPLORER: &'static str = "file.open_explorer";
     pub const FILE_OPEN_WITH: &'static str = "file.open_with";
     pub const FILE_OPEN_EXTERNAL: &'static str = "file.open_external";
+    pub const FILE_OPEN_TERMINAL: &'static str = "file.open_terminal";
+    pub const FILE_SET_WALLPAPER: &'static str = "file.set_wallpaper";
     pub const FILE_PROPERTIES: &'static str = "file.properties";
 
     // ========================================
@@ -151,9 +174,14 @@ impl CommandId {
     pub const META_TAG_REMOVE: &'static str = "meta.tag_remove";
     pub const META_EDIT_TAGS: &'static str = "meta.edit_tags";
     pub const META_COPY_META: &'static str = "meta.copy_meta";
+    pub const META_PASTE_META: &'static str = "meta.paste_meta";
     pub const META_EDIT_COMMENT: &'static str = "meta.edit_comment";
+    pub const META_EXPORT_SIDECARS: &'static str = "meta.export_sidecars";
+    pub const META_EXPORT_ALL_SIDECARS: &'static str = "meta.export_all_sidecars";
+    pub const META_IMPORT_SIDECARS: &'static str = "meta.import_sidecars";
     pub const META_TOGGLE_MARK: &'static str = "meta.toggle_mark";
     pub const META_SELECT_MARKED: &'static str = "meta.select_marked";
+    pub const META_EXPORT_MARKS: &'static str = "meta.export_marks";
 
     // ========================================
     // App Commands (app.*)
@@ -175,6 +203,9 @@ impl CommandId {
     pub const APP_LAYOUT_LOAD: &'static str = "app.layout_load";
     pub const APP_LAYOUT_RESET: &'static str = "app.layout_reset";
     pub const APP_SEARCH: &'static str = "app.search";
+    pub const APP_RELOAD_CONFIG: &'static str = "app.reload_config";
+    pub const APP_COMMAND_PALETTE: &'static str = "app.command_palette";
+    pub const APP_EMPTY_TRASH: &'static str = "app.empty_trash";
 
     // Legacy alias
     pub const APP_QUIT: &'static str = "app.exit";
@@ -269,6 +300,8 @@ pub struct CommandParams {
     pub app_id: Option<String>,
     /// External app arguments
     pub args: Option<String>,
+    /// Wallpaper layout (Fit/Fill/Stretch)
+    pub wallpaper_style: Option<WallpaperStyle>,
 
     // Metadata parameters
     /// Rating value (0-5)
@@ -342,6 +375,38 @@ pub enum PathFormat { Full, Name, Dir }
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LabelColor { Red, Blue, Green, Yellow, Purple, None }
 
+impl LabelColor {
+    const PALETTE: [(LabelColor, &'static str, u32); 5] = [
+        (LabelColor::Red, "Red", 0xFF0000),
+        (LabelColor::Blue, "Blue", 0x0000FF),
+        (LabelColor::Green, "Green", 0x00FF00),
+        (LabelColor::Yellow, "Yellow", 0xFFFF00),
+        (LabelColor::Purple, "Purple", 0x800080),
+    ];
+
+    /// Display name
+    pub fn name(self) -> &'static str {
+        Self::PALETTE.iter()
+            .find(|(color, _, _)| *color == self)
+            .map(|(_, name, _)| *name)
+            .unwrap_or("None")
+    }
+
+    /// 0xRRGGBB value stored in the DB (None clears the label)
+    pub fn rgb(self) -> Option<u32> {
+        Self::PALETTE.iter()
+            .find(|(color, _, _)| *color == self)
+            .map(|(_, _, rgb)| *rgb)
+    }
+
+    /// Palette color for a stored value, if it is one
+    pub fn from_rgb(rgb: u32) -> Option<Self> {
+        Self::PALETTE.iter()
+            .find(|(_, _, value)| *value == rgb)
+            .map(|(color, _, _)| *color)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CopyTarget { Rating, Tags, All }
 
@@ -481,6 +546,11 @@ impl Command {
         self
     }
 
+    pub fn with_wallpaper_style(mut self, style: WallpaperStyle) -> Self {
+        self.params.wallpaper_style = Some(style);
+        self
+    }
+
     // Metadata builders
     pub fn with_value(mut self, value: i32) -> Self {
         self.params.value = Some(value);
@@ -492,6 +562,11 @@ impl Command {
         self
     }
 
+    pub fn with_copy_target(mut self, target: CopyTarget) -> Self {
+        self.params.copy_target = Some(target);
+        self
+    }
+
     pub fn with_tag_name(mut self, name: &str) -> Self {
         self.params.name = Some(name.to_string());
         self
@@ -15,6 +15,8 @@ pub struct AppConfig {
     pub navigation: NavigationConfig,
     pub keybindings: HashMap<String, Vec<String>>,
     pub recent_folders: Vec<String>,
+    /// Applications offered by file.open_with
+    pub external_apps: Vec<ExternalApp>,
 }
 
 impl Default for AppConfig {
@@ -26,10 +28,29 @@ impl Default for AppConfig {
             navigation: NavigationConfig::default(),
             keybindings: default_keybindings(),
             recent_folders: Vec::new(),
+            external_apps: Vec::new(),
         }
     }
 }
 
+/// An application registered for file.open_with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExternalApp {
+    /// Name shown in the menu; also the command's app_id
+    pub name: String,
+    /// Executable path, or a program name found on PATH
+    pub path: String,
+    /// Argument template with {file} and {dir}
+    pub args: String,
+}
+
+impl Default for ExternalApp {
+    fn default() -> Self {
+        Self { name: String::new(), path: String::new(), args: "{file}".to_string() }
+    }
+}
+
 /// Navigation configuration (Doc 3 compliant)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -63,6 +84,12 @@ pub struct GeneralConfig {
     pub start_maximized: bool,
     pub remember_window_state: bool,
     pub check_updates: bool,
+    /// Days of view history kept for the Recent view
+    pub history_retention_days: u32,
+    /// Reopen the last folder, selection and view mode on startup
+    pub restore_session: bool,
+    /// Folder with Susie plugins (empty = "plugins" next to the executable)
+    pub susie_plugin_dir: String,
 }
 
 impl Default for GeneralConfig {
@@ -73,6 +100,9 @@ impl Default for GeneralConfig {
             start_maximized: false,
             remember_window_state: true,
             check_updates: false,
+            history_retention_days: 30,
+            restore_session: true,
+            susie_plugin_dir: String::new(),
         }
     }
 }
@@ -87,7 +117,15 @@ pub struct ViewerConfig {
     pub reading_direction: ReadingDirection,
     pub slideshow_interval_ms: u64,
     pub enable_animation: bool,
+    /// Crossfade duration between images (ms)
+    pub transition_duration_ms: u64,
     pub preload_count: usize,
+    /// Keep the image from being panned out of view (false = free panning)
+    pub clamp_pan: bool,
+    /// Snap image edges flush to the viewport edges when panned close to them
+    pub snap_to_edges: bool,
+    /// Loupe magnification (screen pixels per image pixel)
+    pub loupe_magnification: f32,
 }
 
 impl Default for ViewerConfig {
@@ -100,7 +138,11 @@ impl Default for ViewerConfig {
             reading_direction: ReadingDirection::LeftToRight,
             slideshow_interval_ms: 3000,
             enable_animation: true,
+            transition_duration_ms: 200,
             preload_count: 3,
+            clamp_pan: true,
+            snap_to_edges: true,
+            loupe_magnification: 4.0,
         }
     }
 }
@@ -111,10 +153,32 @@ pub struct FilerConfig {
     pub show_hidden_files: bool,
     pub sort_by: SortBy,
     pub sort_order: SortOrder,
+    /// Group folders before files (default for folders without a stored sort)
+    pub dirs_first: bool,
     pub thumbnail_size: u32,
+    /// Maximum number of thumbnail textures kept in memory
+    pub thumbnail_memory_limit: usize,
+    /// Maximum size of the on-disk thumbnail cache (MB, 0 = unlimited)
+    pub thumbnail_cache_limit_mb: u64,
+    /// Thumbnail generation threads (0 = one per CPU)
+    pub thumbnail_workers: usize,
     pub view_mode: ViewMode,
+    /// Timeline: group headers by day or month
+    pub timeline_grouping: TimelineGrouping,
+    /// Timeline: show newest files first within each group
+    pub timeline_newest_first: bool,
+    /// Maximum perceptual-hash distance (0-64) for images to count as duplicates
+    pub duplicate_threshold: u32,
+    /// file.flatten_subfolders: how many folder levels below the current one to include
+    pub flatten_max_depth: usize,
     pub confirm_delete: bool,
     pub use_recycle_bin: bool,
+    /// What to do when a file being written (e.g. file.save_as) already exists
+    pub conflict_policy: ConflictPolicy,
+    /// How many folders nav.recent_folders remembers
+    pub recent_folder_limit: usize,
+    /// file.open_terminal command line with {dir}; empty = the platform terminal
+    pub terminal_command: String,
 }
 
 impl Default for FilerConfig {
@@ -123,10 +187,21 @@ impl Default for FilerConfig {
             show_hidden_files: false,
             sort_by: SortBy::Name,
             sort_order: SortOrder::Ascending,
+            dirs_first: true,
             thumbnail_size: 128,
+            thumbnail_memory_limit: 1000,
+            thumbnail_cache_limit_mb: 1024,
+            thumbnail_workers: 0,
             view_mode: ViewMode::Grid,
+            timeline_grouping: TimelineGrouping::Day,
+            timeline_newest_first: true,
+            duplicate_threshold: 10,
+            flatten_max_depth: 8,
             confirm_delete: true,
+            conflict_policy: ConflictPolicy::Rename,
             use_recycle_bin: true,
+            recent_folder_limit: 20,
+            terminal_command: String::new(),
         }
     }
 }
@@ -183,6 +258,18 @@ pub enum SortBy {
     Type,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConflictPolicy {
+    /// Write under a numbered name ("photo (2).png")
+    #[serde(rename = "rename")]
+    Rename,
+    #[serde(rename = "overwrite")]
+    Overwrite,
+    /// Leave the existing file alone and write nothing
+    #[serde(rename = "skip")]
+    Skip,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SortOrder {
     #[serde(rename = "asc")]
@@ -201,6 +288,14 @@ pub enum ViewMode {
     Details,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimelineGrouping {
+    #[serde(rename = "day")]
+    Day,
+    #[serde(rename = "month")]
+    Month,
+}
+
 impl AppConfig {
     /// Load configuration from file
     pub fn load() -> anyhow::Result<Self> {
@@ -234,9 +329,14 @@ impl AppConfig {
 
     /// Get the configuration file path
     pub fn config_path() -> PathBuf {
+        Self::config_dir().join("config.toml")
+    }
+
+    /// Directory holding config.toml and the other per-user settings files
+    pub fn config_dir() -> PathBuf {
         ProjectDirs::from("com", "LightningFiler", "LightningFiler")
-            .map(|dirs| dirs.config_dir().join("config.toml"))
-            .unwrap_or_else(|| PathBuf::from("./config.toml"))
+            .map(|dirs| dirs.config_dir().to_path_buf())
+            .unwrap_or_else(|| PathBuf::from("."))
     }
 }
 
@@ -257,9 +357,9 @@ fn default_keybindings() -> HashMap<String, Vec<String>> {
     kb.insert("nav.home".into(), vec!["Home".into()]);
     kb.insert("nav.end".into(), vec!["End".into()]);
 
-    // Item navigation (Viewer context)
-    kb.insert("nav.next_item".into(), vec!["Right".into(), "l".into()]);
-    kb.insert("nav.prev_item".into(), vec!["Left".into(), "h".into()]);
+    // Item navigation (Left/Right already step images in the viewer through nav.move_*)
+    kb.insert("nav.next_item".into(), vec!["n".into()]);
+    kb.insert("nav.prev_item".into(), vec!["p".into()]);
 
     // Hierarchy navigation
     kb.insert("nav.enter".into(), vec!["Return".into(), "o".into()]);
@@ -267,6 +367,7 @@ fn default_keybindings() -> HashMap<String, Vec<String>> {
     kb.insert("nav.next_sibling".into(), vec!["Ctrl+Right".into(), "Ctrl+l".into()]);
     kb.insert("nav.prev_sibling".into(), vec!["Ctrl+Left".into(), "Ctrl+h".into()]);
     kb.insert("nav.root".into(), vec!["Ctrl+Home".into()]);
+    kb.insert("nav.recent_folders".into(), vec!["Ctrl+o".into()]);
 
     // ========================================
     // View (view.*)
@@ -276,6 +377,7 @@ fn default_keybindings() -> HashMap<String, Vec<String>> {
     kb.insert("view.zoom_in".into(), vec!["Plus".into(), "=".into(), "Ctrl+Up".into()]);
     kb.insert("view.zoom_out".into(), vec!["Minus".into(), "Ctrl+Down".into()]);
     kb.insert("view.zoom_set".into(), vec!["0".into(), "Ctrl+0".into()]);
+    kb.insert("view.actual_pixels".into(), vec!["1".into()]);
 
     // Display
     kb.insert("view.toggle_fullscreen".into(), vec!["F11".into(), "f".into()]);
@@ -283,6 +385,9 @@ fn default_keybindings() -> HashMap<String, Vec<String>> {
     kb.insert("view.spread_mode".into(), vec!["s".into()]);
     kb.insert("view.set_background".into(), vec!["b".into()]);
     kb.insert("view.toggle_info".into(), vec!["i".into()]);
+    kb.insert("view.toggle_histogram".into(), vec!["g".into()]);
+    kb.insert("view.toggle_loupe".into(), vec!["z".into()]);
+    kb.insert("view.histogram_mode".into(), vec!["Shift+g".into()]);
 
     // Smart scroll
     kb.insert("view.smart_scroll_down".into(), vec!["Space".into()]);
@@ -291,6 +396,9 @@ fn default_keybindings() -> HashMap<String, Vec<String>> {
     // Slideshow
     kb.insert("view.slideshow".into(), vec!["F5".into()]);
 
+    // Timeline
+    kb.insert("view.timeline".into(), vec!["t".into()]);
+
     // ========================================
     // File (file.*)
     // ========================================
@@ -302,6 +410,12 @@ fn default_keybindings() -> HashMap<String, Vec<String>> {
     kb.insert("file.paste".into(), vec!["Ctrl+v".into()]);
     kb.insert("file.copy_path".into(), vec!["Ctrl+Shift+c".into()]);
     kb.insert("file.open_explorer".into(), vec!["Ctrl+e".into()]);
+    kb.insert("file.open_terminal".into(), vec!["Ctrl+Shift+t".into()]);
+    kb.insert("file.bookmark_folder".into(), vec!["Ctrl+d".into()]);
+    kb.insert("file.find_duplicates".into(), vec!["Ctrl+Shift+d".into()]);
+    kb.insert("file.toggle_hidden".into(), vec!["Ctrl+Shift+h".into()]);
+    kb.insert("file.flatten_subfolders".into(), vec!["Ctrl+Shift+f".into()]);
+    kb.insert("file.toggle_sort_order".into(), vec!["Shift+r".into()]);
 
     // ========================================
     // Metadata (meta.*)
@@ -316,6 +430,8 @@ fn default_keybindings() -> HashMap<String, Vec<String>> {
     kb.insert("meta.rate:5".into(), vec!["Numpad5".into()]);
     kb.insert("meta.toggle_mark".into(), vec!["m".into()]);
     kb.insert("meta.copy_meta".into(), vec!["`".into()]);
+    kb.insert("meta.paste_meta".into(), vec!["Ctrl+`".into()]);
+    kb.insert("meta.edit_comment".into(), vec!["Shift+c".into()]);
 
     // ========================================
     // App (app.*)
@@ -324,6 +440,8 @@ fn default_keybindings() -> HashMap<String, Vec<String>> {
     kb.insert("app.open_settings".into(), vec!["Ctrl+Comma".into()]);
     kb.insert("app.exit".into(), vec!["Alt+F4".into(), "q".into()]);
     kb.insert("app.search".into(), vec!["Ctrl+f".into(), "/".into()]);
+    kb.insert("app.reload_config".into(), vec!["Ctrl+Shift+r".into()]);
+    kb.insert("app.command_palette".into(), vec!["Ctrl+Shift+p".into()]);
     kb.insert("app.toggle_panel:tree".into(), vec!["F3".into()]);
     kb.insert("app.toggle_panel:info".into(), vec!["F4".into()]);
 
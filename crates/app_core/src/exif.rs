@@ -0,0 +1,317 @@
+//! Minimal EXIF reader for JPEG files
+//!
+//! Only what thumbnail generation and the properties window need:
+//! - Orientation tag (IFD0, 0x0112)
+//! - Embedded JPEG thumbnail (IFD1, 0x0201/0x0202)
+//! - GPS latitude/longitude (GPS IFD via 0x8825)
+//! - DateTimeOriginal (Exif IFD via 0x8769, 0x9003)
+
+use app_fs::TiffReader;
+use chrono::NaiveDateTime;
+use image::DynamicImage;
+use std::fmt;
+use std::ops::Range;
+
+const TAG_ORIENTATION: u16 = 0x0112;
+const TAG_THUMBNAIL_OFFSET: u16 = 0x0201;
+const TAG_THUMBNAIL_LENGTH: u16 = 0x0202;
+const TAG_EXIF_IFD: u16 = 0x8769;
+const TAG_DATE_TIME_ORIGINAL: u16 = 0x9003;
+const TAG_GPS_IFD: u16 = 0x8825;
+const TAG_GPS_LATITUDE_REF: u16 = 0x0001;
+const TAG_GPS_LATITUDE: u16 = 0x0002;
+const TAG_GPS_LONGITUDE_REF: u16 = 0x0003;
+const TAG_GPS_LONGITUDE: u16 = 0x0004;
+
+/// EXIF fields read from a JPEG
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExifInfo {
+    /// EXIF orientation (1-8, 1 = normal)
+    pub orientation: u16,
+    /// Byte range of the embedded JPEG thumbnail within the file data
+    pub thumbnail: Option<Range<usize>>,
+    /// Where the photo was taken, if geotagged
+    pub gps: Option<GpsPosition>,
+    /// Capture time (camera local time, no zone)
+    pub date_taken: Option<NaiveDateTime>,
+}
+
+/// Geotag position in decimal degrees (south and west are negative)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GpsPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl GpsPosition {
+    /// OpenStreetMap link centered on the position
+    pub fn map_url(&self) -> String {
+        format!(
+            "https://www.openstreetmap.org/?mlat={lat:.6}&mlon={lon:.6}#map=16/{lat:.6}/{lon:.6}",
+            lat = self.latitude,
+            lon = self.longitude,
+        )
+    }
+}
+
+impl fmt::Display for GpsPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ns = if self.latitude < 0.0 { 'S' } else { 'N' };
+        let ew = if self.longitude < 0.0 { 'W' } else { 'E' };
+        write!(f, "{:.6}° {}, {:.6}° {}", self.latitude.abs(), ns, self.longitude.abs(), ew)
+    }
+}
+
+/// Read EXIF orientation and embedded thumbnail location from JPEG data
+pub fn read_exif(data: &[u8]) -> Option<ExifInfo> {
+    let tiff = find_exif_segment(data)?;
+    let reader = TiffReader::new(data.get(tiff.clone())?)?;
+
+    let ifd0 = reader.ifd0()?;
+    let mut orientation = 1;
+    let mut gps = None;
+    let mut date_taken = None;
+    for (tag, value_pos) in reader.fields(ifd0)? {
+        match tag {
+            TAG_ORIENTATION => orientation = reader.u16(value_pos).unwrap_or(1),
+            TAG_GPS_IFD => gps = reader.u32(value_pos).and_then(|ifd| read_gps(&reader, ifd as usize)),
+            TAG_EXIF_IFD => date_taken = reader.u32(value_pos).and_then(|ifd| read_date_taken(&reader, ifd as usize)),
+            _ => {}
+        }
+    }
+
+    // IFD1 holds the thumbnail
+    let mut thumbnail = None;
+    let ifd1 = reader.next_ifd(ifd0).unwrap_or(0) as usize;
+    if ifd1 != 0 {
+        let (mut offset, mut length) = (None, None);
+        for (tag, value_pos) in reader.fields(ifd1).into_iter().flatten() {
+            match tag {
+                TAG_THUMBNAIL_OFFSET => offset = reader.u32(value_pos),
+                TAG_THUMBNAIL_LENGTH => length = reader.u32(value_pos),
+                _ => {}
+            }
+        }
+        if let (Some(offset), Some(length)) = (offset, length) {
+            let start = tiff.start + offset as usize;
+            let end = start + length as usize;
+            if end <= tiff.end && data[start..end].starts_with(&[0xFF, 0xD8]) {
+                thumbnail = Some(start..end);
+            }
+        }
+    }
+
+    Some(ExifInfo {
+        orientation: if (1..=8).contains(&orientation) { orientation } else { 1 },
+        thumbnail,
+        gps,
+        date_taken,
+    })
+}
+
+/// DateTimeOriginal ("YYYY:MM:DD HH:MM:SS") from the Exif IFD
+fn read_date_taken(reader: &TiffReader, ifd: usize) -> Option<NaiveDateTime> {
+    let (_, value_pos) = reader.fields(ifd)?
+        .find(|(tag, _)| *tag == TAG_DATE_TIME_ORIGINAL)?;
+    // 20 bytes including the NUL, so the value field is an offset
+    let offset = reader.u32(value_pos)? as usize;
+    let text = std::str::from_utf8(reader.bytes(offset, 19)?).ok()?;
+    NaiveDateTime::parse_from_str(text, "%Y:%m:%d %H:%M:%S").ok()
+}
+
+/// Latitude/longitude from the GPS IFD; None unless both are present
+fn read_gps(reader: &TiffReader, ifd: usize) -> Option<GpsPosition> {
+    let (mut lat, mut lat_ref, mut lon, mut lon_ref) = (None, b'N', None, b'E');
+    for (tag, value_pos) in reader.fields(ifd)? {
+        match tag {
+            // Single-letter ASCII refs fit in the value field
+            TAG_GPS_LATITUDE_REF => lat_ref = reader.u8(value_pos).unwrap_or(b'N'),
+            TAG_GPS_LONGITUDE_REF => lon_ref = reader.u8(value_pos).unwrap_or(b'E'),
+            TAG_GPS_LATITUDE => lat = reader.u32(value_pos).and_then(|pos| reader.degrees(pos as usize)),
+            TAG_GPS_LONGITUDE => lon = reader.u32(value_pos).and_then(|pos| reader.degrees(pos as usize)),
+            _ => {}
+        }
+    }
+
+    let latitude = if lat_ref == b'S' { -lat? } else { lat? };
+    let longitude = if lon_ref == b'W' { -lon? } else { lon? };
+    ((-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude))
+        .then_some(GpsPosition { latitude, longitude })
+}
+
+/// Rotate/flip an image according to its EXIF orientation
+pub fn apply_orientation(img: DynamicImage, orientation: u16) -> DynamicImage {
+    match orientation {
+        2 => img.fliph(),
+        3 => img.rotate180(),
+        4 => img.flipv(),
+        5 => img.rotate90().fliph(),
+        6 => img.rotate90(),
+        7 => img.rotate270().fliph(),
+        8 => img.rotate270(),
+        _ => img,
+    }
+}
+
+/// Locate the TIFF block of the APP1 Exif segment
+fn find_exif_segment(data: &[u8]) -> Option<Range<usize>> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        // Start of scan / end of image: no more metadata
+        if marker == 0xDA || marker == 0xD9 {
+            return None;
+        }
+        let len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        if len < 2 {
+            return None;
+        }
+        let end = (pos + 2 + len).min(data.len());
+        if marker == 0xE1 && data[pos + 4..end].starts_with(b"Exif\0\0") {
+            return Some(pos + 10..end);
+        }
+        pos += 2 + len;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// JPEG with an Exif segment: orientation 6 and a 4-byte fake thumbnail
+    fn sample_jpeg() -> Vec<u8> {
+        let mut tiff = Vec::new();
+        tiff.extend_from_slice(b"II");
+        tiff.extend_from_slice(&42u16.to_le_bytes());
+        tiff.extend_from_slice(&8u32.to_le_bytes());
+        // IFD0 at 8: one entry (orientation)
+        tiff.extend_from_slice(&1u16.to_le_bytes());
+        tiff.extend_from_slice(&TAG_ORIENTATION.to_le_bytes());
+        tiff.extend_from_slice(&3u16.to_le_bytes());
+        tiff.extend_from_slice(&1u32.to_le_bytes());
+        tiff.extend_from_slice(&[6, 0, 0, 0]);
+        // Next IFD at 26
+        tiff.extend_from_slice(&26u32.to_le_bytes());
+        // IFD1 at 26: thumbnail offset/length
+        tiff.extend_from_slice(&2u16.to_le_bytes());
+        for (tag, value) in [(TAG_THUMBNAIL_OFFSET, 56u32), (TAG_THUMBNAIL_LENGTH, 4u32)] {
+            tiff.extend_from_slice(&tag.to_le_bytes());
+            tiff.extend_from_slice(&4u16.to_le_bytes());
+            tiff.extend_from_slice(&1u32.to_le_bytes());
+            tiff.extend_from_slice(&value.to_le_bytes());
+        }
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        // Thumbnail at 56
+        tiff.extend_from_slice(&[0xFF, 0xD8, 0xFF, 0xD9]);
+        jpeg_with_tiff(&tiff)
+    }
+
+    fn jpeg_with_tiff(tiff: &[u8]) -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        data.extend_from_slice(&((tiff.len() + 8) as u16).to_be_bytes());
+        data.extend_from_slice(b"Exif\0\0");
+        data.extend_from_slice(tiff);
+        data.extend_from_slice(&[0xFF, 0xD9]);
+        data
+    }
+
+    fn ifd_entry(tiff: &mut Vec<u8>, tag: u16, kind: u16, count: u32, value: [u8; 4]) {
+        tiff.extend_from_slice(&tag.to_le_bytes());
+        tiff.extend_from_slice(&kind.to_le_bytes());
+        tiff.extend_from_slice(&count.to_le_bytes());
+        tiff.extend_from_slice(&value);
+    }
+
+    #[test]
+    fn test_read_gps() {
+        let mut tiff = Vec::new();
+        tiff.extend_from_slice(b"II");
+        tiff.extend_from_slice(&42u16.to_le_bytes());
+        tiff.extend_from_slice(&8u32.to_le_bytes());
+        // IFD0 at 8: GPS IFD pointer to 26
+        tiff.extend_from_slice(&1u16.to_le_bytes());
+        ifd_entry(&mut tiff, TAG_GPS_IFD, 4, 1, 26u32.to_le_bytes());
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        // GPS IFD at 26: four entries, rationals at 80 and 104
+        tiff.extend_from_slice(&4u16.to_le_bytes());
+        ifd_entry(&mut tiff, TAG_GPS_LATITUDE_REF, 2, 2, *b"S\0\0\0");
+        ifd_entry(&mut tiff, TAG_GPS_LATITUDE, 5, 3, 80u32.to_le_bytes());
+        ifd_entry(&mut tiff, TAG_GPS_LONGITUDE_REF, 2, 2, *b"E\0\0\0");
+        ifd_entry(&mut tiff, TAG_GPS_LONGITUDE, 5, 3, 104u32.to_le_bytes());
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        // 33° 51' 21.56" and 151° 12' 51.48"
+        for (num, den) in [(33u32, 1u32), (51, 1), (2156, 100), (151, 1), (12, 1), (5148, 100)] {
+            tiff.extend_from_slice(&num.to_le_bytes());
+            tiff.extend_from_slice(&den.to_le_bytes());
+        }
+
+        let info = read_exif(&jpeg_with_tiff(&tiff)).unwrap();
+        let gps = info.gps.unwrap();
+        assert!((gps.latitude - -33.855989).abs() < 1e-6);
+        assert!((gps.longitude - 151.2143).abs() < 1e-6);
+        assert_eq!(gps.to_string(), "33.855989° S, 151.214300° E");
+        assert!(gps.map_url().contains("mlat=-33.855989&mlon=151.214300"));
+
+        // No GPS IFD: field omitted
+        assert_eq!(read_exif(&sample_jpeg()).unwrap().gps, None);
+    }
+
+    #[test]
+    fn test_read_date_taken() {
+        let mut tiff = Vec::new();
+        tiff.extend_from_slice(b"MM");
+        tiff.extend_from_slice(&42u16.to_be_bytes());
+        tiff.extend_from_slice(&8u32.to_be_bytes());
+        // IFD0 at 8: Exif IFD pointer to 26
+        tiff.extend_from_slice(&1u16.to_be_bytes());
+        tiff.extend_from_slice(&TAG_EXIF_IFD.to_be_bytes());
+        tiff.extend_from_slice(&4u16.to_be_bytes());
+        tiff.extend_from_slice(&1u32.to_be_bytes());
+        tiff.extend_from_slice(&26u32.to_be_bytes());
+        tiff.extend_from_slice(&0u32.to_be_bytes());
+        // Exif IFD at 26: DateTimeOriginal string at 44
+        tiff.extend_from_slice(&1u16.to_be_bytes());
+        tiff.extend_from_slice(&TAG_DATE_TIME_ORIGINAL.to_be_bytes());
+        tiff.extend_from_slice(&2u16.to_be_bytes());
+        tiff.extend_from_slice(&20u32.to_be_bytes());
+        tiff.extend_from_slice(&44u32.to_be_bytes());
+        tiff.extend_from_slice(&0u32.to_be_bytes());
+        tiff.extend_from_slice(b"2023:07:14 18:05:09\0");
+
+        let info = read_exif(&jpeg_with_tiff(&tiff)).unwrap();
+        let expected = NaiveDateTime::parse_from_str("2023-07-14 18:05:09", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(info.date_taken, Some(expected));
+        assert_eq!(read_exif(&sample_jpeg()).unwrap().date_taken, None);
+    }
+
+    #[test]
+    fn test_read_exif() {
+        let data = sample_jpeg();
+        let info = read_exif(&data).unwrap();
+        assert_eq!(info.orientation, 6);
+        let range = info.thumbnail.unwrap();
+        assert_eq!(&data[range], &[0xFF, 0xD8, 0xFF, 0xD9]);
+    }
+
+    #[test]
+    fn test_no_exif() {
+        assert!(read_exif(&[0xFF, 0xD8, 0xFF, 0xD9]).is_none());
+        assert!(read_exif(b"not a jpeg").is_none());
+    }
+
+    #[test]
+    fn test_apply_orientation() {
+        let img = DynamicImage::new_rgba8(4, 2);
+        let rotated = apply_orientation(img, 6);
+        assert_eq!((rotated.width(), rotated.height()), (2, 4));
+    }
+}
@@ -1,12 +1,17 @@
 //! Image loading and decoding service
 
 use crate::AppError;
+use crate::config::Interpolation;
+use crate::exif;
+use crate::pdf;
 use crate::resource::ImageFormat;
+use crate::tiff_pages::TiffPages;
 use app_fs::UniversalPath;
 use image::{GenericImageView, ImageReader};
 use rayon::prelude::*;
 use std::io::Cursor;
 use std::path::Path;
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use xxhash_rust::xxh3::xxh3_64;
 
@@ -33,6 +38,8 @@ pub struct LoadedImage {
     pub data: Vec<u8>,
     pub format: ImageFormat,
     pub hash: u64,
+    /// Pages in the file (multi-page TIFF); 1 for everything else
+    pub page_count: usize,
 }
 
 impl ImageLoader {
@@ -71,9 +78,11 @@ impl ImageLoader {
         // Read file
         let data = std::fs::read(path.as_path())?;
         let hash = xxh3_64(&data);
+        let data = Arc::new(data);
+        let page_count = TiffPages::parse(data.clone()).map_or(1, |pages| pages.page_count());
 
         // Decode image
-        let reader = ImageReader::new(Cursor::new(&data))
+        let reader = ImageReader::new(Cursor::new(data.as_slice()))
             .with_guessed_format()
             .map_err(|e| AppError::ImageDecode(e.to_string()))?;
 
@@ -103,6 +112,7 @@ impl ImageLoader {
             data: rgba.into_raw(),
             format: ImageFormat::Rgba8,
             hash,
+            page_count,
         })
     }
 }
@@ -113,6 +123,10 @@ impl Default for ImageLoader {
     }
 }
 
+/// Relative position in a clip used for video thumbnails
+#[cfg(feature = "video-thumbnails")]
+const VIDEO_THUMBNAIL_POSITION: f64 = 0.1;
+
 /// Thumbnail generator
 pub struct ThumbnailGenerator {
     size: u32,
@@ -123,20 +137,41 @@ impl ThumbnailGenerator {
         Self { size }
     }
 
-    /// Generate thumbnail for an image file
+    /// Generate thumbnail for an image, video or PDF file
     pub fn generate(&self, path: &Path) -> Result<LoadedImage, AppError> {
+        if is_supported_video(path) {
+            return self.generate_video(path);
+        }
+        if pdf::is_pdf(path) {
+            return self.generate_pdf(path);
+        }
+
         let data = std::fs::read(path)?;
         let hash = xxh3_64(&data);
 
-        let reader = ImageReader::new(Cursor::new(&data))
-            .with_guessed_format()
-            .map_err(|e| AppError::ImageDecode(e.to_string()))?;
+        let exif = exif::read_exif(&data);
+        let orientation = exif.as_ref().map(|e| e.orientation).unwrap_or(1);
 
-        let img = reader.decode()
-            .map_err(|e| AppError::ImageDecode(e.to_string()))?;
+        // Embedded EXIF thumbnail is near-instant; use it when it is large enough
+        let embedded = exif
+            .and_then(|e| e.thumbnail)
+            .and_then(|range| image::load_from_memory(&data[range]).ok())
+            .filter(|thumb| thumb.width().max(thumb.height()) >= self.size * 3 / 4);
+
+        let img = match embedded {
+            Some(img) => img,
+            None => {
+                let reader = ImageReader::new(Cursor::new(&data))
+                    .with_guessed_format()
+                    .map_err(|e| AppError::ImageDecode(e.to_string()))?;
+
+                reader.decode()
+                    .map_err(|e| AppError::ImageDecode(e.to_string()))?
+            }
+        };
 
         // Generate thumbnail
-        let thumb = img.thumbnail(self.size, self.size);
+        let thumb = exif::apply_orientation(img.thumbnail(self.size, self.size), orientation);
         let rgba = thumb.to_rgba8();
         let (width, height) = rgba.dimensions();
 
@@ -147,6 +182,77 @@ impl ThumbnailGenerator {
             data: rgba.into_raw(),
             format: ImageFormat::Rgba8,
             hash,
+            page_count: 1,
+        })
+    }
+
+    /// Extract a representative frame from a video via ffmpeg
+    #[cfg(feature = "video-thumbnails")]
+    fn generate_video(&self, path: &Path) -> Result<LoadedImage, AppError> {
+        let seek = probe_video_duration(path).unwrap_or(0.0) * VIDEO_THUMBNAIL_POSITION;
+        let scale = format!(
+            "scale={0}:{0}:force_original_aspect_ratio=decrease",
+            self.size
+        );
+
+        let output = background_command("ffmpeg")
+            .args(["-v", "error", "-ss", &format!("{:.3}", seek), "-i"])
+            .arg(path)
+            .args(["-frames:v", "1", "-vf", &scale, "-f", "image2pipe", "-vcodec", "png", "-"])
+            .output()?;
+
+        if !output.status.success() || output.stdout.is_empty() {
+            return Err(AppError::ImageDecode(format!(
+                "ffmpeg failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let img = image::load_from_memory(&output.stdout)
+            .map_err(|e| AppError::ImageDecode(e.to_string()))?;
+        let rgba = img.to_rgba8();
+        let (width, height) = rgba.dimensions();
+
+        // Hashing a whole clip is too slow; key videos by path
+        let upath = UniversalPath::new(path);
+        let hash = upath.id();
+
+        Ok(LoadedImage {
+            path: upath,
+            width,
+            height,
+            data: rgba.into_raw(),
+            format: ImageFormat::Rgba8,
+            hash,
+            page_count: 1,
+        })
+    }
+
+    #[cfg(not(feature = "video-thumbnails"))]
+    fn generate_video(&self, path: &Path) -> Result<LoadedImage, AppError> {
+        Err(AppError::UnsupportedFormat(format!(
+            "video thumbnails are not enabled: {}",
+            path.display()
+        )))
+    }
+
+    /// Render the first page of a PDF
+    fn generate_pdf(&self, path: &Path) -> Result<LoadedImage, AppError> {
+        let rgba = pdf::render_page(path, 0, self.size)?.to_rgba8();
+        let (width, height) = rgba.dimensions();
+
+        // Keyed by path like videos; rendering is the expensive part, not reading
+        let upath = UniversalPath::new(path);
+        let hash = upath.id();
+
+        Ok(LoadedImage {
+            path: upath,
+            width,
+            height,
+            data: rgba.into_raw(),
+            format: ImageFormat::Rgba8,
+            hash,
+            page_count: 1,
         })
     }
 
@@ -158,6 +264,32 @@ impl ThumbnailGenerator {
     }
 }
 
+/// Clip duration in seconds via ffprobe
+#[cfg(feature = "video-thumbnails")]
+fn probe_video_duration(path: &Path) -> Option<f64> {
+    let output = background_command("ffprobe")
+        .args(["-v", "error", "-show_entries", "format=duration", "-of", "default=noprint_wrappers=1:nokey=1"])
+        .arg(path)
+        .output()
+        .ok()?;
+
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// Helper tool process (ffmpeg, mutool) without a console window
+#[cfg(any(feature = "video-thumbnails", feature = "pdf"))]
+pub(crate) fn background_command(program: &str) -> std::process::Command {
+    #[allow(unused_mut)]
+    let mut cmd = std::process::Command::new(program);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    cmd
+}
+
 /// Get image dimensions without fully decoding
 pub fn get_image_dimensions(path: &Path) -> Result<(u32, u32), AppError> {
     let reader = ImageReader::open(path)
@@ -169,6 +301,154 @@ pub fn get_image_dimensions(path: &Path) -> Result<(u32, u32), AppError> {
         .map_err(|e| AppError::ImageDecode(e.to_string()))
 }
 
+/// EXIF segment fits in the first 64KB APP1, plus some room for earlier segments
+const EXIF_READ_LIMIT: u64 = 128 * 1024;
+
+/// Read EXIF fields (orientation, GPS) from the head of a JPEG file
+pub fn read_exif_info(path: &Path) -> Option<exif::ExifInfo> {
+    use std::io::Read;
+
+    let mut data = Vec::new();
+    std::fs::File::open(path).ok()?
+        .take(EXIF_READ_LIMIT)
+        .read_to_end(&mut data)
+        .ok()?;
+    exif::read_exif(&data)
+}
+
+/// Raw EXIF block of an image, with the orientation reset
+/// For saving pixels that are already upright under the original metadata.
+pub fn read_exif_block(path: &Path) -> Option<Vec<u8>> {
+    use image::{ImageDecoder, metadata::Orientation};
+
+    let mut decoder = ImageReader::open(path).ok()?
+        .with_guessed_format().ok()?
+        .into_decoder().ok()?;
+    let mut exif = decoder.exif_metadata().ok()??;
+    let _ = Orientation::remove_from_exif_chunk(&mut exif);
+    Some(exif)
+}
+
+/// Formats images can be saved as (file.save_as)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveFormat {
+    Png,
+    Jpeg,
+    /// Always lossless (the encoder has no lossy mode)
+    WebP,
+    Bmp,
+}
+
+impl SaveFormat {
+    pub const ALL: [SaveFormat; 4] = [SaveFormat::Png, SaveFormat::Jpeg, SaveFormat::WebP, SaveFormat::Bmp];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            SaveFormat::Png => "png",
+            SaveFormat::Jpeg => "jpg",
+            SaveFormat::WebP => "webp",
+            SaveFormat::Bmp => "bmp",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SaveFormat::Png => "PNG",
+            SaveFormat::Jpeg => "JPEG",
+            SaveFormat::WebP => "WebP (lossless)",
+            SaveFormat::Bmp => "BMP",
+        }
+    }
+
+    /// Whether `encode_image` uses the quality setting
+    pub fn is_lossy(self) -> bool {
+        self == SaveFormat::Jpeg
+    }
+
+    /// Whether EXIF metadata can be embedded
+    pub fn supports_exif(self) -> bool {
+        self != SaveFormat::Bmp
+    }
+
+    /// Format matching a file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "png" => Some(SaveFormat::Png),
+            "jpg" | "jpeg" => Some(SaveFormat::Jpeg),
+            "webp" => Some(SaveFormat::WebP),
+            "bmp" => Some(SaveFormat::Bmp),
+            _ => None,
+        }
+    }
+}
+
+/// Encode RGBA pixels; `quality` (1-100) applies to lossy formats only.
+/// `exif` is embedded when the format supports it.
+pub fn encode_image(
+    rgba: &image::RgbaImage,
+    format: SaveFormat,
+    quality: u8,
+    exif: Option<Vec<u8>>,
+) -> Result<Vec<u8>, AppError> {
+    use image::codecs::{bmp::BmpEncoder, jpeg::JpegEncoder, png::PngEncoder, webp::WebPEncoder};
+    use image::{ExtendedColorType, ImageEncoder};
+
+    fn write<E: ImageEncoder>(mut encoder: E, pixels: &[u8], (w, h): (u32, u32), color: ExtendedColorType, exif: Option<Vec<u8>>) -> image::ImageResult<()> {
+        if let Some(exif) = exif {
+            if let Err(e) = encoder.set_exif_metadata(exif) {
+                tracing::warn!("EXIF not written: {}", e);
+            }
+        }
+        encoder.write_image(pixels, w, h, color)
+    }
+
+    let mut data = Vec::new();
+    let size = rgba.dimensions();
+    let result = match format {
+        SaveFormat::Png => write(PngEncoder::new(&mut data), rgba.as_raw(), size, ExtendedColorType::Rgba8, exif),
+        SaveFormat::Jpeg => {
+            // JPEG has no alpha channel
+            let rgb = image::DynamicImage::ImageRgba8(rgba.clone()).to_rgb8();
+            let encoder = JpegEncoder::new_with_quality(&mut data, quality.clamp(1, 100));
+            write(encoder, rgb.as_raw(), size, ExtendedColorType::Rgb8, exif)
+        }
+        SaveFormat::WebP => write(WebPEncoder::new_lossless(&mut data), rgba.as_raw(), size, ExtendedColorType::Rgba8, exif),
+        SaveFormat::Bmp => write(BmpEncoder::new(&mut data), rgba.as_raw(), size, ExtendedColorType::Rgba8, None),
+    };
+    result.map_err(|e| AppError::ImageDecode(e.to_string()))?;
+    Ok(data)
+}
+
+/// Resize to exactly `width` x `height` with the filter matching `interpolation`
+pub fn resize_image(rgba: &image::RgbaImage, width: u32, height: u32, interpolation: Interpolation) -> image::RgbaImage {
+    use image::imageops::FilterType;
+
+    let filter = match interpolation {
+        Interpolation::Nearest => FilterType::Nearest,
+        Interpolation::Bilinear => FilterType::Triangle,
+        Interpolation::Lanczos3 => FilterType::Lanczos3,
+    };
+    image::imageops::resize(rgba, width.max(1), height.max(1), filter)
+}
+
+/// Estimated encoded size of a `width` x `height` image, from encoding a small
+/// `sample` of it and scaling by the pixel count
+pub fn estimate_encoded_size(
+    sample: &image::RgbaImage,
+    width: u32,
+    height: u32,
+    format: SaveFormat,
+    quality: u8,
+) -> Option<u64> {
+    let sample_pixels = sample.width() as u64 * sample.height() as u64;
+    if sample_pixels == 0 {
+        return None;
+    }
+    let encoded = encode_image(sample, format, quality, None).ok()?.len() as u64;
+    Some(encoded * (width as u64 * height as u64) / sample_pixels)
+}
+
 /// Check if a file is a supported image format
 pub fn is_supported_image(path: &Path) -> bool {
     path.extension()
@@ -182,6 +462,18 @@ pub fn is_supported_image(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
+/// Check if a file is a video format (thumbnails need the `video-thumbnails` feature)
+pub fn is_supported_video(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| app_fs::VIDEO_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// Check if a file is an image, video or PDF the thumbnail generator accepts
+pub fn is_supported_media(path: &Path) -> bool {
+    is_supported_image(path) || is_supported_video(path) || pdf::is_pdf(path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,4 +486,51 @@ mod tests {
         assert!(!is_supported_image(Path::new("test.txt")));
         assert!(!is_supported_image(Path::new("test.mp4")));
     }
+
+    #[test]
+    fn test_is_supported_media() {
+        assert!(is_supported_media(Path::new("clip.MKV")));
+        assert!(is_supported_media(Path::new("test.jpg")));
+        assert!(is_supported_video(Path::new("clip.webm")));
+        assert!(is_supported_media(Path::new("manual.pdf")));
+        assert!(!is_supported_media(Path::new("test.txt")));
+        // Same list the file browser uses
+        for ext in app_fs::VIDEO_EXTENSIONS {
+            assert!(is_supported_video(Path::new(&format!("clip.{}", ext))));
+        }
+    }
+
+    #[test]
+    fn test_encode_image() {
+        let rgba = image::RgbaImage::from_pixel(4, 3, image::Rgba([200, 40, 10, 255]));
+        // Minimal little-endian TIFF block with no entries
+        let exif = b"II\x2A\0\x08\0\0\0\0\0\0\0\0\0".to_vec();
+
+        for format in SaveFormat::ALL {
+            let data = encode_image(&rgba, format, 90, Some(exif.clone())).unwrap();
+            let decoded = image::load_from_memory(&data).unwrap();
+            assert_eq!(decoded.dimensions(), (4, 3), "{:?}", format);
+            assert_eq!(SaveFormat::from_path(Path::new(&format!("out.{}", format.extension()))), Some(format));
+
+            if format.supports_exif() {
+                let reader = ImageReader::new(Cursor::new(&data)).with_guessed_format().unwrap();
+                let mut decoder = reader.into_decoder().unwrap();
+                assert_eq!(image::ImageDecoder::exif_metadata(&mut decoder).unwrap(), Some(exif.clone()), "{:?}", format);
+            }
+        }
+        assert!(SaveFormat::Jpeg.is_lossy() && !SaveFormat::Png.is_lossy());
+    }
+
+    #[test]
+    fn test_resize_and_estimate() {
+        let rgba = image::RgbaImage::from_fn(64, 32, |x, y| image::Rgba([(x * 4) as u8, (y * 8) as u8, 128, 255]));
+        for interpolation in [Interpolation::Nearest, Interpolation::Bilinear, Interpolation::Lanczos3] {
+            assert_eq!(resize_image(&rgba, 16, 8, interpolation).dimensions(), (16, 8));
+        }
+
+        // Four times the pixels: four times the sample's encoded size
+        let sample_size = encode_image(&rgba, SaveFormat::Jpeg, 80, None).unwrap().len() as u64;
+        assert_eq!(estimate_encoded_size(&rgba, 128, 64, SaveFormat::Jpeg, 80), Some(sample_size * 4));
+        assert!(estimate_encoded_size(&image::RgbaImage::new(0, 0), 10, 10, SaveFormat::Jpeg, 80).is_none());
+    }
 }
@@ -2,8 +2,9 @@
 //!
 //! This crate contains:
 //! - Application state management
-//! - Command system
+//! - Command system and registry
 //! - Configuration
+//! - Session restore
 //! - Error types
 //! - Navigation context
 //! - Resource management
@@ -18,12 +19,22 @@ pub mod resource;
 pub mod i18n;
 pub mod image_loader;
 pub mod thumbnail_manager;
+pub mod exif;
+pub mod batch_rename;
+pub mod batch_convert;
+pub mod pdf;
+pub mod tiff_pages;
+pub mod animation;
+pub mod phash;
+pub mod session;
+pub mod registry;
+pub mod bridge;
 
 pub use state::AppState;
 pub use config::{
     AppConfig, GeneralConfig, ViewerConfig, FilerConfig, NavigationConfig,
     FitMode, Interpolation, SpreadMode, ReadingDirection,
-    SortBy, SortOrder, ViewMode,
+    SortBy, SortOrder, ViewMode, TimelineGrouping, ConflictPolicy, ExternalApp,
 };
 pub use command::{
     Command, CommandId, CommandDispatcher, CommandParams, CommandHandler,
@@ -33,11 +44,19 @@ pub use command::{
     InfoLevel, TransitionMode, PathFormat, LabelColor, CopyTarget,
 };
 // Note: SpreadMode is exported from config module
+pub use session::Session;
+pub use registry::{CommandInfo, command_registry};
+pub use bridge::{BridgeConnection, BridgeHealth, BridgeHealthHandle, BridgeLauncher, BridgeSupervisor, BridgeTimeouts, NamedSharedMemory, ProcessConnection, SharedMemoryReader, spawn_heartbeat};
 pub use error::AppError;
 pub use navigation::{NavigationContext, NavigationState, GridLayout, SelectionState, FileEntry as NavFileEntry};
-pub use resource::ResourceManager;
-pub use image_loader::{ImageLoader, LoadedImage, ThumbnailGenerator, is_supported_image, get_image_dimensions};
-pub use thumbnail_manager::{ThumbnailManager, ThumbnailSize, CacheStats};
+pub use resource::{ResourceManager, LruCache};
+pub use image_loader::{ImageLoader, LoadedImage, ThumbnailGenerator, is_supported_image, is_supported_video, is_supported_media, get_image_dimensions, read_exif_info, SaveFormat, encode_image, read_exif_block, resize_image, estimate_encoded_size};
+pub use exif::{ExifInfo, GpsPosition};
+pub use tiff_pages::TiffPages;
+pub use animation::AnimationFrame;
+pub use batch_rename::{RenameSource, DateSource, PatternError, expand_pattern, uses_date};
+pub use batch_convert::{ConvertOptions, ConvertOutcome};
+pub use thumbnail_manager::{ThumbnailManager, ThumbnailSize, CacheStats, GenerationQueue};
 
 use once_cell::sync::OnceCell;
 
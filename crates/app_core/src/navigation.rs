@@ -44,6 +44,19 @@ pub enum NavigationContext {
         results: Vec<FileEntry>,
         current_index: usize,
     },
+
+    /// Recently viewed files (newest first)
+    Recent {
+        results: Vec<FileEntry>,
+        current_index: usize,
+    },
+
+    /// Files of a folder and its subfolders as one list
+    Flattened {
+        root: UniversalPath,
+        results: Vec<FileEntry>,
+        current_index: usize,
+    },
 }
 
 /// File entry in navigation list
@@ -203,6 +216,8 @@ impl NavigationState {
             NavigationContext::Timeline { results, .. } => results,
             NavigationContext::Archive { entries, .. } => entries,
             NavigationContext::Search { results, .. } => results,
+            NavigationContext::Recent { results, .. } => results,
+            NavigationContext::Flattened { results, .. } => results,
         }
     }
 
@@ -219,6 +234,8 @@ impl NavigationState {
             NavigationContext::Timeline { current_index, .. } => *current_index,
             NavigationContext::Archive { current_index, .. } => *current_index,
             NavigationContext::Search { current_index, .. } => *current_index,
+            NavigationContext::Recent { current_index, .. } => *current_index,
+            NavigationContext::Flattened { current_index, .. } => *current_index,
         }
     }
 
@@ -236,6 +253,8 @@ impl NavigationState {
             NavigationContext::Timeline { current_index, .. } => *current_index = index,
             NavigationContext::Archive { current_index, .. } => *current_index = index,
             NavigationContext::Search { current_index, .. } => *current_index = index,
+            NavigationContext::Recent { current_index, .. } => *current_index = index,
+            NavigationContext::Flattened { current_index, .. } => *current_index = index,
         }
     }
 
@@ -247,41 +266,22 @@ impl NavigationState {
     /// Returns true if position changed
     pub fn move_up(&mut self, amount: usize, select: bool) -> bool {
         let current = self.current_index();
-        let columns = self.grid_layout.columns;
-        let move_by = columns * amount;
+        let columns = self.grid_layout.columns.max(1);
 
-        if current >= move_by {
-            let new_index = current - move_by;
-            self.set_index(new_index);
-            if select {
-                self.selection.select_range(self.selection.anchor.unwrap_or(current), new_index);
-            } else {
-                self.selection.select_single(new_index);
-            }
-            true
-        } else if current > 0 {
-            // Move to first item in column or first item
-            let new_index = current % columns;
-            if new_index != current {
-                self.set_index(new_index);
-                if select {
-                    self.selection.select_range(self.selection.anchor.unwrap_or(current), new_index);
-                } else {
-                    self.selection.select_single(new_index);
-                }
-                true
-            } else {
-                self.set_index(0);
-                if select {
-                    self.selection.select_range(self.selection.anchor.unwrap_or(current), 0);
-                } else {
-                    self.selection.select_single(0);
-                }
-                true
-            }
+        // Stop at the top row, keeping the column
+        let rows = amount.min(current / columns);
+        if rows == 0 {
+            return false;
+        }
+
+        let new_index = current - rows * columns;
+        self.set_index(new_index);
+        if select {
+            self.selection.select_range(self.selection.anchor.unwrap_or(current), new_index);
         } else {
-            false
+            self.selection.select_single(new_index);
         }
+        true
     }
 
     /// Move down in grid (nav.move_down)
@@ -312,14 +312,14 @@ impl NavigationState {
     /// Move left in grid (nav.move_left)
     pub fn move_left(&mut self, amount: usize, select: bool, wrap: bool) -> bool {
         let current = self.current_index();
-        let columns = self.grid_layout.columns;
+        let columns = self.grid_layout.columns.max(1);
         let current_col = current % columns;
 
         let new_index = if current_col >= amount {
             current - amount
         } else if wrap && current >= columns {
-            // Wrap to end of previous row
-            current - current_col + columns - 1 - columns
+            // Wrap onto the end of the previous row(s): plain reading-order movement
+            current.saturating_sub(amount)
         } else if current > 0 {
             // Move to beginning of row or 0
             current - current_col
@@ -605,3 +605,90 @@ impl Default for NavigationState {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A folder of `count` files laid out in `columns` columns, cursor at `index`
+    fn grid(count: usize, columns: usize, index: usize) -> NavigationState {
+        let files = (0..count)
+            .map(|i| FileEntry {
+                path: format!("/photos/{}.jpg", i),
+                name: format!("{}.jpg", i),
+                is_dir: false,
+                size: None,
+                modified: None,
+                thumbnail_hash: None,
+            })
+            .collect();
+        let mut nav = NavigationState::new();
+        nav.context = NavigationContext::PhysicalFolder {
+            path: UniversalPath::new("/photos"),
+            files,
+            current_index: 0,
+        };
+        nav.update_grid_layout(columns, 3);
+        nav.set_index(index);
+        nav
+    }
+
+    #[test]
+    fn test_move_left_wraps_to_previous_row_end() {
+        // 4 columns: index 8 is the first column of the third row
+        let mut nav = grid(12, 4, 8);
+        assert!(nav.move_left(1, false, true));
+        assert_eq!(nav.current_index(), 7);
+
+        // Without wrap the cursor stays in the row
+        let mut nav = grid(12, 4, 8);
+        assert!(!nav.move_left(1, false, false));
+        assert_eq!(nav.current_index(), 8);
+    }
+
+    #[test]
+    fn test_move_left_first_row() {
+        // Nothing before the first row to wrap onto
+        let mut nav = grid(12, 4, 0);
+        assert!(!nav.move_left(1, false, true));
+        assert_eq!(nav.current_index(), 0);
+
+        // A large amount from mid-row stops at the row start
+        let mut nav = grid(12, 4, 2);
+        assert!(nav.move_left(5, false, true));
+        assert_eq!(nav.current_index(), 0);
+    }
+
+    #[test]
+    fn test_move_left_single_column() {
+        let mut nav = grid(5, 1, 3);
+        assert!(nav.move_left(1, false, true));
+        assert_eq!(nav.current_index(), 2);
+
+        // Amount larger than the index clamps instead of underflowing
+        let mut nav = grid(5, 1, 3);
+        assert!(nav.move_left(10, false, true));
+        assert_eq!(nav.current_index(), 0);
+    }
+
+    #[test]
+    fn test_move_up_keeps_column() {
+        // 3 columns, 8 items: the last row (6, 7) is ragged
+        let mut nav = grid(8, 3, 4);
+        assert!(nav.move_up(1, false));
+        assert_eq!(nav.current_index(), 1);
+
+        // Top row: no-op
+        assert!(!nav.move_up(1, false));
+        assert_eq!(nav.current_index(), 1);
+
+        // From the ragged row, a large amount stops in row 0 of the same column
+        let mut nav = grid(8, 3, 7);
+        assert!(nav.move_up(10, false));
+        assert_eq!(nav.current_index(), 1);
+
+        let mut nav = grid(8, 3, 6);
+        assert!(nav.move_up(1, false));
+        assert_eq!(nav.current_index(), 3);
+    }
+}
@@ -0,0 +1,94 @@
+//! PDF pages rendered through MuPDF's `mutool`
+//! Needs the `pdf` feature and `mutool` on PATH; without the feature every
+//! call fails with `UnsupportedFormat`.
+
+use crate::AppError;
+use image::DynamicImage;
+use std::path::Path;
+
+/// Check if a file is a PDF document
+pub fn is_pdf(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("pdf"))
+}
+
+/// Number of pages in the document
+#[cfg(feature = "pdf")]
+pub fn page_count(path: &Path) -> Result<usize, AppError> {
+    let output = crate::image_loader::background_command("mutool")
+        .arg("info")
+        .arg(path)
+        .output()?;
+
+    parse_page_count(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| {
+        AppError::ImageDecode(format!(
+            "mutool info failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    })
+}
+
+/// Render `page` (0-based) scaled to fit a `max_size` square
+#[cfg(feature = "pdf")]
+pub fn render_page(path: &Path, page: usize, max_size: u32) -> Result<DynamicImage, AppError> {
+    let size = max_size.max(1).to_string();
+    let output = crate::image_loader::background_command("mutool")
+        .args(["draw", "-F", "png", "-o", "-", "-w", &size, "-h", &size])
+        .arg(path)
+        .arg((page + 1).to_string())
+        .output()?;
+
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err(AppError::ImageDecode(format!(
+            "mutool draw failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    image::load_from_memory(&output.stdout).map_err(|e| AppError::ImageDecode(e.to_string()))
+}
+
+#[cfg(not(feature = "pdf"))]
+pub fn page_count(path: &Path) -> Result<usize, AppError> {
+    Err(not_enabled(path))
+}
+
+#[cfg(not(feature = "pdf"))]
+pub fn render_page(path: &Path, _page: usize, _max_size: u32) -> Result<DynamicImage, AppError> {
+    Err(not_enabled(path))
+}
+
+#[cfg(not(feature = "pdf"))]
+fn not_enabled(path: &Path) -> AppError {
+    AppError::UnsupportedFormat(format!("PDF support is not enabled: {}", path.display()))
+}
+
+/// Page count from `mutool info` output ("Pages: 12")
+#[cfg(any(feature = "pdf", test))]
+fn parse_page_count(info: &str) -> Option<usize> {
+    info.lines()
+        .find_map(|line| line.trim().strip_prefix("Pages:"))
+        .and_then(|count| count.trim().parse().ok())
+        .filter(|&count| count > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_pdf() {
+        assert!(is_pdf(Path::new("manual.pdf")));
+        assert!(is_pdf(Path::new("SCAN.PDF")));
+        assert!(!is_pdf(Path::new("pdf.txt")));
+    }
+
+    #[test]
+    fn test_parse_page_count() {
+        let info = "sample.pdf:\n\nPDF-1.7\nInfo object (12 0 R):\n<</Producer(x)>>\nPages: 12\n\nRetrieving info from pages 1-12...\n";
+        assert_eq!(parse_page_count(info), Some(12));
+        assert_eq!(parse_page_count("error: cannot open"), None);
+        assert_eq!(parse_page_count("Pages: 0"), None);
+    }
+}
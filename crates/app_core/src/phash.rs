@@ -0,0 +1,97 @@
+//! Perceptual hashing for near-duplicate detection
+//!
+//! dHash: compares adjacent pixels of a 9x8 grayscale downscale, giving a
+//! 64-bit hash that survives resizing and recompression.
+
+use image::{imageops::FilterType, DynamicImage};
+
+/// Compute the 64-bit difference hash of an image
+pub fn dhash(img: &DynamicImage) -> u64 {
+    let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y)[0];
+            let right = small.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | (left > right) as u64;
+        }
+    }
+    hash
+}
+
+/// Number of differing bits between two hashes
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Group hashes whose Hamming distance is within `threshold` (transitively)
+///
+/// Returns groups of indices into `hashes`; singletons are omitted.
+pub fn group_similar(hashes: &[u64], threshold: u32) -> Vec<Vec<usize>> {
+    // Union-find over all pairs
+    let mut parent: Vec<usize> = (0..hashes.len()).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    for i in 0..hashes.len() {
+        for j in i + 1..hashes.len() {
+            if hamming_distance(hashes[i], hashes[j]) <= threshold {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                if a != b {
+                    parent[b] = a;
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_of_root = std::collections::HashMap::new();
+    for i in 0..hashes.len() {
+        let root = find(&mut parent, i);
+        let group = *group_of_root.entry(root).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[group].push(i);
+    }
+
+    groups.retain(|g| g.len() > 1);
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    fn gradient(width: u32, height: u32, flip: bool) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+            let v = ((x * 255 / width) as u8).wrapping_add((y * 40 / height) as u8);
+            let v = if flip { 255 - v } else { v };
+            Rgb([v, v, v])
+        }))
+    }
+
+    #[test]
+    fn test_dhash_scale_invariant() {
+        let big = dhash(&gradient(400, 300, false));
+        let small = dhash(&gradient(100, 75, false));
+        let other = dhash(&gradient(400, 300, true));
+
+        assert!(hamming_distance(big, small) <= 4);
+        assert!(hamming_distance(big, other) > 32);
+    }
+
+    #[test]
+    fn test_group_similar() {
+        let hashes = [0b0000, 0xFFFF_0000, 0b0011, 0xFFFF_0001, 0x1234_5678_9ABC_DEF0];
+        let groups = group_similar(&hashes, 2);
+        assert_eq!(groups, vec![vec![0, 2], vec![1, 3]]);
+    }
+}
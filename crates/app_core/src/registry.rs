@@ -0,0 +1,226 @@
+//! Command registry: display names and default parameters for the command palette
+
+use crate::command::{BackgroundColor, Command, CommandId, CopyTarget, Direction, FlipAxis, LabelColor, PathFormat, ZoomMode};
+use app_fs::WallpaperStyle;
+
+/// A runnable command with a display name
+#[derive(Debug, Clone)]
+pub struct CommandInfo {
+    /// Keybinding name ("meta.rate:3" for bindings with a parameter)
+    pub binding: &'static str,
+    pub label: &'static str,
+    pub command: Command,
+}
+
+fn entry(binding: &'static str, label: &'static str, command: Command) -> CommandInfo {
+    CommandInfo { binding, label, command }
+}
+
+fn plain(id: &'static str, label: &'static str) -> CommandInfo {
+    entry(id, label, Command::new(id))
+}
+
+/// Commands offered by the command palette
+/// Commands that need input (targets, tag names) open their usual dialog or prompt
+pub fn command_registry() -> Vec<CommandInfo> {
+    vec![
+        // Navigation
+        plain(CommandId::NAV_MOVE_UP, "Move Up"),
+        plain(CommandId::NAV_MOVE_DOWN, "Move Down"),
+        plain(CommandId::NAV_MOVE_LEFT, "Move Left"),
+        plain(CommandId::NAV_MOVE_RIGHT, "Move Right"),
+        plain(CommandId::NAV_PAGE_UP, "Page Up"),
+        plain(CommandId::NAV_PAGE_DOWN, "Page Down"),
+        plain(CommandId::NAV_HOME, "Go to First Item"),
+        plain(CommandId::NAV_END, "Go to Last Item"),
+        plain(CommandId::NAV_NEXT_ITEM, "Next Item"),
+        plain(CommandId::NAV_PREV_ITEM, "Previous Item"),
+        plain(CommandId::NAV_NEXT_PAGE, "Next Page"),
+        plain(CommandId::NAV_PREV_PAGE, "Previous Page"),
+        plain(CommandId::NAV_ENTER, "Open"),
+        plain(CommandId::NAV_PARENT, "Go to Parent Folder"),
+        plain(CommandId::NAV_NEXT_SIBLING, "Next Folder"),
+        plain(CommandId::NAV_PREV_SIBLING, "Previous Folder"),
+        plain(CommandId::NAV_ROOT, "Go to Root"),
+        plain(CommandId::NAV_RECENT_FOLDERS, "Recent Folders…"),
+        entry(CommandId::NAV_SCROLL_Y, "Scroll Down", Command::new(CommandId::NAV_SCROLL_Y).with_direction(Direction::Down)),
+        entry("nav.scroll_y:up", "Scroll Up", Command::new(CommandId::NAV_SCROLL_Y).with_direction(Direction::Up)),
+        entry(CommandId::NAV_SCROLL_X, "Scroll Right", Command::new(CommandId::NAV_SCROLL_X).with_direction(Direction::Right)),
+        entry("nav.scroll_x:left", "Scroll Left", Command::new(CommandId::NAV_SCROLL_X).with_direction(Direction::Left)),
+        // View
+        plain(CommandId::VIEW_ZOOM_IN, "Zoom In"),
+        plain(CommandId::VIEW_ZOOM_OUT, "Zoom Out"),
+        entry(CommandId::VIEW_ZOOM_SET, "Fit to Window", Command::new(CommandId::VIEW_ZOOM_SET).with_zoom_mode(ZoomMode::FitWindow)),
+        entry("view.zoom_set:width", "Fit Width", Command::new(CommandId::VIEW_ZOOM_SET).with_zoom_mode(ZoomMode::FitWidth)),
+        entry("view.zoom_set:height", "Fit Height", Command::new(CommandId::VIEW_ZOOM_SET).with_zoom_mode(ZoomMode::FitHeight)),
+        entry("view.zoom_set:original", "Actual Size", Command::new(CommandId::VIEW_ZOOM_SET).with_zoom_mode(ZoomMode::Original)),
+        entry("view.zoom_set:reset", "Reset Zoom", Command::new(CommandId::VIEW_ZOOM_RESET).with_scale(1.0)),
+        plain(CommandId::VIEW_ZOOM_MODE_CYCLE, "Cycle Zoom Mode"),
+        plain(CommandId::VIEW_LOCK_ZOOM, "Lock Zoom"),
+        plain(CommandId::VIEW_ACTUAL_PIXELS, "Actual Pixels (100%)"),
+        entry(CommandId::VIEW_ROTATE, "Rotate Right", Command::new(CommandId::VIEW_ROTATE).with_angle(90)),
+        entry("view.rotate:-90", "Rotate Left", Command::new(CommandId::VIEW_ROTATE).with_angle(-90)),
+        entry(CommandId::VIEW_FLIP, "Flip Horizontal", Command::new(CommandId::VIEW_FLIP).with_axis(FlipAxis::Horizontal)),
+        entry("view.flip:vertical", "Flip Vertical", Command::new(CommandId::VIEW_FLIP).with_axis(FlipAxis::Vertical)),
+        plain(CommandId::VIEW_SPREAD_MODE, "Cycle Spread Mode"),
+        plain(CommandId::VIEW_SET_BACKGROUND, "Cycle Background"),
+        entry("view.set_background:black", "Black Background", Command::new(CommandId::VIEW_SET_BACKGROUND).with_background(BackgroundColor::Black)),
+        entry("view.set_background:check", "Checkerboard Background", Command::new(CommandId::VIEW_SET_BACKGROUND).with_background(BackgroundColor::Check)),
+        plain(CommandId::VIEW_TOGGLE_FULLSCREEN, "Toggle Fullscreen"),
+        plain(CommandId::VIEW_TOGGLE_CHROMELESS, "Toggle Chromeless"),
+        plain(CommandId::VIEW_TOGGLE_INFO, "Toggle Info Overlay"),
+        plain(CommandId::VIEW_TOGGLE_HISTOGRAM, "Toggle Histogram"),
+        plain(CommandId::VIEW_HISTOGRAM_MODE, "Cycle Histogram Mode"),
+        plain(CommandId::VIEW_TOGGLE_LOUPE, "Toggle Loupe"),
+        plain(CommandId::VIEW_TOGGLE_EYEDROPPER, "Toggle Eyedropper"),
+        plain(CommandId::VIEW_ADJUST, "Adjust Brightness/Contrast…"),
+        plain(CommandId::VIEW_GRAYSCALE, "Toggle Grayscale"),
+        plain(CommandId::VIEW_INVERT, "Toggle Invert"),
+        plain(CommandId::VIEW_TOGGLE_TRANSITION, "Toggle Page Transition"),
+        plain(CommandId::VIEW_SLIDESHOW, "Slideshow"),
+        plain(CommandId::VIEW_SPLIT_MODE, "Split View"),
+        plain(CommandId::VIEW_SYNC_SCROLL, "Sync Scroll"),
+        plain(CommandId::VIEW_NEXT_VIEW_AREA, "Split View: Next Pane"),
+        plain(CommandId::VIEW_COPY_VIEW_STATE, "Split View: Copy View to Other Pane"),
+        plain(CommandId::VIEW_QUICK_LOOK, "Quick Look"),
+        plain(CommandId::VIEW_TIMELINE, "Timeline"),
+        plain(CommandId::VIEW_NEXT_FOLDER, "Viewer: Next Folder"),
+        plain(CommandId::VIEW_PREV_FOLDER, "Viewer: Previous Folder"),
+        plain(CommandId::VIEW_PARENT, "Viewer: Back to Browser"),
+        plain(CommandId::VIEW_SMART_SCROLL_DOWN, "Smart Scroll Down"),
+        plain(CommandId::VIEW_SMART_SCROLL_UP, "Smart Scroll Up"),
+        plain(CommandId::VIEW_SCROLL_N_TYPE_DOWN, "N-Type Scroll Down"),
+        plain(CommandId::VIEW_SCROLL_N_TYPE_UP, "N-Type Scroll Up"),
+        plain(CommandId::VIEW_TOGGLE_SNAP, "Toggle Edge Snap"),
+        // File
+        plain(CommandId::FILE_COPY, "Copy"),
+        plain(CommandId::FILE_CUT, "Cut"),
+        plain(CommandId::FILE_PASTE, "Paste"),
+        plain(CommandId::FILE_COPY_IMAGE, "Copy Image"),
+        plain(CommandId::FILE_COPY_PATH, "Copy Path"),
+        entry("file.copy_path:name", "Copy File Name", Command::new(CommandId::FILE_COPY_PATH).with_path_format(PathFormat::Name)),
+        plain(CommandId::FILE_DELETE, "Delete"),
+        plain(CommandId::FILE_RENAME, "Rename"),
+        plain(CommandId::FILE_BATCH_RENAME, "Batch Rename"),
+        plain(CommandId::FILE_ROTATE_SAVE, "Rotate and Save"),
+        entry("file.rotate_save:-90", "Rotate Left and Save", Command::new(CommandId::FILE_ROTATE_SAVE).with_angle(-90)),
+        plain(CommandId::FILE_SAVE_AS, "Save As…"),
+        plain(CommandId::FILE_EXPORT, "Export (Resize)…"),
+        plain(CommandId::FILE_BATCH_CONVERT, "Batch Convert…"),
+        plain(CommandId::FILE_CREATE_DIR, "New Folder"),
+        plain(CommandId::FILE_COPY_TO, "Copy To…"),
+        plain(CommandId::FILE_MOVE_TO, "Move To…"),
+        plain(CommandId::FILE_BOOKMARK_FOLDER, "Bookmark Folder"),
+        plain(CommandId::FILE_FIND_DUPLICATES, "Find Duplicates"),
+        plain(CommandId::FILE_TOGGLE_HIDDEN, "Show/Hide Hidden Files"),
+        plain(CommandId::FILE_FLATTEN_SUBFOLDERS, "Flatten Subfolders"),
+        plain(CommandId::FILE_TOGGLE_SORT_ORDER, "Reverse Sort Order"),
+        plain(CommandId::FILE_OPEN_EXPLORER, "Show in File Manager"),
+        plain(CommandId::FILE_OPEN_WITH, "Open With…"),
+        plain(CommandId::FILE_OPEN_EXTERNAL, "Open in Default App"),
+        plain(CommandId::FILE_OPEN_TERMINAL, "Open Terminal Here"),
+        plain(CommandId::FILE_SET_WALLPAPER, "Set as Wallpaper"),
+        entry("file.set_wallpaper:fit", "Set as Wallpaper (Fit)", Command::new(CommandId::FILE_SET_WALLPAPER).with_wallpaper_style(WallpaperStyle::Fit)),
+        entry("file.set_wallpaper:stretch", "Set as Wallpaper (Stretch)", Command::new(CommandId::FILE_SET_WALLPAPER).with_wallpaper_style(WallpaperStyle::Stretch)),
+        plain(CommandId::FILE_PROPERTIES, "Properties"),
+        // Metadata
+        entry("meta.rate:0", "Clear Rating", Command::new(CommandId::META_RATE).with_value(0)),
+        entry("meta.rate:1", "Rate ★1", Command::new(CommandId::META_RATE).with_value(1)),
+        entry("meta.rate:2", "Rate ★2", Command::new(CommandId::META_RATE).with_value(2)),
+        entry("meta.rate:3", "Rate ★3", Command::new(CommandId::META_RATE).with_value(3)),
+        entry("meta.rate:4", "Rate ★4", Command::new(CommandId::META_RATE).with_value(4)),
+        entry("meta.rate:5", "Rate ★5", Command::new(CommandId::META_RATE).with_value(5)),
+        entry("meta.label:red", "Label Red", Command::new(CommandId::META_LABEL).with_label(LabelColor::Red)),
+        entry("meta.label:blue", "Label Blue", Command::new(CommandId::META_LABEL).with_label(LabelColor::Blue)),
+        entry("meta.label:green", "Label Green", Command::new(CommandId::META_LABEL).with_label(LabelColor::Green)),
+        entry("meta.label:yellow", "Label Yellow", Command::new(CommandId::META_LABEL).with_label(LabelColor::Yellow)),
+        entry("meta.label:purple", "Label Purple", Command::new(CommandId::META_LABEL).with_label(LabelColor::Purple)),
+        entry("meta.label:none", "Clear Label", Command::new(CommandId::META_LABEL).with_label(LabelColor::None)),
+        plain(CommandId::META_EDIT_TAGS, "Edit Tags"),
+        plain(CommandId::META_EDIT_COMMENT, "Edit Comment"),
+        plain(CommandId::META_COPY_META, "Copy Metadata"),
+        entry("meta.copy_meta:rating", "Copy Rating", Command::new(CommandId::META_COPY_META).with_copy_target(CopyTarget::Rating)),
+        entry("meta.copy_meta:tags", "Copy Tags", Command::new(CommandId::META_COPY_META).with_copy_target(CopyTarget::Tags)),
+        plain(CommandId::META_PASTE_META, "Paste Metadata"),
+        plain(CommandId::META_TOGGLE_MARK, "Toggle Mark"),
+        plain(CommandId::META_SELECT_MARKED, "Select Marked"),
+        plain(CommandId::META_EXPORT_MARKS, "Export Marked List"),
+        plain(CommandId::META_EXPORT_SIDECARS, "Export Sidecars (Folder)"),
+        plain(CommandId::META_EXPORT_ALL_SIDECARS, "Export Sidecars (Catalog)"),
+        plain(CommandId::META_IMPORT_SIDECARS, "Import Sidecars"),
+        // App
+        plain(CommandId::APP_SEARCH, "Search"),
+        plain(CommandId::APP_OPEN_SETTINGS, "Settings"),
+        plain(CommandId::APP_RELOAD_CONFIG, "Reload Configuration"),
+        entry("app.toggle_panel:tree", "Toggle Folder Tree", Command::new(CommandId::APP_TOGGLE_PANEL).with_panel("tree")),
+        entry("app.toggle_panel:info", "Toggle Info Panel", Command::new(CommandId::APP_TOGGLE_PANEL).with_panel("info")),
+        plain(CommandId::APP_CLEAR_CACHE, "Clear Cache"),
+        plain(CommandId::APP_EMPTY_TRASH, "Empty Recycle Bin"),
+        plain(CommandId::APP_MINIMIZE, "Minimize"),
+        plain(CommandId::APP_MAXIMIZE, "Maximize"),
+        plain(CommandId::APP_TOPMOST, "Always on Top"),
+        plain(CommandId::APP_OPEN_MANUAL, "Manual"),
+        plain(CommandId::APP_ABOUT, "About"),
+    ]
+}
+
+/// Command ids left out of the palette: they need a parameter the palette cannot
+/// supply (tag names, seek positions, panel ids), or would act on the palette itself
+#[cfg(test)]
+const NOT_IN_PALETTE: &[&str] = &[
+    CommandId::VIEW_PAN,
+    CommandId::VIEW_PAN_TO,
+    CommandId::VIEW_SCROLL_UP,
+    CommandId::VIEW_SCROLL_DOWN,
+    CommandId::VIEW_NEXT_ITEM,
+    CommandId::VIEW_PREV_ITEM,
+    CommandId::VIEW_SEEK,
+    CommandId::VIEW_SLIDESHOW_INTERVAL,
+    CommandId::META_RATE_STEP,
+    CommandId::META_TAG_TOGGLE,
+    CommandId::META_TAG_ADD,
+    CommandId::META_TAG_REMOVE,
+    CommandId::APP_EXIT,
+    CommandId::APP_RESTART,
+    CommandId::APP_NEW_WINDOW,
+    CommandId::APP_FOCUS_PANEL,
+    CommandId::APP_LAYOUT_SAVE,
+    CommandId::APP_LAYOUT_LOAD,
+    CommandId::APP_LAYOUT_RESET,
+    CommandId::APP_COMMAND_PALETTE,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_command_listed() {
+        let registry = command_registry();
+        // Every `pub const NAME: &'static str = "id";` in CommandId
+        let source = include_str!("command.rs");
+        let ids: Vec<&str> = source.lines()
+            .filter_map(|line| line.trim().strip_prefix("pub const "))
+            .filter(|line| line.contains("&'static str"))
+            .filter_map(|line| line.split('"').nth(1))
+            .collect();
+        assert!(ids.len() > 100);
+
+        for id in ids {
+            let listed = registry.iter().any(|c| c.command.id.as_str() == id);
+            assert!(listed != NOT_IN_PALETTE.contains(&id), "{} must be in the palette or NOT_IN_PALETTE", id);
+        }
+    }
+
+    #[test]
+    fn test_bindings_unique() {
+        let registry = command_registry();
+        let mut bindings: Vec<_> = registry.iter().map(|c| c.binding).collect();
+        bindings.sort();
+        bindings.dedup();
+        assert_eq!(bindings.len(), registry.len());
+
+        // The binding name starts with the command id
+        assert!(registry.iter().all(|c| c.binding.starts_with(c.command.id.as_str())));
+    }
+}
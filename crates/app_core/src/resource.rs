@@ -34,14 +34,14 @@ pub enum ImageFormat {
 type LoadResult = Result<Arc<DecodedImage>, String>;
 
 /// Simple LRU cache
-struct LruCache<K, V> {
+pub struct LruCache<K, V> {
     capacity: usize,
     entries: Vec<(K, V, usize)>, // key, value, access_order
     order_counter: usize,
 }
 
 impl<K: Eq + Clone, V> LruCache<K, V> {
-    fn new(capacity: usize) -> Self {
+    pub fn new(capacity: usize) -> Self {
         Self {
             capacity,
             entries: Vec::with_capacity(capacity),
@@ -49,7 +49,7 @@ impl<K: Eq + Clone, V> LruCache<K, V> {
         }
     }
 
-    fn get(&mut self, key: &K) -> Option<&V> {
+    pub fn get(&mut self, key: &K) -> Option<&V> {
         if let Some(idx) = self.entries.iter().position(|(k, _, _)| k == key) {
             self.order_counter += 1;
             self.entries[idx].2 = self.order_counter;
@@ -59,7 +59,7 @@ impl<K: Eq + Clone, V> LruCache<K, V> {
         }
     }
 
-    fn insert(&mut self, key: K, value: V) -> Option<V> {
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
         self.order_counter += 1;
 
         // Check if key exists
@@ -85,7 +85,7 @@ impl<K: Eq + Clone, V> LruCache<K, V> {
         None
     }
 
-    fn remove(&mut self, key: &K) -> Option<V> {
+    pub fn remove(&mut self, key: &K) -> Option<V> {
         if let Some(idx) = self.entries.iter().position(|(k, _, _)| k == key) {
             Some(self.entries.remove(idx).1)
         } else {
@@ -93,14 +93,31 @@ impl<K: Eq + Clone, V> LruCache<K, V> {
         }
     }
 
-    fn clear(&mut self) {
+    pub fn clear(&mut self) {
         self.entries.clear();
         self.order_counter = 0;
     }
 
-    fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         self.entries.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Change the capacity, evicting least-recently-used entries if over it
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        if self.entries.len() > self.capacity {
+            self.entries.sort_by_key(|(_, _, order)| std::cmp::Reverse(*order));
+            self.entries.truncate(self.capacity);
+        }
+    }
 }
 
 impl ResourceManager {
@@ -188,3 +205,24 @@ pub struct CacheStats {
     pub ram_usage: usize,
     pub ram_limit: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut cache = LruCache::new(2);
+        cache.insert(1, "a");
+        cache.insert(2, "b");
+        // Touch 1 so 2 becomes least recently used
+        assert_eq!(cache.get(&1), Some(&"a"));
+        cache.insert(3, "c");
+        assert!(cache.get(&2).is_none());
+        assert_eq!(cache.len(), 2);
+
+        cache.set_capacity(1);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&3), Some(&"c"));
+    }
+}
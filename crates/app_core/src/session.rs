@@ -0,0 +1,97 @@
+//! Session state restored on the next launch
+
+use crate::config::{AppConfig, FitMode};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Where the user left off
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// Folder that was open
+    pub current_path: Option<PathBuf>,
+    /// Selected file's path id (u64 stored as i64; TOML has no unsigned integers)
+    pub selected_id: Option<i64>,
+    /// Browser shown (false = viewer mode)
+    pub show_browser: bool,
+    pub fit_mode: FitMode,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            current_path: None,
+            selected_id: None,
+            show_browser: true,
+            fit_mode: FitMode::FitToWindow,
+        }
+    }
+}
+
+impl Session {
+    /// Load the saved session, if any
+    pub fn load() -> Option<Self> {
+        let path = Self::session_path();
+        let content = std::fs::read_to_string(&path).ok()?;
+        match toml::from_str(&content) {
+            Ok(session) => Some(session),
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable session {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Save the session
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::session_path();
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        std::fs::write(&path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Selected file's path id
+    pub fn selected(&self) -> Option<u64> {
+        self.selected_id.map(|id| id as u64)
+    }
+
+    pub fn set_selected(&mut self, id: Option<u64>) {
+        self.selected_id = id.map(|id| id as i64);
+    }
+
+    /// Get the session file path (next to config.toml)
+    pub fn session_path() -> PathBuf {
+        AppConfig::config_dir().join("session.toml")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut session = Session {
+            current_path: Some(PathBuf::from("/photos/2024")),
+            show_browser: false,
+            fit_mode: FitMode::FitWidth,
+            ..Default::default()
+        };
+        // Hashes above i64::MAX survive the signed encoding
+        session.set_selected(Some(u64::MAX - 1));
+
+        let text = toml::to_string_pretty(&session).unwrap();
+        let loaded: Session = toml::from_str(&text).unwrap();
+        assert_eq!(loaded, session);
+        assert_eq!(loaded.selected(), Some(u64::MAX - 1));
+    }
+
+    #[test]
+    fn test_session_path_next_to_config() {
+        assert_eq!(Session::session_path().parent(), AppConfig::config_path().parent());
+    }
+}
@@ -5,10 +5,12 @@
 //! - Async thumbnail generation
 //! - Memory-based texture cache
 
-use crate::{AppError, ThumbnailGenerator, LoadedImage};
+use crate::{AppError, LruCache, ThumbnailGenerator, LoadedImage};
 use app_db::{ThumbnailCache, CacheKey};
 use app_fs::UniversalPath;
-use std::collections::HashMap;
+use parking_lot::{Condvar, Mutex};
+use std::collections::{HashSet, VecDeque};
+use std::ops::Range;
 use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
@@ -38,6 +40,152 @@ impl ThumbnailSize {
             ThumbnailSize::Large => 512,
         }
     }
+
+    /// Nearest preset for a pixel size (e.g. `FilerConfig::thumbnail_size`)
+    pub fn from_u32(px: u32) -> Self {
+        if px >= 384 {
+            ThumbnailSize::Large
+        } else if px >= 192 {
+            ThumbnailSize::Medium
+        } else {
+            ThumbnailSize::Small
+        }
+    }
+
+    /// Next larger preset (saturates at Large)
+    pub fn larger(self) -> Self {
+        match self {
+            ThumbnailSize::Small => ThumbnailSize::Medium,
+            ThumbnailSize::Medium | ThumbnailSize::Large => ThumbnailSize::Large,
+        }
+    }
+
+    /// Next smaller preset (saturates at Small)
+    pub fn smaller(self) -> Self {
+        match self {
+            ThumbnailSize::Large => ThumbnailSize::Medium,
+            ThumbnailSize::Medium | ThumbnailSize::Small => ThumbnailSize::Small,
+        }
+    }
+}
+
+/// Default number of thumbnails kept in the in-memory cache
+const DEFAULT_MEMORY_ENTRIES: usize = 100;
+
+type MemoryCache = Arc<RwLock<LruCache<(u64, ThumbnailSize), Vec<u8>>>>;
+
+/// Items beyond the visible range (on each side) that still count as near-visible
+const PREFETCH_MARGIN: usize = 32;
+
+/// Called after each batch thumbnail is written to the cache
+pub type ThumbnailNotify = Arc<dyn Fn() + Send + Sync>;
+
+/// Batch generation queue ordered by distance from the visible range
+///
+/// Slots follow listing order (`None` for entries without a thumbnail). Items
+/// in the focus range (plus margin) are taken first; the rest follow in
+/// listing order, so items that scroll away simply lose their priority.
+#[derive(Default)]
+pub struct GenerationQueue {
+    slots: Vec<Option<UniversalPath>>,
+    focus: Range<usize>,
+    /// Every slot below this index has been taken
+    cursor: usize,
+    remaining: usize,
+}
+
+impl GenerationQueue {
+    /// Replace the queue contents (dropping any unfinished batch)
+    pub fn reset(&mut self, slots: Vec<Option<UniversalPath>>) {
+        self.remaining = slots.iter().filter(|s| s.is_some()).count();
+        self.slots = slots;
+        self.cursor = 0;
+    }
+
+    /// Set the range of items currently on screen
+    pub fn set_focus(&mut self, focus: Range<usize>) {
+        self.focus = focus;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.remaining
+    }
+
+    /// Take the next item to generate: visible first, then near-visible, then the rest
+    pub fn pop(&mut self) -> Option<(usize, UniversalPath)> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let len = self.slots.len();
+        let visible = self.focus.start.min(len)..self.focus.end.min(len);
+        let near_start = visible.start.saturating_sub(PREFETCH_MARGIN);
+        let near_end = (visible.end + PREFETCH_MARGIN).min(len);
+        let index = visible.clone()
+            .chain(visible.end..near_end)
+            .chain((near_start..visible.start).rev())
+            .find(|&i| self.slots[i].is_some())
+            .or_else(|| {
+                while self.cursor < len && self.slots[self.cursor].is_none() {
+                    self.cursor += 1;
+                }
+                (self.cursor < len).then_some(self.cursor)
+            })?;
+
+        self.remaining -= 1;
+        self.slots[index].take().map(|path| (index, path))
+    }
+}
+
+/// Queued and in-flight generation work shared by the worker pool
+#[derive(Default)]
+struct WorkState {
+    /// Whole-folder requests, ordered by distance from the viewport
+    batch: GenerationQueue,
+    batch_size: Option<ThumbnailSize>,
+    /// Individual requests (served before the batch)
+    urgent: VecDeque<(UniversalPath, ThumbnailSize)>,
+    /// Keys queued in `urgent` or being generated
+    pending: HashSet<(u64, ThumbnailSize)>,
+    /// Keys that failed to decode (never retried)
+    failed: HashSet<(u64, ThumbnailSize)>,
+    notify: Option<ThumbnailNotify>,
+}
+
+impl WorkState {
+    /// Next job that isn't already in flight or known to fail
+    fn next_job(&mut self) -> Option<(UniversalPath, ThumbnailSize)> {
+        if let Some(job) = self.urgent.pop_front() {
+            return Some(job);
+        }
+        let size = self.batch_size?;
+        while let Some((_, path)) = self.batch.pop() {
+            let key = (path.id(), size);
+            if !self.failed.contains(&key) && self.pending.insert(key) {
+                return Some((path, size));
+            }
+        }
+        None
+    }
+
+    fn queued(&self) -> usize {
+        self.urgent.len() + self.batch.len()
+    }
+}
+
+#[derive(Default)]
+struct WorkQueue {
+    state: Mutex<WorkState>,
+    ready: Condvar,
+}
+
+/// Default worker count: one per CPU
+pub fn default_worker_count() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4)
 }
 
 /// Thumbnail request
@@ -55,18 +203,22 @@ pub struct ThumbnailManager {
     cache: Arc<ThumbnailCache>,
 
     /// In-memory cache for recently loaded thumbnails
-    memory_cache: Arc<RwLock<HashMap<(u64, ThumbnailSize), Vec<u8>>>>,
+    memory_cache: MemoryCache,
 
     /// Channel for thumbnail generation requests
     request_tx: mpsc::UnboundedSender<ThumbnailRequest>,
+
+    /// Deduplicated queue served by the generation worker pool
+    work: Arc<WorkQueue>,
 }
 
 impl ThumbnailManager {
-    /// Create a new thumbnail manager
-    pub fn new(cache: Arc<ThumbnailCache>) -> Self {
+    /// Create a new thumbnail manager with `workers` generation threads
+    /// (0 = one per CPU)
+    pub fn new(cache: Arc<ThumbnailCache>, workers: usize) -> Self {
         let (request_tx, mut request_rx) = mpsc::unbounded_channel::<ThumbnailRequest>();
         let cache_clone = cache.clone();
-        let memory_cache = Arc::new(RwLock::new(HashMap::new()));
+        let memory_cache = Arc::new(RwLock::new(LruCache::new(DEFAULT_MEMORY_ENTRIES)));
         let memory_cache_clone = memory_cache.clone();
 
         // Spawn worker thread for thumbnail generation
@@ -82,10 +234,118 @@ impl ThumbnailManager {
             }
         });
 
+        let work = Arc::new(WorkQueue::default());
+        let workers = if workers == 0 { default_worker_count() } else { workers };
+        for i in 0..workers {
+            let work = work.clone();
+            let cache = cache.clone();
+            let spawned = std::thread::Builder::new()
+                .name(format!("thumbnail-{}", i))
+                .spawn(move || Self::run_worker(&work, &cache));
+            if let Err(e) = spawned {
+                tracing::error!("Failed to spawn thumbnail worker: {}", e);
+            }
+        }
+
         Self {
             cache,
             memory_cache,
             request_tx,
+            work,
+        }
+    }
+
+    /// Queue thumbnails for a folder listing (`None` slots are skipped),
+    /// replacing any batch still in progress
+    pub fn enqueue_batch(
+        &self,
+        slots: Vec<Option<UniversalPath>>,
+        size: ThumbnailSize,
+        notify: ThumbnailNotify,
+    ) {
+        let mut state = self.work.state.lock();
+        state.batch.reset(slots);
+        state.batch_size = Some(size);
+        state.notify = Some(notify);
+        self.work.ready.notify_all();
+    }
+
+    /// Queue a single thumbnail ahead of the batch.
+    /// Ignored if the same thumbnail is already queued, in flight or failed.
+    pub fn request(&self, path: UniversalPath, size: ThumbnailSize, notify: ThumbnailNotify) {
+        let key = (path.id(), size);
+        let mut state = self.work.state.lock();
+        if state.failed.contains(&key) || !state.pending.insert(key) {
+            return;
+        }
+        state.urgent.push_back((path, size));
+        state.notify = Some(notify);
+        self.work.ready.notify_one();
+    }
+
+    /// Whether generation already failed for this thumbnail
+    pub fn has_failed(&self, path: &UniversalPath, size: ThumbnailSize) -> bool {
+        self.work.state.lock().failed.contains(&(path.id(), size))
+    }
+
+    /// Report the items currently on screen so they are generated first
+    pub fn set_visible_range(&self, range: Range<usize>) {
+        self.work.state.lock().batch.set_focus(range);
+    }
+
+    /// Drop all queued requests (e.g. when navigating away); in-flight jobs finish
+    pub fn clear_queue(&self) {
+        let mut state = self.work.state.lock();
+        state.batch.reset(Vec::new());
+        let urgent: Vec<_> = state.urgent.drain(..).collect();
+        for (path, size) in urgent {
+            state.pending.remove(&(path.id(), size));
+        }
+    }
+
+    /// Number of thumbnails waiting for a worker
+    pub fn queue_depth(&self) -> usize {
+        self.work.state.lock().queued()
+    }
+
+    /// Pool worker: generates queued thumbnails one at a time, highest priority first
+    fn run_worker(work: &WorkQueue, cache: &ThumbnailCache) {
+        loop {
+            let (path, size, notify) = {
+                let mut state = work.state.lock();
+                loop {
+                    if let Some((path, size)) = state.next_job() {
+                        break (path, size, state.notify.clone());
+                    }
+                    work.ready.wait(&mut state);
+                }
+            };
+
+            // Path-based hash with the preset dimensions matches get_cached_sync
+            let key = (path.id(), size);
+            let (width, height) = size.to_dimensions();
+            let cache_key = CacheKey::new(key.0, width, height);
+            let mut failed = false;
+            if !cache.exists(cache_key).unwrap_or(false) {
+                match ThumbnailGenerator::new(size.to_u32()).generate(path.as_path()) {
+                    Ok(loaded) => {
+                        let _ = cache.put(cache_key, &loaded.data);
+                        if let Some(notify) = notify {
+                            notify();
+                        }
+                    }
+                    Err(e) => {
+                        tracing::debug!("Thumbnail generation failed for {}: {}", path, e);
+                        failed = true;
+                    }
+                }
+            }
+
+            let mut state = work.state.lock();
+            state.pending.remove(&key);
+            if failed {
+                state.failed.insert(key);
+            }
         }
     }
 
@@ -102,8 +362,8 @@ impl ThumbnailManager {
 
         // Check memory cache first
         {
-            let cache_read = self.memory_cache.read().await;
-            if let Some(data) = cache_read.get(&(hash, size)) {
+            let mut cache_write = self.memory_cache.write().await;
+            if let Some(data) = cache_write.get(&(hash, size)) {
                 let (width, height) = size.to_dimensions();
                 return Ok(LoadedImage {
                     path: path.clone(),
@@ -112,6 +372,7 @@ impl ThumbnailManager {
                     data: data.clone(),
                     format: crate::resource::ImageFormat::Rgba8,
                     hash,
+                    page_count: 1,
                 });
             }
         }
@@ -132,6 +393,7 @@ impl ThumbnailManager {
                 data: cached_data,
                 format: crate::resource::ImageFormat::Rgba8,
                 hash,
+                page_count: 1,
             });
         }
 
@@ -151,7 +413,7 @@ impl ThumbnailManager {
         path: &UniversalPath,
         size: ThumbnailSize,
         cache: &ThumbnailCache,
-        memory_cache: &Arc<RwLock<HashMap<(u64, ThumbnailSize), Vec<u8>>>>,
+        memory_cache: &MemoryCache,
     ) -> Result<LoadedImage, AppError> {
         tracing::debug!("Generating thumbnail: {} ({:?})", path, size);
 
@@ -163,22 +425,10 @@ impl ThumbnailManager {
         let cache_key = CacheKey::new(loaded.hash, width, height);
         cache.put(cache_key, &loaded.data)?;
 
-        // Store in memory cache
+        // Store in memory cache (evicts least-recently-used when full)
         let mut mem_cache = memory_cache.blocking_write();
         mem_cache.insert((loaded.hash, size), loaded.data.clone());
 
-        // Limit memory cache size (keep ~100 thumbnails)
-        if mem_cache.len() > 100 {
-            // Note: HashMap doesn't preserve insertion order, so this removes
-            // arbitrary entries rather than true LRU. For proper LRU behavior,
-            // consider using `indexmap::IndexMap` or `lru` crate.
-            // Current implementation is a simple size-based eviction.
-            let keys_to_remove: Vec<_> = mem_cache.keys().take(20).cloned().collect();
-            for key in keys_to_remove {
-                mem_cache.remove(&key);
-            }
-        }
-
         Ok(loaded)
     }
 
@@ -201,6 +451,7 @@ impl ThumbnailManager {
             data: cached_data,
             format: crate::resource::ImageFormat::Rgba8,
             hash: path_hash,
+            page_count: 1,
         })
     }
 
@@ -221,6 +472,36 @@ impl ThumbnailManager {
         cache_write.clear();
     }
 
+    /// Evict least-recently-used thumbnails when over budget
+    /// (memory: entry count, disk: bytes; 0 disables the disk cap)
+    pub fn prune(&self, max_memory_entries: usize, max_disk_bytes: u64) -> CacheStats {
+        let memory_entries = match self.memory_cache.try_write() {
+            Ok(mut mem_cache) => {
+                mem_cache.set_capacity(max_memory_entries);
+                mem_cache.len()
+            }
+            Err(_) => 0,
+        };
+
+        let mut stats = CacheStats {
+            memory_entries,
+            disk_size_bytes: self.cache.approximate_size(),
+            queue_depth: self.queue_depth(),
+        };
+
+        if max_disk_bytes > 0 && stats.disk_size_bytes > max_disk_bytes {
+            match self.cache.prune_to(max_disk_bytes) {
+                Ok(removed) => {
+                    tracing::debug!("Pruned {} thumbnails from disk cache", removed);
+                    stats.disk_size_bytes = self.cache.approximate_size();
+                }
+                Err(e) => tracing::warn!("Thumbnail cache prune failed: {}", e),
+            }
+        }
+
+        stats
+    }
+
     /// Get cache statistics
     pub async fn cache_stats(&self) -> CacheStats {
         let memory_size = self.memory_cache.read().await.len();
@@ -229,6 +510,7 @@ impl ThumbnailManager {
         CacheStats {
             memory_entries: memory_size,
             disk_size_bytes: disk_size,
+            queue_depth: self.queue_depth(),
         }
     }
 }
@@ -238,4 +520,71 @@ impl ThumbnailManager {
 pub struct CacheStats {
     pub memory_entries: usize,
     pub disk_size_bytes: u64,
+    /// Thumbnails waiting for a generation worker
+    pub queue_depth: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_size_presets() {
+        assert_eq!(ThumbnailSize::from_u32(128), ThumbnailSize::Small);
+        assert_eq!(ThumbnailSize::from_u32(256), ThumbnailSize::Medium);
+        assert_eq!(ThumbnailSize::from_u32(1000), ThumbnailSize::Large);
+        assert_eq!(ThumbnailSize::Small.larger().larger().larger(), ThumbnailSize::Large);
+        assert_eq!(ThumbnailSize::Large.smaller(), ThumbnailSize::Medium);
+        assert_eq!(ThumbnailSize::Small.smaller(), ThumbnailSize::Small);
+    }
+
+    #[test]
+    fn test_generation_queue_priority() {
+        let slots: Vec<_> = (0..200)
+            .map(|i| (i % 10 != 0).then(|| UniversalPath::new(format!("/img/{i}.png"))))
+            .collect();
+        let mut queue = GenerationQueue::default();
+        queue.reset(slots);
+        assert_eq!(queue.len(), 180);
+
+        // Visible items come first, in order, skipping empty slots
+        queue.set_focus(100..103);
+        let first: Vec<_> = (0..3).map(|_| queue.pop().unwrap().0).collect();
+        assert_eq!(first, vec![101, 102, 103]);
+
+        // Scrolling back to the top moves those items ahead again
+        queue.set_focus(0..2);
+        assert_eq!(queue.pop().unwrap().0, 1);
+        assert_eq!(queue.pop().unwrap().0, 2);
+
+        // Everything is eventually generated exactly once
+        let mut rest = 5;
+        while queue.pop().is_some() {
+            rest += 1;
+        }
+        assert_eq!(rest, 180);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_work_state_dedup() {
+        let path = UniversalPath::new("/img/a.png");
+        let mut state = WorkState::default();
+        state.batch.reset(vec![Some(path.clone()), Some(UniversalPath::new("/img/b.png"))]);
+        state.batch_size = Some(ThumbnailSize::Small);
+        assert_eq!(state.queued(), 2);
+
+        let (first, size) = state.next_job().unwrap();
+        assert_eq!(first.id(), path.id());
+
+        // A duplicate of an in-flight job is skipped
+        state.batch.reset(vec![Some(path.clone())]);
+        assert!(state.next_job().is_none());
+
+        // Once done (or failed) it is not handed out again
+        state.pending.remove(&(path.id(), size));
+        state.failed.insert((path.id(), size));
+        state.batch.reset(vec![Some(path)]);
+        assert!(state.next_job().is_none());
+    }
 }
@@ -0,0 +1,214 @@
+//! Multi-page TIFF support
+//! Pages are the IFDs chained from the header. Page N is decoded by serving
+//! the file with the header's first-IFD offset pointed at IFD N, so `image`
+//! handles every pixel format and compression it already supports.
+
+use crate::AppError;
+use image::{DynamicImage, ImageFormat, ImageReader};
+use std::collections::HashSet;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Upper bound on pages, in case of a corrupt IFD chain
+const MAX_PAGES: usize = 10_000;
+
+/// Check if a file is a TIFF image
+pub fn is_tiff(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("tif") || e.eq_ignore_ascii_case("tiff"))
+}
+
+/// A TIFF file held in memory with the offset of each page's IFD
+#[derive(Debug, Clone)]
+pub struct TiffPages {
+    data: Arc<Vec<u8>>,
+    ifds: Vec<u64>,
+    little_endian: bool,
+    big_tiff: bool,
+}
+
+impl TiffPages {
+    /// Walk the IFD chain; None if `data` is not a TIFF
+    pub fn parse(data: Arc<Vec<u8>>) -> Option<Self> {
+        let little_endian = match data.get(0..2)? {
+            b"II" => true,
+            b"MM" => false,
+            _ => return None,
+        };
+        let mut pages = Self { data, ifds: Vec::new(), little_endian, big_tiff: false };
+        let (first, big_tiff) = match pages.read_u16(2)? {
+            42 => (pages.read_u32(4)? as u64, false),
+            43 => (pages.read_u64(8)?, true),
+            _ => return None,
+        };
+        pages.big_tiff = big_tiff;
+
+        let mut seen = HashSet::new();
+        let mut next = first;
+        while next != 0 && pages.ifds.len() < MAX_PAGES && seen.insert(next) {
+            pages.ifds.push(next);
+            match pages.next_ifd(next as usize) {
+                Some(offset) => next = offset,
+                None => break,
+            }
+        }
+        (!pages.ifds.is_empty()).then_some(pages)
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.ifds.len()
+    }
+
+    /// Decode page `page` (0-based)
+    pub fn decode_page(&self, page: usize) -> Result<DynamicImage, AppError> {
+        let ifd = *self.ifds.get(page).ok_or_else(|| {
+            AppError::ImageDecode(format!("page {} out of range (1-{})", page + 1, self.ifds.len()))
+        })?;
+
+        let reader = PageReader::new(self.data.clone(), self.header_for(ifd));
+        ImageReader::with_format(BufReader::new(reader), ImageFormat::Tiff)
+            .decode()
+            .map_err(|e| AppError::ImageDecode(e.to_string()))
+    }
+
+    /// File header with the first-IFD offset replaced by `ifd`
+    fn header_for(&self, ifd: u64) -> Vec<u8> {
+        let len = if self.big_tiff { 16 } else { 8 };
+        let mut header = self.data[..len.min(self.data.len())].to_vec();
+        let offset = match (self.big_tiff, self.little_endian) {
+            (false, true) => (ifd as u32).to_le_bytes().to_vec(),
+            (false, false) => (ifd as u32).to_be_bytes().to_vec(),
+            (true, true) => ifd.to_le_bytes().to_vec(),
+            (true, false) => ifd.to_be_bytes().to_vec(),
+        };
+        let start = len - offset.len();
+        header[start..].copy_from_slice(&offset);
+        header
+    }
+
+    /// Offset of the IFD following the one at `ifd` (0 = last)
+    fn next_ifd(&self, ifd: usize) -> Option<u64> {
+        if self.big_tiff {
+            let count = self.read_u64(ifd)? as usize;
+            self.read_u64(ifd.checked_add(8)?.checked_add(count.checked_mul(20)?)?)
+        } else {
+            let count = self.read_u16(ifd)? as usize;
+            self.read_u32(ifd + 2 + count * 12).map(u64::from)
+        }
+    }
+
+    fn bytes<const N: usize>(&self, pos: usize) -> Option<[u8; N]> {
+        self.data.get(pos..pos.checked_add(N)?)?.try_into().ok()
+    }
+
+    fn read_u16(&self, pos: usize) -> Option<u16> {
+        let b = self.bytes(pos)?;
+        Some(if self.little_endian { u16::from_le_bytes(b) } else { u16::from_be_bytes(b) })
+    }
+
+    fn read_u32(&self, pos: usize) -> Option<u32> {
+        let b = self.bytes(pos)?;
+        Some(if self.little_endian { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) })
+    }
+
+    fn read_u64(&self, pos: usize) -> Option<u64> {
+        let b = self.bytes(pos)?;
+        Some(if self.little_endian { u64::from_le_bytes(b) } else { u64::from_be_bytes(b) })
+    }
+}
+
+/// Reads shared TIFF data with its header swapped out
+struct PageReader {
+    data: Arc<Vec<u8>>,
+    header: Vec<u8>,
+    pos: u64,
+}
+
+impl PageReader {
+    fn new(data: Arc<Vec<u8>>, header: Vec<u8>) -> Self {
+        Self { data, header, pos: 0 }
+    }
+}
+
+impl Read for PageReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let pos = self.pos as usize;
+        let source = if pos < self.header.len() {
+            &self.header[pos..]
+        } else {
+            self.data.get(pos..).unwrap_or_default()
+        };
+        let n = source.len().min(buf.len());
+        buf[..n].copy_from_slice(&source[..n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for PageReader {
+    fn seek(&mut self, from: SeekFrom) -> io::Result<u64> {
+        let pos = match from {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(delta) => (self.data.len() as u64).checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+        };
+        self.pos = pos.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "seek before start"))?;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Little-endian TIFF with one 1x1 8-bit gray page per value in `pixels`
+    fn multi_page_tiff(pixels: &[u8]) -> Vec<u8> {
+        const ENTRIES: u16 = 8;
+        let ifd_len = 2 + ENTRIES as usize * 12 + 4;
+        let mut data = b"II".to_vec();
+        data.extend_from_slice(&42u16.to_le_bytes());
+        data.extend_from_slice(&8u32.to_le_bytes());
+
+        for (i, &pixel) in pixels.iter().enumerate() {
+            let ifd = data.len();
+            let strip = (ifd + ifd_len) as u32;
+            let next = if i + 1 < pixels.len() { strip + 2 } else { 0 };
+            data.extend_from_slice(&ENTRIES.to_le_bytes());
+            for (tag, kind, value) in [
+                (256u16, 3u16, 1u32), // ImageWidth
+                (257, 3, 1),          // ImageLength
+                (258, 3, 8),          // BitsPerSample
+                (259, 3, 1),          // Compression: none
+                (262, 3, 1),          // Photometric: BlackIsZero
+                (273, 4, strip),      // StripOffsets
+                (278, 3, 1),          // RowsPerStrip
+                (279, 4, 1),          // StripByteCounts
+            ] {
+                data.extend_from_slice(&tag.to_le_bytes());
+                data.extend_from_slice(&kind.to_le_bytes());
+                data.extend_from_slice(&1u32.to_le_bytes());
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+            data.extend_from_slice(&next.to_le_bytes());
+            // Pixel plus a pad byte so IFDs stay word-aligned
+            data.extend_from_slice(&[pixel, 0]);
+        }
+        data
+    }
+
+    #[test]
+    fn test_pages() {
+        let pages = TiffPages::parse(Arc::new(multi_page_tiff(&[10, 200, 90]))).unwrap();
+        assert_eq!(pages.page_count(), 3);
+        for (page, expected) in [(1, 200), (0, 10), (2, 90)] {
+            let img = pages.decode_page(page).unwrap().to_luma8();
+            assert_eq!(img.get_pixel(0, 0).0[0], expected, "page {}", page);
+        }
+        assert!(pages.decode_page(3).is_err());
+
+        assert!(TiffPages::parse(Arc::new(b"\x89PNG".to_vec())).is_none());
+        assert!(is_tiff(Path::new("scan.TIF")) && !is_tiff(Path::new("scan.png")));
+    }
+}
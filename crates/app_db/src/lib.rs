@@ -2,17 +2,20 @@
 //!
 //! Provides:
 //! - SQLite for metadata storage (files, tags, history)
+//! - Metadata sidecar export/import
 //! - RocksDB for KVS cache (thumbnails, hashes)
 
 mod sqlite;
 mod rocksdb_cache;
 mod schema;
 mod pool;
+mod sidecar;
 
-pub use sqlite::{MetadataDb, FileRecord, TagRecord, FileTagRecord};
+pub use sqlite::{MetadataDb, FileRecord, TagRecord, FileTagRecord, BookmarkRecord, FolderSort};
 pub use rocksdb_cache::{ThumbnailCache, CacheKey};
 pub use pool::DbPool;
 pub use schema::migrate;
+pub use sidecar::{SidecarExport, SidecarMetadata, SIDECAR_SUFFIX, sidecar_path, sidecar_target};
 
 use std::path::PathBuf;
 use directories::ProjectDirs;
@@ -1,9 +1,18 @@
 //! RocksDB-based thumbnail and hash cache
 
 use crate::Result;
-use rocksdb::{Options, DB};
+use rocksdb::{IteratorMode, Options, DB};
 use std::path::Path;
 
+/// Key prefix for last-access timestamps of thumbnails
+const ATIME_PREFIX: &[u8] = b"atime:";
+
+/// Key prefix for perceptual hashes
+const PHASH_PREFIX: &[u8] = b"phash:";
+
+/// Reads only rewrite a thumbnail's last-access time once it is this old (seconds)
+const ATIME_RESOLUTION: u64 = 60 * 60;
+
 /// Key for thumbnail cache
 #[derive(Debug, Clone, Copy)]
 pub struct CacheKey {
@@ -67,20 +76,78 @@ impl ThumbnailCache {
     /// Store a thumbnail
     pub fn put(&self, key: CacheKey, data: &[u8]) -> Result<()> {
         self.db.put(key.to_bytes(), data)?;
+        self.touch(key)?;
         Ok(())
     }
 
-    /// Retrieve a thumbnail
+    /// Retrieve a thumbnail (updates its last-access time at `ATIME_RESOLUTION`)
     pub fn get(&self, key: CacheKey) -> Result<Option<Vec<u8>>> {
-        Ok(self.db.get(key.to_bytes())?)
+        let data = self.db.get(key.to_bytes())?;
+        if data.is_some() && now_secs().saturating_sub(self.last_access(key)?) >= ATIME_RESOLUTION {
+            self.touch(key)?;
+        }
+        Ok(data)
     }
 
     /// Delete a thumbnail
     pub fn delete(&self, key: CacheKey) -> Result<()> {
         self.db.delete(key.to_bytes())?;
+        self.db.delete(atime_key(key))?;
+        Ok(())
+    }
+
+    /// Record the current time as the thumbnail's last access
+    fn touch(&self, key: CacheKey) -> Result<()> {
+        self.set_last_access(key, now_secs())
+    }
+
+    fn set_last_access(&self, key: CacheKey, secs: u64) -> Result<()> {
+        self.db.put(atime_key(key), secs.to_be_bytes())?;
         Ok(())
     }
 
+    /// Last access time (unix seconds); 0 if never recorded
+    fn last_access(&self, key: CacheKey) -> Result<u64> {
+        match self.db.get(atime_key(key))? {
+            Some(bytes) if bytes.len() == 8 => {
+                Ok(u64::from_be_bytes(bytes[..8].try_into().unwrap()))
+            }
+            _ => Ok(0),
+        }
+    }
+
+    /// Evict least-recently-used thumbnails until their total size is within `max_bytes`
+    /// Returns the number of thumbnails removed
+    pub fn prune_to(&self, max_bytes: u64) -> Result<usize> {
+        let mut entries = Vec::new();
+        let mut total = 0u64;
+
+        for item in self.db.iterator(IteratorMode::Start) {
+            let (key, value) = item?;
+            // Thumbnail keys are exactly 16 bytes; skip hash/atime records
+            let Some(cache_key) = CacheKey::from_bytes(&key) else {
+                continue;
+            };
+            let size = value.len() as u64;
+            total += size;
+            entries.push((self.last_access(cache_key)?, cache_key, size));
+        }
+
+        entries.sort_by_key(|(atime, _, _)| *atime);
+
+        let mut removed = 0;
+        for (_, key, size) in entries {
+            if total <= max_bytes {
+                break;
+            }
+            self.delete(key)?;
+            total -= size;
+            removed += 1;
+        }
+
+        Ok(removed)
+    }
+
     /// Delete all thumbnails for a file hash
     pub fn delete_by_hash(&self, hash: u64) -> Result<usize> {
         let prefix = hash.to_be_bytes();
@@ -89,12 +156,13 @@ impl ThumbnailCache {
         let iter = self.db.prefix_iterator(&prefix);
         for item in iter {
             let (key, _) = item?;
-            if key.starts_with(&prefix) {
-                self.db.delete(&key)?;
-                count += 1;
-            } else {
+            if !key.starts_with(&prefix) {
                 break;
             }
+            if let Some(cache_key) = CacheKey::from_bytes(&key) {
+                self.delete(cache_key)?;
+                count += 1;
+            }
         }
 
         Ok(count)
@@ -138,6 +206,46 @@ impl ThumbnailCache {
             _ => Ok(None),
         }
     }
+
+    /// Store a perceptual hash for a file, tagged with its modification time
+    pub fn put_phash(&self, path_hash: u64, modified: i64, phash: u64) -> Result<()> {
+        let mut value = [0u8; 16];
+        value[0..8].copy_from_slice(&modified.to_be_bytes());
+        value[8..16].copy_from_slice(&phash.to_be_bytes());
+        self.db.put(phash_key(path_hash), value)?;
+        Ok(())
+    }
+
+    /// Get a perceptual hash; `None` if missing or the file changed since it was stored
+    pub fn get_phash(&self, path_hash: u64, modified: i64) -> Result<Option<u64>> {
+        match self.db.get(phash_key(path_hash))? {
+            Some(bytes) if bytes.len() == 16 => {
+                let stored = i64::from_be_bytes(bytes[0..8].try_into().unwrap());
+                let phash = u64::from_be_bytes(bytes[8..16].try_into().unwrap());
+                Ok((stored == modified).then_some(phash))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+fn phash_key(path_hash: u64) -> Vec<u8> {
+    let mut key = PHASH_PREFIX.to_vec();
+    key.extend_from_slice(&path_hash.to_be_bytes());
+    key
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn atime_key(key: CacheKey) -> Vec<u8> {
+    let mut bytes = ATIME_PREFIX.to_vec();
+    bytes.extend_from_slice(&key.to_bytes());
+    bytes
 }
 
 #[cfg(test)]
@@ -178,4 +286,59 @@ mod tests {
         cache.delete(key).unwrap();
         assert!(!cache.exists(key).unwrap());
     }
+
+    #[test]
+    fn test_prune_lru() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = ThumbnailCache::open(temp_dir.path()).unwrap();
+
+        let keys: Vec<_> = (0..3).map(|i| CacheKey::new(i, 128, 128)).collect();
+        for (i, key) in keys.iter().enumerate() {
+            cache.put(*key, &[0u8; 10]).unwrap();
+            cache.set_last_access(*key, 100 + i as u64).unwrap();
+        }
+        // Key 0 was read most recently
+        cache.set_last_access(keys[0], 200).unwrap();
+
+        assert_eq!(cache.prune_to(20).unwrap(), 1);
+        assert!(cache.exists(keys[0]).unwrap());
+        assert!(!cache.exists(keys[1]).unwrap());
+        assert!(cache.exists(keys[2]).unwrap());
+    }
+
+    #[test]
+    fn test_atime_throttle_and_delete_by_hash() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = ThumbnailCache::open(temp_dir.path()).unwrap();
+
+        let key = CacheKey::new(9, 128, 128);
+        cache.put(key, &[1, 2, 3]).unwrap();
+        let now = now_secs();
+
+        // A recent access is not rewritten on read
+        cache.set_last_access(key, now - 10).unwrap();
+        cache.get(key).unwrap();
+        assert_eq!(cache.last_access(key).unwrap(), now - 10);
+
+        // A stale one is
+        cache.set_last_access(key, now - ATIME_RESOLUTION - 10).unwrap();
+        cache.get(key).unwrap();
+        assert!(cache.last_access(key).unwrap() >= now);
+
+        assert_eq!(cache.delete_by_hash(9).unwrap(), 1);
+        assert!(!cache.exists(key).unwrap());
+        assert_eq!(cache.db.get(atime_key(key)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_phash_invalidation() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = ThumbnailCache::open(temp_dir.path()).unwrap();
+
+        cache.put_phash(42, 1000, 0xDEAD_BEEF).unwrap();
+        assert_eq!(cache.get_phash(42, 1000).unwrap(), Some(0xDEAD_BEEF));
+        // File modified since hashing
+        assert_eq!(cache.get_phash(42, 2000).unwrap(), None);
+        assert_eq!(cache.get_phash(7, 1000).unwrap(), None);
+    }
 }
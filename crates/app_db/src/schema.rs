@@ -1,8 +1,10 @@
 //! Database schema and migrations
 
 use crate::{DbPool, Result, DbError};
+use app_fs::UniversalPath;
+use std::collections::HashMap;
 
-const SCHEMA_VERSION: i32 = 1;
+const SCHEMA_VERSION: i32 = 6;
 
 /// Run database migrations
 pub fn migrate(pool: &DbPool) -> Result<()> {
@@ -24,6 +26,21 @@ pub fn migrate(pool: &DbPool) -> Result<()> {
         if current_version < 1 {
             apply_v1(&conn)?;
         }
+        if current_version < 2 {
+            apply_v2(&conn)?;
+        }
+        if current_version < 3 {
+            apply_v3(&conn)?;
+        }
+        if current_version < 4 {
+            apply_v4(&conn)?;
+        }
+        if current_version < 5 {
+            apply_v5(&conn)?;
+        }
+        if current_version < 6 {
+            apply_v6(&conn)?;
+        }
 
         // Update version
         conn.execute(&format!("PRAGMA user_version = {}", SCHEMA_VERSION), [])?;
@@ -132,6 +149,230 @@ fn apply_v1(conn: &rusqlite::Connection) -> Result<()> {
     Ok(())
 }
 
+/// v2: FTS5 index over file names and paths
+fn apply_v2(conn: &rusqlite::Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- External-content FTS table backed by files
+        CREATE VIRTUAL TABLE IF NOT EXISTS files_fts USING fts5(
+            file_name,
+            path_display,
+            content = 'files',
+            content_rowid = 'file_id',
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        -- Keep the index in sync with files
+        CREATE TRIGGER IF NOT EXISTS files_fts_insert AFTER INSERT ON files BEGIN
+            INSERT INTO files_fts(rowid, file_name, path_display)
+            VALUES (new.file_id, new.file_name, new.path_display);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS files_fts_delete AFTER DELETE ON files BEGIN
+            INSERT INTO files_fts(files_fts, rowid, file_name, path_display)
+            VALUES ('delete', old.file_id, old.file_name, old.path_display);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS files_fts_update AFTER UPDATE OF file_name, path_display ON files BEGIN
+            INSERT INTO files_fts(files_fts, rowid, file_name, path_display)
+            VALUES ('delete', old.file_id, old.file_name, old.path_display);
+            INSERT INTO files_fts(rowid, file_name, path_display)
+            VALUES (new.file_id, new.file_name, new.path_display);
+        END;
+
+        -- Backfill from existing rows
+        INSERT INTO files_fts(files_fts) VALUES ('rebuild');
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v3: view history
+fn apply_v3(conn: &rusqlite::Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- One row per image view
+        CREATE TABLE IF NOT EXISTS history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_id INTEGER NOT NULL REFERENCES files(file_id) ON DELETE CASCADE,
+            viewed_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_history_file ON history(file_id);
+        CREATE INDEX IF NOT EXISTS idx_history_time ON history(viewed_at);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn apply_v4(conn: &rusqlite::Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Per-folder view preferences (keyed by the folder's own path hash,
+        -- i.e. the parent_hash of its files)
+        CREATE TABLE IF NOT EXISTS folder_prefs (
+            parent_hash INTEGER PRIMARY KEY,
+            sort_by TEXT NOT NULL,
+            sort_order TEXT NOT NULL,
+            dirs_first INTEGER NOT NULL DEFAULT 1
+        );
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v5: recently visited folders
+fn apply_v5(conn: &rusqlite::Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- One row per folder; a revisit re-inserts it with a new id, so the
+        -- highest id is the most recent
+        CREATE TABLE IF NOT EXISTS recent_folders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path_hash INTEGER NOT NULL UNIQUE,
+            path_display TEXT NOT NULL
+        );
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v6: recompute path hashes from the stored paths
+/// Path ids are now hashed from the display path without the extended-length
+/// prefix and case-folded on Windows, so rows written by earlier versions no
+/// longer match there. Rows that fold into one path are merged.
+/// (Thumbnail cache entries are regenerated; stale ones age out of the LRU.)
+fn apply_v6(conn: &rusqlite::Connection) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    // Old folder hash -> new, to carry folder_prefs over
+    let mut folder_hashes: HashMap<i64, i64> = HashMap::new();
+    rehash_paths(&tx, "files", "file_id", &mut folder_hashes, merge_file)?;
+    rehash_paths(&tx, "folders", "folder_id", &mut folder_hashes, |conn, _, duplicate| {
+        conn.execute("DELETE FROM folders WHERE folder_id = ?1", [duplicate])?;
+        Ok(())
+    })?;
+
+    for table in ["bookmarks", "recent_folders"] {
+        // Newest first, so a recent folder visited under two spellings keeps its latest visit
+        let rows = query_pairs::<String>(&tx, &format!("SELECT rowid, path_display FROM {} ORDER BY rowid DESC", table))?;
+        // Park the old hashes so the new ones can't collide with them (recent_folders is UNIQUE)
+        tx.execute(&format!("UPDATE {} SET path_hash = -rowid", table), [])?;
+        let mut seen = std::collections::HashSet::new();
+        for (rowid, display) in rows {
+            let path = UniversalPath::new(&display);
+            if table == "recent_folders" && !seen.insert(path.id()) {
+                tx.execute("DELETE FROM recent_folders WHERE rowid = ?1", [rowid])?;
+                continue;
+            }
+            tx.execute(
+                &format!("UPDATE {} SET path_hash = ?1, path_display = ?2 WHERE rowid = ?3", table),
+                rusqlite::params![path.id() as i64, path.display(), rowid],
+            )?;
+        }
+    }
+
+    // folder_prefs is keyed by the hash itself: rewrite it wholesale
+    let prefs = {
+        let mut stmt = tx.prepare("SELECT parent_hash, sort_by, sort_order, dirs_first FROM folder_prefs")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, i64>(3)?)))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        rows
+    };
+    tx.execute("DELETE FROM folder_prefs", [])?;
+    for (old, sort_by, sort_order, dirs_first) in prefs {
+        let hash = folder_hashes.get(&old).copied().unwrap_or(old);
+        tx.execute(
+            "INSERT OR REPLACE INTO folder_prefs (parent_hash, sort_by, sort_order, dirs_first) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![hash, sort_by, sort_order, dirs_first],
+        )?;
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// Fold file row `duplicate` into `kept`: tags, views and metadata move over
+fn merge_file(conn: &rusqlite::Connection, kept: i64, duplicate: i64) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO file_tags (file_id, tag_id, added_at)
+         SELECT ?1, tag_id, added_at FROM file_tags WHERE file_id = ?2",
+        [kept, duplicate],
+    )?;
+    conn.execute("UPDATE history SET file_id = ?1 WHERE file_id = ?2", [kept, duplicate])?;
+    conn.execute(
+        "UPDATE files SET metadata = (SELECT metadata FROM files WHERE file_id = ?2)
+         WHERE file_id = ?1 AND metadata IS NULL",
+        [kept, duplicate],
+    )?;
+    conn.execute("DELETE FROM files WHERE file_id = ?1", [duplicate])?;
+    Ok(())
+}
+
+/// Recompute `path_hash` / `parent_hash` of a table from its `path_blob`
+/// Old folder hashes are mapped to their new values in `folder_hashes`; a row
+/// whose new hash is already taken is folded into the first one by `merge`.
+fn rehash_paths(
+    conn: &rusqlite::Connection,
+    table: &str,
+    id_column: &str,
+    folder_hashes: &mut HashMap<i64, i64>,
+    merge: impl Fn(&rusqlite::Connection, i64, i64) -> Result<()>,
+) -> Result<()> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, path_hash, parent_hash, path_blob FROM {} ORDER BY {}",
+        id_column, table, id_column
+    ))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, Option<i64>>(2)?, row.get::<_, Vec<u8>>(3)?))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    // Park the old hashes so the new ones can't collide with them (path_hash is UNIQUE)
+    conn.execute(&format!("UPDATE {} SET path_hash = -{}", table, id_column), [])?;
+    let mut kept: HashMap<i64, i64> = HashMap::new();
+    for (id, old_hash, old_parent, blob) in rows {
+        let (hash, parent, display) = match UniversalPath::from_raw_bytes(&blob) {
+            Some(path) => {
+                let parent = path.parent().map(|p| p.id() as i64).unwrap_or(0);
+                (path.id() as i64, Some(parent), path.display().to_string())
+            }
+            None => {
+                conn.execute(&format!("UPDATE {} SET path_hash = ?1 WHERE {} = ?2", table, id_column), [old_hash, id])?;
+                continue;
+            }
+        };
+        folder_hashes.insert(old_hash, hash);
+        if let (Some(old), Some(new)) = (old_parent, parent) {
+            folder_hashes.insert(old, new);
+        }
+        if let Some(&first) = kept.get(&hash) {
+            merge(conn, first, id)?;
+            continue;
+        }
+        kept.insert(hash, id);
+        conn.execute(
+            &format!("UPDATE {} SET path_hash = ?1, parent_hash = ?2, path_display = ?3 WHERE {} = ?4", table, id_column),
+            rusqlite::params![hash, parent, display, id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Rows of (i64, T) from a two-column query
+fn query_pairs<T: rusqlite::types::FromSql>(conn: &rusqlite::Connection, sql: &str) -> Result<Vec<(i64, T)>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -145,4 +386,105 @@ mod tests {
         let result = migrate(&pool);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_fts_backfill() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let pool = init_pool(temp_file.path()).unwrap();
+
+        // A v1 database with an existing row
+        {
+            let conn = pool.get().unwrap();
+            apply_v1(&conn).unwrap();
+            conn.execute(
+                "INSERT INTO files (path_hash, path_display, path_blob, parent_hash, file_name)
+                 VALUES (1, '/photos/holiday.jpg', x'00', 0, 'holiday.jpg')",
+                [],
+            ).unwrap();
+            conn.execute("PRAGMA user_version = 1", []).unwrap();
+        }
+
+        migrate(&pool).unwrap();
+
+        let conn = pool.get().unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM files_fts WHERE files_fts MATCH 'holiday'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_rehash_keeps_metadata() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let pool = init_pool(temp_file.path()).unwrap();
+        let image = UniversalPath::new("/photos/a.jpg");
+        let folder = UniversalPath::new("/photos");
+
+        // A v5 database whose rows carry hashes from an older id scheme
+        {
+            let conn = pool.get().unwrap();
+            for apply in [apply_v1, apply_v2, apply_v3, apply_v4, apply_v5] {
+                apply(&conn).unwrap();
+            }
+            conn.execute(
+                "INSERT INTO files (path_hash, path_display, path_blob, parent_hash, file_name, metadata)
+                 VALUES (101, '/photos/a.jpg', ?1, 202, 'a.jpg', '{\"rating\":4}')",
+                [image.as_raw_bytes()],
+            ).unwrap();
+            conn.execute(
+                "INSERT INTO folder_prefs (parent_hash, sort_by, sort_order, dirs_first) VALUES (202, 'size', 'desc', 1)",
+                [],
+            ).unwrap();
+            conn.execute("INSERT INTO recent_folders (path_hash, path_display) VALUES (303, '/photos')", []).unwrap();
+            conn.execute("PRAGMA user_version = 5", []).unwrap();
+        }
+
+        migrate(&pool).unwrap();
+
+        let db = crate::MetadataDb::new(pool);
+        assert_eq!(db.get_rating(image.id()).unwrap(), 4);
+        assert_eq!(db.folder_ratings(folder.id()).unwrap().get(&image.id()), Some(&4));
+        assert!(db.get_folder_sort(folder.id()).unwrap().is_some());
+        assert!(db.remove_recent_folder(&folder).unwrap());
+    }
+
+    #[test]
+    fn test_rehash_merges_folded_paths() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let pool = init_pool(temp_file.path()).unwrap();
+        let image = UniversalPath::new("/photos/a.jpg");
+
+        // Two rows for one file under different old ids (e.g. `C:\A.jpg` and `\\?\C:\a.jpg`)
+        {
+            let conn = pool.get().unwrap();
+            for apply in [apply_v1, apply_v2, apply_v3, apply_v4, apply_v5] {
+                apply(&conn).unwrap();
+            }
+            for (hash, metadata) in [(101, None), (102, Some(r#"{"rating":5}"#))] {
+                conn.execute(
+                    "INSERT INTO files (path_hash, path_display, path_blob, parent_hash, file_name, metadata)
+                     VALUES (?1, '/photos/a.jpg', ?2, 0, 'a.jpg', ?3)",
+                    rusqlite::params![hash, image.as_raw_bytes(), metadata],
+                ).unwrap();
+            }
+            conn.execute("INSERT INTO tags (name) VALUES ('sky'), ('sea')", []).unwrap();
+            conn.execute("INSERT INTO file_tags (file_id, tag_id) VALUES (1, 1), (2, 1), (2, 2)", []).unwrap();
+            conn.execute("INSERT INTO history (file_id) VALUES (2)", []).unwrap();
+            conn.execute("INSERT INTO recent_folders (path_hash, path_display) VALUES (1, '/photos'), (2, '/photos')", []).unwrap();
+            conn.execute("PRAGMA user_version = 5", []).unwrap();
+        }
+
+        migrate(&pool).unwrap();
+
+        let conn = pool.get().unwrap();
+        let count = |sql: &str| -> i64 { conn.query_row(sql, [], |row| row.get(0)).unwrap() };
+        assert_eq!(count("SELECT COUNT(*) FROM files"), 1);
+        assert_eq!(count("SELECT COUNT(*) FROM file_tags"), 2);
+        assert_eq!(count("SELECT COUNT(*) FROM history WHERE file_id = 1"), 1);
+        assert_eq!(count("SELECT COUNT(*) FROM recent_folders"), 1);
+        drop(conn);
+
+        let db = crate::MetadataDb::new(pool);
+        assert_eq!(db.get_rating(image.id()).unwrap(), 5);
+    }
 }
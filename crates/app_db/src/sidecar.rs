@@ -0,0 +1,191 @@
+//! Metadata sidecar files
+//!
+//! Each file's tags, rating, label and comment can be written next to it as
+//! `<file name>.lf.json` and read back on another machine.
+
+use crate::{MetadataDb, Result};
+use app_fs::UniversalPath;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Sidecar file suffix (appended to the full file name)
+pub const SIDECAR_SUFFIX: &str = ".lf.json";
+
+/// Metadata stored in a sidecar
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SidecarMetadata {
+    pub tags: Vec<String>,
+    pub rating: i32,
+    pub label: Option<u32>,
+    pub comment: Option<String>,
+}
+
+impl SidecarMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.rating == 0 && self.label.is_none() && self.comment.is_none()
+    }
+}
+
+/// Outcome of writing sidecars for many files
+#[derive(Debug, Default)]
+pub struct SidecarExport {
+    pub written: usize,
+    /// `<path>: <error>` for each file whose sidecar couldn't be written
+    pub errors: Vec<String>,
+}
+
+/// Sidecar path for a file (`image.jpg` -> `image.jpg.lf.json`)
+pub fn sidecar_path(file: &Path) -> PathBuf {
+    let mut name = file.as_os_str().to_owned();
+    name.push(SIDECAR_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// File a sidecar belongs to, if `path` is a sidecar
+pub fn sidecar_target(path: &Path) -> Option<PathBuf> {
+    let name = path.to_str()?;
+    name.strip_suffix(SIDECAR_SUFFIX).map(PathBuf::from)
+}
+
+impl MetadataDb {
+    /// Collect a file's metadata for export
+    pub fn sidecar_metadata(&self, path: &UniversalPath) -> Result<SidecarMetadata> {
+        let hash = path.id();
+        let Some(record) = self.get_file_by_hash(hash)? else {
+            return Ok(SidecarMetadata::default());
+        };
+
+        Ok(SidecarMetadata {
+            tags: self.get_file_tags(record.file_id)?.into_iter().map(|t| t.name).collect(),
+            rating: self.get_rating(hash)?,
+            label: self.get_label(hash)?,
+            comment: self.get_comment(hash)?,
+        })
+    }
+
+    /// Write a sidecar next to the file; returns false if it has no metadata
+    pub fn export_sidecar(&self, path: &UniversalPath) -> Result<bool> {
+        let metadata = self.sidecar_metadata(path)?;
+        if metadata.is_empty() {
+            return Ok(false);
+        }
+
+        let json = serde_json::to_string_pretty(&metadata)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(sidecar_path(path.as_path()), json)?;
+        Ok(true)
+    }
+
+    /// Apply a file's sidecar to the database (adding the file first if needed)
+    /// Returns false if the file has no sidecar
+    pub fn import_sidecar(&self, path: &UniversalPath, size: Option<i64>, modified_at: Option<i64>) -> Result<bool> {
+        let sidecar = sidecar_path(path.as_path());
+        if !sidecar.is_file() {
+            return Ok(false);
+        }
+
+        let json = std::fs::read_to_string(&sidecar)?;
+        let metadata: SidecarMetadata = serde_json::from_str(&json)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        let file_id = self.upsert_file(path, size, modified_at)?;
+        let hash = path.id();
+        for tag in &metadata.tags {
+            let tag_id = self.get_or_create_tag(tag)?;
+            self.add_tag_to_file(file_id, tag_id)?;
+        }
+        if metadata.rating > 0 {
+            self.set_rating(hash, metadata.rating)?;
+        }
+        if metadata.label.is_some() {
+            self.set_label(hash, metadata.label)?;
+        }
+        if let Some(ref comment) = metadata.comment {
+            self.set_comment(hash, comment)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Write sidecars for the given files, carrying on past files that fail
+    pub fn export_sidecars(&self, paths: &[UniversalPath]) -> SidecarExport {
+        let mut export = SidecarExport::default();
+        for path in paths {
+            match self.export_sidecar(path) {
+                Ok(true) => export.written += 1,
+                Ok(false) => {}
+                Err(e) => export.errors.push(format!("{}: {}", path, e)),
+            }
+        }
+        export
+    }
+
+    /// Write sidecars for every file in the catalog that has metadata
+    pub fn export_all_sidecars(&self) -> Result<SidecarExport> {
+        let paths: Vec<UniversalPath> = self.list_annotated_files()?
+            .iter()
+            .map(|record| record.path())
+            // Skip files that are gone from disk
+            .filter(|path| path.as_path().is_file())
+            .collect();
+        Ok(self.export_sidecars(&paths))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sidecar_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let image = dir.path().join("a.jpg");
+        std::fs::write(&image, b"jpg").unwrap();
+        let path = UniversalPath::new(&image);
+
+        // Export from one catalog
+        let db = MetadataDb::open_test(&dir);
+        let file_id = db.upsert_file(&path, None, None).unwrap();
+        let tag = db.create_tag("beach", None).unwrap();
+        db.add_tag_to_file(file_id, tag).unwrap();
+        db.set_rating(path.id(), 4).unwrap();
+        db.set_comment(path.id(), "summer").unwrap();
+        assert_eq!(db.export_all_sidecars().unwrap().written, 1);
+        assert_eq!(sidecar_target(&sidecar_path(&image)), Some(image.clone()));
+
+        // Import into a fresh catalog that doesn't know the file yet
+        let other = TempDir::new().unwrap();
+        let db2 = MetadataDb::open_test(&other);
+        assert!(db2.import_sidecar(&path, None, None).unwrap());
+
+        let metadata = db2.sidecar_metadata(&path).unwrap();
+        assert_eq!(metadata, SidecarMetadata {
+            tags: vec!["beach".to_string()],
+            rating: 4,
+            label: None,
+            comment: Some("summer".to_string()),
+        });
+    }
+    #[test]
+    fn test_export_continues_past_failures() {
+        let dir = TempDir::new().unwrap();
+        let image = dir.path().join("a.jpg");
+        std::fs::write(&image, b"jpg").unwrap();
+        let good = UniversalPath::new(&image);
+        // Sidecar can't be written: its folder doesn't exist
+        let bad = UniversalPath::new(dir.path().join("missing").join("b.jpg"));
+
+        let db = MetadataDb::open_test(&dir);
+        for path in [&bad, &good] {
+            db.upsert_file(path, None, None).unwrap();
+            db.set_rating(path.id(), 3).unwrap();
+        }
+
+        let export = db.export_sidecars(&[bad, good]);
+        assert_eq!(export.written, 1);
+        assert_eq!(export.errors.len(), 1);
+        assert!(sidecar_path(&image).is_file());
+    }
+}
@@ -1,7 +1,8 @@
 //! SQLite metadata operations
 
 use crate::{DbError, DbPool, Result};
-use app_fs::UniversalPath;
+use app_fs::{SortBy, SortOrder, UniversalPath};
+use rusqlite::OptionalExtension;
 use serde::{Deserialize, Serialize};
 
 /// File record in the database
@@ -21,6 +22,32 @@ pub struct FileRecord {
     pub indexed_at: i64,
 }
 
+impl FileRecord {
+    /// The file's path, from the raw bytes (the display string is lossy)
+    pub fn path(&self) -> UniversalPath {
+        UniversalPath::from_raw_bytes(&self.path_blob)
+            .unwrap_or_else(|| UniversalPath::new(&self.path_display))
+    }
+
+    /// Map a row whose first 12 columns are the `files` columns in declaration order
+    pub fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            file_id: row.get(0)?,
+            path_hash: row.get(1)?,
+            path_display: row.get(2)?,
+            path_blob: row.get(3)?,
+            parent_hash: row.get(4)?,
+            file_name: row.get(5)?,
+            extension: row.get(6)?,
+            size: row.get(7)?,
+            modified_at: row.get(8)?,
+            created_at: row.get(9)?,
+            metadata: row.get(10)?,
+            indexed_at: row.get(11)?,
+        })
+    }
+}
+
 /// Tag record
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TagRecord {
@@ -38,7 +65,25 @@ pub struct FileTagRecord {
     pub added_at: i64,
 }
 
+/// Bookmarked folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkRecord {
+    pub bookmark_id: i64,
+    pub path_display: String,
+    pub name: Option<String>,
+    pub sort_order: i64,
+}
+
+/// Remembered sort for a folder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FolderSort {
+    pub sort_by: SortBy,
+    pub sort_order: SortOrder,
+    pub dirs_first: bool,
+}
+
 /// Metadata database operations
+#[derive(Clone)]
 pub struct MetadataDb {
     pool: DbPool,
 }
@@ -53,36 +98,18 @@ impl MetadataDb {
     /// Insert or update a file record
     pub fn upsert_file(&self, path: &UniversalPath, size: Option<i64>, modified_at: Option<i64>) -> Result<i64> {
         let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+        upsert_file_with(&conn, path, size, modified_at)
+    }
 
-        let path_hash = path.id() as i64;
-        let parent_hash = path.parent().map(|p| p.id() as i64).unwrap_or(0);
-        let file_name = path.file_name().unwrap_or("").to_string();
-        let extension = path.extension().map(|s| s.to_lowercase());
-
-        conn.execute(
-            r#"
-            INSERT INTO files (path_hash, path_display, path_blob, parent_hash, file_name, extension, size, modified_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-            ON CONFLICT(path_hash) DO UPDATE SET
-                path_display = excluded.path_display,
-                size = excluded.size,
-                modified_at = excluded.modified_at,
-                indexed_at = strftime('%s', 'now')
-            "#,
-            rusqlite::params![
-                path_hash,
-                path.display(),
-                path.as_raw_bytes(),
-                parent_hash,
-                file_name,
-                extension,
-                size,
-                modified_at,
-            ],
-        )?;
-
-        let file_id = conn.last_insert_rowid();
-        Ok(file_id)
+    /// Insert or update many file records (path, size, modified) in one transaction
+    pub fn upsert_files(&self, files: &[(UniversalPath, Option<i64>, Option<i64>)]) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+        let tx = conn.transaction()?;
+        for (path, size, modified_at) in files {
+            upsert_file_with(&tx, path, *size, *modified_at)?;
+        }
+        tx.commit()?;
+        Ok(())
     }
 
     /// Get a file by path hash
@@ -94,22 +121,7 @@ impl MetadataDb {
              FROM files WHERE path_hash = ?1"
         )?;
 
-        let result = stmt.query_row([path_hash as i64], |row| {
-            Ok(FileRecord {
-                file_id: row.get(0)?,
-                path_hash: row.get(1)?,
-                path_display: row.get(2)?,
-                path_blob: row.get(3)?,
-                parent_hash: row.get(4)?,
-                file_name: row.get(5)?,
-                extension: row.get(6)?,
-                size: row.get(7)?,
-                modified_at: row.get(8)?,
-                created_at: row.get(9)?,
-                metadata: row.get(10)?,
-                indexed_at: row.get(11)?,
-            })
-        });
+        let result = stmt.query_row([path_hash as i64], FileRecord::from_row);
 
         match result {
             Ok(record) => Ok(Some(record)),
@@ -129,22 +141,7 @@ impl MetadataDb {
              LIMIT ?2 OFFSET ?3"
         )?;
 
-        let rows = stmt.query_map([parent_hash as i64, limit as i64, offset as i64], |row| {
-            Ok(FileRecord {
-                file_id: row.get(0)?,
-                path_hash: row.get(1)?,
-                path_display: row.get(2)?,
-                path_blob: row.get(3)?,
-                parent_hash: row.get(4)?,
-                file_name: row.get(5)?,
-                extension: row.get(6)?,
-                size: row.get(7)?,
-                modified_at: row.get(8)?,
-                created_at: row.get(9)?,
-                metadata: row.get(10)?,
-                indexed_at: row.get(11)?,
-            })
-        })?;
+        let rows = stmt.query_map([parent_hash as i64, limit as i64, offset as i64], FileRecord::from_row)?;
 
         let mut files = Vec::new();
         for row in rows {
@@ -162,6 +159,55 @@ impl MetadataDb {
         Ok(rows > 0)
     }
 
+    /// Move a file's row to a new path, keeping its metadata, tags and history
+    /// Returns false if the old path was not in the database
+    pub fn rename_file(&self, from: &UniversalPath, to: &UniversalPath) -> Result<bool> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        // A stale row for the target would violate the unique path_hash
+        conn.execute(
+            "DELETE FROM files WHERE path_hash = ?1 AND path_hash <> ?2",
+            [to.id() as i64, from.id() as i64],
+        )?;
+
+        let rows = conn.execute(
+            "UPDATE files SET path_hash = ?1, path_display = ?2, path_blob = ?3, parent_hash = ?4, file_name = ?5, extension = ?6
+             WHERE path_hash = ?7",
+            rusqlite::params![
+                to.id() as i64,
+                to.display(),
+                to.as_raw_bytes(),
+                to.parent().map(|p| p.id() as i64).unwrap_or(0),
+                to.file_name().unwrap_or(""),
+                to.extension().map(|s| s.to_lowercase()),
+                from.id() as i64,
+            ],
+        )?;
+
+        Ok(rows > 0)
+    }
+
+    /// Files that carry tags or metadata (rating, label, comment)
+    pub fn list_annotated_files(&self) -> Result<Vec<FileRecord>> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT file_id, path_hash, path_display, path_blob, parent_hash, file_name, extension, size, modified_at, created_at, metadata, indexed_at
+             FROM files
+             WHERE metadata IS NOT NULL OR file_id IN (SELECT file_id FROM file_tags)
+             ORDER BY path_display"
+        )?;
+
+        let rows = stmt.query_map([], FileRecord::from_row)?;
+
+        let mut files = Vec::new();
+        for row in rows {
+            files.push(row?);
+        }
+
+        Ok(files)
+    }
+
     /// Search files by name pattern
     pub fn search_files(&self, pattern: &str, limit: usize) -> Result<Vec<FileRecord>> {
         let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
@@ -175,22 +221,7 @@ impl MetadataDb {
              LIMIT ?2"
         )?;
 
-        let rows = stmt.query_map([&search_pattern, &limit.to_string()], |row| {
-            Ok(FileRecord {
-                file_id: row.get(0)?,
-                path_hash: row.get(1)?,
-                path_display: row.get(2)?,
-                path_blob: row.get(3)?,
-                parent_hash: row.get(4)?,
-                file_name: row.get(5)?,
-                extension: row.get(6)?,
-                size: row.get(7)?,
-                modified_at: row.get(8)?,
-                created_at: row.get(9)?,
-                metadata: row.get(10)?,
-                indexed_at: row.get(11)?,
-            })
-        })?;
+        let rows = stmt.query_map([&search_pattern, &limit.to_string()], FileRecord::from_row)?;
 
         let mut files = Vec::new();
         for row in rows {
@@ -214,6 +245,16 @@ impl MetadataDb {
         Ok(conn.last_insert_rowid())
     }
 
+    /// Find a tag by name (case-insensitive), creating it if missing
+    pub fn get_or_create_tag(&self, name: &str) -> Result<i64> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [name])?;
+        let tag_id = conn.query_row("SELECT tag_id FROM tags WHERE name = ?1", [name], |row| row.get(0))?;
+
+        Ok(tag_id)
+    }
+
     /// Get all tags
     pub fn list_tags(&self) -> Result<Vec<TagRecord>> {
         let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
@@ -237,6 +278,38 @@ impl MetadataDb {
         Ok(tags)
     }
 
+    /// Get all tags with the number of files carrying each
+    pub fn list_tags_with_counts(&self) -> Result<Vec<(TagRecord, usize)>> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT t.tag_id, t.name, t.color, t.parent_tag_id, COUNT(ft.file_id)
+             FROM tags t
+             LEFT JOIN file_tags ft ON t.tag_id = ft.tag_id
+             GROUP BY t.tag_id
+             ORDER BY t.name"
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                TagRecord {
+                    tag_id: row.get(0)?,
+                    name: row.get(1)?,
+                    color: row.get(2)?,
+                    parent_tag_id: row.get(3)?,
+                },
+                row.get::<_, i64>(4)? as usize,
+            ))
+        })?;
+
+        let mut tags = Vec::new();
+        for row in rows {
+            tags.push(row?);
+        }
+
+        Ok(tags)
+    }
+
     /// Add a tag to a file
     pub fn add_tag_to_file(&self, file_id: i64, tag_id: i64) -> Result<()> {
         let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
@@ -249,6 +322,35 @@ impl MetadataDb {
         Ok(())
     }
 
+    /// Get the tags on a file
+    pub fn get_file_tags(&self, file_id: i64) -> Result<Vec<TagRecord>> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT t.tag_id, t.name, t.color, t.parent_tag_id
+             FROM tags t
+             INNER JOIN file_tags ft ON t.tag_id = ft.tag_id
+             WHERE ft.file_id = ?1
+             ORDER BY t.name"
+        )?;
+
+        let rows = stmt.query_map([file_id], |row| {
+            Ok(TagRecord {
+                tag_id: row.get(0)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+                parent_tag_id: row.get(3)?,
+            })
+        })?;
+
+        let mut tags = Vec::new();
+        for row in rows {
+            tags.push(row?);
+        }
+
+        Ok(tags)
+    }
+
     /// Remove a tag from a file
     pub fn remove_tag_from_file(&self, file_id: i64, tag_id: i64) -> Result<()> {
         let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
@@ -274,22 +376,7 @@ impl MetadataDb {
              LIMIT ?2"
         )?;
 
-        let rows = stmt.query_map([tag_id, limit as i64], |row| {
-            Ok(FileRecord {
-                file_id: row.get(0)?,
-                path_hash: row.get(1)?,
-                path_display: row.get(2)?,
-                path_blob: row.get(3)?,
-                parent_hash: row.get(4)?,
-                file_name: row.get(5)?,
-                extension: row.get(6)?,
-                size: row.get(7)?,
-                modified_at: row.get(8)?,
-                created_at: row.get(9)?,
-                metadata: row.get(10)?,
-                indexed_at: row.get(11)?,
-            })
-        })?;
+        let rows = stmt.query_map([tag_id, limit as i64], FileRecord::from_row)?;
 
         let mut files = Vec::new();
         for row in rows {
@@ -299,6 +386,326 @@ impl MetadataDb {
         Ok(files)
     }
 
+    /// Get files carrying all of the given tags (AND semantics)
+    pub fn find_files_by_tags(&self, tag_ids: &[i64]) -> Result<Vec<FileRecord>> {
+        if tag_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let placeholders = vec!["?"; tag_ids.len()].join(", ");
+        let sql = format!(
+            "SELECT f.file_id, f.path_hash, f.path_display, f.path_blob, f.parent_hash, f.file_name, f.extension, f.size, f.modified_at, f.created_at, f.metadata, f.indexed_at
+             FROM files f
+             INNER JOIN file_tags ft ON f.file_id = ft.file_id
+             WHERE ft.tag_id IN ({})
+             GROUP BY f.file_id
+             HAVING COUNT(DISTINCT ft.tag_id) = {}
+             ORDER BY f.file_name COLLATE NOCASE",
+            placeholders,
+            tag_ids.len()
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(tag_ids), FileRecord::from_row)?;
+
+        let mut files = Vec::new();
+        for row in rows {
+            files.push(row?);
+        }
+
+        Ok(files)
+    }
+
+    /// Full-text search over file names and paths (FTS5)
+    ///
+    /// Whitespace-separated terms are AND-ed; a trailing `*` makes a term a prefix match.
+    pub fn fts_search(&self, query: &str, limit: usize) -> Result<Vec<FileRecord>> {
+        let Some(match_expr) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT f.file_id, f.path_hash, f.path_display, f.path_blob, f.parent_hash, f.file_name, f.extension, f.size, f.modified_at, f.created_at, f.metadata, f.indexed_at
+             FROM files_fts
+             INNER JOIN files f ON f.file_id = files_fts.rowid
+             WHERE files_fts MATCH ?1
+             ORDER BY rank
+             LIMIT ?2"
+        )?;
+
+        let rows = stmt.query_map(rusqlite::params![match_expr, limit as i64], FileRecord::from_row)?;
+
+        let mut files = Vec::new();
+        for row in rows {
+            files.push(row?);
+        }
+
+        Ok(files)
+    }
+
+    /// Get files modified within [start, end] (unix seconds), oldest first
+    pub fn list_files_by_date_range(&self, start: i64, end: i64) -> Result<Vec<FileRecord>> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT file_id, path_hash, path_display, path_blob, parent_hash, file_name, extension, size, modified_at, created_at, metadata, indexed_at
+             FROM files
+             WHERE modified_at BETWEEN ?1 AND ?2
+             ORDER BY modified_at, file_name COLLATE NOCASE"
+        )?;
+
+        let rows = stmt.query_map([start, end], FileRecord::from_row)?;
+
+        let mut files = Vec::new();
+        for row in rows {
+            files.push(row?);
+        }
+
+        Ok(files)
+    }
+
+    /// List months ("YYYY-MM", local time) that have files, with file counts, oldest first
+    pub fn list_file_months(&self) -> Result<Vec<(String, usize)>> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT strftime('%Y-%m', modified_at, 'unixepoch', 'localtime') AS month, COUNT(*)
+             FROM files
+             WHERE modified_at IS NOT NULL
+             GROUP BY month
+             ORDER BY month"
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+        })?;
+
+        let mut months = Vec::new();
+        for row in rows {
+            months.push(row?);
+        }
+
+        Ok(months)
+    }
+
+    // ===== Bookmark Operations =====
+
+    /// Bookmark a folder at the end of the list; returns the existing id if already bookmarked
+    pub fn add_bookmark(&self, path: &UniversalPath, name: Option<&str>) -> Result<i64> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let path_hash = path.id() as i64;
+        if let Some(id) = conn.query_row(
+            "SELECT bookmark_id FROM bookmarks WHERE path_hash = ?1",
+            [path_hash],
+            |row| row.get(0),
+        ).optional()? {
+            return Ok(id);
+        }
+
+        conn.execute(
+            "INSERT INTO bookmarks (path_hash, path_display, name, sort_order)
+             VALUES (?1, ?2, ?3, (SELECT COALESCE(MAX(sort_order), -1) + 1 FROM bookmarks))",
+            rusqlite::params![path_hash, path.display(), name],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Get all bookmarks in display order
+    pub fn list_bookmarks(&self) -> Result<Vec<BookmarkRecord>> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT bookmark_id, path_display, name, sort_order FROM bookmarks ORDER BY sort_order, bookmark_id"
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(BookmarkRecord {
+                bookmark_id: row.get(0)?,
+                path_display: row.get(1)?,
+                name: row.get(2)?,
+                sort_order: row.get(3)?,
+            })
+        })?;
+
+        let mut bookmarks = Vec::new();
+        for row in rows {
+            bookmarks.push(row?);
+        }
+
+        Ok(bookmarks)
+    }
+
+    /// Remove a bookmark
+    pub fn remove_bookmark(&self, bookmark_id: i64) -> Result<bool> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let rows = conn.execute("DELETE FROM bookmarks WHERE bookmark_id = ?1", [bookmark_id])?;
+        Ok(rows > 0)
+    }
+
+    /// Store a new bookmark order (ids in display order)
+    pub fn reorder_bookmarks(&self, bookmark_ids: &[i64]) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let tx = conn.transaction()?;
+        for (order, id) in bookmark_ids.iter().enumerate() {
+            tx.execute(
+                "UPDATE bookmarks SET sort_order = ?1 WHERE bookmark_id = ?2",
+                [order as i64, *id],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    // ===== Folder Preferences =====
+
+    /// Stored sort preference for a folder, if any
+    pub fn get_folder_sort(&self, folder_hash: u64) -> Result<Option<FolderSort>> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let row: Option<(String, String, bool)> = conn.query_row(
+            "SELECT sort_by, sort_order, dirs_first FROM folder_prefs WHERE parent_hash = ?1",
+            [folder_hash as i64],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        ).optional()?;
+
+        // Rows written by a newer version with unknown values are ignored
+        Ok(row.and_then(|(sort_by, sort_order, dirs_first)| {
+            Some(FolderSort {
+                sort_by: parse_sort_by(&sort_by)?,
+                sort_order: if sort_order == "desc" { SortOrder::Descending } else { SortOrder::Ascending },
+                dirs_first,
+            })
+        }))
+    }
+
+    /// Remember the sort for a folder
+    pub fn set_folder_sort(&self, folder_hash: u64, sort: FolderSort) -> Result<()> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let sort_by = match sort.sort_by {
+            SortBy::Name => "name",
+            SortBy::Size => "size",
+            SortBy::Modified => "modified",
+            SortBy::Extension => "type",
+        };
+        let sort_order = match sort.sort_order {
+            SortOrder::Ascending => "asc",
+            SortOrder::Descending => "desc",
+        };
+
+        conn.execute(
+            "INSERT INTO folder_prefs (parent_hash, sort_by, sort_order, dirs_first) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(parent_hash) DO UPDATE SET sort_by = ?2, sort_order = ?3, dirs_first = ?4",
+            rusqlite::params![folder_hash as i64, sort_by, sort_order, sort.dirs_first],
+        )?;
+
+        Ok(())
+    }
+
+    // ===== View History =====
+
+    /// Record that a file was viewed now
+    pub fn record_view(&self, file_id: i64) -> Result<()> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        conn.execute("INSERT INTO history (file_id) VALUES (?1)", [file_id])?;
+
+        Ok(())
+    }
+
+    /// Recently viewed files with their latest view time, newest first (one entry per file)
+    pub fn recent_views(&self, limit: usize) -> Result<Vec<(FileRecord, i64)>> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT f.file_id, f.path_hash, f.path_display, f.path_blob, f.parent_hash, f.file_name, f.extension, f.size, f.modified_at, f.created_at, f.metadata, f.indexed_at, MAX(h.viewed_at) AS last_view
+             FROM history h
+             INNER JOIN files f ON f.file_id = h.file_id
+             GROUP BY h.file_id
+             ORDER BY last_view DESC, MAX(h.id) DESC
+             LIMIT ?1"
+        )?;
+
+        let rows = stmt.query_map([limit as i64], |row| {
+            Ok((
+                FileRecord::from_row(row)?,
+                row.get(12)?,
+            ))
+        })?;
+
+        let mut views = Vec::new();
+        for row in rows {
+            views.push(row?);
+        }
+
+        Ok(views)
+    }
+
+    /// Delete view history older than `days`; returns the number of rows removed
+    pub fn prune_history(&self, days: u32) -> Result<usize> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let rows = conn.execute(
+            "DELETE FROM history WHERE viewed_at < strftime('%s', 'now') - ?1",
+            [days as i64 * 24 * 60 * 60],
+        )?;
+
+        Ok(rows)
+    }
+
+    // ===== Recent Folders =====
+
+    /// Move a folder to the top of the recent list, keeping at most `limit` entries
+    pub fn record_folder_visit(&self, path: &UniversalPath, limit: usize) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM recent_folders WHERE path_hash = ?1", [path.id() as i64])?;
+        tx.execute(
+            "INSERT INTO recent_folders (path_hash, path_display) VALUES (?1, ?2)",
+            rusqlite::params![path.id() as i64, path.display()],
+        )?;
+        tx.execute(
+            "DELETE FROM recent_folders WHERE id NOT IN (SELECT id FROM recent_folders ORDER BY id DESC LIMIT ?1)",
+            [limit as i64],
+        )?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Recently visited folders, most recent first
+    pub fn recent_folders(&self) -> Result<Vec<String>> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let mut stmt = conn.prepare("SELECT path_display FROM recent_folders ORDER BY id DESC")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+
+        let mut folders = Vec::new();
+        for row in rows {
+            folders.push(row?);
+        }
+
+        Ok(folders)
+    }
+
+    /// Drop a folder from the recent list
+    pub fn remove_recent_folder(&self, path: &UniversalPath) -> Result<bool> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let rows = conn.execute("DELETE FROM recent_folders WHERE path_hash = ?1", [path.id() as i64])?;
+        Ok(rows > 0)
+    }
+
     // ===== Rating Operations =====
 
     /// Set rating for a file (0-5)
@@ -351,6 +758,95 @@ impl MetadataDb {
         Ok(rating)
     }
 
+    /// Ratings of rated files in a folder, keyed by path hash
+    pub fn folder_ratings(&self, parent_hash: u64) -> Result<std::collections::HashMap<u64, i32>> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT path_hash, json_extract(metadata, '$.rating')
+             FROM files
+             WHERE parent_hash = ?1 AND json_extract(metadata, '$.rating') > 0"
+        )?;
+
+        let rows = stmt.query_map([parent_hash as i64], |row| {
+            Ok((row.get::<_, i64>(0)? as u64, row.get::<_, i32>(1)?))
+        })?;
+
+        let mut ratings = std::collections::HashMap::new();
+        for row in rows {
+            let (path_hash, rating) = row?;
+            ratings.insert(path_hash, rating);
+        }
+
+        Ok(ratings)
+    }
+
+    // ===== Comment Operations =====
+
+    /// Set the comment for a file (empty removes it)
+    pub fn set_comment(&self, path_hash: u64, comment: &str) -> Result<()> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let current_metadata: Option<String> = conn.query_row(
+            "SELECT metadata FROM files WHERE path_hash = ?1",
+            [path_hash as i64],
+            |row| row.get(0),
+        ).ok().flatten();
+
+        let mut json = current_metadata
+            .and_then(|json_str| serde_json::from_str::<serde_json::Value>(&json_str).ok())
+            .filter(|json| json.is_object())
+            .unwrap_or_else(|| serde_json::json!({}));
+        if comment.is_empty() {
+            json.as_object_mut().map(|obj| obj.remove("comment"));
+        } else {
+            json["comment"] = serde_json::json!(comment);
+        }
+
+        conn.execute(
+            "UPDATE files SET metadata = ?1 WHERE path_hash = ?2",
+            rusqlite::params![json.to_string(), path_hash as i64],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get the comment for a file
+    pub fn get_comment(&self, path_hash: u64) -> Result<Option<String>> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let metadata: Option<String> = conn.query_row(
+            "SELECT metadata FROM files WHERE path_hash = ?1",
+            [path_hash as i64],
+            |row| row.get(0),
+        ).ok().flatten();
+
+        let comment = metadata
+            .and_then(|json_str| serde_json::from_str::<serde_json::Value>(&json_str).ok())
+            .and_then(|json| json["comment"].as_str().map(str::to_string));
+
+        Ok(comment)
+    }
+
+    /// Path hashes of files in a folder that have a comment
+    pub fn folder_comments(&self, parent_hash: u64) -> Result<std::collections::HashSet<u64>> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT path_hash FROM files
+             WHERE parent_hash = ?1 AND json_extract(metadata, '$.comment') <> ''"
+        )?;
+
+        let rows = stmt.query_map([parent_hash as i64], |row| row.get::<_, i64>(0))?;
+
+        let mut hashes = std::collections::HashSet::new();
+        for row in rows {
+            hashes.insert(row? as u64);
+        }
+
+        Ok(hashes)
+    }
+
     /// Set label color for a file
     pub fn set_label(&self, path_hash: u64, label: Option<u32>) -> Result<()> {
         let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
@@ -384,4 +880,382 @@ impl MetadataDb {
 
         Ok(())
     }
+
+    /// Label colors of labeled files in a folder, keyed by path hash
+    pub fn folder_labels(&self, parent_hash: u64) -> Result<std::collections::HashMap<u64, u32>> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT path_hash, json_extract(metadata, '$.label')
+             FROM files
+             WHERE parent_hash = ?1 AND json_extract(metadata, '$.label') IS NOT NULL"
+        )?;
+
+        let rows = stmt.query_map([parent_hash as i64], |row| {
+            Ok((row.get::<_, i64>(0)? as u64, row.get::<_, i64>(1)? as u32))
+        })?;
+
+        let mut labels = std::collections::HashMap::new();
+        for row in rows {
+            let (path_hash, label) = row?;
+            labels.insert(path_hash, label);
+        }
+
+        Ok(labels)
+    }
+
+    /// Get label color for a file
+    pub fn get_label(&self, path_hash: u64) -> Result<Option<u32>> {
+        let conn = self.pool.get().map_err(|e| DbError::Pool(e.to_string()))?;
+
+        let metadata: Option<String> = conn.query_row(
+            "SELECT metadata FROM files WHERE path_hash = ?1",
+            [path_hash as i64],
+            |row| row.get(0),
+        ).ok().flatten();
+
+        let label = metadata
+            .and_then(|json_str| serde_json::from_str::<serde_json::Value>(&json_str).ok())
+            .and_then(|json| json["label"].as_u64())
+            .map(|l| l as u32);
+
+        Ok(label)
+    }
+}
+
+/// Build an FTS5 MATCH expression from user input, quoting each term
+fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .filter_map(|term| {
+            let (term, prefix) = match term.strip_suffix('*') {
+                Some(stem) => (stem, true),
+                None => (term, false),
+            };
+            if term.is_empty() {
+                return None;
+            }
+            let quoted = format!("\"{}\"", term.replace('"', "\"\""));
+            Some(if prefix { quoted + "*" } else { quoted })
+        })
+        .collect();
+
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+fn parse_sort_by(s: &str) -> Option<SortBy> {
+    match s {
+        "name" => Some(SortBy::Name),
+        "size" => Some(SortBy::Size),
+        "modified" => Some(SortBy::Modified),
+        "type" => Some(SortBy::Extension),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+impl MetadataDb {
+    /// Migrated database in a temporary directory
+    pub(crate) fn open_test(dir: &tempfile::TempDir) -> Self {
+        let pool = crate::pool::init_pool(&dir.path().join("test.db")).unwrap();
+        crate::migrate(&pool).unwrap();
+        Self::new(pool)
+    }
+}
+
+/// Insert or update a file record on an open connection
+fn upsert_file_with(conn: &rusqlite::Connection, path: &UniversalPath, size: Option<i64>, modified_at: Option<i64>) -> Result<i64> {
+    let path_hash = path.id() as i64;
+    let parent_hash = path.parent().map(|p| p.id() as i64).unwrap_or(0);
+    let file_name = path.file_name().unwrap_or("").to_string();
+    let extension = path.extension().map(|s| s.to_lowercase());
+
+    // RETURNING yields the row id for updates too (last_insert_rowid does not)
+    let file_id = conn.query_row(
+        r#"
+        INSERT INTO files (path_hash, path_display, path_blob, parent_hash, file_name, extension, size, modified_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        ON CONFLICT(path_hash) DO UPDATE SET
+            path_display = excluded.path_display,
+            size = excluded.size,
+            modified_at = excluded.modified_at,
+            indexed_at = strftime('%s', 'now')
+        RETURNING file_id
+        "#,
+        rusqlite::params![
+            path_hash,
+            path.display(),
+            path.as_raw_bytes(),
+            parent_hash,
+            file_name,
+            extension,
+            size,
+            modified_at,
+        ],
+        |row| row.get(0),
+    )?;
+
+    Ok(file_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_files_by_tags() {
+        let dir = TempDir::new().unwrap();
+        let db = MetadataDb::open_test(&dir);
+
+        let a = db.upsert_file(&UniversalPath::new("/photos/a.jpg"), None, None).unwrap();
+        let b = db.upsert_file(&UniversalPath::new("/photos/b.jpg"), None, None).unwrap();
+        let cat = db.create_tag("cat", None).unwrap();
+        let dog = db.create_tag("dog", None).unwrap();
+
+        db.add_tag_to_file(a, cat).unwrap();
+        db.add_tag_to_file(a, dog).unwrap();
+        db.add_tag_to_file(b, cat).unwrap();
+
+        let names = |files: Vec<FileRecord>| files.into_iter().map(|f| f.file_name).collect::<Vec<_>>();
+        assert_eq!(names(db.find_files_by_tags(&[cat]).unwrap()), vec!["a.jpg", "b.jpg"]);
+        assert_eq!(names(db.find_files_by_tags(&[cat, dog]).unwrap()), vec!["a.jpg"]);
+        assert!(db.find_files_by_tags(&[]).unwrap().is_empty());
+
+        let counts: Vec<_> = db.list_tags_with_counts().unwrap()
+            .into_iter()
+            .map(|(t, n)| (t.name, n))
+            .collect();
+        assert_eq!(counts, vec![("cat".to_string(), 2), ("dog".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_upsert_files() {
+        let dir = TempDir::new().unwrap();
+        let db = MetadataDb::open_test(&dir);
+
+        let a = UniversalPath::new("/photos/a.jpg");
+        let b = UniversalPath::new("/photos/b.jpg");
+        db.upsert_files(&[(a.clone(), Some(10), Some(100)), (b.clone(), None, None)]).unwrap();
+        // Re-indexing updates the existing row
+        db.upsert_files(&[(a.clone(), Some(20), Some(200))]).unwrap();
+
+        let record = db.get_file_by_hash(a.id()).unwrap().unwrap();
+        assert_eq!((record.size, record.modified_at), (Some(20), Some(200)));
+        assert_eq!(record.path().as_path(), a.as_path());
+        assert_eq!(db.list_files_in_folder(a.parent().unwrap().id(), 0, 10).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_folder_ratings() {
+        let dir = TempDir::new().unwrap();
+        let db = MetadataDb::open_test(&dir);
+
+        let a = UniversalPath::new("/photos/a.jpg");
+        let b = UniversalPath::new("/photos/b.jpg");
+        db.upsert_file(&a, None, None).unwrap();
+        db.upsert_file(&b, None, None).unwrap();
+        db.set_rating(a.id(), 4).unwrap();
+
+        let parent = a.parent().unwrap().id();
+        let ratings = db.folder_ratings(parent).unwrap();
+        assert_eq!(ratings.get(&a.id()), Some(&4));
+        assert!(!ratings.contains_key(&b.id()));
+    }
+
+    #[test]
+    fn test_folder_labels() {
+        let dir = TempDir::new().unwrap();
+        let db = MetadataDb::open_test(&dir);
+
+        let a = UniversalPath::new("/photos/a.jpg");
+        let b = UniversalPath::new("/photos/b.jpg");
+        let c = UniversalPath::new("/photos/c.jpg");
+        for path in [&a, &b, &c] {
+            db.upsert_file(path, None, None).unwrap();
+        }
+        db.set_label(a.id(), Some(0xFF0000)).unwrap();
+        db.set_label(b.id(), Some(0x0000FF)).unwrap();
+        db.set_label(b.id(), None).unwrap();
+
+        let labels = db.folder_labels(a.parent().unwrap().id()).unwrap();
+        assert_eq!(labels.get(&a.id()), Some(&0xFF0000));
+        assert!(!labels.contains_key(&b.id()));
+        assert!(!labels.contains_key(&c.id()));
+    }
+
+    #[test]
+    fn test_list_files_by_date_range() {
+        let dir = TempDir::new().unwrap();
+        let db = MetadataDb::open_test(&dir);
+
+        // Mid-month timestamps so the month is the same in any time zone
+        let jan = 1_705_320_000; // 2024-01-15 12:00 UTC
+        let feb = 1_707_998_400; // 2024-02-15 12:00 UTC
+        db.upsert_file(&UniversalPath::new("/photos/a.jpg"), None, Some(feb)).unwrap();
+        db.upsert_file(&UniversalPath::new("/photos/b.jpg"), None, Some(jan)).unwrap();
+        db.upsert_file(&UniversalPath::new("/photos/c.jpg"), None, Some(jan + 60)).unwrap();
+        db.upsert_file(&UniversalPath::new("/photos/d.jpg"), None, None).unwrap();
+
+        let names = |files: Vec<FileRecord>| files.into_iter().map(|f| f.file_name).collect::<Vec<_>>();
+        assert_eq!(names(db.list_files_by_date_range(jan, feb).unwrap()), vec!["b.jpg", "c.jpg", "a.jpg"]);
+        assert_eq!(names(db.list_files_by_date_range(jan + 1, feb - 1).unwrap()), vec!["c.jpg"]);
+
+        assert_eq!(
+            db.list_file_months().unwrap(),
+            vec![("2024-01".to_string(), 2), ("2024-02".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_fts_search() {
+        let dir = TempDir::new().unwrap();
+        let db = MetadataDb::open_test(&dir);
+
+        db.upsert_file(&UniversalPath::new("/photos/summer_beach.jpg"), None, None).unwrap();
+        db.upsert_file(&UniversalPath::new("/photos/winter.png"), None, None).unwrap();
+        let gone = UniversalPath::new("/photos/summit.jpg");
+        db.upsert_file(&gone, None, None).unwrap();
+
+        let names = |files: Vec<FileRecord>| {
+            let mut names: Vec<_> = files.into_iter().map(|f| f.file_name).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(db.fts_search("beach", 10).unwrap()), vec!["summer_beach.jpg"]);
+        assert_eq!(names(db.fts_search("sum*", 10).unwrap()), vec!["summer_beach.jpg", "summit.jpg"]);
+        assert_eq!(names(db.fts_search("photos png", 10).unwrap()), vec!["winter.png"]);
+
+        // Deleted rows drop out of the index
+        db.delete_file(gone.id()).unwrap();
+        assert_eq!(names(db.fts_search("sum*", 10).unwrap()), vec!["summer_beach.jpg"]);
+
+        // Quotes in input are not FTS syntax
+        assert!(db.fts_search("\"", 10).unwrap().is_empty());
+        assert!(db.fts_search("", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_recent_views() {
+        let dir = TempDir::new().unwrap();
+        let db = MetadataDb::open_test(&dir);
+
+        let a = db.upsert_file(&UniversalPath::new("/photos/a.jpg"), None, None).unwrap();
+        let b = db.upsert_file(&UniversalPath::new("/photos/b.jpg"), None, None).unwrap();
+        // Re-upserting returns the existing id
+        assert_eq!(db.upsert_file(&UniversalPath::new("/photos/a.jpg"), Some(1), None).unwrap(), a);
+
+        db.record_view(a).unwrap();
+        db.record_view(b).unwrap();
+        db.record_view(a).unwrap();
+
+        let recent: Vec<_> = db.recent_views(10).unwrap()
+            .into_iter()
+            .map(|(f, _)| f.file_name)
+            .collect();
+        assert_eq!(recent, vec!["a.jpg", "b.jpg"]);
+
+        // Nothing is older than a day yet
+        assert_eq!(db.prune_history(1).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let dir = TempDir::new().unwrap();
+        let db = MetadataDb::open_test(&dir);
+
+        let a = db.add_bookmark(&UniversalPath::new("/photos"), None).unwrap();
+        let b = db.add_bookmark(&UniversalPath::new("/comics"), Some("Comics")).unwrap();
+        let c = db.add_bookmark(&UniversalPath::new("/music"), None).unwrap();
+        assert_eq!(db.add_bookmark(&UniversalPath::new("/photos"), None).unwrap(), a);
+
+        let ids = |db: &MetadataDb| db.list_bookmarks().unwrap().into_iter().map(|b| b.bookmark_id).collect::<Vec<_>>();
+        assert_eq!(ids(&db), vec![a, b, c]);
+
+        db.reorder_bookmarks(&[c, a, b]).unwrap();
+        assert_eq!(ids(&db), vec![c, a, b]);
+
+        assert!(db.remove_bookmark(a).unwrap());
+        assert!(!db.remove_bookmark(a).unwrap());
+        assert_eq!(ids(&db), vec![c, b]);
+        assert_eq!(db.list_bookmarks().unwrap()[1].name.as_deref(), Some("Comics"));
+    }
+
+    #[test]
+    fn test_recent_folders() {
+        let dir = TempDir::new().unwrap();
+        let db = MetadataDb::open_test(&dir);
+
+        for path in ["/photos", "/comics", "/music", "/photos"] {
+            db.record_folder_visit(&UniversalPath::new(path), 3).unwrap();
+        }
+        assert_eq!(db.recent_folders().unwrap(), vec!["/photos", "/music", "/comics"]);
+
+        // Oldest entries fall off past the limit
+        db.record_folder_visit(&UniversalPath::new("/work"), 3).unwrap();
+        assert_eq!(db.recent_folders().unwrap(), vec!["/work", "/photos", "/music"]);
+
+        assert!(db.remove_recent_folder(&UniversalPath::new("/photos")).unwrap());
+        assert_eq!(db.recent_folders().unwrap(), vec!["/work", "/music"]);
+    }
+
+    #[test]
+    fn test_comment_survives_rename() {
+        let dir = TempDir::new().unwrap();
+        let db = MetadataDb::open_test(&dir);
+
+        let from = UniversalPath::new("/photos/a.jpg");
+        let to = UniversalPath::new("/photos/b.jpg");
+        db.upsert_file(&from, None, None).unwrap();
+        db.set_rating(from.id(), 4).unwrap();
+        db.set_comment(from.id(), "first trip").unwrap();
+        assert_eq!(db.get_comment(from.id()).unwrap().as_deref(), Some("first trip"));
+
+        assert!(db.rename_file(&from, &to).unwrap());
+        assert_eq!(db.get_comment(from.id()).unwrap(), None);
+        assert_eq!(db.get_comment(to.id()).unwrap().as_deref(), Some("first trip"));
+        assert_eq!(db.get_rating(to.id()).unwrap(), 4);
+        let parent = to.parent().unwrap().id();
+        assert!(db.folder_comments(parent).unwrap().contains(&to.id()));
+
+        db.set_comment(to.id(), "").unwrap();
+        assert_eq!(db.get_comment(to.id()).unwrap(), None);
+        assert_eq!(db.get_rating(to.id()).unwrap(), 4);
+        assert!(db.folder_comments(parent).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_file_tags_and_label() {
+        let dir = TempDir::new().unwrap();
+        let db = MetadataDb::open_test(&dir);
+
+        let path = UniversalPath::new("/photos/a.jpg");
+        let file = db.upsert_file(&path, None, None).unwrap();
+        let cat = db.create_tag("cat", None).unwrap();
+        db.add_tag_to_file(file, cat).unwrap();
+
+        let tags: Vec<_> = db.get_file_tags(file).unwrap().into_iter().map(|t| t.name).collect();
+        assert_eq!(tags, vec!["cat"]);
+
+        assert_eq!(db.get_label(path.id()).unwrap(), None);
+        db.set_label(path.id(), Some(0xFFFF0000)).unwrap();
+        assert_eq!(db.get_label(path.id()).unwrap(), Some(0xFFFF0000));
+    }
+
+    #[test]
+    fn test_folder_sort() {
+        let dir = TempDir::new().unwrap();
+        let db = MetadataDb::open_test(&dir);
+        let folder = UniversalPath::new("/photos").id();
+
+        assert_eq!(db.get_folder_sort(folder).unwrap(), None);
+
+        let sort = FolderSort { sort_by: SortBy::Modified, sort_order: SortOrder::Descending, dirs_first: false };
+        db.set_folder_sort(folder, sort).unwrap();
+        assert_eq!(db.get_folder_sort(folder).unwrap(), Some(sort));
+
+        let sort = FolderSort { sort_by: SortBy::Extension, sort_order: SortOrder::Ascending, dirs_first: true };
+        db.set_folder_sort(folder, sort).unwrap();
+        assert_eq!(db.get_folder_sort(folder).unwrap(), Some(sort));
+    }
 }
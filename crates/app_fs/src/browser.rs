@@ -1,8 +1,19 @@
 //! File system browser - directory listing and file operations
 
 use crate::{FsError, Result, UniversalPath};
+use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Entries per chunk sent by a background listing
+const LISTING_CHUNK: usize = 512;
+
+/// Video file extensions (lowercase)
+pub const VIDEO_EXTENSIONS: &[&str] = &["mp4", "m4v", "mkv", "webm", "mov", "avi", "wmv"];
 
 /// File entry with metadata
 #[derive(Debug, Clone)]
@@ -14,12 +25,31 @@ pub struct FileEntry {
     pub size: u64,
     pub modified: Option<i64>,
     pub extension: String,
+    /// Symbolic link or junction; the other fields describe its target
+    pub is_symlink: bool,
+    /// Where the link points, as stored in the link
+    pub link_target: Option<PathBuf>,
 }
 
 impl FileEntry {
     /// Create a new file entry from path
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref();
+        // Junctions are reported as symlinks too
+        let is_symlink = fs::symlink_metadata(path)
+            .map(|m| m.file_type().is_symlink())
+            .unwrap_or(false);
+        Self::with_link_flag(path, is_symlink)
+    }
+
+    /// Create an entry while listing a directory; the link flag comes from the
+    /// listing itself instead of another metadata call
+    fn from_dir_entry(entry: &fs::DirEntry) -> Result<Self> {
+        let is_symlink = entry.file_type().map(|t| t.is_symlink()).unwrap_or(false);
+        Self::with_link_flag(&entry.path(), is_symlink)
+    }
+
+    fn with_link_flag(path: &Path, is_symlink: bool) -> Result<Self> {
         let universal = UniversalPath::new(path);
 
         let metadata = fs::metadata(path)?;
@@ -41,6 +71,8 @@ impl FileEntry {
 
         let is_hidden = is_hidden_file(path, &name);
 
+        let link_target = if is_symlink { fs::read_link(path).ok() } else { None };
+
         Ok(Self {
             path: universal,
             name,
@@ -49,6 +81,8 @@ impl FileEntry {
             size: metadata.len(),
             modified,
             extension,
+            is_symlink,
+            link_target,
         })
     }
 
@@ -60,6 +94,25 @@ impl FileEntry {
         )
     }
 
+    /// Check if this is a video file
+    pub fn is_video(&self) -> bool {
+        VIDEO_EXTENSIONS.contains(&self.extension.as_str())
+    }
+
+    /// Check if this is a PDF document
+    pub fn is_pdf(&self) -> bool {
+        self.extension == "pdf"
+    }
+
+    /// Check if this is a plain text file (shown in the text preview)
+    pub fn is_text(&self) -> bool {
+        matches!(
+            self.extension.as_str(),
+            "txt" | "md" | "markdown" | "log" | "nfo" | "csv" | "tsv" | "ini" | "cfg"
+                | "conf" | "json" | "xml" | "yaml" | "yml" | "toml"
+        )
+    }
+
     /// Check if this is an archive file
     pub fn is_archive(&self) -> bool {
         matches!(
@@ -93,6 +146,8 @@ pub struct ListOptions {
     pub show_files: bool,
     pub sort_by: SortBy,
     pub sort_order: SortOrder,
+    /// Group directories before files
+    pub dirs_first: bool,
     pub filter_extensions: Option<Vec<String>>,
 }
 
@@ -104,6 +159,7 @@ impl Default for ListOptions {
             show_files: true,
             sort_by: SortBy::Name,
             sort_order: SortOrder::Ascending,
+            dirs_first: true,
             filter_extensions: None,
         }
     }
@@ -121,6 +177,31 @@ impl ListOptions {
         }
     }
 
+    /// Parse an extension filter: "png, jpg", "*.png,*.jpg" or ".png" (None when empty)
+    pub fn parse_extension_filter(text: &str) -> Option<Vec<String>> {
+        let extensions: Vec<String> = text
+            .split([',', ';', ' '])
+            .map(|part| part.trim().trim_start_matches('*').trim_start_matches('.').to_lowercase())
+            .filter(|ext| !ext.is_empty())
+            .collect();
+        (!extensions.is_empty()).then_some(extensions)
+    }
+
+    /// Whether an entry passes the hidden, kind and extension filters
+    pub fn accepts(&self, entry: &FileEntry) -> bool {
+        (self.show_hidden || !entry.is_hidden)
+            && (if entry.is_dir { self.show_directories } else { self.show_files })
+            && self.matches_extension(entry)
+    }
+
+    /// Whether an entry passes the extension filter (directories always pass)
+    pub fn matches_extension(&self, entry: &FileEntry) -> bool {
+        match self.filter_extensions {
+            Some(ref exts) => entry.is_dir || exts.contains(&entry.extension),
+            None => true,
+        }
+    }
+
     /// Filter for archives only
     pub fn archives_only() -> Self {
         Self {
@@ -136,57 +217,272 @@ impl ListOptions {
 /// List directory contents
 pub fn list_directory<P: AsRef<Path>>(path: P, options: &ListOptions) -> Result<Vec<FileEntry>> {
     let path = path.as_ref();
+    check_directory(path)?;
+
+    let mut entries = Vec::new();
+
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let file_entry = match FileEntry::from_dir_entry(&entry) {
+            Ok(e) => e,
+            Err(_) => continue, // Skip entries we can't read
+        };
+
+        if options.accepts(&file_entry) {
+            entries.push(file_entry);
+        }
+    }
+
+    // Sort entries
+    sort_entries(&mut entries, options.sort_by, options.sort_order, options.dirs_first);
 
+    Ok(entries)
+}
+
+/// Error unless `path` is an existing directory
+fn check_directory(path: &Path) -> Result<()> {
     if !path.exists() {
         return Err(FsError::NotFound(path.display().to_string()));
     }
-
     if !path.is_dir() {
         return Err(FsError::InvalidPath(format!("Not a directory: {}", path.display())));
     }
+    Ok(())
+}
 
-    let mut entries = Vec::new();
+/// Message from a background listing thread
+enum ListingMessage {
+    Entries(Vec<FileEntry>),
+    Done(Result<()>),
+}
 
-    for entry in fs::read_dir(path)? {
-        let entry = entry?;
-        let file_entry = match FileEntry::from_path(entry.path()) {
-            Ok(e) => e,
-            Err(_) => continue, // Skip entries we can't read
-        };
+/// Directory listing running on a background thread, for folders too large to list
+/// while the UI waits. Entries arrive unsorted in chunks; dropping the listing cancels it.
+pub struct DirectoryListing {
+    path: PathBuf,
+    options: ListOptions,
+    rx: Receiver<ListingMessage>,
+    cancel: Arc<AtomicBool>,
+    /// Entries received but not yet taken
+    received: Vec<FileEntry>,
+    result: Option<Result<()>>,
+    /// Subfolders are listed too; entries already arrive in their final order
+    recursive: bool,
+}
 
-        // Apply filters
-        if !options.show_hidden && file_entry.is_hidden {
-            continue;
+impl DirectoryListing {
+    /// Start listing `path` in the background
+    pub fn start<P: AsRef<Path>>(path: P, options: &ListOptions) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let (tx, rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let thread_path = path.clone();
+        let thread_options = options.clone();
+        let thread_cancel = cancel.clone();
+        std::thread::spawn(move || {
+            let result = stream_directory(&thread_path, &thread_options, &thread_cancel, |chunk| {
+                tx.send(ListingMessage::Entries(chunk)).is_ok()
+            });
+            let _ = tx.send(ListingMessage::Done(result));
+        });
+
+        Self {
+            path,
+            options: options.clone(),
+            rx,
+            cancel,
+            received: Vec::new(),
+            result: None,
+            recursive: false,
         }
+    }
 
-        if !options.show_directories && file_entry.is_dir {
-            continue;
+    /// Start listing `path` and its subfolders in the background (see `list_recursive`)
+    /// Entries arrive grouped by folder, one chunk per folder.
+    pub fn start_recursive<P: AsRef<Path>>(path: P, options: &ListOptions, max_depth: usize) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let (tx, rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let thread_path = path.clone();
+        let thread_options = options.clone();
+        let thread_cancel = cancel.clone();
+        std::thread::spawn(move || {
+            let mut visited = HashSet::new();
+            let mut send = |chunk: Vec<FileEntry>| {
+                !thread_cancel.load(Ordering::Relaxed) && tx.send(ListingMessage::Entries(chunk)).is_ok()
+            };
+            let result = collect_recursive(&thread_path, &thread_options, max_depth, &mut send, &mut visited, true);
+            let _ = tx.send(ListingMessage::Done(result.map(|_| ())));
+        });
+
+        Self {
+            path,
+            options: options.clone(),
+            rx,
+            cancel,
+            received: Vec::new(),
+            result: None,
+            recursive: true,
         }
+    }
 
-        if !options.show_files && !file_entry.is_dir {
-            continue;
+    /// Folder being listed
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Block up to `timeout` for the listing to finish
+    /// Returns all entries, sorted by the listing options (grouped by folder for a
+    /// recursive listing), if it finished in time.
+    pub fn wait(&mut self, timeout: Duration) -> Option<Result<Vec<FileEntry>>> {
+        let deadline = Instant::now() + timeout;
+        while self.result.is_none() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.rx.recv_timeout(remaining) {
+                Ok(message) => self.receive(message),
+                Err(RecvTimeoutError::Timeout) => return None,
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.result = Some(Err(FsError::InvalidPath("Listing thread stopped".into())));
+                }
+            }
         }
 
-        if let Some(ref exts) = options.filter_extensions {
-            if !file_entry.is_dir && !exts.contains(&file_entry.extension) {
-                continue;
+        let result = self.result.take()?;
+        Some(result.map(|()| {
+            let mut entries = std::mem::take(&mut self.received);
+            if !self.recursive {
+                sort_entries(&mut entries, self.options.sort_by, self.options.sort_order, self.options.dirs_first);
             }
+            entries
+        }))
+    }
+
+    /// Take the entries that arrived since the last call (unsorted), and the final
+    /// result once the listing is complete
+    pub fn poll(&mut self) -> (Vec<FileEntry>, Option<Result<()>>) {
+        while let Ok(message) = self.rx.try_recv() {
+            self.receive(message);
         }
+        (std::mem::take(&mut self.received), self.result.take())
+    }
 
-        entries.push(file_entry);
+    /// Stop the listing thread at the next chunk
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
     }
 
-    // Sort entries
-    sort_entries(&mut entries, options.sort_by, options.sort_order);
+    fn receive(&mut self, message: ListingMessage) {
+        match message {
+            ListingMessage::Entries(chunk) => self.received.extend(chunk),
+            ListingMessage::Done(result) => self.result = Some(result),
+        }
+    }
+}
 
-    Ok(entries)
+impl Drop for DirectoryListing {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Read a directory in chunks of `LISTING_CHUNK` accepted entries
+/// Stops early when cancelled or when `send` returns false.
+fn stream_directory(
+    path: &Path,
+    options: &ListOptions,
+    cancel: &AtomicBool,
+    mut send: impl FnMut(Vec<FileEntry>) -> bool,
+) -> Result<()> {
+    check_directory(path)?;
+
+    let mut chunk = Vec::with_capacity(LISTING_CHUNK);
+    for entry in fs::read_dir(path)? {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let Ok(file_entry) = FileEntry::from_dir_entry(&entry?) else {
+            continue; // Skip entries we can't read
+        };
+        if !options.accepts(&file_entry) {
+            continue;
+        }
+        chunk.push(file_entry);
+        if chunk.len() >= LISTING_CHUNK && !send(std::mem::replace(&mut chunk, Vec::with_capacity(LISTING_CHUNK))) {
+            return Ok(());
+        }
+    }
+    if !chunk.is_empty() {
+        send(chunk);
+    }
+    Ok(())
+}
+
+/// List files in a directory and its subdirectories, up to `max_depth` levels below it
+/// Files are grouped by folder (parent first, subfolders in name order); unreadable subfolders are skipped
+/// A folder reached again through a symlink or junction is listed only once.
+pub fn list_recursive<P: AsRef<Path>>(path: P, options: &ListOptions, max_depth: usize) -> Result<Vec<FileEntry>> {
+    let mut files = Vec::new();
+    let mut visited = HashSet::new();
+    let mut collect = |chunk: Vec<FileEntry>| {
+        files.extend(chunk);
+        true
+    };
+    collect_recursive(path.as_ref(), options, max_depth, &mut collect, &mut visited, true)?;
+    Ok(files)
 }
 
-/// Sort file entries
-fn sort_entries(entries: &mut [FileEntry], sort_by: SortBy, order: SortOrder) {
+/// Walk `path` depth-first, handing each folder's files to `send`
+/// Stops early when `send` returns false.
+fn collect_recursive(
+    path: &Path,
+    options: &ListOptions,
+    depth: usize,
+    send: &mut impl FnMut(Vec<FileEntry>) -> bool,
+    visited: &mut HashSet<PathBuf>,
+    top: bool,
+) -> Result<bool> {
+    let real = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if !visited.insert(real) {
+        return Ok(true);
+    }
+
+    let listing = ListOptions {
+        show_directories: true,
+        show_files: true,
+        sort_by: SortBy::Name,
+        sort_order: SortOrder::Ascending,
+        dirs_first: true,
+        ..options.clone()
+    };
+    let entries = match list_directory(path, &listing) {
+        Ok(entries) => entries,
+        Err(e) if top => return Err(e),
+        Err(_) => return Ok(true),
+    };
+
+    let (dirs, mut here): (Vec<_>, Vec<_>) = entries.into_iter().partition(|e| e.is_dir);
+    sort_entries(&mut here, options.sort_by, options.sort_order, false);
+    if !here.is_empty() && !send(here) {
+        return Ok(false);
+    }
+
+    if depth > 0 {
+        for dir in dirs {
+            if !collect_recursive(dir.path.as_path(), options, depth - 1, send, visited, false)? {
+                return Ok(false);
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Sort file entries (names in natural order)
+pub fn sort_entries(entries: &mut [FileEntry], sort_by: SortBy, order: SortOrder, dirs_first: bool) {
     entries.sort_by(|a, b| {
-        // Directories always come first
-        if a.is_dir != b.is_dir {
+        // Directories come first unless mixed in
+        if dirs_first && a.is_dir != b.is_dir {
             return if a.is_dir {
                 std::cmp::Ordering::Less
             } else {
@@ -259,15 +555,16 @@ enum NaturalSortPart {
     Str(String),
 }
 
-/// Check if a file is hidden
+/// Check if a file is hidden (hidden or system attribute)
 #[cfg(windows)]
 fn is_hidden_file(path: &Path, _name: &str) -> bool {
     use std::os::windows::fs::MetadataExt;
 
     const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
 
     fs::metadata(path)
-        .map(|m| m.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0)
+        .map(|m| m.file_attributes() & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0)
         .unwrap_or(false)
 }
 
@@ -341,6 +638,7 @@ pub fn get_siblings<P: AsRef<Path>>(path: P, skip_empty: bool) -> (Option<Univer
         show_files: false,
         sort_by: SortBy::Name,
         sort_order: SortOrder::Ascending,
+        dirs_first: true,
         filter_extensions: None,
     };
 
@@ -403,6 +701,7 @@ pub fn count_files<P: AsRef<Path>>(path: P) -> Result<usize> {
         show_files: true,
         sort_by: SortBy::Name,
         sort_order: SortOrder::Ascending,
+        dirs_first: true,
         filter_extensions: None,
     };
 
@@ -413,10 +712,114 @@ pub fn count_files<P: AsRef<Path>>(path: P) -> Result<usize> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_text() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, text) in [("readme.MD", true), ("app.log", true), ("a.jpg", false), ("noext", false)] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, b"x").unwrap();
+            let entry = FileEntry::from_path(&path).unwrap();
+            assert_eq!(entry.is_text(), text, "{}", name);
+        }
+    }
+
     #[test]
     fn test_natural_sort() {
         let mut names = vec!["image10.jpg", "image2.jpg", "image1.jpg", "image20.jpg"];
         names.sort_by(|a, b| natural_sort_key(a).cmp(&natural_sort_key(b)));
         assert_eq!(names, vec!["image1.jpg", "image2.jpg", "image10.jpg", "image20.jpg"]);
     }
+
+    #[test]
+    fn test_list_recursive() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("day1/deep")).unwrap();
+        std::fs::create_dir(dir.path().join("day2")).unwrap();
+        for file in ["top.jpg", "day1/a.jpg", "day1/deep/b.jpg", "day2/c.jpg", "day2/notes.txt"] {
+            std::fs::write(dir.path().join(file), "x").unwrap();
+        }
+
+        let options = ListOptions { filter_extensions: Some(vec!["jpg".into()]), ..Default::default() };
+        let names = |depth| -> Vec<String> {
+            list_recursive(dir.path(), &options, depth).unwrap().into_iter().map(|e| e.name).collect()
+        };
+        assert_eq!(names(8), vec!["top.jpg", "a.jpg", "b.jpg", "c.jpg"]);
+        assert_eq!(names(1), vec!["top.jpg", "a.jpg", "c.jpg"]);
+        assert_eq!(names(0), vec!["top.jpg"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loop() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("real")).unwrap();
+        std::fs::write(dir.path().join("real/a.jpg"), "x").unwrap();
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("real/back")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("real"), dir.path().join("alias")).unwrap();
+
+        let alias = FileEntry::from_path(dir.path().join("alias")).unwrap();
+        assert!(alias.is_symlink && alias.is_dir);
+        assert_eq!(alias.link_target, Some(dir.path().join("real")));
+        assert!(!FileEntry::from_path(dir.path().join("real")).unwrap().is_symlink);
+
+        // Listings flag links the same way
+        let listed: Vec<(String, bool)> = list_directory(dir.path(), &ListOptions::default())
+            .unwrap().into_iter().map(|e| (e.name, e.is_symlink)).collect();
+        assert!(listed.contains(&("alias".to_string(), true)));
+        assert!(listed.contains(&("real".to_string(), false)));
+
+        // The loop back to the top and the second route to "real" are not followed
+        let names: Vec<String> = list_recursive(dir.path(), &ListOptions::default(), 64)
+            .unwrap().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["a.jpg"]);
+    }
+
+    #[test]
+    fn test_directory_listing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        for i in 0..LISTING_CHUNK + 10 {
+            std::fs::write(dir.path().join(format!("{:04}.jpg", i)), "x").unwrap();
+        }
+
+        let mut listing = DirectoryListing::start(dir.path(), &ListOptions::default());
+        let entries = listing.wait(Duration::from_secs(10)).unwrap().unwrap();
+        assert_eq!(entries.len(), LISTING_CHUNK + 11);
+        assert_eq!(entries[0].name, "sub");
+        assert_eq!(entries[1].name, "0000.jpg");
+
+        // Streaming: chunks add up to the full listing
+        let mut listing = DirectoryListing::start(dir.path(), &ListOptions::default());
+        let mut streamed = 0;
+        loop {
+            let (chunk, done) = listing.poll();
+            streamed += chunk.len();
+            if let Some(result) = done {
+                assert!(result.is_ok());
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(streamed, LISTING_CHUNK + 11);
+
+        let mut missing = DirectoryListing::start(dir.path().join("missing"), &ListOptions::default());
+        assert!(missing.wait(Duration::from_secs(10)).unwrap().is_err());
+
+        // Recursive: folder order is kept, not re-sorted
+        std::fs::write(dir.path().join("sub/0000.jpg"), "x").unwrap();
+        let mut recursive = DirectoryListing::start_recursive(dir.path(), &ListOptions::default(), 8);
+        let entries = recursive.wait(Duration::from_secs(10)).unwrap().unwrap();
+        assert_eq!(entries.len(), LISTING_CHUNK + 11);
+        assert_eq!(entries.last().unwrap().path.as_path(), dir.path().join("sub/0000.jpg"));
+    }
+
+    #[test]
+    fn test_parse_extension_filter() {
+        let expected = Some(vec!["png".to_string(), "jpg".to_string()]);
+        assert_eq!(ListOptions::parse_extension_filter("png, JPG"), expected);
+        assert_eq!(ListOptions::parse_extension_filter("*.png,*.jpg"), expected);
+        assert_eq!(ListOptions::parse_extension_filter(".png .jpg"), expected);
+        assert_eq!(ListOptions::parse_extension_filter("  "), None);
+        assert_eq!(ListOptions::parse_extension_filter("*"), None);
+    }
 }
@@ -20,9 +20,80 @@ pub enum EncodingHint {
     None,
 }
 
+/// Byte order mark found at the start of a byte sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bom {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+
+impl Bom {
+    /// Length of the mark in bytes
+    pub fn byte_len(self) -> usize {
+        match self {
+            Bom::Utf8 => 3,
+            Bom::Utf16Le | Bom::Utf16Be => 2,
+            Bom::Utf32Le | Bom::Utf32Be => 4,
+        }
+    }
+
+    /// Matching encoding_rs encoding (UTF-32 has none and is decoded by hand)
+    pub fn encoding(self) -> Option<&'static Encoding> {
+        match self {
+            Bom::Utf8 => Some(encoding_rs::UTF_8),
+            Bom::Utf16Le => Some(encoding_rs::UTF_16LE),
+            Bom::Utf16Be => Some(encoding_rs::UTF_16BE),
+            Bom::Utf32Le | Bom::Utf32Be => None,
+        }
+    }
+
+    /// Display name of the encoding, UTF-32 included
+    pub fn name(self) -> &'static str {
+        match self {
+            Bom::Utf32Le => "UTF-32LE",
+            Bom::Utf32Be => "UTF-32BE",
+            bom => bom.encoding().map_or("UTF-8", Encoding::name),
+        }
+    }
+}
+
+/// Detect a byte order mark; when present the encoding is certain
+pub fn detect_bom(bytes: &[u8]) -> Option<Bom> {
+    // UTF-32 LE starts with the UTF-16 LE mark, so check it first
+    match bytes {
+        [0xFF, 0xFE, 0x00, 0x00, ..] => Some(Bom::Utf32Le),
+        [0x00, 0x00, 0xFE, 0xFF, ..] => Some(Bom::Utf32Be),
+        [0xEF, 0xBB, 0xBF, ..] => Some(Bom::Utf8),
+        [0xFF, 0xFE, ..] => Some(Bom::Utf16Le),
+        [0xFE, 0xFF, ..] => Some(Bom::Utf16Be),
+        _ => None,
+    }
+}
+
 /// Detect the most likely encoding of a byte sequence
-pub fn detect_encoding(bytes: &[u8], hint: EncodingHint) -> &'static Encoding {
-    // First, check if it's valid UTF-8
+/// None for UTF-32 (marked by a BOM), which encoding_rs can't decode; `decode_bytes` handles it.
+pub fn detect_encoding(bytes: &[u8], hint: EncodingHint) -> Option<&'static Encoding> {
+    // A byte order mark settles it
+    match detect_bom(bytes) {
+        Some(bom) => bom.encoding(),
+        None => Some(guess_encoding(bytes, hint)),
+    }
+}
+
+/// Display name of the detected encoding, UTF-32 included
+pub fn encoding_name(bytes: &[u8], hint: EncodingHint) -> &'static str {
+    match detect_bom(bytes) {
+        Some(bom) => bom.name(),
+        None => guess_encoding(bytes, hint).name(),
+    }
+}
+
+/// Most likely encoding of bytes without a byte order mark
+fn guess_encoding(bytes: &[u8], hint: EncodingHint) -> &'static Encoding {
+    // Check if it's valid UTF-8 first
     if std::str::from_utf8(bytes).is_ok() {
         return encoding_rs::UTF_8;
     }
@@ -76,19 +147,48 @@ pub fn detect_encoding(bytes: &[u8], hint: EncodingHint) -> &'static Encoding {
 ///
 /// Returns the decoded string and a flag indicating if there were errors
 pub fn decode_bytes(bytes: &[u8], hint: EncodingHint) -> (String, bool) {
-    // First try UTF-8
+    // Byte order mark: decode the rest and drop the mark itself
+    if let Some(bom) = detect_bom(bytes) {
+        let body = &bytes[bom.byte_len()..];
+        return match bom.encoding() {
+            Some(encoding) => {
+                let (result, had_errors) = encoding.decode_without_bom_handling(body);
+                (result.into_owned(), had_errors)
+            }
+            None => decode_utf32(body, bom == Bom::Utf32Be),
+        };
+    }
+
+    // Then try UTF-8
     if let Ok(s) = std::str::from_utf8(bytes) {
         return (s.to_string(), false);
     }
 
     // Detect encoding
-    let encoding = detect_encoding(bytes, hint);
+    let encoding = guess_encoding(bytes, hint);
 
     // Decode with replacement for invalid sequences
     let (result, _, had_errors) = encoding.decode(bytes);
     (result.into_owned(), had_errors)
 }
 
+/// Decode UTF-32 (not supported by encoding_rs), replacing invalid code points
+fn decode_utf32(bytes: &[u8], big_endian: bool) -> (String, bool) {
+    let mut had_errors = bytes.len() % 4 != 0;
+    let result = bytes
+        .chunks_exact(4)
+        .map(|unit| {
+            let unit = [unit[0], unit[1], unit[2], unit[3]];
+            let code = if big_endian { u32::from_be_bytes(unit) } else { u32::from_le_bytes(unit) };
+            char::from_u32(code).unwrap_or_else(|| {
+                had_errors = true;
+                char::REPLACEMENT_CHARACTER
+            })
+        })
+        .collect();
+    (result, had_errors)
+}
+
 /// Force decode bytes with a specific encoding
 pub fn decode_with_encoding(bytes: &[u8], encoding_name: &str) -> Result<String, String> {
     let encoding = Encoding::for_label(encoding_name.as_bytes())
@@ -172,4 +272,24 @@ mod tests {
         let (decoded, _) = decode_bytes(&bytes, EncodingHint::Japanese);
         assert_eq!(decoded, "テスト");
     }
+
+    #[test]
+    fn test_bom_detection() {
+        let cases: [(&[u8], Bom); 5] = [
+            (&[0xEF, 0xBB, 0xBF, b'h', b'i'], Bom::Utf8),
+            (&[0xFF, 0xFE, b'h', 0, b'i', 0], Bom::Utf16Le),
+            (&[0xFE, 0xFF, 0, b'h', 0, b'i'], Bom::Utf16Be),
+            (&[0xFF, 0xFE, 0, 0, b'h', 0, 0, 0, b'i', 0, 0, 0], Bom::Utf32Le),
+            (&[0, 0, 0xFE, 0xFF, 0, 0, 0, b'h', 0, 0, 0, b'i'], Bom::Utf32Be),
+        ];
+        for (bytes, bom) in cases {
+            assert_eq!(detect_bom(bytes), Some(bom));
+            assert_eq!(detect_encoding(bytes, EncodingHint::Japanese), bom.encoding());
+            assert_eq!(encoding_name(bytes, EncodingHint::Japanese), bom.name());
+            assert_eq!(decode_bytes(bytes, EncodingHint::None), ("hi".to_string(), false));
+        }
+        assert_eq!(detect_bom(b"hi"), None);
+        assert_eq!(encoding_name(&[0xFF, 0xFE, 0, 0], EncodingHint::None), "UTF-32LE");
+        assert_eq!(encoding_name(&[0xFE, 0xFF, 0, b'h'], EncodingHint::None), "UTF-16BE");
+    }
 }
@@ -1,6 +1,8 @@
 //! File operations module
 //! Provides clipboard, delete, rename, copy, move operations
 
+use crate::jpeg_orientation::{is_jpeg, read_orientation, rotated_orientation, with_orientation};
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
@@ -29,6 +31,12 @@ pub enum FileOpError {
 
     #[error("File already exists: {0}")]
     AlreadyExists(PathBuf),
+
+    #[error("Not supported on this platform: {0}")]
+    Unsupported(String),
+
+    #[error("Not a JPEG file: {0}")]
+    NotJpeg(PathBuf),
 }
 
 pub type Result<T> = std::result::Result<T, FileOpError>;
@@ -40,6 +48,45 @@ pub enum ClipboardMode {
     Cut,
 }
 
+/// An item in the OS trash (recycle bin)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashEntry {
+    /// Platform identifier of the trashed item
+    pub id: OsString,
+    pub name: String,
+    /// Where the item was before it was deleted
+    pub original_path: PathBuf,
+    /// Deletion time (Unix seconds)
+    pub deleted: i64,
+}
+
+/// Basic file attributes; system and archive exist on Windows only
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileAttributes {
+    pub readonly: bool,
+    /// On Unix this follows a leading dot in the name and cannot be set
+    pub hidden: bool,
+    pub system: bool,
+    pub archive: bool,
+}
+
+/// How a wallpaper is laid out on the desktop
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WallpaperStyle {
+    /// Whole image visible, letterboxed
+    Fit,
+    /// Screen covered, edges cropped
+    #[default]
+    Fill,
+    /// Screen covered, aspect ratio ignored
+    Stretch,
+}
+
+impl WallpaperStyle {
+    /// Whether the style is applied here; macOS keeps the layout chosen in System Settings
+    pub const APPLIED: bool = !cfg!(target_os = "macos");
+}
+
 /// File operations trait
 pub trait FileOperations: Send + Sync {
     /// Copy file paths to clipboard
@@ -63,14 +110,45 @@ pub trait FileOperations: Send + Sync {
     /// Create a new directory
     fn create_dir(&self, path: &Path) -> Result<()>;
 
+    /// Rotate a JPEG clockwise by `quarter_turns` x 90° without re-encoding
+    /// (rewrites the EXIF orientation); returns the new orientation
+    fn rotate_jpeg(&self, path: &Path, quarter_turns: u32) -> Result<u16>;
+
+    /// Read the attributes of a file or directory
+    fn attributes(&self, path: &Path) -> Result<FileAttributes>;
+
+    /// Change the attributes of a file or directory
+    fn set_attributes(&self, path: &Path, attributes: FileAttributes) -> Result<()>;
+
     /// Open file in system file explorer (with selection)
     fn open_in_explorer(&self, path: &Path, select: bool) -> Result<()>;
 
+    /// Open a terminal in `dir`
+    /// `command` is a command line with `{dir}` (see `expand_arguments` for quoting);
+    /// by default the platform terminal is used.
+    fn open_terminal(&self, dir: &Path, command: Option<&str>) -> Result<()>;
+
+    /// Set an image file as the desktop background
+    fn set_wallpaper(&self, path: &Path, style: WallpaperStyle) -> Result<()>;
+
     /// Open file with default application
     fn open_external(&self, path: &Path) -> Result<()>;
 
     /// Open file with specific application
+    /// `args` is an argument template (see `expand_arguments`); by default just the file.
     fn open_with(&self, path: &Path, app_id: &str, args: Option<&str>) -> Result<()>;
+
+    /// List the items in the OS trash
+    fn list_trash(&self) -> Result<Vec<TrashEntry>>;
+
+    /// Move trashed items back to their original location
+    fn restore_from_trash(&self, items: &[TrashEntry]) -> Result<()>;
+
+    /// Permanently delete trashed items
+    fn purge_from_trash(&self, items: &[TrashEntry]) -> Result<()>;
+
+    /// Empty the OS trash; returns the number of items removed
+    fn empty_trash(&self) -> Result<usize>;
 }
 
 /// Default implementation of file operations
@@ -414,6 +492,41 @@ impl FileOperations for DefaultFileOperations {
         Ok(())
     }
 
+    fn rotate_jpeg(&self, path: &Path, quarter_turns: u32) -> Result<u16> {
+        if !is_jpeg(path) {
+            return Err(FileOpError::NotJpeg(path.to_path_buf()));
+        }
+        let data = std::fs::read(path)?;
+        let orientation = read_orientation(&data)
+            .map(|current| rotated_orientation(current, quarter_turns))
+            .ok_or_else(|| FileOpError::NotJpeg(path.to_path_buf()))?;
+        let rotated = with_orientation(&data, orientation)
+            .ok_or_else(|| FileOpError::InvalidOperation(format!("Cannot update the EXIF data of {}", path.display())))?;
+
+        // Write next to the original and swap, so a failure never truncates the photo
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".rotating");
+        let temp = PathBuf::from(temp);
+        std::fs::write(&temp, &rotated)?;
+        if let Err(e) = std::fs::rename(&temp, path) {
+            let _ = std::fs::remove_file(&temp);
+            return Err(e.into());
+        }
+        tracing::info!("Rotated {} (orientation {})", path.display(), orientation);
+
+        Ok(orientation)
+    }
+
+    fn attributes(&self, path: &Path) -> Result<FileAttributes> {
+        attributes::read(path).map_err(|e| denied_as(path, e))
+    }
+
+    fn set_attributes(&self, path: &Path, attributes: FileAttributes) -> Result<()> {
+        attributes::write(path, attributes).map_err(|e| denied_as(path, e))?;
+        tracing::info!("Set attributes of {}: {:?}", path.display(), attributes);
+        Ok(())
+    }
+
     #[cfg(target_os = "windows")]
     fn open_in_explorer(&self, path: &Path, select: bool) -> Result<()> {
         let path_str = path.display().to_string();
@@ -501,6 +614,46 @@ impl FileOperations for DefaultFileOperations {
         Ok(())
     }
 
+    fn open_terminal(&self, dir: &Path, command: Option<&str>) -> Result<()> {
+        if !dir.is_dir() {
+            return Err(FileOpError::NotFound(dir.to_path_buf()));
+        }
+
+        let mut cmd = match command.map(str::trim).filter(|c| !c.is_empty()) {
+            Some(template) => {
+                let dir_arg = dir.to_string_lossy();
+                let mut parts = split_arguments(template).into_iter().map(|part| part.replace("{dir}", &dir_arg));
+                let program = parts.next().ok_or_else(|| FileOpError::InvalidOperation("Empty terminal command".to_string()))?;
+                let mut cmd = std::process::Command::new(program);
+                cmd.args(parts);
+                cmd
+            }
+            None => default_terminal(dir)?,
+        };
+
+        cmd.current_dir(dir).spawn().map_err(|e| {
+            FileOpError::InvalidOperation(format!("Failed to open a terminal: {}", e))
+        })?;
+
+        tracing::info!("Opened terminal in {}", dir.display());
+        Ok(())
+    }
+
+    #[cfg(feature = "wallpaper")]
+    fn set_wallpaper(&self, path: &Path, style: WallpaperStyle) -> Result<()> {
+        if !path.is_file() {
+            return Err(FileOpError::NotFound(path.to_path_buf()));
+        }
+        crate::wallpaper::set(path, style)?;
+        tracing::info!("Set wallpaper ({:?}): {}", style, path.display());
+        Ok(())
+    }
+
+    #[cfg(not(feature = "wallpaper"))]
+    fn set_wallpaper(&self, _path: &Path, _style: WallpaperStyle) -> Result<()> {
+        Err(FileOpError::Unsupported("wallpaper feature not enabled".to_string()))
+    }
+
     #[cfg(feature = "open-external")]
     fn open_external(&self, path: &Path) -> Result<()> {
         if !path.exists() {
@@ -528,14 +681,7 @@ impl FileOperations for DefaultFileOperations {
         }
 
         let mut cmd = std::process::Command::new(app_id);
-        cmd.arg(path.display().to_string());
-
-        if let Some(args_str) = args {
-            // Simple argument parsing (space-separated)
-            for arg in args_str.split_whitespace() {
-                cmd.arg(arg);
-            }
-        }
+        cmd.args(expand_arguments(args.unwrap_or("{file}"), path));
 
         cmd.spawn().map_err(|e| {
             FileOpError::InvalidOperation(format!("Failed to open with {}: {}", app_id, e))
@@ -544,10 +690,336 @@ impl FileOperations for DefaultFileOperations {
         tracing::info!("Opened with {}: {}", app_id, path.display());
         Ok(())
     }
+
+    fn list_trash(&self) -> Result<Vec<TrashEntry>> {
+        trash_bin::list()
+    }
+
+    fn restore_from_trash(&self, items: &[TrashEntry]) -> Result<()> {
+        trash_bin::restore(items)?;
+        tracing::info!("Restored {} item(s) from trash", items.len());
+        Ok(())
+    }
+
+    fn purge_from_trash(&self, items: &[TrashEntry]) -> Result<()> {
+        trash_bin::purge(items)?;
+        tracing::warn!("Permanently deleted {} item(s) from trash", items.len());
+        Ok(())
+    }
+
+    fn empty_trash(&self) -> Result<usize> {
+        let count = trash_bin::empty()?;
+        tracing::warn!("Emptied trash ({} items)", count);
+        Ok(count)
+    }
+}
+
+/// Trash browsing; the trash crate can only list on Windows and freedesktop systems
+#[cfg(all(
+    feature = "trash-support",
+    any(
+        target_os = "windows",
+        all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+    )
+))]
+mod trash_bin {
+    use super::{FileOpError, Result, TrashEntry};
+    use trash::os_limited;
+
+    pub fn list() -> Result<Vec<TrashEntry>> {
+        let mut entries: Vec<_> = os_limited::list()?.iter().map(to_entry).collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.deleted));
+        Ok(entries)
+    }
+
+    pub fn restore(items: &[TrashEntry]) -> Result<()> {
+        os_limited::restore_all(matching(items)?).map_err(|e| match e {
+            trash::Error::RestoreCollision { path, .. } => FileOpError::AlreadyExists(path),
+            e => FileOpError::Trash(e),
+        })
+    }
+
+    pub fn purge(items: &[TrashEntry]) -> Result<()> {
+        Ok(os_limited::purge_all(matching(items)?)?)
+    }
+
+    pub fn empty() -> Result<usize> {
+        let items = os_limited::list()?;
+        let count = items.len();
+        os_limited::purge_all(items)?;
+        Ok(count)
+    }
+
+    /// Current trash items for `items` (ones already gone are skipped)
+    fn matching(items: &[TrashEntry]) -> Result<Vec<trash::TrashItem>> {
+        Ok(os_limited::list()?
+            .into_iter()
+            .filter(|item| items.iter().any(|entry| entry.id == item.id))
+            .collect())
+    }
+
+    pub(super) fn to_entry(item: &trash::TrashItem) -> TrashEntry {
+        TrashEntry {
+            id: item.id.clone(),
+            name: item.name.to_string_lossy().to_string(),
+            original_path: item.original_path(),
+            deleted: item.time_deleted,
+        }
+    }
+}
+
+#[cfg(not(all(
+    feature = "trash-support",
+    any(
+        target_os = "windows",
+        all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+    )
+)))]
+mod trash_bin {
+    use super::{FileOpError, Result, TrashEntry};
+
+    fn unsupported(what: &str) -> FileOpError {
+        FileOpError::Unsupported(what.to_string())
+    }
+
+    pub fn list() -> Result<Vec<TrashEntry>> {
+        Err(unsupported("browsing the trash"))
+    }
+
+    pub fn restore(_items: &[TrashEntry]) -> Result<()> {
+        Err(unsupported("restoring from the trash"))
+    }
+
+    pub fn purge(_items: &[TrashEntry]) -> Result<()> {
+        Err(unsupported("deleting from the trash"))
+    }
+
+    pub fn empty() -> Result<usize> {
+        Err(unsupported("emptying the trash; use the system file manager"))
+    }
+}
+
+#[cfg(windows)]
+mod attributes {
+    use super::{FileAttributes, Result};
+    use std::os::windows::fs::MetadataExt;
+    use std::path::Path;
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::{
+        SetFileAttributesW, FILE_ATTRIBUTE_ARCHIVE, FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_HIDDEN,
+        FILE_ATTRIBUTE_NORMAL, FILE_ATTRIBUTE_READONLY, FILE_ATTRIBUTE_SYSTEM, FILE_FLAGS_AND_ATTRIBUTES,
+    };
+
+    pub fn read(path: &Path) -> Result<FileAttributes> {
+        let bits = std::fs::metadata(path)?.file_attributes();
+        let has = |flag: FILE_FLAGS_AND_ATTRIBUTES| bits & flag.0 != 0;
+        Ok(FileAttributes {
+            readonly: has(FILE_ATTRIBUTE_READONLY),
+            hidden: has(FILE_ATTRIBUTE_HIDDEN),
+            system: has(FILE_ATTRIBUTE_SYSTEM),
+            archive: has(FILE_ATTRIBUTE_ARCHIVE),
+        })
+    }
+
+    /// Other attribute bits (compressed, not indexed, ...) are kept
+    pub fn write(path: &Path, attributes: FileAttributes) -> Result<()> {
+        let managed = FILE_ATTRIBUTE_READONLY | FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM
+            | FILE_ATTRIBUTE_ARCHIVE | FILE_ATTRIBUTE_NORMAL | FILE_ATTRIBUTE_DIRECTORY;
+        let mut bits = std::fs::metadata(path)?.file_attributes() & !managed.0;
+        for (on, flag) in [
+            (attributes.readonly, FILE_ATTRIBUTE_READONLY),
+            (attributes.hidden, FILE_ATTRIBUTE_HIDDEN),
+            (attributes.system, FILE_ATTRIBUTE_SYSTEM),
+            (attributes.archive, FILE_ATTRIBUTE_ARCHIVE),
+        ] {
+            if on {
+                bits |= flag.0;
+            }
+        }
+        if bits == 0 {
+            bits = FILE_ATTRIBUTE_NORMAL.0;
+        }
+        unsafe { SetFileAttributesW(&HSTRING::from(path), FILE_FLAGS_AND_ATTRIBUTES(bits)) }
+            .map_err(std::io::Error::from)?;
+        Ok(())
+    }
+}
+
+#[cfg(not(windows))]
+mod attributes {
+    use super::{FileAttributes, FileOpError, Result};
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+
+    pub fn read(path: &Path) -> Result<FileAttributes> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(FileAttributes {
+            readonly: metadata.permissions().readonly(),
+            hidden: path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')),
+            ..Default::default()
+        })
+    }
+
+    /// Only read-only can change: it clears every write bit, and clearing it
+    /// gives the owner write access back
+    pub fn write(path: &Path, attributes: FileAttributes) -> Result<()> {
+        let current = read(path)?;
+        if attributes.hidden != current.hidden || attributes.system || attributes.archive {
+            return Err(FileOpError::Unsupported(
+                "hidden, system and archive attributes (rename with a leading dot to hide)".to_string(),
+            ));
+        }
+        let mut permissions = std::fs::metadata(path)?.permissions();
+        let mode = permissions.mode();
+        permissions.set_mode(if attributes.readonly { mode & !0o222 } else { mode | 0o200 });
+        std::fs::set_permissions(path, permissions)?;
+        Ok(())
+    }
+}
+
+/// Access errors for `path` reported as `PermissionDenied`
+fn denied_as(path: &Path, error: FileOpError) -> FileOpError {
+    match error {
+        FileOpError::Io(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            FileOpError::PermissionDenied(path.to_path_buf())
+        }
+        e => e,
+    }
+}
+
+/// Windows Terminal when installed, else cmd in a console of its own
+#[cfg(target_os = "windows")]
+fn default_terminal(dir: &Path) -> Result<std::process::Command> {
+    use std::os::windows::process::CommandExt;
+
+    if find_executable("wt").is_some() {
+        let mut cmd = std::process::Command::new("wt");
+        cmd.arg("-d").arg(dir);
+        return Ok(cmd);
+    }
+    const CREATE_NEW_CONSOLE: u32 = 0x0000_0010;
+    let mut cmd = std::process::Command::new("cmd");
+    cmd.creation_flags(CREATE_NEW_CONSOLE);
+    Ok(cmd)
+}
+
+#[cfg(target_os = "macos")]
+fn default_terminal(dir: &Path) -> Result<std::process::Command> {
+    let mut cmd = std::process::Command::new("open");
+    cmd.args(["-a", "Terminal"]).arg(dir);
+    Ok(cmd)
+}
+
+/// $TERMINAL, else the Debian alternative, else the first common terminal installed
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn default_terminal(_dir: &Path) -> Result<std::process::Command> {
+    let from_env = std::env::var("TERMINAL").ok().filter(|t| !t.trim().is_empty());
+    let program = from_env
+        .into_iter()
+        .chain(["x-terminal-emulator", "gnome-terminal", "konsole", "xfce4-terminal", "xterm"].map(String::from))
+        .find(|program| find_executable(program).is_some())
+        .ok_or_else(|| FileOpError::Unsupported("no terminal found; set one in the settings".to_string()))?;
+    Ok(std::process::Command::new(program))
+}
+
+/// Arguments for opening `file` from a template such as `--open "{file}"`
+/// Whitespace separates arguments except inside double quotes; each one is passed
+/// as a single argument, so paths with spaces need no quoting. `{file}` is the file
+/// and `{dir}` its folder; without `{file}` the file goes first.
+pub fn expand_arguments(template: &str, file: &Path) -> Vec<String> {
+    let file_arg = file.to_string_lossy();
+    let dir_arg = file.parent().map(|dir| dir.to_string_lossy()).unwrap_or_default();
+    let mut args: Vec<String> = split_arguments(template)
+        .into_iter()
+        .map(|arg| arg.replace("{file}", &file_arg).replace("{dir}", &dir_arg))
+        .collect();
+    if !template.contains("{file}") {
+        args.insert(0, file_arg.into_owned());
+    }
+    args
+}
+
+/// Split on whitespace outside double quotes (the quotes are dropped)
+fn split_arguments(template: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quoted = false;
+    for c in template.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_arg = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// Full path of `program`: itself when it is a path to a file, else the first match on PATH
+pub fn find_executable(program: &str) -> Option<PathBuf> {
+    let path = Path::new(program);
+    if program.is_empty() {
+        return None;
+    }
+    if path.is_absolute() || path.components().count() > 1 {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    let extensions: &[&str] = if cfg!(windows) { &["", ".exe", ".cmd", ".bat"] } else { &[""] };
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths).find_map(|dir| {
+        extensions
+            .iter()
+            .map(|ext| dir.join(format!("{}{}", program, ext)))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+/// First free name of the form "stem (2).ext" next to `path` (or `path` itself if free)
+pub fn unique_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let ext = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (2..)
+        .map(|n| path.with_file_name(format!("{} ({}){}", stem, n, ext)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| path.to_path_buf())
 }
 
 /// Recursively copy a directory
+/// Links are followed, except one leading back into a folder being copied (a link loop).
 fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
+    copy_dir_within(src, dst, &mut Vec::new())
+}
+
+/// `ancestors` holds the real paths of the folders `src` is nested in
+fn copy_dir_within(src: &Path, dst: &Path, ancestors: &mut Vec<PathBuf>) -> Result<()> {
+    let real = std::fs::canonicalize(src).unwrap_or_else(|_| src.to_path_buf());
+    if ancestors.contains(&real) {
+        return Ok(());
+    }
+    ancestors.push(real);
+    let result = copy_dir_entries(src, dst, ancestors);
+    ancestors.pop();
+    result
+}
+
+fn copy_dir_entries(src: &Path, dst: &Path, ancestors: &mut Vec<PathBuf>) -> Result<()> {
     if !dst.exists() {
         std::fs::create_dir_all(dst)?;
     }
@@ -558,7 +1030,7 @@ fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
         let dst_path = dst.join(entry.file_name());
 
         if src_path.is_dir() {
-            copy_dir_recursive(&src_path, &dst_path)?;
+            copy_dir_within(&src_path, &dst_path, ancestors)?;
         } else {
             std::fs::copy(&src_path, &dst_path)?;
         }
@@ -609,4 +1081,109 @@ mod tests {
         // Clean up
         let _ = fs::remove_file(&to);
     }
+
+    #[test]
+    fn test_attributes() {
+        let ops = DefaultFileOperations::new();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.jpg");
+        fs::write(&path, b"x").unwrap();
+
+        let mut attributes = ops.attributes(&path).unwrap();
+        assert!(!attributes.readonly && !attributes.hidden);
+        attributes.readonly = true;
+        ops.set_attributes(&path, attributes).unwrap();
+        assert!(ops.attributes(&path).unwrap().readonly);
+        attributes.readonly = false;
+        ops.set_attributes(&path, attributes).unwrap();
+        assert!(!ops.attributes(&path).unwrap().readonly);
+
+        assert!(matches!(ops.attributes(&dir.path().join("missing")), Err(FileOpError::Io(_))));
+    }
+
+    #[test]
+    fn test_expand_arguments() {
+        let file = Path::new("/photos/my trip/a b.jpg");
+        assert_eq!(expand_arguments("{file}", file), vec!["/photos/my trip/a b.jpg"]);
+        assert_eq!(
+            expand_arguments("--open \"{file}\" -C {dir}", file),
+            vec!["--open", "/photos/my trip/a b.jpg", "-C", "/photos/my trip"]
+        );
+        assert_eq!(
+            expand_arguments("--title \"My Viewer\"", file),
+            vec!["/photos/my trip/a b.jpg", "--title", "My Viewer"]
+        );
+
+        assert_eq!(find_executable(""), None);
+        assert_eq!(find_executable("/no/such/program"), None);
+    }
+
+    #[test]
+    fn test_open_terminal_missing_dir() {
+        let ops = DefaultFileOperations::new();
+        let result = ops.open_terminal(Path::new("/no/such/folder"), Some("xterm"));
+        assert!(matches!(result, Err(FileOpError::NotFound(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_dir_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(src.join("shared")).unwrap();
+        fs::write(src.join("shared/a.txt"), b"a").unwrap();
+        // Two links to the same folder, and one back to the root
+        std::os::unix::fs::symlink(src.join("shared"), src.join("link1")).unwrap();
+        std::os::unix::fs::symlink(src.join("shared"), src.join("link2")).unwrap();
+        std::os::unix::fs::symlink(&src, src.join("shared/up")).unwrap();
+
+        let dst = dir.path().join("dst");
+        copy_dir_recursive(&src, &dst).unwrap();
+        assert!(dst.join("shared/a.txt").exists());
+        assert!(dst.join("link1/a.txt").exists());
+        assert!(dst.join("link2/a.txt").exists());
+        assert!(!dst.join("shared/up").exists());
+    }
+
+    #[test]
+    fn test_unique_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.png");
+        assert_eq!(unique_path(&path), path);
+
+        fs::write(&path, b"1").unwrap();
+        fs::write(dir.path().join("photo (2).png"), b"2").unwrap();
+        assert_eq!(unique_path(&path), dir.path().join("photo (3).png"));
+    }
+
+    #[test]
+    fn test_rotate_jpeg() {
+        let ops = DefaultFileOperations::new();
+        let dir = tempfile::tempdir().unwrap();
+        let jpeg = dir.path().join("photo.jpg");
+        fs::write(&jpeg, [0xFF, 0xD8, 0xFF, 0xDA, 0x00, 0x02, 0xAB, 0xFF, 0xD9]).unwrap();
+
+        assert_eq!(ops.rotate_jpeg(&jpeg, 1).unwrap(), 6);
+        assert_eq!(ops.rotate_jpeg(&jpeg, 1).unwrap(), 3);
+        assert!(fs::read(&jpeg).unwrap().ends_with(&[0xAB, 0xFF, 0xD9]));
+
+        let png = dir.path().join("image.png");
+        fs::write(&png, b"\x89PNG").unwrap();
+        assert!(matches!(ops.rotate_jpeg(&png, 1), Err(FileOpError::NotJpeg(_))));
+    }
+
+    #[cfg(all(feature = "trash-support", target_os = "linux"))]
+    #[test]
+    fn test_trash_entry_from_item() {
+        let item = trash::TrashItem {
+            id: "/home/u/.local/share/Trash/info/a.jpg.trashinfo".into(),
+            name: "a.jpg".into(),
+            original_parent: PathBuf::from("/home/u/Pictures"),
+            time_deleted: 1_700_000_000,
+        };
+        let entry = trash_bin::to_entry(&item);
+        assert_eq!(entry.name, "a.jpg");
+        assert_eq!(entry.original_path, PathBuf::from("/home/u/Pictures/a.jpg"));
+        assert_eq!(entry.id, item.id);
+    }
 }
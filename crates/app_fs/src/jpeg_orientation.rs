@@ -0,0 +1,230 @@
+//! Lossless JPEG rotation via the EXIF orientation tag
+//! Only the APP1 Exif segment is rewritten; the compressed image data is
+//! copied byte for byte.
+
+use crate::tiff::TiffReader;
+use std::ops::Range;
+use std::path::Path;
+
+const TAG_ORIENTATION: u16 = 0x0112;
+const TYPE_SHORT: u16 = 3;
+const EXIF_HEADER: &[u8] = b"Exif\0\0";
+
+/// Check if a file is a JPEG by extension
+pub fn is_jpeg(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("jpg") || e.eq_ignore_ascii_case("jpeg"))
+}
+
+/// Orientation after turning an image shown with `orientation` clockwise
+/// by `quarter_turns` x 90°
+pub fn rotated_orientation(orientation: u16, quarter_turns: u32) -> u16 {
+    // Each value maps to the next one in its cycle (mirrored ones stay mirrored)
+    const PLAIN: [u16; 4] = [1, 6, 3, 8];
+    const MIRRORED: [u16; 4] = [2, 7, 4, 5];
+    let cycle = if MIRRORED.contains(&orientation) { MIRRORED } else { PLAIN };
+    let pos = cycle.iter().position(|&o| o == orientation).unwrap_or(0);
+    cycle[(pos + quarter_turns as usize) % 4]
+}
+
+/// EXIF orientation of JPEG data (1 when untagged); None if not a JPEG
+pub fn read_orientation(data: &[u8]) -> Option<u16> {
+    let segment = find_segments(data)?.exif;
+    let Some(segment) = segment else {
+        return Some(1);
+    };
+    let tiff = TiffReader::new(&data[segment.clone()])?;
+    let orientation = tiff
+        .entries(tiff.ifd0()?)?
+        .find(|&entry| tiff.u16(entry) == Some(TAG_ORIENTATION))
+        .and_then(|entry| tiff.u16(entry + 8))
+        .unwrap_or(1);
+    Some(if (1..=8).contains(&orientation) { orientation } else { 1 })
+}
+
+/// Copy of JPEG data with its EXIF orientation set to `orientation`
+/// An Exif segment or orientation entry is added when missing.
+pub fn with_orientation(data: &[u8], orientation: u16) -> Option<Vec<u8>> {
+    let segments = find_segments(data)?;
+    let Some(range) = segments.exif else {
+        // No Exif at all: insert a minimal segment after SOI/APP0
+        let mut out = data[..segments.insert_at].to_vec();
+        out.extend_from_slice(&app1_segment(&minimal_tiff(orientation))?);
+        out.extend_from_slice(&data[segments.insert_at..]);
+        return Some(out);
+    };
+
+    let tiff = TiffReader::new(&data[range.clone()])?;
+    let ifd0 = tiff.ifd0()?;
+    let entry = tiff.entries(ifd0)?.find(|&entry| tiff.u16(entry) == Some(TAG_ORIENTATION));
+    let mut block = data[range.clone()].to_vec();
+    match entry {
+        Some(entry) => {
+            tiff.put_u16(&mut block, entry + 2, TYPE_SHORT);
+            tiff.put_u32(&mut block, entry + 4, 1);
+            tiff.put_u16(&mut block, entry + 8, orientation);
+            tiff.put_u16(&mut block, entry + 10, 0);
+        }
+        None => {
+            // Append a copy of IFD0 with the entry added; existing offsets stay valid
+            let count = tiff.u16(ifd0)? as usize;
+            let next = tiff.u32(ifd0 + 2 + count * 12)?;
+            let mut entries: Vec<[u8; 12]> = tiff.entries(ifd0)?
+                .map(|entry| block.get(entry..entry + 12).and_then(|e| e.try_into().ok()))
+                .collect::<Option<_>>()?;
+            let mut added = [0u8; 12];
+            tiff.put_u16(&mut added, 0, TAG_ORIENTATION);
+            tiff.put_u16(&mut added, 2, TYPE_SHORT);
+            tiff.put_u32(&mut added, 4, 1);
+            tiff.put_u16(&mut added, 8, orientation);
+            entries.push(added);
+            // IFD entries must be sorted by tag
+            entries.sort_by_key(|entry| tiff.u16_from(entry));
+
+            if block.len() % 2 == 1 {
+                block.push(0);
+            }
+            let new_ifd = block.len();
+            block.extend_from_slice(&[0; 2]);
+            tiff.put_u16(&mut block, new_ifd, entries.len() as u16);
+            for entry in &entries {
+                block.extend_from_slice(entry);
+            }
+            block.extend_from_slice(&[0; 4]);
+            tiff.put_u32(&mut block, new_ifd + 2 + entries.len() * 12, next);
+            tiff.put_u32(&mut block, 4, u32::try_from(new_ifd).ok()?);
+        }
+    }
+
+    // Segment starts with marker (2), length (2) and the Exif header (6)
+    let segment_start = range.start - 10;
+    let mut out = data[..segment_start].to_vec();
+    out.extend_from_slice(&app1_segment(&block)?);
+    out.extend_from_slice(&data[range.end..]);
+    Some(out)
+}
+
+/// APP1 Exif segment wrapping a TIFF block; None if it exceeds 64KB
+fn app1_segment(tiff: &[u8]) -> Option<Vec<u8>> {
+    let len = u16::try_from(2 + EXIF_HEADER.len() + tiff.len()).ok()?;
+    let mut segment = vec![0xFF, 0xE1];
+    segment.extend_from_slice(&len.to_be_bytes());
+    segment.extend_from_slice(EXIF_HEADER);
+    segment.extend_from_slice(tiff);
+    Some(segment)
+}
+
+/// Big-endian TIFF block with just the orientation entry
+fn minimal_tiff(orientation: u16) -> Vec<u8> {
+    let mut tiff = b"MM\0\x2A\0\0\0\x08".to_vec();
+    tiff.extend_from_slice(&1u16.to_be_bytes());
+    tiff.extend_from_slice(&TAG_ORIENTATION.to_be_bytes());
+    tiff.extend_from_slice(&TYPE_SHORT.to_be_bytes());
+    tiff.extend_from_slice(&1u32.to_be_bytes());
+    tiff.extend_from_slice(&orientation.to_be_bytes());
+    tiff.extend_from_slice(&[0; 2]);
+    tiff.extend_from_slice(&[0; 4]);
+    tiff
+}
+
+/// Where the Exif TIFF block is, and where a new one would go
+struct Segments {
+    exif: Option<Range<usize>>,
+    insert_at: usize,
+}
+
+fn find_segments(data: &[u8]) -> Option<Segments> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+
+    let mut insert_at = 2;
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        // Start of scan / end of image: no more metadata
+        if marker == 0xDA || marker == 0xD9 {
+            break;
+        }
+        let len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        if len < 2 || pos + 2 + len > data.len() {
+            return None;
+        }
+        let end = pos + 2 + len;
+        if marker == 0xE1 && data[pos + 4..end].starts_with(EXIF_HEADER) {
+            return Some(Segments { exif: Some(pos + 10..end), insert_at });
+        }
+        // Keep a leading JFIF APP0 first
+        if marker == 0xE0 && pos == 2 {
+            insert_at = end;
+        }
+        pos = end;
+    }
+
+    Some(Segments { exif: None, insert_at })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SOI, JFIF APP0, fake scan data, EOI
+    fn plain_jpeg() -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x07];
+        data.extend_from_slice(b"JFIF\0");
+        data.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x04, 0x12, 0x34, 0xAB, 0xCD, 0xFF, 0xD9]);
+        data
+    }
+
+    #[test]
+    fn test_rotated_orientation() {
+        assert_eq!(rotated_orientation(1, 1), 6);
+        assert_eq!(rotated_orientation(6, 1), 3);
+        assert_eq!(rotated_orientation(8, 1), 1);
+        assert_eq!(rotated_orientation(1, 3), 8);
+        assert_eq!(rotated_orientation(2, 1), 7);
+        assert_eq!(rotated_orientation(5, 2), 7);
+        assert_eq!(rotated_orientation(3, 4), 3);
+    }
+
+    #[test]
+    fn test_with_orientation() {
+        let data = plain_jpeg();
+        assert_eq!(read_orientation(&data), Some(1));
+        assert_eq!(read_orientation(b"\x89PNG"), None);
+
+        // Untagged: a segment is added after APP0, the scan data is untouched
+        let tagged = with_orientation(&data, 6).unwrap();
+        assert_eq!(read_orientation(&tagged), Some(6));
+        assert_eq!(&tagged[2..11], &data[2..11]);
+        assert!(tagged.ends_with(&data[11..]));
+
+        // Existing entry: rewritten in place
+        let rotated = with_orientation(&tagged, 3).unwrap();
+        assert_eq!(read_orientation(&rotated), Some(3));
+        assert_eq!(rotated.len(), tagged.len());
+
+        // Exif without an orientation entry: IFD0 is rebuilt with it added
+        let mut tiff = b"II\x2A\0\x08\0\0\0".to_vec();
+        tiff.extend_from_slice(&1u16.to_le_bytes());
+        tiff.extend_from_slice(&0x0110u16.to_le_bytes());
+        tiff.extend_from_slice(&2u16.to_le_bytes());
+        tiff.extend_from_slice(&4u32.to_le_bytes());
+        tiff.extend_from_slice(b"Cam\0");
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        let mut exif = data[..2].to_vec();
+        exif.extend_from_slice(&app1_segment(&tiff).unwrap());
+        exif.extend_from_slice(&data[11..]);
+
+        let added = with_orientation(&exif, 8).unwrap();
+        assert_eq!(read_orientation(&added), Some(8));
+        let block = find_segments(&added).unwrap().exif.unwrap();
+        let tiff = TiffReader::new(&added[block]).unwrap();
+        let tags: Vec<_> = tiff.entries(tiff.ifd0().unwrap()).unwrap().map(|e| tiff.u16(e).unwrap()).collect();
+        assert_eq!(tags, [0x0110, TAG_ORIENTATION]);
+    }
+}
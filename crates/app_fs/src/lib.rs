@@ -14,14 +14,20 @@ mod watcher;
 mod sanitize;
 mod browser;
 mod file_operations;
+mod jpeg_orientation;
+mod tiff;
+#[cfg(feature = "wallpaper")]
+mod wallpaper;
 
 pub use universal_path::UniversalPath;
-pub use encoding::{detect_encoding, decode_bytes, EncodingHint};
-pub use vfs::{VirtualFileSystem, VfsEntry, VfsError};
-pub use watcher::{FileWatcher, FsEvent};
-pub use sanitize::{sanitize_filename, SanitizeMode};
-pub use browser::{FileEntry, ListOptions, SortBy, SortOrder, list_directory, list_drives, get_parent, is_root, get_siblings, get_next_sibling, get_prev_sibling, count_files};
-pub use file_operations::{FileOperations, DefaultFileOperations, FileOpError, ClipboardMode};
+pub use encoding::{detect_bom, detect_encoding, encoding_name, decode_bytes, system_encoding_hint, Bom, EncodingHint};
+pub use vfs::{inner_parent, ArchiveFormat, EntryReader, VirtualFileSystem, VfsEntry, VfsError};
+pub use watcher::{EventCoalescer, FileWatcher, FsEvent};
+pub use sanitize::{sanitize_filename, sanitize_filename_for, SanitizeMode, TargetFs};
+pub use browser::{VIDEO_EXTENSIONS, DirectoryListing, FileEntry, ListOptions, SortBy, SortOrder, list_directory, list_recursive, list_drives, sort_entries, get_parent, is_root, get_siblings, get_next_sibling, get_prev_sibling, count_files};
+pub use file_operations::{FileOperations, DefaultFileOperations, FileOpError, ClipboardMode, TrashEntry, FileAttributes, WallpaperStyle, unique_path, expand_arguments, find_executable};
+pub use jpeg_orientation::{is_jpeg, read_orientation, rotated_orientation, with_orientation};
+pub use tiff::TiffReader;
 
 use thiserror::Error;
 
@@ -1,4 +1,6 @@
-//! Filename sanitization for Windows compatibility
+//! Filename sanitization for Windows, POSIX and portable file names
+
+use std::path::Path;
 
 /// Mode for sanitizing filenames
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -11,6 +13,66 @@ pub enum SanitizeMode {
     UrlEncode,
 }
 
+/// File system naming rules to sanitize for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TargetFs {
+    /// NTFS/FAT/exFAT: no `<>:"/\|?*`, trailing dots/spaces or device names
+    #[default]
+    Windows,
+    /// Only `/` and NUL are forbidden
+    Posix,
+    /// Safe everywhere: Windows rules plus no leading spaces and at most 255 bytes
+    Portable,
+}
+
+/// Longest file name (in bytes) accepted by the strict portable rules
+const PORTABLE_MAX_BYTES: usize = 255;
+
+/// File system types on Unix mounts that follow Windows naming rules
+const WINDOWS_LIKE_FS: &[&str] = &[
+    "vfat", "msdos", "exfat", "ntfs", "ntfs3", "fuseblk", "cifs", "smb3",
+];
+
+impl TargetFs {
+    /// Rules of the platform we are running on
+    pub fn native() -> Self {
+        if cfg!(windows) {
+            Self::Windows
+        } else {
+            Self::Posix
+        }
+    }
+
+    /// Rules of the file system `dir` lives on, falling back to [`TargetFs::native`]
+    pub fn for_path(dir: &Path) -> Self {
+        if cfg!(target_os = "linux") {
+            if let Some(fs_type) = mount_fs_type(dir) {
+                if WINDOWS_LIKE_FS.contains(&fs_type.as_str()) {
+                    return Self::Windows;
+                }
+            }
+        }
+        Self::native()
+    }
+}
+
+/// File system type of the mount containing `dir` (from /proc/self/mounts)
+fn mount_fs_type(dir: &Path) -> Option<String> {
+    let mounts = std::fs::read_to_string("/proc/self/mounts").ok()?;
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            Some((mount_point, fs_type))
+        })
+        .filter(|(mount_point, _)| dir.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.len())
+        .map(|(_, fs_type)| fs_type.to_string())
+}
+
 /// Windows reserved filenames
 const RESERVED_NAMES: &[&str] = &[
     "CON", "PRN", "AUX", "NUL",
@@ -33,28 +95,43 @@ const FORBIDDEN_CHARS: &[(char, char)] = &[
 
 /// Sanitize a filename for Windows compatibility
 pub fn sanitize_filename(name: &str, mode: SanitizeMode) -> String {
+    sanitize_filename_for(name, mode, TargetFs::Windows)
+}
+
+/// Sanitize a filename for the given file system rules
+pub fn sanitize_filename_for(name: &str, mode: SanitizeMode, target: TargetFs) -> String {
     let mut result = String::with_capacity(name.len());
 
     // Replace forbidden characters
     for c in name.chars() {
-        if let Some(replacement) = get_replacement(c, mode) {
+        if let Some(replacement) = get_replacement(c, mode, target) {
             result.push_str(&replacement);
         } else {
             result.push(c);
         }
     }
 
-    // Handle reserved names
-    let name_upper = result.to_uppercase();
-    let base_name = name_upper.split('.').next().unwrap_or("");
+    if target != TargetFs::Posix {
+        if target == TargetFs::Portable {
+            result = result.trim_start().to_string();
+        }
 
-    if RESERVED_NAMES.contains(&base_name) {
-        result = format!("_{}", result);
-    }
+        // Handle trailing dots and spaces (Windows removes them)
+        while result.ends_with('.') || result.ends_with(' ') {
+            result.pop();
+        }
+
+        // Suffix reserved device names so "CON.txt" doesn't open the console
+        if is_reserved(&result) {
+            let split = result.find('.').unwrap_or(result.len());
+            result.insert(split, '_');
+        }
 
-    // Handle trailing dots and spaces (Windows removes them)
-    while result.ends_with('.') || result.ends_with(' ') {
-        result.pop();
+        if target == TargetFs::Portable {
+            truncate_keeping_extension(&mut result, PORTABLE_MAX_BYTES);
+        }
+    } else if result == "." || result == ".." {
+        result = result.replace('.', "_");
     }
 
     // Handle empty result
@@ -65,7 +142,37 @@ pub fn sanitize_filename(name: &str, mode: SanitizeMode) -> String {
     result
 }
 
-fn get_replacement(c: char, mode: SanitizeMode) -> Option<String> {
+/// Whether the part before the first dot is a Windows device name
+fn is_reserved(name: &str) -> bool {
+    let base_name = name.split('.').next().unwrap_or("").to_uppercase();
+    RESERVED_NAMES.contains(&base_name.as_str())
+}
+
+/// Shorten the stem (on a char boundary) until the name fits in `max_bytes`
+fn truncate_keeping_extension(name: &mut String, max_bytes: usize) {
+    if name.len() <= max_bytes {
+        return;
+    }
+    let ext = match name.rfind('.') {
+        Some(dot) if dot > 0 && name.len() - dot < max_bytes / 2 => name.split_off(dot),
+        _ => String::new(),
+    };
+    let mut end = max_bytes - ext.len();
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+    name.truncate(end);
+    name.push_str(&ext);
+}
+
+fn get_replacement(c: char, mode: SanitizeMode, target: TargetFs) -> Option<String> {
+    if target == TargetFs::Posix {
+        return match c {
+            '/' | '\0' => get_replacement(c, mode, TargetFs::Windows),
+            _ => None,
+        };
+    }
+
     for (forbidden, fullwidth) in FORBIDDEN_CHARS {
         if c == *forbidden {
             return Some(match mode {
@@ -104,9 +211,7 @@ pub fn is_valid_filename(name: &str) -> bool {
     }
 
     // Check for reserved names
-    let name_upper = name.to_uppercase();
-    let base_name = name_upper.split('.').next().unwrap_or("");
-    if RESERVED_NAMES.contains(&base_name) {
+    if is_reserved(name) {
         return false;
     }
 
@@ -138,12 +243,44 @@ mod tests {
     fn test_sanitize_reserved() {
         assert_eq!(
             sanitize_filename("CON.txt", SanitizeMode::FullWidth),
-            "_CON.txt"
+            "CON_.txt"
         );
         assert_eq!(
             sanitize_filename("aux", SanitizeMode::FullWidth),
-            "_aux"
+            "aux_"
         );
+        // Trailing dots/spaces are stripped before the check
+        assert_eq!(
+            sanitize_filename("nul. ", SanitizeMode::FullWidth),
+            "nul_"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_per_target() {
+        let name = "a:b/c\0d.";
+        assert_eq!(
+            sanitize_filename_for(name, SanitizeMode::Underscore, TargetFs::Windows),
+            "a_b_c_d"
+        );
+        assert_eq!(
+            sanitize_filename_for(name, SanitizeMode::Underscore, TargetFs::Posix),
+            "a:b_c_d."
+        );
+        assert_eq!(
+            sanitize_filename_for("CON.txt", SanitizeMode::Underscore, TargetFs::Posix),
+            "CON.txt"
+        );
+        assert_eq!(
+            sanitize_filename_for("..", SanitizeMode::Underscore, TargetFs::Posix),
+            "__"
+        );
+
+        let long = format!("  {}.jpg", "あ".repeat(100));
+        let portable = sanitize_filename_for(&long, SanitizeMode::Underscore, TargetFs::Portable);
+        assert!(portable.len() <= PORTABLE_MAX_BYTES);
+        assert!(portable.starts_with('あ'));
+        assert!(portable.ends_with(".jpg"));
     }
 
     #[test]
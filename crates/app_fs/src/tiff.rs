@@ -0,0 +1,117 @@
+//! Byte-order aware access to a TIFF block (the payload of a JPEG Exif segment)
+
+/// Reader over a TIFF block; offsets are relative to the block start
+#[derive(Debug, Clone, Copy)]
+pub struct TiffReader<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+impl<'a> TiffReader<'a> {
+    /// Check the byte-order mark and magic number; None if `data` is not TIFF
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        let little_endian = match data.get(0..2)? {
+            b"II" => true,
+            b"MM" => false,
+            _ => return None,
+        };
+        let reader = Self { data, little_endian };
+        (reader.u16(2)? == 42).then_some(reader)
+    }
+
+    /// Offset of the first IFD
+    pub fn ifd0(&self) -> Option<usize> {
+        self.u32(4).map(|offset| offset as usize)
+    }
+
+    /// Position of each 12-byte entry in the IFD at `ifd`
+    /// None unless the whole IFD, including the next pointer, is inside the block.
+    pub fn entries(&self, ifd: usize) -> Option<impl Iterator<Item = usize>> {
+        let count = self.u16(ifd)? as usize;
+        self.data.get(ifd..ifd + 2 + count * 12 + 4)?;
+        Some((0..count).map(move |i| ifd + 2 + i * 12))
+    }
+
+    /// (tag, position of the value field) for each entry in the IFD at `ifd`
+    pub fn fields(&self, ifd: usize) -> Option<impl Iterator<Item = (u16, usize)> + '_> {
+        Some(self.entries(ifd)?.filter_map(|entry| Some((self.u16(entry)?, entry + 8))))
+    }
+
+    /// Offset of the IFD following the one at `ifd` (0 = none)
+    pub fn next_ifd(&self, ifd: usize) -> Option<u32> {
+        let count = self.u16(ifd)? as usize;
+        self.u32(ifd + 2 + count * 12)
+    }
+
+    pub fn u8(&self, pos: usize) -> Option<u8> {
+        self.data.get(pos).copied()
+    }
+
+    pub fn bytes(&self, pos: usize, len: usize) -> Option<&'a [u8]> {
+        self.data.get(pos..pos + len)
+    }
+
+    pub fn u16(&self, pos: usize) -> Option<u16> {
+        self.data.get(pos..pos + 2).map(|b| self.u16_from(b))
+    }
+
+    /// Decode the first two bytes of `bytes` in the block's byte order
+    pub fn u16_from(&self, bytes: &[u8]) -> u16 {
+        let bytes = [bytes[0], bytes[1]];
+        if self.little_endian { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) }
+    }
+
+    pub fn u32(&self, pos: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(pos..pos + 4)?.try_into().ok()?;
+        Some(if self.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    }
+
+    /// Unsigned rational; None if the denominator is zero
+    pub fn rational(&self, pos: usize) -> Option<f64> {
+        let (num, den) = (self.u32(pos)?, self.u32(pos + 4)?);
+        (den != 0).then(|| num as f64 / den as f64)
+    }
+
+    /// Degrees/minutes/seconds (three rationals) as decimal degrees
+    pub fn degrees(&self, pos: usize) -> Option<f64> {
+        let (d, m, s) = (self.rational(pos)?, self.rational(pos + 8)?, self.rational(pos + 16)?);
+        Some(d + m / 60.0 + s / 3600.0)
+    }
+
+    /// Write `value` into `out` in the block's byte order
+    pub fn put_u16(&self, out: &mut [u8], pos: usize, value: u16) {
+        let bytes = if self.little_endian { value.to_le_bytes() } else { value.to_be_bytes() };
+        out[pos..pos + 2].copy_from_slice(&bytes);
+    }
+
+    /// Write `value` into `out` in the block's byte order
+    pub fn put_u32(&self, out: &mut [u8], pos: usize, value: u32) {
+        let bytes = if self.little_endian { value.to_le_bytes() } else { value.to_be_bytes() };
+        out[pos..pos + 4].copy_from_slice(&bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiff_reader() {
+        // Big-endian, IFD0 at 8 with one entry, next IFD at 0x20
+        let mut block = b"MM\0\x2A\0\0\0\x08".to_vec();
+        block.extend_from_slice(&[0, 1, 0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, 6, 0, 0, 0, 0, 0, 0x20]);
+        let tiff = TiffReader::new(&block).unwrap();
+        assert_eq!(tiff.ifd0(), Some(8));
+        assert_eq!(tiff.fields(8).unwrap().collect::<Vec<_>>(), [(0x0112, 18)]);
+        assert_eq!(tiff.u16(18), Some(6));
+        assert_eq!(tiff.next_ifd(8), Some(0x20));
+
+        let mut out = vec![0; 4];
+        tiff.put_u32(&mut out, 0, 0x0102_0304);
+        assert_eq!(out, [1, 2, 3, 4]);
+
+        // Truncated IFD
+        assert!(tiff.entries(block.len() - 4).is_none());
+        assert!(TiffReader::new(b"II\x2B\0").is_none());
+    }
+}
@@ -1,27 +1,33 @@
 //! UniversalPath - Safe path handling for Windows with UNC prefix support
 
 use serde::{Deserialize, Serialize};
-use std::ffi::OsString;
-use std::path::{Path, PathBuf};
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::path::{Component, Path, PathBuf};
 use xxhash_rust::xxh3::xxh3_64;
 
+/// Windows paths longer than this many UTF-16 units get the extended-length prefix
+/// (MAX_PATH is 260 including the NUL; directories must leave room for an 8.3 name)
+#[cfg(windows)]
+const LEGACY_MAX_PATH: usize = 247;
+
 /// A path wrapper that handles Windows path limitations
 ///
 /// Features:
-/// - Automatic UNC prefix (\\?\) for long path support
-/// - Lossy UTF-8 display string for UI
+/// - Automatic extended-length prefix (\\?\, \\?\UNC\) for long paths
+/// - Lossy UTF-8 display string for UI (never prefixed)
 /// - Hash-based ID for database lookups
 /// - Raw bytes preservation for non-UTF8 paths
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UniversalPath {
-    /// Raw path for file system operations (with UNC prefix on Windows)
+    /// Raw path for file system operations (extended-length prefix for long Windows paths)
     #[serde(skip)]
     raw: PathBuf,
 
     /// UTF-8 display string (lossy conversion for UI)
     display: String,
 
-    /// Hash-based ID for fast lookups
+    /// Hash-based ID for fast lookups (case-folded on Windows)
     id: u64,
 
     /// Raw bytes for database storage (preserves non-UTF8 characters)
@@ -52,14 +58,17 @@ impl UniversalPath {
     pub fn new<P: AsRef<Path>>(path: P) -> Self {
         let path = path.as_ref();
 
-        // Normalize and add UNC prefix on Windows
-        let raw = Self::normalize_path(path);
+        // Normalize (absolute, no prefix) for display and hashing
+        let normalized = Self::normalize_path(path);
 
         // Create display string (lossy UTF-8)
-        let display = raw.to_string_lossy().to_string();
+        let display = normalized.to_string_lossy().to_string();
+
+        // Long Windows paths only open with the extended-length prefix
+        let raw = Self::with_extended_prefix(normalized);
 
         // Calculate hash for DB lookups
-        let id = xxh3_64(display.as_bytes());
+        let id = xxh3_64(identity_key(&display).as_bytes());
 
         // Store raw bytes for perfect reconstruction
         let raw_bytes = Self::path_to_bytes(&raw);
@@ -83,12 +92,17 @@ impl UniversalPath {
         &self.raw
     }
 
+    /// Get the raw OsStr for file system operations
+    pub fn as_os_str(&self) -> &OsStr {
+        self.raw.as_os_str()
+    }
+
     /// Get the raw PathBuf (owned)
     pub fn to_path_buf(&self) -> PathBuf {
         self.raw.clone()
     }
 
-    /// Get the display string for UI
+    /// Get the display string for UI (without the extended-length prefix)
     pub fn display(&self) -> &str {
         &self.display
     }
@@ -138,10 +152,45 @@ impl UniversalPath {
         Self::new(self.raw.join(path))
     }
 
-    /// Normalize path and add UNC prefix on Windows
+    /// Resolve `.`/`..` segments and redundant separators lexically, without
+    /// touching the file system. `..` never climbs above the root (or UNC share),
+    /// so ids stay the same however a path was built.
+    pub fn normalize<P: AsRef<Path>>(path: P) -> PathBuf {
+        let mut normalized = PathBuf::new();
+        let mut depth = 0usize;
+        for component in path.as_ref().components() {
+            match component {
+                Component::Prefix(_) | Component::RootDir => normalized.push(component),
+                Component::CurDir => {}
+                Component::ParentDir if depth > 0 => {
+                    normalized.pop();
+                    depth -= 1;
+                }
+                // Relative paths keep leading `..`; rooted ones drop it
+                Component::ParentDir => {
+                    if !normalized.has_root() {
+                        normalized.push("..");
+                    }
+                }
+                Component::Normal(name) => {
+                    normalized.push(name);
+                    depth += 1;
+                }
+            }
+        }
+        if normalized.as_os_str().is_empty() {
+            normalized.push(".");
+        }
+        normalized
+    }
+
+    /// Normalize path (absolute, `.`/`..` resolved, extended-length prefix removed)
     #[cfg(windows)]
     fn normalize_path(path: &Path) -> PathBuf {
-        use std::path::Component;
+        use std::os::windows::ffi::{OsStrExt, OsStringExt};
+
+        let units: Vec<u16> = path.as_os_str().encode_wide().collect();
+        let path = PathBuf::from(OsString::from_wide(&strip_extended(&units)));
 
         // Convert to absolute path
         let absolute = if path.is_absolute() {
@@ -153,30 +202,32 @@ impl UniversalPath {
         };
 
         // Normalize components (resolve . and ..)
-        let mut normalized = PathBuf::new();
-        for component in absolute.components() {
-            match component {
-                Component::ParentDir => {
-                    normalized.pop();
-                }
-                Component::CurDir => {}
-                _ => normalized.push(component),
-            }
-        }
+        Self::normalize(absolute)
+    }
 
-        // Add UNC prefix if not present
-        let path_str = normalized.to_string_lossy();
-        if !path_str.starts_with(r"\\?\") && !path_str.starts_with(r"\\.\") {
-            PathBuf::from(format!(r"\\?\{}", path_str))
+    #[cfg(not(windows))]
+    fn normalize_path(path: &Path) -> PathBuf {
+        // On non-Windows, canonicalize; paths that don't exist (e.g. inside
+        // archives) are normalized lexically
+        path.canonicalize().unwrap_or_else(|_| Self::normalize(path))
+    }
+
+    /// Add the extended-length prefix to a normalized path over `LEGACY_MAX_PATH`
+    #[cfg(windows)]
+    fn with_extended_prefix(path: PathBuf) -> PathBuf {
+        use std::os::windows::ffi::{OsStrExt, OsStringExt};
+
+        let units: Vec<u16> = path.as_os_str().encode_wide().collect();
+        if units.len() > LEGACY_MAX_PATH {
+            PathBuf::from(OsString::from_wide(&to_extended(&units)))
         } else {
-            normalized
+            path
         }
     }
 
     #[cfg(not(windows))]
-    fn normalize_path(path: &Path) -> PathBuf {
-        // On non-Windows, just canonicalize
-        path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+    fn with_extended_prefix(path: PathBuf) -> PathBuf {
+        path
     }
 
     /// Convert PathBuf to raw bytes for storage
@@ -220,6 +271,62 @@ impl UniversalPath {
     }
 }
 
+/// UTF-16 units of `s`, for matching Windows path prefixes
+#[cfg_attr(not(windows), allow(dead_code))]
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().collect()
+}
+
+/// Extended-length form of an absolute Windows path, on its UTF-16 units
+/// (`C:\a` -> `\\?\C:\a`, `\\server\share` -> `\\?\UNC\server\share`)
+#[cfg_attr(not(windows), allow(dead_code))]
+fn to_extended(path: &[u16]) -> Cow<'_, [u16]> {
+    if path.starts_with(&wide(r"\\?\")) || path.starts_with(&wide(r"\\.\")) {
+        Cow::Borrowed(path)
+    } else if let Some(unc) = path.strip_prefix(wide(r"\\").as_slice()) {
+        Cow::Owned([wide(r"\\?\UNC\").as_slice(), unc].concat())
+    } else {
+        Cow::Owned([wide(r"\\?\").as_slice(), path].concat())
+    }
+}
+
+/// Inverse of `to_extended`
+#[cfg_attr(not(windows), allow(dead_code))]
+fn strip_extended(path: &[u16]) -> Cow<'_, [u16]> {
+    if let Some(unc) = path.strip_prefix(wide(r"\\?\UNC\").as_slice()) {
+        Cow::Owned([wide(r"\\").as_slice(), unc].concat())
+    } else if let Some(local) = path.strip_prefix(wide(r"\\?\").as_slice()) {
+        Cow::Borrowed(local)
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+/// Key used for path identity: Windows file systems are case-insensitive, so
+/// case variants of a path must map to the same id there (and only there)
+fn identity_key(display: &str) -> Cow<'_, str> {
+    if cfg!(windows) {
+        Cow::Owned(display.to_lowercase())
+    } else {
+        Cow::Borrowed(display)
+    }
+}
+
+/// Paths are equal when they refer to the same location (see [`UniversalPath::id`])
+impl PartialEq for UniversalPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && identity_key(&self.display) == identity_key(&other.display)
+    }
+}
+
+impl Eq for UniversalPath {}
+
+impl std::hash::Hash for UniversalPath {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
 impl AsRef<Path> for UniversalPath {
     fn as_ref(&self) -> &Path {
         &self.raw
@@ -357,28 +464,65 @@ mod tests {
     #[test]
     fn test_unc_prefix_added() {
         // Doc 2 spec: \\?\ prefix must be added for long path support
-        let path = UniversalPath::new("C:\\Users\\test\\image.jpg");
-        assert!(path.display().starts_with(r"\\?\"));
+        let short = UniversalPath::new("C:\\Users\\test\\image.jpg");
+        assert!(!short.as_os_str().to_string_lossy().starts_with(r"\\?\"));
+
+        let long = UniversalPath::new(format!("C:\\{}\\image.jpg", "a".repeat(300)));
+        assert!(long.as_os_str().to_string_lossy().starts_with(r"\\?\C:\"));
+        // The UI never sees the prefix
+        assert!(long.display().starts_with("C:\\"));
     }
 
     #[cfg(windows)]
     #[test]
     fn test_unc_prefix_not_duplicated() {
         // Should not duplicate \\?\ prefix
-        let path = UniversalPath::new(r"\\?\C:\Users\test\image.jpg");
-        let display = path.display();
+        let path = UniversalPath::new(format!(r"\\?\C:\Users\{}\image.jpg", "b".repeat(300)));
+        let raw = path.as_path().to_string_lossy();
         // Count occurrences of \\?\
-        let count = display.matches(r"\\?\").count();
+        let count = raw.matches(r"\\?\").count();
         assert_eq!(count, 1, "UNC prefix should appear exactly once");
+        assert_eq!(path.display().matches(r"\\?\").count(), 0);
     }
 
     #[cfg(windows)]
     #[test]
     fn test_unc_japanese_path() {
-        // Japanese path with UNC prefix
+        // Japanese path: same identity with or without the prefix
         let path = UniversalPath::new("C:\\ユーザー\\写真\\桜.jpg");
-        assert!(path.display().starts_with(r"\\?\"));
+        let prefixed = UniversalPath::new(r"\\?\C:\ユーザー\写真\桜.jpg");
         assert!(path.display().contains("桜.jpg"));
+        assert_eq!(path.id(), prefixed.id());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_long_unc_share() {
+        let path = UniversalPath::new(format!(r"\\server\share\{}\x.png", "c".repeat(300)));
+        assert!(path.as_path().to_string_lossy().starts_with(r"\\?\UNC\server\share\"));
+        assert!(path.display().starts_with(r"\\server\share\"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_normalize_keeps_unc_share() {
+        assert_eq!(
+            UniversalPath::normalize(r"\\server\share\a\..\..\b"),
+            PathBuf::from(r"\\server\share\b")
+        );
+        let dotted = UniversalPath::new(r"\\server\share\.\a\\b\..\c.png");
+        assert_eq!(dotted.display(), r"\\server\share\a\c.png");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_case_variants_share_id() {
+        let path1 = UniversalPath::new(r"C:\Photos\a.jpg");
+        let path2 = UniversalPath::new(r"c:\photos\A.JPG");
+        assert_eq!(path1.id(), path2.id());
+        assert_eq!(path1, path2);
+        // Display keeps the original spelling
+        assert!(path2.display().ends_with("A.JPG"));
     }
 
     #[cfg(windows)]
@@ -428,4 +572,70 @@ mod tests {
         assert!(path.display().contains("My Documents"));
         assert_eq!(path.file_name(), Some("photo 001.jpg"));
     }
+
+    #[test]
+    fn test_extended_prefix_forms() {
+        let extended = |p: &str| String::from_utf16(&to_extended(&wide(p))).unwrap();
+        let stripped = |p: &str| String::from_utf16(&strip_extended(&wide(p))).unwrap();
+        assert_eq!(extended(r"C:\a\b"), r"\\?\C:\a\b");
+        assert_eq!(extended(r"\\server\share\a"), r"\\?\UNC\server\share\a");
+        assert_eq!(extended(r"\\?\C:\a"), r"\\?\C:\a");
+        assert_eq!(extended(r"\\.\pipe\x"), r"\\.\pipe\x");
+        assert_eq!(stripped(r"\\?\UNC\server\share\a"), r"\\server\share\a");
+        assert_eq!(stripped(r"\\?\C:\a"), r"C:\a");
+        assert_eq!(stripped(r"C:\a"), r"C:\a");
+
+        // Unpaired surrogates (valid in Windows file names) survive the round trip
+        let mut lone = wide(r"\\?\C:\a");
+        lone.push(0xD800);
+        assert_eq!(strip_extended(&lone).as_ref(), [wide(r"C:\a").as_slice(), &[0xD800]].concat());
+        assert_eq!(to_extended(&strip_extended(&lone)).as_ref(), lone.as_slice());
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_normalize_dotted_segments() {
+        assert_eq!(UniversalPath::normalize("/a/./b//c/../d"), PathBuf::from("/a/b/d"));
+        assert_eq!(UniversalPath::normalize("/../a"), PathBuf::from("/a"));
+        assert_eq!(UniversalPath::normalize("../a/./b/.."), PathBuf::from("../a"));
+        assert_eq!(UniversalPath::normalize("a/.."), PathBuf::from("."));
+
+        // Same logical location inside an archive gets the same id
+        let joined = UniversalPath::new("/no/such/book.zip").join("pages/../cover.png");
+        let direct = UniversalPath::new("/no/such//book.zip/./cover.png");
+        assert_eq!(joined.display(), "/no/such/book.zip/cover.png");
+        assert_eq!(joined.id(), direct.id());
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_case_variants_distinct_on_unix() {
+        let path1 = UniversalPath::new("/no/such/Photos/a.jpg");
+        let path2 = UniversalPath::new("/no/such/photos/A.JPG");
+        assert_ne!(path1.id(), path2.id());
+        assert_ne!(path1, path2);
+        assert_eq!(path1, UniversalPath::new("/no/such/Photos/./a.jpg"));
+    }
+
+    #[test]
+    fn test_long_path_open_and_list() {
+        // Nest folders until the full path is well past the legacy 260-char limit
+        let dir = tempfile::tempdir().unwrap();
+        let mut deep = dir.path().to_path_buf();
+        while deep.as_os_str().len() <= 300 {
+            deep.push("a_rather_long_folder_name_for_testing_long_paths");
+        }
+        let deep = UniversalPath::new(&deep);
+        std::fs::create_dir_all(deep.as_path()).unwrap();
+
+        let file = deep.join("image.txt");
+        assert!(file.display().chars().count() > 260);
+        std::fs::write(file.as_path(), b"long").unwrap();
+        assert_eq!(std::fs::read(file.as_path()).unwrap(), b"long");
+
+        let entries = crate::list_directory(deep.as_path(), &crate::ListOptions::default()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "image.txt");
+        assert_eq!(entries[0].path.id(), file.id());
+    }
 }
@@ -2,7 +2,17 @@
 
 use crate::{FsError, Result, UniversalPath, encoding};
 use serde::{Deserialize, Serialize};
-use std::io::Read;
+use std::io::{self, Read};
+use std::path::Path;
+use std::collections::HashSet;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::OnceLock;
+
+/// Size of the chunks handed from the extraction thread to an [`EntryReader`]
+const STREAM_CHUNK: usize = 256 * 1024;
+
+/// Chunks buffered ahead of the reader (bounds memory to about 1 MB)
+const STREAM_DEPTH: usize = 4;
 
 /// Error type for VFS operations
 #[derive(Debug, thiserror::Error)]
@@ -48,13 +58,135 @@ pub struct VfsEntry {
     pub modified: Option<i64>,
 }
 
+impl VfsEntry {
+    /// Direct children of `dir` ("" = archive root) among a flat entry list
+    ///
+    /// Folders that only appear as a prefix of file paths are synthesized as
+    /// directory entries. Files keep their stored `path` so they can be read back.
+    pub fn children(entries: &[VfsEntry], dir: &str) -> Vec<VfsEntry> {
+        let dir = normalize_inner_path(dir);
+        let mut seen_dirs = HashSet::new();
+        let mut children = Vec::new();
+
+        for entry in entries {
+            let path = normalize_inner_path(&entry.path);
+            let relative = if dir.is_empty() {
+                path.as_str()
+            } else {
+                match path.strip_prefix(dir.as_str()).and_then(|rest| rest.strip_prefix('/')) {
+                    Some(rest) => rest,
+                    None => continue,
+                }
+            };
+            if relative.is_empty() {
+                continue;
+            }
+
+            match relative.split_once('/') {
+                // Deeper entry: its first segment is a folder here
+                Some((name, _)) => {
+                    if seen_dirs.insert(name.to_string()) {
+                        children.push(VfsEntry::directory(&dir, name));
+                    }
+                }
+                None if entry.is_dir => {
+                    if seen_dirs.insert(relative.to_string()) {
+                        // Stored folder names end in '/', so the name is rebuilt too
+                        children.push(VfsEntry {
+                            name: relative.to_string(),
+                            path: join_inner_path(&dir, relative),
+                            ..entry.clone()
+                        });
+                    }
+                }
+                None => children.push(entry.clone()),
+            }
+        }
+
+        children
+    }
+
+    fn directory(parent: &str, name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            path: join_inner_path(parent, name),
+            size: 0,
+            compressed_size: None,
+            is_dir: true,
+            modified: None,
+        }
+    }
+}
+
+/// Inner path with `/` separators and no leading `./` or trailing `/`
+fn normalize_inner_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    path.trim_start_matches("./").trim_matches('/').to_string()
+}
+
+fn join_inner_path(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+/// Parent of an inner directory path ("" at the archive root)
+pub fn inner_parent(dir: &str) -> &str {
+    dir.trim_end_matches('/').rsplit_once('/').map(|(parent, _)| parent).unwrap_or("")
+}
+
+/// Streaming reader for an archive entry
+///
+/// A background thread keeps the archive open and decompresses ahead of the
+/// reader; dropping the reader stops it. Errors (including a missing entry)
+/// surface from `read`.
+pub struct EntryReader {
+    /// Chunks of the entry, then `None` once all of it has been read
+    chunks: Receiver<io::Result<Option<Vec<u8>>>>,
+    chunk: Vec<u8>,
+    pos: usize,
+    finished: bool,
+}
+
+impl Read for EntryReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            match self.chunks.recv() {
+                Ok(Ok(Some(chunk))) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                Ok(Ok(None)) => self.finished = true,
+                Ok(Err(e)) => return Err(e),
+                // Extraction thread went away before the end of the entry
+                Err(_) => {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "archive entry stream ended early"));
+                }
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
 /// Virtual File System abstraction
+#[derive(Clone)]
 pub struct VirtualFileSystem {
     /// Archive path
     archive_path: UniversalPath,
 
     /// Archive format
     format: ArchiveFormat,
+
+    /// Flat entry list, read once for directory browsing
+    entries: OnceLock<Vec<VfsEntry>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -78,6 +210,7 @@ impl VirtualFileSystem {
         Ok(Self {
             archive_path: path,
             format,
+            entries: OnceLock::new(),
         })
     }
 
@@ -99,6 +232,20 @@ impl VirtualFileSystem {
         }
     }
 
+    /// Path of the opened archive
+    pub fn archive_path(&self) -> &UniversalPath {
+        &self.archive_path
+    }
+
+    pub fn format(&self) -> ArchiveFormat {
+        self.format
+    }
+
+    /// Use entries listed elsewhere (Susie archives are listed by the bridge)
+    pub fn set_entries(&self, entries: Vec<VfsEntry>) {
+        let _ = self.entries.set(entries);
+    }
+
     /// List all entries in the archive
     pub fn list_entries(&self) -> Result<Vec<VfsEntry>> {
         match self.format {
@@ -113,13 +260,85 @@ impl VirtualFileSystem {
         }
     }
 
+    /// List the folders and files directly inside `inner_dir` ("" = root)
+    pub fn list_dir(&self, inner_dir: &str) -> Result<Vec<VfsEntry>> {
+        let entries = match self.entries.get() {
+            Some(entries) => entries,
+            None => {
+                let _ = self.entries.set(self.list_entries()?);
+                self.entries.get().expect("entries were just set")
+            }
+        };
+        Ok(VfsEntry::children(entries, inner_dir))
+    }
+
     /// Read a file from the archive
     pub fn read_file(&self, inner_path: &str) -> Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.with_entry(inner_path, |reader| reader.read_to_end(&mut buffer).map(|_| ()))?;
+        Ok(buffer)
+    }
+
+    /// Stream a file from the archive without buffering it whole in memory
+    pub fn read_file_streaming(&self, inner_path: &str) -> Result<EntryReader> {
+        if self.format == ArchiveFormat::Susie {
+            return Err(FsError::Archive("Susie archives require Bridge process".into()));
+        }
+
+        let (tx, rx) = mpsc::sync_channel(STREAM_DEPTH);
+        let vfs = self.clone();
+        let inner_path = inner_path.to_string();
+        std::thread::Builder::new()
+            .name("archive-stream".into())
+            .spawn(move || {
+                let result = vfs.with_entry(&inner_path, |reader| loop {
+                    let mut chunk = vec![0; STREAM_CHUNK];
+                    let n = reader.read(&mut chunk)?;
+                    if n == 0 {
+                        let _ = tx.send(Ok(None));
+                        return Ok(());
+                    }
+                    chunk.truncate(n);
+                    // The reader was dropped; stop decompressing
+                    if tx.send(Ok(Some(chunk))).is_err() {
+                        return Ok(());
+                    }
+                });
+                if let Err(e) = result {
+                    let _ = tx.send(Err(io::Error::other(e.to_string())));
+                }
+            })?;
+
+        Ok(EntryReader {
+            chunks: rx,
+            chunk: Vec::new(),
+            pos: 0,
+            finished: false,
+        })
+    }
+
+    /// Extract a file from the archive to `dest`, streaming through a small buffer
+    pub fn extract_to(&self, inner_path: &str, dest: &Path) -> Result<u64> {
+        let mut copied = 0;
+        self.with_entry(inner_path, |reader| {
+            let mut file = std::fs::File::create(dest)?;
+            copied = io::copy(reader, &mut file)?;
+            Ok(())
+        })?;
+        Ok(copied)
+    }
+
+    /// Open the archive and run `f` on a reader for `inner_path`
+    /// The archive stays open until `f` returns.
+    fn with_entry<F>(&self, inner_path: &str, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut dyn Read) -> io::Result<()>,
+    {
         match self.format {
-            ArchiveFormat::Zip => self.read_zip_file(inner_path),
-            ArchiveFormat::SevenZip => self.read_7z_file(inner_path),
+            ArchiveFormat::Zip => self.with_zip_entry(inner_path, f),
+            ArchiveFormat::SevenZip => self.with_7z_entry(inner_path, f),
             ArchiveFormat::Tar | ArchiveFormat::TarGz | ArchiveFormat::TarBz2 => {
-                self.read_tar_file(inner_path)
+                self.with_tar_entry(inner_path, f)
             }
             ArchiveFormat::Susie => {
                 Err(FsError::Archive("Susie archives require Bridge process".into()))
@@ -181,7 +400,10 @@ impl VirtualFileSystem {
         Ok(entries)
     }
 
-    fn read_zip_file(&self, inner_path: &str) -> Result<Vec<u8>> {
+    fn with_zip_entry<F>(&self, inner_path: &str, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut dyn Read) -> io::Result<()>,
+    {
         let file = std::fs::File::open(self.archive_path.as_path())?;
         let mut archive = zip::ZipArchive::new(file)
             .map_err(|e| FsError::Archive(e.to_string()))?;
@@ -189,10 +411,8 @@ impl VirtualFileSystem {
         let mut zip_file = archive.by_name(inner_path)
             .map_err(|e| FsError::Archive(e.to_string()))?;
 
-        let mut buffer = Vec::with_capacity(zip_file.size() as usize);
-        zip_file.read_to_end(&mut buffer)?;
-
-        Ok(buffer)
+        f(&mut zip_file)?;
+        Ok(())
     }
 
     // 7z implementation
@@ -218,17 +438,21 @@ impl VirtualFileSystem {
         Ok(entries)
     }
 
-    fn read_7z_file(&self, inner_path: &str) -> Result<Vec<u8>> {
-        let mut result: Option<Vec<u8>> = None;
+    fn with_7z_entry<F>(&self, inner_path: &str, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut dyn Read) -> io::Result<()>,
+    {
+        let mut f = Some(f);
+        let mut result: Option<io::Result<()>> = None;
 
         sevenz_rust::decompress_file_with_extract_fn(
             self.archive_path.as_path(),
             std::path::Path::new(""),
             |entry, reader, _| {
                 if entry.name() == inner_path {
-                    let mut buffer = Vec::new();
-                    reader.read_to_end(&mut buffer)?;
-                    result = Some(buffer);
+                    if let Some(f) = f.take() {
+                        result = Some(f(reader));
+                    }
                     Ok(false) // Stop extraction
                 } else {
                     Ok(true) // Continue
@@ -236,7 +460,10 @@ impl VirtualFileSystem {
             },
         ).map_err(|e| FsError::Archive(e.to_string()))?;
 
-        result.ok_or_else(|| FsError::Archive(format!("File not found: {}", inner_path)))
+        match result {
+            Some(result) => Ok(result?),
+            None => Err(FsError::Archive(format!("File not found: {}", inner_path))),
+        }
     }
 
     // TAR implementation (with optional compression)
@@ -274,7 +501,10 @@ impl VirtualFileSystem {
         Ok(entries)
     }
 
-    fn read_tar_file(&self, inner_path: &str) -> Result<Vec<u8>> {
+    fn with_tar_entry<F>(&self, inner_path: &str, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut dyn Read) -> io::Result<()>,
+    {
         let file = std::fs::File::open(self.archive_path.as_path())?;
         let reader: Box<dyn Read> = match self.format {
             ArchiveFormat::TarGz => Box::new(flate2::read::GzDecoder::new(file)),
@@ -288,9 +518,8 @@ impl VirtualFileSystem {
             let path = entry.path()?;
 
             if path.to_string_lossy() == inner_path {
-                let mut buffer = Vec::with_capacity(entry.size() as usize);
-                entry.read_to_end(&mut buffer)?;
-                return Ok(buffer);
+                f(&mut entry)?;
+                return Ok(());
             }
         }
 
@@ -316,4 +545,98 @@ mod tests {
         let format = VirtualFileSystem::detect_format(&path).unwrap();
         assert_eq!(format, ArchiveFormat::SevenZip);
     }
+
+    fn file(path: &str) -> VfsEntry {
+        VfsEntry {
+            name: path.rsplit('/').next().unwrap().to_string(),
+            path: path.to_string(),
+            size: 1,
+            compressed_size: None,
+            is_dir: false,
+            modified: None,
+        }
+    }
+
+    #[test]
+    fn test_children_synthesize_dirs() {
+        let mut stored_dir = file("b/");
+        stored_dir.is_dir = true;
+        let entries = vec![
+            file("cover.jpg"),
+            file("a/01.jpg"),
+            file("a/sub/02.jpg"),
+            stored_dir,
+            file("b/03.jpg"),
+            file("./a/04.jpg"),
+        ];
+
+        let root = VfsEntry::children(&entries, "");
+        let names: Vec<_> = root.iter().map(|e| (e.name.as_str(), e.is_dir)).collect();
+        assert_eq!(names, vec![("cover.jpg", false), ("a", true), ("b", true)]);
+        assert_eq!(root[1].path, "a");
+
+        let a = VfsEntry::children(&entries, "a");
+        let paths: Vec<_> = a.iter().map(|e| e.path.as_str()).collect();
+        // Files keep their stored path for read_file
+        assert_eq!(paths, vec!["a/01.jpg", "a/sub", "./a/04.jpg"]);
+
+        assert_eq!(VfsEntry::children(&entries, "a/sub/").len(), 1);
+        assert_eq!(inner_parent("a/sub"), "a");
+        assert_eq!(inner_parent("a"), "");
+    }
+
+    #[test]
+    fn test_susie_entries_from_bridge() {
+        let vfs = VirtualFileSystem::open(UniversalPath::new("test.lzh")).unwrap();
+        assert_eq!(vfs.format(), ArchiveFormat::Susie);
+        assert!(vfs.list_dir("").is_err());
+
+        vfs.set_entries(vec![file("a/01.jpg"), file("cover.jpg")]);
+        let names: Vec<_> = vfs.list_dir("").unwrap().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["a", "cover.jpg"]);
+    }
+
+    #[test]
+    fn test_streaming_matches_read_file() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let archive_path = dir.path().join("big.zip");
+        // Larger than a few chunks so the reader has to wait on the thread
+        let data: Vec<u8> = (0..STREAM_CHUNK * 3 + 17).map(|i| (i % 251) as u8).collect();
+        {
+            let mut writer = zip::ZipWriter::new(std::fs::File::create(&archive_path).unwrap());
+            writer.start_file("dir/movie.bin", zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(&data).unwrap();
+            writer.finish().unwrap();
+        }
+
+        let vfs = VirtualFileSystem::open(archive_path.as_path()).unwrap();
+        let mut streamed = Vec::new();
+        vfs.read_file_streaming("dir/movie.bin").unwrap().read_to_end(&mut streamed).unwrap();
+        assert_eq!(streamed, data);
+        assert_eq!(vfs.read_file("dir/movie.bin").unwrap(), data);
+
+        let dest = dir.path().join("movie.bin");
+        assert_eq!(vfs.extract_to("dir/movie.bin", &dest).unwrap(), data.len() as u64);
+        assert_eq!(std::fs::read(&dest).unwrap(), data);
+
+        // A missing entry reports an error from read
+        let mut missing = vfs.read_file_streaming("nope").unwrap();
+        assert!(missing.read_to_end(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_streaming_cut_short_is_eof_error() {
+        let (tx, rx) = mpsc::sync_channel(STREAM_DEPTH);
+        let mut reader = EntryReader { chunks: rx, chunk: Vec::new(), pos: 0, finished: false };
+        tx.send(Ok(Some(vec![1, 2, 3]))).unwrap();
+        // Extraction thread gone without sending the end marker
+        drop(tx);
+
+        let mut buf = Vec::new();
+        let err = reader.read_to_end(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(buf, vec![1, 2, 3]);
+    }
 }
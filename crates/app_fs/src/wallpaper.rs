@@ -0,0 +1,128 @@
+//! Desktop wallpaper (file.set_wallpaper)
+//! Windows sets it through SystemParametersInfoW, macOS through System Events,
+//! and other systems through GNOME's gsettings or feh.
+
+use crate::file_operations::{FileOpError, Result, WallpaperStyle};
+use std::path::Path;
+
+/// Run a helper program, turning a failure exit into an error with its output
+fn run(cmd: &mut std::process::Command) -> Result<()> {
+    let output = cmd.output()?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    Err(FileOpError::InvalidOperation(if stderr.is_empty() {
+        format!("{:?} failed ({})", cmd.get_program(), output.status)
+    } else {
+        stderr
+    }))
+}
+
+#[cfg(target_os = "windows")]
+pub fn set(path: &Path, style: WallpaperStyle) -> Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::process::CommandExt;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SPIF_SENDCHANGE, SPIF_UPDATEINIFILE, SPI_SETDESKWALLPAPER,
+    };
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    // The style is read from the registry when the wallpaper is applied
+    let value = match style {
+        WallpaperStyle::Fit => "6",
+        WallpaperStyle::Fill => "10",
+        WallpaperStyle::Stretch => "2",
+    };
+    for (name, data) in [("WallpaperStyle", value), ("TileWallpaper", "0")] {
+        let mut reg = std::process::Command::new("reg");
+        reg.args(["add", r"HKCU\Control Panel\Desktop", "/v", name, "/t", "REG_SZ", "/d", data, "/f"])
+            .creation_flags(CREATE_NO_WINDOW);
+        if let Err(e) = run(&mut reg) {
+            tracing::warn!("Failed to set {}: {}", name, e);
+        }
+    }
+
+    let mut wide: Vec<u16> = path.as_os_str().encode_wide().collect();
+    wide.push(0);
+    unsafe {
+        SystemParametersInfoW(
+            SPI_SETDESKWALLPAPER,
+            0,
+            Some(wide.as_mut_ptr().cast()),
+            SPIF_UPDATEINIFILE | SPIF_SENDCHANGE,
+        )
+    }
+    .map_err(std::io::Error::from)?;
+    Ok(())
+}
+
+/// Every desktop gets the picture; the style is left to the system
+#[cfg(target_os = "macos")]
+pub fn set(path: &Path, _style: WallpaperStyle) -> Result<()> {
+    let quoted = path.to_string_lossy().replace('\\', "\\\\").replace('"', "\\\"");
+    let script = format!(
+        "tell application \"System Events\" to tell every desktop to set picture to POSIX file \"{}\"",
+        quoted
+    );
+    run(std::process::Command::new("osascript").args(["-e", &script]))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn set(path: &Path, style: WallpaperStyle) -> Result<()> {
+    use crate::file_operations::find_executable;
+
+    if find_executable("gsettings").is_some() {
+        let uri = file_uri(path);
+        let options = match style {
+            WallpaperStyle::Fit => "scaled",
+            WallpaperStyle::Fill => "zoom",
+            WallpaperStyle::Stretch => "stretched",
+        };
+        let gsettings = |key: &str, value: &str| {
+            run(std::process::Command::new("gsettings").args(["set", "org.gnome.desktop.background", key, value]))
+        };
+        gsettings("picture-uri", &uri)?;
+        // Only GNOME 42 and later have a separate dark-mode picture
+        let _ = gsettings("picture-uri-dark", &uri);
+        return gsettings("picture-options", options);
+    }
+
+    if find_executable("feh").is_some() {
+        let flag = match style {
+            WallpaperStyle::Fit => "--bg-max",
+            WallpaperStyle::Fill => "--bg-fill",
+            WallpaperStyle::Stretch => "--bg-scale",
+        };
+        return run(std::process::Command::new("feh").arg(flag).arg(path));
+    }
+
+    Err(FileOpError::Unsupported("setting the wallpaper needs gsettings (GNOME) or feh".to_string()))
+}
+
+/// file:// URI with everything but unreserved characters and '/' percent-encoded
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn file_uri(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut uri = String::from("file://");
+    for &byte in path.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+#[cfg(all(test, not(any(target_os = "windows", target_os = "macos"))))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_uri() {
+        assert_eq!(file_uri(Path::new("/photos/a.jpg")), "file:///photos/a.jpg");
+        assert_eq!(file_uri(Path::new("/my photos/夏#1.jpg")), "file:///my%20photos/%E5%A4%8F%231.jpg");
+    }
+}
@@ -2,9 +2,16 @@
 
 use notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_mini::{new_debouncer, DebouncedEvent, Debouncer};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, Receiver};
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// Quiet period after which a burst of events is delivered
+const COALESCE_WINDOW: Duration = Duration::from_millis(200);
+
+/// A burst that never goes quiet is still delivered after this many windows
+const COALESCE_MAX_WINDOWS: u32 = 5;
 
 /// File system event types
 ///
@@ -23,11 +30,94 @@ pub enum FsEvent {
     Renamed { from: PathBuf, to: PathBuf },
 }
 
+/// Coalesces bursts of watcher events: events for the same path are merged into one
+/// effective event and the whole burst is released once it has been quiet for `window`
+pub struct EventCoalescer {
+    window: Duration,
+    /// Merged events in arrival order (`None` where a pair cancelled out)
+    pending: Vec<Option<FsEvent>>,
+    /// Path -> slot in `pending`
+    index: HashMap<PathBuf, usize>,
+    first_event: Option<Instant>,
+    last_event: Option<Instant>,
+}
+
+impl EventCoalescer {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: Vec::new(),
+            index: HashMap::new(),
+            first_event: None,
+            last_event: None,
+        }
+    }
+
+    /// Add an event received at `now`
+    pub fn push(&mut self, event: FsEvent, now: Instant) {
+        self.first_event.get_or_insert(now);
+        self.last_event = Some(now);
+
+        let path = match &event {
+            FsEvent::Created(path) | FsEvent::Modified(path) | FsEvent::Removed(path) => path.clone(),
+            // Renames are delivered as they are
+            FsEvent::Renamed { .. } => {
+                self.pending.push(Some(event));
+                return;
+            }
+        };
+
+        match self.index.get(&path).copied() {
+            Some(slot) => {
+                let merged = Self::merge(self.pending[slot].take(), event);
+                if merged.is_none() {
+                    self.index.remove(&path);
+                }
+                self.pending[slot] = merged;
+            }
+            None => {
+                self.index.insert(path, self.pending.len());
+                self.pending.push(Some(event));
+            }
+        }
+    }
+
+    /// Take the coalesced events once the burst is over (empty while it is still going)
+    pub fn drain_ready(&mut self, now: Instant) -> Vec<FsEvent> {
+        let (Some(first), Some(last)) = (self.first_event, self.last_event) else {
+            return Vec::new();
+        };
+        let quiet = now.duration_since(last) >= self.window;
+        let overdue = now.duration_since(first) >= self.window * COALESCE_MAX_WINDOWS;
+        if !quiet && !overdue {
+            return Vec::new();
+        }
+
+        self.first_event = None;
+        self.last_event = None;
+        self.index.clear();
+        self.pending.drain(..).flatten().collect()
+    }
+
+    /// Effective event for a path that saw `previous` and then `next`
+    fn merge(previous: Option<FsEvent>, next: FsEvent) -> Option<FsEvent> {
+        match (previous, next) {
+            // Temporary file: never visible
+            (Some(FsEvent::Created(_)), FsEvent::Removed(_)) => None,
+            (Some(FsEvent::Created(path)), FsEvent::Modified(_)) => Some(FsEvent::Created(path)),
+            // Replaced in place
+            (Some(FsEvent::Removed(_)), FsEvent::Created(path)) => Some(FsEvent::Modified(path)),
+            (_, next) => Some(next),
+        }
+    }
+}
+
 /// File system watcher with debouncing
 pub struct FileWatcher {
     debouncer: Debouncer<RecommendedWatcher>,
     event_rx: Receiver<Result<Vec<DebouncedEvent>, notify::Error>>,
     watched_paths: Vec<PathBuf>,
+    coalescer: EventCoalescer,
 }
 
 impl FileWatcher {
@@ -44,6 +134,7 @@ impl FileWatcher {
             debouncer,
             event_rx: rx,
             watched_paths: Vec::new(),
+            coalescer: EventCoalescer::new(COALESCE_WINDOW),
         })
     }
 
@@ -55,6 +146,15 @@ impl FileWatcher {
         Ok(())
     }
 
+    /// Watch a path and everything below it
+    /// Every subfolder costs a watch on some platforms, so keep this to small subtrees
+    pub fn watch_recursive(&mut self, path: &Path) -> Result<(), notify::Error> {
+        self.debouncer.watcher().watch(path, RecursiveMode::Recursive)?;
+        self.watched_paths.push(path.to_path_buf());
+        tracing::info!("Watching recursively: {}", path.display());
+        Ok(())
+    }
+
     /// Stop watching a path
     pub fn unwatch(&mut self, path: &Path) -> Result<(), notify::Error> {
         self.debouncer.watcher().unwatch(path)?;
@@ -64,15 +164,16 @@ impl FileWatcher {
     }
 
     /// Poll for file system events (non-blocking)
-    pub fn poll_events(&self) -> Vec<FsEvent> {
-        let mut events = Vec::new();
+    /// Bursts are coalesced: at most one event per path, delivered once the burst is over
+    pub fn poll_events(&mut self) -> Vec<FsEvent> {
+        let now = Instant::now();
 
         while let Ok(result) = self.event_rx.try_recv() {
             match result {
                 Ok(debounced_events) => {
                     for event in debounced_events {
                         if let Some(fs_event) = Self::convert_event(event) {
-                            events.push(fs_event);
+                            self.coalescer.push(fs_event, now);
                         }
                     }
                 }
@@ -82,15 +183,7 @@ impl FileWatcher {
             }
         }
 
-        // Deduplication: remove consecutive Modified events for the same path
-        events.dedup_by(|a, b| {
-            match (a, b) {
-                (FsEvent::Modified(p1), FsEvent::Modified(p2)) => p1 == p2,
-                _ => false,
-            }
-        });
-
-        events
+        self.coalescer.drain_ready(now)
     }
 
     /// Convert debounced event to FsEvent
@@ -140,4 +233,36 @@ mod tests {
         let watcher = FileWatcher::new();
         assert!(watcher.is_ok());
     }
+
+    #[test]
+    fn test_event_coalescer() {
+        let window = Duration::from_millis(200);
+        let mut coalescer = EventCoalescer::new(window);
+        let start = Instant::now();
+        let a = PathBuf::from("a.png");
+        let b = PathBuf::from("b.png");
+        let tmp = PathBuf::from("b.tmp");
+        let old = PathBuf::from("old.png");
+
+        coalescer.push(FsEvent::Created(a.clone()), start);
+        coalescer.push(FsEvent::Modified(a.clone()), start);
+        coalescer.push(FsEvent::Created(tmp.clone()), start);
+        coalescer.push(FsEvent::Removed(tmp), start);
+        coalescer.push(FsEvent::Renamed { from: old.clone(), to: b.clone() }, start);
+        coalescer.push(FsEvent::Modified(b.clone()), start + Duration::from_millis(150));
+
+        // Still inside the burst
+        assert!(coalescer.drain_ready(start + Duration::from_millis(300)).is_empty());
+
+        let events = coalescer.drain_ready(start + Duration::from_millis(350));
+        assert_eq!(
+            events,
+            vec![
+                FsEvent::Created(a),
+                FsEvent::Renamed { from: old, to: b.clone() },
+                FsEvent::Modified(b),
+            ]
+        );
+        assert!(coalescer.drain_ready(start + Duration::from_secs(5)).is_empty());
+    }
 }
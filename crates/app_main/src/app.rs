@@ -2,17 +2,19 @@
 //! Integrated with Doc 3 command system
 
 use anyhow::Result;
-use app_core::{state, is_supported_image, Command, CommandId, NavigationState, ThumbnailManager, ThumbnailSize};
-use app_db::{MetadataDb, ThumbnailCache, DbPool};
-use app_fs::{UniversalPath, FileEntry, ListOptions, list_directory, get_parent, is_root, get_next_sibling, get_prev_sibling, count_files, FileOperations, DefaultFileOperations, ClipboardMode, VirtualFileSystem, FileWatcher, FsEvent};
+use app_core::{state, GpsPosition, TimelineGrouping, is_supported_image, get_image_dimensions, LruCache, Command, CommandId, NavigationContext, NavigationState, NavFileEntry, ThumbnailManager, read_exif_info, read_exif_block, encode_image, resize_image, Interpolation, ConvertOptions, ConvertOutcome, ConflictPolicy, AnimationFrame, animation::{is_apng, decode_apng}, RenameSource, TiffPages, tiff_pages::is_tiff, ThumbnailSize, Session, AppConfig, command_registry, BridgeSupervisor, BridgeLauncher, ProcessConnection, NamedSharedMemory, BridgeHealth, BridgeHealthHandle, spawn_heartbeat};
+use app_db::{MetadataDb, SidecarExport, ThumbnailCache, DbPool, FolderSort};
+use app_fs::{UniversalPath, DirectoryListing, FileEntry, ListOptions, SortBy, SortOrder, list_directory, sort_entries, get_parent, is_root, get_next_sibling, get_prev_sibling, count_files, FileOperations, DefaultFileOperations, FileOpError, ClipboardMode, FileAttributes, WallpaperStyle, VirtualFileSystem, VfsEntry, ArchiveFormat, inner_parent, FileWatcher, FsEvent, decode_bytes, encoding_name, system_encoding_hint, SanitizeMode, TargetFs, sanitize_filename_for};
 use app_ui::{
-    components::{FileBrowser, ImageViewer, StatusBar, StatusInfo, Toolbar, ToolbarAction, ToolbarState, SortMode, BrowserAction, BrowserViewMode, SettingsDialog, SettingsAction, ViewerAction, Dialog, DialogResult, ConfirmDialog, RenameDialog, NewFolderDialog, TagEditDialog, SpreadViewer, SpreadMode, SpreadLayout, SplitView, SplitDirection, ImageTransform, ViewerBackground, PageTransition, Slideshow, FolderTree, FolderTreeAction, ThumbnailCatalog, ThumbnailItem, CatalogAction, NavigateDirection},
-    InputHandler, Renderer, Theme,
+    components::{FileBrowser, ImageViewer, StatusBar, StatusInfo, Toolbar, ToolbarAction, ToolbarState, SortMode, BrowserAction, BrowserViewMode, SettingsDialog, SettingsAction, ViewerAction, Dialog, DialogResult, ConfirmDialog, RenameDialog, NewFolderDialog, TagEditDialog, CommentDialog, ExportMarksDialog, ExportMarksAction, PropertiesDialog, PropertiesAction, BatchRenameDialog, SaveAsDialog, SaveAsRequest, ExportDialog, ExportRequest, BatchConvertDialog, BatchConvertSummary, AdjustPanel, Loupe, Eyedropper, ColorSample, RecentFoldersMenu, OpenWithMenu, SpreadViewer, SpreadMode, SpreadLayout, SplitView, SplitDirection, ImageTransform, ViewerBackground, PageTransition, TransitionType, Slideshow, FolderTree, FolderTreeAction, BookmarkEntry, ThumbnailCatalog, ThumbnailItem, CatalogAction, CatalogFilter, NavigateDirection, sort_header, HistogramOverlay, TagPanel, TagEntry, TagPanelAction, DuplicateReview, DuplicateFile, CommandPalette, PaletteEntry, TrashView, TrashAction},
+    InputHandler, TypeAhead, Renderer, Theme,
 };
 use egui_wgpu::ScreenDescriptor;
-use std::collections::{HashSet, HashMap};
+use chrono::{Local, NaiveDate, TimeZone};
+use std::collections::{HashSet, HashMap, VecDeque};
 use std::sync::Arc;
-use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::path::{Path, PathBuf};
 use winit::{
     application::ApplicationHandler,
     event::{ElementState, WindowEvent},
@@ -20,9 +22,553 @@ use winit::{
     window::{Window, WindowId},
 };
 
+/// How often thumbnail caches are pruned to their configured limits
+const THUMBNAIL_PRUNE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Maximum number of file name search results
+const SEARCH_RESULT_LIMIT: usize = 1000;
+
+/// Maximum number of files in the Recent view
+const RECENT_VIEW_LIMIT: usize = 500;
+
+/// Folders that take longer than this to list keep loading in the background
+const SYNC_LISTING_BUDGET: std::time::Duration = std::time::Duration::from_millis(150);
+
+/// Long edge of the downscaled copy adjusted while a slider is dragged
+const ADJUST_PREVIEW_SIZE: u32 = 1024;
+
+/// Window title, also the suffix of per-file titles
+const APP_TITLE: &str = "LightningFiler";
+
+/// Work running on a background thread, with a progress count and a cancel flag
+struct BackgroundJob<T> {
+    total: usize,
+    progress: JobProgress,
+    /// Set when the worker returns
+    result: Arc<std::sync::Mutex<Option<T>>>,
+}
+
+/// Progress reporting and cancellation shared with a job's worker
+#[derive(Clone)]
+struct JobProgress {
+    done: Arc<AtomicUsize>,
+    cancel: Arc<AtomicBool>,
+    egui_ctx: egui::Context,
+}
+
+impl JobProgress {
+    /// Count one finished item and repaint to show it
+    fn step(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+        self.egui_ctx.request_repaint();
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+impl<T: Send + 'static> BackgroundJob<T> {
+    /// Run `work` on a new thread; it reports up to `total` steps
+    fn spawn(total: usize, egui_ctx: &egui::Context, work: impl FnOnce(&JobProgress) -> T + Send + 'static) -> Self {
+        let progress = JobProgress {
+            done: Arc::new(AtomicUsize::new(0)),
+            cancel: Arc::new(AtomicBool::new(false)),
+            egui_ctx: egui_ctx.clone(),
+        };
+        let result = Arc::new(std::sync::Mutex::new(None));
+
+        let worker_progress = progress.clone();
+        let worker_result = result.clone();
+        std::thread::spawn(move || {
+            let value = work(&worker_progress);
+            if let Ok(mut result) = worker_result.lock() {
+                *result = Some(value);
+            }
+            worker_progress.egui_ctx.request_repaint();
+        });
+
+        Self { total, progress, result }
+    }
+
+    /// (steps done, total steps)
+    fn progress(&self) -> (usize, usize) {
+        (self.progress.done.load(Ordering::Relaxed), self.total)
+    }
+
+    /// Ask the worker to stop at its next check
+    fn cancel(&self) {
+        self.progress.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// The worker's result, once it has finished
+    fn take_result(&self) -> Option<T> {
+        self.result.lock().ok().and_then(|mut result| result.take())
+    }
+}
+
+/// Background perceptual-hash scan for the duplicate finder
+struct DuplicateScan {
+    files: Vec<FileEntry>,
+    /// Groups of similar files (indices into `files`)
+    job: BackgroundJob<Vec<Vec<usize>>>,
+}
+
+/// Outcome per converted file, error as text
+type ConvertResults = Vec<Result<ConvertOutcome, String>>;
+
+/// Timeline months (label, file count) once the browsed folder is indexed
+type TimelineMonths = Result<Vec<(String, usize)>, String>;
+
+/// Files matching a search
+type SearchResults = Result<Vec<FileEntry>, String>;
+
+/// Dimensions and geotag of an image, for the properties window
+type ImageProperties = (Option<(u32, u32)>, Option<GpsPosition>);
+
+/// Background conversion of marked images (file.batch_convert)
+struct BatchConvertJob {
+    paths: Vec<PathBuf>,
+    dest: PathBuf,
+    /// Per-file outcome (shorter if cancelled)
+    job: BackgroundJob<ConvertResults>,
+}
+
+/// Encoded image data to decode, read on the UI thread for archive members
+enum ImageSource {
+    File(PathBuf),
+    Memory(Vec<u8>),
+}
+
+impl ImageSource {
+    /// Decode, upright according to the EXIF orientation (as thumbnails are)
+    fn decode(self) -> std::io::Result<image::DynamicImage> {
+        let data = match self {
+            ImageSource::File(path) => std::fs::read(path)?,
+            ImageSource::Memory(data) => data,
+        };
+        let img = image::load_from_memory(&data)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        Ok(match app_core::exif::read_exif(&data) {
+            Some(exif) => app_core::exif::apply_orientation(img, exif.orientation),
+            None => img,
+        })
+    }
+
+    /// Decode a PNG, with all of its frames if it is an APNG
+    fn decode_png(self) -> std::io::Result<Decoded> {
+        let data = match self {
+            ImageSource::File(path) => std::fs::read(path)?,
+            ImageSource::Memory(data) => data,
+        };
+        if is_apng(&data) {
+            match decode_apng(&data) {
+                Ok(frames) if frames.len() > 1 => {
+                    return Ok(Decoded { image: frames[0].image.clone(), frames: Some(frames) });
+                }
+                Ok(_) => {}
+                // e.g. 16-bit frames: fall back to the default image
+                Err(e) => tracing::warn!("APNG decode failed, showing the still image: {}", e),
+            }
+        }
+        ImageSource::Memory(data).decode().map(Decoded::from)
+    }
+}
+
+/// Pixels from a decode thread, plus every frame when the image is animated
+struct Decoded {
+    image: image::RgbaImage,
+    frames: Option<Vec<AnimationFrame>>,
+}
+
+impl From<image::DynamicImage> for Decoded {
+    fn from(image: image::DynamicImage) -> Self {
+        Self { image: image.to_rgba8(), frames: None }
+    }
+}
+
+/// Animated image playing in the viewer
+struct ViewerAnimation {
+    frames: Vec<AnimationFrame>,
+    /// Frame textures, uploaded the first time each frame is shown
+    textures: Vec<Option<egui::TextureHandle>>,
+    current: usize,
+    /// When the current frame is due to be replaced
+    next_at: std::time::Instant,
+}
+
+impl ViewerAnimation {
+    fn new(frames: Vec<AnimationFrame>, first: egui::TextureHandle) -> Self {
+        let mut textures = vec![None; frames.len()];
+        textures[0] = Some(first);
+        let next_at = std::time::Instant::now() + frames[0].delay;
+        Self { frames, textures, current: 0, next_at }
+    }
+}
+
+/// Only the start of large text files is previewed
+const TEXT_PREVIEW_LIMIT: usize = 1024 * 1024;
+
+/// Text file shown in the viewer instead of an image
+struct TextPreview {
+    name: String,
+    text: String,
+    encoding: &'static str,
+    /// The file was longer than TEXT_PREVIEW_LIMIT
+    truncated: bool,
+}
+
+impl TextPreview {
+    fn new(name: &str, mut data: Vec<u8>) -> Self {
+        let truncated = data.len() > TEXT_PREVIEW_LIMIT;
+        if truncated {
+            data.truncate(TEXT_PREVIEW_LIMIT);
+            // Don't let a UTF-8 sequence cut in half push detection elsewhere
+            if let Err(e) = std::str::from_utf8(&data) {
+                if e.error_len().is_none() {
+                    data.truncate(e.valid_up_to());
+                }
+            }
+        }
+        let hint = system_encoding_hint();
+        let (text, _) = decode_bytes(&data, hint);
+        Self {
+            name: name.to_string(),
+            text,
+            encoding: encoding_name(&data, hint),
+            truncated,
+        }
+    }
+}
+
+/// Smallest and largest edge (px) a PDF page is rendered at
+const PDF_RENDER_MIN: u32 = 256;
+const PDF_RENDER_MAX: u32 = 8192;
+
+/// Re-render a PDF page once the needed size is this far off the rendered one
+const PDF_RERENDER_RATIO: f32 = 1.25;
+
+/// Wait for zooming to settle before re-rendering a PDF page
+const PDF_RERENDER_DELAY: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// PDF open in the viewer; pages are rendered on demand at display resolution
+struct PdfDocument {
+    entry: FileEntry,
+    /// File mutool reads (archive members are extracted first)
+    path: PathBuf,
+    /// Current page (0-based)
+    page: usize,
+    pages: usize,
+    /// Edge length (px) the current page was requested at
+    rendered: u32,
+    /// When the needed size first drifted from `rendered`
+    resize_since: Option<std::time::Instant>,
+}
+
+/// Decoded pages of a multi-page TIFF kept for instant flipping
+const TIFF_PAGE_CACHE: usize = 8;
+
+/// Multi-page TIFF open in the viewer; the file stays in memory between pages
+struct TiffDocument {
+    entry: FileEntry,
+    pages: TiffPages,
+    /// Current page (0-based)
+    page: usize,
+    /// Recently shown pages, most recent last
+    cache: VecDeque<(usize, image::RgbaImage)>,
+}
+
+impl TiffDocument {
+    /// Split a multi-page TIFF out of `source`; anything else is handed back
+    /// (as in-memory data if the file had to be read to tell)
+    fn open(entry: &FileEntry, source: ImageSource) -> Result<Self, ImageSource> {
+        if !is_tiff(entry.path.as_path()) {
+            return Err(source);
+        }
+        let data = match source {
+            ImageSource::File(ref path) => match std::fs::read(path) {
+                Ok(data) => data,
+                Err(_) => return Err(source),
+            },
+            ImageSource::Memory(data) => data,
+        };
+
+        let data = Arc::new(data);
+        if let Some(pages) = TiffPages::parse(data.clone()).filter(|pages| pages.page_count() > 1) {
+            return Ok(Self { entry: entry.clone(), pages, page: 0, cache: VecDeque::new() });
+        }
+        Err(ImageSource::Memory(Arc::try_unwrap(data).unwrap_or_else(|data| data.to_vec())))
+    }
+
+    fn cached(&mut self, page: usize) -> Option<image::RgbaImage> {
+        let pos = self.cache.iter().position(|(p, _)| *p == page)?;
+        let hit = self.cache.remove(pos)?;
+        self.cache.push_back(hit);
+        self.cache.back().map(|(_, rgba)| rgba.clone())
+    }
+
+    fn remember(&mut self, page: usize, rgba: &image::RgbaImage) {
+        if self.cache.iter().any(|(p, _)| *p == page) {
+            return;
+        }
+        self.cache.push_back((page, rgba.clone()));
+        while self.cache.len() > TIFF_PAGE_CACHE {
+            self.cache.pop_front();
+        }
+    }
+}
+
+/// Folder archive members are extracted to, private to this process
+/// Removed on exit.
+fn extract_root() -> PathBuf {
+    std::env::temp_dir().join("LightningFiler").join(std::process::id().to_string())
+}
+
+/// Where an archive member is read from, detached from the app so a worker can extract it
+enum MemberSource {
+    Vfs(VirtualFileSystem),
+    Susie(Arc<parking_lot::Mutex<SusieBridge>>, PathBuf),
+}
+
+/// An archive member to write to a temp file for another reader
+struct Extraction {
+    source: MemberSource,
+    inner_path: String,
+    dest: PathBuf,
+}
+
+impl Extraction {
+    /// Write the member to `dest`, streaming it where the archive allows
+    fn run(self) -> Result<PathBuf, String> {
+        if let Some(dir) = self.dest.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        match self.source {
+            MemberSource::Susie(bridge, archive) => {
+                let data = extract_susie(&bridge, &archive, &self.inner_path, |_| {}).map_err(|e| e.to_string())?;
+                std::fs::write(&self.dest, data).map_err(|e| e.to_string())?;
+            }
+            MemberSource::Vfs(vfs) => {
+                vfs.extract_to(&self.inner_path, &self.dest).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(self.dest)
+    }
+}
+
+/// What to do with a file once it is available to other applications
+enum ExternalAction {
+    Open,
+    OpenWith { app_id: String, program: String, template: Option<String> },
+    Wallpaper(Option<WallpaperStyle>),
+}
+
+/// An archive member being extracted for an external action
+struct PendingHandOff {
+    name: String,
+    action: ExternalAction,
+    result: std::sync::mpsc::Receiver<Result<PathBuf, String>>,
+}
+
+/// Delete a nested archive extracted by `external_path` (and its folder)
+fn remove_extracted(path: &Path) {
+    if let Err(e) = std::fs::remove_file(path) {
+        tracing::warn!("Failed to remove extracted archive {}: {}", path.display(), e);
+    }
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::remove_dir(dir);
+    }
+}
+
+/// Archives opened from inside other archives, at most this deep
+const MAX_ARCHIVE_DEPTH: usize = 4;
+
+/// Bridge process hosting the installed Susie plugins
+type SusieBridge = BridgeSupervisor<ProcessConnection, BridgeLauncher>;
+
+/// Pings the Susie bridge this often to catch a wedged plugin
+const BRIDGE_HEARTBEAT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// `App::bridge_progress` while no plugin extraction reports progress
+const NO_PROGRESS: u32 = u32::MAX;
+
+/// Launch the Susie bridge when `plugin_dir` ("plugins" next to the executable
+/// if empty) holds plugins; they are loaded in the background
+fn start_susie_bridge(plugin_dir: &str) -> Option<(Arc<parking_lot::Mutex<SusieBridge>>, BridgeHealthHandle)> {
+    let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    let plugin_dir = match plugin_dir {
+        "" => exe_dir.join("plugins"),
+        dir => PathBuf::from(dir),
+    };
+    if app_core::bridge::plugin_files(&plugin_dir).map_or(true, |files| files.is_empty()) {
+        return None;
+    }
+
+    let exe = exe_dir.join(format!("susie_bridge{}", std::env::consts::EXE_SUFFIX));
+    let supervisor = BridgeSupervisor::new(ProcessConnection::launcher(exe));
+    // Taken before the loader thread holds the lock
+    let health = supervisor.health();
+    let bridge = Arc::new(parking_lot::Mutex::new(supervisor));
+    spawn_heartbeat(Arc::downgrade(&bridge), BRIDGE_HEARTBEAT_INTERVAL);
+    let loading = bridge.clone();
+    std::thread::spawn(move || match loading.lock().load_plugin_dir(&plugin_dir) {
+        Ok(extensions) => tracing::info!("Susie plugins handle: {}", extensions.join(", ")),
+        Err(e) => tracing::warn!("Failed to load Susie plugins: {}", e),
+    });
+    Some((bridge, health))
+}
+
+/// Read `inner_path` out of a plugin-handled archive through the bridge
+fn extract_susie(
+    bridge: &parking_lot::Mutex<SusieBridge>,
+    archive: &Path,
+    inner_path: &str,
+    on_progress: impl FnMut(f32),
+) -> std::io::Result<Vec<u8>> {
+    bridge
+        .lock()
+        .extract_to_memory(archive, inner_path, &NamedSharedMemory, on_progress)
+        .map_err(std::io::Error::other)
+}
+
+/// Open `path` as an archive; plugin-handled formats are listed through the Susie bridge
+/// The bridge may be busy for a long time, so call this off the UI thread.
+fn open_archive(
+    path: &Path,
+    bridge: Option<&parking_lot::Mutex<SusieBridge>>,
+) -> Result<VirtualFileSystem, String> {
+    let vfs = VirtualFileSystem::open(path).map_err(|e| e.to_string())?;
+    if vfs.format() != ArchiveFormat::Susie {
+        return Ok(vfs);
+    }
+    let bridge = bridge.ok_or("No Susie plugin is installed for this format")?;
+    let entries = bridge.lock().list_archive(path).map_err(|e| e.to_string())?;
+    vfs.set_entries(entries.into_iter().map(|e| VfsEntry {
+        name: e.path.rsplit('/').next().unwrap_or(&e.path).to_string(),
+        path: e.path,
+        size: e.size,
+        compressed_size: Some(e.compressed_size),
+        is_dir: e.is_directory,
+        modified: e.timestamp,
+    }).collect());
+    Ok(vfs)
+}
+
+/// Where an archive being opened in the background is entered from
+enum ArchiveOrigin {
+    /// A file in a regular folder
+    Folder,
+    /// A member of the current archive, extracted to `extracted`
+    Nested { entry_path: UniversalPath, extracted: PathBuf },
+}
+
+/// An archive being opened (and listed) in the background
+struct ArchiveOpening {
+    origin: ArchiveOrigin,
+    /// The opened archive, or a message for the status bar
+    job: BackgroundJob<Result<VirtualFileSystem, String>>,
+}
+
+/// Read the properties-window details of an image
+fn image_properties(source: ImageSource) -> ImageProperties {
+    match source {
+        ImageSource::File(path) => (
+            get_image_dimensions(&path).ok(),
+            read_exif_info(&path).and_then(|info| info.gps),
+        ),
+        ImageSource::Memory(data) => {
+            let dimensions = image::ImageReader::new(std::io::Cursor::new(&data))
+                .with_guessed_format()
+                .ok()
+                .and_then(|reader| reader.into_dimensions().ok());
+            (dimensions, app_core::exif::read_exif(&data).and_then(|info| info.gps))
+        }
+    }
+}
+
+/// A text file being read in the background for the viewer
+struct TextLoad {
+    entry: FileEntry,
+    /// Load generation it was started in; stale once the user moves on
+    generation: u64,
+    job: BackgroundJob<std::io::Result<Vec<u8>>>,
+}
+
+/// An outer archive, saved while browsing an archive nested inside it
+struct OuterArchive {
+    vfs: VirtualFileSystem,
+    inner_path: String,
+    /// Pseudo path of the nested archive entry (root of its listing)
+    entry_path: UniversalPath,
+    /// Nested archive extracted to disk; removed when leaving it
+    extracted: PathBuf,
+}
+
+/// Where a background decode is displayed once it finishes
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DecodeTarget {
+    /// Main viewer; `placeholder` = a thumbnail is standing in until then
+    Viewer { placeholder: bool },
+    SplitPane(usize),
+    /// Spread partner at `index` into file_entries
+    SpreadPartner { index: usize, on_left: bool },
+}
+
+impl DecodeTarget {
+    /// Whether a new request for `self` makes an older `other` request stale
+    /// Viewer-side decodes are dropped once the user has navigated on
+    fn follows_navigation(self) -> bool {
+        matches!(self, DecodeTarget::Viewer { .. } | DecodeTarget::SpreadPartner { .. })
+    }
+
+    fn supersedes(self, other: DecodeTarget) -> bool {
+        match (self, other) {
+            (DecodeTarget::Viewer { .. }, DecodeTarget::Viewer { .. } | DecodeTarget::SpreadPartner { .. }) => true,
+            (DecodeTarget::SpreadPartner { .. }, DecodeTarget::SpreadPartner { .. }) => true,
+            (DecodeTarget::SplitPane(a), DecodeTarget::SplitPane(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Decode requested from a background thread (see poll_decodes)
+struct PendingDecode {
+    entry: FileEntry,
+    target: DecodeTarget,
+    /// load_generation when the decode was requested
+    generation: u64,
+}
+
+/// RGBA pixels sent back from a decode thread, tagged with the request id
+struct DecodedImage {
+    id: u64,
+    result: std::io::Result<Decoded>,
+}
+
+/// Metadata captured by meta.copy_meta for meta.paste_meta
+struct MetaClipboard {
+    target: app_core::CopyTarget,
+    rating: i32,
+    label: Option<u32>,
+    tag_ids: Vec<i64>,
+    comment: Option<String>,
+}
+
+/// Second page shown next to the current one in spread mode
+struct SpreadPartner {
+    texture: egui::TextureHandle,
+    size: egui::Vec2,
+    /// Index into file_entries
+    index: usize,
+    /// Drawn to the left of the current page
+    on_left: bool,
+}
+
 /// Main application state for the event loop
 struct App {
     window: Option<Arc<Window>>,
+    // Title last set on the window
+    window_title: String,
     renderer: Option<Renderer>,
     egui_ctx: egui::Context,
     egui_state: Option<egui_winit::State>,
@@ -31,7 +577,10 @@ struct App {
     // UI Components
     file_browser: FileBrowser,
     image_viewer: ImageViewer,
+    /// Viewport size the viewer fit was last computed for
+    viewer_area: egui::Vec2,
     settings_dialog: SettingsDialog,
+    type_ahead: TypeAhead,
     input_handler: Option<InputHandler>,
     theme: Theme,
 
@@ -45,9 +594,11 @@ struct App {
     thumbnail_manager: Option<ThumbnailManager>,
 
     // Texture cache (path_hash -> TextureHandle)
-    thumbnail_textures: HashMap<u64, egui::TextureHandle>,
-    // Track in-flight thumbnail generation to avoid duplicate work
-    pending_thumbnails: Arc<std::sync::Mutex<HashSet<u64>>>,
+    thumbnail_textures: LruCache<u64, egui::TextureHandle>,
+    // Last time thumbnail caches were pruned to their configured limits
+    last_thumbnail_prune: std::time::Instant,
+    // Active thumbnail size (textures above are for this size only)
+    thumbnail_size: ThumbnailSize,
 
     // State
     show_browser: bool,
@@ -56,6 +607,29 @@ struct App {
     file_entries: Vec<FileEntry>,
     selected_index: Option<usize>,
     current_texture: Option<egui::TextureHandle>,
+    // Decoded RGBA pixels of the current image (kept for analysis overlays)
+    current_pixels: Option<image::RgbaImage>,
+    // Downscaled current_pixels shown while an adjustment slider is dragged
+    adjust_preview: Option<image::RgbaImage>,
+    // Background decodes by request id; results without an entry here are stale
+    pending_decodes: HashMap<u64, PendingDecode>,
+    next_decode_id: u64,
+    // Shown in the viewer in place of the image while a text file is selected
+    text_preview: Option<TextPreview>,
+    // Text file being read for text_preview
+    text_loading: Option<TextLoad>,
+    // PDF shown in the viewer, page by page
+    pdf_document: Option<PdfDocument>,
+    // Multi-page TIFF shown in the viewer
+    tiff_document: Option<TiffDocument>,
+    // APNG frames being played in the viewer
+    animation: Option<ViewerAnimation>,
+    // Viewer area in physical pixels, from the last frame (sizes PDF renders)
+    viewer_pixels: egui::Vec2,
+    // Bumped on every navigation; decodes from an older generation are skipped
+    load_generation: Arc<AtomicU64>,
+    decode_tx: std::sync::mpsc::Sender<DecodedImage>,
+    decode_rx: std::sync::mpsc::Receiver<DecodedImage>,
 
     // Grid layout tracking
     grid_columns: usize,
@@ -63,6 +637,9 @@ struct App {
 
     // Temporary marks (cleared on exit)
     marked_files: HashSet<u64>,
+    /// Path ids of the multi-selection (nav_state.selection holds indices into file_entries)
+    selection_ids: Vec<u64>,
+    meta_clipboard: Option<MetaClipboard>,
 
     // Overlay UI state (Doc 4 spec)
     overlay_visible: bool,
@@ -73,30 +650,83 @@ struct App {
 
     // File watcher
     file_watcher: Option<FileWatcher>,
+    /// Folder still being listed in the background (dropping it cancels the listing)
+    pending_listing: Option<DirectoryListing>,
+    /// Recursive listing still streaming into the flattened view
+    pending_flatten: Option<DirectoryListing>,
 
     // Archive support
     current_archive: Option<VirtualFileSystem>,
     archive_inner_path: String,
+    // Outer archives while browsing nested ones (innermost last)
+    archive_stack: Vec<OuterArchive>,
     // Map from FileEntry.path.id() to archive inner path
     archive_path_map: HashMap<u64, String>,
+    /// Reads archives (rar, lzh) through Susie plugins; None without plugins
+    susie_bridge: Option<Arc<parking_lot::Mutex<SusieBridge>>>,
+    /// Read without taking the bridge lock, which a long request may hold
+    bridge_health: Option<BridgeHealthHandle>,
+    /// Percent of the running plugin extraction (NO_PROGRESS when idle)
+    bridge_progress: Arc<AtomicU32>,
 
     // Dialogs
     confirm_dialog: Option<ConfirmDialog>,
     rename_dialog: Option<RenameDialog>,
+    batch_rename_dialog: Option<BatchRenameDialog>,
+    save_as_dialog: Option<SaveAsDialog>,
+    export_dialog: Option<ExportDialog>,
+    batch_convert_dialog: Option<BatchConvertDialog>,
+    recent_folders_menu: Option<RecentFoldersMenu>,
+    open_with_menu: Option<OpenWithMenu>,
+    batch_convert: Option<BatchConvertJob>,
+    // Files picked when the batch convert window opened
+    batch_convert_paths: Vec<PathBuf>,
+    // EXIF of the image being saved, read when the save-as/export window opens
+    save_as_exif: Option<Vec<u8>>,
     new_folder_dialog: Option<NewFolderDialog>,
     tag_dialog: Option<TagEditDialog>,
-    pending_delete_path: Option<PathBuf>,
+    comment_dialog: Option<CommentDialog>,
+    export_marks_dialog: Option<ExportMarksDialog>,
+    properties_dialog: Option<PropertiesDialog>,
+    /// Image dimensions and geotag being read for the properties window
+    properties_info: Option<BackgroundJob<ImageProperties>>,
+    /// File shown in the properties window, when its attributes can be edited
+    properties_path: Option<PathBuf>,
+    pending_comment_entry: Option<FileEntry>,
+    /// Paths awaiting delete confirmation, and whether they go to the trash
+    pending_delete: Option<(Vec<PathBuf>, bool)>,
+    // Confirm dialog is asking whether to empty the trash
+    pending_empty_trash: bool,
+    // Recycle bin items awaiting confirmation to be deleted permanently
+    pending_purge: Option<Vec<app_fs::TrashEntry>>,
+    // Non-JPEG files waiting for confirmation to be rotated by re-encoding
+    pending_reencode: Option<(Vec<PathBuf>, u32)>,
 
     // Spread viewer (two-page display)
     spread_viewer: SpreadViewer,
+    spread_partner: Option<SpreadPartner>,
+    // (folder id, image count) the wide-page set was computed for
+    spread_pages_key: Option<(u64, usize)>,
+    /// Page positions probed in the background for spread_pages_key, and whether each is wide
+    wide_page_scan: Option<std::sync::mpsc::Receiver<(usize, bool)>>,
 
     // Split view (compare two images)
     split_view: SplitView,
+    /// Textures shown in the split view panes (kept alive while displayed)
+    split_textures: [Option<egui::TextureHandle>; 2],
 
     // Viewer effects
     image_transform: ImageTransform,
     viewer_background: ViewerBackground,
     page_transition: PageTransition,
+    // Outgoing texture, with where and how it was drawn, held alive while a crossfade runs
+    transition_from: Option<(egui::TextureHandle, egui::Rect, [egui::Pos2; 4])>,
+    // Rect and texture coordinates of the single image drawn in the last frame
+    viewer_painted: Option<(egui::Rect, [egui::Pos2; 4])>,
+    histogram: HistogramOverlay,
+    adjust_panel: AdjustPanel,
+    loupe: Loupe,
+    eyedropper: Eyedropper,
 
     // Slideshow
     slideshow: Slideshow,
@@ -105,6 +735,34 @@ struct App {
     folder_tree: FolderTree,
     thumbnail_catalog: ThumbnailCatalog,
     catalog_items: Vec<ThumbnailItem>,
+    /// Indices of file_entries the catalog shows, ascending (the catalog filter hides the rest)
+    catalog_view: Vec<usize>,
+    /// Timeline / flatten headers by index into file_entries (mapped onto the catalog view)
+    entry_sections: Vec<(usize, String)>,
+    tag_panel: TagPanel,
+    catalog_filter: CatalogFilter,
+    /// Extension filter text from the catalog header, and the parsed extensions
+    extension_filter_text: String,
+    extension_filter: Option<Vec<String>>,
+    duplicate_review: Option<DuplicateReview>,
+    trash_view: Option<TrashView>,
+    duplicate_scan: Option<DuplicateScan>,
+    command_palette: CommandPalette,
+    /// Timeline months ("YYYY-MM", file count), oldest first
+    timeline_months: Vec<(String, usize)>,
+    /// Background indexing of the browsed folder before entering the timeline
+    timeline_indexing: Option<std::sync::mpsc::Receiver<TimelineMonths>>,
+    timeline_index: usize,
+    /// Search running in the background, with its query
+    search_job: Option<(String, BackgroundJob<SearchResults>)>,
+    /// Folder last indexed for search; indexed again after it changes
+    search_indexed: Option<PathBuf>,
+    /// Sidecar export running in the background
+    sidecar_export: Option<BackgroundJob<Result<SidecarExport, String>>>,
+    /// Archive member being extracted for an external application
+    pending_hand_off: Option<PendingHandOff>,
+    /// Archive being opened in the background
+    archive_opening: Option<ArchiveOpening>,
 
     // Navigation history
     history_back: Vec<UniversalPath>,
@@ -119,14 +777,52 @@ impl App {
         let config = state().map(|s| s.config.read().clone()).unwrap_or_default();
 
         // Get initial path
-        let current_path = state()
+        let mut current_path = state()
             .map(|s| s.current_path())
             .unwrap_or_else(|| UniversalPath::new("."));
 
+        // Reopen the last session's folder (the home folder if it is gone)
+        let session = if config.general.restore_session { Session::load() } else { None };
+        if let Some(saved) = session.as_ref().and_then(|s| s.current_path.as_ref()) {
+            let restored = if saved.is_dir() {
+                Some(saved.clone())
+            } else {
+                tracing::info!("Last session folder {:?} no longer exists", saved);
+                dirs_next::home_dir()
+            };
+            if let Some(path) = restored {
+                current_path = UniversalPath::new(path);
+                if let Some(state) = state() {
+                    state.set_current_path(current_path.clone());
+                }
+            }
+        }
+
         // Load initial directory
         let file_entries = list_directory(current_path.as_path(), &ListOptions::default())
             .unwrap_or_default();
 
+        // Thumbnail size preset from config
+        let thumbnail_size = ThumbnailSize::from_u32(config.filer.thumbnail_size);
+        let mut file_browser = FileBrowser::new();
+        file_browser.thumbnail_size = thumbnail_size.to_u32() as f32;
+        let mut thumbnail_catalog = ThumbnailCatalog::new();
+        thumbnail_catalog.set_thumbnail_size(thumbnail_size.to_u32() as f32);
+
+        let mut spread_viewer = SpreadViewer::new();
+        spread_viewer.reading_direction = config.viewer.reading_direction;
+        spread_viewer.mode = match config.viewer.spread_mode {
+            app_core::SpreadMode::Single => SpreadMode::Single,
+            app_core::SpreadMode::Spread => spread_mode_for(config.viewer.reading_direction),
+            app_core::SpreadMode::Auto => SpreadMode::Auto,
+        };
+
+        // Crossfade between images unless animations are disabled
+        let mut page_transition = PageTransition::new();
+        if config.viewer.enable_animation {
+            page_transition.transition_type = TransitionType::Fade;
+        }
+
         // Initialize navigation state
         let mut nav_state = NavigationState::new();
         nav_state.enter_threshold = config.navigation.enter_threshold.unwrap_or(5);
@@ -136,8 +832,15 @@ impl App {
             Ok((pool, cache)) => {
                 let metadata_db = MetadataDb::new(pool.clone());
                 let cache_arc = Arc::new(cache);
-                let thumbnail_manager = ThumbnailManager::new(cache_arc.clone());
+                let thumbnail_manager = ThumbnailManager::new(cache_arc.clone(), config.filer.thumbnail_workers);
                 tracing::info!("Database initialized successfully");
+
+                // Drop view history past the retention period
+                match metadata_db.prune_history(config.general.history_retention_days) {
+                    Ok(n) if n > 0 => tracing::info!("Pruned {} old history entries", n),
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Failed to prune history: {}", e),
+                }
                 (Some(pool), Some(metadata_db), Some(cache_arc), Some(thumbnail_manager))
             }
             Err(e) => {
@@ -146,6 +849,8 @@ impl App {
             }
         };
 
+        let (decode_tx, decode_rx) = std::sync::mpsc::channel();
+
         // Initialize file watcher
         let file_watcher = match FileWatcher::new() {
             Ok(mut watcher) => {
@@ -161,16 +866,21 @@ impl App {
             }
         };
 
-        Self {
+        let (susie_bridge, bridge_health) = start_susie_bridge(&config.general.susie_plugin_dir).unzip();
+
+        let mut app = Self {
             window: None,
+            window_title: APP_TITLE.to_string(),
             renderer: None,
             egui_ctx: egui::Context::default(),
             egui_state: None,
             egui_renderer: None,
 
-            file_browser: FileBrowser::new(),
+            file_browser,
             image_viewer: ImageViewer::new(),
+            viewer_area: egui::Vec2::ZERO,
             settings_dialog: SettingsDialog::new(config.clone()),
+            type_ahead: TypeAhead::new(),
             input_handler: None,
             theme: Theme::by_name(&config.general.theme),
 
@@ -181,8 +891,9 @@ impl App {
             thumbnail_cache,
             thumbnail_manager,
 
-            thumbnail_textures: HashMap::new(),
-            pending_thumbnails: Arc::new(std::sync::Mutex::new(HashSet::new())),
+            thumbnail_textures: LruCache::new(config.filer.thumbnail_memory_limit),
+            last_thumbnail_prune: std::time::Instant::now(),
+            thumbnail_size,
 
             show_browser: true,
             status: StatusInfo {
@@ -197,11 +908,15 @@ impl App {
             file_entries,
             selected_index: None,
             current_texture: None,
+            current_pixels: None,
+            adjust_preview: None,
 
             grid_columns: 1,
             grid_visible_rows: 10,
 
             marked_files: HashSet::new(),
+            selection_ids: Vec::new(),
+            meta_clipboard: None,
 
             overlay_visible: true,
             last_mouse_move: None,
@@ -209,36 +924,113 @@ impl App {
             file_ops: Arc::new(DefaultFileOperations::new()),
 
             file_watcher,
+            pending_listing: None,
+            pending_flatten: None,
 
             current_archive: None,
             archive_inner_path: String::new(),
+            archive_stack: Vec::new(),
             archive_path_map: HashMap::new(),
+            susie_bridge,
+            bridge_health,
+            bridge_progress: Arc::new(AtomicU32::new(NO_PROGRESS)),
 
             confirm_dialog: None,
             rename_dialog: None,
+            batch_rename_dialog: None,
+            save_as_dialog: None,
+            export_dialog: None,
+            batch_convert_dialog: None,
+            recent_folders_menu: None,
+            open_with_menu: None,
+            batch_convert: None,
+            batch_convert_paths: Vec::new(),
+            save_as_exif: None,
             new_folder_dialog: None,
             tag_dialog: None,
-            pending_delete_path: None,
-
-            spread_viewer: SpreadViewer::new(),
+            comment_dialog: None,
+            export_marks_dialog: None,
+            properties_dialog: None,
+            properties_info: None,
+            properties_path: None,
+            pending_comment_entry: None,
+            pending_delete: None,
+            pending_empty_trash: false,
+            pending_purge: None,
+            pending_reencode: None,
+
+            spread_viewer,
+            spread_partner: None,
+            spread_pages_key: None,
+            wide_page_scan: None,
             split_view: SplitView::new(),
+            split_textures: [None, None],
             image_transform: ImageTransform::new(),
             viewer_background: ViewerBackground::new(),
-            page_transition: PageTransition::new(),
+            page_transition,
+            transition_from: None,
+            viewer_painted: None,
+            histogram: HistogramOverlay::new(),
+            adjust_panel: AdjustPanel::new(),
+            loupe: Loupe::new(),
+            eyedropper: Eyedropper::new(),
             slideshow: Slideshow::new(),
             folder_tree: FolderTree::new(),
-            thumbnail_catalog: ThumbnailCatalog::new(),
+            thumbnail_catalog,
             catalog_items: Vec::new(),
+            tag_panel: TagPanel::new(),
+            catalog_view: Vec::new(),
+            entry_sections: Vec::new(),
+            catalog_filter: CatalogFilter::default(),
+            extension_filter_text: String::new(),
+            extension_filter: None,
+            duplicate_review: None,
+            trash_view: None,
+            duplicate_scan: None,
+            pending_decodes: HashMap::new(),
+            next_decode_id: 0,
+            text_preview: None,
+            text_loading: None,
+            pdf_document: None,
+            tiff_document: None,
+            animation: None,
+            viewer_pixels: egui::Vec2::new(1920.0, 1080.0),
+            load_generation: Arc::new(AtomicU64::new(0)),
+            decode_tx,
+            decode_rx,
+            command_palette: CommandPalette::new(),
+            timeline_months: Vec::new(),
+            timeline_indexing: None,
+            pending_hand_off: None,
+            search_job: None,
+            sidecar_export: None,
+            search_indexed: None,
+            archive_opening: None,
+            timeline_index: 0,
 
             history_back: Vec::new(),
             history_forward: Vec::new(),
             toolbar_state: ToolbarState::new(),
+        };
+
+        app.refresh_tags();
+        app.refresh_bookmarks();
+
+        // Apply the start folder's stored sort
+        let sort = app.folder_sort(&app.current_path);
+        app.set_sort(sort);
+        app.apply_sort();
+        app.apply_catalog_filter();
+
+        if let Some(session) = session {
+            app.restore_session(&session);
         }
+        app
     }
 
     fn init_window(&mut self, event_loop: &ActiveEventLoop) -> Result<()> {
         let window_attrs = Window::default_attributes()
-            .with_title("LightningFiler")
+            .with_title(APP_TITLE)
             .with_inner_size(winit::dpi::LogicalSize::new(1280, 720));
 
         let window = Arc::new(event_loop.create_window(window_attrs)?);
@@ -280,9 +1072,114 @@ impl App {
         self.egui_renderer = Some(egui_renderer);
         self.input_handler = Some(input_handler);
 
+        // Restored viewer-mode session
+        if !self.show_browser {
+            self.set_fullscreen(true);
+        }
+
         Ok(())
     }
 
+    /// Open the command palette with the current keybindings (app.command_palette)
+    fn open_command_palette(&mut self) {
+        let keybindings = state().map(|s| s.config.read().keybindings.clone()).unwrap_or_default();
+        let entries = command_registry().into_iter()
+            .map(|info| PaletteEntry {
+                binding: info.binding.to_string(),
+                label: info.label.to_string(),
+                keys: keybindings.get(info.binding).map(|keys| keys.join(", ")).unwrap_or_default(),
+            })
+            .collect();
+        self.command_palette.show(entries);
+    }
+
+    /// Run a command chosen in the palette
+    fn run_palette_command(&mut self, binding: &str) {
+        let Some(info) = command_registry().into_iter().find(|c| c.binding == binding) else {
+            return;
+        };
+        self.command_palette.record(binding);
+        self.execute_command(&info.command);
+    }
+
+    /// Re-read config.toml and apply theme, keybindings and fonts (app.reload_config)
+    fn reload_config(&mut self) {
+        let config = match AppConfig::load() {
+            Ok(config) => config,
+            Err(e) => {
+                // Keep running with the current config
+                tracing::error!("Failed to reload config: {}", e);
+                self.status.message = format!("Config error: {}", e);
+                return;
+            }
+        };
+
+        self.theme = Theme::by_name(&config.general.theme);
+        self.theme.apply(&self.egui_ctx);
+        if let Some(ref mut handler) = self.input_handler {
+            handler.set_bindings(config.keybindings.clone());
+        }
+        self.setup_fonts();
+
+        if let Some(state) = state() {
+            *state.config.write() = config;
+        }
+        self.status.message = "Configuration reloaded".to_string();
+    }
+
+    /// Re-select the last file and restore the view mode
+    fn restore_session(&mut self, session: &Session) {
+        use app_ui::components::viewer::FitMode;
+
+        self.image_viewer.fit_mode = match session.fit_mode {
+            app_core::FitMode::FitToWindow => FitMode::FitToWindow,
+            app_core::FitMode::FitWidth => FitMode::FitWidth,
+            app_core::FitMode::FitHeight => FitMode::FitHeight,
+            app_core::FitMode::OriginalSize => FitMode::OriginalSize,
+        };
+
+        let Some(id) = session.selected() else {
+            return;
+        };
+        let Some(index) = self.file_entries.iter().position(|e| e.path.id() == id) else {
+            return;
+        };
+        self.selected_index = Some(index);
+
+        let entry = self.file_entries[index].clone();
+        if !entry.is_dir {
+            self.load_image(&entry);
+            // Viewer mode goes fullscreen once the window exists (init_window)
+            self.show_browser = session.show_browser;
+        }
+    }
+
+    /// Save where the user left off (general.restore_session)
+    fn save_session(&self) {
+        let restore = state().map(|s| s.config.read().general.restore_session).unwrap_or(false);
+        if !restore {
+            return;
+        }
+
+        use app_ui::components::viewer::FitMode;
+        let mut session = Session {
+            current_path: Some(self.current_path.as_path().to_path_buf()),
+            show_browser: self.show_browser,
+            fit_mode: match self.image_viewer.fit_mode {
+                FitMode::FitToWindow => app_core::FitMode::FitToWindow,
+                FitMode::FitWidth => app_core::FitMode::FitWidth,
+                FitMode::FitHeight => app_core::FitMode::FitHeight,
+                FitMode::OriginalSize => app_core::FitMode::OriginalSize,
+            },
+            ..Default::default()
+        };
+        session.set_selected(self.selected_index.and_then(|i| self.file_entries.get(i)).map(|e| e.path.id()));
+
+        if let Err(e) = session.save() {
+            tracing::warn!("Failed to save session: {}", e);
+        }
+    }
+
     /// Toggle fullscreen mode
     fn toggle_fullscreen(&self) {
         if let Some(ref window) = self.window {
@@ -441,15 +1338,56 @@ impl App {
         }
 
         // Clear archive state when navigating to a regular directory
-        self.current_archive = None;
-        self.archive_inner_path.clear();
-        self.archive_path_map.clear();
+        self.close_archives();
+
+        self.leave_results_view();
+
+        // Cancel a listing still running for the previous folder
+        self.pending_listing = None;
+        self.pending_flatten = None;
+        self.archive_opening = None;
+        self.search_job = None;
+        self.bump_load_generation();
+
+        // The previous folder's thumbnails are no longer worth generating
+        if let Some(ref manager) = self.thumbnail_manager {
+            manager.clear_queue();
+        }
+        self.text_preview = None;
+        self.pdf_document = None;
+        self.tiff_document = None;
+        self.animation = None;
+
+        let sort = self.folder_sort(&path);
+        let mut listing = DirectoryListing::start(path.as_path(), &self.list_options(sort));
+        let Some(result) = listing.wait(SYNC_LISTING_BUDGET) else {
+            // Huge folder: show it while the rest streams in (see poll_listing)
+            self.current_path = path.clone();
+            self.file_entries.clear();
+            self.restore_selection();
+            self.set_sort(sort);
+            self.selected_index = None;
+            self.status.file_name = path.to_string();
+            self.status.message = "Loading…".to_string();
+            if let Some(ref mut watcher) = self.file_watcher {
+                let _ = watcher.watch(path.as_path());
+            }
+            self.record_folder_visit(&path);
+            if let Some(state) = state() {
+                state.set_current_path(path);
+            }
+            self.pending_listing = Some(listing);
+            return;
+        };
 
-        match list_directory(path.as_path(), &ListOptions::default()) {
+        match result {
             Ok(entries) => {
                 self.current_path = path.clone();
                 self.file_entries = entries;
+                self.set_sort(sort);
                 self.apply_sort(); // Apply current sort mode
+                self.apply_catalog_filter();
+                self.restore_selection();
                 self.selected_index = None;
                 self.status.file_name = path.to_string();
                 self.status.message = format!("{} items", self.file_entries.len());
@@ -461,6 +1399,7 @@ impl App {
 
                 // Request thumbnails for image files
                 self.request_thumbnails_for_current_directory();
+                self.record_folder_visit(&path);
 
                 // Update global state
                 if let Some(state) = state() {
@@ -470,335 +1409,2659 @@ impl App {
             Err(e) => {
                 tracing::error!("Failed to navigate to directory: {}", e);
                 self.status.message = format!("Error: {}", e);
+                // A bookmarked folder may have vanished; update the indicators
+                self.refresh_bookmarks();
             }
         }
     }
 
-    /// Navigate back in history
-    fn navigate_back(&mut self) {
-        if let Some(prev_path) = self.history_back.pop() {
-            self.history_forward.push(self.current_path.clone());
-            self.navigate_to_internal(prev_path, false);
+    /// Move a folder to the top of the recent folders list (nav.recent_folders)
+    /// Archives, and anything else that is not a real folder, are not recorded.
+    fn record_folder_visit(&self, path: &UniversalPath) {
+        let Some(ref db) = self.metadata_db else {
+            return;
+        };
+        if !path.as_path().is_dir() {
+            return;
         }
-    }
-
-    /// Navigate forward in history
-    fn navigate_forward(&mut self) {
-        if let Some(next_path) = self.history_forward.pop() {
-            self.history_back.push(self.current_path.clone());
-            self.navigate_to_internal(next_path, false);
+        let limit = state().map(|s| s.config.read().filer.recent_folder_limit).unwrap_or(20);
+        if let Err(e) = db.record_folder_visit(path, limit) {
+            tracing::warn!("Failed to record folder visit: {}", e);
         }
     }
 
-    /// Apply current sort mode to file entries
-    fn apply_sort(&mut self) {
-        use SortMode::*;
-        self.file_entries.sort_by(|a, b| {
-            // Directories always first
-            if a.is_dir != b.is_dir {
-                return if a.is_dir { std::cmp::Ordering::Less } else { std::cmp::Ordering::Greater };
-            }
-            match self.toolbar_state.sort_mode {
-                Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-                NameDesc => b.name.to_lowercase().cmp(&a.name.to_lowercase()),
-                Size => a.size.cmp(&b.size),
-                SizeDesc => b.size.cmp(&a.size),
-                Modified => a.modified.cmp(&b.modified),
-                ModifiedDesc => b.modified.cmp(&a.modified),
-                Type => a.extension.cmp(&b.extension),
-                TypeDesc => b.extension.cmp(&a.extension),
+    /// Open the recent folders menu, dropping folders that no longer exist
+    fn open_recent_folders(&mut self) {
+        let Some(ref db) = self.metadata_db else {
+            return;
+        };
+        let folders = match db.recent_folders() {
+            Ok(folders) => folders,
+            Err(e) => {
+                self.status.message = format!("Failed to load recent folders: {}", e);
+                return;
             }
-        });
+        };
+        let (existing, stale): (Vec<PathBuf>, Vec<PathBuf>) = folders.into_iter()
+            .map(PathBuf::from)
+            .partition(|folder| folder.is_dir());
+        for folder in stale {
+            if let Err(e) = db.remove_recent_folder(&UniversalPath::new(folder)) {
+                tracing::warn!("Failed to remove recent folder: {}", e);
+            }
+        }
+        self.recent_folders_menu = Some(RecentFoldersMenu::new(existing, self.current_path.as_path()));
     }
 
-    /// Enter an archive file and display its contents as if it were a directory
-    fn enter_archive(&mut self, archive_path: UniversalPath) {
-        match VirtualFileSystem::open(archive_path.as_path()) {
-            Ok(vfs) => {
-                match vfs.list_entries() {
-                    Ok(vfs_entries) => {
-                        // Clear previous archive path mappings
-                        self.archive_path_map.clear();
-
-                        // Convert VfsEntry to FileEntry for display
-                        let file_entries: Vec<FileEntry> = vfs_entries.iter().filter_map(|ve| {
-                            // Create a pseudo-path for the archive entry
-                            let entry_path = archive_path.join(&ve.path);
-
-                            // Store mapping from entry path ID to archive inner path
-                            self.archive_path_map.insert(entry_path.id(), ve.path.clone());
-
-                            Some(FileEntry {
-                                path: entry_path,
-                                name: ve.name.clone(),
-                                is_dir: ve.is_dir,
-                                is_hidden: false,
-                                size: ve.size,
-                                modified: ve.modified,
-                                extension: std::path::Path::new(&ve.name)
-                                    .extension()
-                                    .map(|e| e.to_string_lossy().to_lowercase())
-                                    .unwrap_or_default(),
-                            })
-                        }).collect();
-
-                        self.current_archive = Some(vfs);
-                        self.archive_inner_path = String::new();
-                        self.file_entries = file_entries;
-                        self.selected_index = None;
-                        self.status.message = format!("Archive: {} ({} items)",
-                            archive_path.display(), self.file_entries.len());
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to list archive entries: {}", e);
-                        self.status.message = format!("Archive error: {}", e);
-                    }
-                }
+    /// Take entries from the background listing; sorts and filters once it completes
+    fn poll_listing(&mut self) {
+        let Some(path) = self.pending_listing.as_ref().map(|l| l.path().to_path_buf()) else {
+            return;
+        };
+        // Abandon it if the catalog moved on to something else (archive, search, ...)
+        if path != self.current_path.as_path() || self.current_archive.is_some() || self.in_results_view() {
+            self.pending_listing = None;
+            return;
+        }
+
+        let Some(listing) = self.pending_listing.as_mut() else {
+            return;
+        };
+        let (entries, done) = listing.poll();
+        if !entries.is_empty() {
+            self.file_entries.extend(entries);
+            self.apply_catalog_filter();
+            self.status.message = format!("Loading… {} items", self.file_entries.len());
+        }
+
+        let Some(result) = done else {
+            return;
+        };
+        self.pending_listing = None;
+        match result {
+            Ok(()) => {
+                self.apply_sort();
+                self.apply_catalog_filter();
+                self.restore_selection();
+                self.catalog_items.clear();
+                self.status.message = format!("{} items", self.file_entries.len());
+                self.request_thumbnails_for_current_directory();
             }
             Err(e) => {
-                tracing::error!("Failed to open archive: {}", e);
-                self.status.message = format!("Cannot open archive: {}", e);
+                tracing::error!("Failed to list directory: {}", e);
+                self.status.message = format!("Error: {}", e);
             }
         }
     }
 
-    /// Request thumbnails for all image files in current directory
-    /// This pre-generates thumbnails in the background
-    fn request_thumbnails_for_current_directory(&mut self) {
-        let Some(ref cache) = self.thumbnail_cache else {
+    /// Whether the catalog shows tag search results instead of a folder
+    fn in_tag_search(&self) -> bool {
+        matches!(self.nav_state.context, NavigationContext::TagSearch { .. })
+    }
+
+    /// Reload the tag list (with file counts) from the database
+    fn refresh_tags(&mut self) {
+        let Some(ref db) = self.metadata_db else {
             return;
         };
+        match db.list_tags_with_counts() {
+            Ok(tags) => {
+                let tags = tags.into_iter()
+                    .map(|(tag, count)| TagEntry {
+                        tag_id: tag.tag_id,
+                        name: tag.name,
+                        color: tag.color,
+                        count,
+                    })
+                    .collect();
+                self.tag_panel.set_tags(tags);
+            }
+            Err(e) => tracing::warn!("Failed to load tags: {}", e),
+        }
+    }
 
-        let cache = cache.clone();
-        let egui_ctx = self.egui_ctx.clone();
+    /// Show files carrying all selected tags in the catalog (TagSearch context)
+    fn search_by_tags(&mut self) {
+        let tag_ids = self.tag_panel.selected.clone();
+        if tag_ids.is_empty() {
+            // Back to the folder we were browsing
+            self.navigate_to_internal(self.current_path.clone(), false);
+            return;
+        }
 
-        // Collect image entries
-        let image_entries: Vec<_> = self.file_entries.iter()
-            .filter(|e| e.is_image())
-            .map(|e| e.path.clone())
+        let Some(ref db) = self.metadata_db else {
+            return;
+        };
+        let records = match db.find_files_by_tags(&tag_ids) {
+            Ok(records) => records,
+            Err(e) => {
+                self.status.message = format!("Tag search failed: {}", e);
+                return;
+            }
+        };
+
+        // Results are previewed from disk; skip files that no longer exist
+        let entries: Vec<FileEntry> = records.iter()
+            .filter_map(|r| FileEntry::from_path(&r.path_display).ok())
             .collect();
+        let query = self.tag_panel.selected_names().join(" AND ");
+        let context = NavigationContext::TagSearch {
+            tag_ids,
+            query: query.clone(),
+            results: nav_results(&entries),
+            current_index: 0,
+        };
+        self.enter_results_view(context, entries);
+        self.status.message = format!("Tags: {} ({} files)", query, self.file_entries.len());
+    }
 
-        // Use rayon thread pool for batch thumbnail generation
-        rayon::spawn(move || {
-            let generator = app_core::ThumbnailGenerator::new(128);
-            for path in image_entries {
-                if let Ok(loaded) = generator.generate(path.as_path()) {
-                    // Use path-based hash with fixed dimensions (128x128)
-                    // This matches get_cached_sync lookup which uses ThumbnailSize::Small
-                    let path_hash = path.id();
-                    let cache_key = app_db::CacheKey::new(path_hash, 128, 128);
-                    let _ = cache.put(cache_key, &loaded.data);
-                }
+    /// Show `entries` in the catalog under a results context (tag search, timeline, ...)
+    /// Re-entering the same kind of view replaces it instead of stacking another history entry.
+    fn enter_results_view(&mut self, context: NavigationContext, entries: Vec<FileEntry>) {
+        if std::mem::discriminant(&context) == std::mem::discriminant(&self.nav_state.context) {
+            self.nav_state.context = context;
+        } else {
+            self.leave_results_view();
+            self.nav_state.navigate_to(context);
+        }
+
+        self.close_archives();
+        self.file_entries = entries;
+        self.apply_catalog_filter();
+        self.restore_selection();
+        self.selected_index = None;
+        self.catalog_items.clear();
+        self.request_thumbnails_for_current_directory();
+    }
+
+    /// Whether the catalog shows file name search results instead of a folder
+    fn in_search(&self) -> bool {
+        matches!(self.nav_state.context, NavigationContext::Search { .. })
+    }
+
+    /// Whether the catalog shows recently viewed files instead of a folder
+    fn in_recent(&self) -> bool {
+        matches!(self.nav_state.context, NavigationContext::Recent { .. })
+    }
+
+    /// Whether the catalog shows the images of the current folder and its subfolders
+    fn in_flattened(&self) -> bool {
+        matches!(self.nav_state.context, NavigationContext::Flattened { .. })
+    }
+
+    /// Whether the catalog shows a tag search / timeline / search / recent / flattened list
+    fn in_results_view(&self) -> bool {
+        self.in_tag_search() || self.in_timeline() || self.in_search() || self.in_recent() || self.in_flattened()
+    }
+
+    /// Pop a tag search / timeline / search / recent / flattened context back to folder browsing
+    fn leave_results_view(&mut self) {
+        if self.in_results_view() {
+            self.tag_panel.clear();
+            self.entry_sections.clear();
+            self.thumbnail_catalog.sections.clear();
+            self.nav_state.go_back();
+        }
+    }
+
+    /// Show files whose name or path matches the query (FTS index)
+    fn search_files(&mut self, query: String) {
+        if query.is_empty() {
+            if self.in_search() {
+                self.navigate_to_internal(self.current_path.clone(), false);
             }
-            // Request repaint after batch generation
-            egui_ctx.request_repaint();
+            return;
+        }
+
+        let Some(db) = self.metadata_db.clone() else {
+            self.status.message = "Search requires the metadata database".to_string();
+            return;
+        };
+
+        // Index the folder being browsed so its files are searchable
+        let mut files = Vec::new();
+        let folder = self.current_path.as_path().to_path_buf();
+        if !self.in_search() && self.current_archive.is_none() && self.search_indexed.as_ref() != Some(&folder) {
+            files = self.file_entries.iter()
+                .map(|e| (e.path.clone(), Some(e.size as i64), e.modified))
+                .collect();
+            self.search_indexed = Some(folder);
+        }
+
+        let worker_query = query.clone();
+        let job = BackgroundJob::spawn(0, &self.egui_ctx, move |_| {
+            if let Err(e) = db.upsert_files(&files) {
+                tracing::warn!("Failed to index folder for search: {}", e);
+            }
+            let records = db.fts_search(&worker_query, SEARCH_RESULT_LIMIT).map_err(|e| e.to_string())?;
+            Ok(records.iter().filter_map(|r| FileEntry::from_path(&r.path_display).ok()).collect())
         });
+        self.search_job = Some((query, job));
+        self.status.message = "Searching…".to_string();
     }
 
-    /// Load thumbnail texture for a file entry
-    /// Returns TextureHandle if thumbnail is cached, None otherwise (triggers async generation)
-    fn load_thumbnail_texture(&mut self, entry: &FileEntry) -> Option<egui::TextureHandle> {
-        let Some(ref thumbnail_manager) = self.thumbnail_manager else {
-            return None;
+    /// Show search results once the background search has finished
+    fn poll_search(&mut self) {
+        let Some((_, ref job)) = self.search_job else {
+            return;
+        };
+        let Some(result) = job.take_result() else {
+            return;
+        };
+        let Some((query, _)) = self.search_job.take() else {
+            return;
+        };
+        let entries = match result {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.status.message = format!("Search failed: {}", e);
+                return;
+            }
         };
 
-        let path_hash = entry.path.id();
+        let context = NavigationContext::Search {
+            query: query.clone(),
+            results: nav_results(&entries),
+            current_index: 0,
+        };
+        self.enter_results_view(context, entries);
+        self.status.message = format!("Search: {} ({} files)", query, self.file_entries.len());
+    }
 
-        // Check if texture already loaded
-        if let Some(texture_handle) = self.thumbnail_textures.get(&path_hash) {
-            return Some(texture_handle.clone());
+    /// Show recently viewed files, newest first
+    fn show_recent(&mut self) {
+        let Some(ref db) = self.metadata_db else {
+            self.status.message = "History requires the metadata database".to_string();
+            return;
+        };
+        let views = match db.recent_views(RECENT_VIEW_LIMIT) {
+            Ok(views) => views,
+            Err(e) => {
+                self.status.message = format!("Failed to load history: {}", e);
+                return;
+            }
+        };
+
+        let entries: Vec<FileEntry> = views.iter()
+            .filter_map(|(r, _)| FileEntry::from_path(&r.path_display).ok())
+            .collect();
+        let context = NavigationContext::Recent {
+            results: nav_results(&entries),
+            current_index: 0,
+        };
+        self.enter_results_view(context, entries);
+        self.status.message = format!("Recent: {} files", self.file_entries.len());
+    }
+
+    /// Show every image under the current folder as one list, or return to the folder
+    fn toggle_flatten(&mut self) {
+        if self.in_flattened() {
+            self.navigate_to_internal(self.current_path.clone(), false);
+        } else if self.current_archive.is_some() || self.in_results_view() {
+            self.status.message = "Flatten works on folders only".to_string();
+        } else {
+            self.flatten_subfolders();
         }
+    }
 
-        // Try to get cached thumbnail (sync)
-        if let Some(loaded) = thumbnail_manager.get_cached_sync(entry.path.as_path(), ThumbnailSize::Small) {
-            // Create egui texture
-            let color_image = egui::ColorImage::from_rgba_unmultiplied(
-                [loaded.width as usize, loaded.height as usize],
-                &loaded.data,
-            );
+    /// List the images of the current folder and its subfolders, headed by folder
+    fn flatten_subfolders(&mut self) {
+        let max_depth = state()
+            .map(|s| s.config.read().filer.flatten_max_depth)
+            .unwrap_or(8);
+        let root = self.current_path.clone();
+        let sort = self.folder_sort(&root);
+        let mut listing = DirectoryListing::start_recursive(root.as_path(), &self.list_options(sort), max_depth);
+        let entries = match listing.wait(SYNC_LISTING_BUDGET) {
+            Some(Ok(entries)) => entries,
+            Some(Err(e)) => {
+                self.status.message = format!("Flatten failed: {}", e);
+                return;
+            }
+            None => {
+                // Large tree: show it while the rest streams in (see poll_flatten)
+                let entries = listing.poll().0.into_iter().filter(|e| e.is_image()).collect();
+                self.show_flattened(root, entries);
+                self.status.message = format!("Flattening… {} images", self.file_entries.len());
+                self.pending_flatten = Some(listing);
+                return;
+            }
+        };
 
-            let texture_handle = self.egui_ctx.load_texture(
-                entry.name.clone(),
-                color_image,
-                egui::TextureOptions::LINEAR,
-            );
+        let entries = entries.into_iter().filter(|e| e.is_image()).collect();
+        self.show_flattened(root, entries);
+        self.status.message = format!("Flattened: {} images", self.file_entries.len());
+    }
 
-            self.thumbnail_textures.insert(path_hash, texture_handle.clone());
+    /// Show the images of a folder tree in the Flattened context, with folder headers
+    fn show_flattened(&mut self, root: UniversalPath, entries: Vec<FileEntry>) {
+        let sections = flatten_sections(root.as_path(), &entries);
+        let context = NavigationContext::Flattened {
+            root,
+            results: nav_results(&entries),
+            current_index: 0,
+        };
+        self.enter_results_view(context, entries);
+        self.set_sections(sections);
+    }
 
-            return Some(texture_handle);
+    /// Append images from the streaming recursive listing to the flattened view
+    fn poll_flatten(&mut self) {
+        let Some(listing) = self.pending_flatten.as_mut() else {
+            return;
+        };
+        let root = match self.nav_state.context {
+            NavigationContext::Flattened { ref root, .. } if root.as_path() == listing.path() => root.clone(),
+            // Left the flattened view
+            _ => {
+                self.pending_flatten = None;
+                return;
+            }
+        };
+
+        let (entries, done) = listing.poll();
+        if !entries.is_empty() {
+            self.file_entries.extend(entries.into_iter().filter(|e| e.is_image()));
+            self.apply_catalog_filter();
+            self.set_sections(flatten_sections(root.as_path(), &self.file_entries));
+            self.status.message = format!("Flattening… {} images", self.file_entries.len());
         }
 
-        // Check if already generating (avoid duplicate work)
-        {
-            let pending = self.pending_thumbnails.lock().unwrap();
-            if pending.contains(&path_hash) {
-                return None; // Already in-flight
+        let Some(result) = done else {
+            self.egui_ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            return;
+        };
+        self.pending_flatten = None;
+        if let NavigationContext::Flattened { ref mut results, .. } = self.nav_state.context {
+            *results = nav_results(&self.file_entries);
+        }
+        self.catalog_items.clear();
+        self.request_thumbnails_for_current_directory();
+        self.status.message = match result {
+            Ok(()) => format!("Flattened: {} images", self.file_entries.len()),
+            Err(e) => format!("Flatten failed: {}", e),
+        };
+    }
+
+    /// Whether the catalog shows the date timeline instead of a folder
+    fn in_timeline(&self) -> bool {
+        matches!(self.nav_state.context, NavigationContext::Timeline { .. })
+    }
+
+    /// Enter the timeline at the most recent month, or return to the folder
+    fn toggle_timeline(&mut self) {
+        if self.in_timeline() {
+            self.navigate_to_internal(self.current_path.clone(), false);
+            return;
+        }
+
+        if self.timeline_indexing.is_some() {
+            return;
+        }
+        let Some(db) = self.metadata_db.clone() else {
+            self.status.message = "Timeline requires the metadata database".to_string();
+            return;
+        };
+
+        // Index the folder being browsed so it shows up in the timeline
+        let files: Vec<_> = self.file_entries.iter()
+            .filter(|e| e.is_image() || e.is_video())
+            .map(|e| (e.path.clone(), Some(e.size as i64), e.modified))
+            .collect();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            if let Err(e) = db.upsert_files(&files) {
+                tracing::warn!("Failed to index folder for the timeline: {}", e);
+            }
+            let _ = tx.send(db.list_file_months().map_err(|e| e.to_string()));
+        });
+        self.timeline_indexing = Some(rx);
+        self.status.message = "Building timeline…".to_string();
+    }
+
+    /// Open the timeline once the background indexing has finished
+    fn poll_timeline_index(&mut self) {
+        let Some(ref rx) = self.timeline_indexing else {
+            return;
+        };
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(std::sync::mpsc::TryRecvError::Empty) => {
+                self.egui_ctx.request_repaint_after(std::time::Duration::from_millis(100));
+                return;
+            }
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => Err("indexing stopped".to_string()),
+        };
+        self.timeline_indexing = None;
+
+        match result {
+            Ok(months) => self.timeline_months = months,
+            Err(e) => {
+                self.status.message = format!("Timeline failed: {}", e);
+                return;
+            }
+        }
+        if self.timeline_months.is_empty() {
+            self.status.message = "No dated files in the timeline".to_string();
+            return;
+        }
+
+        self.show_timeline_month(self.timeline_months.len() - 1);
+    }
+
+    /// Show the files of one timeline month, grouped under date headers
+    fn show_timeline_month(&mut self, index: usize) {
+        let Some((month, _)) = self.timeline_months.get(index).cloned() else {
+            return;
+        };
+        let Some((start, end)) = month_bounds(&month) else {
+            return;
+        };
+        let Some(ref db) = self.metadata_db else {
+            return;
+        };
+        let records = match db.list_files_by_date_range(start, end) {
+            Ok(records) => records,
+            Err(e) => {
+                self.status.message = format!("Timeline failed: {}", e);
+                return;
             }
+        };
+
+        let mut entries: Vec<FileEntry> = records.iter()
+            .filter_map(|r| FileEntry::from_path(&r.path_display).ok())
+            .collect();
+        let (grouping, newest_first) = state()
+            .map(|s| {
+                let config = s.config.read();
+                (config.filer.timeline_grouping, config.filer.timeline_newest_first)
+            })
+            .unwrap_or((TimelineGrouping::Day, true));
+        entries.sort_by_key(|e| e.modified.unwrap_or(0));
+        if newest_first {
+            entries.reverse();
         }
 
-        // Mark as pending before spawning
-        {
-            let mut pending = self.pending_thumbnails.lock().unwrap();
-            pending.insert(path_hash);
+        // Date headers between groups
+        let format = match grouping {
+            TimelineGrouping::Day => "%Y-%m-%d (%a)",
+            TimelineGrouping::Month => "%Y-%m",
+        };
+        let mut sections: Vec<(usize, String)> = Vec::new();
+        for (i, entry) in entries.iter().enumerate() {
+            let title = entry.modified
+                .and_then(|t| Local.timestamp_opt(t, 0).single())
+                .map(|dt| dt.format(format).to_string())
+                .unwrap_or_default();
+            if sections.last().map(|(_, t)| t != &title).unwrap_or(true) {
+                sections.push((i, title));
+            }
         }
 
-        // Use rayon thread pool (bounded) instead of unbounded std::thread::spawn
-        let path = entry.path.clone();
-        let egui_ctx = self.egui_ctx.clone();
-        let cache = self.thumbnail_cache.clone();
-        let pending_thumbnails = self.pending_thumbnails.clone();
+        let context = NavigationContext::Timeline {
+            start_date: start,
+            end_date: end,
+            results: nav_results(&entries),
+            current_index: 0,
+        };
+        self.enter_results_view(context, entries);
+        self.set_sections(sections);
+        self.timeline_index = index;
+        self.status.message = format!("Timeline: {} ({} files)", month, self.file_entries.len());
+    }
 
-        rayon::spawn(move || {
-            let generator = app_core::ThumbnailGenerator::new(128);
-            if let Ok(loaded) = generator.generate(path.as_path()) {
-                // Store in cache using path-based hash with fixed dimensions (128x128)
-                // This matches get_cached_sync lookup which uses ThumbnailSize::Small (128x128)
-                if let Some(ref cache) = cache {
-                    let cache_key = app_db::CacheKey::new(path_hash, 128, 128);
-                    let _ = cache.put(cache_key, &loaded.data);
+    /// Rebuild the catalog view: the entries passing the catalog filter (rating threshold / marked / extension)
+    /// file_entries itself is left alone so indices stay valid while a filter is on.
+    fn apply_catalog_filter(&mut self) {
+        let options = ListOptions { filter_extensions: self.extension_filter.clone(), ..Default::default() };
+        let filter = self.catalog_filter;
+        let ratings = if filter.is_active() && filter.min_rating > 0 {
+            self.file_ratings(&self.file_entries)
+        } else {
+            HashMap::new()
+        };
+
+        let marked_files = &self.marked_files;
+        self.catalog_view = self.file_entries.iter().enumerate()
+            .filter(|(_, e)| options.matches_extension(e))
+            .filter(|(_, e)| {
+                if !filter.is_active() {
+                    return true;
                 }
-                // Request repaint to show the newly generated thumbnail
-                egui_ctx.request_repaint();
+                let hash = e.path.id();
+                let rating = ratings.get(&hash).copied().unwrap_or(0);
+                filter.matches(e.is_dir, rating, marked_files.contains(&hash))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        self.catalog_items.clear();
+        self.map_sections();
+    }
+
+    /// Section headers for the current results view, by index into file_entries
+    fn set_sections(&mut self, sections: Vec<(usize, String)>) {
+        self.entry_sections = sections;
+        self.map_sections();
+    }
+
+    /// Place the section headers on the catalog view; sections with every entry filtered out are dropped
+    fn map_sections(&mut self) {
+        let mut sections: Vec<(usize, String)> = Vec::new();
+        for (start, title) in &self.entry_sections {
+            let position = self.catalog_view.partition_point(|&i| i < *start);
+            if position == self.catalog_view.len() {
+                break;
             }
-            // Remove from pending set when done
-            if let Ok(mut pending) = pending_thumbnails.lock() {
-                pending.remove(&path_hash);
+            if sections.last().map(|(p, _)| *p == position).unwrap_or(false) {
+                sections.pop();
             }
-        });
+            sections.push((position, title.clone()));
+        }
+        self.thumbnail_catalog.sections = sections;
+    }
 
-        None
+    /// Position of an entry in the catalog view (None while filtered out)
+    fn catalog_position(&self, index: usize) -> Option<usize> {
+        self.catalog_view.binary_search(&index).ok()
+    }
+
+    /// Entry shown at a catalog position
+    fn catalog_entry(&self, position: usize) -> Option<usize> {
+        self.catalog_view.get(position).copied()
+    }
+
+    /// The selection and multi-selection as catalog positions
+    fn catalog_selection(&self) -> (Option<usize>, HashSet<usize>) {
+        let selected = self.selected_index.and_then(|i| self.catalog_position(i));
+        let multi = self.nav_state.selection.selected.iter()
+            .filter_map(|&i| self.catalog_position(i))
+            .collect();
+        (selected, multi)
+    }
+
+    /// "n / N" within the catalog view
+    fn view_position_text(&self, index: usize) -> String {
+        match self.catalog_position(index) {
+            Some(position) => format!("{} / {}", position + 1, self.catalog_view.len()),
+            None => format!("- / {}", self.catalog_view.len()),
+        }
+    }
+
+    /// Change the catalog filter and apply it to the current listing
+    fn set_catalog_filter(&mut self, filter: CatalogFilter) {
+        if filter == self.catalog_filter {
+            return;
+        }
+        self.catalog_filter = filter;
+        self.apply_catalog_filter();
+    }
+
+    /// Change the extension filter text and apply it to the current listing
+    fn set_extension_filter(&mut self, text: String) {
+        if text == self.extension_filter_text {
+            return;
+        }
+        let extensions = ListOptions::parse_extension_filter(&text);
+        self.extension_filter_text = text;
+        if extensions != self.extension_filter {
+            self.extension_filter = extensions;
+            self.apply_catalog_filter();
+        }
+    }
+
+    /// Rebuild the current listing (folder, archive or results view)
+    fn reload_listing(&mut self) {
+        if self.in_tag_search() {
+            self.search_by_tags();
+        } else if self.in_timeline() {
+            self.show_timeline_month(self.timeline_index);
+        } else if let NavigationContext::Search { query, .. } = &self.nav_state.context {
+            self.search_files(query.clone());
+        } else if self.in_recent() {
+            self.show_recent();
+        } else if self.in_flattened() {
+            self.flatten_subfolders();
+        } else if self.current_archive.is_some() {
+            self.show_archive_dir(self.archive_inner_path.clone());
+        } else {
+            self.refresh_current_directory();
+        }
+        self.catalog_items.clear();
+    }
+
+    /// Navigate back in history
+    fn navigate_back(&mut self) {
+        if let Some(prev_path) = self.history_back.pop() {
+            self.history_forward.push(self.current_path.clone());
+            self.navigate_to_internal(prev_path, false);
+        }
+    }
+
+    /// Navigate forward in history
+    fn navigate_forward(&mut self) {
+        if let Some(next_path) = self.history_forward.pop() {
+            self.history_back.push(self.current_path.clone());
+            self.navigate_to_internal(next_path, false);
+        }
+    }
+
+    /// Listing options that apply a folder sort and the extension filter
+    fn list_options(&self, sort: FolderSort) -> ListOptions {
+        ListOptions {
+            show_hidden: state().map(|s| s.config.read().filer.show_hidden_files).unwrap_or(false),
+            sort_by: sort.sort_by,
+            sort_order: sort.sort_order,
+            dirs_first: sort.dirs_first,
+            ..Default::default()
+        }
+    }
+
+    /// Sort to use for a folder: its stored preference, else the configured default
+    fn folder_sort(&self, path: &UniversalPath) -> FolderSort {
+        if let Some(sort) = self.metadata_db.as_ref().and_then(|db| db.get_folder_sort(path.id()).ok().flatten()) {
+            return sort;
+        }
+
+        let filer = state().map(|s| s.config.read().filer.clone()).unwrap_or_default();
+        FolderSort {
+            sort_by: match filer.sort_by {
+                app_core::SortBy::Name => SortBy::Name,
+                app_core::SortBy::Size => SortBy::Size,
+                app_core::SortBy::Modified => SortBy::Modified,
+                app_core::SortBy::Type => SortBy::Extension,
+            },
+            sort_order: match filer.sort_order {
+                app_core::SortOrder::Ascending => SortOrder::Ascending,
+                app_core::SortOrder::Descending => SortOrder::Descending,
+            },
+            dirs_first: filer.dirs_first,
+        }
+    }
+
+    /// Show a sort in the toolbar (apply_sort uses it)
+    fn set_sort(&mut self, sort: FolderSort) {
+        self.toolbar_state.sort_mode = SortMode::from_sort(sort.sort_by, sort.sort_order);
+        self.toolbar_state.dirs_first = sort.dirs_first;
+    }
+
+    /// Remember the toolbar's sort for the current folder
+    fn save_folder_sort(&self) {
+        // Result views and archives have no folder to key the preference on
+        if self.current_archive.is_some() || self.in_results_view() {
+            return;
+        }
+        let Some(ref db) = self.metadata_db else {
+            return;
+        };
+
+        let (sort_by, sort_order) = self.toolbar_state.sort_mode.to_sort();
+        let sort = FolderSort { sort_by, sort_order, dirs_first: self.toolbar_state.dirs_first };
+        if let Err(e) = db.set_folder_sort(self.current_path.id(), sort) {
+            tracing::warn!("Failed to save folder sort: {}", e);
+        }
+    }
+
+    /// Apply current sort mode to file entries
+    fn apply_sort(&mut self) {
+        let (sort_by, sort_order) = self.toolbar_state.sort_mode.to_sort();
+        sort_entries(&mut self.file_entries, sort_by, sort_order, self.toolbar_state.dirs_first);
+    }
+
+    /// Switch the sort of the shown entries, keeping the selected file where it is on screen
+    fn change_sort(&mut self, mode: SortMode) {
+        let selected = self.selected_index
+            .and_then(|i| Some((self.catalog_position(i)?, self.file_entries.get(i)?.path.id())));
+        self.toolbar_state.sort_mode = mode;
+        self.apply_sort();
+        self.save_folder_sort();
+        self.apply_catalog_filter();
+        self.restore_selection();
+
+        if let Some((old_position, id)) = selected {
+            self.selected_index = self.file_entries.iter().position(|e| e.path.id() == id);
+            self.file_browser.selected = self.selected_index;
+            if let Some(new_position) = self.selected_index.and_then(|i| self.catalog_position(i)) {
+                self.thumbnail_catalog.keep_in_view(old_position, new_position);
+            }
+        }
+    }
+
+    /// Susie bridge trouble, else the running extraction's progress, for the status bar
+    fn bridge_status(&self) -> Option<(String, egui::Color32)> {
+        match self.bridge_health.as_ref().map(BridgeHealthHandle::get) {
+            Some(BridgeHealth::Unavailable) => {
+                return Some(("⚠ Plugin bridge unavailable".to_string(), egui::Color32::from_rgb(230, 90, 80)));
+            }
+            Some(BridgeHealth::Unresponsive) => {
+                return Some(("⚠ Plugin bridge not responding".to_string(), egui::Color32::from_rgb(230, 180, 60)));
+            }
+            Some(BridgeHealth::Healthy) | None => {}
+        }
+        let percent = self.bridge_progress.load(Ordering::Relaxed);
+        (percent != NO_PROGRESS).then(|| (format!("Extracting… {}%", percent), egui::Color32::GRAY))
+    }
+
+    /// The current archive, when it is read through a Susie plugin
+    fn susie_archive(&self) -> Option<(Arc<parking_lot::Mutex<SusieBridge>>, PathBuf)> {
+        let vfs = self.current_archive.as_ref().filter(|vfs| vfs.format() == ArchiveFormat::Susie)?;
+        Some((self.susie_bridge.clone()?, vfs.archive_path().to_path_buf()))
+    }
+
+    /// Enter an archive file and display its contents as if it were a directory
+    /// It is opened in the background (see poll_archive_opening).
+    fn enter_archive(&mut self, archive_path: UniversalPath) {
+        let bridge = self.susie_bridge.clone();
+        self.start_archive_opening(ArchiveOrigin::Folder, move || {
+            open_archive(archive_path.as_path(), bridge.as_deref()).map_err(|e| format!("Cannot open archive: {}", e))
+        });
+    }
+
+    fn start_archive_opening(
+        &mut self,
+        origin: ArchiveOrigin,
+        open: impl FnOnce() -> Result<VirtualFileSystem, String> + Send + 'static,
+    ) {
+        let job = BackgroundJob::spawn(0, &self.egui_ctx, move |_| open());
+        self.archive_opening = Some(ArchiveOpening { origin, job });
+        self.status.message = "Opening archive…".to_string();
+    }
+
+    /// Show an archive once it has been opened in the background
+    fn poll_archive_opening(&mut self) {
+        let Some(ref opening) = self.archive_opening else {
+            return;
+        };
+        let Some(result) = opening.job.take_result() else {
+            return;
+        };
+        let Some(opening) = self.archive_opening.take() else {
+            return;
+        };
+        let vfs = match result {
+            Ok(vfs) => vfs,
+            Err(e) => {
+                tracing::error!("Failed to open archive: {}", e);
+                self.status.message = e;
+                return;
+            }
+        };
+
+        match opening.origin {
+            ArchiveOrigin::Folder => {
+                self.close_archives();
+                self.current_archive = Some(vfs);
+            }
+            ArchiveOrigin::Nested { entry_path, extracted } => {
+                let Some(outer) = self.current_archive.replace(vfs) else {
+                    self.current_archive = None;
+                    remove_extracted(&extracted);
+                    return;
+                };
+                self.archive_stack.push(OuterArchive {
+                    vfs: outer,
+                    inner_path: std::mem::take(&mut self.archive_inner_path),
+                    entry_path,
+                    extracted,
+                });
+            }
+        }
+        self.show_archive_dir(String::new());
+    }
+
+    /// List one folder inside the current archive ("" = archive root)
+    fn show_archive_dir(&mut self, inner_dir: String) {
+        let Some(ref vfs) = self.current_archive else {
+            return;
+        };
+        // Nested archives live in a temp file; list them under their entry path
+        let archive_path = match self.archive_stack.last() {
+            Some(outer) => outer.entry_path.clone(),
+            None => vfs.archive_path().clone(),
+        };
+        match vfs.list_dir(&inner_dir) {
+            Ok(vfs_entries) => {
+                // Clear previous archive path mappings
+                self.archive_path_map.clear();
+
+                // Convert VfsEntry to FileEntry for display
+                let file_entries: Vec<FileEntry> = vfs_entries.iter().filter_map(|ve| {
+                    // Create a pseudo-path for the archive entry
+                    let entry_path = archive_path.join(&ve.path);
+
+                    // Store mapping from entry path ID to archive inner path
+                    self.archive_path_map.insert(entry_path.id(), ve.path.clone());
+
+                    Some(FileEntry {
+                        path: entry_path,
+                        name: ve.name.clone(),
+                        is_dir: ve.is_dir,
+                        is_hidden: false,
+                        size: ve.size,
+                        modified: ve.modified,
+                        extension: std::path::Path::new(&ve.name)
+                            .extension()
+                            .map(|e| e.to_string_lossy().to_lowercase())
+                            .unwrap_or_default(),
+                        is_symlink: false,
+                        link_target: None,
+                    })
+                }).collect();
+
+                self.archive_inner_path = inner_dir;
+                self.file_entries = file_entries;
+                self.apply_catalog_filter();
+                self.restore_selection();
+                self.selected_index = None;
+                self.catalog_items.clear();
+                self.status.message = format!("Archive: {} ({} items)",
+                    archive_path.join(&self.archive_inner_path).display(), self.file_entries.len());
+            }
+            Err(e) => {
+                tracing::error!("Failed to list archive entries: {}", e);
+                self.status.message = format!("Archive error: {}", e);
+            }
+        }
+    }
+
+    /// Open an archive stored inside the current archive
+    /// It is extracted to a temp file and the outer archive is kept on a stack.
+    fn enter_nested_archive(&mut self, entry: &FileEntry) {
+        if self.archive_stack.len() + 1 >= MAX_ARCHIVE_DEPTH {
+            self.status.message = format!("Archives nested too deep (max {})", MAX_ARCHIVE_DEPTH);
+            return;
+        }
+        let extraction = match self.extraction(entry) {
+            Ok(Some(extraction)) => extraction,
+            Ok(None) => return,
+            Err(e) => {
+                self.status.message = format!("Cannot extract {}: {}", entry.name, e);
+                return;
+            }
+        };
+        let origin = ArchiveOrigin::Nested { entry_path: entry.path.clone(), extracted: extraction.dest.clone() };
+        let name = entry.name.clone();
+        let bridge = self.susie_bridge.clone();
+        self.start_archive_opening(origin, move || {
+            let extracted = extraction.run().map_err(|e| format!("Cannot extract {}: {}", name, e))?;
+            open_archive(&extracted, bridge.as_deref()).map_err(|e| {
+                let _ = std::fs::remove_file(&extracted);
+                format!("Cannot open archive: {}", e)
+            })
+        });
+    }
+
+    /// Leave a nested archive for the one containing it; false at the outermost
+    fn pop_nested_archive(&mut self) -> bool {
+        let Some(outer) = self.archive_stack.pop() else {
+            return false;
+        };
+        self.current_archive = Some(outer.vfs);
+        remove_extracted(&outer.extracted);
+        self.show_archive_dir(outer.inner_path);
+        true
+    }
+
+    /// Leave all archives, deleting extracted nested ones
+    fn close_archives(&mut self) {
+        for outer in self.archive_stack.drain(..) {
+            remove_extracted(&outer.extracted);
+        }
+        self.current_archive = None;
+        self.archive_inner_path.clear();
+        self.archive_path_map.clear();
+    }
+
+    /// Descend into a folder listed inside the current archive
+    fn enter_archive_entry_dir(&mut self, entry: &FileEntry) {
+        if let Some(inner_dir) = self.archive_path_map.get(&entry.path.id()).cloned() {
+            self.show_archive_dir(inner_dir);
+        }
+    }
+
+    /// Request thumbnails for all image files in current directory
+    /// This pre-generates thumbnails in the background, visible items first
+    fn request_thumbnails_for_current_directory(&mut self) {
+        let Some(ref manager) = self.thumbnail_manager else {
+            return;
+        };
+
+        // Slots follow listing order so catalog indices map straight onto the queue
+        let slots: Vec<_> = self.file_entries.iter()
+            .map(|e| (e.is_image() || e.is_video() || e.is_pdf()).then(|| e.path.clone()))
+            .collect();
+
+        let egui_ctx = self.egui_ctx.clone();
+        manager.enqueue_batch(slots, self.thumbnail_size, Arc::new(move || egui_ctx.request_repaint()));
+    }
+
+    /// Load thumbnail texture for a file entry
+    /// Returns TextureHandle if thumbnail is cached, None otherwise (triggers async generation)
+    fn load_thumbnail_texture(&mut self, entry: &FileEntry) -> Option<egui::TextureHandle> {
+        let Some(ref thumbnail_manager) = self.thumbnail_manager else {
+            return None;
+        };
+
+        let path_hash = entry.path.id();
+
+        // Check if texture already loaded
+        if let Some(texture_handle) = self.thumbnail_textures.get(&path_hash) {
+            return Some(texture_handle.clone());
+        }
+
+        // Try to get cached thumbnail (sync)
+        if let Some(loaded) = thumbnail_manager.get_cached_sync(entry.path.as_path(), self.thumbnail_size) {
+            // Create egui texture
+            let color_image = egui::ColorImage::from_rgba_unmultiplied(
+                [loaded.width as usize, loaded.height as usize],
+                &loaded.data,
+            );
+
+            let texture_handle = self.egui_ctx.load_texture(
+                entry.name.clone(),
+                color_image,
+                egui::TextureOptions::LINEAR,
+            );
+
+            self.thumbnail_textures.insert(path_hash, texture_handle.clone());
+
+            return Some(texture_handle);
+        }
+
+        // Queue generation on the manager's worker pool (deduplicated, failures not retried)
+        let egui_ctx = self.egui_ctx.clone();
+        thumbnail_manager.request(entry.path.clone(), self.thumbnail_size, Arc::new(move || egui_ctx.request_repaint()));
+
+        None
+    }
+
+    /// Capture the selected file's metadata into the metadata clipboard
+    fn copy_metadata(&mut self, target: app_core::CopyTarget) {
+        use app_core::CopyTarget;
+
+        let Some(entry) = self.selected_index.and_then(|i| self.file_entries.get(i)) else {
+            return;
+        };
+        let Some(ref db) = self.metadata_db else {
+            self.status.message = "Copy metadata requires the metadata database".to_string();
+            return;
+        };
+
+        let hash = entry.path.id();
+        let file_id = match db.upsert_file(&entry.path, Some(entry.size as i64), entry.modified) {
+            Ok(id) => id,
+            Err(e) => {
+                self.status.message = format!("Failed to read metadata: {}", e);
+                return;
+            }
+        };
+        let with_rating = matches!(target, CopyTarget::Rating | CopyTarget::All);
+        let with_tags = matches!(target, CopyTarget::Tags | CopyTarget::All);
+        let with_rest = target == CopyTarget::All;
+
+        let clipboard = MetaClipboard {
+            target,
+            rating: if with_rating { db.get_rating(hash).unwrap_or(0) } else { 0 },
+            label: if with_rest { db.get_label(hash).ok().flatten() } else { None },
+            tag_ids: if with_tags {
+                db.get_file_tags(file_id).map(|tags| tags.into_iter().map(|t| t.tag_id).collect()).unwrap_or_default()
+            } else {
+                Vec::new()
+            },
+            comment: if with_rest { db.get_comment(hash).ok().flatten() } else { None },
+        };
+
+        let what = match target {
+            CopyTarget::Rating => "rating",
+            CopyTarget::Tags => "tags",
+            CopyTarget::All => "all metadata",
+        };
+        self.status.message = format!("Copied {} from {}", what, entry.name);
+        self.meta_clipboard = Some(clipboard);
+    }
+
+    /// Apply the metadata clipboard to the marked files (or the selection if none are marked)
+    fn paste_metadata(&mut self) {
+        use app_core::CopyTarget;
+
+        let Some(ref clipboard) = self.meta_clipboard else {
+            self.status.message = "Nothing to paste".to_string();
+            return;
+        };
+        let Some(ref db) = self.metadata_db else {
+            return;
+        };
+
+        let mut targets: Vec<&FileEntry> = self.file_entries.iter()
+            .filter(|e| !e.is_dir && self.marked_files.contains(&e.path.id()))
+            .collect();
+        if targets.is_empty() {
+            targets.extend(self.selected_index.and_then(|i| self.file_entries.get(i)));
+        }
+
+        let mut pasted = 0;
+        for entry in targets {
+            let hash = entry.path.id();
+            let result = db.upsert_file(&entry.path, Some(entry.size as i64), entry.modified)
+                .and_then(|file_id| {
+                    // Only the copied fields are written; tags are added, never removed
+                    if matches!(clipboard.target, CopyTarget::Rating | CopyTarget::All) {
+                        db.set_rating(hash, clipboard.rating)?;
+                    }
+                    if matches!(clipboard.target, CopyTarget::Tags | CopyTarget::All) {
+                        for tag_id in &clipboard.tag_ids {
+                            db.add_tag_to_file(file_id, *tag_id)?;
+                        }
+                    }
+                    if clipboard.target == CopyTarget::All {
+                        db.set_label(hash, clipboard.label)?;
+                        if let Some(ref comment) = clipboard.comment {
+                            db.set_comment(hash, comment)?;
+                        }
+                    }
+                    Ok(())
+                });
+            match result {
+                Ok(()) => pasted += 1,
+                Err(e) => tracing::warn!("Failed to paste metadata to {}: {}", entry.path, e),
+            }
+        }
+
+        self.status.message = format!("Pasted metadata to {} files", pasted);
+        if clipboard.target != CopyTarget::Rating {
+            self.refresh_tags();
+        }
+        self.catalog_items.clear();
+    }
+
+    /// Store a file's comment and update its catalog indicator
+    fn save_comment(&mut self, entry: &FileEntry, comment: &str) {
+        let Some(ref db) = self.metadata_db else {
+            return;
+        };
+        let result = db.upsert_file(&entry.path, Some(entry.size as i64), entry.modified)
+            .and_then(|_| db.set_comment(entry.path.id(), comment));
+        match result {
+            Ok(()) => {
+                let path = entry.path.as_path();
+                if let Some(item) = self.catalog_items.iter_mut().find(|i| i.path == path) {
+                    item.has_comment = !comment.is_empty();
+                }
+                self.status.message = if comment.is_empty() {
+                    format!("Comment removed: {}", entry.name)
+                } else {
+                    format!("Comment saved: {}", entry.name)
+                };
+            }
+            Err(e) => self.status.message = format!("Failed to save comment: {}", e),
+        }
+    }
+
+    /// Write metadata sidecars for the current folder, or the whole catalog
+    fn export_sidecars(&mut self, whole_catalog: bool) {
+        let Some(db) = self.metadata_db.clone() else {
+            return;
+        };
+        if !whole_catalog && self.current_archive.is_some() {
+            self.status.message = "Sidecars can't be written inside archives".to_string();
+            return;
+        }
+        if self.sidecar_export.is_some() {
+            return;
+        }
+
+        let paths: Vec<UniversalPath> = if whole_catalog {
+            Vec::new()
+        } else {
+            self.file_entries.iter().filter(|e| !e.is_dir).map(|e| e.path.clone()).collect()
+        };
+        self.sidecar_export = Some(BackgroundJob::spawn(0, &self.egui_ctx, move |_| {
+            if whole_catalog {
+                db.export_all_sidecars().map_err(|e| e.to_string())
+            } else {
+                Ok(db.export_sidecars(&paths))
+            }
+        }));
+        self.status.message = "Exporting sidecars…".to_string();
+    }
+
+    /// Report the outcome of a finished sidecar export
+    fn poll_sidecar_export(&mut self) {
+        let Some(ref job) = self.sidecar_export else {
+            return;
+        };
+        let Some(result) = job.take_result() else {
+            return;
+        };
+        self.sidecar_export = None;
+
+        self.status.message = match result {
+            Ok(export) => match export.errors.first() {
+                None => format!("Exported {} sidecar files", export.written),
+                Some(first) => format!(
+                    "Exported {} sidecar files, {} failed ({})",
+                    export.written, export.errors.len(), first
+                ),
+            },
+            Err(e) => format!("Failed to export sidecars: {}", e),
+        };
+    }
+
+    /// Read metadata sidecars for the files in the current folder
+    fn import_sidecars(&mut self) {
+        let Some(ref db) = self.metadata_db else {
+            return;
+        };
+
+        let mut imported = 0;
+        for entry in self.file_entries.iter().filter(|e| !e.is_dir) {
+            match db.import_sidecar(&entry.path, Some(entry.size as i64), entry.modified) {
+                Ok(true) => imported += 1,
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Failed to import sidecar for {}: {}", entry.path, e),
+            }
+        }
+
+        self.status.message = format!("Imported {} sidecar files", imported);
+        if imported > 0 {
+            self.refresh_tags();
+            self.catalog_items.clear();
+        }
+    }
+
+    /// Hash the images in the current folder in the background (file.find_duplicates)
+    fn find_duplicates(&mut self) {
+        if self.current_archive.is_some() {
+            self.status.message = "Duplicate search is not available inside archives".to_string();
+            return;
+        }
+        if self.duplicate_scan.is_some() {
+            return;
+        }
+
+        let files: Vec<FileEntry> = self.file_entries.iter()
+            .filter(|e| e.is_image())
+            .cloned()
+            .collect();
+        let threshold = state()
+            .map(|s| s.config.read().filer.duplicate_threshold)
+            .unwrap_or(10);
+
+        let paths: Vec<(UniversalPath, i64)> = files.iter()
+            .map(|e| (e.path.clone(), e.modified.unwrap_or(0)))
+            .collect();
+        let cache = self.thumbnail_cache.clone();
+
+        let job = BackgroundJob::spawn(files.len(), &self.egui_ctx, move |progress| {
+            use rayon::prelude::*;
+
+            let hashes: Vec<Option<u64>> = paths.par_iter()
+                .map(|(path, modified)| {
+                    let path_hash = path.id();
+                    // Reuse hashes of unchanged files
+                    let cached = cache.as_ref()
+                        .and_then(|c| c.get_phash(path_hash, *modified).ok().flatten());
+                    let hash = cached.or_else(|| {
+                        let img = image::open(path.as_path()).ok()?;
+                        let hash = app_core::phash::dhash(&img);
+                        if let Some(ref cache) = cache {
+                            let _ = cache.put_phash(path_hash, *modified, hash);
+                        }
+                        Some(hash)
+                    });
+                    progress.step();
+                    hash
+                })
+                .collect();
+
+            // Pairwise grouping is quadratic; keep it in the worker too
+            let hashed: Vec<(usize, u64)> = hashes.iter()
+                .enumerate()
+                .filter_map(|(i, h)| h.map(|h| (i, h)))
+                .collect();
+            let values: Vec<u64> = hashed.iter().map(|(_, h)| *h).collect();
+            app_core::phash::group_similar(&values, threshold)
+                .into_iter()
+                .map(|group| group.into_iter().map(|i| hashed[i].0).collect())
+                .collect()
+        });
+
+        self.duplicate_review = Some(DuplicateReview::new(files.len()));
+        self.duplicate_scan = Some(DuplicateScan { files, job });
+    }
+
+    /// Update duplicate scan progress and show the groups once hashing finishes
+    fn poll_duplicate_scan(&mut self) {
+        let Some(ref scan) = self.duplicate_scan else {
+            return;
+        };
+        let Some(ref mut review) = self.duplicate_review else {
+            // Window closed: the worker finishes on its own and its result is dropped
+            self.duplicate_scan = None;
+            return;
+        };
+
+        let Some(groups) = scan.job.take_result() else {
+            review.progress = Some(scan.job.progress());
+            return;
+        };
+
+        let groups = groups.into_iter()
+            .map(|group| {
+                group.into_iter()
+                    .map(|i| {
+                        let entry = &scan.files[i];
+                        DuplicateFile {
+                            path: entry.path.as_path().to_path_buf(),
+                            name: entry.name.clone(),
+                            size: entry.size,
+                            marked: false,
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        review.set_groups(groups);
+        self.status.message = format!("Found {} duplicate groups", review.groups.len());
+        self.duplicate_scan = None;
+    }
+
+    /// Open the batch convert window for the marked images (file.batch_convert)
+    fn open_batch_convert(&mut self) {
+        if self.batch_convert.is_some() {
+            self.status.message = "A batch conversion is already running".to_string();
+            return;
+        }
+        if self.current_archive.is_some() {
+            self.status.message = "Batch conversion is not available inside archives".to_string();
+            return;
+        }
+        let paths: Vec<PathBuf> = self.operation_targets().into_iter()
+            .filter(|path| path.is_file() && is_supported_image(path))
+            .collect();
+        let Some(first) = paths.first() else {
+            self.status.message = "No images to convert".to_string();
+            return;
+        };
+
+        let conflict = state().map(|s| s.config.read().filer.conflict_policy).unwrap_or(ConflictPolicy::Rename);
+        let dest = self.current_path.as_path().join("converted");
+        let dialog = BatchConvertDialog::new(paths.len(), dest, Some(RenameSource::load(first)), conflict);
+        self.batch_convert_dialog = Some(dialog);
+        self.batch_convert_paths = paths;
+    }
+
+    /// Convert the files picked in open_batch_convert on a background thread
+    fn start_batch_convert(&mut self, options: ConvertOptions) {
+        let paths = std::mem::take(&mut self.batch_convert_paths);
+        if let Err(e) = std::fs::create_dir_all(&options.dest) {
+            self.status.message = format!("Cannot create {}: {}", options.dest.display(), e);
+            self.batch_convert_dialog = None;
+            return;
+        }
+
+        let dest = options.dest.clone();
+        let worker_paths = paths.clone();
+        // One file at a time, so numbered names never race each other
+        let job = BackgroundJob::spawn(paths.len(), &self.egui_ctx, move |progress| {
+            let mut outcomes = Vec::with_capacity(worker_paths.len());
+            for (i, path) in worker_paths.iter().enumerate() {
+                if progress.is_cancelled() {
+                    break;
+                }
+                outcomes.push(app_core::batch_convert::convert_file(path, i + 1, &options).map_err(|e| e.to_string()));
+                progress.step();
+            }
+            outcomes
+        });
+
+        self.batch_convert = Some(BatchConvertJob { paths, dest, job });
+    }
+
+    /// Update batch convert progress and summarize once the job finishes
+    fn poll_batch_convert(&mut self) {
+        let Some(ref job) = self.batch_convert else {
+            return;
+        };
+        let Some(results) = job.job.take_result() else {
+            if let Some(ref mut dialog) = self.batch_convert_dialog {
+                dialog.progress = Some(job.job.progress());
+            }
+            return;
+        };
+
+        let mut summary = BatchConvertSummary::default();
+        for (path, result) in job.paths.iter().zip(&results) {
+            match result {
+                Ok(ConvertOutcome::Written(_)) => summary.written += 1,
+                Ok(ConvertOutcome::Skipped(_)) => summary.skipped += 1,
+                Err(e) => summary.errors.push(format!(
+                    "{}: {}",
+                    path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default(),
+                    e
+                )),
+            }
+        }
+        let cancelled = results.len() < job.paths.len();
+        self.status.message = format!(
+            "Converted {} files{}{}",
+            summary.written,
+            if summary.errors.is_empty() { String::new() } else { format!(", {} failed", summary.errors.len()) },
+            if cancelled { " (cancelled)" } else { "" },
+        );
+        if job.dest.parent() == Some(self.current_path.as_path()) || job.dest == self.current_path.as_path() {
+            self.refresh_current_directory();
+        }
+        if let Some(ref mut dialog) = self.batch_convert_dialog {
+            dialog.finish(summary);
+        }
+        self.batch_convert = None;
+    }
+
+    /// Remember which files the multi-selection points at
+    fn record_selection(&mut self) {
+        self.selection_ids = self.nav_state.selection.selected.iter()
+            .filter_map(|&i| self.file_entries.get(i).map(|e| e.path.id()))
+            .collect();
+    }
+
+    /// Point the multi-selection back at its files after the listing changed
+    /// Files no longer listed drop out, so indices never pick up other files.
+    fn restore_selection(&mut self) {
+        let ids: HashSet<u64> = self.selection_ids.iter().copied().collect();
+        self.nav_state.selection.clear();
+        self.nav_state.selection.selected = self.file_entries.iter().enumerate()
+            .filter(|(_, e)| ids.contains(&e.path.id()))
+            .map(|(i, _)| i)
+            .collect();
+        self.record_selection();
+    }
+
+    /// Paths a file command acts on: the marked files in the listing, else the multi-selection, else the selected file
+    fn operation_targets(&self) -> Vec<PathBuf> {
+        let marked: Vec<PathBuf> = self.file_entries.iter()
+            .filter(|e| self.marked_files.contains(&e.path.id()))
+            .map(|e| e.path.as_path().to_path_buf())
+            .collect();
+        if !marked.is_empty() {
+            return marked;
+        }
+        // Resolved by id: the indices go stale whenever the listing changes
+        if self.selection_ids.len() > 1 {
+            let ids: HashSet<u64> = self.selection_ids.iter().copied().collect();
+            return self.file_entries.iter()
+                .filter(|e| ids.contains(&e.path.id()))
+                .map(|e| e.path.as_path().to_path_buf())
+                .collect();
+        }
+        self.selected_index
+            .and_then(|i| self.file_entries.get(i))
+            .map(|e| vec![e.path.as_path().to_path_buf()])
+            .unwrap_or_default()
+    }
+
+    /// Absolute paths of all marked files, sorted; marks outside the current folder are resolved through the DB
+    fn marked_paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = Vec::new();
+        let mut unresolved: HashSet<u64> = self.marked_files.clone();
+        for entry in &self.file_entries {
+            if unresolved.remove(&entry.path.id()) {
+                paths.push(entry.path.as_path().display().to_string());
+            }
+        }
+        if let Some(ref db) = self.metadata_db {
+            for hash in unresolved {
+                match db.get_file_by_hash(hash) {
+                    Ok(Some(record)) => paths.push(record.path_display),
+                    Ok(None) => tracing::warn!("Marked file {:016x} is not in the catalog", hash),
+                    Err(e) => tracing::warn!("Failed to resolve marked file {:016x}: {}", hash, e),
+                }
+            }
+        }
+        paths.sort();
+        paths
+    }
+
+    /// Write the marked file list to a UTF-8 text file, one path per line
+    fn export_marks(&mut self, target: &std::path::Path) {
+        let paths = self.marked_paths();
+        let mut text = paths.join("\n");
+        text.push('\n');
+        self.status.message = match std::fs::write(target, text) {
+            Ok(()) => format!("Exported {} marked files to {}", paths.len(), target.display()),
+            Err(e) => format!("Export error: {}", e),
+        };
+    }
+
+    /// Copy the marked file list to the clipboard, one path per line
+    fn copy_marks_to_clipboard(&mut self) {
+        let paths = self.marked_paths();
+        #[cfg(feature = "clipboard")]
+        {
+            if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                let _ = clipboard.set_text(paths.join("\n"));
+            }
+        }
+        self.status.message = format!("Copied {} marked paths", paths.len());
+    }
+
+    /// Ask for confirmation before deleting the operation targets
+    fn confirm_delete(&mut self, use_trash: bool) {
+        let paths = self.operation_targets();
+        self.confirm_delete_paths(paths, use_trash);
+    }
+
+    /// Ask for confirmation before deleting `paths`
+    fn confirm_delete_paths(&mut self, paths: Vec<PathBuf>, use_trash: bool) {
+        let dialog = match paths.as_slice() {
+            [] => return,
+            [path] => ConfirmDialog::new_delete(
+                &path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+                use_trash,
+            ),
+            _ => ConfirmDialog::new_delete_many(paths.len(), use_trash),
+        };
+        self.pending_delete = Some((paths, use_trash));
+        self.pending_empty_trash = false;
+        self.pending_purge = None;
+        self.pending_reencode = None;
+        self.confirm_dialog = Some(dialog);
+    }
+
+    /// Delete files one by one, reporting a summary of any failures
+    fn delete_paths(&mut self, paths: Vec<PathBuf>, use_trash: bool) {
+        let errors: Vec<String> = paths.iter()
+            .filter_map(|path| self.file_ops.delete(std::slice::from_ref(path), use_trash).err().map(|e| e.to_string()))
+            .collect();
+
+        let action = if use_trash { "Moved to trash" } else { "Deleted" };
+        self.status.message = file_op_summary(action, &paths, &errors);
+        self.marked_files.clear();
+        if let Some(ref mut review) = self.duplicate_review {
+            let deleted: Vec<PathBuf> = paths.iter().filter(|p| !p.exists()).cloned().collect();
+            review.remove_paths(&deleted);
+        }
+        self.navigate_to(self.current_path.clone());
+    }
+
+    /// Copy or move files into a directory one by one, reporting a summary of any failures
+    fn transfer_paths(&mut self, paths: Vec<PathBuf>, target_dir: &std::path::Path, is_move: bool) {
+        let errors: Vec<String> = paths.iter()
+            .filter_map(|path| {
+                let sources = std::slice::from_ref(path);
+                let result = if is_move {
+                    self.file_ops.move_to(sources, target_dir)
+                } else {
+                    self.file_ops.copy_to(sources, target_dir)
+                };
+                result.err().map(|e| e.to_string())
+            })
+            .collect();
+
+        let action = if is_move { "Moved" } else { "Copied" };
+        self.status.message = format!("{} to {}", file_op_summary(action, &paths, &errors), target_dir.display());
+        self.marked_files.clear();
+        if is_move {
+            self.navigate_to(self.current_path.clone());
+        }
+    }
+
+    /// Delete files marked in the duplicate review
+    fn delete_duplicates(&mut self, paths: Vec<PathBuf>) {
+        let (use_trash, confirm) = state()
+            .map(|s| {
+                let config = s.config.read();
+                (config.filer.use_recycle_bin, config.filer.confirm_delete)
+            })
+            .unwrap_or((true, true));
+        if confirm {
+            self.confirm_delete_paths(paths, use_trash);
+        } else {
+            self.delete_paths(paths, use_trash);
+        }
+    }
+
+    /// Evict least-recently-used thumbnails (textures, memory and disk caches)
+    fn prune_thumbnails(&mut self) {
+        let (memory_limit, disk_limit_mb) = match state() {
+            Some(state) => {
+                let config = state.config.read();
+                (config.filer.thumbnail_memory_limit, config.filer.thumbnail_cache_limit_mb)
+            }
+            None => return,
+        };
+
+        self.thumbnail_textures.set_capacity(memory_limit);
+
+        // Disk pruning scans the cache; keep it off the UI thread
+        if let Some(manager) = self.thumbnail_manager.clone() {
+            rayon::spawn(move || {
+                let stats = manager.prune(memory_limit, disk_limit_mb * 1024 * 1024);
+                tracing::debug!("Thumbnail cache: {:?}", stats);
+            });
+        }
+    }
+
+    /// Step the thumbnail size up (+) or down (-) one preset
+    fn resize_thumbnails(&mut self, step: i32) {
+        let size = if step > 0 {
+            self.thumbnail_size.larger()
+        } else {
+            self.thumbnail_size.smaller()
+        };
+        self.set_thumbnail_size(size);
+    }
+
+    /// Switch the active thumbnail size and re-request thumbnails at that size
+    /// (saved to config by `save_thumbnail_size` when the gesture ends)
+    fn set_thumbnail_size(&mut self, size: ThumbnailSize) {
+        if size == self.thumbnail_size {
+            return;
+        }
+        self.thumbnail_size = size;
+
+        let px = size.to_u32();
+        self.thumbnail_catalog.set_thumbnail_size(px as f32);
+        self.file_browser.thumbnail_size = px as f32;
+
+        // Loaded textures belong to the previous size
+        self.thumbnail_textures.clear();
+        self.catalog_items.clear();
+        self.request_thumbnails_for_current_directory();
+
+        self.status.message = format!("Thumbnail size: {}px", px);
+    }
+
+    /// Persist the active thumbnail size
+    fn save_thumbnail_size(&mut self) {
+        let px = self.thumbnail_size.to_u32();
+        if let Some(state) = state() {
+            let mut config = state.config.write();
+            if config.filer.thumbnail_size == px {
+                return;
+            }
+            config.filer.thumbnail_size = px;
+            if let Err(e) = config.save() {
+                tracing::error!("Failed to save config: {}", e);
+            }
+        }
+    }
+
+    /// Show or hide hidden files, keeping the selected file if it stays visible
+    fn toggle_hidden_files(&mut self) {
+        let Some(state) = state() else {
+            return;
+        };
+        let show_hidden = {
+            let mut config = state.config.write();
+            config.filer.show_hidden_files = !config.filer.show_hidden_files;
+            if let Err(e) = config.save() {
+                tracing::error!("Failed to save config: {}", e);
+            }
+            config.filer.show_hidden_files
+        };
+
+        if self.current_archive.is_none() && !self.in_results_view() {
+            self.refresh_current_directory();
+            self.catalog_items.clear();
+        }
+        self.status.message = if show_hidden { "Showing hidden files" } else { "Hiding hidden files" }.to_string();
+    }
+
+    /// Navigate up to parent directory
+    fn navigate_up(&mut self) {
+        // Inside an archive, climb its folders before leaving it
+        if self.current_archive.is_some() && !self.archive_inner_path.is_empty() {
+            let parent = inner_parent(&self.archive_inner_path).to_string();
+            self.show_archive_dir(parent);
+            return;
+        }
+        // Then return to the archive this one was opened from
+        if self.pop_nested_archive() {
+            return;
+        }
+        if self.current_archive.is_some() {
+            self.close_archives();
+            // Reload the directory containing the archive
+            let path = self.current_path.clone();
+            if let Some(parent) = get_parent(path.as_path()) {
+                self.navigate_to(parent);
+            }
+            return;
+        }
+
+        // Normal directory navigation
+        if !is_root(self.current_path.as_path()) {
+            if let Some(parent) = get_parent(self.current_path.as_path()) {
+                self.navigate_to(parent);
+            }
+        }
+    }
+
+    /// Navigate to a path (PathBuf version)
+    fn navigate_to_path(&mut self, path: &std::path::Path) {
+        let universal_path = UniversalPath::new(path);
+        self.navigate_to(universal_path);
+        // Clear catalog items to force refresh
+        self.catalog_items.clear();
+    }
+
+    /// Where to decode `entry` from (archive members are read into memory)
+    /// The archive read is left to the returned job; run it off the UI thread.
+    fn image_source_job(
+        &self,
+        entry: &FileEntry,
+    ) -> std::io::Result<Box<dyn FnOnce() -> std::io::Result<ImageSource> + Send>> {
+        let Some(ref vfs) = self.current_archive else {
+            let path = entry.path.as_path().to_path_buf();
+            return Ok(Box::new(move || Ok(ImageSource::File(path))));
+        };
+        let Some(inner_path) = self.archive_path_map.get(&entry.path.id()).cloned() else {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Archive path not found"));
+        };
+        if let Some((bridge, archive)) = self.susie_archive() {
+            return Ok(Box::new(move || extract_susie(&bridge, &archive, &inner_path, |_| {}).map(ImageSource::Memory)));
+        }
+        let vfs = vfs.clone();
+        Ok(Box::new(move || vfs.read_file(&inner_path).map(ImageSource::Memory).map_err(std::io::Error::other)))
+    }
+
+    /// Load and display an image
+    /// Shows the cached thumbnail right away, then swaps in the full image once
+    /// it has been decoded in the background (see poll_decodes)
+    fn load_image(&mut self, entry: &FileEntry) {
+        if !is_supported_image(entry.path.as_path()) {
+            return;
+        }
+        let already_decoding = self.pending_decodes.values().any(|p| {
+            matches!(p.target, DecodeTarget::Viewer { .. }) && p.entry.path.as_path() == entry.path.as_path()
+        });
+        if already_decoding {
+            return;
+        }
+
+        tracing::info!("Loading image: {}", entry.path);
+        self.text_preview = None;
+        self.pdf_document = None;
+        self.tiff_document = None;
+        self.animation = None;
+
+        // Plugin extraction can take a while, so it runs with the decode
+        if let Some((bridge, archive)) = self.susie_archive() {
+            let Some(inner_path) = self.archive_path_map.get(&entry.path.id()).cloned() else {
+                return;
+            };
+            self.bump_load_generation();
+            let placeholder = self.show_placeholder(entry);
+            let progress = self.bridge_progress.clone();
+            let egui_ctx = self.egui_ctx.clone();
+            self.start_decode_with(entry, DecodeTarget::Viewer { placeholder }, move || {
+                let data = extract_susie(&bridge, &archive, &inner_path, |fraction| {
+                    progress.store((fraction * 100.0) as u32, Ordering::Relaxed);
+                    egui_ctx.request_repaint();
+                });
+                progress.store(NO_PROGRESS, Ordering::Relaxed);
+                ImageSource::Memory(data?).decode().map(Decoded::from)
+            });
+            return;
+        }
+
+        // Files and built-in archives read quickly; plugin archives were handled above
+        let source = match self.image_source_job(entry).and_then(|read| read()) {
+            Ok(source) => source,
+            Err(e) => {
+                self.pending_decodes.retain(|_, p| !matches!(p.target, DecodeTarget::Viewer { .. }));
+                self.image_load_failed(e);
+                return;
+            }
+        };
+
+        self.bump_load_generation();
+        let placeholder = self.show_placeholder(entry);
+        match TiffDocument::open(entry, source) {
+            Ok(doc) => {
+                self.tiff_document = Some(doc);
+                self.render_tiff_page(placeholder);
+            }
+            Err(source) => self.start_decode(entry, source, DecodeTarget::Viewer { placeholder }),
+        }
+    }
+
+    /// Rename files to the names confirmed in the batch rename window
+    fn apply_batch_rename(&mut self, renames: Vec<(PathBuf, String)>) {
+        let mut renamed = 0;
+        let mut errors = Vec::new();
+        for (from, new_name) in renames {
+            let target = from.parent().map(TargetFs::for_path).unwrap_or_else(TargetFs::native);
+            let to = from.with_file_name(sanitize_filename_for(&new_name, SanitizeMode::FullWidth, target));
+            match self.file_ops.rename(&from, &to) {
+                Ok(_) => {
+                    if let Some(ref db) = self.metadata_db {
+                        let _ = db.rename_file(&UniversalPath::new(&from), &UniversalPath::new(&to));
+                    }
+                    renamed += 1;
+                }
+                Err(e) => errors.push(format!("{}: {}", from.display(), e)),
+            }
+        }
+
+        self.status.message = match errors.first() {
+            None => format!("Renamed {} files", renamed),
+            Some(first) => format!("Renamed {} files, {} failed ({})", renamed, errors.len(), first),
+        };
+        self.navigate_to(self.current_path.clone());
+    }
+
+    /// Open the properties window for `entry`, with resolution and geotag for images
+    fn show_properties(&mut self, entry: &FileEntry) {
+        let dialog = PropertiesDialog::new(&entry.name, entry.path.display(), entry.size, entry.is_dir);
+        // Resolution and geotag are filled in once read (see poll_properties_info)
+        self.properties_info = None;
+        if !entry.is_dir && is_supported_image(entry.path.as_path()) {
+            if let Ok(read) = self.image_source_job(entry) {
+                self.properties_info = Some(BackgroundJob::spawn(0, &self.egui_ctx, move |_| {
+                    read().map(image_properties).unwrap_or_default()
+                }));
+            }
+        }
+        // Archive members have no attributes of their own
+        let attributes = self.file_ops.attributes(entry.path.as_path()).ok();
+        self.properties_path = attributes.map(|_| entry.path.as_path().to_path_buf());
+        self.properties_dialog = Some(dialog.with_attributes(attributes));
+    }
+
+    /// Show the resolution and geotag read for the properties window
+    fn poll_properties_info(&mut self) {
+        let Some(ref job) = self.properties_info else {
+            return;
+        };
+        let Some((dimensions, gps)) = job.take_result() else {
+            return;
+        };
+        self.properties_info = None;
+        if let Some(ref mut dialog) = self.properties_dialog {
+            dialog.dimensions = dimensions;
+            dialog.gps = gps;
+        }
+    }
+
+    /// Apply attributes edited in the properties window and refresh the entry
+    fn apply_attributes(&mut self, attributes: FileAttributes) {
+        let Some(path) = self.properties_path.clone() else {
+            return;
+        };
+        let result = self.file_ops.set_attributes(&path, attributes);
+        let current = self.file_ops.attributes(&path).ok();
+        if let Some(ref mut dialog) = self.properties_dialog {
+            dialog.attributes = current;
+            dialog.error = result.as_ref().err().map(|e| e.to_string());
+        }
+
+        match result {
+            Ok(()) => {
+                if let Some(index) = self.file_entries.iter().position(|e| e.path.as_path() == path) {
+                    if let Ok(entry) = FileEntry::from_path(&path) {
+                        self.file_entries[index] = entry;
+                    }
+                }
+                self.status.message = format!("Updated attributes: {}", path.display());
+            }
+            Err(e) => self.status.message = format!("Failed to change attributes: {}", e),
+        }
+    }
+
+    /// Open the selected file with `app_id`: a registered application's name, else a program
+    fn open_selected_with(&mut self, app_id: &str, args: Option<&str>) {
+        let Some(entry) = self.selected_index.and_then(|i| self.file_entries.get(i)).cloned() else {
+            return;
+        };
+        let registered = state().and_then(|s| {
+            s.config.read().external_apps.iter().find(|app| app.name == app_id).cloned()
+        });
+        let (program, template) = match registered {
+            Some(app) => (app.path.trim().to_string(), Some(app.args)),
+            None => (app_id.to_string(), args.map(str::to_string)),
+        };
+        self.hand_off(&entry, ExternalAction::OpenWith { app_id: app_id.to_string(), program, template });
+    }
+
+    /// Open a terminal in the current folder (the containing folder inside an archive)
+    fn open_terminal(&mut self) {
+        let archive = self.archive_stack.first().map(|outer| &outer.vfs).or(self.current_archive.as_ref());
+        let dir = match archive {
+            Some(vfs) => vfs.archive_path().as_path().parent().map(Path::to_path_buf),
+            None => Some(self.current_path.as_path().to_path_buf()),
+        };
+        let Some(dir) = dir else {
+            return;
+        };
+
+        let command = state().map(|s| s.config.read().filer.terminal_command.clone()).unwrap_or_default();
+        self.status.message = match self.file_ops.open_terminal(&dir, Some(&command)) {
+            Ok(()) => format!("Opened terminal in {}", dir.display()),
+            Err(e) => format!("Failed to open terminal: {}", e),
+        };
+    }
+
+    /// Make the selected image the desktop background
+    fn set_wallpaper(&mut self, style: Option<WallpaperStyle>) {
+        let Some(entry) = self.selected_index.and_then(|i| self.file_entries.get(i)).cloned() else {
+            return;
+        };
+        if !entry.is_image() {
+            self.status.message = format!("Not an image: {}", entry.name);
+            return;
+        }
+        self.hand_off(&entry, ExternalAction::Wallpaper(style));
+    }
+
+    /// Offer the registered applications for the selected file
+    fn open_with_menu(&mut self) {
+        let Some(entry) = self.selected_index.and_then(|i| self.file_entries.get(i)) else {
+            return;
+        };
+        let apps = state()
+            .map(|s| s.config.read().external_apps.iter().map(|app| app.name.clone()).collect())
+            .unwrap_or_default();
+        self.open_with_menu = Some(OpenWithMenu::new(apps, &entry.name));
+    }
+
+    /// Path `entry` can be read from as a file
+    /// Archive members are streamed out to a temp file first, on the calling thread.
+    fn external_path(&self, entry: &FileEntry) -> Result<PathBuf, String> {
+        match self.extraction(entry)? {
+            Some(extraction) => extraction.run(),
+            None => Ok(entry.path.to_path_buf()),
+        }
+    }
+
+    /// What it takes to get `entry` out of the current archive; None outside archives
+    fn extraction(&self, entry: &FileEntry) -> Result<Option<Extraction>, String> {
+        let Some(ref vfs) = self.current_archive else {
+            return Ok(None);
+        };
+        let inner_path = self
+            .archive_path_map
+            .get(&entry.path.id())
+            .ok_or_else(|| "Archive path not found".to_string())?;
+        let source = match self.susie_archive() {
+            Some((bridge, archive)) => MemberSource::Susie(bridge, archive),
+            None => MemberSource::Vfs(vfs.clone()),
+        };
+        Ok(Some(Extraction {
+            source,
+            inner_path: inner_path.clone(),
+            dest: extract_root().join(format!("{:016x}", entry.path.id())).join(&entry.name),
+        }))
+    }
+
+    /// Run `action` on `entry`, extracting archive members in the background first
+    fn hand_off(&mut self, entry: &FileEntry, action: ExternalAction) {
+        let extraction = match self.extraction(entry) {
+            Ok(Some(extraction)) => extraction,
+            Ok(None) => return self.finish_hand_off(&entry.name, action, Ok(entry.path.to_path_buf())),
+            Err(e) => return self.finish_hand_off(&entry.name, action, Err(e)),
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        let egui_ctx = self.egui_ctx.clone();
+        std::thread::spawn(move || {
+            let _ = tx.send(extraction.run());
+            egui_ctx.request_repaint();
+        });
+        self.pending_hand_off = Some(PendingHandOff { name: entry.name.clone(), action, result: rx });
+        self.status.message = format!("Extracting {}…", entry.name);
+    }
+
+    /// Finish an external action once its archive member is extracted
+    fn poll_hand_off(&mut self) {
+        let Some(ref pending) = self.pending_hand_off else {
+            return;
+        };
+        let result = match pending.result.try_recv() {
+            Ok(result) => result,
+            Err(std::sync::mpsc::TryRecvError::Empty) => {
+                self.egui_ctx.request_repaint_after(std::time::Duration::from_millis(100));
+                return;
+            }
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => Err("extraction stopped".to_string()),
+        };
+        if let Some(pending) = self.pending_hand_off.take() {
+            self.finish_hand_off(&pending.name, pending.action, result);
+        }
+    }
+
+    /// Pass `path` (or the error getting it) to the external action
+    fn finish_hand_off(&mut self, name: &str, action: ExternalAction, path: Result<PathBuf, String>) {
+        self.status.message = match action {
+            ExternalAction::Open => match path.and_then(|path| self.file_ops.open_external(&path).map_err(|e| e.to_string())) {
+                Ok(()) => format!("Opened: {}", name),
+                Err(e) => format!("Open external error: {}", e),
+            },
+            ExternalAction::OpenWith { app_id, program, template } => {
+                let opened = path.and_then(|path| {
+                    self.file_ops.open_with(&path, &program, template.as_deref()).map_err(|e| e.to_string())
+                });
+                match opened {
+                    Ok(()) => format!("Opened {} with {}", name, app_id),
+                    Err(e) => format!("Open with error: {}", e),
+                }
+            }
+            ExternalAction::Wallpaper(style) => {
+                let set = path.and_then(|path| {
+                    self.file_ops.set_wallpaper(&path, style.unwrap_or_default()).map_err(|e| e.to_string())
+                });
+                match set {
+                    Ok(()) if style.is_some() && !WallpaperStyle::APPLIED => {
+                        format!("Wallpaper set: {} (fit/fill/stretch is ignored on macOS)", name)
+                    }
+                    Ok(()) => format!("Wallpaper set: {}", name),
+                    Err(e) => format!("Failed to set wallpaper: {}", e),
+                }
+            }
+        };
+    }
+
+    /// Open a PDF in the viewer at its first page
+    fn load_pdf(&mut self, entry: &FileEntry) {
+        self.text_preview = None;
+        self.tiff_document = None;
+        self.animation = None;
+        let opened = self.external_path(entry).and_then(|path| {
+            let pages = app_core::pdf::page_count(&path).map_err(|e| e.to_string())?;
+            Ok((path, pages))
+        });
+        match opened {
+            Ok((path, pages)) => {
+                self.bump_load_generation();
+                self.pdf_document = Some(PdfDocument {
+                    entry: entry.clone(),
+                    path,
+                    page: 0,
+                    pages,
+                    rendered: 0,
+                    resize_since: None,
+                });
+                self.render_pdf_page(false);
+            }
+            Err(e) => {
+                self.pdf_document = None;
+                self.image_load_failed(std::io::Error::other(e));
+            }
+        }
+    }
+
+    /// (current page, page count) of the open PDF or multi-page TIFF
+    fn document_pages(&self) -> Option<(usize, usize)> {
+        if let Some(ref doc) = self.pdf_document {
+            return Some((doc.page, doc.pages));
+        }
+        self.tiff_document.as_ref().map(|doc| (doc.page, doc.pages.page_count()))
+    }
+
+    /// Show page `page` (0-based) of the open document
+    fn show_document_page(&mut self, page: usize) {
+        self.bump_load_generation();
+        if let Some(ref mut doc) = self.pdf_document {
+            doc.page = page;
+            self.render_pdf_page(false);
+        } else if let Some(ref mut doc) = self.tiff_document {
+            doc.page = page;
+            self.render_tiff_page(false);
+        }
+    }
+
+    /// Move `amount` pages through the open document; false when there is none
+    /// or it is already at that end (page keys then move to the next file)
+    fn step_document_pages(&mut self, forward: bool, amount: usize) -> bool {
+        let Some((current, pages)) = self.document_pages() else {
+            return false;
+        };
+        let amount = amount.max(1);
+        let page = if forward {
+            (current + amount).min(pages - 1)
+        } else {
+            current.saturating_sub(amount)
+        };
+        if page == current {
+            return false;
+        }
+        self.show_document_page(page);
+        true
+    }
+
+    /// Show the current TIFF page, from the page cache when possible
+    fn render_tiff_page(&mut self, keep_view: bool) {
+        let Some(ref mut doc) = self.tiff_document else {
+            return;
+        };
+        let (entry, page) = (doc.entry.clone(), doc.page);
+        self.status.message = format!("{}: page {} / {}", entry.name, page + 1, doc.pages.page_count());
+        if let Some(rgba) = doc.cached(page) {
+            self.show_decoded_image(&entry, rgba, keep_view);
+            return;
+        }
+        let pages = doc.pages.clone();
+        self.start_decode_with(&entry, DecodeTarget::Viewer { placeholder: keep_view }, move || {
+            pages.decode_page(page).map(Decoded::from).map_err(std::io::Error::other)
+        });
+    }
+
+    /// Edge length (px) that shows the current PDF page sharply at the current zoom
+    fn pdf_render_size(&self) -> u32 {
+        let size = self.viewer_pixels.max_elem() * self.image_viewer.zoom.max(1.0);
+        (size.ceil() as u32).clamp(PDF_RENDER_MIN, PDF_RENDER_MAX)
+    }
+
+    /// Render the current PDF page in the background
+    /// `keep_view` swaps the texture in place (re-render after zooming).
+    fn render_pdf_page(&mut self, keep_view: bool) {
+        let size = self.pdf_render_size();
+        let Some(ref mut doc) = self.pdf_document else {
+            return;
+        };
+        doc.rendered = size;
+        doc.resize_since = None;
+        let (entry, path, page) = (doc.entry.clone(), doc.path.clone(), doc.page);
+        self.status.message = format!("{}: page {} / {}", entry.name, page + 1, doc.pages);
+        self.start_decode_with(&entry, DecodeTarget::Viewer { placeholder: keep_view }, move || {
+            app_core::pdf::render_page(&path, page, size)
+                .map(Decoded::from)
+                .map_err(std::io::Error::other)
+        });
+    }
+
+    /// Re-render the PDF page once zooming or resizing has settled on a
+    /// size the current render is too small (or needlessly large) for
+    fn check_pdf_resolution(&mut self) {
+        let wanted = self.pdf_render_size() as f32;
+        let Some(ref mut doc) = self.pdf_document else {
+            return;
+        };
+        let ratio = wanted / doc.rendered.max(1) as f32;
+        if (1.0 / PDF_RERENDER_RATIO..=PDF_RERENDER_RATIO).contains(&ratio) {
+            doc.resize_since = None;
+            return;
+        }
+        match doc.resize_since {
+            None => {
+                doc.resize_since = Some(std::time::Instant::now());
+                self.egui_ctx.request_repaint_after(PDF_RERENDER_DELAY);
+            }
+            Some(since) if since.elapsed() >= PDF_RERENDER_DELAY => self.render_pdf_page(true),
+            Some(_) => self.egui_ctx.request_repaint_after(PDF_RERENDER_DELAY),
+        }
+    }
+
+    /// Read the start of a text file in the background, to show it in the viewer
+    fn load_text_preview(&mut self, entry: &FileEntry) {
+        use std::io::Read;
+
+        let read = match self.image_source_job(entry) {
+            Ok(read) => read,
+            Err(e) => {
+                self.status.message = format!("Failed to read {}: {}", entry.name, e);
+                return;
+            }
+        };
+        self.bump_load_generation();
+        let job = BackgroundJob::spawn(0, &self.egui_ctx, move |_| match read()? {
+            ImageSource::File(path) => std::fs::File::open(path).and_then(|file| {
+                let mut data = Vec::new();
+                file.take(TEXT_PREVIEW_LIMIT as u64 + 1).read_to_end(&mut data)?;
+                Ok(data)
+            }),
+            ImageSource::Memory(data) => Ok(data),
+        });
+        let generation = self.load_generation.load(Ordering::Relaxed);
+        self.text_loading = Some(TextLoad { entry: entry.clone(), generation, job });
+    }
+
+    /// Show a text file once it has been read, unless the user has moved on
+    fn poll_text_preview(&mut self) {
+        let Some(ref loading) = self.text_loading else {
+            return;
+        };
+        let Some(data) = loading.job.take_result() else {
+            return;
+        };
+        let Some(TextLoad { entry, generation, .. }) = self.text_loading.take() else {
+            return;
+        };
+        if generation != self.load_generation.load(Ordering::Relaxed) {
+            return;
+        }
+        match data {
+            Ok(data) => {
+                self.pdf_document = None;
+                self.tiff_document = None;
+                self.animation = None;
+                self.text_preview = Some(TextPreview::new(&entry.name, data));
+            }
+            Err(e) => self.status.message = format!("Failed to read {}: {}", entry.name, e),
+        }
+    }
+
+    /// Decode `source` on a background thread; the result arrives in poll_decodes.
+    /// Older requests for the same target are forgotten, so their results are dropped.
+    fn start_decode(&mut self, entry: &FileEntry, source: ImageSource, target: DecodeTarget) {
+        // Only the viewer animates; PNGs are checked for APNG frames there
+        let animate = matches!(target, DecodeTarget::Viewer { .. })
+            && entry.extension == "png"
+            && state().map(|s| s.config.read().viewer.enable_animation).unwrap_or(true);
+        if animate {
+            self.start_decode_with(entry, target, move || source.decode_png());
+        } else {
+            self.start_decode_with(entry, target, move || source.decode().map(Decoded::from));
+        }
+    }
+
+    /// Like start_decode, with the decoding done by `decode`
+    fn start_decode_with<F>(&mut self, entry: &FileEntry, target: DecodeTarget, decode: F)
+    where
+        F: FnOnce() -> std::io::Result<Decoded> + Send + 'static,
+    {
+        self.pending_decodes.retain(|_, p| !target.supersedes(p.target));
+        self.next_decode_id += 1;
+        let id = self.next_decode_id;
+        let generation = self.load_generation.load(Ordering::Relaxed);
+        self.pending_decodes.insert(id, PendingDecode { entry: entry.clone(), target, generation });
+
+        // Skip work for images the user has already moved past
+        let current = target.follows_navigation().then(|| self.load_generation.clone());
+        let is_stale = move || current.as_ref().is_some_and(|g| g.load(Ordering::Relaxed) != generation);
+
+        let tx = self.decode_tx.clone();
+        let egui_ctx = self.egui_ctx.clone();
+        std::thread::spawn(move || {
+            if is_stale() {
+                return;
+            }
+            let decoded = decode();
+            if is_stale() {
+                return;
+            }
+            let _ = tx.send(DecodedImage { id, result: decoded });
+            egui_ctx.request_repaint();
+        });
+    }
+
+    /// Start a new load generation, making in-flight viewer decodes stale
+    fn bump_load_generation(&mut self) {
+        let generation = self.load_generation.fetch_add(1, Ordering::Relaxed) + 1;
+        self.pending_decodes.retain(|_, p| !p.target.follows_navigation() || p.generation == generation);
+    }
+
+    /// Upload pixels of the viewed image with the current adjustments applied
+    fn display_texture(&self, name: &str, rgba: &image::RgbaImage) -> egui::TextureHandle {
+        let adjustments = self.adjust_panel.adjustments;
+        if adjustments.is_identity() {
+            return self.rgba_texture(name, rgba);
+        }
+        let mut adjusted = rgba.clone();
+        adjustments.apply(&mut adjusted);
+        self.rgba_texture(name, &adjusted)
+    }
+
+    /// Re-upload the viewed image after the adjustments changed
+    fn refresh_adjusted_texture(&mut self) {
+        if let Some(ref mut animation) = self.animation {
+            // Frames are re-adjusted as they come up
+            animation.textures.iter_mut().for_each(|texture| *texture = None);
+        }
+        let Some(ref pixels) = self.current_pixels else {
+            return;
+        };
+        let (width, height) = pixels.dimensions();
+
+        // Adjust a downscaled copy while a slider moves; the full image follows on release
+        if !self.adjust_panel.dragging {
+            self.adjust_preview = None;
+        } else if self.adjust_preview.is_none() {
+            let scale = (ADJUST_PREVIEW_SIZE as f32 / width.max(height) as f32).min(1.0);
+            let (preview_width, preview_height) =
+                (((width as f32 * scale) as u32).max(1), ((height as f32 * scale) as u32).max(1));
+            self.adjust_preview = Some(image::imageops::thumbnail(pixels, preview_width, preview_height));
+        }
+        let shown = self.adjust_preview.as_ref().unwrap_or(pixels);
+        let texture = self.display_texture(&self.image_viewer.file_name, shown);
+        self.image_viewer.replace_texture(texture.id(), width, height);
+        self.current_texture = Some(texture);
+    }
+
+    /// Upload decoded pixels as an egui texture
+    fn rgba_texture(&self, name: &str, rgba: &image::RgbaImage) -> egui::TextureHandle {
+        let (width, height) = rgba.dimensions();
+        let color_image = egui::ColorImage::from_rgba_unmultiplied(
+            [width as usize, height as usize],
+            rgba.as_raw(),
+        );
+        self.egui_ctx.load_texture(name, color_image, egui::TextureOptions::LINEAR)
+    }
+
+    /// Stretch the cached thumbnail of `entry` to the image's full size while it decodes
+    fn show_placeholder(&mut self, entry: &FileEntry) -> bool {
+        if self.current_archive.is_some() || self.split_view.enabled {
+            return false;
+        }
+        let Some(ref manager) = self.thumbnail_manager else {
+            return false;
+        };
+        let Ok((width, height)) = get_image_dimensions(entry.path.as_path()) else {
+            return false;
+        };
+        let Some(thumb) = [ThumbnailSize::Large, ThumbnailSize::Medium, ThumbnailSize::Small]
+            .into_iter()
+            .find_map(|size| manager.get_cached_sync(entry.path.as_path(), size))
+        else {
+            return false;
+        };
+
+        let color_image = egui::ColorImage::from_rgba_unmultiplied(
+            [thumb.width as usize, thumb.height as usize],
+            &thumb.data,
+        );
+        let texture = self.egui_ctx.load_texture(
+            format!("{} (preview)", entry.name),
+            color_image,
+            egui::TextureOptions::LINEAR,
+        );
+
+        let previous = self.current_texture.take();
+        self.image_viewer.set_image(texture.id(), width, height);
+        self.current_texture = Some(texture);
+        self.start_transition(previous);
+        self.spread_partner = None;
+        true
+    }
+
+    /// Hand finished background decodes to their targets (stale results are dropped)
+    fn poll_decodes(&mut self) {
+        while let Ok(DecodedImage { id, result }) = self.decode_rx.try_recv() {
+            let Some(PendingDecode { entry, target, generation }) = self.pending_decodes.remove(&id) else {
+                continue;
+            };
+            if target.follows_navigation() && generation != self.load_generation.load(Ordering::Relaxed) {
+                continue;
+            }
+
+            match target {
+                DecodeTarget::Viewer { placeholder } => {
+                    // Navigated elsewhere while decoding
+                    let selected = self.selected_index.and_then(|i| self.file_entries.get(i));
+                    if selected.map(|e| e.path.as_path()) != Some(entry.path.as_path()) {
+                        continue;
+                    }
+                    match result {
+                        Ok(Decoded { image: rgba, frames }) => {
+                            if let Some(ref mut doc) = self.tiff_document {
+                                doc.remember(doc.page, &rgba);
+                            }
+                            self.show_decoded_image(&entry, rgba, placeholder);
+                            if let (Some(frames), Some(texture)) = (frames, self.current_texture.clone()) {
+                                self.animation = Some(ViewerAnimation::new(frames, texture));
+                            }
+                        }
+                        Err(e) => self.image_load_failed(e),
+                    }
+                }
+                DecodeTarget::SplitPane(pane) => match result.map(|decoded| decoded.image) {
+                    Ok(rgba) => {
+                        let texture = self.rgba_texture(&entry.name, &rgba);
+                        self.set_split_pane(pane, &entry, texture, rgba.dimensions());
+                    }
+                    Err(e) => tracing::warn!("Failed to load split pane {}: {}", entry.name, e),
+                },
+                DecodeTarget::SpreadPartner { index, on_left } => match result.map(|decoded| decoded.image) {
+                    Ok(rgba) if self.spread_viewer.is_spread_mode() => {
+                        let (width, height) = rgba.dimensions();
+                        self.spread_partner = Some(SpreadPartner {
+                            texture: self.rgba_texture(&entry.name, &rgba),
+                            size: egui::Vec2::new(width as f32, height as f32),
+                            index,
+                            on_left,
+                        });
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Failed to load spread partner {}: {}", entry.name, e),
+                },
+            }
+        }
+    }
+
+    /// Advance the playing animation when its frame delay has passed
+    /// Paused while the browser is shown or animations are turned off.
+    fn tick_animation(&mut self) {
+        if self.show_browser || !state().map(|s| s.config.read().viewer.enable_animation).unwrap_or(true) {
+            return;
+        }
+        let Some(ref mut animation) = self.animation else {
+            return;
+        };
+        let now = std::time::Instant::now();
+        if now < animation.next_at {
+            return;
+        }
+
+        animation.current = (animation.current + 1) % animation.frames.len();
+        let frame = &animation.frames[animation.current];
+        animation.next_at = now + frame.delay;
+        let (width, height) = frame.image.dimensions();
+        let adjustments = self.adjust_panel.adjustments;
+        let texture = animation.textures[animation.current]
+            .get_or_insert_with(|| {
+                let mut pixels = frame.image.clone();
+                adjustments.apply(&mut pixels);
+                let color_image = egui::ColorImage::from_rgba_unmultiplied(
+                    [width as usize, height as usize],
+                    pixels.as_raw(),
+                );
+                self.egui_ctx.load_texture("animation frame", color_image, egui::TextureOptions::LINEAR)
+            })
+            .clone();
+        self.image_viewer.replace_texture(texture.id(), width, height);
+        self.current_texture = Some(texture);
+    }
+
+    /// Display a fully decoded image (replacing its placeholder without resetting the view)
+    fn show_decoded_image(&mut self, entry: &FileEntry, rgba: image::RgbaImage, placeholder: bool) {
+        let (width, height) = rgba.dimensions();
+        self.adjust_panel.image_changed();
+        self.adjust_preview = None;
+        let texture = self.display_texture(&entry.name, &rgba);
+
+        if placeholder {
+            self.image_viewer.replace_texture(texture.id(), width, height);
+            self.current_texture = Some(texture.clone());
+        } else {
+            // Update viewer (keep the outgoing texture for the crossfade)
+            let previous = self.current_texture.take();
+            self.image_viewer.set_image(texture.id(), width, height);
+            self.current_texture = Some(texture.clone());
+            self.start_transition(previous);
+        }
+        if self.split_view.enabled {
+            // Navigation fills the active split pane
+            let active = self.split_view.active_pane;
+            self.set_split_pane(active, entry, texture, (width, height));
+        }
+        self.current_pixels = Some(rgba);
+
+        // Histogram is recomputed only when the image changes
+        if let Some(ref pixels) = self.current_pixels {
+            self.histogram.set_image(pixels.as_raw());
+        }
+
+        // Update viewer overlay info (Doc 4)
+        self.image_viewer.file_name = entry.name.clone();
+        self.image_viewer.resolution_text = format!("{}×{}", width, height);
+        self.image_viewer.current_index = self.selected_index.map(|i| i + 1).unwrap_or(1);
+        self.image_viewer.total_files = self.file_entries.len();
+
+        // Update status
+        self.status.file_name = entry.name.clone();
+        self.status.dimensions = format!("{}×{}", width, height);
+        self.status.file_size = format_size(entry.size);
+
+        self.update_spread();
+        self.record_view(entry);
+    }
+
+    fn image_load_failed(&mut self, e: std::io::Error) {
+        tracing::error!("Failed to load image: {}", e);
+        self.animation = None;
+        self.status.message = format!("Error: {}", e);
+        self.image_viewer.clear();
+        self.current_texture = None;
+        self.current_pixels = None;
+        self.histogram.clear();
+        self.spread_partner = None;
+    }
+
+    /// Show a loaded texture in split view pane `pane`
+    fn set_split_pane(&mut self, pane: usize, entry: &FileEntry, texture: egui::TextureHandle, size: (u32, u32)) {
+        self.split_view.panes[pane].set_image(entry.path.as_path().to_path_buf(), texture.id(), size);
+        self.split_textures[pane] = Some(texture);
+    }
+
+    /// Decode `entry` into split view pane `pane` (in the background)
+    fn load_split_pane(&mut self, pane: usize, entry: &FileEntry) {
+        match self.image_source_job(entry) {
+            Ok(read) => self.start_decode_with(entry, DecodeTarget::SplitPane(pane), move || {
+                read()?.decode().map(Decoded::from)
+            }),
+            Err(e) => tracing::warn!("Failed to load split pane {}: {}", entry.name, e),
+        }
+    }
+
+    /// Point the selection at the active split pane's file so navigation continues from it
+    fn sync_selection_from_split(&mut self) {
+        let Some(path) = self.split_view.panes[self.split_view.active_pane].path.clone() else {
+            return;
+        };
+        let Some(index) = self.file_entries.iter().position(|e| e.path.as_path() == path.as_path()) else {
+            return;
+        };
+        self.selected_index = Some(index);
+        self.nav_state.selection.select_single(index);
+        self.record_selection();
+        self.file_browser.selected = Some(index);
+        self.status.position = self.view_position_text(index);
     }
 
-    /// Navigate up to parent directory
-    fn navigate_up(&mut self) {
-        // If we're in an archive, exit the archive first
+    /// Add a successfully loaded file to the view history
+    fn record_view(&self, entry: &FileEntry) {
+        // Archive members have no stable path on disk
         if self.current_archive.is_some() {
-            self.current_archive = None;
-            self.archive_inner_path.clear();
-            self.archive_path_map.clear();
-            // Reload the directory containing the archive
-            let path = self.current_path.clone();
-            if let Some(parent) = get_parent(path.as_path()) {
-                self.navigate_to(parent);
-            }
             return;
         }
-
-        // Normal directory navigation
-        if !is_root(self.current_path.as_path()) {
-            if let Some(parent) = get_parent(self.current_path.as_path()) {
-                self.navigate_to(parent);
-            }
+        let Some(ref db) = self.metadata_db else {
+            return;
+        };
+        let result = db.upsert_file(&entry.path, Some(entry.size as i64), entry.modified)
+            .and_then(|file_id| db.record_view(file_id));
+        if let Err(e) = result {
+            tracing::warn!("Failed to record view: {}", e);
         }
     }
 
-    /// Navigate to a path (PathBuf version)
-    fn navigate_to_path(&mut self, path: &std::path::Path) {
-        let universal_path = UniversalPath::new(path);
-        self.navigate_to(universal_path);
-        // Clear catalog items to force refresh
-        self.catalog_items.clear();
+    /// Start a crossfade from the outgoing texture to the current one (viewer mode only).
+    /// The outgoing image stays where it was last drawn, zoom, pan and rotation included.
+    /// Starting a new transition mid-fade snaps to the new target and restarts the timer.
+    fn start_transition(&mut self, from: Option<egui::TextureHandle>) {
+        let (enabled, duration_ms) = state()
+            .map(|s| {
+                let config = s.config.read();
+                (config.viewer.enable_animation, config.viewer.transition_duration_ms)
+            })
+            .unwrap_or((true, 200));
+
+        self.page_transition.clear();
+        self.transition_from = None;
+
+        if let (Some(from), Some((rect, uvs))) = (from, self.viewer_painted) {
+            if enabled && !self.show_browser {
+                self.page_transition.duration = std::time::Duration::from_millis(duration_ms);
+                self.page_transition.start(Some(from.id()), self.current_texture.as_ref().map(|t| t.id()));
+                if self.page_transition.is_active() {
+                    self.transition_from = Some((from, rect, uvs));
+                }
+            }
+        }
     }
 
-    /// Load and display an image
-    fn load_image(&mut self, entry: &FileEntry) {
-        if !is_supported_image(entry.path.as_path()) {
+    /// Pair the current page with its spread partner and load the partner texture
+    fn update_spread(&mut self) {
+        self.spread_partner = None;
+        if !self.spread_viewer.is_spread_mode() {
             return;
         }
 
-        tracing::info!("Loading image: {}", entry.path);
+        if let Some(state) = state() {
+            self.spread_viewer.reading_direction = state.config.read().viewer.reading_direction;
+        }
 
-        // Load image data - handle both filesystem and archive
-        let image_result = if let Some(ref vfs) = self.current_archive {
-            // Loading from archive - get the inner path from mapping
-            if let Some(inner_path) = self.archive_path_map.get(&entry.path.id()) {
-                match vfs.read_file(inner_path) {
-                    Ok(data) => {
-                        image::load_from_memory(&data)
-                            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to read from archive: {}", e);
-                        Err(std::io::Error::new(std::io::ErrorKind::Other, e))
-                    }
-                }
-            } else {
-                tracing::error!("Archive path not found in mapping");
-                Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Archive path not found"))
-            }
+        let image_indices = self.image_indices();
+        let position = self.current_image_position(&image_indices);
+        self.refresh_wide_pages(&image_indices);
+
+        // The current page's real size is known now (covers archive entries too)
+        let size = self.image_viewer.image_size;
+        if SpreadViewer::is_wide(size.x as u32, size.y as u32) {
+            self.spread_viewer.wide_pages.insert(position);
         } else {
-            // Loading from filesystem
-            image::open(entry.path.as_path())
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
-        };
+            self.spread_viewer.wide_pages.remove(&position);
+        }
 
-        match image_result {
-            Ok(img) => {
-                let rgba = img.to_rgba8();
-                let (width, height) = rgba.dimensions();
-                let pixels = rgba.as_flat_samples();
+        let (partner_pos, on_left) = match self.spread_viewer.go_to(position, image_indices.len()) {
+            (Some(left), Some(right)) if left == position => (right, false),
+            (Some(left), Some(_)) => (left, true),
+            _ => return,
+        };
 
-                // Create egui texture
-                let color_image = egui::ColorImage::from_rgba_unmultiplied(
-                    [width as usize, height as usize],
-                    pixels.as_slice(),
-                );
+        let Some(&index) = image_indices.get(partner_pos) else {
+            return;
+        };
+        let Some(entry) = self.file_entries.get(index).cloned() else {
+            return;
+        };
 
-                let texture = self.egui_ctx.load_texture(
-                    entry.name.clone(),
-                    color_image,
-                    egui::TextureOptions::LINEAR,
-                );
+        // Read and decoded in the background; shown by poll_decodes
+        match self.image_source_job(&entry) {
+            Ok(source) => self.start_decode_with(&entry, DecodeTarget::SpreadPartner { index, on_left }, move || {
+                source()?.decode().map(Decoded::from)
+            }),
+            Err(e) => tracing::warn!("Failed to load spread partner {}: {}", entry.name, e),
+        }
+    }
 
-                // Update viewer
-                self.image_viewer.set_image(texture.id(), width, height);
-                self.current_texture = Some(texture);
+    /// Detect landscape pages (displayed alone in spreads) once per folder
+    /// Image headers are probed in the background; see poll_wide_pages.
+    fn refresh_wide_pages(&mut self, image_indices: &[usize]) {
+        let key = (self.current_path.id(), image_indices.len());
+        if self.spread_pages_key == Some(key) {
+            return;
+        }
+        self.spread_pages_key = Some(key);
+        self.spread_viewer.wide_pages.clear();
+        // Dropping the receiver stops an older probe
+        self.wide_page_scan = None;
 
-                // Update viewer overlay info (Doc 4)
-                self.image_viewer.file_name = entry.name.clone();
-                self.image_viewer.resolution_text = format!("{}×{}", width, height);
-                self.image_viewer.current_index = self.selected_index.map(|i| i + 1).unwrap_or(1);
-                self.image_viewer.total_files = self.file_entries.len();
+        // Archive entries are only classified as they are decoded
+        if self.current_archive.is_some() {
+            return;
+        }
 
-                // Update status
-                self.status.file_name = entry.name.clone();
-                self.status.dimensions = format!("{}×{}", width, height);
-                self.status.file_size = format_size(entry.size);
+        let pages: Vec<(usize, PathBuf)> = image_indices.iter().enumerate()
+            .filter_map(|(pos, &idx)| Some((pos, self.file_entries.get(idx)?.path.as_path().to_path_buf())))
+            .collect();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for (pos, path) in pages {
+                let wide = get_image_dimensions(&path).is_ok_and(|(w, h)| SpreadViewer::is_wide(w, h));
+                if tx.send((pos, wide)).is_err() {
+                    return;
+                }
             }
-            Err(e) => {
-                tracing::error!("Failed to load image: {}", e);
-                self.status.message = format!("Error: {}", e);
-                self.image_viewer.clear();
-                self.current_texture = None;
+        });
+        self.wide_page_scan = Some(rx);
+    }
+
+    /// Collect probed wide pages; re-pair the spread once the probe finds any
+    fn poll_wide_pages(&mut self) {
+        let Some(ref scan) = self.wide_page_scan else {
+            return;
+        };
+        let mut found = false;
+        let mut finished = false;
+        loop {
+            match scan.try_recv() {
+                Ok((pos, wide)) => {
+                    if wide && self.spread_viewer.wide_pages.insert(pos) {
+                        found = true;
+                    }
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    finished = true;
+                    break;
+                }
             }
         }
+        if finished {
+            self.wide_page_scan = None;
+        } else {
+            // Keep polling while the probe runs
+            self.egui_ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+        if found && self.spread_viewer.is_spread_mode() && !self.show_browser {
+            self.update_spread();
+        }
     }
 
     /// Handle selection change
     fn on_select(&mut self, index: usize) {
         self.selected_index = Some(index);
+        self.nav_state.selection.select_single(index);
+        self.record_selection();
         self.file_browser.selected = Some(index);
 
         if let Some(entry) = self.file_entries.get(index) {
             if entry.is_image() {
                 self.load_image(&entry.clone());
+            } else if entry.is_pdf() {
+                self.load_pdf(&entry.clone());
+            } else if entry.is_text() {
+                self.load_text_preview(&entry.clone());
             }
 
             // Update position status
-            self.status.position = format!("{} / {}", index + 1, self.file_entries.len());
+            self.status.position = self.view_position_text(index);
+        }
+    }
+
+    /// Title the window after the viewed file and its position, or the
+    /// current folder in the browser
+    fn update_window_title(&mut self) {
+        let viewed = (!self.show_browser)
+            .then(|| self.selected_index.and_then(|i| self.file_entries.get(i).map(|entry| (i, entry))))
+            .flatten();
+        let title = match viewed {
+            Some((i, entry)) => format!("{} ({}/{}) — {}", entry.name, i + 1, self.file_entries.len(), APP_TITLE),
+            None => match self.current_path.file_name() {
+                Some(folder) if self.show_browser => format!("{} — {}", folder, APP_TITLE),
+                _ => APP_TITLE.to_string(),
+            },
+        };
+        if title == self.window_title {
+            return;
         }
+        let Some(ref window) = self.window else {
+            return;
+        };
+        window.set_title(&title);
+        self.window_title = title;
     }
 
     /// Handle open (enter folder or open image)
     fn on_open(&mut self, index: usize) {
         if let Some(entry) = self.file_entries.get(index).cloned() {
-            if entry.is_dir {
+            if entry.is_dir && self.current_archive.is_some() {
+                self.enter_archive_entry_dir(&entry);
+            } else if entry.is_dir {
                 self.navigate_to(entry.path);
+            } else if entry.is_archive() && self.current_archive.is_some() {
+                self.enter_nested_archive(&entry);
             } else if entry.is_archive() {
                 self.enter_archive(entry.path);
             } else if entry.is_image() {
                 self.load_image(&entry);
                 self.enter_viewer_mode(); // Switch to viewer mode (fullscreen)
+            } else if entry.is_pdf() {
+                self.load_pdf(&entry);
+                self.enter_viewer_mode();
+            } else if entry.is_text() {
+                self.load_text_preview(&entry);
+                self.enter_viewer_mode();
             }
         }
     }
@@ -808,7 +4071,9 @@ impl App {
     /// If folder has > threshold files, enter in Browser mode
     fn on_enter_with_threshold(&mut self, index: usize, threshold: i32) {
         if let Some(entry) = self.file_entries.get(index).cloned() {
-            if entry.is_dir {
+            if entry.is_dir && self.current_archive.is_some() {
+                self.enter_archive_entry_dir(&entry);
+            } else if entry.is_dir {
                 // Check file count in the target directory
                 match count_files(entry.path.as_path()) {
                     Ok(file_count) => {
@@ -839,6 +4104,8 @@ impl App {
                 // Regular file - open in Viewer
                 self.load_image(&entry);
                 self.enter_viewer_mode(); // Viewer mode (fullscreen)
+            } else if entry.is_archive() && self.current_archive.is_some() {
+                self.enter_nested_archive(&entry);
             } else if entry.is_archive() {
                 // Archive - open as directory
                 self.enter_archive(entry.path);
@@ -848,58 +4115,59 @@ impl App {
 
     /// Navigate to next image
     fn next_image(&mut self) {
-        let current = self.selected_index.unwrap_or(0);
-        let max = self.file_entries.len().saturating_sub(1);
-
-        // Find next image file
-        for i in (current + 1)..=max {
-            if let Some(entry) = self.file_entries.get(i) {
-                if entry.is_image() {
-                    self.on_select(i);
-                    return;
-                }
-            }
+        let mut current = self.selected_index.unwrap_or(0);
+        // In spread mode, step past the partner page as well
+        if let Some(ref partner) = self.spread_partner {
+            current = current.max(partner.index);
+        }
+
+        // Find next image file the catalog filter lets through
+        if let Some(i) = self.image_indices().into_iter().find(|&i| i > current) {
+            self.on_select(i);
         }
     }
 
     /// Navigate to previous image
     fn prev_image(&mut self) {
-        let current = self.selected_index.unwrap_or(0);
+        let mut current = self.selected_index.unwrap_or(0);
+        if let Some(ref partner) = self.spread_partner {
+            current = current.min(partner.index);
+        }
 
-        // Find previous image file
-        for i in (0..current).rev() {
-            if let Some(entry) = self.file_entries.get(i) {
-                if entry.is_image() {
-                    self.on_select(i);
-                    return;
-                }
+        // Find previous image file the catalog filter lets through
+        if let Some(i) = self.image_indices().into_iter().rev().find(|&i| i < current) {
+            self.on_select(i);
+        }
+    }
+
+    /// Move `amount` pages in the viewer
+    /// In spread mode each page is a spread, since next_image steps past the partner
+    fn step_pages(&mut self, forward: bool, amount: usize) {
+        for _ in 0..amount.max(1) {
+            let before = self.selected_index;
+            if forward {
+                self.next_image();
+            } else {
+                self.prev_image();
+            }
+            // Stop at either end
+            if self.selected_index == before {
+                break;
             }
         }
     }
 
     /// Navigate to first image
     fn first_image(&mut self) {
-        // Find first image file
-        for i in 0..self.file_entries.len() {
-            if let Some(entry) = self.file_entries.get(i) {
-                if entry.is_image() {
-                    self.on_select(i);
-                    return;
-                }
-            }
+        if let Some(&i) = self.image_indices().first() {
+            self.on_select(i);
         }
     }
 
     /// Navigate to last image
     fn last_image(&mut self) {
-        // Find last image file
-        for i in (0..self.file_entries.len()).rev() {
-            if let Some(entry) = self.file_entries.get(i) {
-                if entry.is_image() {
-                    self.on_select(i);
-                    return;
-                }
-            }
+        if let Some(&i) = self.image_indices().last() {
+            self.on_select(i);
         }
     }
 
@@ -947,14 +4215,25 @@ impl App {
         let show_browser = self.show_browser;
         let selected_index = self.selected_index;
         let entries = self.file_entries.clone();
+        let bridge_status = self.bridge_status();
+        let status_message = self.status.message.clone();
 
         // Viewer state for rendering
+        if !self.show_browser {
+            self.refit_viewer();
+            self.clamp_viewer_pan();
+        }
         let viewer_texture = self.image_viewer.texture;
         let viewer_image_size = self.image_viewer.image_size;
         let viewer_zoom = self.image_viewer.zoom;
         let viewer_pan = self.image_viewer.pan;
         let viewer_rotation = self.image_viewer.rotation;
         let viewer_fit_mode = self.image_viewer.fit_mode;
+        let viewer_uvs = app_ui::components::viewer::display_uvs(
+            viewer_rotation,
+            self.image_transform.flip_horizontal,
+            self.image_transform.flip_vertical,
+        );
 
         // Track UI actions from egui closure
         let mut clicked_index: Option<usize> = None;
@@ -963,8 +4242,22 @@ impl App {
         // Track dialog results for post-closure handling
         let mut confirm_result: Option<bool> = None;
         let mut rename_result: Option<String> = None;
+        let mut batch_rename_result: Option<Vec<(PathBuf, String)>> = None;
+        let mut save_as_result: Option<SaveAsRequest> = None;
+        let mut export_result: Option<ExportRequest> = None;
+        let mut recent_folder_result: Option<PathBuf> = None;
+        let mut open_with_result: Option<String> = None;
+        let mut batch_convert_result: Option<DialogResult<ConvertOptions>> = None;
+        let mut adjustments_changed = false;
         let mut new_folder_result: Option<String> = None;
         let mut tag_result: Option<Vec<String>> = None;
+        let mut duplicate_delete: Option<Vec<PathBuf>> = None;
+        let mut trash_action: Option<TrashAction> = None;
+        let mut map_url: Option<String> = None;
+        let mut attributes_change: Option<FileAttributes> = None;
+        let mut comment_result: Option<String> = None;
+        let mut palette_result: Option<String> = None;
+        let mut export_marks_result: Option<ExportMarksAction> = None;
 
         // Track viewer input for post-closure handling
         let mut viewer_zoom_delta: f32 = 0.0;
@@ -972,15 +4265,22 @@ impl App {
         let mut viewer_drag_started = false;
         let mut viewer_drag_ended = false;
         let mut viewer_double_clicked = false;
+        let mut eyedropper_clicked = false;
+        let mut split_active_changed = false;
 
         // Overlay UI state
         let overlay_visible = self.overlay_visible;
-        let image_count: usize = entries.iter().filter(|e| e.is_image()).count();
-        let current_image_pos: usize = if let Some(idx) = selected_index {
-            entries.iter().take(idx + 1).filter(|e| e.is_image()).count()
-        } else {
-            0
+        let image_indices = self.image_indices();
+        let image_count = image_indices.len();
+        let current_image_pos: usize = match selected_index {
+            Some(idx) => image_indices.partition_point(|&i| i <= idx),
+            None => 0,
         };
+        // Multi-page documents show their pages on the seek bar instead of files
+        let document_pages = self.document_pages();
+        let (seek_pos, seek_count) = document_pages
+            .map(|(page, pages)| (page + 1, pages))
+            .unwrap_or((current_image_pos, image_count));
         let mut mouse_moved = false;
         let mut seek_bar_clicked: Option<f32> = None;
         let mut nav_action: Option<&str> = None;
@@ -994,10 +4294,29 @@ impl App {
 
         // Folder tree and thumbnail catalog for browser mode
         let mut folder_tree = std::mem::take(&mut self.folder_tree);
+        let mut tag_panel = std::mem::take(&mut self.tag_panel);
+        let mut tag_action: Option<TagPanelAction> = None;
+        let catalog_title = if self.in_tag_search() {
+            format!("🏷 {}", tag_panel.selected_names().join(" AND "))
+        } else if self.in_timeline() {
+            "🕒 Timeline".to_string()
+        } else if let NavigationContext::Search { query, .. } = &self.nav_state.context {
+            format!("🔍 {}", query)
+        } else if self.in_recent() {
+            "🕘 Recent".to_string()
+        } else if self.in_flattened() {
+            format!("🗂 {} (all subfolders)", current_path_str)
+        } else {
+            format!("📁 {}", current_path_str)
+        };
+        let mut catalog_filter = self.catalog_filter;
+        let mut extension_filter_text = self.extension_filter_text.clone();
+        let timeline_months = if self.in_timeline() { self.timeline_months.clone() } else { Vec::new() };
+        let mut timeline_index = self.timeline_index;
         let mut thumbnail_catalog = std::mem::take(&mut self.thumbnail_catalog);
         let current_path_buf = self.current_path.as_path().to_path_buf();
         let catalog_items = self.catalog_items.clone();
-        thumbnail_catalog.selected = selected_index;
+        (thumbnail_catalog.selected, thumbnail_catalog.multi_selected) = self.catalog_selection();
         let mut folder_action: Option<FolderTreeAction> = None;
         let mut catalog_action: Option<CatalogAction> = None;
 
@@ -1022,6 +4341,13 @@ impl App {
                                 ui.label(&entry.name);
                             }
                         }
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if let Some((text, color)) = &bridge_status {
+                                ui.colored_label(*color, text);
+                                ui.separator();
+                            }
+                            ui.label(&status_message);
+                        });
                     });
                 });
             }
@@ -1042,6 +4368,16 @@ impl App {
                         .min_width(80.0)
                         .max_width(600.0)
                         .show_inside(ui, |ui| {
+                            // Tag sidebar below the folder tree
+                            egui::TopBottomPanel::bottom("tag_panel")
+                                .resizable(true)
+                                .default_height(160.0)
+                                .show_inside(ui, |ui| {
+                                    if let Some(action) = tag_panel.ui(ui) {
+                                        tag_action = Some(action);
+                                    }
+                                });
+
                             ui.heading("Folders");
                             ui.separator();
                             if let Some(action) = folder_tree.ui(ui, &current_path_buf) {
@@ -1053,11 +4389,41 @@ impl App {
                     egui::CentralPanel::default().show_inside(ui, |ui| {
                         // Header with path and image count
                         ui.horizontal(|ui| {
-                            ui.label(format!("📁 {}", current_path_str));
+                            ui.label(&catalog_title);
                             ui.separator();
-                            let img_count = entries.iter().filter(|e| e.is_image()).count();
-                            ui.label(format!("{} images", img_count));
+                            ui.label(format!("{} images", image_count));
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                catalog_filter.ui(ui);
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut extension_filter_text)
+                                        .hint_text("*.png,*.jpg")
+                                        .desired_width(100.0)
+                                );
+                                ui.separator();
+                                ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
+                                    if let Some(mode) = sort_header(ui, toolbar_state.sort_mode) {
+                                        toolbar_action = Some(ToolbarAction::Sort(mode));
+                                    }
+                                });
+                            });
                         });
+
+                        // Month scrubber (timeline only)
+                        if !timeline_months.is_empty() {
+                            ui.horizontal(|ui| {
+                                let last = timeline_months.len() - 1;
+                                if ui.add_enabled(timeline_index > 0, egui::Button::new("◀")).clicked() {
+                                    timeline_index -= 1;
+                                }
+                                ui.add(egui::Slider::new(&mut timeline_index, 0..=last).show_value(false));
+                                if ui.add_enabled(timeline_index < last, egui::Button::new("▶")).clicked() {
+                                    timeline_index += 1;
+                                }
+                                if let Some((month, count)) = timeline_months.get(timeline_index) {
+                                    ui.label(format!("{} ({} files)", month, count));
+                                }
+                            });
+                        }
                         ui.separator();
 
                         // Thumbnail grid
@@ -1068,6 +4434,7 @@ impl App {
                 } else {
                     // Image viewer mode - TRUE fullscreen, no margins
                     let available = ui.available_rect_before_wrap();
+                    self.viewer_pixels = available.size() * ctx.pixels_per_point();
 
                     // Draw black background (edge to edge)
                     ui.painter().rect_filled(
@@ -1105,14 +4472,35 @@ impl App {
                     if response.double_clicked() {
                         viewer_double_clicked = true;
                     }
+                    if response.clicked() && self.eyedropper.active {
+                        eyedropper_clicked = true;
+                    }
 
                     // Check mouse activity for overlay visibility
                     if ui.input(|i| i.pointer.delta().length() > 0.0) {
                         mouse_moved = true;
                     }
 
-                    // Render image if texture exists
-                    if let Some(texture_id) = viewer_texture {
+                    // Text preview replaces the image while a text file is selected
+                    if let Some(ref preview) = self.text_preview {
+                        ui.allocate_new_ui(egui::UiBuilder::new().max_rect(available.shrink(16.0)), |ui| {
+                            ui.horizontal(|ui| {
+                                ui.heading(egui::RichText::new(&preview.name).color(egui::Color32::WHITE));
+                                ui.label(egui::RichText::new(preview.encoding).color(egui::Color32::GRAY));
+                                if preview.truncated {
+                                    ui.label(egui::RichText::new("(first 1 MB)").color(egui::Color32::GRAY));
+                                }
+                            });
+                            ui.separator();
+                            egui::ScrollArea::both().auto_shrink([false; 2]).show(ui, |ui| {
+                                ui.label(
+                                    egui::RichText::new(&preview.text)
+                                        .monospace()
+                                        .color(egui::Color32::LIGHT_GRAY),
+                                );
+                            });
+                        });
+                    } else if let Some(texture_id) = viewer_texture {
                         // Calculate display size based on fit mode (allow scale up for fit)
                         let rotated_size = if viewer_rotation == 90 || viewer_rotation == 270 {
                             egui::Vec2::new(viewer_image_size.y, viewer_image_size.x)
@@ -1120,21 +4508,7 @@ impl App {
                             viewer_image_size
                         };
 
-                        let base_scale = match viewer_fit_mode {
-                            app_ui::components::viewer::FitMode::FitToWindow => {
-                                // Scale to fit window (allow scale up)
-                                let scale_x = available.width() / rotated_size.x;
-                                let scale_y = available.height() / rotated_size.y;
-                                scale_x.min(scale_y) // Removed .min(1.0) to allow scaling up
-                            }
-                            app_ui::components::viewer::FitMode::FitWidth => {
-                                available.width() / rotated_size.x
-                            }
-                            app_ui::components::viewer::FitMode::FitHeight => {
-                                available.height() / rotated_size.y
-                            }
-                            app_ui::components::viewer::FitMode::OriginalSize => 1.0,
-                        };
+                        let base_scale = fit_scale(viewer_fit_mode, available.size(), rotated_size);
 
                         let display_size = rotated_size * base_scale * viewer_zoom;
                         let center = available.center() + viewer_pan;
@@ -1145,7 +4519,68 @@ impl App {
                             egui::Pos2::ZERO,
                             egui::Pos2::new(1.0, 1.0),
                         );
-                        ui.painter().image(texture_id, image_rect, uv, egui::Color32::WHITE);
+                        self.viewer_painted = None;
+                        if self.split_view.enabled {
+                            // Split view: each pane draws its own image, zoom and pan
+                            self.split_view.paint_panes(ui.painter(), available);
+                            split_active_changed = self.split_view.ui(ui, available).active_changed;
+                        } else if let Some(ref partner) = self.spread_partner {
+                            // Two-page spread: both pages fitted side by side
+                            let current = (viewer_image_size.x as u32, viewer_image_size.y as u32);
+                            let other = (partner.size.x as u32, partner.size.y as u32);
+                            let (left_size, right_size, left_tex, right_tex) = if partner.on_left {
+                                (other, current, partner.texture.id(), texture_id)
+                            } else {
+                                (current, other, texture_id, partner.texture.id())
+                            };
+                            let layout = self.spread_viewer.calculate_layout(
+                                Some(left_size),
+                                Some(right_size),
+                                (available.width(), available.height()),
+                            );
+                            let offset = available.min.to_vec2();
+                            if let Some(rect) = layout.left {
+                                ui.painter().image(left_tex, rect.translate(offset), uv, egui::Color32::WHITE);
+                            }
+                            if let Some(rect) = layout.right {
+                                ui.painter().image(right_tex, rect.translate(offset), uv, egui::Color32::WHITE);
+                            }
+                        } else {
+                            app_ui::components::viewer::paint_image(ui.painter(), texture_id, image_rect, viewer_uvs);
+                            self.viewer_painted = Some((image_rect, viewer_uvs));
+                        }
+
+                        // Crossfade: outgoing image drawn on top, where it was shown, with decreasing alpha
+                        if let Some((_, from_rect, from_uvs)) = self.transition_from {
+                            if self.page_transition.is_active() && !self.split_view.enabled {
+                                let painter = ui.painter().with_clip_rect(available);
+                                self.page_transition.render(&painter, from_rect, from_uvs);
+                                ctx.request_repaint();
+                            }
+                        }
+
+                        // Histogram overlay (bottom-right corner)
+                        self.histogram.render(ui.painter(), available);
+
+                        // Loupe and eyedropper next to the cursor (single image only)
+                        if !self.split_view.enabled && self.spread_partner.is_none() {
+                            if let Some(cursor) = ui.input(|i| i.pointer.hover_pos()) {
+                                let painter = ui.painter().with_clip_rect(available);
+                                self.loupe.render(&painter, texture_id, image_rect, rotated_size, viewer_uvs, cursor);
+
+                                if self.eyedropper.active {
+                                    self.eyedropper.sample = self.current_pixels.as_ref()
+                                        .filter(|pixels| egui::Vec2::new(pixels.width() as f32, pixels.height() as f32) == viewer_image_size)
+                                        .and_then(|pixels| {
+                                            let (x, y) = app_ui::components::viewer::screen_to_image(
+                                                cursor, image_rect, pixels.dimensions(), viewer_uvs,
+                                            )?;
+                                            ColorSample::from_image(pixels, x, y)
+                                        });
+                                    self.eyedropper.render(&painter, cursor);
+                                }
+                            }
+                        }
 
                         // === OVERLAY UI (drawn AFTER image, so appears on top) ===
 
@@ -1166,7 +4601,7 @@ impl App {
                         let top_bar_hovered = hover_pos.map(|p| top_bar_rect.expand(20.0).contains(p)).unwrap_or(false);
 
                         // Calculate opacity
-                        let overlay_opacity = if seek_bar_hovered || top_bar_hovered {
+                        let overlay_opacity = if seek_bar_hovered || top_bar_hovered || self.image_viewer.is_editing_zoom() {
                             220u8
                         } else if overlay_visible {
                             140u8
@@ -1210,15 +4645,14 @@ impl App {
                                 egui::Color32::WHITE,
                             );
 
-                            // Right: Zoom info
-                            let zoom_text = format!("{:.0}%", viewer_zoom * base_scale * 100.0);
-                            ui.painter().text(
-                                top_bar_rect.right_center() - egui::Vec2::new(10.0, 0.0),
-                                egui::Align2::RIGHT_CENTER,
-                                &zoom_text,
-                                egui::FontId::proportional(14.0),
-                                egui::Color32::WHITE,
+                            // Right: Zoom info (click to type an exact percentage)
+                            let zoom_rect = egui::Rect::from_min_max(
+                                egui::Pos2::new(top_bar_rect.right() - 80.0, top_bar_rect.top() + 6.0),
+                                egui::Pos2::new(top_bar_rect.right() - 10.0, top_bar_rect.bottom() - 6.0),
                             );
+                            if self.image_viewer.zoom_percent_ui(ui, zoom_rect, base_scale) {
+                                ctx.request_repaint();
+                            }
 
                             // === Seek Bar (on top of image, at bottom) ===
                             ui.painter().rect_filled(seek_bar_rect, 0.0, overlay_bg);
@@ -1233,8 +4667,8 @@ impl App {
                             ui.painter().rect_filled(track_rect, 2.0, track_color);
 
                             // Draw position indicator
-                            if image_count > 0 {
-                                let progress = current_image_pos as f32 / image_count as f32;
+                            if seek_count > 0 {
+                                let progress = seek_pos as f32 / seek_count as f32;
                                 let indicator_x = track_rect.left() + track_rect.width() * progress;
 
                                 // Filled portion
@@ -1252,7 +4686,11 @@ impl App {
                             }
 
                             // Item counter at right side of seek bar
-                            let counter_text = format!("{} / {}", current_image_pos, image_count);
+                            let counter_text = if document_pages.is_some() {
+                                format!("p. {} / {}", seek_pos, seek_count)
+                            } else {
+                                format!("{} / {}", seek_pos, seek_count)
+                            };
                             let text_color = egui::Color32::from_rgba_unmultiplied(255, 255, 255, overlay_opacity);
                             ui.painter().text(
                                 egui::Pos2::new(seek_bar_rect.right() - 10.0, seek_bar_rect.center().y),
@@ -1363,6 +4801,141 @@ impl App {
                 }
             }
 
+            // Comment dialog
+            if let Some(ref mut dialog) = self.comment_dialog {
+                match dialog.ui(ctx) {
+                    DialogResult::Ok(comment) => {
+                        comment_result = Some(comment);
+                        self.comment_dialog = None;
+                    }
+                    DialogResult::Cancel => {
+                        self.comment_dialog = None;
+                        self.pending_comment_entry = None;
+                    }
+                    _ => {}
+                }
+            }
+
+            // Marked list export dialog
+            if let Some(ref mut dialog) = self.export_marks_dialog {
+                match dialog.ui(ctx) {
+                    DialogResult::Ok(action) => {
+                        export_marks_result = Some(action);
+                        self.export_marks_dialog = None;
+                    }
+                    DialogResult::Cancel => {
+                        self.export_marks_dialog = None;
+                    }
+                    _ => {}
+                }
+            }
+
+            // Batch rename window
+            if let Some(ref mut dialog) = self.batch_rename_dialog {
+                match dialog.ui(ctx) {
+                    DialogResult::Ok(renames) => {
+                        batch_rename_result = Some(renames);
+                        self.batch_rename_dialog = None;
+                    }
+                    DialogResult::Cancel => self.batch_rename_dialog = None,
+                    _ => {}
+                }
+            }
+
+            // Save-as window
+            if let Some(ref mut dialog) = self.save_as_dialog {
+                match dialog.ui(ctx) {
+                    DialogResult::Ok(request) => {
+                        save_as_result = Some(request);
+                        self.save_as_dialog = None;
+                    }
+                    DialogResult::Cancel => self.save_as_dialog = None,
+                    _ => {}
+                }
+            }
+
+            // Export window
+            if let Some(ref mut dialog) = self.export_dialog {
+                match dialog.ui(ctx) {
+                    DialogResult::Ok(request) => {
+                        export_result = Some(request);
+                        self.export_dialog = None;
+                    }
+                    DialogResult::Cancel => self.export_dialog = None,
+                    _ => {}
+                }
+            }
+
+            // Brightness/contrast/gamma sliders (non-modal)
+            if self.adjust_panel.ui(ctx) {
+                adjustments_changed = true;
+            }
+
+            // Recent folders menu
+            if let Some(ref mut menu) = self.recent_folders_menu {
+                match menu.ui(ctx) {
+                    DialogResult::Ok(folder) => {
+                        recent_folder_result = Some(folder);
+                        self.recent_folders_menu = None;
+                    }
+                    DialogResult::Cancel => self.recent_folders_menu = None,
+                    _ => {}
+                }
+            }
+
+            // Open with menu
+            if let Some(ref mut menu) = self.open_with_menu {
+                match menu.ui(ctx) {
+                    DialogResult::Ok(name) => {
+                        open_with_result = Some(name);
+                        self.open_with_menu = None;
+                    }
+                    DialogResult::Cancel => self.open_with_menu = None,
+                    _ => {}
+                }
+            }
+
+            // Batch convert window (stays open for progress and the summary)
+            if let Some(ref mut dialog) = self.batch_convert_dialog {
+                match dialog.ui(ctx) {
+                    DialogResult::None => {}
+                    result => batch_convert_result = Some(result),
+                }
+            }
+
+            // Properties window
+            if let Some(ref mut dialog) = self.properties_dialog {
+                match dialog.ui(ctx) {
+                    DialogResult::Ok(PropertiesAction::OpenMap(url)) => map_url = Some(url),
+                    DialogResult::Ok(PropertiesAction::SetAttributes(attributes)) => attributes_change = Some(attributes),
+                    DialogResult::Cancel => self.properties_dialog = None,
+                    _ => {}
+                }
+            }
+
+            // Command palette
+            if let DialogResult::Ok(binding) = self.command_palette.ui(ctx) {
+                palette_result = Some(binding);
+            }
+
+            // Duplicate review window
+            if let Some(ref mut review) = self.duplicate_review {
+                match review.ui(ctx) {
+                    DialogResult::Ok(paths) => duplicate_delete = Some(paths),
+                    DialogResult::Cancel => self.duplicate_review = None,
+                    _ => {}
+                }
+            }
+
+            // Recycle bin window
+            if let Some(ref mut view) = self.trash_view {
+                match view.ui(ctx) {
+                    DialogResult::Ok(action) => trash_action = Some(action),
+                    DialogResult::Cancel => self.trash_view = None,
+                    _ => {}
+                }
+            }
+
             // Tag edit dialog
             if let Some(ref mut dialog) = self.tag_dialog {
                 match dialog.ui(ctx) {
@@ -1381,8 +4954,15 @@ impl App {
         // Restore toolbar state
         self.toolbar_state = toolbar_state;
 
+        // Release the outgoing texture once the crossfade has finished
+        if self.transition_from.is_some() && !self.page_transition.is_active() {
+            self.transition_from = None;
+            self.page_transition.clear();
+        }
+
         // Restore folder tree and thumbnail catalog
         self.folder_tree = folder_tree;
+        self.tag_panel = tag_panel;
         self.thumbnail_catalog = thumbnail_catalog;
 
         // Handle toolbar actions
@@ -1392,24 +4972,36 @@ impl App {
 
         // Handle folder tree actions
         if let Some(action) = folder_action {
-            match action {
-                FolderTreeAction::SelectFolder(path) => {
-                    self.navigate_to_path(&path);
-                }
-                FolderTreeAction::ToggleExpand(_) => {
-                    // Tree handles this internally
-                }
-                FolderTreeAction::GoToParent => {
-                    self.navigate_up();
-                }
-            }
+            self.handle_folder_action(action);
+        }
+
+        // Handle catalog filter changes
+        self.set_catalog_filter(catalog_filter);
+        self.set_extension_filter(extension_filter_text);
+
+        // Handle tag panel actions
+        if tag_action.is_some() {
+            self.search_by_tags();
+        }
+
+        // Handle timeline scrubbing
+        if self.in_timeline() && timeline_index != self.timeline_index {
+            self.show_timeline_month(timeline_index);
         }
 
         // Handle thumbnail catalog actions
         if let Some(action) = catalog_action {
             match action {
-                CatalogAction::Select(idx) => self.on_select(idx),
-                CatalogAction::Open(idx) => self.on_open(idx),
+                CatalogAction::Select(pos) => {
+                    if let Some(idx) = self.catalog_entry(pos) {
+                        self.on_select(idx);
+                    }
+                }
+                CatalogAction::Open(pos) => {
+                    if let Some(idx) = self.catalog_entry(pos) {
+                        self.on_open(idx);
+                    }
+                }
                 CatalogAction::GoToParent => self.navigate_up(),
                 CatalogAction::Navigate(dir) => {
                     match dir {
@@ -1418,6 +5010,8 @@ impl App {
                         _ => {} // PageUp, PageDown, Home, End handled by catalog
                     }
                 }
+                CatalogAction::ResizeThumbnails(step) => self.resize_thumbnails(step),
+                CatalogAction::ResizeFinished => self.save_thumbnail_size(),
             }
         }
 
@@ -1429,14 +5023,60 @@ impl App {
         }
 
         // Handle dialog results
+        if let Some(comment) = comment_result {
+            if let Some(entry) = self.pending_comment_entry.take() {
+                self.save_comment(&entry, &comment);
+            }
+        }
+
+        if let Some(paths) = duplicate_delete {
+            self.delete_duplicates(paths);
+        }
+
+        if let Some(action) = trash_action {
+            self.handle_trash_action(action);
+        }
+
+        if let Some(attributes) = attributes_change {
+            self.apply_attributes(attributes);
+        }
+
+        if let Some(url) = map_url {
+            if let Err(e) = open::that(&url) {
+                self.status.message = format!("Failed to open map: {}", e);
+            }
+        }
+
+        if let Some(action) = export_marks_result {
+            match action {
+                ExportMarksAction::Save(path) => self.export_marks(&PathBuf::from(path)),
+                ExportMarksAction::CopyToClipboard => self.copy_marks_to_clipboard(),
+            }
+        }
+
+        if let Some(binding) = palette_result {
+            self.run_palette_command(&binding);
+        }
+
         if let Some(confirmed) = confirm_result {
             if confirmed {
-                if let Some(path) = self.pending_delete_path.take() {
-                    let _ = self.file_ops.delete(&[path], true);
-                    self.navigate_to(self.current_path.clone());
+                if let Some((paths, use_trash)) = self.pending_delete.take() {
+                    self.delete_paths(paths, use_trash);
+                }
+                if std::mem::take(&mut self.pending_empty_trash) {
+                    self.empty_trash();
+                }
+                if let Some(items) = self.pending_purge.take() {
+                    self.purge_trash(items);
+                }
+                if let Some((paths, quarter_turns)) = self.pending_reencode.take() {
+                    self.reencode_rotated(paths, quarter_turns);
                 }
             } else {
-                self.pending_delete_path = None;
+                self.pending_delete = None;
+                self.pending_empty_trash = false;
+                self.pending_purge = None;
+                self.pending_reencode = None;
             }
         }
 
@@ -1444,9 +5084,15 @@ impl App {
             if let Some(idx) = self.selected_index {
                 if let Some(entry) = self.file_entries.get(idx) {
                     let from = entry.path.as_path();
-                    let to = from.with_file_name(new_name);
+                    // Sanitize for the file system the file lives on (e.g. FAT on Linux)
+                    let target = from.parent().map(TargetFs::for_path).unwrap_or_else(TargetFs::native);
+                    let to = from.with_file_name(sanitize_filename_for(&new_name, SanitizeMode::FullWidth, target));
                     match self.file_ops.rename(from, &to) {
                         Ok(_) => {
+                            // Keep ratings/comments/tags with the file
+                            if let Some(ref db) = self.metadata_db {
+                                let _ = db.rename_file(&entry.path, &UniversalPath::new(&to));
+                            }
                             self.status.message = format!("Renamed to: {}", to.display());
                             self.navigate_to(self.current_path.clone());
                         }
@@ -1458,6 +5104,42 @@ impl App {
             }
         }
 
+        if let Some(renames) = batch_rename_result {
+            self.apply_batch_rename(renames);
+        }
+
+        if let Some(request) = save_as_result {
+            self.save_image_as(request);
+        }
+
+        if let Some(request) = export_result {
+            self.export_image(request);
+        }
+
+        if let Some(folder) = recent_folder_result {
+            self.navigate_to(UniversalPath::new(folder));
+        }
+
+        if let Some(name) = open_with_result {
+            self.open_selected_with(&name, None);
+        }
+
+        if adjustments_changed {
+            self.refresh_adjusted_texture();
+        }
+
+        match batch_convert_result {
+            Some(DialogResult::Ok(options)) => self.start_batch_convert(options),
+            Some(DialogResult::Cancel) => {
+                // Closing the window stops a running batch after the current file
+                if let Some(ref batch) = self.batch_convert {
+                    batch.job.cancel();
+                }
+                self.batch_convert_dialog = None;
+            }
+            _ => {}
+        }
+
         if let Some(tags) = tag_result {
             if let Some(idx) = self.selected_index {
                 if let Some(_entry) = self.file_entries.get(idx) {
@@ -1482,92 +5164,35 @@ impl App {
             }
         }
 
+        if split_active_changed {
+            self.sync_selection_from_split();
+        }
+
         // Handle viewer input (Doc 4 compliant)
         if !self.show_browser {
-            // Zoom with scroll wheel
-            if viewer_zoom_delta != 0.0 {
-                let zoom_factor = if viewer_zoom_delta > 0.0 { 1.1 } else { 0.9 };
-                self.image_viewer.zoom = (self.image_viewer.zoom * zoom_factor).clamp(0.1, 10.0);
-            }
-
-            // Pan with drag (1:1 tracking)
-            if viewer_pan_delta != egui::Vec2::ZERO {
-                self.image_viewer.pan += viewer_pan_delta;
-            }
-
-            // Edge snap when drag ends
-            if viewer_drag_ended && !self.show_browser {
-                let snap_threshold = 30.0;
-                let img_size = self.image_viewer.image_size;
-                let zoom = self.image_viewer.zoom;
-
-                // Calculate rotated size
-                let rotated_size = if self.image_viewer.rotation == 90 || self.image_viewer.rotation == 270 {
-                    egui::Vec2::new(img_size.y, img_size.x)
-                } else {
-                    img_size
-                };
-
-                // Get screen size
-                if let Some(window) = &self.window {
-                    let screen = window.inner_size();
-                    let screen_size = egui::Vec2::new(screen.width as f32, screen.height as f32);
-
-                    // Calculate display size after zoom
-                    let scale = match self.image_viewer.fit_mode {
-                        app_ui::components::viewer::FitMode::FitToWindow => {
-                            let scale_x = screen_size.x / rotated_size.x;
-                            let scale_y = screen_size.y / rotated_size.y;
-                            scale_x.min(scale_y)
-                        }
-                        app_ui::components::viewer::FitMode::FitWidth => screen_size.x / rotated_size.x,
-                        app_ui::components::viewer::FitMode::FitHeight => screen_size.y / rotated_size.y,
-                        app_ui::components::viewer::FitMode::OriginalSize => 1.0,
-                    };
-                    let display_size = rotated_size * scale * zoom;
-
-                    // Image bounds in screen coordinates
-                    // pan is offset of image center from screen center
-                    // Image center = screen_center + pan
-                    let half_display = display_size * 0.5;
-                    let half_screen = screen_size * 0.5;
-                    let pan = self.image_viewer.pan;
-
-                    // Image edges in screen coordinates:
-                    // left_edge = half_screen.x + pan.x - half_display.x
-                    // right_edge = half_screen.x + pan.x + half_display.x
-                    let left_edge = half_screen.x + pan.x - half_display.x;
-                    let right_edge = half_screen.x + pan.x + half_display.x;
-                    let top_edge = half_screen.y + pan.y - half_display.y;
-                    let bottom_edge = half_screen.y + pan.y + half_display.y;
+            // Split view panes handle their own zoom and pan
+            if !self.split_view.enabled {
+                // Zoom with scroll wheel
+                if viewer_zoom_delta != 0.0 {
+                    let zoom_factor = if viewer_zoom_delta > 0.0 { 1.1 } else { 0.9 };
+                    self.image_viewer.zoom = (self.image_viewer.zoom * zoom_factor).clamp(0.1, 10.0);
+                }
 
-                    // Horizontal snap
-                    // Snap to left (image left edge near screen left = 0)
-                    if left_edge.abs() < snap_threshold {
-                        // Set pan so left_edge = 0: pan.x = half_display.x - half_screen.x
-                        self.image_viewer.pan.x = half_display.x - half_screen.x;
-                    }
-                    // Snap to right (image right edge near screen right = screen_size.x)
-                    else if (right_edge - screen_size.x).abs() < snap_threshold {
-                        // Set pan so right_edge = screen_size.x: pan.x = half_screen.x - half_display.x
-                        self.image_viewer.pan.x = half_screen.x - half_display.x;
-                    }
+                // Pan with drag (1:1 tracking)
+                if viewer_pan_delta != egui::Vec2::ZERO {
+                    self.image_viewer.pan += viewer_pan_delta;
+                }
 
-                    // Vertical snap
-                    // Snap to top (image top edge near screen top = 0)
-                    if top_edge.abs() < snap_threshold {
-                        // Set pan so top_edge = 0: pan.y = half_display.y - half_screen.y
-                        self.image_viewer.pan.y = half_display.y - half_screen.y;
-                    }
-                    // Snap to bottom (image bottom edge near screen bottom = screen_size.y)
-                    else if (bottom_edge - screen_size.y).abs() < snap_threshold {
-                        // Set pan so bottom_edge = screen_size.y: pan.y = half_screen.y - half_display.y
-                        self.image_viewer.pan.y = half_screen.y - half_display.y;
-                    }
+                // Edge snap when drag ends or the zoom changes
+                if viewer_drag_ended || viewer_zoom_delta != 0.0 {
+                    self.snap_viewer_pan();
                 }
             }
 
             // Double-click to CLOSE viewer (return to browser)
+            if eyedropper_clicked {
+                self.copy_eyedropper_color();
+            }
             if viewer_double_clicked {
                 self.exit_viewer_mode();
             }
@@ -1584,13 +5209,14 @@ impl App {
             }
 
             // Handle seek bar navigation
-            if let Some(position) = seek_bar_clicked {
+            if let (Some(position), Some((current, pages))) = (seek_bar_clicked, self.document_pages()) {
+                let page = ((position * pages as f32) as usize).min(pages - 1);
+                if page != current {
+                    self.show_document_page(page);
+                }
+            } else if let Some(position) = seek_bar_clicked {
                 // Jump to image at given position (0.0 - 1.0)
-                let image_indices: Vec<usize> = self.file_entries.iter()
-                    .enumerate()
-                    .filter(|(_, e)| e.is_image())
-                    .map(|(i, _)| i)
-                    .collect();
+                let image_indices = self.image_indices();
                 if !image_indices.is_empty() {
                     let target_idx = ((position * image_indices.len() as f32) as usize)
                         .min(image_indices.len() - 1);
@@ -1721,17 +5347,7 @@ impl App {
                         // Folder tree - only shows folders
                         let current = self.current_path.as_path().to_path_buf();
                         if let Some(action) = self.folder_tree.ui(ui, &current) {
-                            match action {
-                                FolderTreeAction::SelectFolder(path) => {
-                                    self.navigate_to_path(&path);
-                                }
-                                FolderTreeAction::ToggleExpand(_) => {
-                                    // Tree handles this internally
-                                }
-                                FolderTreeAction::GoToParent => {
-                                    self.navigate_up();
-                                }
-                            }
+                            self.handle_folder_action(action);
                         }
                     });
 
@@ -1754,21 +5370,31 @@ impl App {
                     self.update_catalog_items();
 
                     // Sync selection
-                    self.thumbnail_catalog.selected = self.selected_index;
+                    (self.thumbnail_catalog.selected, self.thumbnail_catalog.multi_selected) = self.catalog_selection();
 
                     // Thumbnail catalog grid
                     let catalog_items = self.catalog_items.clone();
                     if let Some(action) = self.thumbnail_catalog.ui(ui, &catalog_items) {
                         match action {
-                            CatalogAction::Select(idx) => self.on_select(idx),
-                            CatalogAction::Open(idx) => self.on_open(idx),
+                            CatalogAction::Select(pos) => {
+                                if let Some(idx) = self.catalog_entry(pos) {
+                                    self.on_select(idx);
+                                }
+                            }
+                            CatalogAction::Open(pos) => {
+                                if let Some(idx) = self.catalog_entry(pos) {
+                                    self.on_open(idx);
+                                }
+                            }
                             CatalogAction::GoToParent => self.navigate_up(),
                             CatalogAction::Navigate(_) => {
                                 // Navigation already handled internally
-                                if let Some(idx) = self.thumbnail_catalog.selected {
+                                if let Some(idx) = self.thumbnail_catalog.selected.and_then(|pos| self.catalog_entry(pos)) {
                                     self.on_select(idx);
                                 }
                             }
+                            CatalogAction::ResizeThumbnails(step) => self.resize_thumbnails(step),
+                            CatalogAction::ResizeFinished => self.save_thumbnail_size(),
                         }
                     }
                 });
@@ -1783,43 +5409,96 @@ impl App {
     }
 
     /// Update catalog items from current file entries
+    /// Path hashes of the given entries that have a comment
+    fn commented_files(&self, entries: &[FileEntry]) -> HashSet<u64> {
+        let Some(ref db) = self.metadata_db else {
+            return HashSet::new();
+        };
+        entry_folders(entries).into_iter()
+            .filter_map(|folder| db.folder_comments(folder).ok())
+            .flatten()
+            .collect()
+    }
+
+    /// Ratings of the given entries keyed by path hash; ratings are stored per file in the DB, so query once per folder
+    fn file_ratings(&self, entries: &[FileEntry]) -> HashMap<u64, i32> {
+        let mut ratings = HashMap::new();
+        let Some(ref db) = self.metadata_db else {
+            return ratings;
+        };
+        for folder in entry_folders(entries) {
+            match db.folder_ratings(folder) {
+                Ok(folder_ratings) => ratings.extend(folder_ratings),
+                Err(e) => tracing::warn!("Failed to load ratings: {}", e),
+            }
+        }
+        ratings
+    }
+
+    /// Label colors of the given entries keyed by path hash, one query per folder
+    fn file_labels(&self, entries: &[FileEntry]) -> HashMap<u64, u32> {
+        let mut labels = HashMap::new();
+        let Some(ref db) = self.metadata_db else {
+            return labels;
+        };
+        for folder in entry_folders(entries) {
+            match db.folder_labels(folder) {
+                Ok(folder_labels) => labels.extend(folder_labels),
+                Err(e) => tracing::warn!("Failed to load labels: {}", e),
+            }
+        }
+        labels
+    }
+
     fn update_catalog_items(&mut self) {
         // Rebuild catalog if entries changed
-        if self.catalog_items.len() != self.file_entries.len() {
+        if self.catalog_items.len() != self.catalog_view.len() {
             // Clone entries to avoid borrow conflict
-            let entries: Vec<_> = self.file_entries.iter().cloned().collect();
+            let entries: Vec<_> = self.catalog_view.iter()
+                .filter_map(|&i| self.file_entries.get(i).cloned())
+                .collect();
+            let commented = self.commented_files(&entries);
+            let ratings = self.file_ratings(&entries);
+            let labels = self.file_labels(&entries);
             self.catalog_items = entries.iter().map(|e| {
                 let mut item = ThumbnailItem::new(
                     e.path.as_path().to_path_buf(),
                     e.is_dir,
                     e.is_image(),
                 );
-
-                // Load thumbnail texture if available
-                if e.is_image() {
-                    if let Some(texture) = self.load_thumbnail_texture(e) {
-                        item.set_texture(texture);
-                    }
-                }
-
+                item.has_comment = commented.contains(&e.path.id());
+                item.rating = ratings.get(&e.path.id()).copied().unwrap_or(0);
+                item.label = labels.get(&e.path.id()).copied().and_then(label_color32);
+                item.link_target = e.link_target.clone();
                 item
             }).collect();
-        } else {
-            // Update thumbnails for existing items that don't have one yet
-            // Collect indices and entries to update first to avoid borrow conflict
-            let updates: Vec<_> = self.file_entries.iter().enumerate()
-                .filter(|(idx, entry)| {
-                    entry.is_image() &&
-                    self.catalog_items.get(*idx).map(|i| i.texture.is_none()).unwrap_or(false)
-                })
-                .map(|(idx, entry)| (idx, entry.clone()))
-                .collect();
+        }
 
-            for (idx, entry) in updates {
-                if let Some(texture) = self.load_thumbnail_texture(&entry) {
-                    if let Some(item) = self.catalog_items.get_mut(idx) {
-                        item.set_texture(texture);
-                    }
+        // Only items near the viewport hold textures; the rest are dropped (the LRU
+        // texture cache keeps recent ones) and reloaded when scrolled back into view
+        let visible = self.thumbnail_catalog.visible_range(self.catalog_items.len());
+        if let Some(ref manager) = self.thumbnail_manager {
+            manager.set_visible_range(visible.clone());
+        }
+        let keep = visible.start.saturating_sub(visible.len())..visible.end + visible.len();
+        for (idx, item) in self.catalog_items.iter_mut().enumerate() {
+            if !keep.contains(&idx) {
+                item.texture = None;
+            }
+        }
+
+        // Load thumbnails for visible items that don't have one yet
+        // Collect indices and entries to update first to avoid borrow conflict
+        let updates: Vec<_> = visible
+            .filter(|&idx| self.catalog_items[idx].texture.is_none())
+            .filter_map(|idx| self.file_entries.get(*self.catalog_view.get(idx)?).map(|entry| (idx, entry.clone())))
+            .filter(|(_, entry)| entry.is_image() || entry.is_video() || entry.is_pdf())
+            .collect();
+
+        for (idx, entry) in updates {
+            if let Some(texture) = self.load_thumbnail_texture(&entry) {
+                if let Some(item) = self.catalog_items.get_mut(idx) {
+                    item.set_texture(texture);
                 }
             }
         }
@@ -1850,8 +5529,9 @@ impl App {
             }
             ViewerAction::SeekTo(position) => {
                 // Seek to position in file list (0.0-1.0)
-                if !self.file_entries.is_empty() {
-                    let target_idx = ((self.file_entries.len() as f32 - 1.0) * position) as usize;
+                if !self.catalog_view.is_empty() {
+                    let target_pos = ((self.catalog_view.len() as f32 - 1.0) * position) as usize;
+                    let target_idx = self.catalog_view[target_pos.min(self.catalog_view.len() - 1)];
                     self.on_select(target_idx);
                     // If it's an image, load it
                     if let Some(entry) = self.file_entries.get(target_idx).cloned() {
@@ -1864,6 +5544,79 @@ impl App {
         }
     }
 
+    fn handle_folder_action(&mut self, action: FolderTreeAction) {
+        match action {
+            FolderTreeAction::SelectFolder(path) => {
+                self.navigate_to_path(&path);
+            }
+            FolderTreeAction::ToggleExpand(_) => {
+                // Tree handles this internally
+            }
+            FolderTreeAction::GoToParent => {
+                self.navigate_up();
+            }
+            FolderTreeAction::OpenTrash => {
+                self.open_trash();
+            }
+            FolderTreeAction::RemoveBookmark(id) => {
+                if let Some(ref db) = self.metadata_db {
+                    if let Err(e) = db.remove_bookmark(id) {
+                        self.status.message = format!("Failed to remove bookmark: {}", e);
+                    }
+                }
+                self.refresh_bookmarks();
+            }
+            FolderTreeAction::MoveBookmark(id, delta) => {
+                let mut ids: Vec<i64> = self.folder_tree.bookmarks.iter().map(|b| b.bookmark_id).collect();
+                if let Some(pos) = ids.iter().position(|&b| b == id) {
+                    let target = pos as i32 + delta;
+                    if target >= 0 && (target as usize) < ids.len() {
+                        ids.swap(pos, target as usize);
+                        if let Some(ref db) = self.metadata_db {
+                            if let Err(e) = db.reorder_bookmarks(&ids) {
+                                self.status.message = format!("Failed to reorder bookmarks: {}", e);
+                            }
+                        }
+                    }
+                }
+                self.refresh_bookmarks();
+            }
+        }
+    }
+
+    /// Reload bookmarks (and their existence) into the folder tree
+    fn refresh_bookmarks(&mut self) {
+        let Some(ref db) = self.metadata_db else {
+            return;
+        };
+        match db.list_bookmarks() {
+            Ok(bookmarks) => {
+                self.folder_tree.bookmarks = bookmarks.into_iter()
+                    .map(|b| BookmarkEntry::new(b.bookmark_id, PathBuf::from(b.path_display), b.name))
+                    .collect();
+            }
+            Err(e) => tracing::warn!("Failed to load bookmarks: {}", e),
+        }
+    }
+
+    /// Bookmark the folder being browsed (file.bookmark_folder)
+    fn bookmark_current_folder(&mut self) {
+        let Some(ref db) = self.metadata_db else {
+            self.status.message = "Bookmarks require the metadata database".to_string();
+            return;
+        };
+        let path = self.current_path.clone();
+        if self.folder_tree.bookmarks.iter().any(|b| b.path == path.as_path()) {
+            self.status.message = format!("Already bookmarked: {}", path);
+            return;
+        }
+        match db.add_bookmark(&path, None) {
+            Ok(_) => self.status.message = format!("Bookmarked: {}", path),
+            Err(e) => self.status.message = format!("Failed to add bookmark: {}", e),
+        }
+        self.refresh_bookmarks();
+    }
+
     #[allow(dead_code)]
     fn handle_toolbar_action(&mut self, action: ToolbarAction) {
         match action {
@@ -1875,6 +5628,8 @@ impl App {
                 let path = self.current_path.clone();
                 self.navigate_to(path);
             }
+            ToolbarAction::Search(query) => self.search_files(query),
+            ToolbarAction::Recent => self.show_recent(),
             ToolbarAction::NavigateTo(path_str) => {
                 let path = PathBuf::from(&path_str);
                 if path.exists() {
@@ -1887,27 +5642,17 @@ impl App {
                 self.new_folder_dialog = Some(NewFolderDialog::new());
             }
             ToolbarAction::Copy => {
-                if let Some(idx) = self.selected_index {
-                    if let Some(entry) = self.file_entries.get(idx) {
-                        let path_buf = entry.path.as_path().to_path_buf();
-                        let _ = self.file_ops.copy_to_clipboard(&[path_buf], ClipboardMode::Copy);
-                    }
+                let paths = self.operation_targets();
+                if !paths.is_empty() {
+                    let _ = self.file_ops.copy_to_clipboard(&paths, ClipboardMode::Copy);
                 }
             }
             ToolbarAction::Delete => {
-                if let Some(idx) = self.selected_index {
-                    if let Some(entry) = self.file_entries.get(idx) {
-                        self.pending_delete_path = Some(entry.path.as_path().to_path_buf());
-                        self.confirm_dialog = Some(ConfirmDialog::new_delete(&entry.name, true));
-                    }
-                }
+                self.confirm_delete(true);
             }
 
             // Sort
-            ToolbarAction::Sort(mode) => {
-                self.toolbar_state.sort_mode = mode;
-                self.apply_sort();
-            }
+            ToolbarAction::Sort(mode) => self.change_sort(mode),
 
             // Settings
             ToolbarAction::Settings => {
@@ -1962,6 +5707,38 @@ impl App {
             // Navigation Commands (nav.*)
             // ========================================
 
+            // Scroll the catalog without moving the cursor
+            CommandId::NAV_SCROLL_Y | CommandId::NAV_SCROLL_X => {
+                if self.show_browser {
+                    use app_core::{Direction, ScrollUnit};
+                    let vertical = cmd_id == CommandId::NAV_SCROLL_Y;
+                    let mut steps = cmd.params.amount.unwrap_or(1) as f32;
+                    if matches!(cmd.params.direction, Some(Direction::Up | Direction::Left)) {
+                        steps = -steps;
+                    }
+
+                    let catalog = &self.thumbnail_catalog;
+                    let step = match (cmd.params.unit.unwrap_or(ScrollUnit::Line), vertical) {
+                        (ScrollUnit::Pixel, _) => 1.0,
+                        (ScrollUnit::Line, true) => catalog.row_height(),
+                        (ScrollUnit::Line, false) => catalog.column_width(),
+                        (ScrollUnit::Page, true) => catalog.page_height(),
+                        (ScrollUnit::Page | ScrollUnit::Screen, false) => catalog.viewport_size().x,
+                        (ScrollUnit::Screen, true) => catalog.viewport_size().y,
+                    };
+
+                    let delta = if vertical {
+                        egui::vec2(0.0, steps * step)
+                    } else {
+                        egui::vec2(steps * step, 0.0)
+                    };
+                    self.thumbnail_catalog.scroll_by(delta);
+                    true
+                } else {
+                    false
+                }
+            }
+
             // Grid movement
             CommandId::NAV_MOVE_UP => {
                 if self.show_browser {
@@ -2045,6 +5822,21 @@ impl App {
                 }
                 true
             }
+            // Page movement: items in the browser, pages (or spreads) in the viewer
+            CommandId::NAV_NEXT_PAGE | CommandId::NAV_PREV_PAGE => {
+                let forward = cmd_id == CommandId::NAV_NEXT_PAGE;
+                if self.show_browser {
+                    if forward {
+                        self.nav_state.next_item(amount, wrap);
+                    } else {
+                        self.nav_state.prev_item(amount, wrap);
+                    }
+                    self.sync_selection_from_nav();
+                } else if !self.step_document_pages(forward, amount) {
+                    self.step_pages(forward, amount);
+                }
+                true
+            }
 
             // Hierarchy navigation
             CommandId::NAV_ENTER => {
@@ -2093,6 +5885,10 @@ impl App {
                     false
                 }
             }
+            CommandId::NAV_RECENT_FOLDERS => {
+                self.open_recent_folders();
+                true
+            }
 
             // ========================================
             // View Commands (view.*)
@@ -2135,6 +5931,9 @@ impl App {
             }
             CommandId::VIEW_ROTATE => {
                 let angle = cmd.params.angle.unwrap_or(90);
+                if self.split_view.enabled {
+                    self.split_view.active_pane_mut().rotate(angle > 0);
+                }
                 if angle > 0 {
                     self.image_transform.rotate_cw();
                     self.image_viewer.rotate_right();
@@ -2185,16 +5984,16 @@ impl App {
                 // Convert core SpreadMode to ui SpreadMode
                 match cmd.params.spread {
                     Some(CoreSpreadMode::Single) => self.spread_viewer.mode = SpreadMode::Single,
-                    Some(CoreSpreadMode::Spread) => self.spread_viewer.mode = SpreadMode::SpreadRTL,
+                    Some(CoreSpreadMode::Spread) => {
+                        self.spread_viewer.mode = spread_mode_for(self.spread_viewer.reading_direction);
+                    }
                     Some(CoreSpreadMode::Auto) | None => {
                         // Cycle through modes
                         self.spread_viewer.cycle_mode();
                     }
                 };
-                // Recalculate spread for current position
-                if let Some(idx) = self.selected_index {
-                    self.spread_viewer.go_to(idx, self.file_entries.len());
-                }
+                // Recalculate spread (and partner page) for current position
+                self.update_spread();
                 self.status.message = format!("Spread: {}", self.spread_viewer.mode_name());
                 true
             }
@@ -2251,10 +6050,37 @@ impl App {
                 }
                 true
             }
+            CommandId::VIEW_TOGGLE_SNAP => {
+                self.toggle_edge_snap();
+                true
+            }
+            CommandId::VIEW_SCROLL_N_TYPE_DOWN | CommandId::VIEW_SCROLL_N_TYPE_UP => {
+                // Zigzag reading for tall pages: down each column, left to right
+                let overlap = cmd.params.overlap.unwrap_or(50) as f32;
+                let available = self.image_viewer.get_estimated_available();
+                if cmd_id == CommandId::VIEW_SCROLL_N_TYPE_DOWN {
+                    if self.image_viewer.scroll_n_type_down(available, overlap) {
+                        self.next_image();
+                    }
+                } else if self.image_viewer.scroll_n_type_up(available, overlap) {
+                    self.prev_image();
+                }
+                true
+            }
             CommandId::VIEW_SLIDESHOW => {
-                use app_core::SlideshowAction;
-                let total = self.file_entries.iter().filter(|e| e.is_image()).count();
-                let current = self.selected_index.unwrap_or(0);
+                use app_core::{SlideshowAction, SlideshowOrder as CoreOrder};
+                use app_ui::components::SlideshowOrder;
+                if let Some(order) = cmd.params.order {
+                    self.slideshow.set_order(match order {
+                        CoreOrder::Normal => SlideshowOrder::Normal,
+                        CoreOrder::Reverse => SlideshowOrder::Reverse,
+                        CoreOrder::Shuffle => SlideshowOrder::Shuffle,
+                        CoreOrder::Random => SlideshowOrder::Random,
+                    });
+                }
+                let image_indices = self.image_indices();
+                let total = image_indices.len();
+                let current = self.current_image_position(&image_indices);
                 match cmd.params.action {
                     Some(SlideshowAction::Start) => self.slideshow.start(total, current),
                     Some(SlideshowAction::Stop) => self.slideshow.stop(),
@@ -2311,6 +6137,10 @@ impl App {
                 }
                 true
             }
+            CommandId::VIEW_ACTUAL_PIXELS => {
+                self.toggle_actual_pixels();
+                true
+            }
             CommandId::VIEW_ZOOM_MODE_CYCLE => {
                 // Cycle through zoom modes: FitWindow -> Original -> FitWidth -> FitHeight -> FitWindow
                 use app_ui::components::viewer::FitMode;
@@ -2363,7 +6193,74 @@ impl App {
                 }
             }
             CommandId::VIEW_TOGGLE_TRANSITION => {
-                self.status.message = "Transition toggled".to_string();
+                use app_core::TransitionMode;
+                self.page_transition.transition_type = match cmd.params.transition {
+                    Some(TransitionMode::None) => TransitionType::None,
+                    Some(TransitionMode::Fade) => TransitionType::Fade,
+                    Some(TransitionMode::Slide) => TransitionType::SlideLeft,
+                    None => {
+                        if self.page_transition.transition_type == TransitionType::None {
+                            TransitionType::Fade
+                        } else {
+                            TransitionType::None
+                        }
+                    }
+                };
+                self.page_transition.clear();
+                self.transition_from = None;
+                self.status.message = self.page_transition.status_text().to_string();
+                true
+            }
+            CommandId::VIEW_TIMELINE => {
+                self.toggle_timeline();
+                true
+            }
+            CommandId::VIEW_TOGGLE_HISTOGRAM => {
+                self.histogram.toggle();
+                self.status.message = if self.histogram.visible {
+                    format!("Histogram: {}", self.histogram.mode.name())
+                } else {
+                    "Histogram: Off".to_string()
+                };
+                true
+            }
+            CommandId::VIEW_ADJUST => {
+                self.adjust_panel.toggle();
+                true
+            }
+            CommandId::VIEW_GRAYSCALE => {
+                let adjustments = &mut self.adjust_panel.adjustments;
+                adjustments.grayscale = !adjustments.grayscale;
+                self.status.message = format!("Grayscale: {}", if adjustments.grayscale { "On" } else { "Off" });
+                self.refresh_adjusted_texture();
+                true
+            }
+            CommandId::VIEW_INVERT => {
+                let adjustments = &mut self.adjust_panel.adjustments;
+                adjustments.invert = !adjustments.invert;
+                self.status.message = format!("Invert: {}", if adjustments.invert { "On" } else { "Off" });
+                self.refresh_adjusted_texture();
+                true
+            }
+            CommandId::VIEW_TOGGLE_LOUPE => {
+                self.loupe.magnification = state().map(|s| s.config.read().viewer.loupe_magnification).unwrap_or(4.0);
+                self.loupe.toggle();
+                self.status.message = format!("Loupe: {}", if self.loupe.visible { "On" } else { "Off" });
+                true
+            }
+            CommandId::VIEW_TOGGLE_EYEDROPPER => {
+                self.eyedropper.toggle();
+                self.status.message = if self.eyedropper.active {
+                    "Eyedropper: click to copy the color".to_string()
+                } else {
+                    "Eyedropper: Off".to_string()
+                };
+                true
+            }
+            CommandId::VIEW_HISTOGRAM_MODE => {
+                self.histogram.cycle_mode();
+                self.histogram.visible = true;
+                self.status.message = format!("Histogram: {}", self.histogram.mode.name());
                 true
             }
             CommandId::VIEW_TOGGLE_CHROMELESS => {
@@ -2386,22 +6283,55 @@ impl App {
             CommandId::VIEW_SPLIT_MODE => {
                 self.split_view.toggle();
                 if self.split_view.enabled {
-                    // Set second pane to next file
+                    // First pane shows the current image, second the next one;
+                    // afterwards navigation fills whichever pane is active
+                    self.split_view.clear_panes();
                     if let Some(idx) = self.selected_index {
-                        if idx + 1 < self.file_entries.len() {
-                            self.split_view.panes[1].path = Some(
-                                self.file_entries[idx + 1].path.as_path().to_path_buf()
-                            );
+                        if let (Some(entry), Some(texture)) = (self.file_entries.get(idx).cloned(), self.current_texture.clone()) {
+                            let size = self.image_viewer.image_size;
+                            self.set_split_pane(0, &entry, texture, (size.x as u32, size.y as u32));
+                        }
+                        let next = self.file_entries.iter().skip(idx + 1).find(|e| e.is_image()).cloned();
+                        if let Some(entry) = next {
+                            self.load_split_pane(1, &entry);
                         }
                     }
                     self.status.message = format!("Split view: ON ({})", self.split_view.status_text());
                 } else {
+                    self.split_view.clear_panes();
+                    self.split_textures = [None, None];
                     self.status.message = "Split view: OFF".to_string();
                 }
                 true
             }
+            CommandId::VIEW_NEXT_VIEW_AREA => {
+                // Only split view has more than one area; otherwise leave the key alone
+                if !self.split_view.enabled {
+                    return false;
+                }
+                self.split_view.next_pane();
+                self.sync_selection_from_split();
+                self.status.message = format!("Active pane: {}", self.split_view.active_pane + 1);
+                true
+            }
+            CommandId::VIEW_COPY_VIEW_STATE => {
+                if self.split_view.enabled {
+                    let target = self.split_view.copy_view_state();
+                    self.status.message = format!("View copied to pane {}", target + 1);
+                } else {
+                    self.status.message = "Split view is off".to_string();
+                }
+                true
+            }
             CommandId::VIEW_SYNC_SCROLL => {
-                self.split_view.toggle_sync();
+                use app_core::SyncMode;
+                use app_ui::components::SplitSync;
+                match cmd.params.sync_mode {
+                    Some(SyncMode::None) => self.split_view.set_sync(None),
+                    Some(SyncMode::Position) => self.split_view.set_sync(Some(SplitSync::Position)),
+                    Some(SyncMode::Relative) => self.split_view.set_sync(Some(SplitSync::Relative)),
+                    None => self.split_view.toggle_sync(),
+                }
                 let sync = if self.split_view.sync_zoom { "ON" } else { "OFF" };
                 self.status.message = format!("Sync scroll: {}", sync);
                 true
@@ -2409,14 +6339,10 @@ impl App {
             CommandId::VIEW_SEEK => {
                 // Seek to position (0.0-1.0)
                 if let Some(pos) = cmd.params.seek_position {
-                    let total = self.file_entries.iter().filter(|e| e.is_image()).count();
+                    let image_indices = self.image_indices();
+                    let total = image_indices.len();
                     if total > 0 {
                         let target_idx = ((pos * total as f32) as usize).min(total - 1);
-                        let image_indices: Vec<usize> = self.file_entries.iter()
-                            .enumerate()
-                            .filter(|(_, e)| e.is_image())
-                            .map(|(i, _)| i)
-                            .collect();
                         if let Some(&idx) = image_indices.get(target_idx) {
                             self.on_select(idx);
                         }
@@ -2441,23 +6367,21 @@ impl App {
             // ========================================
 
             CommandId::FILE_COPY | CommandId::FILE_CUT => {
-                if let Some(idx) = self.selected_index {
-                    if let Some(entry) = self.file_entries.get(idx) {
-                        let mode = if cmd_id == CommandId::FILE_CUT {
-                            ClipboardMode::Cut
-                        } else {
-                            ClipboardMode::Copy
-                        };
+                let paths = self.operation_targets();
+                if !paths.is_empty() {
+                    let mode = if cmd_id == CommandId::FILE_CUT {
+                        ClipboardMode::Cut
+                    } else {
+                        ClipboardMode::Copy
+                    };
 
-                        let paths = vec![entry.path.as_path().to_path_buf()];
-                        match self.file_ops.copy_to_clipboard(&paths, mode) {
-                            Ok(_) => {
-                                let action = if cmd_id == CommandId::FILE_CUT { "Cut" } else { "Copied" };
-                                self.status.message = format!("{}: {}", action, entry.name);
-                            }
-                            Err(e) => {
-                                self.status.message = format!("Clipboard error: {}", e);
-                            }
+                    match self.file_ops.copy_to_clipboard(&paths, mode) {
+                        Ok(_) => {
+                            let action = if cmd_id == CommandId::FILE_CUT { "Cut" } else { "Copied" };
+                            self.status.message = format!("{}: {}", action, describe_paths(&paths));
+                        }
+                        Err(e) => {
+                            self.status.message = format!("Clipboard error: {}", e);
                         }
                     }
                 }
@@ -2505,33 +6429,15 @@ impl App {
                 true
             }
             CommandId::FILE_DELETE => {
-                if let Some(idx) = self.selected_index {
-                    if let Some(entry) = self.file_entries.get(idx) {
-                        let use_trash = cmd.params.trash.unwrap_or(true);
-                        let confirm = cmd.params.confirm.unwrap_or(true);
-
-                        if confirm {
-                            // ダイアログ表示
-                            self.pending_delete_path = Some(entry.path.as_path().to_path_buf());
-                            self.confirm_dialog = Some(ConfirmDialog::new_delete(
-                                &entry.name,
-                                use_trash
-                            ));
-                        } else {
-                            // 即削除
-                            let paths = vec![entry.path.as_path().to_path_buf()];
-                            match self.file_ops.delete(&paths, use_trash) {
-                                Ok(_) => {
-                                    let action = if use_trash { "Moved to trash" } else { "Deleted" };
-                                    self.status.message = format!("{}: {}", action, entry.name);
-                                    // Refresh directory
-                                    self.navigate_to(self.current_path.clone());
-                                }
-                                Err(e) => {
-                                    self.status.message = format!("Delete error: {}", e);
-                                }
-                            }
-                        }
+                let use_trash = cmd.params.trash.unwrap_or(true);
+                if cmd.params.confirm.unwrap_or(true) {
+                    // ダイアログ表示
+                    self.confirm_delete(use_trash);
+                } else {
+                    // 即削除
+                    let paths = self.operation_targets();
+                    if !paths.is_empty() {
+                        self.delete_paths(paths, use_trash);
                     }
                 }
                 true
@@ -2545,6 +6451,47 @@ impl App {
                 }
                 true
             }
+            CommandId::FILE_BATCH_RENAME => {
+                let files: Vec<RenameSource> = self.operation_targets().iter()
+                    .filter(|path| path.is_file())
+                    .map(|path| RenameSource::load(path))
+                    .collect();
+                if files.is_empty() {
+                    self.status.message = "No files to rename".to_string();
+                } else {
+                    self.batch_rename_dialog = Some(BatchRenameDialog::new(files));
+                }
+                true
+            }
+            CommandId::FILE_ROTATE_SAVE => {
+                // Without an angle, bake in the current view rotation (or turn right)
+                let angle = cmd.params.angle.unwrap_or(match self.image_viewer.rotation {
+                    0 => 90,
+                    rotation => rotation,
+                });
+                self.rotate_and_save(angle);
+                true
+            }
+            CommandId::FILE_SAVE_AS => {
+                if let Some((source, has_exif)) = self.save_source() {
+                    let has_adjustments = !self.adjust_panel.adjustments.is_identity();
+                    self.save_as_dialog = Some(SaveAsDialog::new(&source, has_exif).with_adjustments(has_adjustments));
+                }
+                true
+            }
+            CommandId::FILE_EXPORT => {
+                if let (Some((source, has_exif)), Some(pixels)) = (self.save_source(), self.viewed_pixels(true)) {
+                    let interpolation = state().map(|s| s.config.read().viewer.interpolation).unwrap_or(Interpolation::Lanczos3);
+                    let has_adjustments = !self.adjust_panel.adjustments.is_identity();
+                    self.export_dialog =
+                        Some(ExportDialog::new(&source, has_exif, &pixels, interpolation).with_adjustments(has_adjustments));
+                }
+                true
+            }
+            CommandId::FILE_BATCH_CONVERT => {
+                self.open_batch_convert();
+                true
+            }
             CommandId::FILE_CREATE_DIR => {
                 // TODO: Show dialog to get directory name
                 self.status.message = "Create directory (dialog required - not yet implemented)".to_string();
@@ -2557,33 +6504,32 @@ impl App {
                 // }
                 true
             }
-            CommandId::FILE_COPY_TO | CommandId::FILE_MOVE_TO => {
-                if let Some(target_str) = &cmd.params.target {
-                    if let Some(idx) = self.selected_index {
-                        if let Some(entry) = self.file_entries.get(idx) {
-                            let target_dir = PathBuf::from(target_str);
-                            let sources = vec![entry.path.as_path().to_path_buf()];
-
-                            let result = if cmd_id == CommandId::FILE_MOVE_TO {
-                                self.file_ops.move_to(&sources, &target_dir)
-                            } else {
-                                self.file_ops.copy_to(&sources, &target_dir)
-                            };
-
-                            match result {
-                                Ok(files) => {
-                                    let action = if cmd_id == CommandId::FILE_MOVE_TO { "Moved" } else { "Copied" };
-                                    self.status.message = format!("{} {} to {}", action, entry.name, target_str);
-                                    // Refresh if moved
-                                    if cmd_id == CommandId::FILE_MOVE_TO {
-                                        self.navigate_to(self.current_path.clone());
-                                    }
-                                }
-                                Err(e) => {
-                                    self.status.message = format!("File operation error: {}", e);
-                                }
-                            }
-                        }
+            CommandId::FILE_FIND_DUPLICATES => {
+                self.find_duplicates();
+                true
+            }
+            CommandId::FILE_FLATTEN_SUBFOLDERS => {
+                self.toggle_flatten();
+                true
+            }
+            CommandId::FILE_TOGGLE_HIDDEN => {
+                self.toggle_hidden_files();
+                true
+            }
+            CommandId::FILE_TOGGLE_SORT_ORDER => {
+                self.change_sort(self.toolbar_state.sort_mode.reversed());
+                self.status.message = format!("Sort: {}", self.toolbar_state.sort_mode.label());
+                true
+            }
+            CommandId::FILE_BOOKMARK_FOLDER => {
+                self.bookmark_current_folder();
+                true
+            }
+            CommandId::FILE_COPY_TO | CommandId::FILE_MOVE_TO => {
+                if let Some(target_str) = &cmd.params.target {
+                    let sources = self.operation_targets();
+                    if !sources.is_empty() {
+                        self.transfer_paths(sources, &PathBuf::from(target_str), cmd_id == CommandId::FILE_MOVE_TO);
                     }
                 } else {
                     // TODO: Show dialog to select target directory
@@ -2591,6 +6537,14 @@ impl App {
                 }
                 true
             }
+            CommandId::FILE_OPEN_TERMINAL => {
+                self.open_terminal();
+                true
+            }
+            CommandId::FILE_SET_WALLPAPER => {
+                self.set_wallpaper(cmd.params.wallpaper_style);
+                true
+            }
             CommandId::FILE_OPEN_EXPLORER => {
                 let select = cmd.params.select.unwrap_or(true);
                 let path = if let Some(idx) = self.selected_index {
@@ -2612,48 +6566,21 @@ impl App {
                 true
             }
             CommandId::FILE_OPEN_WITH => {
-                if let Some(app_id) = &cmd.params.app_id {
-                    if let Some(idx) = self.selected_index {
-                        if let Some(entry) = self.file_entries.get(idx) {
-                            let args = cmd.params.args.as_deref();
-                            match self.file_ops.open_with(entry.path.as_path(), app_id, args) {
-                                Ok(_) => {
-                                    self.status.message = format!("Opened {} with {}", entry.name, app_id);
-                                }
-                                Err(e) => {
-                                    self.status.message = format!("Open with error: {}", e);
-                                }
-                            }
-                        }
-                    }
+                match &cmd.params.app_id {
+                    Some(app_id) => self.open_selected_with(app_id, cmd.params.args.as_deref()),
+                    None => self.open_with_menu(),
                 }
                 true
             }
             CommandId::FILE_OPEN_EXTERNAL => {
-                if let Some(idx) = self.selected_index {
-                    if let Some(entry) = self.file_entries.get(idx) {
-                        match self.file_ops.open_external(entry.path.as_path()) {
-                            Ok(_) => {
-                                self.status.message = format!("Opened: {}", entry.name);
-                            }
-                            Err(e) => {
-                                self.status.message = format!("Open external error: {}", e);
-                            }
-                        }
-                    }
+                if let Some(entry) = self.selected_index.and_then(|idx| self.file_entries.get(idx)).cloned() {
+                    self.hand_off(&entry, ExternalAction::Open);
                 }
                 true
             }
             CommandId::FILE_PROPERTIES => {
-                if let Some(idx) = self.selected_index {
-                    if let Some(entry) = self.file_entries.get(idx) {
-                        self.status.message = format!(
-                            "{}: {} ({} bytes)",
-                            entry.name,
-                            if entry.is_dir { "Directory" } else { "File" },
-                            entry.size
-                        );
-                    }
+                if let Some(entry) = self.selected_index.and_then(|idx| self.file_entries.get(idx)).cloned() {
+                    self.show_properties(&entry);
                 }
                 true
             }
@@ -2674,6 +6601,9 @@ impl App {
                                 // Set rating
                                 match db.set_rating(entry.path.id(), rating) {
                                     Ok(_) => {
+                                        if let Some(item) = self.catalog_items.get_mut(idx) {
+                                            item.rating = rating;
+                                        }
                                         self.status.message = format!("{}: Rating {} (saved)", entry.name, "★".repeat(rating as usize));
                                     }
                                     Err(e) => {
@@ -2699,6 +6629,9 @@ impl App {
                             let _ = db.upsert_file(&entry.path, Some(entry.size as i64), entry.modified);
                             match db.set_rating(entry.path.id(), new_rating) {
                                 Ok(_) => {
+                                    if let Some(item) = self.catalog_items.get_mut(idx) {
+                                        item.rating = new_rating;
+                                    }
                                     self.status.message = format!("{}: Rating {} (saved)", entry.name, "★".repeat(new_rating as usize));
                                 }
                                 Err(e) => {
@@ -2714,20 +6647,17 @@ impl App {
             }
             CommandId::META_LABEL => {
                 use app_core::LabelColor;
-                let (label_name, label_value) = match cmd.params.label_color {
-                    Some(LabelColor::Red) => ("Red", Some(0xFF0000u32)),
-                    Some(LabelColor::Blue) => ("Blue", Some(0x0000FF)),
-                    Some(LabelColor::Green) => ("Green", Some(0x00FF00)),
-                    Some(LabelColor::Yellow) => ("Yellow", Some(0xFFFF00)),
-                    Some(LabelColor::Purple) => ("Purple", Some(0x800080)),
-                    Some(LabelColor::None) | None => ("None", None),
-                };
+                let color = cmd.params.label_color.unwrap_or(LabelColor::None);
+                let (label_name, label_value) = (color.name(), color.rgb());
                 if let Some(idx) = self.selected_index {
                     if let Some(entry) = self.file_entries.get(idx) {
                         if let Some(ref db) = self.metadata_db {
                             let _ = db.upsert_file(&entry.path, Some(entry.size as i64), entry.modified);
                             match db.set_label(entry.path.id(), label_value) {
                                 Ok(_) => {
+                                    if let Some(item) = self.catalog_items.get_mut(idx) {
+                                        item.label = label_value.and_then(label_color32);
+                                    }
                                     self.status.message = format!("{}: Label {} (saved)", entry.name, label_name);
                                 }
                                 Err(e) => {
@@ -2781,6 +6711,7 @@ impl App {
                                     match result {
                                         Ok(_) => {
                                             self.status.message = format!("{}: {} (saved)", action, tag_name);
+                                            self.refresh_tags();
                                         }
                                         Err(e) => {
                                             self.status.message = format!("Failed to {} {}: {}", action.to_lowercase(), tag_name, e);
@@ -2807,17 +6738,47 @@ impl App {
                 true
             }
             CommandId::META_COPY_META => {
-                use app_core::CopyTarget;
-                let target = match cmd.params.copy_target {
-                    Some(CopyTarget::Rating) => "rating",
-                    Some(CopyTarget::Tags) => "tags",
-                    Some(CopyTarget::All) | None => "all metadata",
-                };
-                self.status.message = format!("Copied {}", target);
+                let target = cmd.params.copy_target.unwrap_or(app_core::CopyTarget::All);
+                self.copy_metadata(target);
+                true
+            }
+            CommandId::META_PASTE_META => {
+                self.paste_metadata();
                 true
             }
             CommandId::META_EDIT_COMMENT => {
-                self.status.message = "Edit comment (dialog required)".to_string();
+                if let Some(entry) = self.selected_index.and_then(|i| self.file_entries.get(i)).cloned() {
+                    let comment = self.metadata_db.as_ref()
+                        .and_then(|db| db.get_comment(entry.path.id()).ok().flatten());
+                    self.comment_dialog = Some(CommentDialog::new(&entry.name, comment));
+                    self.pending_comment_entry = Some(entry);
+                }
+                true
+            }
+            CommandId::META_EXPORT_MARKS => {
+                if self.marked_files.is_empty() {
+                    self.status.message = "No marked files".to_string();
+                } else if let Some(target) = &cmd.params.target {
+                    self.export_marks(&PathBuf::from(target));
+                } else {
+                    let default_path = self.current_path.as_path().join("marked.txt");
+                    self.export_marks_dialog = Some(ExportMarksDialog::new(
+                        &default_path.display().to_string(),
+                        self.marked_files.len(),
+                    ));
+                }
+                true
+            }
+            CommandId::META_EXPORT_SIDECARS => {
+                self.export_sidecars(false);
+                true
+            }
+            CommandId::META_EXPORT_ALL_SIDECARS => {
+                self.export_sidecars(true);
+                true
+            }
+            CommandId::META_IMPORT_SIDECARS => {
+                self.import_sidecars();
                 true
             }
             CommandId::META_TOGGLE_MARK => {
@@ -2837,10 +6798,19 @@ impl App {
             }
             CommandId::META_SELECT_MARKED => {
                 // Select all marked files in current folder
-                let marked_count = self.file_entries.iter()
-                    .filter(|e| self.marked_files.contains(&e.path.id()))
-                    .count();
-                self.status.message = format!("{} marked files in current folder", marked_count);
+                let marked: Vec<usize> = self.file_entries.iter()
+                    .enumerate()
+                    .filter(|(_, e)| self.marked_files.contains(&e.path.id()))
+                    .map(|(i, _)| i)
+                    .collect();
+                if let Some(&first) = marked.first() {
+                    self.status.message = format!("Selected {} marked files", marked.len());
+                    self.on_select(first);
+                    self.nav_state.selection.selected = marked;
+                    self.record_selection();
+                } else {
+                    self.status.message = "No marked files in current folder".to_string();
+                }
                 true
             }
 
@@ -2852,6 +6822,14 @@ impl App {
                 // Will be handled by returning true and checking in event loop
                 true
             }
+            CommandId::APP_COMMAND_PALETTE => {
+                self.open_command_palette();
+                true
+            }
+            CommandId::APP_RELOAD_CONFIG => {
+                self.reload_config();
+                true
+            }
             CommandId::APP_OPEN_SETTINGS => {
                 let config = state().map(|s| s.config.read().clone()).unwrap_or_default();
                 self.settings_dialog.open(config, None);
@@ -2872,6 +6850,10 @@ impl App {
                 self.status.message = "Cache cleared".to_string();
                 true
             }
+            CommandId::APP_EMPTY_TRASH => {
+                self.confirm_empty_trash();
+                true
+            }
             CommandId::APP_MINIMIZE => {
                 if let Some(window) = &self.window {
                     window.set_minimized(true);
@@ -2934,7 +6916,7 @@ impl App {
                 true
             }
             CommandId::APP_SEARCH => {
-                self.status.message = "Search (dialog required)".to_string();
+                self.toolbar_state.focus_search = true;
                 true
             }
             CommandId::APP_RESTART => {
@@ -2949,14 +6931,464 @@ impl App {
         }
     }
 
+    /// Indices of the image entries the viewer steps through (those passing the catalog filter)
+    fn image_indices(&self) -> Vec<usize> {
+        self.catalog_view.iter()
+            .copied()
+            .filter(|&i| self.file_entries.get(i).map(|e| e.is_image()).unwrap_or(false))
+            .collect()
+    }
+
+    /// Position of the selected entry within `image_indices` (0 if not an image)
+    fn current_image_position(&self, image_indices: &[usize]) -> usize {
+        self.selected_index
+            .and_then(|idx| image_indices.iter().position(|&i| i == idx))
+            .unwrap_or(0)
+    }
+
+    /// Show the recycle bin window
+    fn open_trash(&mut self) {
+        self.trash_view = Some(match self.file_ops.list_trash() {
+            Ok(items) => TrashView::new(items),
+            Err(FileOpError::Unsupported(_)) => TrashView::unavailable(
+                "Browsing the recycle bin is not supported on this platform".to_string(),
+            ),
+            Err(e) => TrashView::unavailable(format!("Failed to read the recycle bin: {}", e)),
+        });
+    }
+
+    /// Rotate the operation targets on disk (file.rotate_save)
+    /// JPEGs are rotated losslessly; other images need confirmation to be re-encoded.
+    fn rotate_and_save(&mut self, angle: i32) {
+        let quarter_turns = (angle.rem_euclid(360) / 90) as u32;
+        let paths: Vec<PathBuf> = self.operation_targets().into_iter()
+            .filter(|path| path.is_file() && is_supported_image(path))
+            .collect();
+        if quarter_turns == 0 || paths.is_empty() {
+            self.status.message = "Nothing to rotate".to_string();
+            return;
+        }
+
+        let (jpegs, others): (Vec<PathBuf>, Vec<PathBuf>) = paths.into_iter().partition(|path| app_fs::is_jpeg(path));
+        let mut rotated = Vec::new();
+        let mut errors = Vec::new();
+        for path in jpegs {
+            match self.file_ops.rotate_jpeg(&path, quarter_turns) {
+                Ok(_) => rotated.push(path),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        self.status.message = match errors.first() {
+            Some(error) => format!("Rotated {} JPEG files, {} failed: {}", rotated.len(), errors.len(), error),
+            None => format!("Rotated {} JPEG files losslessly", rotated.len()),
+        };
+        self.rotation_saved(&rotated);
+
+        if !others.is_empty() {
+            self.pending_delete = None;
+            self.pending_empty_trash = false;
+            self.pending_purge = None;
+            self.confirm_dialog = Some(ConfirmDialog::new_reencode(others.len()));
+            self.pending_reencode = Some((others, quarter_turns));
+        }
+    }
+
+    /// Rotate non-JPEG images by decoding and saving them in their own format
+    fn reencode_rotated(&mut self, paths: Vec<PathBuf>, quarter_turns: u32) {
+        let mut rotated = Vec::new();
+        let mut errors = Vec::new();
+        for path in paths {
+            let result = image::ImageFormat::from_path(&path).and_then(|format| {
+                let img = image::open(&path)?;
+                let img = match quarter_turns {
+                    1 => img.rotate90(),
+                    2 => img.rotate180(),
+                    _ => img.rotate270(),
+                };
+                // Write next to the original and swap, as rotate_jpeg does
+                let mut temp = path.as_os_str().to_owned();
+                temp.push(".rotating");
+                let temp = PathBuf::from(temp);
+                img.save_with_format(&temp, format)?;
+                if let Err(e) = std::fs::rename(&temp, &path) {
+                    let _ = std::fs::remove_file(&temp);
+                    return Err(e.into());
+                }
+                Ok(())
+            });
+            match result {
+                Ok(()) => rotated.push(path),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        self.status.message = match errors.first() {
+            Some(error) => format!("Re-encoded {} files, {} failed: {}", rotated.len(), errors.len(), error),
+            None => format!("Rotated and re-encoded {} files", rotated.len()),
+        };
+        self.rotation_saved(&rotated);
+    }
+
+    /// Drop stale thumbnails and reload the viewer after files were rotated on disk
+    fn rotation_saved(&mut self, paths: &[PathBuf]) {
+        for path in paths {
+            let id = UniversalPath::new(path).id();
+            if let Some(ref cache) = self.thumbnail_cache {
+                let _ = cache.delete_by_hash(id);
+            }
+            self.thumbnail_textures.remove(&id);
+        }
+
+        let current = self.selected_index.and_then(|i| self.file_entries.get(i)).cloned();
+        if let Some(entry) = current.filter(|e| paths.iter().any(|p| p.as_path() == e.path.as_path())) {
+            // The rotation is now part of the file
+            self.image_viewer.rotation = 0;
+            self.image_transform.reset();
+            self.load_image(&entry);
+        }
+    }
+
+    /// Default path and whether EXIF can be kept, for saving the image in the viewer
+    /// (file.save_as / file.export); reads the EXIF to copy
+    fn save_source(&mut self) -> Option<(PathBuf, bool)> {
+        let entry = self.selected_index.and_then(|i| self.file_entries.get(i)).cloned();
+        let (Some(entry), Some(_)) = (entry, self.current_pixels.as_ref()) else {
+            self.status.message = "No image to save".to_string();
+            return None;
+        };
+
+        // Default next to the original; archive members go next to the archive
+        let source = match self.current_archive {
+            Some(ref vfs) => vfs.archive_path().as_path().with_file_name(&entry.name),
+            None => entry.path.to_path_buf(),
+        };
+        self.save_as_exif = self.external_path(&entry).ok().and_then(|path| read_exif_block(&path));
+        Some((source, self.save_as_exif.is_some()))
+    }
+
+    /// Pixels as shown in the viewer: adjustments (if `adjusted`), flips, then the view rotation
+    fn viewed_pixels(&self, adjusted: bool) -> Option<image::RgbaImage> {
+        use image::imageops;
+
+        let mut pixels = self.current_pixels.clone()?;
+        if adjusted {
+            self.adjust_panel.adjustments.apply(&mut pixels);
+        }
+        if self.image_transform.flip_horizontal {
+            imageops::flip_horizontal_in_place(&mut pixels);
+        }
+        if self.image_transform.flip_vertical {
+            imageops::flip_vertical_in_place(&mut pixels);
+        }
+        Some(match self.image_viewer.rotation {
+            90 => imageops::rotate90(&pixels),
+            180 => imageops::rotate180(&pixels),
+            270 => imageops::rotate270(&pixels),
+            _ => pixels,
+        })
+    }
+
+    /// Save the viewed image (file.save_as)
+    fn save_image_as(&mut self, request: SaveAsRequest) {
+        match self.viewed_pixels(request.apply_adjustments) {
+            Some(pixels) => self.write_image(&pixels, request),
+            None => self.status.message = "No image to save".to_string(),
+        }
+    }
+
+    /// Save the viewed image resized (file.export)
+    fn export_image(&mut self, request: ExportRequest) {
+        let Some(pixels) = self.viewed_pixels(request.save.apply_adjustments) else {
+            self.status.message = "No image to save".to_string();
+            return;
+        };
+        let pixels = if pixels.dimensions() == (request.width, request.height) {
+            pixels
+        } else {
+            resize_image(&pixels, request.width, request.height, request.interpolation)
+        };
+        self.write_image(&pixels, request.save);
+    }
+
+    /// Encode pixels and write them, resolving name conflicts by the configured policy
+    fn write_image(&mut self, pixels: &image::RgbaImage, request: SaveAsRequest) {
+        let exif = self.save_as_exif.take().filter(|_| request.keep_exif);
+        let policy = state().map(|s| s.config.read().filer.conflict_policy).unwrap_or(ConflictPolicy::Rename);
+        let target = match policy {
+            _ if !request.path.exists() => request.path,
+            ConflictPolicy::Overwrite => request.path,
+            ConflictPolicy::Rename => app_fs::unique_path(&request.path),
+            ConflictPolicy::Skip => {
+                self.status.message = format!("Not saved: {} already exists", request.path.display());
+                return;
+            }
+        };
+
+        let result = encode_image(pixels, request.format, request.quality, exif)
+            .map_err(|e| e.to_string())
+            .and_then(|data| std::fs::write(&target, data).map_err(|e| e.to_string()));
+        self.status.message = match result {
+            Ok(()) => format!("Saved {}", target.display()),
+            Err(e) => format!("Cannot save {}: {}", target.display(), e),
+        };
+        if target.parent() == Some(self.current_path.as_path()) {
+            self.refresh_current_directory();
+        }
+    }
+
+    /// Ask before emptying the trash (app.empty_trash)
+    fn confirm_empty_trash(&mut self) {
+        let count = match self.file_ops.list_trash() {
+            Ok(items) if items.is_empty() => {
+                self.status.message = "The recycle bin is already empty".to_string();
+                return;
+            }
+            Ok(items) => Some(items.len()),
+            Err(_) => None,
+        };
+        self.pending_delete = None;
+        self.pending_empty_trash = true;
+        self.pending_purge = None;
+        self.pending_reencode = None;
+        self.confirm_dialog = Some(ConfirmDialog::new_empty_trash(count));
+    }
+
+    fn empty_trash(&mut self) {
+        self.status.message = match self.file_ops.empty_trash() {
+            Ok(count) => format!("Emptied the recycle bin ({} items removed)", count),
+            Err(e) => format!("Cannot empty the recycle bin: {}", e),
+        };
+        if let Some(ref mut view) = self.trash_view {
+            view.set_items(Vec::new());
+        }
+    }
+
+    /// Restore items checked in the recycle bin window, or ask before purging them
+    fn handle_trash_action(&mut self, action: TrashAction) {
+        if let TrashAction::Purge(items) = action {
+            if !items.is_empty() {
+                self.pending_delete = None;
+                self.pending_empty_trash = false;
+                self.pending_reencode = None;
+                self.confirm_dialog = Some(ConfirmDialog::new_purge_trash(items.len()));
+                self.pending_purge = Some(items);
+            }
+            return;
+        }
+        let result = match action {
+            TrashAction::Restore(items) => self.file_ops.restore_from_trash(&items).map(|_| {
+                // Restored files may land in the folder being browsed
+                self.reload_listing();
+                format!("Restored {} item(s)", items.len())
+            }),
+            TrashAction::Purge(_) | TrashAction::Refresh => Ok(String::new()),
+        };
+        match result {
+            Ok(message) if !message.is_empty() => self.status.message = message,
+            Ok(_) => {}
+            Err(e) => self.status.message = format!("Recycle bin error: {}", e),
+        }
+        self.refresh_trash_view();
+    }
+
+    /// Delete recycle bin items for good, once confirmed
+    fn purge_trash(&mut self, items: Vec<app_fs::TrashEntry>) {
+        self.status.message = match self.file_ops.purge_from_trash(&items) {
+            Ok(()) => format!("Permanently deleted {} item(s)", items.len()),
+            Err(e) => format!("Recycle bin error: {}", e),
+        };
+        self.refresh_trash_view();
+    }
+
+    /// Reload the recycle bin window's list
+    fn refresh_trash_view(&mut self) {
+        match self.file_ops.list_trash() {
+            Ok(items) => {
+                if let Some(ref mut view) = self.trash_view {
+                    view.set_items(items);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to refresh the recycle bin: {}", e),
+        }
+    }
+
+    /// True while a modal dialog or popup is open
+    fn dialog_open(&self) -> bool {
+        self.settings_dialog.open
+            || self.command_palette.is_open()
+            || self.confirm_dialog.is_some()
+            || self.rename_dialog.is_some()
+            || self.batch_rename_dialog.is_some()
+            || self.save_as_dialog.is_some()
+            || self.export_dialog.is_some()
+            || self.batch_convert_dialog.is_some()
+            || self.recent_folders_menu.is_some()
+            || self.open_with_menu.is_some()
+            || self.new_folder_dialog.is_some()
+            || self.tag_dialog.is_some()
+            || self.comment_dialog.is_some()
+            || self.export_marks_dialog.is_some()
+            || self.properties_dialog.is_some()
+            || self.duplicate_review.is_some()
+            || self.trash_view.is_some()
+    }
+
+    /// Jump to the first file whose name starts with the typed text (browser mode only)
+    /// Unbound letters start the buffer; while it is active every plain character extends it
+    fn handle_type_ahead(&mut self, event: &winit::event::KeyEvent) -> bool {
+        use winit::keyboard::Key;
+
+        let Key::Character(text) = &event.logical_key else {
+            return false;
+        };
+        if !self.show_browser || self.dialog_open() || text.trim().is_empty() {
+            return false;
+        }
+        let Some(handler) = &self.input_handler else {
+            return false;
+        };
+        let modifiers = handler.modifiers();
+        if modifiers.control_key() || modifiers.alt_key() || modifiers.super_key() {
+            return false;
+        }
+
+        let now = std::time::Instant::now();
+        if !self.type_ahead.is_active(now) && handler.handle_key(event).is_some() {
+            return false;
+        }
+
+        let names = self.file_entries.iter().map(|e| e.name.as_str());
+        if let Some(idx) = self.type_ahead.input(text, names, self.selected_index, now) {
+            self.on_select(idx);
+        }
+        true
+    }
+
+    /// Current image size with the viewer rotation applied
+    fn viewer_rotated_size(&self) -> egui::Vec2 {
+        if self.image_viewer.rotation == 90 || self.image_viewer.rotation == 270 {
+            egui::Vec2::new(self.image_viewer.image_size.y, self.image_viewer.image_size.x)
+        } else {
+            self.image_viewer.image_size
+        }
+    }
+
+    /// Follow viewport size changes in the fit modes
+    /// A manual zoom keeps its on-screen scale instead of snapping back to fit
+    fn refit_viewer(&mut self) {
+        use app_ui::components::viewer::{refit_zoom, FitMode, REFIT_TOLERANCE};
+
+        let available = self.egui_ctx.screen_rect().size();
+        let delta = (available - self.viewer_area).abs();
+        if delta.x < REFIT_TOLERANCE && delta.y < REFIT_TOLERANCE {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.viewer_area, available);
+
+        let fit_mode = self.image_viewer.fit_mode;
+        if fit_mode == FitMode::OriginalSize
+            || previous == egui::Vec2::ZERO
+            || self.image_viewer.image_size == egui::Vec2::ZERO
+        {
+            return;
+        }
+
+        let rotated_size = self.viewer_rotated_size();
+        let old_base = fit_scale(fit_mode, previous, rotated_size);
+        let new_base = fit_scale(fit_mode, available, rotated_size);
+        self.image_viewer.zoom = refit_zoom(self.image_viewer.zoom, old_base, new_base);
+    }
+
+    /// Copy the hex value of the pixel under the eyedropper
+    fn copy_eyedropper_color(&mut self) {
+        let Some(sample) = self.eyedropper.sample else {
+            return;
+        };
+        let hex = sample.hex();
+        #[cfg(feature = "clipboard")]
+        {
+            if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                let _ = clipboard.set_text(&hex);
+            }
+        }
+        self.status.message = format!("Color copied: {}", hex);
+    }
+
+    /// Jump to 100% with the pixel under the cursor kept in place, or back to
+    /// the previous fit mode (view.actual_pixels)
+    fn toggle_actual_pixels(&mut self) {
+        if self.image_viewer.image_size == egui::Vec2::ZERO {
+            return;
+        }
+        let screen = self.egui_ctx.screen_rect();
+        let anchor = self.egui_ctx.input(|i| i.pointer.hover_pos())
+            .filter(|pos| screen.contains(*pos))
+            .map_or(egui::Vec2::ZERO, |pos| pos - screen.center());
+        let scale = fit_scale(self.image_viewer.fit_mode, screen.size(), self.viewer_rotated_size()) * self.image_viewer.zoom;
+        self.image_viewer.toggle_actual_pixels(anchor, scale);
+        self.status.message = if self.image_viewer.fit_mode == app_ui::components::viewer::FitMode::OriginalSize {
+            "Zoom: 100%".to_string()
+        } else {
+            format!("Zoom: {:?}", self.image_viewer.fit_mode)
+        };
+    }
+
+    /// Snap image edges that are near the viewport edges flush to them (viewer.snap_to_edges)
+    fn snap_viewer_pan(&mut self) {
+        use app_ui::components::viewer::{snap_pan, SNAP_THRESHOLD};
+
+        let snap = state().map(|s| s.config.read().viewer.snap_to_edges).unwrap_or(true);
+        self.image_viewer.snap_to_edges = snap;
+        if !snap || self.image_viewer.image_size == egui::Vec2::ZERO {
+            return;
+        }
+        let rotated_size = self.viewer_rotated_size();
+        let available = self.egui_ctx.screen_rect().size();
+        let base_scale = fit_scale(self.image_viewer.fit_mode, available, rotated_size);
+        let display_size = rotated_size * base_scale * self.image_viewer.zoom;
+        self.image_viewer.pan = snap_pan(self.image_viewer.pan, display_size, available, SNAP_THRESHOLD);
+    }
+
+    /// Toggle edge snapping while panning and persist it (viewer.snap_to_edges)
+    fn toggle_edge_snap(&mut self) {
+        let Some(state) = state() else {
+            return;
+        };
+        let enabled = {
+            let mut config = state.config.write();
+            config.viewer.snap_to_edges = !config.viewer.snap_to_edges;
+            if let Err(e) = config.save() {
+                tracing::error!("Failed to save config: {}", e);
+            }
+            config.viewer.snap_to_edges
+        };
+        self.image_viewer.snap_to_edges = enabled;
+        self.status.message = format!("Edge snap: {}", if enabled { "ON" } else { "OFF" });
+    }
+
+    /// Keep the viewer image from being panned out of view (viewer.clamp_pan)
+    fn clamp_viewer_pan(&mut self) {
+        let clamp = state().map(|s| s.config.read().viewer.clamp_pan).unwrap_or(true);
+        self.image_viewer.clamp_pan = clamp;
+        if !clamp || self.image_viewer.image_size == egui::Vec2::ZERO {
+            return;
+        }
+
+        let rotated_size = self.viewer_rotated_size();
+        let available = self.egui_ctx.screen_rect().size();
+        let base_scale = fit_scale(self.image_viewer.fit_mode, available, rotated_size);
+        let display_size = rotated_size * base_scale * self.image_viewer.zoom;
+        self.image_viewer.pan = app_ui::components::viewer::clamp_pan(self.image_viewer.pan, display_size, available);
+    }
+
     /// Sync selection state from NavigationState to app state
     fn sync_selection_from_nav(&mut self) {
         let idx = self.nav_state.current_index();
         self.selected_index = Some(idx);
         self.file_browser.selected = Some(idx);
+        self.record_selection();
 
         // Update position status
-        self.status.position = format!("{} / {}", idx + 1, self.file_entries.len());
+        self.status.position = self.view_position_text(idx);
 
         // Load image preview if applicable
         if let Some(entry) = self.file_entries.get(idx) {
@@ -2978,13 +7410,25 @@ impl App {
         }
     }
 
-    /// Handle file system events from watcher
-    fn handle_fs_event(&mut self, event: FsEvent) {
+    /// Handle a batch of coalesced watcher events with a single directory refresh
+    fn handle_fs_events(&mut self, events: Vec<FsEvent>) {
+        let mut refresh = false;
+        for event in events {
+            refresh |= self.handle_fs_event(event);
+        }
+        if refresh {
+            // The folder's files changed since it was indexed for search
+            self.search_indexed = None;
+            self.refresh_current_directory();
+        }
+    }
+
+    /// Handle a file system event from the watcher
+    /// Returns true if the directory listing needs a refresh
+    fn handle_fs_event(&mut self, event: FsEvent) -> bool {
         match event {
             FsEvent::Created(path) => {
                 tracing::info!("File created: {}", path.display());
-                // Refresh directory list
-                self.refresh_current_directory();
 
                 // DB registration
                 if let Some(ref db) = self.metadata_db {
@@ -2996,10 +7440,10 @@ impl App {
                         .map(|d| d.as_secs() as i64);
                     let _ = db.upsert_file(&upath, size, modified);
                 }
+                true
             }
             FsEvent::Removed(path) => {
                 tracing::info!("File removed: {}", path.display());
-                self.refresh_current_directory();
 
                 // DB deletion
                 if let Some(ref db) = self.metadata_db {
@@ -3012,6 +7456,7 @@ impl App {
                     let upath = UniversalPath::new(&path);
                     let _ = cache.delete_by_hash(upath.id());
                 }
+                true
             }
             FsEvent::Modified(path) => {
                 tracing::debug!("File modified: {}", path.display());
@@ -3024,17 +7469,17 @@ impl App {
                         }
                     }
                 }
+                false
             }
             FsEvent::Renamed { from, to } => {
                 tracing::info!("File renamed: {} -> {}", from.display(), to.display());
-                self.refresh_current_directory();
 
-                // DB: delete old + insert new (since rename_file doesn't exist yet)
+                // DB: move the row so metadata follows the file
                 if let Some(ref db) = self.metadata_db {
                     let old_upath = UniversalPath::new(&from);
-                    let _ = db.delete_file(old_upath.id());
-
                     let new_upath = UniversalPath::new(&to);
+                    let _ = db.rename_file(&old_upath, &new_upath);
+
                     let size = to.metadata().map(|m| m.len() as i64).ok();
                     let modified = to.metadata().ok()
                         .and_then(|m| m.modified().ok())
@@ -3048,19 +7493,27 @@ impl App {
                     let old_upath = UniversalPath::new(&from);
                     let _ = cache.delete_by_hash(old_upath.id());
                 }
+                true
             }
         }
     }
 
     /// Refresh current directory while preserving selection
     fn refresh_current_directory(&mut self) {
-        if let Ok(entries) = list_directory(self.current_path.as_path(), &ListOptions::default()) {
+        // A full listing supersedes one still streaming in
+        self.pending_listing = None;
+        let (sort_by, sort_order) = self.toolbar_state.sort_mode.to_sort();
+        let sort = FolderSort { sort_by, sort_order, dirs_first: self.toolbar_state.dirs_first };
+        if let Ok(entries) = list_directory(self.current_path.as_path(), &self.list_options(sort)) {
             // Preserve selected path
             let selected_path = self.selected_index
                 .and_then(|i| self.file_entries.get(i))
                 .map(|e| e.path.clone());
 
             self.file_entries = entries;
+            self.apply_sort();
+            self.apply_catalog_filter();
+            self.restore_selection();
 
             // Restore selection
             if let Some(path) = selected_path {
@@ -3089,6 +7542,19 @@ impl ApplicationHandler for App {
         _window_id: WindowId,
         event: WindowEvent,
     ) {
+        // Key presses being recorded in the settings dialog bypass egui and the keybindings
+        if self.settings_dialog.is_capturing() {
+            if let WindowEvent::KeyboardInput { event: ref key_event, .. } = event {
+                if let Some(key) = self.input_handler.as_ref().and_then(|h| h.key_string(key_event)) {
+                    self.settings_dialog.capture_key(&key);
+                    if let Some(window) = &self.window {
+                        window.request_redraw();
+                    }
+                }
+                return;
+            }
+        }
+
         // Let egui handle the event first
         if let Some(egui_state) = &mut self.egui_state {
             if let Some(window) = &self.window {
@@ -3112,6 +7578,10 @@ impl ApplicationHandler for App {
                 if let Some(renderer) = &mut self.renderer {
                     renderer.resize((size.width, size.height));
                 }
+                // Redraw so the viewer fit follows the new size right away
+                if let Some(window) = &self.window {
+                    window.request_redraw();
+                }
             }
 
             WindowEvent::KeyboardInput { event, .. } => {
@@ -3119,6 +7589,13 @@ impl ApplicationHandler for App {
                 if event.state == ElementState::Pressed {
                     use winit::keyboard::{Key, NamedKey};
 
+                    if self.handle_type_ahead(&event) {
+                        if let Some(window) = &self.window {
+                            window.request_redraw();
+                        }
+                        return;
+                    }
+
                     // Try InputHandler first (configurable keybindings)
                     let mut handled = false;
                     if let Some(handler) = &self.input_handler {
@@ -3263,31 +7740,55 @@ impl ApplicationHandler for App {
         }
     }
 
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        // Every exit path (close button, app.exit, q) ends here
+        self.save_session();
+        // Archive members handed to other applications
+        let _ = std::fs::remove_dir_all(extract_root());
+    }
+
     fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
         // File watcher event processing
-        if let Some(ref watcher) = self.file_watcher {
+        if let Some(ref mut watcher) = self.file_watcher {
             let events = watcher.poll_events();
-            for event in events {
-                self.handle_fs_event(event);
+            if !events.is_empty() {
+                self.handle_fs_events(events);
             }
         }
 
+        self.poll_listing();
+        self.poll_flatten();
+        self.poll_decodes();
+        self.poll_wide_pages();
+        self.poll_timeline_index();
+        self.poll_search();
+        self.poll_hand_off();
+        self.poll_archive_opening();
+        self.poll_text_preview();
+        self.poll_properties_info();
+        self.check_pdf_resolution();
+        self.tick_animation();
+        self.poll_duplicate_scan();
+        self.poll_batch_convert();
+        self.poll_sidecar_export();
+        self.update_window_title();
+
+        // Keep thumbnail caches within their configured limits
+        if self.last_thumbnail_prune.elapsed() >= THUMBNAIL_PRUNE_INTERVAL {
+            self.last_thumbnail_prune = std::time::Instant::now();
+            self.prune_thumbnails();
+        }
+
         // Slideshow advancement
-        if self.slideshow.should_advance() {
-            if let Some(current) = self.selected_index {
-                let total = self.file_entries.iter().filter(|e| e.is_image()).count();
-                if let Some(next) = self.slideshow.next_index(current, total) {
-                    // Find actual index for image at position `next`
-                    let image_indices: Vec<usize> = self.file_entries.iter()
-                        .enumerate()
-                        .filter(|(_, e)| e.is_image())
-                        .map(|(i, _)| i)
-                        .collect();
-                    if let Some(&actual_idx) = image_indices.get(next) {
-                        self.on_select(actual_idx);
-                        if let Some(entry) = self.file_entries.get(actual_idx).cloned() {
-                            self.load_image(&entry);
-                        }
+        if self.slideshow.should_advance() && self.selected_index.is_some() {
+            // Slideshow positions are indices into the image-only list
+            let image_indices = self.image_indices();
+            let current = self.current_image_position(&image_indices);
+            if let Some(next) = self.slideshow.next_index(current, image_indices.len()) {
+                if let Some(&actual_idx) = image_indices.get(next) {
+                    self.on_select(actual_idx);
+                    if let Some(entry) = self.file_entries.get(actual_idx).cloned() {
+                        self.load_image(&entry);
                     }
                 }
             }
@@ -3310,6 +7811,26 @@ pub fn run() -> Result<()> {
     Ok(())
 }
 
+/// Two-page spread mode matching a reading direction
+fn spread_mode_for(direction: app_core::ReadingDirection) -> SpreadMode {
+    match direction {
+        app_core::ReadingDirection::RightToLeft => SpreadMode::SpreadRTL,
+        app_core::ReadingDirection::LeftToRight => SpreadMode::SpreadLTR,
+    }
+}
+
+/// Base scale for an image of `size` in `available` space under the given fit mode
+fn fit_scale(fit_mode: app_ui::components::viewer::FitMode, available: egui::Vec2, size: egui::Vec2) -> f32 {
+    use app_ui::components::viewer::FitMode;
+    match fit_mode {
+        // Scale to fit window (allow scale up)
+        FitMode::FitToWindow => (available.x / size.x).min(available.y / size.y),
+        FitMode::FitWidth => available.x / size.x,
+        FitMode::FitHeight => available.y / size.y,
+        FitMode::OriginalSize => 1.0,
+    }
+}
+
 /// Format file size for display
 fn format_size(bytes: u64) -> String {
     const KB: u64 = 1024;
@@ -3326,3 +7847,76 @@ fn format_size(bytes: u64) -> String {
         format!("{} B", bytes)
     }
 }
+
+/// A listing as navigation entries
+/// Folder headers (relative to `root`) between the groups of a flattened listing
+fn flatten_sections(root: &std::path::Path, entries: &[FileEntry]) -> Vec<(usize, String)> {
+    let mut sections: Vec<(usize, String)> = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let folder = entry.path.as_path().parent().unwrap_or(root);
+        let title = match folder.strip_prefix(root) {
+            Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+            Ok(rel) => rel.display().to_string(),
+            Err(_) => folder.display().to_string(),
+        };
+        if sections.last().map(|(_, t)| t != &title).unwrap_or(true) {
+            sections.push((i, title));
+        }
+    }
+    sections
+}
+
+fn nav_results(entries: &[FileEntry]) -> Vec<NavFileEntry> {
+    entries.iter()
+        .map(|e| NavFileEntry {
+            path: e.path.display().to_string(),
+            name: e.name.clone(),
+            is_dir: e.is_dir,
+            size: Some(e.size),
+            modified: e.modified,
+            thumbnail_hash: None,
+        })
+        .collect()
+}
+
+/// Path hashes of the folders containing the given files
+fn entry_folders(entries: &[FileEntry]) -> HashSet<u64> {
+    entries.iter()
+        .filter(|e| !e.is_dir)
+        .filter_map(|e| e.path.parent())
+        .map(|p| p.id())
+        .collect()
+}
+
+/// Catalog border color for a stored label value (values outside the palette are ignored)
+fn label_color32(rgb: u32) -> Option<egui::Color32> {
+    let rgb = app_core::LabelColor::from_rgb(rgb)?.rgb()?;
+    Some(egui::Color32::from_rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+}
+
+/// Short description of a set of paths for status messages: the name of a single file, or a count
+fn describe_paths(paths: &[PathBuf]) -> String {
+    match paths {
+        [path] => path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+        _ => format!("{} files", paths.len()),
+    }
+}
+
+/// Status message for a batch file operation, with the first error when some files failed
+fn file_op_summary(action: &str, paths: &[PathBuf], errors: &[String]) -> String {
+    match errors.first() {
+        None => format!("{}: {}", action, describe_paths(paths)),
+        Some(first) => format!(
+            "{} {} of {} files, {} failed ({})",
+            action, paths.len() - errors.len(), paths.len(), errors.len(), first
+        ),
+    }
+}
+
+/// Local-time [start, end] unix seconds of a "YYYY-MM" month
+fn month_bounds(month: &str) -> Option<(i64, i64)> {
+    let first = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").ok()?;
+    let next = first.checked_add_months(chrono::Months::new(1))?;
+    let to_ts = |d: NaiveDate| Local.from_local_datetime(&d.and_hms_opt(0, 0, 0)?).earliest().map(|dt| dt.timestamp());
+    Some((to_ts(first)?, to_ts(next)? - 1))
+}
@@ -0,0 +1,191 @@
+//! Image adjustments (view.adjust)
+//! Brightness, contrast, gamma, grayscale and invert applied to the displayed
+//! pixels only; the file and its thumbnails are left untouched.
+
+use egui::{Align2, Context, Window};
+
+/// Adjustment values; the default changes nothing
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImageAdjustments {
+    /// -1.0 (black) to 1.0 (white)
+    pub brightness: f32,
+    /// -1.0 (flat gray) to 1.0 (4x contrast)
+    pub contrast: f32,
+    /// 0.1 to 4.0; above 1.0 brightens midtones
+    pub gamma: f32,
+    /// Luminance only (view.grayscale)
+    pub grayscale: bool,
+    /// Per-channel inversion (view.invert)
+    pub invert: bool,
+}
+
+impl Default for ImageAdjustments {
+    fn default() -> Self {
+        Self { brightness: 0.0, contrast: 0.0, gamma: 1.0, grayscale: false, invert: false }
+    }
+}
+
+impl ImageAdjustments {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Output value for each input channel value
+    pub fn lookup_table(&self) -> [u8; 256] {
+        let contrast = (1.0 + self.contrast).powi(2);
+        let inverse_gamma = 1.0 / self.gamma.max(0.01);
+        let mut table = [0u8; 256];
+        for (i, out) in table.iter_mut().enumerate() {
+            let mut v = (i as f32 / 255.0).powf(inverse_gamma);
+            v += self.brightness;
+            v = (v - 0.5) * contrast + 0.5;
+            *out = (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+        table
+    }
+
+    /// Adjust RGBA pixels in place (alpha is kept)
+    /// The tone curve comes first, then grayscale, then inversion.
+    pub fn apply(&self, rgba: &mut [u8]) {
+        if self.is_identity() {
+            return;
+        }
+        let table = self.lookup_table();
+        for pixel in rgba.chunks_exact_mut(4) {
+            for channel in &mut pixel[..3] {
+                *channel = table[*channel as usize];
+            }
+            if self.grayscale {
+                // Rec. 601 luma
+                let luma = (299 * pixel[0] as u32 + 587 * pixel[1] as u32 + 114 * pixel[2] as u32 + 500) / 1000;
+                pixel[..3].fill(luma as u8);
+            }
+            if self.invert {
+                for channel in &mut pixel[..3] {
+                    *channel = 255 - *channel;
+                }
+            }
+        }
+    }
+}
+
+/// Overlay panel with the adjustment sliders
+#[derive(Debug, Default)]
+pub struct AdjustPanel {
+    pub open: bool,
+    pub adjustments: ImageAdjustments,
+    /// Keep the adjustments when moving to another image
+    pub locked: bool,
+    /// A slider is being dragged
+    pub dragging: bool,
+}
+
+impl AdjustPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// Called when a new image is shown
+    pub fn image_changed(&mut self) {
+        if !self.locked {
+            self.adjustments = ImageAdjustments::default();
+        }
+    }
+
+    /// Draw the panel; returns true when the adjustments changed or a slider was released
+    pub fn ui(&mut self, ctx: &Context) -> bool {
+        if !self.open {
+            let released = self.dragging;
+            self.dragging = false;
+            return released;
+        }
+
+        let before = self.adjustments;
+        let was_dragging = self.dragging;
+        let mut open = self.open;
+        let mut dragging = false;
+
+        Window::new("画像の調整")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::RIGHT_TOP, [-16.0, 48.0])
+            .show(ctx, |ui| {
+                egui::Grid::new("adjust_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("明るさ:");
+                    dragging |= ui.add(egui::Slider::new(&mut self.adjustments.brightness, -1.0..=1.0)).dragged();
+                    ui.end_row();
+
+                    ui.label("コントラスト:");
+                    dragging |= ui.add(egui::Slider::new(&mut self.adjustments.contrast, -1.0..=1.0)).dragged();
+                    ui.end_row();
+
+                    ui.label("ガンマ:");
+                    dragging |= ui.add(egui::Slider::new(&mut self.adjustments.gamma, 0.1..=4.0).logarithmic(true)).dragged();
+                    ui.end_row();
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.adjustments.grayscale, "グレースケール");
+                    ui.checkbox(&mut self.adjustments.invert, "反転");
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.locked, "調整を固定");
+                    if ui.button("リセット").clicked() {
+                        self.adjustments = ImageAdjustments::default();
+                    }
+                });
+            });
+
+        self.open = open;
+        self.dragging = dragging;
+        self.adjustments != before || (was_dragging && !dragging)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjustments() {
+        let identity = ImageAdjustments::default();
+        let table = identity.lookup_table();
+        assert!(table.iter().enumerate().all(|(i, &v)| v as usize == i));
+
+        let mut pixels = vec![100, 150, 200, 7];
+        let brighter = ImageAdjustments { brightness: 0.2, ..identity };
+        brighter.apply(&mut pixels);
+        assert_eq!(pixels, [151, 201, 251, 7]);
+
+        let flat = ImageAdjustments { contrast: -1.0, ..identity };
+        assert!(flat.lookup_table().iter().all(|&v| v == 128));
+
+        let gamma = ImageAdjustments { gamma: 2.0, ..identity };
+        assert!(gamma.lookup_table()[64] > 64);
+        assert_eq!(gamma.lookup_table()[255], 255);
+
+        // Grayscale and invert stack on top of the tone curve
+        let mut pixels = vec![255, 0, 0, 200];
+        ImageAdjustments { grayscale: true, ..identity }.apply(&mut pixels);
+        assert_eq!(pixels, [76, 76, 76, 200]);
+        ImageAdjustments { invert: true, ..identity }.apply(&mut pixels);
+        assert_eq!(pixels, [179, 179, 179, 200]);
+        let mut pixels = vec![100, 150, 200, 7];
+        ImageAdjustments { invert: true, ..brighter }.apply(&mut pixels);
+        assert_eq!(pixels, [104, 54, 4, 7]);
+
+        // Unlocked panels reset on the next image
+        let mut panel = AdjustPanel::new();
+        panel.adjustments = brighter;
+        panel.locked = true;
+        panel.image_changed();
+        assert_eq!(panel.adjustments, brighter);
+        panel.locked = false;
+        panel.image_changed();
+        assert!(panel.adjustments.is_identity());
+    }
+}
@@ -0,0 +1,235 @@
+//! Batch convert window (file.batch_convert)
+//! Settings first, then progress while the files are converted in the
+//! background, then a summary with per-file errors.
+
+use super::dialogs::{Dialog, DialogResult};
+use super::save_as::DEFAULT_SAVE_QUALITY;
+use app_core::batch_convert::output_name;
+use app_core::{ConflictPolicy, ConvertOptions, PatternError, RenameSource, SaveFormat};
+use egui::{Color32, Context, RichText, Window};
+use std::path::PathBuf;
+
+/// Longest edge offered by default (a common upload limit)
+pub const DEFAULT_MAX_DIMENSION: u32 = 2048;
+
+/// Result counts shown when the batch finishes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchConvertSummary {
+    pub written: usize,
+    pub skipped: usize,
+    /// "file name: error" per failed file
+    pub errors: Vec<String>,
+}
+
+/// Batch convert window
+pub struct BatchConvertDialog {
+    pub open: bool,
+    pub count: usize,
+    pub dest: String,
+    pub format: SaveFormat,
+    pub quality: u8,
+    pub limit_size: bool,
+    pub max_dimension: u32,
+    pub pattern: String,
+    pub keep_exif: bool,
+    pub conflict: ConflictPolicy,
+    /// First file, for the example name
+    pub first: Option<RenameSource>,
+    /// (converted, total) while running
+    pub progress: Option<(usize, usize)>,
+    pub summary: Option<BatchConvertSummary>,
+}
+
+impl BatchConvertDialog {
+    pub fn new(count: usize, dest: PathBuf, first: Option<RenameSource>, conflict: ConflictPolicy) -> Self {
+        Self {
+            open: true,
+            count,
+            dest: dest.display().to_string(),
+            format: SaveFormat::Jpeg,
+            quality: DEFAULT_SAVE_QUALITY,
+            limit_size: true,
+            max_dimension: DEFAULT_MAX_DIMENSION,
+            pattern: "{name}".to_string(),
+            keep_exif: true,
+            conflict,
+            first,
+            progress: None,
+            summary: None,
+        }
+    }
+
+    /// Output name of the first file
+    pub fn example(&self) -> Option<Result<String, PatternError>> {
+        self.first.as_ref().map(|first| output_name(&self.pattern, first, 1, self.format))
+    }
+
+    pub fn options(&self) -> ConvertOptions {
+        ConvertOptions {
+            dest: PathBuf::from(self.dest.trim()),
+            format: self.format,
+            quality: self.quality,
+            max_dimension: self.limit_size.then_some(self.max_dimension.max(1)),
+            pattern: self.pattern.clone(),
+            keep_exif: self.keep_exif && self.format.supports_exif(),
+            conflict: self.conflict,
+        }
+    }
+
+    /// Switch to the summary once the background job is done
+    pub fn finish(&mut self, summary: BatchConvertSummary) {
+        self.progress = None;
+        self.summary = Some(summary);
+    }
+
+    fn settings_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.label(format!("{} 個のファイルを変換", self.count));
+        egui::Grid::new("batch_convert_grid").num_columns(2).show(ui, |ui| {
+            ui.label("保存先:");
+            ui.add(egui::TextEdit::singleline(&mut self.dest).desired_width(320.0));
+            ui.end_row();
+
+            ui.label("形式:");
+            egui::ComboBox::from_id_salt("batch_convert_format")
+                .selected_text(self.format.label())
+                .show_ui(ui, |ui| {
+                    for option in SaveFormat::ALL {
+                        ui.selectable_value(&mut self.format, option, option.label());
+                    }
+                });
+            ui.end_row();
+
+            if self.format.is_lossy() {
+                ui.label("品質:");
+                ui.add(egui::Slider::new(&mut self.quality, 1..=100));
+                ui.end_row();
+            }
+
+            ui.checkbox(&mut self.limit_size, "最大サイズ:");
+            ui.add_enabled(
+                self.limit_size,
+                egui::DragValue::new(&mut self.max_dimension).range(16..=65535).suffix(" px"),
+            );
+            ui.end_row();
+
+            ui.label("ファイル名:");
+            ui.add(egui::TextEdit::singleline(&mut self.pattern).desired_width(200.0));
+            ui.end_row();
+
+            ui.label("");
+            ui.add_enabled(
+                self.format.supports_exif(),
+                egui::Checkbox::new(&mut self.keep_exif, "EXIF を保持"),
+            );
+            ui.end_row();
+        });
+        ui.label(RichText::new("{name}  {n} / {n:3}  {date} / {date:%Y%m%d}").weak());
+
+        let example = self.example();
+        match example {
+            Some(Ok(ref name)) => {
+                ui.label(format!("例: {}", name));
+            }
+            Some(Err(ref e)) => {
+                ui.colored_label(Color32::RED, e.to_string());
+            }
+            None => {}
+        }
+        !self.dest.trim().is_empty() && !matches!(example, Some(Err(_)))
+    }
+}
+
+impl Dialog for BatchConvertDialog {
+    type Output = ConvertOptions;
+
+    fn ui(&mut self, ctx: &Context) -> DialogResult<ConvertOptions> {
+        if !self.open {
+            return DialogResult::None;
+        }
+
+        let mut result = DialogResult::None;
+        let mut open = self.open;
+
+        Window::new("一括変換")
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(440.0)
+            .show(ctx, |ui| {
+                if let Some((done, total)) = self.progress {
+                    let fraction = if total == 0 { 1.0 } else { done as f32 / total as f32 };
+                    ui.label(format!("変換中... {}/{}", done, total));
+                    ui.add(egui::ProgressBar::new(fraction).show_percentage());
+                    if ui.button("中止").clicked() {
+                        result = DialogResult::Cancel;
+                    }
+                    return;
+                }
+
+                if let Some(ref summary) = self.summary {
+                    ui.label(format!(
+                        "{} 件を変換、{} 件をスキップ、{} 件が失敗",
+                        summary.written, summary.skipped, summary.errors.len()
+                    ));
+                    if !summary.errors.is_empty() {
+                        egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                            for error in &summary.errors {
+                                ui.colored_label(Color32::RED, error);
+                            }
+                        });
+                    }
+                    if ui.button("閉じる").clicked() {
+                        result = DialogResult::Cancel;
+                    }
+                    return;
+                }
+
+                let ready = self.settings_ui(ui);
+                ui.add_space(12.0);
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(ready, egui::Button::new("変換")).clicked() {
+                        self.progress = Some((0, self.count));
+                        result = DialogResult::Ok(self.options());
+                    }
+                    if ui.button("キャンセル").clicked() {
+                        result = DialogResult::Cancel;
+                    }
+                });
+            });
+
+        if !open {
+            result = DialogResult::Cancel;
+        }
+        if matches!(result, DialogResult::Cancel) {
+            self.open = false;
+        }
+
+        result
+    }
+
+    fn is_open(&self) -> bool { self.open }
+    fn close(&mut self) { self.open = false; }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use app_core::DateSource;
+
+    #[test]
+    fn test_example_and_options() {
+        let first = RenameSource { path: PathBuf::from("/photos/IMG_1.JPG"), date: None, date_source: DateSource::Missing };
+        let mut dialog = BatchConvertDialog::new(3, PathBuf::from("/photos/web"), Some(first), ConflictPolicy::Skip);
+        assert_eq!(dialog.example(), Some(Ok("IMG_1.jpg".to_string())));
+
+        dialog.pattern = "{date}".to_string();
+        assert_eq!(dialog.example(), Some(Err(PatternError::NoDate)));
+
+        dialog.format = SaveFormat::Bmp;
+        dialog.limit_size = false;
+        let options = dialog.options();
+        assert_eq!(options.max_dimension, None);
+        assert!(!options.keep_exif);
+        assert_eq!(options.conflict, ConflictPolicy::Skip);
+        assert_eq!(options.dest, PathBuf::from("/photos/web"));
+    }
+}
@@ -0,0 +1,215 @@
+//! Batch rename window
+//! Names come from a pattern (see `app_core::batch_rename`); the preview shows
+//! every resolved name and flags files whose date isn't from EXIF
+
+use super::dialogs::{Dialog, DialogResult};
+use app_core::batch_rename::{expand_pattern, uses_date, DateSource, PatternError, RenameSource};
+use egui::{Color32, Context, RichText, Window};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Pattern the window opens with
+pub const DEFAULT_RENAME_PATTERN: &str = "{date:%Y%m%d_%H%M%S}";
+
+/// Batch rename window
+pub struct BatchRenameDialog {
+    pub open: bool,
+    pub pattern: String,
+    /// First value of `{n}`
+    pub start: usize,
+    pub sources: Vec<RenameSource>,
+    /// Checked state, parallel to `sources`
+    pub checked: Vec<bool>,
+}
+
+impl BatchRenameDialog {
+    pub fn new(sources: Vec<RenameSource>) -> Self {
+        Self {
+            open: true,
+            pattern: DEFAULT_RENAME_PATTERN.to_string(),
+            start: 1,
+            checked: vec![true; sources.len()],
+            sources,
+        }
+    }
+
+    /// Resolved name for each file, in order
+    pub fn preview(&self) -> Vec<Result<String, PatternError>> {
+        self.sources.iter()
+            .enumerate()
+            .map(|(i, source)| expand_pattern(&self.pattern, source, self.start + i))
+            .collect()
+    }
+
+    /// Whether the name of `sources[index]` uses a date that isn't from EXIF
+    pub fn is_flagged(&self, index: usize) -> bool {
+        uses_date(&self.pattern)
+            && self.sources.get(index).is_some_and(|s| s.date_source != DateSource::Exif)
+    }
+
+    /// (path, new name) for checked files that resolve to a new, unique name
+    pub fn renames(&self) -> Vec<(PathBuf, String)> {
+        let preview = self.preview();
+        let counts = name_counts(&preview, &self.checked);
+        self.sources.iter()
+            .zip(&self.checked)
+            .zip(preview)
+            .filter(|((_, checked), _)| **checked)
+            .filter_map(|((source, _), name)| {
+                let name = name.ok()?;
+                (counts[&name] == 1 && name != source.file_name()).then(|| (source.path.clone(), name))
+            })
+            .collect()
+    }
+}
+
+/// How often each resolved name occurs among the checked files
+fn name_counts(preview: &[Result<String, PatternError>], checked: &[bool]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for (name, _) in preview.iter().zip(checked).filter(|(_, checked)| **checked) {
+        if let Ok(name) = name {
+            *counts.entry(name.clone()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+impl Dialog for BatchRenameDialog {
+    type Output = Vec<(PathBuf, String)>;
+
+    fn ui(&mut self, ctx: &Context) -> DialogResult<Vec<(PathBuf, String)>> {
+        if !self.open {
+            return DialogResult::None;
+        }
+
+        let mut result = DialogResult::None;
+        let mut open = self.open;
+
+        Window::new("一括リネーム")
+            .open(&mut open)
+            .default_size([640.0, 420.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("パターン:");
+                    ui.add(egui::TextEdit::singleline(&mut self.pattern).desired_width(280.0));
+                    ui.label("開始番号:");
+                    ui.add(egui::DragValue::new(&mut self.start));
+                });
+                ui.label(RichText::new("{name}  {n} / {n:3}  {date} / {date:%Y%m%d_%H%M%S}").weak());
+                ui.separator();
+
+                let preview = self.preview();
+                let counts = name_counts(&preview, &self.checked);
+                let flagged = (0..self.sources.len()).filter(|&i| self.is_flagged(i)).count();
+                if flagged > 0 {
+                    ui.colored_label(
+                        Color32::from_rgb(230, 160, 40),
+                        format!("⚠ {} 件は EXIF の撮影日時がありません (更新日時を使用)", flagged),
+                    );
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(ui.available_height() - 32.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("batch_rename_preview").striped(true).show(ui, |ui| {
+                            for (i, name) in preview.iter().enumerate() {
+                                let source = &self.sources[i];
+                                ui.checkbox(&mut self.checked[i], source.file_name());
+                                ui.label("→");
+                                match name {
+                                    Ok(name) if counts.get(name).is_some_and(|&c| c > 1) => {
+                                        ui.colored_label(Color32::RED, format!("{} (重複)", name));
+                                    }
+                                    Ok(name) => {
+                                        ui.label(name);
+                                    }
+                                    Err(e) => {
+                                        ui.colored_label(Color32::RED, e.to_string());
+                                    }
+                                }
+                                if self.is_flagged(i) {
+                                    let note = match source.date_source {
+                                        DateSource::Missing => "日時なし",
+                                        _ => "⚠ 更新日時",
+                                    };
+                                    ui.colored_label(Color32::from_rgb(230, 160, 40), note);
+                                } else {
+                                    ui.label("");
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    });
+
+                ui.separator();
+                let count = self.renames().len();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(count > 0, egui::Button::new(format!("{} 件をリネーム", count))).clicked() {
+                        result = DialogResult::Ok(self.renames());
+                        self.open = false;
+                    }
+                    if ui.button("キャンセル").clicked() {
+                        result = DialogResult::Cancel;
+                        self.open = false;
+                    }
+                });
+            });
+
+        if !open {
+            self.open = false;
+            result = DialogResult::Cancel;
+        }
+
+        result
+    }
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn source(name: &str, date_source: DateSource) -> RenameSource {
+        let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(9, 30, 0);
+        RenameSource {
+            path: PathBuf::from("/photos").join(name),
+            date: (date_source != DateSource::Missing).then_some(date).flatten(),
+            date_source,
+        }
+    }
+
+    #[test]
+    fn test_preview_flags_and_renames() {
+        let mut dialog = BatchRenameDialog::new(vec![
+            source("a.jpg", DateSource::Exif),
+            source("b.jpg", DateSource::Modified),
+            source("c.jpg", DateSource::Missing),
+        ]);
+        dialog.pattern = "{date:%Y%m%d}_{n:2}".to_string();
+
+        assert!(!dialog.is_flagged(0));
+        assert!(dialog.is_flagged(1));
+        assert!(dialog.is_flagged(2));
+        assert_eq!(dialog.preview()[1], Ok("20240501_02.jpg".to_string()));
+        assert_eq!(dialog.preview()[2], Err(PatternError::NoDate));
+
+        // Unresolvable names are skipped; unchecking drops the mtime fallback
+        dialog.checked[1] = false;
+        assert_eq!(dialog.renames(), vec![(PathBuf::from("/photos/a.jpg"), "20240501_01.jpg".to_string())]);
+
+        // Colliding names are never applied, and dates only flag date patterns
+        dialog.checked[1] = true;
+        dialog.pattern = "{date:%Y}".to_string();
+        assert!(dialog.renames().is_empty());
+        dialog.pattern = "{name}_{n}".to_string();
+        assert!(!dialog.is_flagged(1));
+    }
+}
@@ -0,0 +1,236 @@
+//! Command palette: run any command by fuzzy-searching its name
+//! Recently used commands are listed first
+
+use super::dialogs::{Dialog, DialogResult};
+use egui::{Align2, Context, Key, Window};
+
+/// Number of recently used commands remembered
+const RECENT_LIMIT: usize = 10;
+
+/// A command shown in the palette
+#[derive(Debug, Clone)]
+pub struct PaletteEntry {
+    /// Keybinding name identifying the command ("meta.rate:3")
+    pub binding: String,
+    pub label: String,
+    /// Current key bindings, for display ("Ctrl+f, /")
+    pub keys: String,
+}
+
+/// Command palette window
+#[derive(Default)]
+pub struct CommandPalette {
+    pub open: bool,
+    pub query: String,
+    entries: Vec<PaletteEntry>,
+    /// Bindings of recently run commands, most recent first
+    recent: Vec<String>,
+    /// Highlighted row in the filtered list
+    selected: usize,
+    focus_query: bool,
+}
+
+impl CommandPalette {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open with a fresh query; entries carry the current keybindings
+    pub fn show(&mut self, entries: Vec<PaletteEntry>) {
+        self.entries = entries;
+        self.query.clear();
+        self.selected = 0;
+        self.focus_query = true;
+        self.open = true;
+    }
+
+    /// Remember a command as recently used
+    pub fn record(&mut self, binding: &str) {
+        self.recent.retain(|b| b != binding);
+        self.recent.insert(0, binding.to_string());
+        self.recent.truncate(RECENT_LIMIT);
+    }
+
+    /// Indices of entries matching the query, best first
+    pub fn filtered(&self) -> Vec<usize> {
+        let recent_rank = |entry: &PaletteEntry| self.recent.iter().position(|b| *b == entry.binding);
+
+        let mut matches: Vec<(usize, i32)> = self.entries.iter()
+            .enumerate()
+            .filter_map(|(i, entry)| {
+                let score = if self.query.is_empty() {
+                    0
+                } else {
+                    fuzzy_score(&self.query, &entry.label)
+                        .max(fuzzy_score(&self.query, &entry.binding))?
+                };
+                // Recent commands rank above equally good matches
+                let bonus = recent_rank(entry).map_or(0, |rank| (RECENT_LIMIT - rank) as i32);
+                Some((i, score * 100 + bonus))
+            })
+            .collect();
+
+        matches.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+        matches.into_iter().map(|(i, _)| i).collect()
+    }
+}
+
+impl Dialog for CommandPalette {
+    /// Binding of the chosen command
+    type Output = String;
+
+    fn ui(&mut self, ctx: &Context) -> DialogResult<String> {
+        if !self.open {
+            return DialogResult::None;
+        }
+
+        let mut result = DialogResult::None;
+        let filtered = self.filtered();
+
+        // Keyboard navigation while the query box has focus
+        let (up, down, enter, escape) = ctx.input(|i| (
+            i.key_pressed(Key::ArrowUp),
+            i.key_pressed(Key::ArrowDown),
+            i.key_pressed(Key::Enter),
+            i.key_pressed(Key::Escape),
+        ));
+        if down {
+            self.selected = (self.selected + 1).min(filtered.len().saturating_sub(1));
+        }
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        self.selected = self.selected.min(filtered.len().saturating_sub(1));
+
+        Window::new("Command Palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .fixed_size([480.0, 360.0])
+            .anchor(Align2::CENTER_TOP, [0.0, 80.0])
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("Type a command…")
+                        .desired_width(f32::INFINITY)
+                );
+                if self.focus_query {
+                    response.request_focus();
+                    self.focus_query = false;
+                }
+                if response.changed() {
+                    self.selected = 0;
+                }
+
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .id_salt("command_palette_scroll")
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        for (row, &index) in filtered.iter().enumerate() {
+                            let entry = &self.entries[index];
+                            let is_selected = row == self.selected;
+                            let is_recent = self.query.is_empty() && self.recent.contains(&entry.binding);
+
+                            let row_response = ui.horizontal(|ui| {
+                                let label = if is_recent { format!("🕘 {}", entry.label) } else { entry.label.clone() };
+                                let clicked = ui.selectable_label(is_selected, label).clicked();
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    ui.weak(&entry.keys);
+                                });
+                                clicked
+                            });
+                            if row_response.inner {
+                                result = DialogResult::Ok(entry.binding.clone());
+                            }
+                            if is_selected && (up || down) {
+                                row_response.response.scroll_to_me(None);
+                            }
+                        }
+                        if filtered.is_empty() {
+                            ui.weak("No matching commands");
+                        }
+                    });
+            });
+
+        if enter {
+            if let Some(&index) = filtered.get(self.selected) {
+                result = DialogResult::Ok(self.entries[index].binding.clone());
+            }
+        }
+        if escape {
+            result = DialogResult::Cancel;
+        }
+        if !matches!(result, DialogResult::None) {
+            self.open = false;
+        }
+
+        result
+    }
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+    }
+}
+
+/// Fuzzy match score: query characters must appear in order (case-insensitive)
+/// Consecutive matches and matches at word starts score higher
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last_match: Option<usize> = None;
+
+    for qc in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = pos + text[pos..].iter().position(|&c| c == qc)?;
+        score += 1;
+        if last_match == Some(found.wrapping_sub(1)) {
+            score += 5;
+        }
+        if found == 0 || matches!(text[found - 1], ' ' | '.' | '_' | ':' | '-') {
+            score += 3;
+        }
+        last_match = Some(found);
+        pos = found + 1;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(binding: &str, label: &str) -> PaletteEntry {
+        PaletteEntry { binding: binding.to_string(), label: label.to_string(), keys: String::new() }
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("zin", "Zoom In").is_some());
+        assert!(fuzzy_score("niz", "Zoom In").is_none());
+        // Contiguous word-start match beats scattered letters
+        assert!(fuzzy_score("rot", "Rotate Right") > fuzzy_score("rot", "Toggle Root Folder"));
+    }
+
+    #[test]
+    fn test_recent_first() {
+        let mut palette = CommandPalette::new();
+        palette.show(vec![
+            entry("view.zoom_in", "Zoom In"),
+            entry("view.zoom_out", "Zoom Out"),
+            entry("app.search", "Search"),
+        ]);
+        palette.record("app.search");
+        assert_eq!(palette.filtered(), vec![2, 0, 1]);
+
+        palette.query = "zo".to_string();
+        palette.record("view.zoom_out");
+        assert_eq!(palette.filtered(), vec![1, 0]);
+    }
+}
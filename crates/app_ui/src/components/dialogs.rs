@@ -1,5 +1,7 @@
 //! Dialog components for file operations
 
+use app_core::GpsPosition;
+use app_fs::FileAttributes;
 use egui::{Context, Window, Align2};
 
 /// Result of dialog interaction
@@ -38,6 +40,45 @@ impl ConfirmDialog {
             dangerous: !use_trash,
         }
     }
+
+    pub fn new_delete_many(count: usize, use_trash: bool) -> Self {
+        Self {
+            message: format!("{} 個の項目を削除しますか？", count),
+            ..Self::new_delete("", use_trash)
+        }
+    }
+
+    /// Files that can't be rotated losslessly (not JPEG)
+    pub fn new_reencode(count: usize) -> Self {
+        Self {
+            title: "回転して保存".to_string(),
+            message: format!("{} 個のファイルは JPEG ではないため無劣化で回転できません。再エンコードして保存しますか？", count),
+            confirm_text: "再エンコード".to_string(),
+            ..Self::new_delete("", true)
+        }
+    }
+
+    /// Items checked in the recycle bin window
+    pub fn new_purge_trash(count: usize) -> Self {
+        Self {
+            message: format!("ゴミ箱の {} 個の項目を完全に削除しますか？", count),
+            ..Self::new_delete("", false)
+        }
+    }
+
+    /// `count` is None when the trash can't be listed on this platform
+    pub fn new_empty_trash(count: Option<usize>) -> Self {
+        let message = match count {
+            Some(count) => format!("ゴミ箱の {} 個の項目を完全に削除しますか？", count),
+            None => "ゴミ箱を空にしますか？".to_string(),
+        };
+        Self {
+            title: "ゴミ箱を空にする".to_string(),
+            message,
+            confirm_text: "空にする".to_string(),
+            ..Self::new_delete("", false)
+        }
+    }
 }
 
 impl Dialog for ConfirmDialog {
@@ -369,3 +410,280 @@ impl Dialog for TagEditDialog {
     fn is_open(&self) -> bool { self.open }
     fn close(&mut self) { self.open = false; }
 }
+
+/// Comment edit dialog
+pub struct CommentDialog {
+    pub open: bool,
+    pub file_name: String,
+    pub text: String,
+}
+
+impl CommentDialog {
+    pub fn new(file_name: &str, comment: Option<String>) -> Self {
+        Self {
+            open: true,
+            file_name: file_name.to_string(),
+            text: comment.unwrap_or_default(),
+        }
+    }
+}
+
+impl Dialog for CommentDialog {
+    type Output = String;
+
+    fn ui(&mut self, ctx: &Context) -> DialogResult<String> {
+        if !self.open {
+            return DialogResult::None;
+        }
+
+        let mut result = DialogResult::None;
+
+        Window::new("コメント編集")
+            .collapsible(false)
+            .resizable(true)
+            .default_size([400.0, 240.0])
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(&self.file_name);
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.text)
+                        .desired_rows(6)
+                        .desired_width(f32::INFINITY)
+                );
+
+                ui.add_space(8.0);
+
+                ui.horizontal(|ui| {
+                    if ui.button("OK").clicked() {
+                        result = DialogResult::Ok(self.text.trim().to_string());
+                        self.open = false;
+                    }
+                    if ui.button("キャンセル").clicked() {
+                        result = DialogResult::Cancel;
+                        self.open = false;
+                    }
+                });
+            });
+
+        result
+    }
+
+    fn is_open(&self) -> bool { self.open }
+    fn close(&mut self) { self.open = false; }
+}
+
+/// What to do with the marked file list
+pub enum ExportMarksAction {
+    /// Write the list to a text file
+    Save(String),
+    /// Copy the list to the clipboard
+    CopyToClipboard,
+}
+
+/// Marked file list export dialog
+pub struct ExportMarksDialog {
+    pub open: bool,
+    pub path: String,
+    pub count: usize,
+}
+
+impl ExportMarksDialog {
+    pub fn new(default_path: &str, count: usize) -> Self {
+        Self {
+            open: true,
+            path: default_path.to_string(),
+            count,
+        }
+    }
+}
+
+impl Dialog for ExportMarksDialog {
+    type Output = ExportMarksAction;
+
+    fn ui(&mut self, ctx: &Context) -> DialogResult<ExportMarksAction> {
+        if !self.open {
+            return DialogResult::None;
+        }
+
+        let mut result = DialogResult::None;
+
+        Window::new("マーク一覧の書き出し")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("{} 個のマーク済みファイル", self.count));
+                ui.horizontal(|ui| {
+                    ui.label("保存先:");
+                    ui.add(egui::TextEdit::singleline(&mut self.path).desired_width(320.0));
+                });
+
+                ui.add_space(16.0);
+
+                ui.horizontal(|ui| {
+                    if ui.button("保存").clicked() && !self.path.trim().is_empty() {
+                        result = DialogResult::Ok(ExportMarksAction::Save(self.path.trim().to_string()));
+                        self.open = false;
+                    }
+                    if ui.button("クリップボードにコピー").clicked() {
+                        result = DialogResult::Ok(ExportMarksAction::CopyToClipboard);
+                        self.open = false;
+                    }
+                    if ui.button("キャンセル").clicked() {
+                        result = DialogResult::Cancel;
+                        self.open = false;
+                    }
+                });
+            });
+
+        result
+    }
+
+    fn is_open(&self) -> bool { self.open }
+    fn close(&mut self) { self.open = false; }
+}
+
+/// Requests from the properties window
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertiesAction {
+    /// Map URL to open in the browser
+    OpenMap(String),
+    /// Apply the edited attributes to the file
+    SetAttributes(FileAttributes),
+}
+
+/// File properties window
+pub struct PropertiesDialog {
+    pub open: bool,
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    pub is_dir: bool,
+    pub dimensions: Option<(u32, u32)>,
+    /// Geotag from EXIF; the row is omitted when absent
+    pub gps: Option<GpsPosition>,
+    /// Attributes of a file on disk; the row is omitted for archive members
+    pub attributes: Option<FileAttributes>,
+    /// Last attribute change error
+    pub error: Option<String>,
+}
+
+impl PropertiesDialog {
+    pub fn new(name: &str, path: &str, size: u64, is_dir: bool) -> Self {
+        Self {
+            open: true,
+            name: name.to_string(),
+            path: path.to_string(),
+            size,
+            is_dir,
+            dimensions: None,
+            gps: None,
+            attributes: None,
+            error: None,
+        }
+    }
+
+    pub fn with_dimensions(mut self, dimensions: Option<(u32, u32)>) -> Self {
+        self.dimensions = dimensions;
+        self
+    }
+
+    pub fn with_gps(mut self, gps: Option<GpsPosition>) -> Self {
+        self.gps = gps;
+        self
+    }
+
+    pub fn with_attributes(mut self, attributes: Option<FileAttributes>) -> Self {
+        self.attributes = attributes;
+        self
+    }
+}
+
+impl Dialog for PropertiesDialog {
+    type Output = PropertiesAction;
+
+    fn ui(&mut self, ctx: &Context) -> DialogResult<PropertiesAction> {
+        if !self.open {
+            return DialogResult::None;
+        }
+
+        let mut result = DialogResult::None;
+
+        Window::new("プロパティ")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                egui::Grid::new("properties_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("名前:");
+                    ui.label(&self.name);
+                    ui.end_row();
+
+                    ui.label("場所:");
+                    ui.label(&self.path);
+                    ui.end_row();
+
+                    ui.label("種類:");
+                    ui.label(if self.is_dir { "フォルダー" } else { "ファイル" });
+                    ui.end_row();
+
+                    if !self.is_dir {
+                        ui.label("サイズ:");
+                        ui.label(format!("{} bytes", self.size));
+                        ui.end_row();
+                    }
+
+                    if let Some((w, h)) = self.dimensions {
+                        ui.label("解像度:");
+                        ui.label(format!("{} x {}", w, h));
+                        ui.end_row();
+                    }
+
+                    if let Some(gps) = self.gps {
+                        ui.label("位置情報:");
+                        ui.horizontal(|ui| {
+                            ui.label(gps.to_string());
+                            if ui.link("Open in map").clicked() {
+                                result = DialogResult::Ok(PropertiesAction::OpenMap(gps.map_url()));
+                            }
+                        });
+                        ui.end_row();
+                    }
+
+                    if let Some(current) = self.attributes {
+                        let mut edited = current;
+                        ui.label("属性:");
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut edited.readonly, "読み取り専用");
+                            // Unix hides by a leading dot in the name
+                            ui.add_enabled(cfg!(windows), egui::Checkbox::new(&mut edited.hidden, "隠しファイル"));
+                            if cfg!(windows) {
+                                ui.checkbox(&mut edited.system, "システム");
+                                ui.checkbox(&mut edited.archive, "アーカイブ");
+                            }
+                        });
+                        ui.end_row();
+                        if edited != current {
+                            result = DialogResult::Ok(PropertiesAction::SetAttributes(edited));
+                        }
+                    }
+                });
+
+                if let Some(ref error) = self.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+
+                ui.add_space(8.0);
+
+                if ui.button("閉じる").clicked() {
+                    result = DialogResult::Cancel;
+                    self.open = false;
+                }
+            });
+
+        result
+    }
+
+    fn is_open(&self) -> bool { self.open }
+    fn close(&mut self) { self.open = false; }
+}
@@ -0,0 +1,156 @@
+//! Duplicate review window
+//! Lists groups of visually similar images; checked files are deleted on confirm
+
+use super::dialogs::{Dialog, DialogResult};
+use egui::{Context, Window};
+use std::path::PathBuf;
+
+/// A file in a duplicate group
+#[derive(Debug, Clone)]
+pub struct DuplicateFile {
+    pub path: PathBuf,
+    pub name: String,
+    pub size: u64,
+    /// Marked for deletion
+    pub marked: bool,
+}
+
+/// Duplicate finder window: progress while hashing, then group review
+pub struct DuplicateReview {
+    pub open: bool,
+    /// (hashed, total) while the scan runs
+    pub progress: Option<(usize, usize)>,
+    pub groups: Vec<Vec<DuplicateFile>>,
+}
+
+impl DuplicateReview {
+    /// Start in the scanning state
+    pub fn new(total: usize) -> Self {
+        Self {
+            open: true,
+            progress: Some((0, total)),
+            groups: Vec::new(),
+        }
+    }
+
+    /// Finish scanning; keeps the largest file of each group and marks the rest
+    pub fn set_groups(&mut self, groups: Vec<Vec<DuplicateFile>>) {
+        self.progress = None;
+        self.groups = groups;
+        for group in &mut self.groups {
+            let keep = group.iter()
+                .enumerate()
+                .max_by_key(|(_, f)| f.size)
+                .map(|(i, _)| i);
+            for (i, file) in group.iter_mut().enumerate() {
+                file.marked = Some(i) != keep;
+            }
+        }
+    }
+
+    /// Paths currently marked for deletion
+    pub fn marked_paths(&self) -> Vec<PathBuf> {
+        self.groups.iter()
+            .flatten()
+            .filter(|f| f.marked)
+            .map(|f| f.path.clone())
+            .collect()
+    }
+
+    /// Drop deleted files; groups left with a single file are removed
+    pub fn remove_paths(&mut self, paths: &[PathBuf]) {
+        for group in &mut self.groups {
+            group.retain(|f| !paths.contains(&f.path));
+        }
+        self.groups.retain(|g| g.len() > 1);
+    }
+}
+
+impl Dialog for DuplicateReview {
+    type Output = Vec<PathBuf>;
+
+    fn ui(&mut self, ctx: &Context) -> DialogResult<Vec<PathBuf>> {
+        if !self.open {
+            return DialogResult::None;
+        }
+
+        let mut result = DialogResult::None;
+        let mut open = self.open;
+
+        Window::new("Find Duplicates")
+            .open(&mut open)
+            .default_size([480.0, 400.0])
+            .show(ctx, |ui| {
+                if let Some((done, total)) = self.progress {
+                    let fraction = if total == 0 { 1.0 } else { done as f32 / total as f32 };
+                    ui.label(format!("Hashing images... {}/{}", done, total));
+                    ui.add(egui::ProgressBar::new(fraction).show_percentage());
+                    return;
+                }
+
+                if self.groups.is_empty() {
+                    ui.label("No duplicates found");
+                    return;
+                }
+
+                ui.label(format!("{} groups of similar images", self.groups.len()));
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(ui.available_height() - 32.0)
+                    .show(ui, |ui| {
+                        for (i, group) in self.groups.iter_mut().enumerate() {
+                            ui.strong(format!("Group {}", i + 1));
+                            for file in group.iter_mut() {
+                                ui.checkbox(&mut file.marked, format!("{} ({} KB)", file.name, file.size / 1024))
+                                    .on_hover_text(file.path.to_string_lossy());
+                            }
+                            ui.add_space(4.0);
+                        }
+                    });
+
+                ui.separator();
+                let marked = self.marked_paths();
+                let delete = egui::Button::new(
+                    egui::RichText::new(format!("Delete {} marked", marked.len())).color(egui::Color32::RED),
+                );
+                if ui.add_enabled(!marked.is_empty(), delete).clicked() {
+                    result = DialogResult::Ok(marked);
+                }
+            });
+
+        if !open {
+            self.open = false;
+            result = DialogResult::Cancel;
+        }
+
+        result
+    }
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, size: u64) -> DuplicateFile {
+        DuplicateFile { path: PathBuf::from(name), name: name.to_string(), size, marked: false }
+    }
+
+    #[test]
+    fn test_keeps_largest() {
+        let mut review = DuplicateReview::new(3);
+        review.set_groups(vec![vec![file("a.jpg", 10), file("b.jpg", 30), file("c.jpg", 20)]]);
+        assert_eq!(review.marked_paths(), vec![PathBuf::from("a.jpg"), PathBuf::from("c.jpg")]);
+
+        review.remove_paths(&[PathBuf::from("a.jpg"), PathBuf::from("c.jpg")]);
+        assert!(review.groups.is_empty());
+    }
+}
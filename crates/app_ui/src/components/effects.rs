@@ -283,74 +283,29 @@ impl PageTransition {
         1.0 - (1.0 - t).powi(3)
     }
 
-    pub fn render(&self, ui: &mut egui::Ui, rect: egui::Rect) {
+    /// Offset and tint of the outgoing image at this point of the transition,
+    /// for an image drawn `size` large
+    fn outgoing(&self, size: egui::Vec2) -> Option<(egui::Vec2, egui::Color32)> {
         if !self.is_active() {
-            return;
+            return None;
         }
 
         let t = Self::ease_out(self.progress());
+        let offset = 1.0 - t;
+        Some(match self.transition_type {
+            TransitionType::Fade => (egui::Vec2::ZERO, egui::Color32::from_white_alpha(((1.0 - t) * 255.0) as u8)),
+            TransitionType::SlideLeft => (egui::vec2(-size.x * offset, 0.0), egui::Color32::WHITE),
+            TransitionType::SlideRight => (egui::vec2(size.x * offset, 0.0), egui::Color32::WHITE),
+            TransitionType::SlideUp => (egui::vec2(0.0, -size.y * offset), egui::Color32::WHITE),
+            TransitionType::SlideDown => (egui::vec2(0.0, size.y * offset), egui::Color32::WHITE),
+            TransitionType::None => return None,
+        })
+    }
 
-        match self.transition_type {
-            TransitionType::Fade => {
-                if let Some(from) = self.from_texture {
-                    let alpha = ((1.0 - t) * 255.0) as u8;
-                    ui.painter().image(
-                        from,
-                        rect,
-                        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
-                        egui::Color32::from_rgba_unmultiplied(255, 255, 255, alpha),
-                    );
-                }
-            }
-            TransitionType::SlideLeft => {
-                let offset = rect.width() * (1.0 - t);
-                if let Some(from) = self.from_texture {
-                    let from_rect = rect.translate(egui::vec2(-offset, 0.0));
-                    ui.painter().image(
-                        from,
-                        from_rect,
-                        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
-                        egui::Color32::WHITE,
-                    );
-                }
-            }
-            TransitionType::SlideRight => {
-                let offset = rect.width() * (1.0 - t);
-                if let Some(from) = self.from_texture {
-                    let from_rect = rect.translate(egui::vec2(offset, 0.0));
-                    ui.painter().image(
-                        from,
-                        from_rect,
-                        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
-                        egui::Color32::WHITE,
-                    );
-                }
-            }
-            TransitionType::SlideUp => {
-                let offset = rect.height() * (1.0 - t);
-                if let Some(from) = self.from_texture {
-                    let from_rect = rect.translate(egui::vec2(0.0, -offset));
-                    ui.painter().image(
-                        from,
-                        from_rect,
-                        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
-                        egui::Color32::WHITE,
-                    );
-                }
-            }
-            TransitionType::SlideDown => {
-                let offset = rect.height() * (1.0 - t);
-                if let Some(from) = self.from_texture {
-                    let from_rect = rect.translate(egui::vec2(0.0, offset));
-                    ui.painter().image(
-                        from,
-                        from_rect,
-                        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
-                        egui::Color32::WHITE,
-                    );
-                }
-            }
-            TransitionType::None => {}
+    /// Draw the outgoing image where it was shown (`rect`, with texture coordinates `uvs`)
+    pub fn render(&self, painter: &egui::Painter, rect: egui::Rect, uvs: [egui::Pos2; 4]) {
+        if let (Some(from), Some((offset, tint))) = (self.from_texture, self.outgoing(rect.size())) {
+            super::viewer::paint_image_tinted(painter, from, rect.translate(offset), uvs, tint);
         }
     }
 
@@ -387,6 +342,25 @@ impl PageTransition {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_transition_outgoing() {
+        let mut transition = PageTransition::new();
+        transition.transition_type = TransitionType::Fade;
+        assert!(transition.outgoing(egui::vec2(100.0, 50.0)).is_none());
+
+        transition.duration = Duration::from_secs(60);
+        transition.start(Some(egui::TextureId::default()), None);
+        let (offset, tint) = transition.outgoing(egui::vec2(100.0, 50.0)).unwrap();
+        assert_eq!(offset, egui::Vec2::ZERO);
+        assert!(tint.a() > 250);
+
+        transition.transition_type = TransitionType::SlideDown;
+        let (offset, tint) = transition.outgoing(egui::vec2(100.0, 50.0)).unwrap();
+        assert_eq!(offset.x, 0.0);
+        assert!(offset.y > 49.0);
+        assert_eq!(tint, egui::Color32::WHITE);
+    }
+
     #[test]
     fn test_rotate_cw() {
         let mut transform = ImageTransform::new();
@@ -425,4 +399,20 @@ mod tests {
         bg.cycle();
         assert!(matches!(bg.color, BackgroundColor::Gray(_)));
     }
+
+    #[test]
+    fn test_transition_start() {
+        let mut transition = PageTransition::new();
+        transition.start(Some(egui::TextureId::Managed(1)), Some(egui::TextureId::Managed(2)));
+        assert!(!transition.is_active());
+
+        transition.transition_type = TransitionType::Fade;
+        transition.duration = Duration::from_secs(60);
+        transition.start(Some(egui::TextureId::Managed(1)), Some(egui::TextureId::Managed(2)));
+        assert!(transition.is_active());
+        assert!(transition.progress() < 1.0);
+
+        transition.clear();
+        assert!(!transition.is_active());
+    }
 }
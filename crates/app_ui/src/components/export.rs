@@ -0,0 +1,261 @@
+//! Export window (file.export)
+//! Resizes the viewed image to given dimensions or a percentage, then saves
+//! it with the same options as save-as.
+
+use super::dialogs::{Dialog, DialogResult};
+use super::save_as::{SaveAsDialog, SaveAsRequest};
+use app_core::{estimate_encoded_size, resize_image, Interpolation, SaveFormat};
+use egui::{Align2, Context, RichText, Window};
+use image::RgbaImage;
+use std::path::Path;
+
+/// Long edge of the downscaled copy used for file size estimates
+const SAMPLE_SIZE: u32 = 256;
+
+/// Save options plus the output size
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportRequest {
+    pub save: SaveAsRequest,
+    pub width: u32,
+    pub height: u32,
+    pub interpolation: Interpolation,
+}
+
+/// Inputs the size estimate was computed for
+type EstimateKey = (u32, u32, SaveFormat, u8);
+
+/// Export window
+pub struct ExportDialog {
+    pub open: bool,
+    /// Path, format, quality and EXIF options
+    pub save: SaveAsDialog,
+    pub source_size: (u32, u32),
+    pub width: u32,
+    pub height: u32,
+    pub percent: f32,
+    /// Size by percentage instead of pixels
+    pub by_percent: bool,
+    pub keep_aspect: bool,
+    pub interpolation: Interpolation,
+    sample: RgbaImage,
+    estimate: Option<(EstimateKey, Option<u64>)>,
+}
+
+impl ExportDialog {
+    /// `pixels` is the image as it will be exported, before resizing
+    pub fn new(source: &Path, has_exif: bool, pixels: &RgbaImage, interpolation: Interpolation) -> Self {
+        let (width, height) = pixels.dimensions();
+        let scale = (SAMPLE_SIZE as f32 / width.max(height).max(1) as f32).min(1.0);
+        let sample = resize_image(
+            pixels,
+            (width as f32 * scale).round() as u32,
+            (height as f32 * scale).round() as u32,
+            Interpolation::Bilinear,
+        );
+        Self {
+            open: true,
+            save: SaveAsDialog::new(source, has_exif),
+            source_size: (width, height),
+            width,
+            height,
+            percent: 100.0,
+            by_percent: false,
+            keep_aspect: true,
+            interpolation,
+            sample,
+            estimate: None,
+        }
+    }
+
+    /// Offer to apply the viewer's adjustments (on by default)
+    pub fn with_adjustments(mut self, has_adjustments: bool) -> Self {
+        self.save = self.save.with_adjustments(has_adjustments);
+        self
+    }
+
+    /// Set the width, adjusting the height when the aspect ratio is locked
+    pub fn set_width(&mut self, width: u32) {
+        self.width = width.max(1);
+        if self.keep_aspect {
+            let (w, h) = self.source_size;
+            self.height = scaled(self.width, h, w);
+        }
+    }
+
+    /// Set the height, adjusting the width when the aspect ratio is locked
+    pub fn set_height(&mut self, height: u32) {
+        self.height = height.max(1);
+        if self.keep_aspect {
+            let (w, h) = self.source_size;
+            self.width = scaled(self.height, w, h);
+        }
+    }
+
+    /// Size both dimensions to `percent` of the source
+    pub fn set_percent(&mut self, percent: f32) {
+        self.percent = percent.max(1.0);
+        let (w, h) = self.source_size;
+        self.width = ((w as f32 * self.percent / 100.0).round() as u32).max(1);
+        self.height = ((h as f32 * self.percent / 100.0).round() as u32).max(1);
+    }
+
+    /// Estimated output size in bytes (JPEG and WebP only)
+    pub fn estimated_size(&mut self) -> Option<u64> {
+        let format = self.save.format;
+        if !matches!(format, SaveFormat::Jpeg | SaveFormat::WebP) {
+            return None;
+        }
+        let key = (self.width, self.height, format, self.save.quality);
+        match self.estimate {
+            Some((cached, estimate)) if cached == key => estimate,
+            _ => {
+                let estimate = estimate_encoded_size(&self.sample, self.width, self.height, format, self.save.quality);
+                self.estimate = Some((key, estimate));
+                estimate
+            }
+        }
+    }
+
+    pub fn request(&self) -> ExportRequest {
+        ExportRequest {
+            save: self.save.request(),
+            width: self.width,
+            height: self.height,
+            interpolation: self.interpolation,
+        }
+    }
+}
+
+/// `value * numerator / denominator`, rounded, at least 1
+fn scaled(value: u32, numerator: u32, denominator: u32) -> u32 {
+    ((value as u64 * numerator as u64 + denominator as u64 / 2) / denominator.max(1) as u64).max(1) as u32
+}
+
+/// Byte count as KB/MB
+fn format_size(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{} KB", bytes.div_ceil(1024))
+    }
+}
+
+impl Dialog for ExportDialog {
+    type Output = ExportRequest;
+
+    fn ui(&mut self, ctx: &Context) -> DialogResult<ExportRequest> {
+        if !self.open {
+            return DialogResult::None;
+        }
+
+        let mut result = DialogResult::None;
+
+        Window::new("エクスポート")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("元のサイズ: {} x {}", self.source_size.0, self.source_size.1));
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.by_percent, false, "ピクセル");
+                    ui.radio_value(&mut self.by_percent, true, "パーセント");
+                });
+
+                egui::Grid::new("export_size_grid").num_columns(2).show(ui, |ui| {
+                    if self.by_percent {
+                        ui.label("倍率:");
+                        let mut percent = self.percent;
+                        if ui.add(egui::Slider::new(&mut percent, 1.0..=400.0).suffix("%")).changed() {
+                            self.set_percent(percent);
+                        }
+                        ui.end_row();
+                    } else {
+                        ui.label("幅:");
+                        let mut width = self.width;
+                        if ui.add(egui::DragValue::new(&mut width).range(1..=65535).suffix(" px")).changed() {
+                            self.set_width(width);
+                        }
+                        ui.end_row();
+
+                        ui.label("高さ:");
+                        let mut height = self.height;
+                        if ui.add(egui::DragValue::new(&mut height).range(1..=65535).suffix(" px")).changed() {
+                            self.set_height(height);
+                        }
+                        ui.end_row();
+
+                        ui.label("");
+                        if ui.checkbox(&mut self.keep_aspect, "縦横比を固定").changed() && self.keep_aspect {
+                            let width = self.width;
+                            self.set_width(width);
+                        }
+                        ui.end_row();
+                    }
+
+                    ui.label("補間:");
+                    egui::ComboBox::from_id_salt("export_interpolation")
+                        .selected_text(format!("{:?}", self.interpolation))
+                        .show_ui(ui, |ui| {
+                            for option in [Interpolation::Nearest, Interpolation::Bilinear, Interpolation::Lanczos3] {
+                                ui.selectable_value(&mut self.interpolation, option, format!("{:?}", option));
+                            }
+                        });
+                    ui.end_row();
+                });
+
+                ui.label(format!("出力サイズ: {} x {}", self.width, self.height));
+                ui.separator();
+                self.save.options_ui(ui);
+
+                if let Some(bytes) = self.estimated_size() {
+                    ui.label(RichText::new(format!("推定ファイルサイズ: 約 {}", format_size(bytes))).weak());
+                }
+
+                ui.add_space(16.0);
+
+                ui.horizontal(|ui| {
+                    if ui.button("エクスポート").clicked() && !self.save.path.trim().is_empty() {
+                        result = DialogResult::Ok(self.request());
+                        self.open = false;
+                    }
+                    if ui.button("キャンセル").clicked() {
+                        result = DialogResult::Cancel;
+                        self.open = false;
+                    }
+                });
+            });
+
+        result
+    }
+
+    fn is_open(&self) -> bool { self.open }
+    fn close(&mut self) { self.open = false; }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_controls() {
+        let pixels = RgbaImage::from_pixel(400, 300, image::Rgba([90, 120, 200, 255]));
+        let mut dialog = ExportDialog::new(Path::new("/photos/a.jpg"), false, &pixels, Interpolation::Lanczos3);
+        assert_eq!(dialog.sample.dimensions(), (256, 192));
+
+        dialog.set_width(200);
+        assert_eq!((dialog.width, dialog.height), (200, 150));
+        dialog.set_percent(25.0);
+        assert_eq!((dialog.width, dialog.height), (100, 75));
+
+        dialog.keep_aspect = false;
+        dialog.set_height(40);
+        assert_eq!((dialog.width, dialog.height), (100, 40));
+
+        // Estimates for lossy formats only, recomputed when the quality changes
+        let estimate = dialog.estimated_size().unwrap();
+        dialog.save.quality = 20;
+        assert!(dialog.estimated_size().unwrap() <= estimate);
+        dialog.save.set_format(SaveFormat::Png);
+        assert_eq!(dialog.estimated_size(), None);
+    }
+}
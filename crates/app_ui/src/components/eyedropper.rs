@@ -0,0 +1,100 @@
+//! Eyedropper (view.toggle_eyedropper)
+//! Reads the full-resolution pixel under the cursor; clicking copies its hex value.
+
+use egui::{Color32, FontId, Painter, Pos2, Rect, Stroke, Vec2};
+use image::RgbaImage;
+
+/// A pixel read from the decoded image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorSample {
+    pub x: u32,
+    pub y: u32,
+    pub rgba: [u8; 4],
+}
+
+impl ColorSample {
+    pub fn from_image(pixels: &RgbaImage, x: u32, y: u32) -> Option<Self> {
+        let pixel = pixels.get_pixel_checked(x, y)?;
+        Some(Self { x, y, rgba: pixel.0 })
+    }
+
+    /// "#RRGGBB", with an alpha byte appended when not opaque
+    pub fn hex(&self) -> String {
+        let [r, g, b, a] = self.rgba;
+        if a == 255 {
+            format!("#{:02X}{:02X}{:02X}", r, g, b)
+        } else {
+            format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+        }
+    }
+
+    pub fn label(&self) -> String {
+        let [r, g, b, a] = self.rgba;
+        format!("({}, {})  R {} G {} B {} A {}  {}", self.x, self.y, r, g, b, a, self.hex())
+    }
+}
+
+/// Eyedropper mode and the last sample
+#[derive(Debug, Default)]
+pub struct Eyedropper {
+    pub active: bool,
+    pub sample: Option<ColorSample>,
+}
+
+impl Eyedropper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn toggle(&mut self) {
+        self.active = !self.active;
+        self.sample = None;
+    }
+
+    /// Swatch and values next to the cursor
+    pub fn render(&self, painter: &Painter, cursor: Pos2) {
+        let Some(sample) = self.sample else {
+            return;
+        };
+        let [r, g, b, a] = sample.rgba;
+        let text = painter.layout_no_wrap(sample.label(), FontId::monospace(13.0), Color32::WHITE);
+        let swatch = 16.0;
+        let size = Vec2::new(swatch + 8.0 + text.size().x, swatch.max(text.size().y)) + Vec2::splat(12.0);
+
+        // Below-right of the cursor, flipped to stay on screen
+        let clip = painter.clip_rect();
+        let mut min = cursor + Vec2::new(16.0, 16.0);
+        if min.x + size.x > clip.right() {
+            min.x = cursor.x - 16.0 - size.x;
+        }
+        if min.y + size.y > clip.bottom() {
+            min.y = cursor.y - 16.0 - size.y;
+        }
+        let frame = Rect::from_min_size(min, size);
+        painter.rect_filled(frame, 4.0, Color32::from_black_alpha(200));
+
+        let swatch_rect = Rect::from_min_size(frame.min + Vec2::splat(6.0), Vec2::splat(swatch));
+        painter.rect_filled(swatch_rect, 2.0, Color32::from_rgba_unmultiplied(r, g, b, a));
+        painter.rect_stroke(swatch_rect, 2.0, Stroke::new(1.0, Color32::GRAY));
+        let text_pos = Pos2::new(swatch_rect.right() + 8.0, swatch_rect.center().y - text.size().y / 2.0);
+        painter.galley(text_pos, text, Color32::WHITE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_sample() {
+        let mut pixels = RgbaImage::new(4, 3);
+        pixels.put_pixel(2, 1, image::Rgba([255, 128, 0, 255]));
+        pixels.put_pixel(3, 2, image::Rgba([1, 2, 3, 64]));
+
+        let sample = ColorSample::from_image(&pixels, 2, 1).unwrap();
+        assert_eq!(sample.hex(), "#FF8000");
+        assert_eq!(sample.label(), "(2, 1)  R 255 G 128 B 0 A 255  #FF8000");
+        assert_eq!(ColorSample::from_image(&pixels, 3, 2).unwrap().hex(), "#01020340");
+        assert_eq!(ColorSample::from_image(&pixels, 4, 0), None);
+    }
+}
@@ -1,6 +1,7 @@
 //! File browser component (grid/list view)
 
 use egui::{Ui, Vec2, Response};
+use std::path::PathBuf;
 
 /// File browser component
 pub struct FileBrowser {
@@ -236,7 +237,18 @@ impl FileBrowser {
                     .wrap_mode(egui::TextWrapMode::Truncate)
             );
 
-            response
+            // Link indicator in the bottom-right corner
+            if item.link_target.is_some() {
+                ui.painter().text(
+                    rect.right_bottom() + Vec2::new(-2.0, -2.0),
+                    egui::Align2::RIGHT_BOTTOM,
+                    "↪",
+                    egui::FontId::proportional(14.0),
+                    egui::Color32::from_rgb(120, 200, 255),
+                );
+            }
+
+            item.link_hover(response)
         }).inner
     }
 
@@ -320,9 +332,9 @@ impl FileBrowser {
                 ui.label(icon);
 
                 // Name
-                let response = ui.selectable_label(is_selected, &item.name);
+                let response = ui.selectable_label(is_selected, item.display_name());
 
-                response
+                item.link_hover(response)
             }).inner;
 
             if response.clicked() {
@@ -419,7 +431,7 @@ impl FileBrowser {
                 for (idx, item) in items.iter().enumerate() {
                     let is_selected = self.selected == Some(idx);
 
-                    let response = ui.selectable_label(is_selected, &item.name);
+                    let response = item.link_hover(ui.selectable_label(is_selected, item.display_name()));
                     ui.label(format_size(item.size));
                     ui.label(format_date(item.modified));
                     ui.label(if item.is_dir { "Folder" } else { &item.extension });
@@ -456,6 +468,26 @@ pub struct FileItem {
     pub modified: Option<i64>,
     pub extension: String,
     pub thumbnail: Option<egui::TextureId>,
+    /// Target of a symbolic link or junction (shows an indicator)
+    pub link_target: Option<PathBuf>,
+}
+
+impl FileItem {
+    /// Name with the link indicator for links
+    fn display_name(&self) -> String {
+        match self.link_target {
+            Some(_) => format!("{} ↪", self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Show the link target on hover
+    fn link_hover(&self, response: Response) -> Response {
+        match self.link_target {
+            Some(ref target) => response.on_hover_text(format!("→ {}", target.display())),
+            None => response,
+        }
+    }
 }
 
 /// Browser action
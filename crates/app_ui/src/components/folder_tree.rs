@@ -1,9 +1,12 @@
 //! Folder tree component for left panel
 //! Displays only folders in a hierarchical tree structure
 
+use app_fs::{FileWatcher, FsEvent};
 use egui::{Ui, Response, Vec2};
 use std::path::{Path, PathBuf};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::SystemTime;
 
 /// Action returned from folder tree interaction
 #[derive(Debug, Clone)]
@@ -14,6 +17,34 @@ pub enum FolderTreeAction {
     ToggleExpand(PathBuf),
     /// User wants to go to parent
     GoToParent,
+    /// Remove a bookmark
+    RemoveBookmark(i64),
+    /// Move a bookmark up (-1) or down (+1) in the list
+    MoveBookmark(i64, i32),
+    /// Open the recycle bin
+    OpenTrash,
+}
+
+/// A bookmarked (favorite) folder
+#[derive(Debug, Clone)]
+pub struct BookmarkEntry {
+    pub bookmark_id: i64,
+    pub path: PathBuf,
+    pub name: String,
+    /// False if the folder no longer exists
+    pub exists: bool,
+}
+
+impl BookmarkEntry {
+    pub fn new(bookmark_id: i64, path: PathBuf, name: Option<String>) -> Self {
+        let name = name.unwrap_or_else(|| {
+            path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string_lossy().to_string())
+        });
+        let exists = path.is_dir();
+        Self { bookmark_id, path, name, exists }
+    }
 }
 
 /// A node in the folder tree
@@ -23,31 +54,122 @@ pub struct FolderNode {
     pub name: String,
     pub has_children: bool,
     pub depth: usize,
+    /// Why the folder's children could not be listed (shown as an error node)
+    pub error: Option<String>,
+    /// Placeholder shown while the parent's children are scanned
+    pub loading: bool,
 }
 
 impl FolderNode {
     pub fn new(path: PathBuf, depth: usize) -> Self {
+        let has_children = has_subfolders(&path);
+        Self::with_children(path, depth, has_children)
+    }
+
+    /// Node whose subfolder check has already been done
+    pub fn with_children(path: PathBuf, depth: usize, has_children: bool) -> Self {
         let name = path
             .file_name()
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_else(|| path.to_string_lossy().to_string());
 
-        // Check if has subdirectories
-        let has_children = std::fs::read_dir(&path)
-            .map(|entries| {
-                entries
-                    .filter_map(|e| e.ok())
-                    .any(|e| e.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
-            })
-            .unwrap_or(false);
-
         Self {
             path,
             name,
             has_children,
             depth,
+            error: None,
+            loading: false,
+        }
+    }
+
+    /// Top-level node for a drive or filesystem root
+    /// Not probed for subfolders, so slow or missing drives don't block
+    pub fn root(path: PathBuf) -> Self {
+        Self {
+            name: path.to_string_lossy().to_string(),
+            path,
+            has_children: true,
+            depth: 0,
+            error: None,
+            loading: false,
         }
     }
+
+    /// Placeholder under a folder whose children could not be listed
+    pub fn error(parent: &Path, depth: usize, error: &str) -> Self {
+        Self {
+            path: parent.to_path_buf(),
+            name: error.to_string(),
+            has_children: false,
+            depth,
+            error: Some(error.to_string()),
+            loading: false,
+        }
+    }
+
+    /// Placeholder under a folder whose children are still being scanned
+    pub fn loading(parent: &Path, depth: usize) -> Self {
+        Self {
+            path: parent.to_path_buf(),
+            name: String::new(),
+            has_children: false,
+            depth,
+            error: None,
+            loading: true,
+        }
+    }
+}
+
+/// Subfolders of a folder, scanned on a background thread
+#[derive(Debug, Clone)]
+struct FolderChildren {
+    /// Subfolder paths and whether each has subfolders of its own, sorted by name
+    folders: Vec<(PathBuf, bool)>,
+    /// Folder mtime at scan time; a different mtime on re-expand triggers a rescan
+    modified: Option<SystemTime>,
+}
+
+type ScanResult = (PathBuf, Result<FolderChildren, String>);
+
+/// Whether a folder contains at least one subfolder
+fn has_subfolders(path: &Path) -> bool {
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .any(|e| e.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
+        })
+        .unwrap_or(false)
+}
+
+/// List a folder's subfolders (blocking; runs on a scan thread)
+fn scan_children(path: &Path) -> Result<FolderChildren, String> {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    let entries = std::fs::read_dir(path).map_err(|e| e.to_string())?;
+
+    let mut folders: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
+        .map(|e| e.path())
+        .collect();
+
+    // Sort alphabetically
+    folders.sort_by(|a, b| {
+        a.file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .cmp(&b.file_name().map(|n| n.to_string_lossy().to_lowercase()))
+    });
+
+    let folders = folders
+        .into_iter()
+        .map(|folder| {
+            let has_children = has_subfolders(&folder);
+            (folder, has_children)
+        })
+        .collect();
+
+    Ok(FolderChildren { folders, modified })
 }
 
 /// Folder tree component
@@ -60,97 +182,199 @@ pub struct FolderTree {
     pub roots: Vec<PathBuf>,
     /// Cached folder nodes
     nodes: Vec<FolderNode>,
-    /// Last refreshed path
-    last_root: Option<PathBuf>,
+    /// Favorite folders shown above the tree
+    pub bookmarks: Vec<BookmarkEntry>,
+    /// Scanned children per folder (kept when collapsed)
+    children: HashMap<PathBuf, Result<FolderChildren, String>>,
+    /// Folders being scanned
+    loading: HashSet<PathBuf>,
+    scan_tx: Sender<ScanResult>,
+    scan_rx: Receiver<ScanResult>,
+    /// Watches the visible expanded branches so the tree follows folder changes
+    watcher: Option<FileWatcher>,
+    /// Watched folders (each non-recursively, so a huge subtree costs nothing)
+    watched: HashSet<PathBuf>,
 }
 
+/// Empty tree without roots (cheap; used as a placeholder by `std::mem::take`)
 impl Default for FolderTree {
     fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl FolderTree {
-    pub fn new() -> Self {
-        let roots = Self::get_root_paths();
-
+        let (scan_tx, scan_rx) = mpsc::channel();
         Self {
             selected: None,
             expanded: HashSet::new(),
-            roots,
+            roots: Vec::new(),
             nodes: Vec::new(),
-            last_root: None,
+            bookmarks: Vec::new(),
+            children: HashMap::new(),
+            loading: HashSet::new(),
+            scan_tx,
+            scan_rx,
+            watcher: None,
+            watched: HashSet::new(),
         }
     }
+}
+
+impl FolderTree {
+    pub fn new() -> Self {
+        let mut tree = Self {
+            roots: app_fs::list_drives()
+                .iter()
+                .map(|drive| drive.as_path().to_path_buf())
+                .collect(),
+            watcher: FileWatcher::new()
+                .map_err(|e| tracing::warn!("Folder tree watcher unavailable: {}", e))
+                .ok(),
+            ..Self::default()
+        };
+        tree.refresh_nodes();
+        tree
+    }
 
-    /// Get root paths (drives on Windows, / on Unix)
-    #[cfg(windows)]
-    fn get_root_paths() -> Vec<PathBuf> {
-        // Get available drives
-        let mut drives = Vec::new();
-        for letter in b'A'..=b'Z' {
-            let drive = format!("{}:\\", letter as char);
-            let path = PathBuf::from(&drive);
-            if path.exists() {
-                drives.push(path);
+    /// Make sure the root containing a path is listed (e.g. a network share)
+    pub fn add_root(&mut self, path: &Path) {
+        if let Some(root) = path.ancestors().last() {
+            if !self.roots.iter().any(|r| r == root) {
+                self.roots.push(root.to_path_buf());
+                self.refresh_nodes();
             }
         }
-        if drives.is_empty() {
-            drives.push(PathBuf::from("C:\\"));
-        }
-        drives
     }
 
-    #[cfg(not(windows))]
-    fn get_root_paths() -> Vec<PathBuf> {
-        vec![PathBuf::from("/")]
-    }
+    /// Rebuild the folder tree from the roots
+    fn refresh_nodes(&mut self) {
+        self.nodes.clear();
 
-    /// Set the current root folder to display
-    pub fn set_root(&mut self, path: &Path) {
-        if self.last_root.as_deref() != Some(path) {
-            self.last_root = Some(path.to_path_buf());
-            self.refresh_nodes(path);
+        for root in self.roots.clone() {
+            self.nodes.push(FolderNode::root(root.clone()));
+            // Recursively add expanded folders
+            self.add_children(&root, 1);
         }
     }
 
-    /// Refresh the folder tree from a root path
-    fn refresh_nodes(&mut self, root: &Path) {
-        self.nodes.clear();
+    /// Add children of a folder if it's expanded, scanning them in the background if not cached
+    fn add_children(&mut self, parent: &Path, depth: usize) {
+        if !self.expanded.contains(parent) {
+            return;
+        }
 
-        // Add the root itself
-        self.nodes.push(FolderNode::new(root.to_path_buf(), 0));
+        let folders = match self.children.get(parent) {
+            Some(Ok(children)) => children.folders.clone(),
+            Some(Err(error)) => {
+                let node = FolderNode::error(parent, depth, error);
+                self.nodes.push(node);
+                return;
+            }
+            None => {
+                self.start_scan(parent);
+                self.nodes.push(FolderNode::loading(parent, depth));
+                return;
+            }
+        };
 
-        // Recursively add expanded folders
-        self.add_children(root, 1);
+        for (folder, has_children) in folders {
+            self.nodes.push(FolderNode::with_children(folder.clone(), depth, has_children));
+            // Recursively add if expanded
+            self.add_children(&folder, depth + 1);
+        }
     }
 
-    /// Add children of a folder if it's expanded
-    fn add_children(&mut self, parent: &Path, depth: usize) {
-        if !self.expanded.contains(parent) {
+    /// Scan a folder's children on a background thread (once at a time per folder)
+    fn start_scan(&mut self, path: &Path) {
+        if !self.loading.insert(path.to_path_buf()) {
             return;
         }
+        let tx = self.scan_tx.clone();
+        let path = path.to_path_buf();
+        std::thread::spawn(move || {
+            let result = scan_children(&path);
+            let _ = tx.send((path, result));
+        });
+    }
 
-        if let Ok(entries) = std::fs::read_dir(parent) {
-            let mut folders: Vec<_> = entries
-                .filter_map(|e| e.ok())
-                .filter(|e| e.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
-                .map(|e| e.path())
-                .collect();
-
-            // Sort alphabetically
-            folders.sort_by(|a, b| {
-                a.file_name()
-                    .map(|n| n.to_string_lossy().to_lowercase())
-                    .cmp(&b.file_name().map(|n| n.to_string_lossy().to_lowercase()))
-            });
+    /// Take finished scans; returns true if the tree changed
+    fn poll_scans(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok((path, result)) = self.scan_rx.try_recv() {
+            self.loading.remove(&path);
+            self.children.insert(path, result);
+            changed = true;
+        }
+        if changed {
+            self.refresh_nodes();
+        }
+        changed
+    }
+
+    /// Watch the visible expanded folders (and nothing else)
+    fn sync_watches(&mut self) {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return;
+        };
+        let plan = watch_plan(&self.expanded);
+        if plan == self.watched {
+            return;
+        }
 
-            for folder in folders {
-                self.nodes.push(FolderNode::new(folder.clone(), depth));
-                // Recursively add if expanded
-                self.add_children(&folder, depth + 1);
+        for path in self.watched.difference(&plan) {
+            let _ = watcher.unwatch(path);
+        }
+        for path in plan.difference(&self.watched) {
+            if let Err(e) = watcher.watch(path) {
+                tracing::debug!("Cannot watch {}: {}", path.display(), e);
             }
         }
+        self.watched = plan;
+    }
+
+    /// Rescan the folders affected by watcher events; returns true if the tree changed
+    fn poll_watcher(&mut self) -> bool {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return false;
+        };
+        let mut changed_paths = Vec::new();
+        for event in watcher.poll_events() {
+            match event {
+                FsEvent::Created(path) if path.is_dir() => changed_paths.push(path),
+                FsEvent::Removed(path) => changed_paths.push(path),
+                FsEvent::Renamed { from, to } => changed_paths.extend([from, to]),
+                // File contents don't change the tree
+                FsEvent::Created(_) | FsEvent::Modified(_) => {}
+            }
+        }
+
+        let mut stale = HashSet::new();
+        for path in changed_paths {
+            // The nearest listed folder shows either the change or a child's expand arrow
+            let Some(folder) = path.ancestors().skip(1).find(|a| self.children.contains_key(*a)) else {
+                continue;
+            };
+            let is_listed_child = matches!(
+                self.children.get(folder),
+                Some(Ok(children)) if children.folders.iter().any(|(p, _)| *p == path)
+            );
+            // A removed file right inside a listed folder changes nothing
+            if path.parent() == Some(folder) && !is_listed_child && !path.is_dir() {
+                continue;
+            }
+            stale.insert(folder.to_path_buf());
+        }
+
+        if stale.is_empty() {
+            return false;
+        }
+        for folder in &stale {
+            self.children.remove(folder);
+        }
+        self.refresh_nodes();
+        true
+    }
+
+    /// Forget a folder's cached children and scan them again
+    pub fn rescan(&mut self, path: &Path) {
+        self.children.remove(path);
+        self.refresh_nodes();
     }
 
     /// Toggle expansion of a folder
@@ -158,27 +382,29 @@ impl FolderTree {
         if self.expanded.contains(path) {
             self.expanded.remove(path);
         } else {
+            // Reuse the cached children unless the folder changed since the scan
+            if let Some(Ok(children)) = self.children.get(path) {
+                let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+                if modified != children.modified {
+                    self.children.remove(path);
+                }
+            }
             self.expanded.insert(path.to_path_buf());
         }
 
-        // Refresh if we have a root
-        if let Some(root) = self.last_root.clone() {
-            self.refresh_nodes(&root);
-        }
+        self.refresh_nodes();
     }
 
     /// Expand to show a specific path
     pub fn expand_to(&mut self, path: &Path) {
         // Expand all ancestors
-        let mut current = path.to_path_buf();
-        while let Some(parent) = current.parent() {
-            self.expanded.insert(parent.to_path_buf());
-            current = parent.to_path_buf();
+        let mut changed = false;
+        for ancestor in path.ancestors().skip(1) {
+            changed |= self.expanded.insert(ancestor.to_path_buf());
         }
 
-        // Refresh
-        if let Some(root) = self.last_root.clone() {
-            self.refresh_nodes(&root);
+        if changed {
+            self.refresh_nodes();
         }
     }
 
@@ -186,19 +412,28 @@ impl FolderTree {
     pub fn ui(&mut self, ui: &mut Ui, current_path: &Path) -> Option<FolderTreeAction> {
         let mut action = None;
 
-        // Make sure tree is updated for current path
-        if let Some(root) = current_path.ancestors().last() {
-            self.set_root(root);
-        } else {
-            self.set_root(current_path);
-        }
-
-        // Ensure current path is visible
+        // Make sure the current path's drive is listed and the path is visible
+        self.add_root(current_path);
         self.expand_to(current_path);
 
+        self.poll_scans();
+        self.sync_watches();
+        self.poll_watcher();
+        if !self.loading.is_empty() {
+            ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+        }
+
         egui::ScrollArea::vertical()
             .auto_shrink([false, false])
             .show(ui, |ui| {
+                // Favorites
+                if !self.bookmarks.is_empty() {
+                    if let Some(a) = self.bookmarks_ui(ui, current_path) {
+                        action = Some(a);
+                    }
+                    ui.separator();
+                }
+
                 // Parent folder button
                 if current_path.parent().is_some() {
                     let parent_response = ui.horizontal(|ui| {
@@ -225,6 +460,23 @@ impl FolderTree {
 
                     let indent = node.depth as f32 * 16.0;
 
+                    if node.loading {
+                        ui.horizontal(|ui| {
+                            ui.add_space(indent + 22.0);
+                            ui.spinner();
+                        });
+                        continue;
+                    }
+
+                    if let Some(error) = &node.error {
+                        ui.horizontal(|ui| {
+                            ui.add_space(indent + 22.0);
+                            ui.label(egui::RichText::new(format!("⚠ {}", error)).weak())
+                                .on_hover_text(node.path.to_string_lossy());
+                        });
+                        continue;
+                    }
+
                     ui.horizontal(|ui| {
                         ui.add_space(indent + 4.0);
 
@@ -267,10 +519,115 @@ impl FolderTree {
                         if label_response.double_clicked() {
                             self.toggle_expand(&node.path);
                         }
+
+                        label_response.context_menu(|ui| {
+                            if ui.button("Refresh").clicked() {
+                                self.rescan(&node.path);
+                                ui.close_menu();
+                            }
+                        });
                     });
                 }
+
+                ui.separator();
+                let trash_response = ui.horizontal(|ui| {
+                    ui.add_space(4.0);
+                    ui.selectable_label(false, "🗑 Recycle Bin")
+                }).inner;
+                if trash_response.clicked() {
+                    action = Some(FolderTreeAction::OpenTrash);
+                }
+            });
+
+        action
+    }
+
+    /// Render the favorites section
+    fn bookmarks_ui(&self, ui: &mut Ui, current_path: &Path) -> Option<FolderTreeAction> {
+        let mut action = None;
+        let last = self.bookmarks.len() - 1;
+
+        ui.label(egui::RichText::new("★ Favorites").strong());
+        for (i, bookmark) in self.bookmarks.iter().enumerate() {
+            let is_current = current_path == bookmark.path;
+            let response = ui.horizontal(|ui| {
+                ui.add_space(4.0);
+                if bookmark.exists {
+                    ui.selectable_label(is_current, format!("📁 {}", bookmark.name))
+                        .on_hover_text(bookmark.path.to_string_lossy())
+                } else {
+                    // Broken link: shown but not navigable
+                    ui.label(egui::RichText::new(format!("⚠ {}", bookmark.name)).weak().strikethrough())
+                        .on_hover_text(format!("Folder not found: {}", bookmark.path.to_string_lossy()))
+                }
+            }).inner;
+
+            if bookmark.exists && response.clicked() {
+                action = Some(FolderTreeAction::SelectFolder(bookmark.path.clone()));
+            }
+
+            response.context_menu(|ui| {
+                if ui.add_enabled(i > 0, egui::Button::new("Move up")).clicked() {
+                    action = Some(FolderTreeAction::MoveBookmark(bookmark.bookmark_id, -1));
+                    ui.close_menu();
+                }
+                if ui.add_enabled(i < last, egui::Button::new("Move down")).clicked() {
+                    action = Some(FolderTreeAction::MoveBookmark(bookmark.bookmark_id, 1));
+                    ui.close_menu();
+                }
+                if ui.button("Remove").clicked() {
+                    action = Some(FolderTreeAction::RemoveBookmark(bookmark.bookmark_id));
+                    ui.close_menu();
+                }
             });
+        }
 
         action
     }
 }
+
+/// Folders to watch for a set of expanded folders: only those whose ancestors are all
+/// expanded too (visible). Each shows just its direct children, so none needs a recursive watch.
+fn watch_plan(expanded: &HashSet<PathBuf>) -> HashSet<PathBuf> {
+    let visible = |path: &Path| path.ancestors().skip(1).all(|a| expanded.contains(a));
+    expanded.iter().filter(|path| visible(path)).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_children() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("b/inner")).unwrap();
+        std::fs::create_dir(dir.path().join("A")).unwrap();
+        std::fs::write(dir.path().join("file.txt"), "x").unwrap();
+
+        let children = scan_children(dir.path()).unwrap();
+        let names: Vec<_> = children.folders.iter()
+            .map(|(path, has)| (path.file_name().unwrap().to_string_lossy().to_string(), *has))
+            .collect();
+        assert_eq!(names, vec![("A".to_string(), false), ("b".to_string(), true)]);
+        assert!(children.modified.is_some());
+
+        assert!(scan_children(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_watch_plan() {
+        let expanded: HashSet<PathBuf> = ["/", "/home", "/home/me", "/home/me/pics", "/tmp/hidden"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        let plan = watch_plan(&expanded);
+
+        // Every visible expanded folder, however deep; /tmp/hidden is not
+        // visible because /tmp is collapsed
+        let expected: HashSet<PathBuf> = ["/", "/home", "/home/me", "/home/me/pics"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(plan, expected);
+    }
+}
@@ -0,0 +1,201 @@
+//! RGB / luminance histogram overlay for the viewer
+
+/// Number of bins per channel
+pub const HISTOGRAM_BINS: usize = 256;
+
+/// Histogram display mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum HistogramMode {
+    /// Combined luminance (Rec.709 weights)
+    #[default]
+    Luminance,
+    /// Separate red/green/blue channels
+    Rgb,
+}
+
+impl HistogramMode {
+    pub fn cycle(self) -> Self {
+        match self {
+            HistogramMode::Luminance => HistogramMode::Rgb,
+            HistogramMode::Rgb => HistogramMode::Luminance,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HistogramMode::Luminance => "Luminance",
+            HistogramMode::Rgb => "RGB",
+        }
+    }
+}
+
+/// Per-channel pixel value histogram
+#[derive(Clone, Debug)]
+pub struct Histogram {
+    pub red: [u32; HISTOGRAM_BINS],
+    pub green: [u32; HISTOGRAM_BINS],
+    pub blue: [u32; HISTOGRAM_BINS],
+    pub luminance: [u32; HISTOGRAM_BINS],
+}
+
+impl Histogram {
+    /// Compute histogram from an RGBA8 buffer
+    pub fn from_rgba(data: &[u8]) -> Self {
+        let mut hist = Self {
+            red: [0; HISTOGRAM_BINS],
+            green: [0; HISTOGRAM_BINS],
+            blue: [0; HISTOGRAM_BINS],
+            luminance: [0; HISTOGRAM_BINS],
+        };
+
+        for px in data.chunks_exact(4) {
+            let (r, g, b) = (px[0] as usize, px[1] as usize, px[2] as usize);
+            hist.red[r] += 1;
+            hist.green[g] += 1;
+            hist.blue[b] += 1;
+            // Rec.709 luma in fixed point (weights sum to 256)
+            let luma = (54 * r + 183 * g + 19 * b) >> 8;
+            hist.luminance[luma] += 1;
+        }
+
+        hist
+    }
+
+    /// Largest bin count for the given mode (used for vertical scaling)
+    pub fn max_count(&self, mode: HistogramMode) -> u32 {
+        match mode {
+            HistogramMode::Luminance => self.luminance.iter().copied().max().unwrap_or(0),
+            HistogramMode::Rgb => self.red.iter()
+                .chain(self.green.iter())
+                .chain(self.blue.iter())
+                .copied()
+                .max()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Histogram overlay state (computed once per image, drawn every frame)
+#[derive(Default)]
+pub struct HistogramOverlay {
+    pub visible: bool,
+    pub mode: HistogramMode,
+    histogram: Option<Histogram>,
+}
+
+impl HistogramOverlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recompute from a newly decoded image
+    pub fn set_image(&mut self, rgba: &[u8]) {
+        self.histogram = Some(Histogram::from_rgba(rgba));
+    }
+
+    pub fn clear(&mut self) {
+        self.histogram = None;
+    }
+
+    pub fn histogram(&self) -> Option<&Histogram> {
+        self.histogram.as_ref()
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn cycle_mode(&mut self) {
+        self.mode = self.mode.cycle();
+    }
+
+    /// Draw the histogram in the bottom-right corner of `area`
+    pub fn render(&self, painter: &egui::Painter, area: egui::Rect) {
+        if !self.visible {
+            return;
+        }
+        let Some(ref hist) = self.histogram else {
+            return;
+        };
+
+        let size = egui::vec2(HISTOGRAM_BINS as f32, 100.0);
+        // Keep clear of the seek bar at the bottom edge
+        let margin = egui::vec2(12.0, 44.0);
+        let rect = egui::Rect::from_min_size(area.max - margin - size, size);
+
+        painter.rect_filled(rect.expand(4.0), 4.0, egui::Color32::from_black_alpha(160));
+
+        let max = hist.max_count(self.mode);
+        if max == 0 {
+            return;
+        }
+
+        match self.mode {
+            HistogramMode::Luminance => {
+                let color = egui::Color32::from_gray(220);
+                for (i, &count) in hist.luminance.iter().enumerate() {
+                    let h = count as f32 / max as f32 * rect.height();
+                    let x = rect.left() + i as f32 + 0.5;
+                    painter.line_segment(
+                        [egui::pos2(x, rect.bottom()), egui::pos2(x, rect.bottom() - h)],
+                        egui::Stroke::new(1.0, color),
+                    );
+                }
+            }
+            HistogramMode::Rgb => {
+                let channels = [
+                    (&hist.red, egui::Color32::from_rgb(255, 80, 80)),
+                    (&hist.green, egui::Color32::from_rgb(80, 255, 80)),
+                    (&hist.blue, egui::Color32::from_rgb(80, 140, 255)),
+                ];
+                for (bins, color) in channels {
+                    let points: Vec<egui::Pos2> = bins.iter().enumerate()
+                        .map(|(i, &count)| {
+                            let h = count as f32 / max as f32 * rect.height();
+                            egui::pos2(rect.left() + i as f32 + 0.5, rect.bottom() - h)
+                        })
+                        .collect();
+                    painter.add(egui::Shape::line(points, egui::Stroke::new(1.0, color)));
+                }
+            }
+        }
+
+        painter.text(
+            rect.left_top() + egui::vec2(4.0, 2.0),
+            egui::Align2::LEFT_TOP,
+            self.mode.name(),
+            egui::FontId::proportional(11.0),
+            egui::Color32::from_white_alpha(180),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_counts() {
+        // Two pure red pixels and one white pixel
+        let data = [255, 0, 0, 255, 255, 0, 0, 255, 255, 255, 255, 255];
+        let hist = Histogram::from_rgba(&data);
+
+        assert_eq!(hist.red[255], 3);
+        assert_eq!(hist.green[0], 2);
+        assert_eq!(hist.green[255], 1);
+        assert_eq!(hist.blue[0], 2);
+        assert_eq!(hist.luminance[255], 1);
+        assert_eq!(hist.luminance.iter().sum::<u32>(), 3);
+        assert_eq!(hist.max_count(HistogramMode::Rgb), 3);
+    }
+
+    #[test]
+    fn test_mode_cycle() {
+        let mut overlay = HistogramOverlay::new();
+        assert_eq!(overlay.mode, HistogramMode::Luminance);
+        overlay.cycle_mode();
+        assert_eq!(overlay.mode, HistogramMode::Rgb);
+        overlay.cycle_mode();
+        assert_eq!(overlay.mode, HistogramMode::Luminance);
+    }
+}
@@ -0,0 +1,121 @@
+//! Loupe (view.toggle_loupe)
+//! A circular inset next to the cursor showing the full-resolution image
+//! around the point under it, magnified.
+
+use super::viewer::display_to_uv;
+use egui::{epaint::Mesh, Color32, Painter, Pos2, Rect, Stroke, TextureId, Vec2};
+
+/// Rim vertices of the circular inset
+const SEGMENTS: usize = 48;
+
+/// Gap between the cursor and the inset (px)
+const CURSOR_GAP: f32 = 24.0;
+
+/// Magnifier overlay
+#[derive(Debug, Clone)]
+pub struct Loupe {
+    pub visible: bool,
+    /// Screen pixels per image pixel
+    pub magnification: f32,
+    /// Inset radius (px)
+    pub radius: f32,
+}
+
+impl Default for Loupe {
+    fn default() -> Self {
+        Self { visible: false, magnification: 4.0, radius: 90.0 }
+    }
+}
+
+impl Loupe {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Position of `cursor` across an image drawn in `image_rect` (0-1 each way)
+    pub fn cursor_offset(image_rect: Rect, cursor: Pos2) -> Option<Vec2> {
+        if !image_rect.contains(cursor) || image_rect.width() <= 0.0 || image_rect.height() <= 0.0 {
+            return None;
+        }
+        let offset = cursor - image_rect.min;
+        Some(Vec2::new(offset.x / image_rect.width(), offset.y / image_rect.height()))
+    }
+
+    /// Radius of the sampled area across an image shown `display_size` pixels
+    /// large at 100% (0-1 each way)
+    pub fn sample_radius(&self, display_size: Vec2) -> Vec2 {
+        let pixels = self.radius / self.magnification.max(0.1);
+        Vec2::new(pixels / display_size.x.max(1.0), pixels / display_size.y.max(1.0))
+    }
+
+    /// Inset center: above-right of the cursor, flipped to stay inside `bounds`
+    pub fn inset_center(&self, cursor: Pos2, bounds: Rect) -> Pos2 {
+        let offset = self.radius + CURSOR_GAP;
+        let mut center = cursor + Vec2::new(offset, -offset);
+        if center.x + self.radius > bounds.right() {
+            center.x = cursor.x - offset;
+        }
+        if center.y - self.radius < bounds.top() {
+            center.y = cursor.y + offset;
+        }
+        center
+    }
+
+    /// Draw the inset when the cursor is over the image
+    /// `display_size` is the image's size in pixels as shown (after rotation)
+    /// and `uvs` its corner texture coordinates (see `viewer::display_uvs`).
+    pub fn render(&self, painter: &Painter, texture: TextureId, image_rect: Rect, display_size: Vec2, uvs: [Pos2; 4], cursor: Pos2) {
+        if !self.visible {
+            return;
+        }
+        let Some(offset) = Self::cursor_offset(image_rect, cursor) else {
+            return;
+        };
+
+        let center = self.inset_center(cursor, painter.clip_rect());
+        let sample = self.sample_radius(display_size);
+        painter.circle_filled(center, self.radius, Color32::BLACK);
+
+        // Triangle fan: texture coordinates beyond the image edge are clamped
+        let mut mesh = Mesh::with_texture(texture);
+        mesh.colored_vertex(center, Color32::WHITE);
+        mesh.vertices[0].uv = display_to_uv(uvs, offset);
+        for i in 0..=SEGMENTS {
+            let angle = i as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+            let direction = Vec2::angled(angle);
+            let pos = center + direction * self.radius;
+            let rim_uv = display_to_uv(uvs, offset + Vec2::new(direction.x * sample.x, direction.y * sample.y));
+            mesh.vertices.push(egui::epaint::Vertex { pos, uv: rim_uv, color: Color32::WHITE });
+            if i > 0 {
+                mesh.add_triangle(0, i as u32, i as u32 + 1);
+            }
+        }
+        painter.add(mesh);
+        painter.circle_stroke(center, self.radius, Stroke::new(2.0, Color32::from_gray(220)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loupe_geometry() {
+        let image_rect = Rect::from_min_size(Pos2::new(100.0, 50.0), Vec2::new(400.0, 200.0));
+        assert_eq!(Loupe::cursor_offset(image_rect, Pos2::new(300.0, 100.0)), Some(Vec2::new(0.5, 0.25)));
+        assert_eq!(Loupe::cursor_offset(image_rect, Pos2::new(50.0, 100.0)), None);
+
+        // 90px at 4x covers 22.5 image pixels either way, whatever the on-screen zoom
+        let loupe = Loupe::new();
+        assert_eq!(loupe.sample_radius(Vec2::new(4500.0, 2250.0)), Vec2::new(0.005, 0.01));
+
+        // Kept inside the bounds near the top-right corner
+        let bounds = Rect::from_min_size(Pos2::ZERO, Vec2::new(800.0, 600.0));
+        assert_eq!(loupe.inset_center(Pos2::new(400.0, 300.0), bounds), Pos2::new(514.0, 186.0));
+        assert_eq!(loupe.inset_center(Pos2::new(750.0, 20.0), bounds), Pos2::new(636.0, 134.0));
+    }
+}
@@ -12,16 +12,44 @@ pub mod effects;
 pub mod slideshow;
 pub mod folder_tree;
 pub mod thumbnail_catalog;
+pub mod histogram;
+pub mod tag_panel;
+pub mod duplicates;
+pub mod command_palette;
+pub mod trash;
+pub mod batch_rename;
+pub mod save_as;
+pub mod export;
+pub mod batch_convert;
+pub mod adjust;
+pub mod loupe;
+pub mod eyedropper;
+pub mod recent_folders;
+pub mod open_with;
 
 pub use file_browser::{FileBrowser, FileItem, BrowserAction, BrowserViewMode};
 pub use viewer::{ImageViewer, ViewerAction, FitMode};
 pub use toolbar::{Toolbar, ToolbarAction, ToolbarState, SortMode};
 pub use status_bar::{StatusBar, StatusInfo};
 pub use settings::{SettingsDialog, SettingsTab, SettingsAction};
-pub use dialogs::{Dialog, DialogResult, ConfirmDialog, RenameDialog, NewFolderDialog, TagEditDialog};
+pub use dialogs::{Dialog, DialogResult, ConfirmDialog, RenameDialog, NewFolderDialog, TagEditDialog, CommentDialog, ExportMarksDialog, ExportMarksAction, PropertiesDialog, PropertiesAction};
 pub use spread_viewer::{SpreadViewer, SpreadMode, SpreadLayout, PagePosition};
-pub use split_view::{SplitView, SplitDirection, SplitPane, SplitViewResponse};
+pub use split_view::{SplitView, SplitDirection, SplitPane, SplitSync, SplitViewResponse};
 pub use effects::{ImageTransform, Rotation, ViewerBackground, BackgroundColor, PageTransition, TransitionType};
-pub use slideshow::{Slideshow, SlideshowState, SlideshowConfig};
-pub use folder_tree::{FolderTree, FolderTreeAction, FolderNode};
-pub use thumbnail_catalog::{ThumbnailCatalog, ThumbnailItem, CatalogAction, NavigateDirection};
+pub use slideshow::{Slideshow, SlideshowState, SlideshowConfig, SlideshowOrder};
+pub use folder_tree::{FolderTree, FolderTreeAction, FolderNode, BookmarkEntry};
+pub use thumbnail_catalog::{ThumbnailCatalog, ThumbnailItem, CatalogAction, CatalogFilter, NavigateDirection, sort_header};
+pub use histogram::{Histogram, HistogramOverlay, HistogramMode};
+pub use tag_panel::{TagPanel, TagEntry, TagPanelAction};
+pub use duplicates::{DuplicateReview, DuplicateFile};
+pub use command_palette::{CommandPalette, PaletteEntry};
+pub use trash::{TrashView, TrashAction};
+pub use batch_rename::BatchRenameDialog;
+pub use save_as::{SaveAsDialog, SaveAsRequest};
+pub use export::{ExportDialog, ExportRequest};
+pub use batch_convert::{BatchConvertDialog, BatchConvertSummary};
+pub use adjust::{AdjustPanel, ImageAdjustments};
+pub use loupe::Loupe;
+pub use eyedropper::{Eyedropper, ColorSample};
+pub use recent_folders::RecentFoldersMenu;
+pub use open_with::OpenWithMenu;
@@ -0,0 +1,86 @@
+//! Open with menu (file.open_with without an app_id)
+//! Applications registered in the settings; pick one to open the selected file.
+
+use super::dialogs::{Dialog, DialogResult};
+use egui::{Align2, Context, Key, Window};
+
+/// Quick menu of registered applications
+pub struct OpenWithMenu {
+    pub open: bool,
+    /// Application names, in settings order
+    pub apps: Vec<String>,
+    /// File name shown in the title
+    pub file_name: String,
+    pub selected: usize,
+}
+
+impl OpenWithMenu {
+    pub fn new(apps: Vec<String>, file_name: &str) -> Self {
+        Self {
+            open: true,
+            apps,
+            file_name: file_name.to_string(),
+            selected: 0,
+        }
+    }
+}
+
+impl Dialog for OpenWithMenu {
+    /// Name of the chosen application
+    type Output = String;
+
+    fn ui(&mut self, ctx: &Context) -> DialogResult<String> {
+        if !self.open {
+            return DialogResult::None;
+        }
+
+        let mut result = DialogResult::None;
+        let (up, down, enter, escape) = ctx.input(|i| (
+            i.key_pressed(Key::ArrowUp),
+            i.key_pressed(Key::ArrowDown),
+            i.key_pressed(Key::Enter),
+            i.key_pressed(Key::Escape),
+        ));
+        if down {
+            self.selected = (self.selected + 1).min(self.apps.len().saturating_sub(1));
+        }
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+
+        Window::new("プログラムから開く")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(320.0)
+            .anchor(Align2::CENTER_TOP, [0.0, 80.0])
+            .show(ctx, |ui| {
+                ui.weak(&self.file_name);
+                ui.separator();
+                for (row, name) in self.apps.iter().enumerate() {
+                    if ui.selectable_label(row == self.selected, name).clicked() {
+                        result = DialogResult::Ok(name.clone());
+                    }
+                }
+                if self.apps.is_empty() {
+                    ui.weak("登録されたアプリはありません (設定 > Apps)");
+                }
+            });
+
+        if enter {
+            if let Some(name) = self.apps.get(self.selected) {
+                result = DialogResult::Ok(name.clone());
+            }
+        }
+        if escape {
+            result = DialogResult::Cancel;
+        }
+        if !matches!(result, DialogResult::None) {
+            self.open = false;
+        }
+
+        result
+    }
+
+    fn is_open(&self) -> bool { self.open }
+    fn close(&mut self) { self.open = false; }
+}
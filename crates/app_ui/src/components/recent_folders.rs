@@ -0,0 +1,104 @@
+//! Recent folders menu (nav.recent_folders)
+//! Folders visited lately, most recent first; pick one to jump back to it.
+
+use super::dialogs::{Dialog, DialogResult};
+use egui::{Align2, Context, Key, Window};
+use std::path::{Path, PathBuf};
+
+/// Quick menu of recently visited folders
+pub struct RecentFoldersMenu {
+    pub open: bool,
+    pub folders: Vec<PathBuf>,
+    pub selected: usize,
+}
+
+impl RecentFoldersMenu {
+    /// `folders` most recent first; the folder being browsed is left out
+    pub fn new(folders: Vec<PathBuf>, current: &Path) -> Self {
+        Self {
+            open: true,
+            folders: folders.into_iter().filter(|folder| folder != current).collect(),
+            selected: 0,
+        }
+    }
+}
+
+impl Dialog for RecentFoldersMenu {
+    type Output = PathBuf;
+
+    fn ui(&mut self, ctx: &Context) -> DialogResult<PathBuf> {
+        if !self.open {
+            return DialogResult::None;
+        }
+
+        let mut result = DialogResult::None;
+        let (up, down, enter, escape) = ctx.input(|i| (
+            i.key_pressed(Key::ArrowUp),
+            i.key_pressed(Key::ArrowDown),
+            i.key_pressed(Key::Enter),
+            i.key_pressed(Key::Escape),
+        ));
+        if down {
+            self.selected = (self.selected + 1).min(self.folders.len().saturating_sub(1));
+        }
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+
+        Window::new("最近のフォルダ")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(420.0)
+            .anchor(Align2::CENTER_TOP, [0.0, 80.0])
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+                    for (row, folder) in self.folders.iter().enumerate() {
+                        let name = folder.file_name()
+                            .map(|n| n.to_string_lossy().into_owned())
+                            .unwrap_or_else(|| folder.display().to_string());
+                        let response = ui.selectable_label(row == self.selected, name)
+                            .on_hover_text(folder.display().to_string());
+                        if response.clicked() {
+                            result = DialogResult::Ok(folder.clone());
+                        }
+                        if row == self.selected && (up || down) {
+                            response.scroll_to_me(None);
+                        }
+                    }
+                    if self.folders.is_empty() {
+                        ui.weak("最近のフォルダはありません");
+                    }
+                });
+            });
+
+        if enter {
+            if let Some(folder) = self.folders.get(self.selected) {
+                result = DialogResult::Ok(folder.clone());
+            }
+        }
+        if escape {
+            result = DialogResult::Cancel;
+        }
+        if !matches!(result, DialogResult::None) {
+            self.open = false;
+        }
+
+        result
+    }
+
+    fn is_open(&self) -> bool { self.open }
+    fn close(&mut self) { self.open = false; }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_folder_left_out() {
+        let folders = vec![PathBuf::from("/photos"), PathBuf::from("/comics"), PathBuf::from("/music")];
+        let menu = RecentFoldersMenu::new(folders, Path::new("/comics"));
+        assert_eq!(menu.folders, vec![PathBuf::from("/photos"), PathBuf::from("/music")]);
+        assert_eq!(menu.selected, 0);
+    }
+}
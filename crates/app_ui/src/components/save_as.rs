@@ -0,0 +1,196 @@
+//! Save-as window (file.save_as)
+//! Picks the output path, format and JPEG quality; the file extension
+//! follows the selected format.
+
+use super::dialogs::{Dialog, DialogResult};
+use app_core::SaveFormat;
+use egui::{Align2, Context, Ui, Window};
+use std::path::{Path, PathBuf};
+
+/// Default JPEG quality
+pub const DEFAULT_SAVE_QUALITY: u8 = 90;
+
+/// What the user chose to save
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveAsRequest {
+    pub path: PathBuf,
+    pub format: SaveFormat,
+    pub quality: u8,
+    /// Copy the source EXIF into the new file
+    pub keep_exif: bool,
+    /// Bake the viewer's image adjustments into the saved pixels
+    pub apply_adjustments: bool,
+}
+
+/// Save-as window
+pub struct SaveAsDialog {
+    pub open: bool,
+    pub path: String,
+    pub format: SaveFormat,
+    pub quality: u8,
+    pub keep_exif: bool,
+    /// Whether the source has EXIF to keep
+    pub has_exif: bool,
+    pub apply_adjustments: bool,
+    /// Whether the viewer has adjustments that could be applied
+    pub has_adjustments: bool,
+}
+
+impl SaveAsDialog {
+    /// Start from the source path, keeping its format when it can be written
+    pub fn new(source: &Path, has_exif: bool) -> Self {
+        let format = SaveFormat::from_path(source).unwrap_or(SaveFormat::Png);
+        let mut dialog = Self {
+            open: true,
+            path: source.display().to_string(),
+            format,
+            quality: DEFAULT_SAVE_QUALITY,
+            keep_exif: has_exif,
+            has_exif,
+            apply_adjustments: false,
+            has_adjustments: false,
+        };
+        dialog.set_format(format);
+        dialog
+    }
+
+    /// Offer to apply the viewer's adjustments (on by default)
+    pub fn with_adjustments(mut self, has_adjustments: bool) -> Self {
+        self.has_adjustments = has_adjustments;
+        self.apply_adjustments = has_adjustments;
+        self
+    }
+
+    /// Switch format, changing the extension of the path to match
+    pub fn set_format(&mut self, format: SaveFormat) {
+        self.format = format;
+        let path = PathBuf::from(self.path.trim());
+        if SaveFormat::from_path(&path) != Some(format) {
+            self.path = path.with_extension(format.extension()).display().to_string();
+        }
+    }
+
+    /// Path, format, quality, EXIF and adjustment rows (shared with the export window)
+    pub fn options_ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("save_as_grid").num_columns(2).show(ui, |ui| {
+            ui.label("保存先:");
+            ui.add(egui::TextEdit::singleline(&mut self.path).desired_width(360.0));
+            ui.end_row();
+
+            ui.label("形式:");
+            let mut format = self.format;
+            egui::ComboBox::from_id_salt("save_as_format")
+                .selected_text(format.label())
+                .show_ui(ui, |ui| {
+                    for option in SaveFormat::ALL {
+                        ui.selectable_value(&mut format, option, option.label());
+                    }
+                });
+            if format != self.format {
+                self.set_format(format);
+            }
+            ui.end_row();
+
+            if self.format.is_lossy() {
+                ui.label("品質:");
+                ui.add(egui::Slider::new(&mut self.quality, 1..=100));
+                ui.end_row();
+            }
+
+            if self.has_exif {
+                ui.label("");
+                ui.add_enabled(
+                    self.format.supports_exif(),
+                    egui::Checkbox::new(&mut self.keep_exif, "EXIF を保持"),
+                );
+                ui.end_row();
+            }
+
+            if self.has_adjustments {
+                ui.label("");
+                ui.checkbox(&mut self.apply_adjustments, "画像の調整を適用");
+                ui.end_row();
+            }
+        });
+    }
+
+    pub fn request(&self) -> SaveAsRequest {
+        SaveAsRequest {
+            path: PathBuf::from(self.path.trim()),
+            format: self.format,
+            quality: self.quality,
+            keep_exif: self.keep_exif && self.has_exif && self.format.supports_exif(),
+            apply_adjustments: self.apply_adjustments && self.has_adjustments,
+        }
+    }
+}
+
+impl Dialog for SaveAsDialog {
+    type Output = SaveAsRequest;
+
+    fn ui(&mut self, ctx: &Context) -> DialogResult<SaveAsRequest> {
+        if !self.open {
+            return DialogResult::None;
+        }
+
+        let mut result = DialogResult::None;
+
+        Window::new("名前を付けて保存")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                self.options_ui(ui);
+
+                ui.add_space(16.0);
+
+                ui.horizontal(|ui| {
+                    if ui.button("保存").clicked() && !self.path.trim().is_empty() {
+                        result = DialogResult::Ok(self.request());
+                        self.open = false;
+                    }
+                    if ui.button("キャンセル").clicked() {
+                        result = DialogResult::Cancel;
+                        self.open = false;
+                    }
+                });
+            });
+
+        result
+    }
+
+    fn is_open(&self) -> bool { self.open }
+    fn close(&mut self) { self.open = false; }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_sets_extension() {
+        let mut dialog = SaveAsDialog::new(Path::new("/photos/scan.tif"), true);
+        // TIFF can't be written: PNG with the extension swapped
+        assert_eq!(dialog.format, SaveFormat::Png);
+        assert_eq!(dialog.path, Path::new("/photos/scan.png").display().to_string());
+
+        dialog.set_format(SaveFormat::Jpeg);
+        assert_eq!(dialog.request().path, PathBuf::from("/photos/scan.jpg"));
+        assert!(dialog.request().keep_exif);
+
+        // A matching extension the user typed is kept
+        dialog.path = "/photos/scan.JPEG".to_string();
+        dialog.set_format(SaveFormat::Jpeg);
+        assert_eq!(dialog.path, "/photos/scan.JPEG");
+
+        dialog.set_format(SaveFormat::Bmp);
+        assert!(!dialog.request().keep_exif);
+
+        // Adjustments are applied unless unticked, and only when there are any
+        assert!(!dialog.request().apply_adjustments);
+        let mut dialog = dialog.with_adjustments(true);
+        assert!(dialog.request().apply_adjustments);
+        dialog.apply_adjustments = false;
+        assert!(!dialog.request().apply_adjustments);
+    }
+}
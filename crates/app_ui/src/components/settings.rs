@@ -1,6 +1,8 @@
 //! Settings dialog component
 
-use app_core::AppConfig;
+use crate::input::{find_conflicts, normalize_binding};
+use app_core::{AppConfig, ExternalApp};
+use app_fs::find_executable;
 use egui::{Color32, ComboBox, Slider, Ui};
 
 /// Settings dialog state
@@ -13,6 +15,8 @@ pub struct SettingsDialog {
     pub working_config: AppConfig,
     /// Whether any changes have been made
     pub modified: bool,
+    /// Command whose next key press is being recorded
+    capturing: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -21,6 +25,7 @@ pub enum SettingsTab {
     Viewer,
     Navigation,
     Keybinds,
+    Apps,
 }
 
 /// Actions from settings dialog
@@ -40,6 +45,7 @@ impl SettingsDialog {
             current_tab: SettingsTab::General,
             working_config: config,
             modified: false,
+            capturing: None,
         }
     }
 
@@ -57,6 +63,36 @@ impl SettingsDialog {
     pub fn close(&mut self) {
         self.open = false;
         self.modified = false;
+        self.capturing = None;
+    }
+
+    /// Whether a keybinding row is waiting for a key press
+    pub fn is_capturing(&self) -> bool {
+        self.open && self.capturing.is_some()
+    }
+
+    /// Record a key press (InputHandler::key_string) for the capturing row
+    /// Escape cancels, Backspace clears the command's bindings
+    pub fn capture_key(&mut self, key: &str) {
+        let Some(command) = self.capturing.take() else {
+            return;
+        };
+        let Some(bindings) = self.working_config.keybindings.get_mut(&command) else {
+            return;
+        };
+
+        match key {
+            "Escape" => return,
+            "Backspace" => bindings.clear(),
+            _ => {
+                let normalized = normalize_binding(key);
+                if bindings.iter().any(|b| normalize_binding(b) == normalized) {
+                    return;
+                }
+                bindings.push(key.to_string());
+            }
+        }
+        self.modified = true;
     }
 
     /// Render the settings dialog
@@ -81,6 +117,7 @@ impl SettingsDialog {
                     ui.selectable_value(&mut self.current_tab, SettingsTab::Viewer, "Viewer");
                     ui.selectable_value(&mut self.current_tab, SettingsTab::Navigation, "Navigation");
                     ui.selectable_value(&mut self.current_tab, SettingsTab::Keybinds, "Keybinds");
+                    ui.selectable_value(&mut self.current_tab, SettingsTab::Apps, "Apps");
                 });
 
                 ui.separator();
@@ -92,6 +129,7 @@ impl SettingsDialog {
                         SettingsTab::Viewer => self.ui_viewer_tab(ui),
                         SettingsTab::Navigation => self.ui_navigation_tab(ui),
                         SettingsTab::Keybinds => self.ui_keybinds_tab(ui),
+                        SettingsTab::Apps => self.ui_apps_tab(ui),
                     }
                 });
 
@@ -109,6 +147,23 @@ impl SettingsDialog {
                         action = Some(SettingsAction::Cancel);
                     }
 
+                    // Conflicts are warned about, not blocked
+                    let conflicts = find_conflicts(&self.working_config.keybindings).len();
+                    if conflicts > 0 {
+                        ui.label(
+                            egui::RichText::new(format!("⚠ {} commands share keys", conflicts))
+                                .color(Color32::LIGHT_RED),
+                        ).on_hover_text("See the Keybinds tab");
+                    }
+
+                    let missing = self.missing_apps().len();
+                    if missing > 0 {
+                        ui.label(
+                            egui::RichText::new(format!("⚠ {} apps not found", missing))
+                                .color(Color32::LIGHT_RED),
+                        ).on_hover_text("See the Apps tab");
+                    }
+
                     // Show modified indicator
                     if self.modified {
                         ui.label(
@@ -315,6 +370,39 @@ impl SettingsDialog {
                 }
                 ui.end_row();
 
+                // Clamp Pan
+                ui.label("Keep Image in View:");
+                if ui.checkbox(&mut self.working_config.viewer.clamp_pan, "").changed() {
+                    self.modified = true;
+                }
+                ui.end_row();
+
+                // Snap to Edges
+                ui.label("Snap to Edges:");
+                if ui.checkbox(&mut self.working_config.viewer.snap_to_edges, "").changed() {
+                    self.modified = true;
+                }
+                ui.end_row();
+
+                // Loupe Magnification
+                ui.label("Loupe Magnification:");
+                if ui.add(Slider::new(&mut self.working_config.viewer.loupe_magnification, 1.0..=16.0).suffix("x")).changed() {
+                    self.modified = true;
+                }
+                ui.end_row();
+
+                // Transition Duration
+                ui.label("Transition Duration (ms):");
+                let mut duration = self.working_config.viewer.transition_duration_ms as f64;
+                if ui.add_enabled(
+                    self.working_config.viewer.enable_animation,
+                    Slider::new(&mut duration, 50.0..=1000.0).step_by(50.0),
+                ).changed() {
+                    self.working_config.viewer.transition_duration_ms = duration as u64;
+                    self.modified = true;
+                }
+                ui.end_row();
+
                 // Preload Count
                 ui.label("Preload Count:");
                 let mut preload = self.working_config.viewer.preload_count as f64;
@@ -383,6 +471,74 @@ impl SettingsDialog {
             });
     }
 
+    /// Registered apps whose executable cannot be found
+    pub fn missing_apps(&self) -> Vec<usize> {
+        self.working_config.external_apps.iter()
+            .enumerate()
+            .filter(|(_, app)| find_executable(app.path.trim()).is_none())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    fn ui_apps_tab(&mut self, ui: &mut Ui) {
+        ui.heading("Open With Applications");
+        ui.add_space(10.0);
+        ui.label("Arguments: {file} = the file, {dir} = its folder; quote with \"...\"");
+        ui.add_space(6.0);
+
+        let missing = self.missing_apps();
+        let mut remove = None;
+        egui::Grid::new("apps_grid")
+            .num_columns(5)
+            .spacing([10.0, 8.0])
+            .show(ui, |ui| {
+                ui.strong("Name");
+                ui.strong("Executable");
+                ui.strong("Arguments");
+                ui.label("");
+                ui.label("");
+                ui.end_row();
+
+                for (i, app) in self.working_config.external_apps.iter_mut().enumerate() {
+                    let mut changed = ui.add(egui::TextEdit::singleline(&mut app.name).desired_width(120.0)).changed();
+                    changed |= ui.add(egui::TextEdit::singleline(&mut app.path).desired_width(240.0)).changed();
+                    changed |= ui.add(egui::TextEdit::singleline(&mut app.args).desired_width(140.0)).changed();
+                    if missing.contains(&i) {
+                        ui.label(egui::RichText::new("⚠ Not found").color(Color32::LIGHT_RED));
+                    } else {
+                        ui.label(egui::RichText::new("✓").color(Color32::LIGHT_GREEN));
+                    }
+                    if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                    self.modified |= changed;
+                }
+            });
+
+        if let Some(i) = remove {
+            self.working_config.external_apps.remove(i);
+            self.modified = true;
+        }
+        if ui.button("➕ Add Application").clicked() {
+            self.working_config.external_apps.push(ExternalApp::default());
+            self.modified = true;
+        }
+
+        ui.add_space(16.0);
+        ui.heading("Terminal");
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            ui.label("Command:");
+            let edit = egui::TextEdit::singleline(&mut self.working_config.filer.terminal_command)
+                .hint_text("platform default")
+                .desired_width(320.0);
+            if ui.add(edit).on_hover_text("{dir} = the folder; e.g. alacritty --working-directory {dir}").changed() {
+                self.modified = true;
+            }
+        });
+    }
+
     fn ui_keybinds_tab(&mut self, ui: &mut Ui) {
         ui.heading("Keybind Settings");
         ui.add_space(10.0);
@@ -390,6 +546,8 @@ impl SettingsDialog {
         ui.label("Command → Key Bindings");
         ui.separator();
 
+        let conflicts = find_conflicts(&self.working_config.keybindings);
+
         // Group keybindings by category
         let categories = [
             ("Navigation", "nav."),
@@ -414,36 +572,63 @@ impl SettingsDialog {
                             .collect();
                         keys.sort();
 
+                        let mut start_capture = None;
                         for key in keys {
-                            ui.label(&key);
-
-                            if let Some(bindings) = self.working_config.keybindings.get_mut(&key) {
-                                let binding_text = bindings.join(", ");
-                                let mut new_text = binding_text.clone();
-
-                                let response = ui.add(
-                                    egui::TextEdit::singleline(&mut new_text)
-                                        .desired_width(200.0)
-                                        .hint_text("e.g., Ctrl+N, Down")
-                                );
-
-                                if response.changed() {
-                                    // Parse the new bindings
-                                    let new_bindings: Vec<String> = new_text
-                                        .split(',')
-                                        .map(|s| s.trim().to_string())
-                                        .filter(|s| !s.is_empty())
-                                        .collect();
-                                    *bindings = new_bindings;
-                                    self.modified = true;
+                            match conflicts.get(&key) {
+                                Some(others) => {
+                                    let tooltip = others.iter()
+                                        .map(|(binding, other)| format!("{} is also bound to {}", binding, other))
+                                        .collect::<Vec<_>>()
+                                        .join("\n");
+                                    ui.label(egui::RichText::new(&key).color(Color32::LIGHT_RED))
+                                        .on_hover_text(tooltip);
                                 }
-
-                                if response.on_hover_text("Separate multiple keys with commas").changed() {
-                                    // Already handled above
+                                None => {
+                                    ui.label(&key);
                                 }
                             }
+
+                            if self.capturing.as_ref() == Some(&key) {
+                                ui.horizontal(|ui| {
+                                    ui.label(egui::RichText::new("Press a key…").italics().color(Color32::YELLOW));
+                                    ui.weak("Esc: cancel, Backspace: clear");
+                                });
+                            } else if let Some(bindings) = self.working_config.keybindings.get_mut(&key) {
+                                ui.horizontal(|ui| {
+                                    let binding_text = bindings.join(", ");
+                                    let mut new_text = binding_text.clone();
+
+                                    let response = ui.add(
+                                        egui::TextEdit::singleline(&mut new_text)
+                                            .desired_width(200.0)
+                                            .hint_text("e.g., Ctrl+N, Down")
+                                    );
+
+                                    if response.changed() {
+                                        // Parse the new bindings
+                                        let new_bindings: Vec<String> = new_text
+                                            .split(',')
+                                            .map(|s| s.trim().to_string())
+                                            .filter(|s| !s.is_empty())
+                                            .collect();
+                                        *bindings = new_bindings;
+                                        self.modified = true;
+                                    }
+
+                                    if response.on_hover_text("Separate multiple keys with commas").changed() {
+                                        // Already handled above
+                                    }
+
+                                    if ui.small_button("⌨").on_hover_text("Record a key").clicked() {
+                                        start_capture = Some(key.clone());
+                                    }
+                                });
+                            }
                             ui.end_row();
                         }
+                        if start_capture.is_some() {
+                            self.capturing = start_capture;
+                        }
                     });
             });
         }
@@ -487,3 +672,49 @@ fn parse_hex_color(hex: &str) -> Color32 {
     // Fallback to dark gray
     Color32::from_rgb(32, 32, 32)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_key() {
+        let mut dialog = SettingsDialog::new(AppConfig::default());
+        dialog.open(AppConfig::default(), Some(SettingsTab::Keybinds));
+        let bindings = |d: &SettingsDialog| d.working_config.keybindings["app.exit"].clone();
+        let before = bindings(&dialog);
+
+        dialog.capturing = Some("app.exit".to_string());
+        assert!(dialog.is_capturing());
+        dialog.capture_key("Escape");
+        assert!(!dialog.is_capturing());
+        assert_eq!(bindings(&dialog), before);
+
+        dialog.capturing = Some("app.exit".to_string());
+        dialog.capture_key("Ctrl+Shift+x");
+        assert_eq!(bindings(&dialog).last().map(String::as_str), Some("Ctrl+Shift+x"));
+
+        // Same key in another notation is not added twice
+        dialog.capturing = Some("app.exit".to_string());
+        dialog.capture_key("shift+ctrl+X");
+        assert_eq!(bindings(&dialog).len(), before.len() + 1);
+
+        dialog.capturing = Some("app.exit".to_string());
+        dialog.capture_key("Backspace");
+        assert!(bindings(&dialog).is_empty());
+        assert!(dialog.modified);
+    }
+
+    #[test]
+    fn test_missing_apps() {
+        let mut config = AppConfig::default();
+        let existing = std::env::current_exe().unwrap().display().to_string();
+        config.external_apps = vec![
+            ExternalApp { name: "Self".into(), path: existing, ..Default::default() },
+            ExternalApp { name: "Gone".into(), path: "/no/such/editor".into(), ..Default::default() },
+        ];
+        let mut dialog = SettingsDialog::new(AppConfig::default());
+        dialog.open(config, Some(SettingsTab::Apps));
+        assert_eq!(dialog.missing_apps(), vec![1]);
+    }
+}
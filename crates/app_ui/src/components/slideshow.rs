@@ -11,6 +11,18 @@ pub enum SlideshowState {
     Paused,
 }
 
+/// Slideshow playback order
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SlideshowOrder {
+    #[default]
+    Normal,
+    Reverse,
+    /// Walk a random permutation, reshuffling once every image has been shown
+    Shuffle,
+    /// Pick a random image each step
+    Random,
+}
+
 /// Slideshow configuration
 #[derive(Clone, Debug)]
 pub struct SlideshowConfig {
@@ -18,6 +30,7 @@ pub struct SlideshowConfig {
     pub loop_mode: bool,
     pub shuffle: bool,
     pub reverse: bool,
+    pub random: bool,
 }
 
 impl Default for SlideshowConfig {
@@ -27,6 +40,7 @@ impl Default for SlideshowConfig {
             loop_mode: true,
             shuffle: false,
             reverse: false,
+            random: false,
         }
     }
 }
@@ -38,6 +52,7 @@ pub struct Slideshow {
     last_advance: Option<Instant>,
     shuffle_order: Vec<usize>,
     shuffle_index: usize,
+    rng_state: usize,
 }
 
 impl Default for Slideshow {
@@ -54,6 +69,33 @@ impl Slideshow {
             last_advance: None,
             shuffle_order: Vec::new(),
             shuffle_index: 0,
+            rng_state: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos() as usize)
+                .unwrap_or(0),
+        }
+    }
+
+    /// Set playback order
+    pub fn set_order(&mut self, order: SlideshowOrder) {
+        self.config.reverse = order == SlideshowOrder::Reverse;
+        self.config.shuffle = order == SlideshowOrder::Shuffle;
+        self.config.random = order == SlideshowOrder::Random;
+        // Regenerated lazily on the next advance
+        self.shuffle_order.clear();
+        self.shuffle_index = 0;
+    }
+
+    /// Current playback order
+    pub fn order(&self) -> SlideshowOrder {
+        if self.config.shuffle {
+            SlideshowOrder::Shuffle
+        } else if self.config.random {
+            SlideshowOrder::Random
+        } else if self.config.reverse {
+            SlideshowOrder::Reverse
+        } else {
+            SlideshowOrder::Normal
         }
     }
 
@@ -99,21 +141,20 @@ impl Slideshow {
         self.state != SlideshowState::Stopped
     }
 
+    /// Next pseudo-random number (LCG, no rand dependency)
+    fn next_random(&mut self) -> usize {
+        self.rng_state = self.rng_state.wrapping_mul(1103515245).wrapping_add(12345);
+        // Low bits of an LCG are weak; use the upper half
+        self.rng_state >> (usize::BITS / 2)
+    }
+
     /// Generate shuffle order (simple implementation without rand)
     fn generate_shuffle_order(&mut self, total: usize, current: usize) {
-        // Simple pseudo-random shuffle using current time
-        let seed = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_nanos() as usize)
-            .unwrap_or(0);
-
         let mut order: Vec<usize> = (0..total).collect();
 
         // Fisher-Yates shuffle with simple PRNG
-        let mut state = seed;
         for i in (1..total).rev() {
-            state = state.wrapping_mul(1103515245).wrapping_add(12345);
-            let j = state % (i + 1);
+            let j = self.next_random() % (i + 1);
             order.swap(i, j);
         }
 
@@ -148,19 +189,38 @@ impl Slideshow {
             return None;
         }
 
-        if self.config.shuffle && !self.shuffle_order.is_empty() {
+        if self.config.shuffle {
+            // Item count changed (or order just switched): start a fresh permutation from here
+            if self.shuffle_order.len() != total {
+                self.generate_shuffle_order(total, current);
+            }
+
             self.shuffle_index += 1;
             if self.shuffle_index >= self.shuffle_order.len() {
-                if self.config.loop_mode {
-                    self.shuffle_index = 0;
-                } else {
+                if !self.config.loop_mode {
                     self.stop();
                     return None;
                 }
+                // Every image has been shown: reshuffle, avoiding an immediate repeat
+                let last = self.shuffle_order[self.shuffle_order.len() - 1];
+                self.generate_shuffle_order(total, usize::MAX);
+                if total > 1 && self.shuffle_order[0] == last {
+                    self.shuffle_order.swap(0, 1);
+                }
+                self.shuffle_index = 0;
             }
             return Some(self.shuffle_order[self.shuffle_index]);
         }
 
+        if self.config.random {
+            if total == 1 {
+                return Some(0);
+            }
+            // Any image except the current one
+            let offset = 1 + self.next_random() % (total - 1);
+            return Some((current + offset) % total);
+        }
+
         let next = if self.config.reverse {
             if current == 0 {
                 if self.config.loop_mode {
@@ -254,6 +314,7 @@ impl Slideshow {
                 let mut opts = Vec::new();
                 if self.config.loop_mode { opts.push("Loop"); }
                 if self.config.shuffle { opts.push("Shuffle"); }
+                if self.config.random { opts.push("Random"); }
                 if self.config.reverse { opts.push("Rev"); }
                 let opts_str = if opts.is_empty() { String::new() } else { format!(" [{}]", opts.join(",")) };
                 format!("Slideshow {:.1}s{}", interval, opts_str)
@@ -298,6 +359,46 @@ mod tests {
         assert_eq!(ss.next_index(3, 5), Some(2));
     }
 
+    #[test]
+    fn test_shuffle_covers_all() {
+        let mut ss = Slideshow::new();
+        ss.set_order(SlideshowOrder::Shuffle);
+        ss.start(6, 2);
+
+        // First cycle starts at the current item and visits every other item once
+        let mut seen = vec![2];
+        let mut current = 2;
+        for _ in 0..5 {
+            current = ss.next_index(current, 6).unwrap();
+            seen.push(current);
+        }
+        seen.sort();
+        assert_eq!(seen, vec![0, 1, 2, 3, 4, 5]);
+
+        // Reshuffle never repeats the last item immediately
+        for _ in 0..50 {
+            let next = ss.next_index(current, 6).unwrap();
+            assert_ne!(next, current);
+            current = next;
+        }
+    }
+
+    #[test]
+    fn test_random_order() {
+        let mut ss = Slideshow::new();
+        ss.set_order(SlideshowOrder::Random);
+        assert_eq!(ss.order(), SlideshowOrder::Random);
+
+        let mut current = 0;
+        for _ in 0..50 {
+            let next = ss.next_index(current, 4).unwrap();
+            assert!(next < 4);
+            assert_ne!(next, current);
+            current = next;
+        }
+        assert_eq!(ss.next_index(0, 1), Some(0));
+    }
+
     #[test]
     fn test_interval() {
         let mut ss = Slideshow::new();
@@ -11,6 +11,16 @@ pub enum SplitDirection {
     Vertical,    // Left/Right split
 }
 
+/// How the other pane follows when zoom/pan sync is on
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SplitSync {
+    /// Same transform: the other pane copies zoom and pan
+    #[default]
+    Position,
+    /// Same delta: the other pane applies the same zoom step and pan movement
+    Relative,
+}
+
 /// A single pane in the split view
 #[derive(Clone, Default)]
 pub struct SplitPane {
@@ -19,6 +29,8 @@ pub struct SplitPane {
     pub image_size: Option<(u32, u32)>,
     pub zoom: f32,
     pub pan: Vec2,
+    /// Rotation (degrees, 0/90/180/270)
+    pub rotation: i32,
     pub locked: bool,
 }
 
@@ -30,6 +42,7 @@ impl SplitPane {
             image_size: None,
             zoom: 1.0,
             pan: Vec2::ZERO,
+            rotation: 0,
             locked: true,
         }
     }
@@ -40,6 +53,39 @@ impl SplitPane {
         self.image_size = None;
         self.zoom = 1.0;
         self.pan = Vec2::ZERO;
+        self.rotation = 0;
+    }
+
+    /// Show an image in this pane; zoom and pan reset when the file changes
+    pub fn set_image(&mut self, path: PathBuf, texture_id: egui::TextureId, size: (u32, u32)) {
+        if self.path.as_ref() != Some(&path) {
+            self.zoom = 1.0;
+            self.pan = Vec2::ZERO;
+            self.rotation = 0;
+        }
+        self.path = Some(path);
+        self.texture_id = Some(texture_id);
+        self.image_size = Some(size);
+    }
+
+    /// Rotate by 90 degrees (clockwise or counter-clockwise)
+    pub fn rotate(&mut self, clockwise: bool) {
+        self.rotation = (self.rotation + if clockwise { 90 } else { 270 }) % 360;
+    }
+
+    /// Screen rect of the image fitted into `rect` with this pane's zoom and pan
+    pub fn image_rect(&self, rect: Rect) -> Option<Rect> {
+        let (w, h) = self.image_size?;
+        if w == 0 || h == 0 {
+            return None;
+        }
+        let size = if self.rotation == 90 || self.rotation == 270 {
+            Vec2::new(h as f32, w as f32)
+        } else {
+            Vec2::new(w as f32, h as f32)
+        };
+        let scale = (rect.width() / size.x).min(rect.height() / size.y);
+        Some(Rect::from_center_size(rect.center() + self.pan, size * scale * self.zoom))
     }
 }
 
@@ -52,6 +98,9 @@ pub struct SplitView {
     pub active_pane: usize,
     pub sync_zoom: bool,
     pub sync_pan: bool,
+    pub sync_mode: SplitSync,
+    /// Pane rects from the last frame (used to sync pan between differently sized images)
+    pane_rects: [Rect; 2],
 }
 
 impl Default for SplitView {
@@ -68,8 +117,10 @@ impl SplitView {
             ratio: 0.5,
             panes: [SplitPane::new(), SplitPane::new()],
             active_pane: 0,
-            sync_zoom: true,
-            sync_pan: true,
+            sync_zoom: false,
+            sync_pan: false,
+            sync_mode: SplitSync::Position,
+            pane_rects: [Rect::NOTHING, Rect::NOTHING],
         }
     }
 
@@ -101,6 +152,11 @@ impl SplitView {
         self.active_pane = idx.min(1);
     }
 
+    /// Make the next pane active (wrapping)
+    pub fn next_pane(&mut self) {
+        self.active_pane = (self.active_pane + 1) % self.panes.len();
+    }
+
     /// Get active pane
     pub fn active_pane_mut(&mut self) -> &mut SplitPane {
         &mut self.panes[self.active_pane]
@@ -111,6 +167,51 @@ impl SplitView {
         &mut self.panes[1 - self.active_pane]
     }
 
+    /// Clear both panes and make the first one active
+    pub fn clear_panes(&mut self) {
+        for pane in &mut self.panes {
+            pane.clear();
+        }
+        self.active_pane = 0;
+    }
+
+    /// Paint both pane images clipped to their rects (borders are drawn by `ui`)
+    pub fn paint_panes(&self, painter: &egui::Painter, viewport: Rect) {
+        // Texture corners in screen order (top-left, top-right, bottom-right, bottom-left)
+        const UV: [Pos2; 4] = [Pos2::new(0.0, 0.0), Pos2::new(1.0, 0.0), Pos2::new(1.0, 1.0), Pos2::new(0.0, 1.0)];
+
+        for (pane, rect) in self.panes.iter().zip(self.calculate_rects(viewport)) {
+            let (Some(texture_id), Some(image_rect)) = (pane.texture_id, pane.image_rect(rect)) else {
+                continue;
+            };
+            let steps = (pane.rotation / 90).rem_euclid(4) as usize;
+            let corners = [image_rect.left_top(), image_rect.right_top(), image_rect.right_bottom(), image_rect.left_bottom()];
+            let mut mesh = egui::Mesh::with_texture(texture_id);
+            for (i, pos) in corners.into_iter().enumerate() {
+                mesh.vertices.push(egui::epaint::Vertex {
+                    pos,
+                    uv: UV[(i + 4 - steps) % 4],
+                    color: egui::Color32::WHITE,
+                });
+            }
+            mesh.add_triangle(0, 1, 2);
+            mesh.add_triangle(0, 2, 3);
+            painter.with_clip_rect(rect).add(egui::Shape::mesh(mesh));
+        }
+    }
+
+    /// Copy the active pane's zoom, pan and rotation to the other pane
+    /// Pan maps by fraction of the displayed image so differently sized images show the same region.
+    /// Returns the index of the pane that was updated.
+    pub fn copy_view_state(&mut self) -> usize {
+        let from = self.active_pane;
+        let to = 1 - from;
+        self.panes[to].zoom = self.panes[from].zoom;
+        self.panes[to].rotation = self.panes[from].rotation;
+        self.panes[to].pan = self.convert_pan(self.panes[from].pan, from);
+        to
+    }
+
     /// Calculate rectangles for both panes
     pub fn calculate_rects(&self, viewport: Rect) -> [Rect; 2] {
         let (w, h) = (viewport.width(), viewport.height());
@@ -170,22 +271,64 @@ impl SplitView {
     }
 
     /// Apply zoom to a pane (with optional sync)
+    /// Zoom is relative to each pane's fit, so equal zoom means the same relative scale
     pub fn apply_zoom(&mut self, delta: f32, pane_idx: usize) {
         self.panes[pane_idx].zoom = (self.panes[pane_idx].zoom * (1.0 + delta)).clamp(0.1, 10.0);
 
         if self.sync_zoom {
             let other = 1 - pane_idx;
-            self.panes[other].zoom = self.panes[pane_idx].zoom;
+            match self.sync_mode {
+                SplitSync::Position => {
+                    self.panes[other].zoom = self.panes[pane_idx].zoom;
+                    self.panes[other].pan = self.convert_pan(self.panes[pane_idx].pan, pane_idx);
+                }
+                SplitSync::Relative => {
+                    self.panes[other].zoom = (self.panes[other].zoom * (1.0 + delta)).clamp(0.1, 10.0);
+                }
+            }
         }
     }
 
     /// Apply pan to a pane (with optional sync)
+    /// The other pane moves by the same fraction of its own displayed image
     pub fn apply_pan(&mut self, delta: Vec2, pane_idx: usize) {
         self.panes[pane_idx].pan += delta;
 
         if self.sync_pan {
             let other = 1 - pane_idx;
-            self.panes[other].pan += delta;
+            match self.sync_mode {
+                SplitSync::Position => {
+                    self.panes[other].pan = self.convert_pan(self.panes[pane_idx].pan, pane_idx);
+                }
+                SplitSync::Relative => {
+                    self.panes[other].pan += self.convert_pan(delta, pane_idx);
+                }
+            }
+        }
+    }
+
+    /// Convert a pan offset of pane `from` into the other pane, as a fraction of the
+    /// displayed image size (unchanged when either size is unknown)
+    fn convert_pan(&self, pan: Vec2, from: usize) -> Vec2 {
+        let to = 1 - from;
+        let size = |idx: usize| {
+            self.panes[idx]
+                .image_rect(self.pane_rects[idx])
+                .map(|r| r.size())
+                .filter(|s| s.x > 0.0 && s.y > 0.0)
+        };
+        match (size(from), size(to)) {
+            (Some(from_size), Some(to_size)) => pan / from_size * to_size,
+            _ => pan,
+        }
+    }
+
+    /// Turn sync on with the given mode, or off with `None`
+    pub fn set_sync(&mut self, mode: Option<SplitSync>) {
+        self.sync_zoom = mode.is_some();
+        self.sync_pan = mode.is_some();
+        if let Some(mode) = mode {
+            self.sync_mode = mode;
         }
     }
 
@@ -213,6 +356,7 @@ impl SplitView {
 
         let rects = self.calculate_rects(viewport);
         let splitter = self.splitter_rect(viewport);
+        self.pane_rects = rects;
 
         // Draw panes
         for (i, rect) in rects.iter().enumerate() {
@@ -293,7 +437,11 @@ impl SplitView {
                 SplitDirection::Horizontal => "H",
                 SplitDirection::Vertical => "V",
             };
-            let sync = if self.sync_zoom { "Sync" } else { "Async" };
+            let sync = match (self.sync_zoom, self.sync_mode) {
+                (false, _) => "Async",
+                (true, SplitSync::Position) => "Sync",
+                (true, SplitSync::Relative) => "Sync(rel)",
+            };
             format!("Split:{} {} Active:{}", dir, sync, self.active_pane + 1)
         } else {
             String::new()
@@ -336,6 +484,15 @@ mod tests {
         assert!(!view.enabled);
     }
 
+    #[test]
+    fn test_next_pane() {
+        let mut view = SplitView::new();
+        view.next_pane();
+        assert_eq!(view.active_pane, 1);
+        view.next_pane();
+        assert_eq!(view.active_pane, 0);
+    }
+
     #[test]
     fn test_sync_zoom() {
         let mut view = SplitView::new();
@@ -344,6 +501,54 @@ mod tests {
         assert!((view.panes[0].zoom - view.panes[1].zoom).abs() < 0.001);
     }
 
+    #[test]
+    fn test_sync_pan_by_fraction() {
+        let texture = egui::TextureId::Managed(1);
+        let mut view = SplitView::new();
+        view.set_sync(Some(SplitSync::Position));
+        // Original and a half-size crop, both fitted into 400x400 panes
+        view.panes[0].set_image(PathBuf::from("full.png"), texture, (800, 800));
+        view.panes[1].set_image(PathBuf::from("crop.png"), texture, (400, 400));
+        view.pane_rects = [
+            Rect::from_min_size(Pos2::ZERO, Vec2::splat(400.0)),
+            Rect::from_min_size(Pos2::new(400.0, 0.0), Vec2::splat(200.0)),
+        ];
+
+        // Pane 1 is drawn half as large, so it moves half as far
+        view.apply_pan(Vec2::new(40.0, -20.0), 0);
+        assert_eq!(view.panes[1].pan, Vec2::new(20.0, -10.0));
+
+        // Relative mode applies the same zoom step to both panes
+        view.set_sync(Some(SplitSync::Relative));
+        view.panes[1].zoom = 2.0;
+        view.apply_zoom(0.5, 0);
+        assert_eq!(view.panes[0].zoom, 1.5);
+        assert_eq!(view.panes[1].zoom, 3.0);
+
+        view.set_sync(None);
+        view.apply_pan(Vec2::new(10.0, 0.0), 0);
+        assert_eq!(view.panes[1].pan, Vec2::new(20.0, -10.0));
+    }
+
+    #[test]
+    fn test_copy_view_state() {
+        let texture = egui::TextureId::Managed(1);
+        let mut view = SplitView::new();
+        view.panes[0].set_image(PathBuf::from("wide.png"), texture, (800, 400));
+        view.panes[1].set_image(PathBuf::from("tall.png"), texture, (400, 800));
+        view.pane_rects = [Rect::from_min_size(Pos2::ZERO, Vec2::splat(400.0)); 2];
+
+        // Pan maps by fraction of each pane's displayed size (400x800 -> 800x400)
+        view.panes[0].rotate(true);
+        view.panes[0].zoom = 2.0;
+        view.panes[0].pan = Vec2::new(30.0, -50.0);
+        view.set_active(0);
+        assert_eq!(view.copy_view_state(), 1);
+        assert_eq!(view.panes[1].rotation, 90);
+        assert_eq!(view.panes[1].zoom, 2.0);
+        assert!((view.panes[1].pan - Vec2::new(60.0, -25.0)).length() < 1e-3);
+    }
+
     #[test]
     fn test_calculate_rects() {
         let view = SplitView::new();
@@ -354,4 +559,24 @@ mod tests {
         assert!(rects[0].width() > 0.0);
         assert!(rects[1].width() > 0.0);
     }
+
+    #[test]
+    fn test_pane_image() {
+        let texture = egui::TextureId::Managed(1);
+        let mut pane = SplitPane::new();
+        pane.set_image(PathBuf::from("a.png"), texture, (400, 200));
+        pane.zoom = 2.0;
+
+        // Same file keeps the pane's own zoom, a new file resets it
+        pane.set_image(PathBuf::from("a.png"), texture, (400, 200));
+        assert_eq!(pane.zoom, 2.0);
+        pane.set_image(PathBuf::from("b.png"), texture, (400, 200));
+        assert_eq!(pane.zoom, 1.0);
+
+        // Fitted into the pane and centered
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(200.0, 200.0));
+        let image = pane.image_rect(rect).unwrap();
+        assert_eq!(image.size(), Vec2::new(200.0, 100.0));
+        assert_eq!(image.center(), rect.center());
+    }
 }
@@ -1,6 +1,8 @@
 //! Spread (two-page) viewing mode for manga/comics
 
 use super::viewer::FitMode;
+use app_core::ReadingDirection;
+use std::collections::HashSet;
 
 /// Spread display mode
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
@@ -9,7 +11,7 @@ pub enum SpreadMode {
     Single,       // Single page
     SpreadRTL,    // Spread (right-to-left, manga)
     SpreadLTR,    // Spread (left-to-right, western books)
-    Auto,         // Auto-detect from filename/metadata
+    Auto,         // Spread following the configured reading direction
 }
 
 /// Page position in spread
@@ -34,9 +36,13 @@ pub struct SpreadLayout {
 pub struct SpreadViewer {
     pub mode: SpreadMode,
     pub current_spread: (Option<usize>, Option<usize>),  // (left_idx, right_idx)
-    pub first_page_single: bool,  // Cover page displayed alone
+    pub cover_page: bool,         // Cover page displayed alone: (0),(1,2),(3,4),...
     pub last_page_single: bool,   // Last page displayed alone
     pub fit_mode: FitMode,
+    /// Reading direction used by `SpreadMode::Auto`
+    pub reading_direction: ReadingDirection,
+    /// Landscape pages, always displayed as a single centered page
+    pub wide_pages: HashSet<usize>,
 }
 
 impl Default for SpreadViewer {
@@ -50,12 +56,49 @@ impl SpreadViewer {
         Self {
             mode: SpreadMode::Single,
             current_spread: (None, None),
-            first_page_single: true,
+            cover_page: true,
             last_page_single: true,
             fit_mode: FitMode::FitToWindow,
+            reading_direction: ReadingDirection::LeftToRight,
+            wide_pages: HashSet::new(),
         }
     }
 
+    /// Whether a page of the given size should be displayed alone
+    pub fn is_wide(width: u32, height: u32) -> bool {
+        width > height
+    }
+
+    /// Whether the earlier page is displayed on the right
+    pub fn is_rtl(&self) -> bool {
+        match self.mode {
+            SpreadMode::SpreadRTL => true,
+            SpreadMode::SpreadLTR | SpreadMode::Single => false,
+            SpreadMode::Auto => self.reading_direction == ReadingDirection::RightToLeft,
+        }
+    }
+
+    /// Group pages into spreads in reading order: (earlier, later)
+    pub fn spreads(&self, total: usize) -> Vec<(usize, Option<usize>)> {
+        let mut spreads = Vec::new();
+        let mut i = 0;
+        while i < total {
+            let single = (self.cover_page && i == 0)
+                || i + 1 >= total
+                || self.wide_pages.contains(&i)
+                || self.wide_pages.contains(&(i + 1))
+                || (self.last_page_single && i + 1 == total - 1);
+            if single {
+                spreads.push((i, None));
+                i += 1;
+            } else {
+                spreads.push((i, Some(i + 1)));
+                i += 2;
+            }
+        }
+        spreads
+    }
+
     /// Navigate to specified index and calculate spread pair
     pub fn go_to(&mut self, index: usize, total: usize) -> (Option<usize>, Option<usize>) {
         if total == 0 {
@@ -68,48 +111,17 @@ impl SpreadViewer {
                 self.current_spread = (Some(index), None);
             }
             SpreadMode::SpreadRTL | SpreadMode::SpreadLTR | SpreadMode::Auto => {
-                // Cover page alone
-                if self.first_page_single && index == 0 {
-                    self.current_spread = (Some(0), None);
-                    return self.current_spread;
-                }
-
-                // Last page alone
-                if self.last_page_single && index == total - 1 && total > 1 {
-                    self.current_spread = (Some(total - 1), None);
-                    return self.current_spread;
-                }
-
-                // Calculate spread pair
-                let adjusted_index = if self.first_page_single {
-                    index.saturating_sub(1)
-                } else {
-                    index
-                };
-                let pair_start = (adjusted_index / 2) * 2;
-                let real_start = if self.first_page_single { pair_start + 1 } else { pair_start };
-
-                let left_idx = real_start;
-                let right_idx = real_start + 1;
-
-                // Range check
-                let left = if left_idx < total { Some(left_idx) } else { None };
-                let right = if right_idx < total {
-                    // Check if last page should be single
-                    if self.last_page_single && right_idx == total - 1 {
-                        None
-                    } else {
-                        Some(right_idx)
-                    }
-                } else {
-                    None
-                };
-
-                // Swap left/right for RTL/LTR
-                self.current_spread = match self.mode {
-                    SpreadMode::SpreadRTL | SpreadMode::Auto => (right, left),
-                    SpreadMode::SpreadLTR => (left, right),
-                    _ => (left, right),
+                let index = index.min(total - 1);
+                let (earlier, later) = self.spreads(total)
+                    .into_iter()
+                    .find(|&(first, second)| first == index || second == Some(index))
+                    .unwrap_or((index, None));
+
+                // RTL: the earlier page sits on the right
+                self.current_spread = match later {
+                    Some(later) if self.is_rtl() => (Some(later), Some(earlier)),
+                    Some(later) => (Some(earlier), Some(later)),
+                    None => (Some(earlier), None),
                 };
             }
         }
@@ -136,15 +148,14 @@ impl SpreadViewer {
 
     /// Move to previous page/spread
     pub fn prev(&mut self, total: usize) -> (Option<usize>, Option<usize>) {
-        let current_min = self.current_spread.0.or(self.current_spread.1);
+        let current_min = match (self.current_spread.0, self.current_spread.1) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
 
         if let Some(idx) = current_min {
-            let step = match self.mode {
-                SpreadMode::Single => 1,
-                _ => 2,
-            };
-            let prev_idx = idx.saturating_sub(step);
-            self.go_to(prev_idx, total)
+            // Any page of the previous spread; go_to resolves its partner
+            self.go_to(idx.saturating_sub(1), total)
         } else {
             self.go_to(0, total)
         }
@@ -277,7 +288,7 @@ mod tests {
     fn test_spread_rtl_first_page() {
         let mut viewer = SpreadViewer::new();
         viewer.mode = SpreadMode::SpreadRTL;
-        viewer.first_page_single = true;
+        viewer.cover_page = true;
 
         let spread = viewer.go_to(0, 10);
         assert_eq!(spread, (Some(0), None)); // Cover alone
@@ -287,7 +298,7 @@ mod tests {
     fn test_spread_rtl_pair() {
         let mut viewer = SpreadViewer::new();
         viewer.mode = SpreadMode::SpreadRTL;
-        viewer.first_page_single = true;
+        viewer.cover_page = true;
         viewer.last_page_single = false;
 
         let spread = viewer.go_to(1, 10);
@@ -295,6 +306,45 @@ mod tests {
         assert_eq!(spread, (Some(2), Some(1)));
     }
 
+    #[test]
+    fn test_spread_ltr_without_cover() {
+        let mut viewer = SpreadViewer::new();
+        viewer.mode = SpreadMode::SpreadLTR;
+        viewer.cover_page = false;
+        viewer.last_page_single = false;
+
+        assert_eq!(viewer.go_to(1, 10), (Some(0), Some(1)));
+        assert_eq!(viewer.next(10), (Some(2), Some(3)));
+        assert_eq!(viewer.prev(10), (Some(0), Some(1)));
+    }
+
+    #[test]
+    fn test_auto_follows_reading_direction() {
+        let mut viewer = SpreadViewer::new();
+        viewer.mode = SpreadMode::Auto;
+        viewer.last_page_single = false;
+
+        viewer.reading_direction = ReadingDirection::RightToLeft;
+        assert_eq!(viewer.go_to(2, 10), (Some(2), Some(1)));
+
+        viewer.reading_direction = ReadingDirection::LeftToRight;
+        assert_eq!(viewer.go_to(2, 10), (Some(1), Some(2)));
+    }
+
+    #[test]
+    fn test_wide_page_single() {
+        let mut viewer = SpreadViewer::new();
+        viewer.mode = SpreadMode::SpreadRTL;
+        viewer.last_page_single = false;
+        viewer.wide_pages.insert(3);
+
+        // Page 3 is a landscape double page: (0),(1,2),(3),(4,5),...
+        assert_eq!(viewer.spreads(7), vec![(0, None), (1, Some(2)), (3, None), (4, Some(5)), (6, None)]);
+        assert_eq!(viewer.go_to(3, 7), (Some(3), None));
+        assert_eq!(viewer.next(7), (Some(5), Some(4)));
+        assert_eq!(viewer.prev(7), (Some(3), None));
+    }
+
     #[test]
     fn test_next_prev() {
         let mut viewer = SpreadViewer::new();
@@ -0,0 +1,137 @@
+//! Tag sidebar for browsing files by tag
+//! Selecting several tags narrows results to files carrying all of them
+
+use egui::Ui;
+
+/// A tag with the number of files carrying it
+#[derive(Debug, Clone)]
+pub struct TagEntry {
+    pub tag_id: i64,
+    pub name: String,
+    /// 0xAARRGGBB
+    pub color: Option<u32>,
+    pub count: usize,
+}
+
+/// Action returned from tag panel interaction
+#[derive(Debug, Clone)]
+pub enum TagPanelAction {
+    /// Selection changed (tag toggled); search with the current selection
+    Toggle(i64),
+    /// Selection cleared; return to folder browsing
+    Clear,
+}
+
+/// Tag panel component
+#[derive(Default)]
+pub struct TagPanel {
+    pub tags: Vec<TagEntry>,
+    /// Selected tag ids (AND-ed together)
+    pub selected: Vec<i64>,
+}
+
+impl TagPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the tag list, dropping selections for tags that no longer exist
+    pub fn set_tags(&mut self, tags: Vec<TagEntry>) {
+        self.selected.retain(|id| tags.iter().any(|t| t.tag_id == *id));
+        self.tags = tags;
+    }
+
+    pub fn toggle(&mut self, tag_id: i64) {
+        if let Some(pos) = self.selected.iter().position(|&id| id == tag_id) {
+            self.selected.remove(pos);
+        } else {
+            self.selected.push(tag_id);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.selected.clear();
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.selected.is_empty()
+    }
+
+    /// Names of the selected tags, in selection order
+    pub fn selected_names(&self) -> Vec<String> {
+        self.selected.iter()
+            .filter_map(|id| self.tags.iter().find(|t| t.tag_id == *id))
+            .map(|t| t.name.clone())
+            .collect()
+    }
+
+    /// Render the tag list
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<TagPanelAction> {
+        let mut action = None;
+
+        ui.horizontal(|ui| {
+            ui.strong("Tags");
+            if self.is_active() && ui.small_button("Clear").clicked() {
+                self.clear();
+                action = Some(TagPanelAction::Clear);
+            }
+        });
+
+        if self.tags.is_empty() {
+            ui.weak("No tags");
+            return action;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt("tag_panel_scroll")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for tag in &self.tags {
+                    let is_selected = self.selected.contains(&tag.tag_id);
+                    let mut text = egui::RichText::new(format!("{} ({})", tag.name, tag.count));
+                    if let Some(argb) = tag.color {
+                        let [_, r, g, b] = argb.to_be_bytes();
+                        text = text.color(egui::Color32::from_rgb(r, g, b));
+                    }
+                    if ui.selectable_label(is_selected, text).clicked() {
+                        action = Some(TagPanelAction::Toggle(tag.tag_id));
+                    }
+                }
+            });
+
+        if let Some(TagPanelAction::Toggle(tag_id)) = action {
+            self.toggle(tag_id);
+            if !self.is_active() {
+                action = Some(TagPanelAction::Clear);
+            }
+        }
+
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(tag_id: i64, name: &str) -> TagEntry {
+        TagEntry { tag_id, name: name.to_string(), color: None, count: 0 }
+    }
+
+    #[test]
+    fn test_toggle_and_refresh() {
+        let mut panel = TagPanel::new();
+        panel.set_tags(vec![tag(1, "cat"), tag(2, "dog")]);
+
+        panel.toggle(2);
+        panel.toggle(1);
+        assert_eq!(panel.selected_names(), vec!["dog", "cat"]);
+
+        panel.toggle(2);
+        assert_eq!(panel.selected, vec![1]);
+
+        // Tag 1 deleted elsewhere
+        panel.set_tags(vec![tag(2, "dog")]);
+        assert!(!panel.is_active());
+    }
+}
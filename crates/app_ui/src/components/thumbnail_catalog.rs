@@ -1,9 +1,28 @@
 //! Thumbnail catalog component for right panel
 //! Displays image thumbnails in a grid layout
 
+use super::toolbar::SortMode;
+use app_fs::{SortBy, SortOrder};
 use egui::{Ui, Vec2, Rect, Response, TextureHandle};
+use std::collections::HashSet;
+use std::ops::Range;
 use std::path::PathBuf;
 
+/// Rows above and below the viewport whose thumbnails are loaded ahead of scrolling
+const PREFETCH_ROWS: usize = 2;
+
+/// Cell size beyond the thumbnail: padding across, padding and label down
+const ITEM_EXTRA: Vec2 = Vec2::new(8.0, 28.0);
+
+/// Gap between grid cells
+const ITEM_SPACING: f32 = 8.0;
+
+/// Zoom factor a Ctrl+scroll gesture must reach before the size steps
+const ZOOM_STEP_THRESHOLD: f32 = 1.1;
+
+/// Seconds without zoom input after which a Ctrl+scroll gesture has ended
+const ZOOM_GESTURE_GAP: f64 = 0.3;
+
 /// Action returned from thumbnail catalog interaction
 #[derive(Debug, Clone)]
 pub enum CatalogAction {
@@ -15,6 +34,61 @@ pub enum CatalogAction {
     GoToParent,
     /// Navigation action
     Navigate(NavigateDirection),
+    /// Switch to the next larger (+1) or smaller (-1) thumbnail size (Ctrl+scroll)
+    ResizeThumbnails(i32),
+    /// The Ctrl+scroll gesture that resized the thumbnails has ended
+    ResizeFinished,
+}
+
+/// Ctrl+scroll gesture state: one size step per gesture
+#[derive(Debug)]
+struct ZoomGesture {
+    /// Zoom accumulated since the gesture started
+    factor: f32,
+    /// Whether this gesture already stepped the size
+    stepped: bool,
+    /// Time of the last zoom input
+    last_input: f64,
+}
+
+impl ZoomGesture {
+    fn new() -> Self {
+        Self { factor: 1.0, stepped: false, last_input: 0.0 }
+    }
+
+    /// Feed one frame's zoom delta; returns the action for this frame
+    fn update(&mut self, zoom: f32, time: f64) -> Option<CatalogAction> {
+        if zoom != 1.0 {
+            self.factor *= zoom;
+            self.last_input = time;
+            if self.stepped {
+                return None;
+            }
+            let step = if self.factor >= ZOOM_STEP_THRESHOLD {
+                1
+            } else if self.factor <= 1.0 / ZOOM_STEP_THRESHOLD {
+                -1
+            } else {
+                return None;
+            };
+            self.stepped = true;
+            return Some(CatalogAction::ResizeThumbnails(step));
+        }
+
+        if self.is_active() && time - self.last_input >= ZOOM_GESTURE_GAP {
+            let stepped = self.stepped;
+            *self = Self::new();
+            if stepped {
+                return Some(CatalogAction::ResizeFinished);
+            }
+        }
+        None
+    }
+
+    /// Whether a gesture is in progress
+    fn is_active(&self) -> bool {
+        self.stepped || self.factor != 1.0
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -29,6 +103,49 @@ pub enum NavigateDirection {
     End,
 }
 
+/// Catalog filter: minimum star rating and/or marked files only
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CatalogFilter {
+    /// Minimum star rating (0 = off)
+    pub min_rating: i32,
+    /// Only show marked files
+    pub marked_only: bool,
+}
+
+impl CatalogFilter {
+    pub fn is_active(&self) -> bool {
+        self.min_rating > 0 || self.marked_only
+    }
+
+    /// Whether an entry passes the filter (folders always pass)
+    pub fn matches(&self, is_dir: bool, rating: i32, marked: bool) -> bool {
+        is_dir || (rating >= self.min_rating && (marked || !self.marked_only))
+    }
+
+    /// Filter controls for the catalog header; returns true when changed
+    pub fn ui(&mut self, ui: &mut Ui) -> bool {
+        let before = *self;
+
+        let label = |rating: i32| {
+            if rating == 0 {
+                "All ratings".to_string()
+            } else {
+                format!("≥ {}", "★".repeat(rating as usize))
+            }
+        };
+        egui::ComboBox::from_id_salt("catalog_rating_filter")
+            .selected_text(label(self.min_rating))
+            .show_ui(ui, |ui| {
+                for rating in 0..=5 {
+                    ui.selectable_value(&mut self.min_rating, rating, label(rating));
+                }
+            });
+        ui.checkbox(&mut self.marked_only, "Marked");
+
+        *self != before
+    }
+}
+
 /// A thumbnail item in the catalog
 #[derive(Clone)]
 pub struct ThumbnailItem {
@@ -37,6 +154,14 @@ pub struct ThumbnailItem {
     pub texture: Option<TextureHandle>,
     pub is_folder: bool,
     pub is_image: bool,
+    /// File has a comment (shows an indicator)
+    pub has_comment: bool,
+    /// Star rating (0 = unrated, shows nothing)
+    pub rating: i32,
+    /// Color label (drawn as a border)
+    pub label: Option<egui::Color32>,
+    /// Target of a symbolic link or junction (shows an indicator)
+    pub link_target: Option<PathBuf>,
 }
 
 impl ThumbnailItem {
@@ -52,6 +177,10 @@ impl ThumbnailItem {
             texture: None,
             is_folder,
             is_image,
+            has_comment: false,
+            rating: 0,
+            label: None,
+            link_target: None,
         }
     }
 
@@ -60,16 +189,64 @@ impl ThumbnailItem {
     }
 }
 
+/// Keys offered in the catalog's sort header
+const HEADER_KEYS: [(SortBy, &str); 3] = [
+    (SortBy::Name, "名前"),
+    (SortBy::Modified, "日付"),
+    (SortBy::Size, "サイズ"),
+];
+
+/// Sort after clicking `key` in the header: the active key flips direction,
+/// another key starts ascending
+pub fn header_click(current: SortMode, key: SortBy) -> SortMode {
+    if current.to_sort().0 == key {
+        current.reversed()
+    } else {
+        SortMode::from_sort(key, SortOrder::Ascending)
+    }
+}
+
+/// Name / date / size header; returns the new sort when one was clicked
+pub fn sort_header(ui: &mut Ui, current: SortMode) -> Option<SortMode> {
+    let (active, order) = current.to_sort();
+    let mut clicked = None;
+    for (key, label) in HEADER_KEYS {
+        let text = if key == active {
+            format!("{} {}", label, if order == SortOrder::Ascending { "▲" } else { "▼" })
+        } else {
+            label.to_string()
+        };
+        if ui.selectable_label(key == active, text).clicked() {
+            clicked = Some(header_click(current, key));
+        }
+    }
+    clicked
+}
+
 /// Thumbnail catalog component
 pub struct ThumbnailCatalog {
     /// Thumbnail size
     pub thumbnail_size: f32,
     /// Currently selected index
     pub selected: Option<usize>,
+    /// Other highlighted indices (multi-select)
+    pub multi_selected: HashSet<usize>,
     /// Number of columns (calculated from width)
     columns: usize,
     /// Number of visible rows
     visible_rows: usize,
+    /// Section headers: (first item index, title), in item order
+    pub sections: Vec<(usize, String)>,
+    /// Scroll offset, content size and viewport size from the last frame
+    scroll_offset: Vec2,
+    content_size: Vec2,
+    viewport_size: Vec2,
+    /// Offset to apply on the next frame (nav.scroll_x / nav.scroll_y)
+    pending_scroll: Option<Vec2>,
+    /// Items on screen in the last frame
+    visible: Range<usize>,
+    /// Ctrl+scroll gesture in progress
+    zoom_gesture: ZoomGesture,
 }
 
 impl Default for ThumbnailCatalog {
@@ -83,8 +260,16 @@ impl ThumbnailCatalog {
         Self {
             thumbnail_size: 128.0,
             selected: None,
+            multi_selected: HashSet::new(),
             columns: 4,
             visible_rows: 4,
+            sections: Vec::new(),
+            scroll_offset: Vec2::ZERO,
+            content_size: Vec2::ZERO,
+            viewport_size: Vec2::ZERO,
+            pending_scroll: None,
+            visible: 0..0,
+            zoom_gesture: ZoomGesture::new(),
         }
     }
 
@@ -105,11 +290,63 @@ impl ThumbnailCatalog {
 
     /// Calculate grid dimensions
     fn calculate_grid(&mut self, available_width: f32, available_height: f32) {
-        let item_width = self.thumbnail_size + 16.0; // padding
-        let item_height = self.thumbnail_size + 32.0; // padding + label
+        self.columns = (available_width / self.column_width()).max(1.0) as usize;
+        self.visible_rows = (available_height / self.row_height()).max(1.0) as usize;
+    }
+
+    /// Size of one grid cell (thumbnail and label)
+    fn item_size(&self) -> Vec2 {
+        Vec2::splat(self.thumbnail_size) + ITEM_EXTRA
+    }
+
+    /// Height of one grid row (cell and spacing)
+    pub fn row_height(&self) -> f32 {
+        self.item_size().y + ITEM_SPACING
+    }
+
+    /// Width of one grid column (cell and spacing)
+    pub fn column_width(&self) -> f32 {
+        self.item_size().x + ITEM_SPACING
+    }
+
+    /// Height of the fully visible rows
+    pub fn page_height(&self) -> f32 {
+        self.visible_rows as f32 * self.row_height()
+    }
+
+    /// Size of the scrolled viewport
+    pub fn viewport_size(&self) -> Vec2 {
+        self.viewport_size
+    }
+
+    /// Items on screen in the last frame plus a prefetch margin, clamped to `item_count`
+    /// Only these need thumbnail textures.
+    pub fn visible_range(&self, item_count: usize) -> Range<usize> {
+        let margin = PREFETCH_ROWS * self.columns.max(1);
+        let end = if self.visible.is_empty() {
+            // Nothing rendered yet: assume the first screen
+            (self.visible_rows + 1) * self.columns.max(1)
+        } else {
+            self.visible.end
+        };
+        let start = self.visible.start.saturating_sub(margin).min(item_count);
+        start..(end + margin).min(item_count)
+    }
 
-        self.columns = (available_width / item_width).max(1.0) as usize;
-        self.visible_rows = (available_height / item_height).max(1.0) as usize;
+    /// Scroll by `delta` points, clamped to the content; applied on the next frame
+    /// The selection is left alone
+    pub fn scroll_by(&mut self, delta: Vec2) {
+        let base = self.pending_scroll.unwrap_or(self.scroll_offset);
+        let max = (self.content_size - self.viewport_size).max(Vec2::ZERO);
+        self.pending_scroll = Some((base + delta).clamp(Vec2::ZERO, max));
+    }
+
+    /// Scroll so the item moved from `old_index` to `new_index` (by a re-sort)
+    /// stays at the same place on screen
+    pub fn keep_in_view(&mut self, old_index: usize, new_index: usize) {
+        let columns = self.columns.max(1);
+        let rows = (new_index / columns) as f32 - (old_index / columns) as f32;
+        self.scroll_by(Vec2::new(0.0, rows * self.row_height()));
     }
 
     /// Navigate selection
@@ -178,40 +415,116 @@ impl ThumbnailCatalog {
         // Handle keyboard navigation
         action = self.handle_keyboard(ui, items.len());
 
-        egui::ScrollArea::vertical()
-            .auto_shrink([false, false])
-            .show(ui, |ui| {
-                let item_width = self.thumbnail_size + 16.0;
-                let item_height = self.thumbnail_size + 32.0;
-
-                egui::Grid::new("thumbnail_grid")
-                    .num_columns(self.columns)
-                    .spacing(Vec2::new(8.0, 8.0))
-                    .show(ui, |ui| {
-                        for (idx, item) in items.iter().enumerate() {
-                            let is_selected = self.selected == Some(idx);
-
-                            let response = self.render_thumbnail_item(ui, item, is_selected, idx);
-
-                            // Handle clicks
-                            if response.clicked() {
-                                self.selected = Some(idx);
-                                action = Some(CatalogAction::Select(idx));
-                            }
+        // Ctrl+scroll over the catalog switches thumbnail size, one step per gesture
+        let (zoom, time) = ui.input(|i| (i.zoom_delta(), i.time));
+        let zoom = if ui.rect_contains_pointer(ui.max_rect()) { zoom } else { 1.0 };
+        if let Some(zoom_action) = self.zoom_gesture.update(zoom, time) {
+            action = Some(zoom_action);
+        }
+        if self.zoom_gesture.is_active() {
+            // Wake up to notice the end of the gesture
+            ui.ctx().request_repaint_after(std::time::Duration::from_secs_f64(ZOOM_GESTURE_GAP));
+        }
 
-                            if response.double_clicked() {
-                                action = Some(CatalogAction::Open(idx));
-                            }
+        // Sections (e.g. timeline dates) are rendered as headers between grids
+        let sections = std::mem::take(&mut self.sections);
 
-                            // End row
-                            if (idx + 1) % self.columns == 0 {
-                                ui.end_row();
+        let mut scroll_area = egui::ScrollArea::vertical().auto_shrink([false, false]);
+        if let Some(offset) = self.pending_scroll.take() {
+            scroll_area = scroll_area.scroll_offset(offset);
+        }
+
+        let output = if sections.is_empty() {
+            // Only the rows in view are laid out
+            let columns = self.columns.max(1);
+            let total_rows = items.len().div_ceil(columns);
+            let item_height = self.item_size().y;
+            ui.scope(|ui| {
+                ui.spacing_mut().item_spacing = Vec2::splat(ITEM_SPACING);
+                scroll_area.show_rows(ui, item_height, total_rows, |ui, rows| {
+                    let range = (rows.start * columns).min(items.len())..(rows.end * columns).min(items.len());
+                    self.visible = range.clone();
+                    for row in range.step_by(columns) {
+                        ui.horizontal(|ui| {
+                            for idx in row..(row + columns).min(items.len()) {
+                                self.render_item(ui, items, idx, &mut action);
                             }
+                        });
+                    }
+                })
+            })
+            .inner
+        } else {
+            scroll_area.show(ui, |ui| {
+                self.visible = 0..0;
+                for (i, (start, title)) in sections.iter().enumerate() {
+                    let end = sections.get(i + 1).map(|(s, _)| *s).unwrap_or(items.len());
+                    ui.add_space(4.0);
+                    ui.heading(title);
+                    ui.separator();
+                    self.render_grid(ui, items, *start..end, ("thumbnail_grid", i), &mut action);
+                }
+            })
+        };
+
+        self.sections = sections;
+        self.scroll_offset = output.state.offset;
+        self.content_size = output.content_size;
+        self.viewport_size = output.inner_rect.size();
+
+        action
+    }
+
+    /// Render a grid of items in `range` (indices stay global)
+    fn render_grid(
+        &mut self,
+        ui: &mut Ui,
+        items: &[ThumbnailItem],
+        range: std::ops::Range<usize>,
+        id_salt: impl std::hash::Hash,
+        action: &mut Option<CatalogAction>,
+    ) {
+        let start = range.start;
+        egui::Grid::new(id_salt)
+            .num_columns(self.columns)
+            .spacing(Vec2::splat(ITEM_SPACING))
+            .show(ui, |ui| {
+                for idx in range {
+                    let response = self.render_item(ui, items, idx, action);
+
+                    // Track what is on screen (sections are laid out in full)
+                    if ui.is_rect_visible(response.rect) {
+                        if self.visible.is_empty() {
+                            self.visible = idx..idx + 1;
+                        } else {
+                            self.visible.end = idx + 1;
                         }
-                    });
+                    }
+
+                    // End row
+                    if (idx - start + 1) % self.columns == 0 {
+                        ui.end_row();
+                    }
+                }
             });
+    }
 
-        action
+    /// Render item `idx` and handle clicks on it
+    fn render_item(&mut self, ui: &mut Ui, items: &[ThumbnailItem], idx: usize, action: &mut Option<CatalogAction>) -> Response {
+        let is_selected = self.selected == Some(idx) || self.multi_selected.contains(&idx);
+        let response = self.render_thumbnail_item(ui, &items[idx], is_selected, idx);
+
+        // Handle clicks
+        if response.clicked() {
+            self.selected = Some(idx);
+            *action = Some(CatalogAction::Select(idx));
+        }
+
+        if response.double_clicked() {
+            *action = Some(CatalogAction::Open(idx));
+        }
+
+        response
     }
 
     /// Handle keyboard input
@@ -289,7 +602,7 @@ impl ThumbnailCatalog {
         is_selected: bool,
         _idx: usize,
     ) -> Response {
-        let item_size = Vec2::new(self.thumbnail_size + 8.0, self.thumbnail_size + 28.0);
+        let item_size = self.item_size();
 
         let (rect, response) = ui.allocate_exact_size(item_size, egui::Sense::click());
 
@@ -349,6 +662,50 @@ impl ThumbnailCatalog {
                 );
             }
 
+            // Comment indicator
+            if item.has_comment {
+                painter.text(
+                    thumb_rect.right_top() + Vec2::new(-2.0, 2.0),
+                    egui::Align2::RIGHT_TOP,
+                    "💬",
+                    egui::FontId::proportional(14.0),
+                    egui::Color32::WHITE,
+                );
+            }
+
+            // Link indicator in the bottom-right corner
+            if item.link_target.is_some() {
+                painter.text(
+                    thumb_rect.right_bottom() + Vec2::new(-2.0, -2.0),
+                    egui::Align2::RIGHT_BOTTOM,
+                    "↪",
+                    egui::FontId::proportional(14.0),
+                    egui::Color32::from_rgb(120, 200, 255),
+                );
+            }
+
+            // Color label border, drawn inside the item rect so the grid doesn't shift
+            if let Some(color) = item.label {
+                painter.rect_stroke(thumb_rect.expand(1.5), 2.0, egui::Stroke::new(3.0, color));
+            }
+
+            // Rating pips in the bottom-left corner
+            if item.rating > 0 {
+                let font = egui::FontId::proportional((self.thumbnail_size * 0.1).clamp(8.0, 16.0));
+                let galley = painter.layout_no_wrap(
+                    "★".repeat(item.rating.min(5) as usize),
+                    font,
+                    egui::Color32::from_rgb(255, 200, 50),
+                );
+                let pos = thumb_rect.left_bottom() + Vec2::new(2.0, -2.0 - galley.size().y);
+                painter.rect_filled(
+                    Rect::from_min_size(pos, galley.size()).expand(1.0),
+                    2.0,
+                    egui::Color32::from_black_alpha(140),
+                );
+                painter.galley(pos, galley, egui::Color32::WHITE);
+            }
+
             // File name label
             let label_rect = Rect::from_min_size(
                 egui::pos2(rect.min.x, thumb_rect.max.y + 2.0),
@@ -378,7 +735,10 @@ impl ThumbnailCatalog {
             );
         }
 
-        response
+        match item.link_target {
+            Some(ref target) => response.on_hover_text(format!("→ {}", target.display())),
+            None => response,
+        }
     }
 
     /// Get current column count
@@ -391,3 +751,86 @@ impl ThumbnailCatalog {
         self.selected
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_filter() {
+        let filter = CatalogFilter { min_rating: 4, marked_only: true };
+        assert!(filter.is_active());
+        assert!(filter.matches(true, 0, false));
+        assert!(filter.matches(false, 5, true));
+        assert!(!filter.matches(false, 5, false));
+        assert!(!filter.matches(false, 3, true));
+
+        let rating_only = CatalogFilter { min_rating: 4, marked_only: false };
+        assert!(rating_only.matches(false, 4, false));
+        assert!(!CatalogFilter::default().is_active());
+    }
+
+    #[test]
+    fn test_zoom_gesture_steps_once() {
+        let mut gesture = ZoomGesture::new();
+        assert!(gesture.update(1.05, 0.0).is_none());
+        assert!(matches!(gesture.update(1.05, 0.02), Some(CatalogAction::ResizeThumbnails(1))));
+        // The rest of the gesture does not step again
+        assert!(gesture.update(1.2, 0.04).is_none());
+        assert!(gesture.update(1.0, 0.1).is_none());
+        assert!(matches!(gesture.update(1.0, 0.5), Some(CatalogAction::ResizeFinished)));
+        assert!(!gesture.is_active());
+
+        // A new gesture steps again
+        assert!(matches!(gesture.update(0.8, 1.0), Some(CatalogAction::ResizeThumbnails(-1))));
+    }
+
+    #[test]
+    fn test_visible_range() {
+        let mut catalog = ThumbnailCatalog::new();
+        catalog.columns = 4;
+        catalog.visible_rows = 3;
+
+        // Before the first frame: the first screen plus the margin
+        assert_eq!(catalog.visible_range(1000), 0..24);
+
+        catalog.visible = 40..56;
+        assert_eq!(catalog.visible_range(1000), 32..64);
+        assert_eq!(catalog.visible_range(50), 32..50);
+    }
+
+    #[test]
+    fn test_scroll_by_clamps() {
+        let mut catalog = ThumbnailCatalog::new();
+        catalog.content_size = Vec2::new(400.0, 1000.0);
+        catalog.viewport_size = Vec2::new(400.0, 300.0);
+
+        catalog.scroll_by(Vec2::new(50.0, 500.0));
+        catalog.scroll_by(Vec2::new(0.0, 500.0));
+        // Nothing to scroll horizontally; vertically stops at the last screen
+        assert_eq!(catalog.pending_scroll, Some(Vec2::new(0.0, 700.0)));
+
+        catalog.scroll_by(Vec2::new(0.0, -1000.0));
+        assert_eq!(catalog.pending_scroll, Some(Vec2::ZERO));
+
+        // Row stride as laid out: 128px thumbnail, 28px label area, 8px spacing
+        assert_eq!(catalog.row_height(), 164.0);
+        assert_eq!(catalog.column_width(), 144.0);
+    }
+
+    #[test]
+    fn test_sort_header() {
+        assert_eq!(header_click(SortMode::Name, SortBy::Name), SortMode::NameDesc);
+        assert_eq!(header_click(SortMode::NameDesc, SortBy::Name), SortMode::Name);
+        assert_eq!(header_click(SortMode::NameDesc, SortBy::Modified), SortMode::Modified);
+        assert_eq!(header_click(SortMode::TypeDesc, SortBy::Size), SortMode::Size);
+
+        // The selected item moving from row 1 to row 6 scrolls five rows down
+        let mut catalog = ThumbnailCatalog::new();
+        catalog.columns = 4;
+        catalog.content_size = Vec2::new(400.0, 5000.0);
+        catalog.viewport_size = Vec2::new(400.0, 300.0);
+        catalog.keep_in_view(5, 25);
+        assert_eq!(catalog.pending_scroll, Some(Vec2::new(0.0, 5.0 * 164.0)));
+    }
+}
@@ -1,5 +1,6 @@
 //! Toolbar component with navigation, path input, and file operations
 
+use app_fs::{SortBy, SortOrder};
 use egui::{Ui, ComboBox};
 
 /// Toolbar state for path editing
@@ -10,6 +11,12 @@ pub struct ToolbarState {
     pub editing_path: bool,
     /// Current sort mode
     pub sort_mode: SortMode,
+    /// Group folders before files
+    pub dirs_first: bool,
+    /// File name search text
+    pub search_text: String,
+    /// Request keyboard focus for the search box (app.search)
+    pub focus_search: bool,
 }
 
 impl Default for ToolbarState {
@@ -18,6 +25,9 @@ impl Default for ToolbarState {
             path_text: String::new(),
             editing_path: false,
             sort_mode: SortMode::Name,
+            dirs_first: true,
+            search_text: String::new(),
+            focus_search: false,
         }
     }
 }
@@ -60,6 +70,43 @@ impl SortMode {
             SortMode::TypeDesc => "Type ↓",
         }
     }
+
+    pub fn from_sort(sort_by: SortBy, sort_order: SortOrder) -> Self {
+        let descending = sort_order == SortOrder::Descending;
+        match (sort_by, descending) {
+            (SortBy::Name, false) => SortMode::Name,
+            (SortBy::Name, true) => SortMode::NameDesc,
+            (SortBy::Size, false) => SortMode::Size,
+            (SortBy::Size, true) => SortMode::SizeDesc,
+            (SortBy::Modified, false) => SortMode::Modified,
+            (SortBy::Modified, true) => SortMode::ModifiedDesc,
+            (SortBy::Extension, false) => SortMode::Type,
+            (SortBy::Extension, true) => SortMode::TypeDesc,
+        }
+    }
+
+    pub fn to_sort(self) -> (SortBy, SortOrder) {
+        match self {
+            SortMode::Name => (SortBy::Name, SortOrder::Ascending),
+            SortMode::NameDesc => (SortBy::Name, SortOrder::Descending),
+            SortMode::Size => (SortBy::Size, SortOrder::Ascending),
+            SortMode::SizeDesc => (SortBy::Size, SortOrder::Descending),
+            SortMode::Modified => (SortBy::Modified, SortOrder::Ascending),
+            SortMode::ModifiedDesc => (SortBy::Modified, SortOrder::Descending),
+            SortMode::Type => (SortBy::Extension, SortOrder::Ascending),
+            SortMode::TypeDesc => (SortBy::Extension, SortOrder::Descending),
+        }
+    }
+
+    /// Same key, opposite direction
+    pub fn reversed(self) -> Self {
+        let (sort_by, sort_order) = self.to_sort();
+        let reversed = match sort_order {
+            SortOrder::Ascending => SortOrder::Descending,
+            SortOrder::Descending => SortOrder::Ascending,
+        };
+        Self::from_sort(sort_by, reversed)
+    }
 }
 
 /// Toolbar component
@@ -101,7 +148,7 @@ impl Toolbar {
 
             // === Path input ===
             let path_response = ui.add_sized(
-                [ui.available_width() - 300.0, 20.0],
+                [ui.available_width() - 490.0, 20.0],
                 egui::TextEdit::singleline(&mut state.path_text)
                     .hint_text("Enter path...")
                     .font(egui::FontId::proportional(13.0))
@@ -120,6 +167,25 @@ impl Toolbar {
                 state.editing_path = true;
             }
 
+            // === Search ===
+            let search_response = ui.add_sized(
+                [150.0, 20.0],
+                egui::TextEdit::singleline(&mut state.search_text)
+                    .hint_text("🔍 Search...")
+                    .font(egui::FontId::proportional(13.0))
+            );
+            if state.focus_search {
+                search_response.request_focus();
+                state.focus_search = false;
+            }
+            if search_response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                action = Some(ToolbarAction::Search(state.search_text.trim().to_string()));
+            }
+
+            if ui.button("🕘").on_hover_text("Recently viewed").clicked() {
+                action = Some(ToolbarAction::Recent);
+            }
+
             ui.separator();
 
             // === File operations ===
@@ -152,6 +218,10 @@ impl Toolbar {
                             action = Some(ToolbarAction::Sort(mode));
                         }
                     }
+                    ui.separator();
+                    if ui.checkbox(&mut state.dirs_first, "Folders first").changed() {
+                        action = Some(ToolbarAction::Sort(state.sort_mode));
+                    }
                 });
 
             // === Right-aligned buttons ===
@@ -181,6 +251,10 @@ pub enum ToolbarAction {
     UpFolder,
     Refresh,
     NavigateTo(String),
+    /// Search file names (empty = leave search results)
+    Search(String),
+    /// Show recently viewed files
+    Recent,
 
     // File operations
     NewFolder,
@@ -0,0 +1,161 @@
+//! Recycle bin window
+//! Lists trashed items with their original location; checked items can be
+//! restored or deleted for good
+
+use super::dialogs::{Dialog, DialogResult};
+use app_fs::TrashEntry;
+use egui::{Context, Window};
+
+/// What to do with the checked items
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrashAction {
+    Restore(Vec<TrashEntry>),
+    Purge(Vec<TrashEntry>),
+    Refresh,
+}
+
+/// Recycle bin window
+pub struct TrashView {
+    pub open: bool,
+    pub items: Vec<TrashEntry>,
+    /// Checked state, parallel to `items`
+    pub checked: Vec<bool>,
+    /// Shown instead of the list when the trash can't be listed
+    pub error: Option<String>,
+}
+
+impl TrashView {
+    pub fn new(items: Vec<TrashEntry>) -> Self {
+        let mut view = Self { open: true, items: Vec::new(), checked: Vec::new(), error: None };
+        view.set_items(items);
+        view
+    }
+
+    /// Window that only explains why the trash can't be shown
+    pub fn unavailable(error: String) -> Self {
+        Self { open: true, items: Vec::new(), checked: Vec::new(), error: Some(error) }
+    }
+
+    pub fn set_items(&mut self, items: Vec<TrashEntry>) {
+        self.checked = vec![false; items.len()];
+        self.items = items;
+        self.error = None;
+    }
+
+    /// Items currently checked
+    pub fn checked_items(&self) -> Vec<TrashEntry> {
+        self.items.iter()
+            .zip(&self.checked)
+            .filter(|(_, checked)| **checked)
+            .map(|(item, _)| item.clone())
+            .collect()
+    }
+}
+
+impl Dialog for TrashView {
+    type Output = TrashAction;
+
+    fn ui(&mut self, ctx: &Context) -> DialogResult<TrashAction> {
+        if !self.open {
+            return DialogResult::None;
+        }
+
+        let mut result = DialogResult::None;
+        let mut open = self.open;
+
+        Window::new("Recycle Bin")
+            .open(&mut open)
+            .default_size([560.0, 400.0])
+            .show(ctx, |ui| {
+                if let Some(ref error) = self.error {
+                    ui.label(error);
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} items", self.items.len()));
+                    if ui.button("Refresh").clicked() {
+                        result = DialogResult::Ok(TrashAction::Refresh);
+                    }
+                });
+                ui.separator();
+
+                if self.items.is_empty() {
+                    ui.label("The recycle bin is empty");
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(ui.available_height() - 32.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("trash_items").striped(true).show(ui, |ui| {
+                            for (item, checked) in self.items.iter().zip(self.checked.iter_mut()) {
+                                ui.checkbox(checked, &item.name);
+                                let location = item.original_path.parent()
+                                    .map(|p| p.to_string_lossy().to_string())
+                                    .unwrap_or_default();
+                                ui.label(egui::RichText::new(location).weak());
+                                ui.end_row();
+                            }
+                        });
+                    });
+
+                ui.separator();
+                let checked = self.checked_items();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!checked.is_empty(), egui::Button::new(format!("Restore {}", checked.len()))).clicked() {
+                        result = DialogResult::Ok(TrashAction::Restore(checked.clone()));
+                    }
+                    let purge = egui::Button::new(
+                        egui::RichText::new(format!("Delete {} permanently", checked.len())).color(egui::Color32::RED),
+                    );
+                    if ui.add_enabled(!checked.is_empty(), purge).clicked() {
+                        result = DialogResult::Ok(TrashAction::Purge(checked.clone()));
+                    }
+                });
+            });
+
+        if !open {
+            self.open = false;
+            result = DialogResult::Cancel;
+        }
+
+        result
+    }
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entry(name: &str) -> TrashEntry {
+        TrashEntry {
+            id: name.into(),
+            name: name.to_string(),
+            original_path: PathBuf::from("/pictures").join(name),
+            deleted: 0,
+        }
+    }
+
+    #[test]
+    fn test_checked_items() {
+        let mut view = TrashView::new(vec![entry("a.jpg"), entry("b.jpg"), entry("c.jpg")]);
+        assert!(view.checked_items().is_empty());
+        view.checked[0] = true;
+        view.checked[2] = true;
+        assert_eq!(view.checked_items(), vec![entry("a.jpg"), entry("c.jpg")]);
+
+        // Refreshing resets the checks
+        view.set_items(vec![entry("b.jpg")]);
+        assert!(view.checked_items().is_empty());
+    }
+}
@@ -45,6 +45,15 @@ pub struct ImageViewer {
     /// Fit mode
     pub fit_mode: FitMode,
 
+    /// Keep the image from being panned out of view
+    pub clamp_pan: bool,
+
+    /// Snap image edges flush to the viewport edges when panned close to them
+    pub snap_to_edges: bool,
+
+    /// Column of the N-type (zigzag) scroll, counted from the left
+    n_scroll_column: usize,
+
     /// Is dragging (panning)
     drag_start: Option<Pos2>,
     pan_start: Vec2,
@@ -71,6 +80,12 @@ pub struct ImageViewer {
 
     // Seek bar state
     seek_dragging: bool,
+
+    /// Text of the zoom percentage field while it is being edited
+    zoom_edit: Option<String>,
+
+    /// Fit mode to go back to when actual pixels is toggled off
+    pre_actual_fit: Option<FitMode>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -92,6 +107,9 @@ impl ImageViewer {
             flip_h: false,
             flip_v: false,
             fit_mode: FitMode::FitToWindow,
+            clamp_pan: true,
+            snap_to_edges: true,
+            n_scroll_column: 0,
             drag_start: None,
             pan_start: Vec2::ZERO,
             // Overlay
@@ -105,6 +123,8 @@ impl ImageViewer {
             total_files: 0,
             slideshow_active: false,
             seek_dragging: false,
+            zoom_edit: None,
+            pre_actual_fit: None,
         }
     }
 
@@ -115,6 +135,13 @@ impl ImageViewer {
         self.reset_view();
     }
 
+    /// Swap in a new texture for the same image (e.g. full resolution after a
+    /// preview) keeping zoom, pan and rotation
+    pub fn replace_texture(&mut self, texture: TextureId, width: u32, height: u32) {
+        self.texture = Some(texture);
+        self.image_size = Vec2::new(width as f32, height as f32);
+    }
+
     /// Clear the current image
     pub fn clear(&mut self) {
         self.texture = None;
@@ -126,6 +153,7 @@ impl ImageViewer {
         self.zoom = 1.0;
         self.pan = Vec2::ZERO;
         self.rotation = 0;
+        self.n_scroll_column = 0;
     }
 
     /// Render the viewer with Doc 4 overlay UI
@@ -417,6 +445,14 @@ impl ImageViewer {
             self.drag_start = None;
         }
 
+        let display_size = self.calculate_display_size(rect.size());
+        if self.snap_to_edges {
+            self.pan = snap_pan(self.pan, display_size, rect.size(), SNAP_THRESHOLD);
+        }
+        if self.clamp_pan {
+            self.pan = clamp_pan(self.pan, display_size, rect.size());
+        }
+
         // Double-click to close viewer (return to browser)
         if response.double_clicked() {
             return Some(ViewerAction::Close);
@@ -480,6 +516,83 @@ impl ImageViewer {
         self.zoom = level.clamp(0.1, 10.0);
     }
 
+    /// Set the effective zoom as a percentage of the original size (clamped to 10%-1000%)
+    /// `base_scale` is the fit scale the zoom is applied on top of
+    pub fn set_zoom_percent(&mut self, percent: f32, base_scale: f32) {
+        if base_scale > 0.0 {
+            self.zoom = percent.clamp(ZOOM_PERCENT_MIN, ZOOM_PERCENT_MAX) / 100.0 / base_scale;
+        }
+    }
+
+    /// Toggle 100% zoom (view.actual_pixels)
+    /// Going to 100% keeps the image point at `anchor` (offset from the view
+    /// center, e.g. the cursor) in place; `scale` is the current on-screen
+    /// scale. Toggling again restores the fit mode used before.
+    pub fn toggle_actual_pixels(&mut self, anchor: Vec2, scale: f32) {
+        let at_actual = self.fit_mode == FitMode::OriginalSize && (self.zoom - 1.0).abs() < 1e-3;
+        if at_actual {
+            self.fit_mode = self.pre_actual_fit.take().unwrap_or(FitMode::FitToWindow);
+            self.zoom = 1.0;
+            self.pan = Vec2::ZERO;
+            return;
+        }
+
+        self.pre_actual_fit = Some(self.fit_mode);
+        self.fit_mode = FitMode::OriginalSize;
+        self.zoom = 1.0;
+        self.pan = zoom_pan_at(self.pan, anchor, scale, 1.0);
+    }
+
+    /// True while the zoom percentage field is being edited
+    pub fn is_editing_zoom(&self) -> bool {
+        self.zoom_edit.is_some()
+    }
+
+    /// Zoom percentage shown in the overlay; click it to type an exact value
+    /// Enter commits, Escape (or clicking elsewhere) cancels. Returns true when the zoom changed.
+    pub fn zoom_percent_ui(&mut self, ui: &mut Ui, rect: Rect, base_scale: f32) -> bool {
+        let id = ui.id().with("zoom_percent");
+        let percent = self.zoom * base_scale * 100.0;
+
+        let Some(text) = self.zoom_edit.as_mut() else {
+            let response = ui.allocate_rect(rect, egui::Sense::click());
+            let color = if response.hovered() { Color32::WHITE } else { Color32::LIGHT_GRAY };
+            ui.painter().text(
+                rect.right_center(),
+                Align2::RIGHT_CENTER,
+                format!("{:.0}%", percent),
+                FontId::proportional(14.0),
+                color,
+            );
+            if response.clicked() {
+                self.zoom_edit = Some(format!("{:.0}", percent));
+                ui.memory_mut(|m| m.request_focus(id));
+            }
+            return false;
+        };
+
+        let response = ui.put(
+            rect,
+            egui::TextEdit::singleline(text)
+                .id(id)
+                .font(FontId::proportional(14.0))
+                .horizontal_align(egui::Align::RIGHT),
+        );
+        if !response.lost_focus() {
+            return false;
+        }
+
+        let committed = ui.input(|i| i.key_pressed(egui::Key::Enter));
+        let text = self.zoom_edit.take().unwrap_or_default();
+        match parse_zoom_percent(&text) {
+            Some(value) if committed => {
+                self.set_zoom_percent(value, base_scale);
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Rotate left
     pub fn rotate_left(&mut self) {
         self.rotation = (self.rotation + 270) % 360;
@@ -559,6 +672,80 @@ impl ImageViewer {
         false
     }
 
+    /// N-type scroll down/forward: down the current column, then up to the top of the
+    /// next column to the right. Returns true if should go to next image (bottom-right reached)
+    pub fn scroll_n_type_down(&mut self, available: Vec2, overlap: f32) -> bool {
+        if self.image_size == Vec2::ZERO {
+            return true;
+        }
+
+        let display_size = self.calculate_display_size(available);
+        if display_size.x <= available.x && display_size.y <= available.y {
+            return true;
+        }
+
+        let max_pan = ((display_size - available) * 0.5).max(Vec2::ZERO);
+        let last_column = n_type_last_column(display_size.x, available.x, overlap);
+        let column = self.n_scroll_column.min(last_column);
+
+        if self.pan.y > -max_pan.y + 1.0 {
+            // Continue down the current column
+            self.pan.y = (self.pan.y - (available.y - overlap).max(1.0)).max(-max_pan.y);
+            self.pan.x = n_type_column_pan(column, max_pan.x, available.x, overlap);
+            self.n_scroll_column = column;
+            return false;
+        }
+
+        if column < last_column {
+            // Bottom of a column: jump to the top of the next one
+            self.n_scroll_column = column + 1;
+            self.pan.x = n_type_column_pan(column + 1, max_pan.x, available.x, overlap);
+            self.pan.y = max_pan.y;
+            return false;
+        }
+
+        // Bottom-right reached
+        self.n_scroll_column = 0;
+        true
+    }
+
+    /// N-type scroll up/backward: the reverse of `scroll_n_type_down`
+    /// Returns true if should go to prev image (top-left reached)
+    pub fn scroll_n_type_up(&mut self, available: Vec2, overlap: f32) -> bool {
+        if self.image_size == Vec2::ZERO {
+            return true;
+        }
+
+        let display_size = self.calculate_display_size(available);
+        if display_size.x <= available.x && display_size.y <= available.y {
+            return true;
+        }
+
+        let max_pan = ((display_size - available) * 0.5).max(Vec2::ZERO);
+        let last_column = n_type_last_column(display_size.x, available.x, overlap);
+        let column = self.n_scroll_column.min(last_column);
+
+        if self.pan.y < max_pan.y - 1.0 {
+            // Continue up the current column
+            self.pan.y = (self.pan.y + (available.y - overlap).max(1.0)).min(max_pan.y);
+            self.pan.x = n_type_column_pan(column, max_pan.x, available.x, overlap);
+            self.n_scroll_column = column;
+            return false;
+        }
+
+        if column > 0 {
+            // Top of a column: jump to the bottom of the previous one
+            self.n_scroll_column = column - 1;
+            self.pan.x = n_type_column_pan(column - 1, max_pan.x, available.x, overlap);
+            self.pan.y = -max_pan.y;
+            return false;
+        }
+
+        // Top-left reached
+        self.n_scroll_column = 0;
+        true
+    }
+
     /// Get estimated available size for smart scroll calculations
     /// This returns a reasonable default; actual size comes from UI rendering
     pub fn get_estimated_available(&self) -> Vec2 {
@@ -572,3 +759,278 @@ impl Default for ImageViewer {
         Self::new()
     }
 }
+
+/// Allowed range of the zoom percentage field
+pub const ZOOM_PERCENT_MIN: f32 = 10.0;
+pub const ZOOM_PERCENT_MAX: f32 = 1000.0;
+
+/// Parse a typed zoom percentage ("250", "250%", " 75.5 ") clamped to the allowed range
+pub fn parse_zoom_percent(text: &str) -> Option<f32> {
+    let value: f32 = text.trim().trim_end_matches('%').trim().parse().ok()?;
+    value
+        .is_finite()
+        .then(|| value.clamp(ZOOM_PERCENT_MIN, ZOOM_PERCENT_MAX))
+}
+
+/// Viewport size change (px) below which the fit is not recomputed
+pub const REFIT_TOLERANCE: f32 = 2.0;
+
+/// Zoom to keep after the fit scale changed from `old_base` to `new_base` (e.g. window resize)
+/// At fit (zoom 1) the image follows the new fit; a manual zoom keeps its on-screen scale
+pub fn refit_zoom(zoom: f32, old_base: f32, new_base: f32) -> f32 {
+    if (zoom - 1.0).abs() < 1e-3 || old_base <= 0.0 || new_base <= 0.0 {
+        return zoom;
+    }
+    (zoom * old_base / new_base).clamp(0.1, 10.0)
+}
+
+/// Pan after changing the on-screen scale from `old_scale` to `new_scale` so
+/// the image point at `anchor` (offset from the view center) stays put
+pub fn zoom_pan_at(pan: Vec2, anchor: Vec2, old_scale: f32, new_scale: f32) -> Vec2 {
+    if old_scale <= 0.0 {
+        return pan;
+    }
+    anchor - (anchor - pan) * (new_scale / old_scale)
+}
+
+/// Texture coordinates at the top-left, top-right, bottom-right and
+/// bottom-left corners of the displayed image (flips, then the clockwise rotation)
+pub fn display_uvs(rotation: i32, flip_h: bool, flip_v: bool) -> [Pos2; 4] {
+    let (left, right) = if flip_h { (1.0, 0.0) } else { (0.0, 1.0) };
+    let (top, bottom) = if flip_v { (1.0, 0.0) } else { (0.0, 1.0) };
+    let corners = [
+        Pos2::new(left, top),
+        Pos2::new(right, top),
+        Pos2::new(right, bottom),
+        Pos2::new(left, bottom),
+    ];
+    // Turning clockwise brings each corner to the position of the next one
+    let turns = (rotation.rem_euclid(360) / 90) as usize;
+    std::array::from_fn(|i| corners[(i + 4 - turns) % 4])
+}
+
+/// Texture coordinates at `offset` (0-1 across the displayed image) for the
+/// corner coordinates `uvs`; offsets outside 0-1 extrapolate
+pub fn display_to_uv(uvs: [Pos2; 4], offset: Vec2) -> Pos2 {
+    uvs[0] + (uvs[1] - uvs[0]) * offset.x + (uvs[3] - uvs[0]) * offset.y
+}
+
+/// Draw a texture into `rect` with per-corner texture coordinates from `display_uvs`
+pub fn paint_image(painter: &egui::Painter, texture: TextureId, rect: Rect, uvs: [Pos2; 4]) {
+    paint_image_tinted(painter, texture, rect, uvs, Color32::WHITE);
+}
+
+/// Like paint_image, multiplied by `tint` (e.g. translucent white to fade it)
+pub fn paint_image_tinted(painter: &egui::Painter, texture: TextureId, rect: Rect, uvs: [Pos2; 4], tint: Color32) {
+    let mut mesh = egui::epaint::Mesh::with_texture(texture);
+    let corners = [rect.left_top(), rect.right_top(), rect.right_bottom(), rect.left_bottom()];
+    for (pos, uv) in corners.into_iter().zip(uvs) {
+        mesh.vertices.push(egui::epaint::Vertex { pos, uv, color: tint });
+    }
+    mesh.add_triangle(0, 1, 2);
+    mesh.add_triangle(0, 2, 3);
+    painter.add(mesh);
+}
+
+/// Pixel of an `image_size` image under `pos`, for an image drawn in
+/// `image_rect` with the texture coordinates `uvs`
+pub fn screen_to_image(pos: Pos2, image_rect: Rect, image_size: (u32, u32), uvs: [Pos2; 4]) -> Option<(u32, u32)> {
+    if !image_rect.contains(pos) || image_rect.width() <= 0.0 || image_rect.height() <= 0.0 {
+        return None;
+    }
+    let s = (pos.x - image_rect.left()) / image_rect.width();
+    let t = (pos.y - image_rect.top()) / image_rect.height();
+    let uv = display_to_uv(uvs, Vec2::new(s, t));
+    let (width, height) = image_size;
+    let x = ((uv.x * width as f32) as u32).min(width.checked_sub(1)?);
+    let y = ((uv.y * height as f32) as u32).min(height.checked_sub(1)?);
+    Some((x, y))
+}
+
+/// Index of the rightmost N-type scroll column for an image `display_width` wide
+fn n_type_last_column(display_width: f32, available_width: f32, overlap: f32) -> usize {
+    if display_width <= available_width {
+        return 0;
+    }
+    let step = (available_width - overlap).max(1.0);
+    ((display_width - available_width) / step).ceil() as usize
+}
+
+/// Horizontal pan showing N-type scroll `column` (0 = left edge), limited to the right edge
+fn n_type_column_pan(column: usize, max_pan_x: f32, available_width: f32, overlap: f32) -> f32 {
+    let step = (available_width - overlap).max(1.0);
+    (max_pan_x - column as f32 * step).max(-max_pan_x)
+}
+
+/// Distance (px) within which an image edge snaps to the viewport edge
+pub const SNAP_THRESHOLD: f32 = 30.0;
+
+/// Snap a pan offset so an image edge within `threshold` of the matching viewport edge
+/// lies flush with it (left/top take precedence over right/bottom)
+pub fn snap_pan(pan: Vec2, display_size: Vec2, available: Vec2, threshold: f32) -> Vec2 {
+    let snap_axis = |pan: f32, display: f32, available: f32| {
+        // pan at which the near (left/top) or far (right/bottom) edges line up
+        let near = (display - available) * 0.5;
+        let far = -near;
+        if (pan - near).abs() < threshold {
+            near
+        } else if (pan - far).abs() < threshold {
+            far
+        } else {
+            pan
+        }
+    };
+    Vec2::new(
+        snap_axis(pan.x, display_size.x, available.x),
+        snap_axis(pan.y, display_size.y, available.y),
+    )
+}
+
+/// Limit a pan offset so the image (already rotated and scaled to `display_size`)
+/// can't leave the viewport: on an axis where it is larger than `available` its edges
+/// can't move inside the viewport, on an axis where it fits it stays centered
+pub fn clamp_pan(pan: Vec2, display_size: Vec2, available: Vec2) -> Vec2 {
+    let max = ((display_size - available) * 0.5).max(Vec2::ZERO);
+    Vec2::new(pan.x.clamp(-max.x, max.x), pan.y.clamp(-max.y, max.y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp_pan() {
+        let available = Vec2::new(800.0, 600.0);
+
+        // Smaller than the viewport: centered
+        assert_eq!(clamp_pan(Vec2::new(50.0, -50.0), Vec2::new(400.0, 300.0), available), Vec2::ZERO);
+
+        // Wider only: free within the overflow horizontally, centered vertically
+        let pan = clamp_pan(Vec2::new(-500.0, 40.0), Vec2::new(1200.0, 300.0), available);
+        assert_eq!(pan, Vec2::new(-200.0, 0.0));
+
+        // Within bounds: unchanged
+        let pan = Vec2::new(100.0, -100.0);
+        assert_eq!(clamp_pan(pan, Vec2::new(1200.0, 1000.0), available), pan);
+    }
+
+    #[test]
+    fn test_snap_pan() {
+        let available = Vec2::new(800.0, 600.0);
+        let display = Vec2::new(1200.0, 1000.0);
+
+        // Near the left and bottom edges: snapped flush
+        assert_eq!(snap_pan(Vec2::new(190.0, -185.0), display, available, 30.0), Vec2::new(200.0, -200.0));
+        // Far from any edge: unchanged
+        let pan = Vec2::new(50.0, 0.0);
+        assert_eq!(snap_pan(pan, display, available, 30.0), pan);
+    }
+
+    #[test]
+    fn test_replace_texture_keeps_view() {
+        let mut viewer = ImageViewer::new();
+        viewer.set_image(TextureId::Managed(1), 4000, 3000);
+        viewer.zoom = 2.0;
+        viewer.pan = Vec2::new(10.0, 20.0);
+
+        viewer.replace_texture(TextureId::Managed(2), 4000, 3000);
+        assert_eq!(viewer.texture, Some(TextureId::Managed(2)));
+        assert_eq!(viewer.zoom, 2.0);
+        assert_eq!(viewer.pan, Vec2::new(10.0, 20.0));
+    }
+
+    #[test]
+    fn test_toggle_actual_pixels() {
+        let mut viewer = ImageViewer::new();
+        viewer.set_image(TextureId::Managed(1), 4000, 3000);
+        viewer.fit_mode = FitMode::FitWidth;
+
+        // At 25%, the point 100px right of center is 400px into the image from
+        // the center; at 100% it must still be under the cursor
+        viewer.toggle_actual_pixels(Vec2::new(100.0, 0.0), 0.25);
+        assert_eq!(viewer.fit_mode, FitMode::OriginalSize);
+        assert_eq!(viewer.zoom, 1.0);
+        assert_eq!(viewer.pan, Vec2::new(-300.0, 0.0));
+
+        viewer.toggle_actual_pixels(Vec2::ZERO, 1.0);
+        assert_eq!(viewer.fit_mode, FitMode::FitWidth);
+        assert_eq!(viewer.pan, Vec2::ZERO);
+    }
+
+    #[test]
+    fn test_screen_to_image() {
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(200.0, 100.0));
+        let at = |x: f32, y: f32, uvs| screen_to_image(Pos2::new(x, y), rect, (20, 10), uvs);
+
+        // Unrotated: 10 screen px per image px
+        assert_eq!(at(15.0, 95.0, display_uvs(0, false, false)), Some((1, 9)));
+        assert_eq!(at(15.0, 95.0, display_uvs(0, true, false)), Some((18, 9)));
+        assert_eq!(at(250.0, 50.0, display_uvs(0, false, false)), None);
+
+        // A 10x20 image turned 90° clockwise fills the same 200x100 rect: the
+        // displayed top-left corner is the image's bottom-left
+        let rotated = |x: f32, y: f32, uvs| screen_to_image(Pos2::new(x, y), rect, (10, 20), uvs);
+        assert_eq!(rotated(1.0, 1.0, display_uvs(90, false, false)), Some((0, 19)));
+        assert_eq!(rotated(199.0, 1.0, display_uvs(90, false, false)), Some((0, 0)));
+        assert_eq!(rotated(1.0, 1.0, display_uvs(270, false, false)), Some((9, 0)));
+        assert_eq!(rotated(1.0, 1.0, display_uvs(90, false, true)), Some((0, 0)));
+        assert_eq!(display_uvs(180, false, false)[0], Pos2::new(1.0, 1.0));
+    }
+
+    #[test]
+    fn test_refit_zoom() {
+        // At fit: stays at fit
+        assert_eq!(refit_zoom(1.0, 0.5, 0.25), 1.0);
+        // Zoomed in: on-screen scale preserved (2 * 0.5 == 4 * 0.25)
+        assert!((refit_zoom(2.0, 0.5, 0.25) - 4.0).abs() < 1e-6);
+        // Degenerate scales leave the zoom alone
+        assert_eq!(refit_zoom(2.0, 0.0, 0.25), 2.0);
+    }
+
+    #[test]
+    fn test_scroll_n_type() {
+        // 1000x3000 image shown at original size in a 600x1000 viewport: two columns
+        let available = Vec2::new(600.0, 1000.0);
+        let mut viewer = ImageViewer::new();
+        viewer.image_size = Vec2::new(1000.0, 3000.0);
+        viewer.fit_mode = FitMode::OriginalSize;
+        viewer.pan = Vec2::new(0.0, 1000.0);
+
+        // Down the left column
+        assert!(!viewer.scroll_n_type_down(available, 0.0));
+        assert_eq!(viewer.pan, Vec2::new(200.0, 0.0));
+        assert!(!viewer.scroll_n_type_down(available, 0.0));
+        assert_eq!(viewer.pan, Vec2::new(200.0, -1000.0));
+
+        // Jump to the top of the right column, then down it
+        assert!(!viewer.scroll_n_type_down(available, 0.0));
+        assert_eq!(viewer.pan, Vec2::new(-200.0, 1000.0));
+        assert!(!viewer.scroll_n_type_down(available, 0.0));
+        assert!(!viewer.scroll_n_type_down(available, 0.0));
+        assert_eq!(viewer.pan, Vec2::new(-200.0, -1000.0));
+
+        // Bottom-right: next image
+        assert!(viewer.scroll_n_type_down(available, 0.0));
+
+        // Backward from the top of the right column goes to the bottom of the left one
+        viewer.n_scroll_column = 1;
+        viewer.pan = Vec2::new(-200.0, 1000.0);
+        assert!(!viewer.scroll_n_type_up(available, 0.0));
+        assert_eq!(viewer.pan, Vec2::new(200.0, -1000.0));
+    }
+
+    #[test]
+    fn test_zoom_percent() {
+        assert_eq!(parse_zoom_percent("250"), Some(250.0));
+        assert_eq!(parse_zoom_percent(" 75.5% "), Some(75.5));
+        assert_eq!(parse_zoom_percent("5"), Some(ZOOM_PERCENT_MIN));
+        assert_eq!(parse_zoom_percent("5000"), Some(ZOOM_PERCENT_MAX));
+        assert_eq!(parse_zoom_percent("abc"), None);
+        assert_eq!(parse_zoom_percent("NaN"), None);
+
+        // Relative to the fit scale: 200% of original at base scale 0.5 is zoom 4
+        let mut viewer = ImageViewer::new();
+        viewer.set_zoom_percent(200.0, 0.5);
+        assert!((viewer.zoom - 4.0).abs() < 1e-6);
+    }
+}
@@ -2,6 +2,7 @@
 
 use app_core::{Command, CommandId};
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use winit::event::{ElementState, KeyEvent, MouseButton};
 use winit::keyboard::{Key, ModifiersState, NamedKey};
 
@@ -17,19 +18,23 @@ pub struct InputHandler {
 impl InputHandler {
     /// Create a new input handler with bindings
     pub fn new(bindings: HashMap<String, Vec<String>>) -> Self {
-        // Invert the bindings map: command -> keys becomes key -> command
-        let mut key_to_command = HashMap::new();
+        let mut handler = Self {
+            bindings: HashMap::new(),
+            modifiers: ModifiersState::empty(),
+        };
+        handler.set_bindings(bindings);
+        handler
+    }
 
+    /// Replace all key bindings (keeps the current modifier state)
+    pub fn set_bindings(&mut self, bindings: HashMap<String, Vec<String>>) {
+        // Invert the bindings map: command -> keys becomes key -> command
+        self.bindings.clear();
         for (command, keys) in bindings {
             for key in keys {
-                key_to_command.insert(key.to_lowercase(), command.clone());
+                self.bindings.insert(normalize_binding(&key), command.clone());
             }
         }
-
-        Self {
-            bindings: key_to_command,
-            modifiers: ModifiersState::empty(),
-        }
     }
 
     /// Update modifier state
@@ -37,6 +42,11 @@ impl InputHandler {
         self.modifiers = modifiers;
     }
 
+    /// Current modifier state
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
     /// Handle a key event and return the corresponding command
     pub fn handle_key(&self, event: &KeyEvent) -> Option<Command> {
         if event.state != ElementState::Pressed {
@@ -53,6 +63,28 @@ impl InputHandler {
             .map(|cmd_id| Command::new(cmd_id))
     }
 
+    /// Binding string for a key press ("Ctrl+Shift+N"), for recording keybindings
+    /// None for releases and bare modifier presses
+    pub fn key_string(&self, event: &KeyEvent) -> Option<String> {
+        if event.state != ElementState::Pressed {
+            return None;
+        }
+        if matches!(
+            event.logical_key,
+            Key::Named(NamedKey::Control | NamedKey::Shift | NamedKey::Alt | NamedKey::Super | NamedKey::Meta)
+        ) {
+            return None;
+        }
+
+        let key_str = self.key_to_string(&event.logical_key);
+        if key_str.is_empty() {
+            return None;
+        }
+        // Letters are recorded lowercase, as in the default bindings
+        let key_str = if key_str.chars().count() == 1 { key_str.to_lowercase() } else { key_str };
+        Some(self.build_key_string(&key_str))
+    }
+
     /// Build a key string with modifiers
     fn build_key_string(&self, key: &str) -> String {
         let mut parts = Vec::new();
@@ -122,3 +154,188 @@ impl InputHandler {
         }
     }
 }
+
+/// Canonical form of a binding string, as looked up by `InputHandler`
+/// Case-insensitive, with modifiers in Ctrl+Alt+Shift+Super order
+/// ("Shift+Control+N" -> "ctrl+shift+n")
+pub fn normalize_binding(binding: &str) -> String {
+    let binding = binding.trim().to_lowercase();
+
+    // The key itself may be "+" ("Ctrl++")
+    let (modifiers, key) = if binding == "+" {
+        ("", "+")
+    } else if let Some(rest) = binding.strip_suffix("++") {
+        (rest, "+")
+    } else {
+        binding.rsplit_once('+').unwrap_or(("", binding.as_str()))
+    };
+
+    let mut flags = [false; 4];
+    let mut unknown = Vec::new();
+    for modifier in modifiers.split('+').map(str::trim).filter(|m| !m.is_empty()) {
+        match modifier {
+            "ctrl" | "control" => flags[0] = true,
+            "alt" | "option" => flags[1] = true,
+            "shift" => flags[2] = true,
+            "super" | "win" | "cmd" | "command" | "meta" => flags[3] = true,
+            other => unknown.push(other),
+        }
+    }
+
+    let mut parts: Vec<&str> = ["ctrl", "alt", "shift", "super"]
+        .into_iter()
+        .zip(flags)
+        .filter_map(|(name, set)| set.then_some(name))
+        .collect();
+    parts.extend(unknown);
+    parts.push(key.trim());
+    parts.join("+")
+}
+
+/// Keys bound to more than one command
+/// Returns command -> [(key, other command)] for every command involved
+pub fn find_conflicts(bindings: &HashMap<String, Vec<String>>) -> HashMap<String, Vec<(String, String)>> {
+    let mut by_key: HashMap<String, Vec<&str>> = HashMap::new();
+    for (command, keys) in bindings {
+        for key in keys {
+            let commands = by_key.entry(normalize_binding(key)).or_default();
+            if !commands.contains(&command.as_str()) {
+                commands.push(command);
+            }
+        }
+    }
+
+    let mut conflicts: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    for (key, commands) in by_key.iter().filter(|(_, c)| c.len() > 1) {
+        for command in commands {
+            for other in commands.iter().filter(|o| *o != command) {
+                conflicts.entry(command.to_string()).or_default().push((key.clone(), other.to_string()));
+            }
+        }
+    }
+    for list in conflicts.values_mut() {
+        list.sort();
+    }
+
+    conflicts
+}
+
+/// Type-ahead buffer for jumping to a file by typing the start of its name
+pub struct TypeAhead {
+    buffer: String,
+    last_input: Option<Instant>,
+}
+
+impl Default for TypeAhead {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypeAhead {
+    /// Inactivity after which the buffer starts over
+    pub const TIMEOUT: Duration = Duration::from_secs(1);
+
+    pub fn new() -> Self {
+        Self {
+            buffer: String::new(),
+            last_input: None,
+        }
+    }
+
+    /// True while typed characters still extend the buffer
+    pub fn is_active(&self, now: Instant) -> bool {
+        self.last_input.is_some_and(|last| now.duration_since(last) < Self::TIMEOUT)
+    }
+
+    /// Add typed text and find the entry to jump to
+    /// A single repeated letter cycles through the names starting with it, after `current`
+    pub fn input<'a>(
+        &mut self,
+        text: &str,
+        names: impl IntoIterator<Item = &'a str>,
+        current: Option<usize>,
+        now: Instant,
+    ) -> Option<usize> {
+        if !self.is_active(now) {
+            self.buffer.clear();
+        }
+        self.last_input = Some(now);
+        self.buffer.push_str(&text.to_lowercase());
+
+        let names: Vec<String> = names.into_iter().map(str::to_lowercase).collect();
+        if names.is_empty() {
+            return None;
+        }
+
+        let mut chars = self.buffer.chars();
+        let first = chars.next()?;
+        let (prefix, start) = if chars.all(|c| c == first) {
+            (first.to_string(), current.map_or(0, |i| i + 1))
+        } else {
+            (self.buffer.clone(), current.unwrap_or(0))
+        };
+
+        (0..names.len())
+            .map(|offset| (start + offset) % names.len())
+            .find(|&i| names[i].starts_with(&prefix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_binding() {
+        assert_eq!(normalize_binding("Ctrl+Shift+N"), "ctrl+shift+n");
+        assert_eq!(normalize_binding("shift + control+n"), "ctrl+shift+n");
+        assert_eq!(normalize_binding("Ctrl++"), "ctrl++");
+        assert_eq!(normalize_binding("Cmd+Alt+Left"), "alt+super+left");
+        assert_eq!(normalize_binding("f"), "f");
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let mut bindings = HashMap::new();
+        bindings.insert("view.fit".to_string(), vec!["f".to_string(), "Ctrl+Shift+N".to_string()]);
+        bindings.insert("app.search".to_string(), vec!["F".to_string()]);
+        bindings.insert("file.new_folder".to_string(), vec!["Shift+Ctrl+n".to_string()]);
+        bindings.insert("app.exit".to_string(), vec!["q".to_string()]);
+
+        let conflicts = find_conflicts(&bindings);
+        assert_eq!(conflicts["view.fit"], vec![
+            ("ctrl+shift+n".to_string(), "file.new_folder".to_string()),
+            ("f".to_string(), "app.search".to_string()),
+        ]);
+        assert_eq!(conflicts["app.search"], vec![("f".to_string(), "view.fit".to_string())]);
+        assert!(!conflicts.contains_key("app.exit"));
+    }
+
+    #[test]
+    fn test_default_bindings_have_no_conflicts() {
+        let conflicts = find_conflicts(&app_core::AppConfig::default().keybindings);
+        assert!(conflicts.is_empty(), "{:?}", conflicts);
+    }
+
+    #[test]
+    fn test_type_ahead() {
+        let names = ["Apple.jpg", "banana.png", "Berry.jpg", "blueberry.gif", "cherry.jpg"];
+        let start = Instant::now();
+        let mut type_ahead = TypeAhead::new();
+
+        // Prefix match is case-insensitive
+        assert_eq!(type_ahead.input("b", names, Some(0), start), Some(1));
+        assert_eq!(type_ahead.input("e", names, Some(1), start), Some(2));
+
+        // After the timeout the buffer starts over; repeating a letter cycles
+        let later = start + TypeAhead::TIMEOUT;
+        assert!(!type_ahead.is_active(later));
+        assert_eq!(type_ahead.input("b", names, Some(2), later), Some(3));
+        assert_eq!(type_ahead.input("b", names, Some(3), later), Some(1));
+
+        // No match leaves the selection alone
+        let mut type_ahead = TypeAhead::new();
+        assert_eq!(type_ahead.input("z", names, Some(0), start), None);
+    }
+}
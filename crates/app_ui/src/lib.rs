@@ -11,5 +11,5 @@ pub mod input;
 pub mod theme;
 
 pub use renderer::Renderer;
-pub use input::InputHandler;
+pub use input::{InputHandler, TypeAhead, normalize_binding, find_conflicts};
 pub use theme::Theme;
@@ -3,9 +3,23 @@
 //! This crate defines the shared data structures and protocol for inter-process
 //! communication between the 64-bit main process and 32-bit Susie plugin bridge.
 
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::{self, Read, Write};
 use uuid::Uuid;
 
+/// IPC protocol version, exchanged in `Hello`/`HelloAck`.
+///
+/// Messages are bincode-encoded, which is positional: adding, removing or
+/// reordering variants or fields changes the wire format. Bump this on any
+/// such change. Main and bridge are only compatible with the exact same
+/// version (see `is_compatible`). Version 1 was the original, unversioned protocol.
+pub const PROTO_VERSION: u32 = 3;
+
+/// Whether a peer speaking `version` can talk to this build
+pub fn is_compatible(version: u32) -> bool {
+    version == PROTO_VERSION
+}
+
 /// Pixel format for image data
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(C)]
@@ -30,9 +44,46 @@ impl PixelFormat {
     }
 }
 
+/// Convert a bridge image buffer into tightly packed RGBA8
+///
+/// `stride` is the byte length of each source row including alignment padding
+/// (see `calculate_aligned_stride`). Rows missing from a short buffer are left
+/// transparent black.
+pub fn convert_to_rgba8(src: &[u8], format: PixelFormat, width: u32, height: u32, stride: u32) -> Vec<u8> {
+    let (width, height, stride) = (width as usize, height as usize, stride as usize);
+    let bpp = format.bytes_per_pixel() as usize;
+    let row_bytes = width * bpp;
+    let mut out = vec![0u8; width * height * 4];
+    if stride < row_bytes {
+        return out;
+    }
+
+    for (src_row, dst_row) in src.chunks(stride).zip(out.chunks_exact_mut(width * 4)) {
+        let Some(src_row) = src_row.get(..row_bytes) else {
+            break;
+        };
+        for (px, dst) in src_row.chunks_exact(bpp).zip(dst_row.chunks_exact_mut(4)) {
+            let rgba = match format {
+                PixelFormat::Rgba8 => [px[0], px[1], px[2], px[3]],
+                PixelFormat::Bgra8 => [px[2], px[1], px[0], px[3]],
+                PixelFormat::Rgb8 => [px[0], px[1], px[2], 255],
+                PixelFormat::Bgr8 => [px[2], px[1], px[0], 255],
+                PixelFormat::Gray8 => [px[0], px[0], px[0], 255],
+                PixelFormat::GrayAlpha8 => [px[0], px[0], px[0], px[1]],
+            };
+            dst.copy_from_slice(&rgba);
+        }
+    }
+    out
+}
+
 /// Commands sent from Main (64-bit) to Bridge (32-bit)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum BridgeCommand {
+    /// Version handshake, always the first command after connecting.
+    /// Must stay the first variant so every version decodes it the same way.
+    Hello { proto_version: u32 },
+
     /// Load a Susie plugin
     LoadPlugin { path: String },
 
@@ -63,6 +114,10 @@ pub enum BridgeCommand {
         dest_path: Option<String>,
     },
 
+    /// Release shared memory handed out in `ImageReady`/`FileExtracted`
+    /// once the main process has copied it out
+    FreeSharedMemory { shmem_handle: String },
+
     /// Health check
     Ping,
 
@@ -70,9 +125,30 @@ pub enum BridgeCommand {
     Shutdown,
 }
 
+impl BridgeCommand {
+    /// Plugin the command is addressed to (None for LoadPlugin, Ping, Shutdown)
+    pub fn plugin_id_mut(&mut self) -> Option<&mut u32> {
+        match self {
+            BridgeCommand::UnloadPlugin { plugin_id }
+            | BridgeCommand::IsSupported { plugin_id, .. }
+            | BridgeCommand::GetPicture { plugin_id, .. }
+            | BridgeCommand::GetArchiveList { plugin_id, .. }
+            | BridgeCommand::ExtractFile { plugin_id, .. } => Some(plugin_id),
+            BridgeCommand::Hello { .. }
+            | BridgeCommand::LoadPlugin { .. }
+            | BridgeCommand::FreeSharedMemory { .. }
+            | BridgeCommand::Ping
+            | BridgeCommand::Shutdown => None,
+        }
+    }
+}
+
 /// Responses from Bridge (32-bit) to Main (64-bit)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum BridgeResponse {
+    /// Answer to `Hello` (must stay the first variant)
+    HelloAck { proto_version: u32, bridge_version: String },
+
     /// Plugin loaded successfully
     PluginLoaded {
         plugin_id: u32,
@@ -112,11 +188,18 @@ pub enum BridgeResponse {
         size: usize,
     },
 
+    /// Shared memory released
+    SharedMemoryFreed,
+
     /// Pong response to Ping
     Pong,
 
     /// Error occurred
     Error { code: ErrorCode, message: String },
+
+    /// Optional progress of a long `GetPicture` or `ExtractFile` (0.0-1.0);
+    /// any number may precede the final response
+    Progress { fraction: f32 },
 }
 
 /// Archive entry information
@@ -145,6 +228,35 @@ pub enum ErrorCode {
     Unknown,
 }
 
+/// Largest message accepted by `read_message` (guards against a corrupt length)
+pub const MAX_MESSAGE_SIZE: u32 = 64 * 1024 * 1024;
+
+/// Write one message: a little-endian u32 length, then the bincode body
+pub fn write_message<W: Write, T: Serialize>(writer: &mut W, message: &T) -> io::Result<()> {
+    let body = bincode::serialize(message).map_err(io::Error::other)?;
+    let len = u32::try_from(body.len())
+        .ok()
+        .filter(|&len| len <= MAX_MESSAGE_SIZE)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "IPC message too large"))?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(&body)?;
+    writer.flush()
+}
+
+/// Read one message written by `write_message`
+/// (`UnexpectedEof` once the peer has closed its end)
+pub fn read_message<R: Read, T: DeserializeOwned>(reader: &mut R) -> io::Result<T> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len);
+    if len > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "IPC message too large"));
+    }
+    let mut body = vec![0; len as usize];
+    reader.read_exact(&mut body)?;
+    bincode::deserialize(&body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
 /// Named pipe name for IPC
 pub fn pipe_name() -> String {
     format!("\\\\.\\pipe\\LightningFiler_{}", std::process::id())
@@ -155,6 +267,12 @@ pub fn shmem_name() -> String {
     format!("Local\\LF_IMG_{}", Uuid::new_v4())
 }
 
+/// Size of a shared memory block holding `size` bytes (same 256-byte
+/// alignment as image rows, so one allocator serves both)
+pub fn aligned_size(size: usize) -> usize {
+    (size + 255) & !255
+}
+
 /// Calculate aligned stride for wgpu (256-byte alignment)
 pub fn calculate_aligned_stride(width: u32, bytes_per_pixel: u32) -> u32 {
     let original_stride = width * bytes_per_pixel;
@@ -175,6 +293,86 @@ mod tests {
 
         // 100 * 4 = 400, needs padding to 512
         assert_eq!(calculate_aligned_stride(100, 4), 512);
+
+        assert_eq!(aligned_size(0), 0);
+        assert_eq!(aligned_size(1), 256);
+        assert_eq!(aligned_size(512), 512);
+    }
+
+    #[test]
+    fn test_convert_to_rgba8() {
+        // 2x2 images; stride pads each row to 8 bytes (the padding must be skipped)
+        let bgra = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+        assert_eq!(
+            convert_to_rgba8(&bgra, PixelFormat::Bgra8, 2, 2, 8),
+            vec![3, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16]
+        );
+        assert_eq!(convert_to_rgba8(&bgra, PixelFormat::Rgba8, 2, 2, 8), bgra.to_vec());
+
+        let rgb = [1, 2, 3, 4, 5, 6, 0xEE, 0xEE, 7, 8, 9, 10, 11, 12, 0xEE, 0xEE];
+        assert_eq!(
+            convert_to_rgba8(&rgb, PixelFormat::Rgb8, 2, 2, 8),
+            vec![1, 2, 3, 255, 4, 5, 6, 255, 7, 8, 9, 255, 10, 11, 12, 255]
+        );
+        assert_eq!(
+            convert_to_rgba8(&rgb, PixelFormat::Bgr8, 2, 2, 8),
+            vec![3, 2, 1, 255, 6, 5, 4, 255, 9, 8, 7, 255, 12, 11, 10, 255]
+        );
+
+        let gray = [10, 20, 0xEE, 0xEE, 0xEE, 0xEE, 0xEE, 0xEE, 30, 40];
+        assert_eq!(
+            convert_to_rgba8(&gray, PixelFormat::Gray8, 2, 2, 8),
+            vec![10, 10, 10, 255, 20, 20, 20, 255, 30, 30, 30, 255, 40, 40, 40, 255]
+        );
+
+        let gray_alpha = [10, 100, 20, 200, 0xEE, 0xEE, 0xEE, 0xEE, 30, 50, 40, 60];
+        assert_eq!(
+            convert_to_rgba8(&gray_alpha, PixelFormat::GrayAlpha8, 2, 2, 8),
+            vec![10, 10, 10, 100, 20, 20, 20, 200, 30, 30, 30, 50, 40, 40, 40, 60]
+        );
+
+        // wgpu-aligned stride: 3 RGBA pixels (12 bytes) padded to 256
+        let stride = calculate_aligned_stride(3, 4);
+        let mut aligned = vec![0xEE; stride as usize + 12];
+        aligned[..12].copy_from_slice(&[1; 12]);
+        aligned[stride as usize..].copy_from_slice(&[2; 12]);
+        let out = convert_to_rgba8(&aligned, PixelFormat::Rgba8, 3, 2, stride);
+        assert_eq!(out, [[1; 12], [2; 12]].concat());
+
+        // Short buffer: the missing row stays transparent
+        assert_eq!(convert_to_rgba8(&bgra[..8], PixelFormat::Rgba8, 2, 2, 8)[8..], [0; 8]);
+    }
+
+    #[test]
+    fn test_hello_is_first_variant() {
+        // Hello/HelloAck keep variant index 0 so any bridge version can decode them
+        let hello = bincode::serialize(&BridgeCommand::Hello { proto_version: PROTO_VERSION }).unwrap();
+        assert_eq!(&hello[..4], &[0, 0, 0, 0]);
+        let ack = bincode::serialize(&BridgeResponse::HelloAck {
+            proto_version: PROTO_VERSION,
+            bridge_version: String::new(),
+        })
+        .unwrap();
+        assert_eq!(&ack[..4], &[0, 0, 0, 0]);
+        assert!(is_compatible(PROTO_VERSION));
+        assert!(!is_compatible(PROTO_VERSION - 1));
+    }
+
+    #[test]
+    fn test_message_framing() {
+        let mut stream = Vec::new();
+        write_message(&mut stream, &BridgeCommand::Ping).unwrap();
+        write_message(&mut stream, &BridgeCommand::LoadPlugin { path: "ifpi.spi".into() }).unwrap();
+
+        let mut reader = stream.as_slice();
+        assert!(matches!(read_message(&mut reader).unwrap(), BridgeCommand::Ping));
+        assert!(matches!(read_message(&mut reader).unwrap(), BridgeCommand::LoadPlugin { path } if path == "ifpi.spi"));
+        let eof = read_message::<_, BridgeCommand>(&mut reader).unwrap_err();
+        assert_eq!(eof.kind(), io::ErrorKind::UnexpectedEof);
+
+        let corrupt = (MAX_MESSAGE_SIZE + 1).to_le_bytes();
+        let err = read_message::<_, BridgeCommand>(&mut corrupt.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
     }
 
     #[test]
@@ -1,50 +1,78 @@
 //! IPC Bridge for communication with main process
+//!
+//! The main process launches the bridge with piped stdin/stdout and exchanges
+//! `ipc_proto` messages over them (see `ipc_proto::write_message`). Logging
+//! goes to stderr so it can't corrupt the stream.
 
+use crate::shmem::SharedBlock;
+use crate::susie::PluginManager;
 use anyhow::Result;
-use ipc_proto::{BridgeCommand, BridgeResponse, ErrorCode};
-
-#[cfg(windows)]
-use std::os::windows::io::FromRawHandle;
+use ipc_proto::{read_message, write_message, BridgeCommand, BridgeResponse, ErrorCode};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Bridge state kept across commands
+#[derive(Default)]
+struct Bridge {
+    plugins: PluginManager,
+    /// Blocks handed out in FileExtracted, by name, until FreeSharedMemory
+    shared: HashMap<String, SharedBlock>,
+}
 
-/// Run the bridge process
+/// Run the bridge process until the main process closes the pipe
 pub fn run() -> Result<()> {
-    tracing::info!("Bridge waiting for connection...");
+    let mut input = std::io::stdin().lock();
+    let mut output = std::io::stdout().lock();
+    let mut bridge = Bridge::default();
+    tracing::info!("Bridge ready");
 
-    // TODO: Implement named pipe server
-    // For now, this is a placeholder that will be implemented in Phase 2
-
-    // Main loop
     loop {
-        // Read command from pipe
-        let command = read_command()?;
-
-        // Process command
-        let response = process_command(command);
-
-        // Send response
-        send_response(&response)?;
-
-        // Check for shutdown
-        if matches!(response, BridgeResponse::Pong) {
-            // Continue
-        }
+        let command = match read_message(&mut input) {
+            Ok(command) => command,
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                tracing::info!("Main process closed the pipe");
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let response = process_command(&mut bridge, command);
+        write_message(&mut output, &response)?;
     }
 }
 
-fn read_command() -> Result<BridgeCommand> {
-    // Placeholder - will be replaced with named pipe reading
-    std::thread::sleep(std::time::Duration::from_secs(1));
-    Ok(BridgeCommand::Ping)
-}
+/// Last progress percentage sent for the running command
+static LAST_PROGRESS: AtomicU32 = AtomicU32::new(0);
 
-fn send_response(response: &BridgeResponse) -> Result<()> {
-    // Placeholder - will be replaced with named pipe writing
-    let _ = response;
-    Ok(())
+/// Send a Progress message ahead of the final response (whole percents only)
+fn send_progress(fraction: f32) {
+    let percent = (fraction.clamp(0.0, 1.0) * 100.0) as u32;
+    if LAST_PROGRESS.swap(percent, Ordering::Relaxed) == percent {
+        return;
+    }
+    // stdout's lock is reentrant, so this is safe while run() holds it
+    if let Err(e) = write_message(&mut std::io::stdout(), &BridgeResponse::Progress { fraction }) {
+        tracing::warn!("Failed to send progress: {}", e);
+    }
 }
 
-fn process_command(command: BridgeCommand) -> BridgeResponse {
+fn process_command(bridge: &mut Bridge, command: BridgeCommand) -> BridgeResponse {
+    let plugins = &mut bridge.plugins;
     match command {
+        BridgeCommand::Hello { proto_version } => {
+            if !ipc_proto::is_compatible(proto_version) {
+                tracing::error!(
+                    "Main process speaks protocol v{}, bridge v{}",
+                    proto_version,
+                    ipc_proto::PROTO_VERSION
+                );
+            }
+            BridgeResponse::HelloAck {
+                proto_version: ipc_proto::PROTO_VERSION,
+                bridge_version: env!("CARGO_PKG_VERSION").to_string(),
+            }
+        }
+
         BridgeCommand::Ping => {
             tracing::debug!("Received Ping");
             BridgeResponse::Pong
@@ -57,13 +85,33 @@ fn process_command(command: BridgeCommand) -> BridgeResponse {
 
         BridgeCommand::LoadPlugin { path } => {
             tracing::info!("Loading plugin: {}", path);
-            // TODO: Implement plugin loading
-            BridgeResponse::Error {
-                code: ErrorCode::PluginNotFound,
-                message: "Plugin loading not yet implemented".to_string(),
+            match plugins.load_plugin(std::path::Path::new(&path)) {
+                Ok(plugin_id) => {
+                    let plugin = plugins.get_plugin(plugin_id).expect("plugin was just loaded");
+                    BridgeResponse::PluginLoaded {
+                        plugin_id,
+                        name: plugin.get_name(),
+                        version: String::new(),
+                        supported_extensions: plugin.get_extensions(),
+                    }
+                }
+                Err(e) => BridgeResponse::Error {
+                    code: ErrorCode::PluginLoadFailed,
+                    message: format!("{}: {}", path, e),
+                },
             }
         }
 
+        BridgeCommand::UnloadPlugin { plugin_id } => {
+            plugins.unload_plugin(plugin_id);
+            BridgeResponse::PluginUnloaded { plugin_id }
+        }
+
+        BridgeCommand::IsSupported { plugin_id, header } => match plugins.get_plugin(plugin_id) {
+            Some(plugin) => BridgeResponse::Supported { supported: plugin.is_supported("", &header) },
+            None => plugin_not_found(plugin_id),
+        },
+
         BridgeCommand::GetPicture { plugin_id, file_path, .. } => {
             tracing::info!("GetPicture: plugin={}, file={}", plugin_id, file_path);
             // TODO: Implement image decoding
@@ -73,17 +121,57 @@ fn process_command(command: BridgeCommand) -> BridgeResponse {
             }
         }
 
+        BridgeCommand::FreeSharedMemory { shmem_handle } => {
+            tracing::debug!("FreeSharedMemory: {}", shmem_handle);
+            if bridge.shared.remove(&shmem_handle).is_none() {
+                tracing::warn!("Unknown shared memory block: {}", shmem_handle);
+            }
+            BridgeResponse::SharedMemoryFreed
+        }
+
         BridgeCommand::GetArchiveList { plugin_id, archive_path } => {
             tracing::info!("GetArchiveList: plugin={}, archive={}", plugin_id, archive_path);
-            // TODO: Implement archive listing
-            BridgeResponse::ArchiveList { entries: vec![] }
+            let Some(plugin) = plugins.get_plugin(plugin_id) else {
+                return plugin_not_found(plugin_id);
+            };
+            match plugin.list_archive(&archive_path) {
+                Ok(files) => BridgeResponse::ArchiveList { entries: files.into_iter().map(|f| f.entry).collect() },
+                Err(e) => BridgeResponse::Error { code: ErrorCode::ArchiveCorrupted, message: e.to_string() },
+            }
         }
 
-        _ => {
-            BridgeResponse::Error {
-                code: ErrorCode::Unknown,
-                message: "Command not implemented".to_string(),
+        BridgeCommand::ExtractFile { plugin_id, archive_path, inner_path, dest_path } => {
+            tracing::info!("ExtractFile: plugin={}, archive={}, file={}", plugin_id, archive_path, inner_path);
+            let Some(plugin) = plugins.get_plugin(plugin_id) else {
+                return plugin_not_found(plugin_id);
+            };
+            LAST_PROGRESS.store(0, Ordering::Relaxed);
+            let data = match plugin.extract(&archive_path, &inner_path, send_progress) {
+                Ok(data) => data,
+                Err(e) => return BridgeResponse::Error { code: ErrorCode::ArchiveCorrupted, message: e.to_string() },
+            };
+            let size = data.len();
+            let written = match dest_path {
+                Some(dest) => std::fs::write(&dest, &data).map(|()| (Some(dest), None)).map_err(anyhow::Error::from),
+                None => {
+                    let name = ipc_proto::shmem_name();
+                    SharedBlock::create(&name, &data).map(|block| {
+                        bridge.shared.insert(name.clone(), block);
+                        (None, Some(name))
+                    })
+                }
+            };
+            match written {
+                Ok((path, shmem_handle)) => BridgeResponse::FileExtracted { path, shmem_handle, size },
+                Err(e) => BridgeResponse::Error { code: ErrorCode::MemoryAllocationFailed, message: e.to_string() },
             }
         }
     }
 }
+
+fn plugin_not_found(plugin_id: u32) -> BridgeResponse {
+    BridgeResponse::Error {
+        code: ErrorCode::PluginNotFound,
+        message: format!("No plugin with id {}", plugin_id),
+    }
+}
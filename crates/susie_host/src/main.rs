@@ -1,11 +1,13 @@
 //! Susie Plugin Bridge (32-bit)
 //!
 //! This binary runs as a separate 32-bit process to host legacy Susie plugins.
-//! It communicates with the main 64-bit process via named pipes and shared memory.
+//! It communicates with the main 64-bit process over its stdin/stdout pipes;
+//! bulk data is handed over in shared memory.
 //!
 //! Build: cargo build --target i686-pc-windows-msvc -p susie_host
 
 mod bridge;
+mod shmem;
 mod susie;
 
 use anyhow::Result;
@@ -15,7 +17,7 @@ fn main() -> Result<()> {
     // Initialize logging
     tracing_subscriber::registry()
         .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
-        .with(fmt::layer())
+        .with(fmt::layer().with_writer(std::io::stderr))
         .init();
 
     tracing::info!("Susie Bridge starting (32-bit)");
@@ -0,0 +1,50 @@
+//! Named shared memory blocks handed to the main process
+
+/// Named block holding a copy of some bytes; the mapping lives until dropped
+/// (the main process copies it out, then sends FreeSharedMemory)
+#[cfg(windows)]
+pub struct SharedBlock(windows::Win32::Foundation::HANDLE);
+
+#[cfg(windows)]
+impl SharedBlock {
+    pub fn create(name: &str, data: &[u8]) -> anyhow::Result<Self> {
+        use windows::core::HSTRING;
+        use windows::Win32::Foundation::INVALID_HANDLE_VALUE;
+        use windows::Win32::System::Memory::{
+            CreateFileMappingW, MapViewOfFile, UnmapViewOfFile, FILE_MAP_WRITE, PAGE_READWRITE,
+        };
+
+        // A zero-sized mapping can't be created
+        let size = ipc_proto::aligned_size(data.len().max(1));
+        unsafe {
+            let handle = CreateFileMappingW(INVALID_HANDLE_VALUE, None, PAGE_READWRITE, 0, size as u32, &HSTRING::from(name))?;
+            let block = Self(handle);
+            let view = MapViewOfFile(handle, FILE_MAP_WRITE, 0, 0, size);
+            if view.Value.is_null() {
+                return Err(windows::core::Error::from_win32().into());
+            }
+            std::ptr::copy_nonoverlapping(data.as_ptr(), view.Value as *mut u8, data.len());
+            let _ = UnmapViewOfFile(view);
+            Ok(block)
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for SharedBlock {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = windows::Win32::Foundation::CloseHandle(self.0);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub struct SharedBlock;
+
+#[cfg(not(windows))]
+impl SharedBlock {
+    pub fn create(_name: &str, _data: &[u8]) -> anyhow::Result<Self> {
+        anyhow::bail!("Shared memory requires Windows")
+    }
+}
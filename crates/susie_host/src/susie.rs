@@ -5,10 +5,13 @@
 //!
 //! This module is Windows-only. On non-Windows platforms, stub types are provided.
 
+use ipc_proto::ArchiveEntry;
 #[cfg(windows)]
 use libloading::{Library, Symbol};
 use std::ffi::{c_char, c_int, c_void};
 #[cfg(windows)]
+use std::ffi::CString;
+#[cfg(windows)]
 use std::path::Path;
 
 /// Susie plugin handle
@@ -40,11 +43,34 @@ type GetArchiveInfo = unsafe extern "stdcall" fn(*const c_char, i32, u32, *mut *
 type GetFile = unsafe extern "stdcall" fn(*const c_char, i32, *mut c_char, u32, ProgressCallback, i32) -> c_int;
 #[cfg(windows)]
 type ProgressCallback = Option<extern "stdcall" fn(c_int, c_int, i32) -> c_int>;
+#[cfg(windows)]
+use windows::Win32::Foundation::{LocalFree, HLOCAL};
+#[cfg(windows)]
+use windows::Win32::System::Memory::{LocalLock, LocalSize, LocalUnlock};
 
 /// Plugin info types
 pub const INFO_TYPE_NAME: c_int = 0;
 pub const INFO_TYPE_EXT: c_int = 1;
 
+/// Header bytes passed to IsSupported
+#[cfg(windows)]
+pub const HEADER_SIZE: usize = 2048;
+
+/// GetFile flag: write the file into a LocalAlloc'd block instead of a folder
+#[cfg(windows)]
+const GETFILE_TO_MEMORY: u32 = 0x0100;
+
+/// Receives GetFile progress (0.0-1.0) while `SusiePlugin::extract` runs
+pub type ProgressSink = fn(f32);
+
+/// Archive member as listed by a plugin
+pub struct ArchiveFile {
+    pub entry: ArchiveEntry,
+    /// Plugin-defined offset that GetFile takes to find the member
+    #[cfg(windows)]
+    pub position: u32,
+}
+
 /// Error codes
 pub const SPI_SUCCESS: c_int = 0;
 pub const SPI_UNSUPPORTED: c_int = -1;
@@ -121,6 +147,9 @@ impl SusiePlugin {
     /// Check if a file is supported
     pub fn is_supported(&self, path: &str, header: &[u8]) -> bool {
         let c_path = std::ffi::CString::new(path).unwrap();
+        // Plugins may read the full 2 KB header buffer
+        let mut header = header.to_vec();
+        header.resize(header.len().max(HEADER_SIZE), 0);
         unsafe {
             let result = (self.is_supported)(c_path.as_ptr(), header.as_ptr() as *const c_void);
             result != 0
@@ -136,6 +165,147 @@ impl SusiePlugin {
     pub fn is_archive_plugin(&self) -> bool {
         self.get_archive_info.is_some() && self.get_file.is_some()
     }
+
+    /// Members of the archive at `path` (GetArchiveInfo)
+    pub fn list_archive(&self, path: &str) -> anyhow::Result<Vec<ArchiveFile>> {
+        let get_archive_info = self.get_archive_info.ok_or_else(|| anyhow::anyhow!("Not an archive plugin"))?;
+        let c_path = to_ansi(path)?;
+        let mut handle: *mut c_void = std::ptr::null_mut();
+        let result = unsafe { get_archive_info(c_path.as_ptr(), 0, 0, &mut handle) };
+        if result != SPI_SUCCESS || handle.is_null() {
+            anyhow::bail!("GetArchiveInfo failed ({})", result);
+        }
+
+        let mut files = Vec::new();
+        unsafe {
+            let block = HLOCAL(handle);
+            // The table ends with an entry whose method is empty
+            let mut info = LocalLock(block) as *const FileInfo;
+            while !info.is_null() && (*info).method[0] != 0 {
+                let file = std::ptr::read_unaligned(info);
+                let dir = from_ansi(&file.path).replace('\\', "/");
+                let name = from_ansi(&file.filename);
+                let path = format!("{}/{}", dir.trim_end_matches('/'), name);
+                files.push(ArchiveFile {
+                    entry: ArchiveEntry {
+                        path: path.trim_start_matches('/').to_string(),
+                        size: u64::from(file.filesize),
+                        compressed_size: u64::from(file.compsize),
+                        is_directory: false,
+                        timestamp: Some(i64::from(file.timestamp)),
+                    },
+                    position: file.position,
+                });
+                info = info.add(1);
+            }
+            let _ = LocalUnlock(block);
+            let _ = LocalFree(block);
+        }
+        Ok(files)
+    }
+
+    /// Extract `inner_path` from the archive at `path` into memory (GetFile)
+    pub fn extract(&self, path: &str, inner_path: &str, progress: ProgressSink) -> anyhow::Result<Vec<u8>> {
+        let get_file = self.get_file.ok_or_else(|| anyhow::anyhow!("Not an archive plugin"))?;
+        let file = self
+            .list_archive(path)?
+            .into_iter()
+            .find(|f| f.entry.path == inner_path)
+            .ok_or_else(|| anyhow::anyhow!("{} is not in {}", inner_path, path))?;
+
+        let c_path = to_ansi(path)?;
+        let mut handle: *mut c_void = std::ptr::null_mut();
+        PROGRESS_SINK.with(|sink| sink.set(Some(progress)));
+        // With GETFILE_TO_MEMORY the destination is an HLOCAL* rather than a folder
+        let result = unsafe {
+            get_file(
+                c_path.as_ptr(),
+                file.position as i32,
+                &mut handle as *mut *mut c_void as *mut c_char,
+                GETFILE_TO_MEMORY,
+                Some(report_progress),
+                0,
+            )
+        };
+        PROGRESS_SINK.with(|sink| sink.set(None));
+        if result != SPI_SUCCESS || handle.is_null() {
+            anyhow::bail!("GetFile failed ({})", result);
+        }
+
+        unsafe {
+            let block = HLOCAL(handle);
+            let size = (file.entry.size as usize).min(LocalSize(block));
+            let data = LocalLock(block) as *const u8;
+            let bytes = if data.is_null() { Vec::new() } else { std::slice::from_raw_parts(data, size).to_vec() };
+            let _ = LocalUnlock(block);
+            let _ = LocalFree(block);
+            Ok(bytes)
+        }
+    }
+}
+
+/// fileInfo entry of a GetArchiveInfo table (byte-packed)
+#[cfg(windows)]
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+struct FileInfo {
+    method: [u8; 8],
+    position: u32,
+    compsize: u32,
+    filesize: u32,
+    timestamp: i32,
+    path: [u8; 200],
+    filename: [u8; 200],
+    crc: u32,
+}
+
+#[cfg(windows)]
+thread_local! {
+    /// Where report_progress forwards to during `SusiePlugin::extract`
+    static PROGRESS_SINK: std::cell::Cell<Option<ProgressSink>> = const { std::cell::Cell::new(None) };
+}
+
+#[cfg(windows)]
+extern "stdcall" fn report_progress(num: c_int, denom: c_int, _data: i32) -> c_int {
+    if denom > 0 {
+        if let Some(sink) = PROGRESS_SINK.with(|sink| sink.get()) {
+            sink(num as f32 / denom as f32);
+        }
+    }
+    0 // keep going
+}
+
+/// Plugins take paths in the system code page
+#[cfg(windows)]
+fn to_ansi(s: &str) -> anyhow::Result<CString> {
+    use windows::core::PCSTR;
+    use windows::Win32::Globalization::{WideCharToMultiByte, CP_ACP};
+
+    let wide: Vec<u16> = s.encode_utf16().collect();
+    let mut buffer = unsafe {
+        let len = WideCharToMultiByte(CP_ACP, 0, &wide, None, PCSTR::null(), None);
+        vec![0u8; len.max(0) as usize]
+    };
+    unsafe { WideCharToMultiByte(CP_ACP, 0, &wide, Some(&mut buffer), PCSTR::null(), None) };
+    Ok(CString::new(buffer)?)
+}
+
+/// NUL-terminated system code page text from a plugin
+#[cfg(windows)]
+fn from_ansi(bytes: &[u8]) -> String {
+    use windows::Win32::Globalization::{MultiByteToWideChar, CP_ACP, MULTI_BYTE_TO_WIDE_CHAR_FLAGS};
+
+    let bytes = &bytes[..bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len())];
+    if bytes.is_empty() {
+        return String::new();
+    }
+    let flags = MULTI_BYTE_TO_WIDE_CHAR_FLAGS(0);
+    let mut wide = unsafe {
+        let len = MultiByteToWideChar(CP_ACP, flags, bytes, None);
+        vec![0u16; len.max(0) as usize]
+    };
+    unsafe { MultiByteToWideChar(CP_ACP, flags, bytes, Some(&mut wide)) };
+    String::from_utf16_lossy(&wide)
 }
 
 /// Plugin manager for loading and managing multiple plugins
@@ -201,11 +371,47 @@ impl Default for PluginManager {
     }
 }
 
+/// Susie plugins are Windows DLLs; elsewhere nothing loads
+#[cfg(not(windows))]
+impl SusiePlugin {
+    pub fn get_name(&self) -> String {
+        String::new()
+    }
+
+    pub fn get_extensions(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    pub fn is_supported(&self, _path: &str, _header: &[u8]) -> bool {
+        false
+    }
+
+    pub fn list_archive(&self, _path: &str) -> anyhow::Result<Vec<ArchiveFile>> {
+        anyhow::bail!("Susie plugins require Windows")
+    }
+
+    pub fn extract(&self, _path: &str, _inner_path: &str, _progress: ProgressSink) -> anyhow::Result<Vec<u8>> {
+        anyhow::bail!("Susie plugins require Windows")
+    }
+}
+
 #[cfg(not(windows))]
 impl PluginManager {
     pub fn new() -> Self {
         Self { _dummy: () }
     }
+
+    pub fn load_plugin(&mut self, _path: &std::path::Path) -> anyhow::Result<u32> {
+        anyhow::bail!("Susie plugins require Windows")
+    }
+
+    pub fn get_plugin(&self, _id: u32) -> Option<&SusiePlugin> {
+        None
+    }
+
+    pub fn unload_plugin(&mut self, _id: u32) -> bool {
+        false
+    }
 }
 
 #[cfg(not(windows))]